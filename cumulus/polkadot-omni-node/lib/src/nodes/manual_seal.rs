@@ -242,6 +242,7 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 					))
 				}
 			},
+			backend: Some(backend.clone()),
 		};
 		let authorship_future = sc_consensus_manual_seal::run_manual_seal(params);
 		task_manager.spawn_essential_handle().spawn_blocking(
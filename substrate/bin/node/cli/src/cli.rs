@@ -89,9 +89,20 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export a snapshot of the finalized state and the block headers leading to it.
+	///
+	/// The node has to be stopped while the snapshot is exported.
+	ExportSnapshot(sc_cli::ExportSnapshotCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Bootstrap an empty database from a snapshot.
+	ImportSnapshot(sc_cli::ImportSnapshotCmd),
+
+	/// Copy the database into a new database of the other backend, RocksDB or ParityDB.
+	MigrateDb(sc_cli::MigrateDbCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
@@ -547,6 +547,22 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 		transaction: Self::BlockImportOperation,
 	) -> sp_blockchain::Result<()>;
 
+	/// Write the trie nodes of a state that is imported in chunks.
+	///
+	/// The nodes are written to the database right away, outside of any block import operation.
+	/// Once all of them are written, the block the state belongs to is imported with
+	/// `StorageChanges::Imported`.
+	///
+	/// Fails if the database already holds the state of a block other than genesis. The nodes
+	/// are not removed if the import is abandoned, the database has to be purged to reclaim
+	/// them.
+	fn import_state_nodes(
+		&self,
+		_nodes: BackendTransaction<HashingFor<Block>>,
+	) -> sp_blockchain::Result<()> {
+		Err(sp_blockchain::Error::Backend("Importing state in chunks is not supported.".into()))
+	}
+
 	/// Finalize block with given `hash`.
 	///
 	/// This should only be called if the parent of the given block has been finalized.
@@ -90,4 +90,15 @@ pub trait ProofProvider<Block: BlockT> {
 		proof: CompactProof,
 		start_keys: &[Vec<u8>],
 	) -> sp_blockchain::Result<(KeyValueStates, usize)>;
+
+	/// Verify read storage proof for a set of keys like [`Self::verify_range_proof`], and write
+	/// the trie nodes of the proof to the backend with `Backend::import_state_nodes`.
+	///
+	/// Used to import a state in chunks while it is downloaded, without keeping it in memory.
+	fn import_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: CompactProof,
+		start_keys: &[Vec<u8>],
+	) -> sp_blockchain::Result<(KeyValueStates, usize)>;
 }
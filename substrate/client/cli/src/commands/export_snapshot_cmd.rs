@@ -0,0 +1,84 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_client_api::{Backend, BlockBackend, HeaderBackend, StorageProvider};
+use sc_service::chain_ops::export_snapshot;
+use sp_api::CallApiAt;
+use sp_runtime::traits::Block as BlockT;
+use std::{fs, io, path::PathBuf, sync::Arc};
+
+/// The `export-snapshot` command used to export a snapshot of the finalized state and the block
+/// headers leading to it.
+///
+/// The command opens the database itself, so the node has to be stopped. To export a snapshot
+/// from a running node, call its unsafe `snapshot_export` RPC instead.
+#[derive(Debug, Clone, Parser)]
+pub struct ExportSnapshotCmd {
+	/// Output file name or stdout if unspecified.
+	#[arg()]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ExportSnapshotCmd {
+	/// Run the export-snapshot command
+	pub async fn run<B, BA, C>(&self, client: Arc<C>, backend: Arc<BA>) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: HeaderBackend<B> + BlockBackend<B> + StorageProvider<B, BA> + CallApiAt<B>,
+	{
+		let file: Box<dyn io::Write> = match &self.output {
+			Some(filename) => Box::new(io::BufWriter::new(fs::File::create(filename)?)),
+			None => Box::new(io::stdout().lock()),
+		};
+
+		export_snapshot(client, backend, file).map(|_| ()).map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ExportSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -0,0 +1,90 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{ImportParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_client_api::{Backend, HeaderBackend};
+use sc_consensus::BlockImport;
+use sc_service::chain_ops::import_snapshot;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fs,
+	io::{self, Read},
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// The `import-snapshot` command used to bootstrap an empty database from a snapshot written by
+/// `export-snapshot`.
+///
+/// The database must not hold any block but genesis. If the import fails, the database holds a
+/// partial state and is refused by later imports: wipe it with `purge-chain` before retrying.
+#[derive(Debug, Parser)]
+pub struct ImportSnapshotCmd {
+	/// Input file or stdin if unspecified.
+	#[arg()]
+	pub input: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ImportSnapshotCmd {
+	/// Run the import-snapshot command
+	///
+	/// `block_import` should be the block import of the node's consensus engine, so that it can
+	/// reset its data from the imported state.
+	pub async fn run<B, BA, C, BI>(
+		&self,
+		client: Arc<C>,
+		backend: Arc<BA>,
+		block_import: BI,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: HeaderBackend<B>,
+		BI: BlockImport<B, Error = sp_consensus::Error>,
+	{
+		let file: Box<dyn Read> = match &self.input {
+			Some(filename) => Box::new(io::BufReader::new(fs::File::open(filename)?)),
+			None => Box::new(io::stdin()),
+		};
+
+		import_snapshot(client, backend, block_import, file).await.map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ImportSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -0,0 +1,118 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_service::DatabaseSource;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fmt::Debug,
+	path::{Path, PathBuf},
+};
+
+/// The `migrate-db` command used to copy a RocksDB database into a new ParityDB database, or a
+/// ParityDB database into a new RocksDB database.
+///
+/// The node must not be running. The original database is kept, so an interrupted migration can
+/// be resumed by running the command again.
+#[derive(Debug, Clone, Parser)]
+pub struct MigrateDbCmd {
+	/// Path of the new database.
+	///
+	/// Defaults to the location where the node looks for a database of the other backend.
+	#[arg(long, value_name = "PATH")]
+	pub target: Option<PathBuf>,
+
+	/// Allow migrating a ParityDB database to RocksDB without its auxiliary and offchain data.
+	///
+	/// ParityDB doesn't keep the keys of these columns, so they can't be copied. The auxiliary
+	/// data holds consensus state such as GRANDPA authority sets and BABE epochs, which the node
+	/// has to sync again.
+	#[arg(long)]
+	pub discard_aux_data: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl MigrateDbCmd {
+	/// Run the migrate-db command
+	pub fn run<B: BlockT>(&self, database_config: DatabaseSource) -> error::Result<()> {
+		let sibling = |path: &Path, name: &str| {
+			path.parent().and_then(Path::parent).map(|base| base.join(name).join("full"))
+		};
+		let (source, target) = match database_config {
+			DatabaseSource::Auto { rocksdb_path, paritydb_path, cache_size } => (
+				DatabaseSource::RocksDb { path: rocksdb_path, cache_size },
+				DatabaseSource::ParityDb { path: self.target.clone().unwrap_or(paritydb_path) },
+			),
+			DatabaseSource::RocksDb { path, cache_size } => {
+				let target = self.target.clone().or_else(|| sibling(&path, "paritydb"));
+				(
+					DatabaseSource::RocksDb { path, cache_size },
+					DatabaseSource::ParityDb { path: target.ok_or("Missing target path")? },
+				)
+			},
+			DatabaseSource::ParityDb { path } => {
+				let target = self.target.clone().or_else(|| sibling(&path, "db"));
+				let cache_size = self.database_params.database_cache_size().unwrap_or(1024);
+				(
+					DatabaseSource::ParityDb { path },
+					DatabaseSource::RocksDb {
+						path: target.ok_or("Missing target path")?,
+						cache_size,
+					},
+				)
+			},
+			DatabaseSource::Custom { .. } =>
+				return Err("Cannot migrate custom database implementation".into()),
+		};
+
+		sc_client_db::migrate_database::<B>(&source, &target, self.discard_aux_data)
+			.map_err(|e| error::Error::Application(Box::new(e)))?;
+		let database = match target {
+			DatabaseSource::ParityDb { .. } => "paritydb",
+			_ => "rocksdb",
+		};
+		log::info!(
+			"Database migrated to {:?}, start the node with `--database {}` to use it.",
+			target.path().expect("migrated databases are stored on disk; qed"),
+			database,
+		);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for MigrateDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
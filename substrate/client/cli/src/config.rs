@@ -43,6 +43,9 @@ pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
 /// Default sub directory to store network config.
 pub(crate) const DEFAULT_NETWORK_CONFIG_PATH: &str = "network";
 
+/// Default file name of the transaction pool journal, stored next to the client database.
+pub(crate) const DEFAULT_TXPOOL_JOURNAL_PATH: &str = "txpool_journal";
+
 /// The recommended open file descriptor limit to be configured for the process.
 const RECOMMENDED_OPEN_FILE_DESCRIPTOR_LIMIT: u64 = 10_000;
 
@@ -366,6 +369,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(false)
 	}
 
+	/// Index events and extrinsics for the `archive` RPC methods.
+	///
+	/// By default this is `false`.
+	fn rpc_archive_index(&self) -> Result<bool> {
+		Ok(false)
+	}
+
 	/// Get the prometheus configuration (`None` if disabled)
 	///
 	/// By default this is `None`.
@@ -504,6 +514,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let keystore = self.keystore_config(&config_dir)?;
 		let telemetry_endpoints = self.telemetry_endpoints(&chain_spec)?;
 		let runtime_cache_size = self.runtime_cache_size()?;
+		let database = self.database_config(&config_dir, database_cache_size, database)?;
+
+		let mut transaction_pool = self.transaction_pool(is_dev)?;
+		if let (None, Some(path)) = (transaction_pool.journal_path(), database.path()) {
+			transaction_pool = transaction_pool
+				.with_journal_path(path.with_file_name(DEFAULT_TXPOOL_JOURNAL_PATH));
+		}
 
 		let rpc_addrs: Option<Vec<sc_service::config::RpcEndpoint>> = self
 			.rpc_addr(DCV::rpc_listen_port())?
@@ -513,7 +530,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			impl_name: C::impl_name(),
 			impl_version: C::impl_version(),
 			tokio_handle,
-			transaction_pool: self.transaction_pool(is_dev)?,
+			transaction_pool,
 			network: self.network_config(
 				&chain_spec,
 				is_dev,
@@ -525,7 +542,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 				DCV::p2p_listen_port(),
 			)?,
 			keystore,
-			database: self.database_config(&config_dir, database_cache_size, database)?,
+			database,
 			data_path: config_dir,
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			state_pruning: self.state_pruning()?,
@@ -552,6 +569,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 				rate_limit: self.rpc_rate_limit()?,
 				rate_limit_whitelisted_ips: self.rpc_rate_limit_whitelisted_ips()?,
 				rate_limit_trust_proxy_headers: self.rpc_rate_limit_trust_proxy_headers()?,
+				archive_index: self.rpc_archive_index()?,
 			},
 			prometheus_config: self
 				.prometheus_config(DCV::prometheus_listen_port(), &chain_spec)?,
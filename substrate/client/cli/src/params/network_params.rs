@@ -23,15 +23,55 @@ use crate::{
 use clap::Args;
 use sc_network::{
 	config::{
-		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig, TransportConfig,
+		BandwidthLimits, NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode,
+		ProtocolBandwidthLimit, SetConfig, TransportConfig,
 	},
 	multiaddr::Protocol,
+	ProtocolName,
 };
 use sc_service::{
 	config::{Multiaddr, MultiaddrWithPeerId},
 	ChainSpec, ChainType,
 };
-use std::{borrow::Cow, num::NonZeroUsize, path::PathBuf};
+use std::{
+	borrow::Cow,
+	num::{NonZeroU64, NonZeroUsize},
+	path::PathBuf,
+};
+
+/// Number of bytes in the KiB unit used by bandwidth limits on the command line.
+const KIB: u64 = 1024;
+
+/// Parse a `<PROTOCOL>=<IN>:<OUT>` per-protocol bandwidth limit, with rates in KiB/s.
+fn parse_protocol_bandwidth_limit(
+	s: &str,
+) -> Result<(ProtocolName, ProtocolBandwidthLimit), String> {
+	let (protocol, rates) = s
+		.rsplit_once('=')
+		.ok_or_else(|| format!("`{s}` is not in the `<PROTOCOL>=<IN>:<OUT>` format"))?;
+	let (inbound, outbound) = rates
+		.split_once(':')
+		.ok_or_else(|| format!("`{rates}` is not in the `<IN>:<OUT>` format"))?;
+
+	let parse_rate = |rate: &str| -> Result<Option<NonZeroU64>, String> {
+		if rate.is_empty() {
+			return Ok(None)
+		}
+
+		rate.parse::<NonZeroU64>()
+			.map(|rate| NonZeroU64::new(rate.get().saturating_mul(KIB)))
+			.map_err(|error| format!("invalid bandwidth limit `{rate}`: {error}"))
+	};
+
+	if protocol.is_empty() {
+		return Err("protocol name must not be empty".into())
+	}
+
+	Ok((
+		protocol.to_owned().into(),
+		ProtocolBandwidthLimit { inbound: parse_rate(inbound)?, outbound: parse_rate(outbound)? },
+	))
+}
 
 /// Parameters used to create the network configuration.
 #[derive(Debug, Clone, Args)]
@@ -165,8 +205,9 @@ pub struct NetworkParams {
 
 	/// Maximum number of blocks per request.
 	///
-	/// Try reducing this number from the default value if you have a slow network connection
-	/// and observe block requests timing out.
+	/// The number of blocks requested from a peer is adapted to the download rate of the peer,
+	/// up to this maximum. Try reducing this number from the default value if you have a slow
+	/// network connection and observe block requests timing out.
 	#[arg(long, value_name = "COUNT", default_value_t = 64)]
 	pub max_blocks_per_request: u32,
 
@@ -182,6 +223,27 @@ pub struct NetworkParams {
 		verbatim_doc_comment
 	)]
 	pub network_backend: Option<NetworkBackendType>,
+
+	/// Limit the inbound traffic of the node, in KiB/s.
+	#[arg(long, value_name = "KIB_PER_SEC")]
+	pub in_bandwidth_limit: Option<NonZeroU64>,
+
+	/// Limit the outbound traffic of the node, in KiB/s.
+	#[arg(long, value_name = "KIB_PER_SEC")]
+	pub out_bandwidth_limit: Option<NonZeroU64>,
+
+	/// Limit the traffic of a single notification or request-response protocol.
+	///
+	/// The value has the format `<PROTOCOL>=<IN>:<OUT>`, with rates in KiB/s. Leave a rate
+	/// empty to not limit that direction, e.g. `/<genesis hash>/sync/2=:512` only limits
+	/// serving blocks to 512 KiB/s. Can be passed multiple times.
+	#[arg(
+		long,
+		value_name = "PROTOCOL=IN:OUT",
+		value_parser = parse_protocol_bandwidth_limit,
+		verbatim_doc_comment
+	)]
+	pub protocol_bandwidth_limit: Vec<(ProtocolName, ProtocolBandwidthLimit)>,
 }
 
 impl NetworkParams {
@@ -278,6 +340,15 @@ impl NetworkParams {
 			ipfs_server: self.ipfs_server,
 			sync_mode: self.sync.into(),
 			network_backend: self.network_backend.map(Into::into),
+			bandwidth_limits: BandwidthLimits {
+				inbound: self
+					.in_bandwidth_limit
+					.and_then(|limit| NonZeroU64::new(limit.get().saturating_mul(KIB))),
+				outbound: self
+					.out_bandwidth_limit
+					.and_then(|limit| NonZeroU64::new(limit.get().saturating_mul(KIB))),
+				protocols: self.protocol_bandwidth_limit.iter().cloned().collect(),
+			},
 		}
 	}
 }
@@ -326,6 +397,40 @@ mod tests {
 		assert_eq!(expected, params.network_params.reserved_nodes);
 	}
 
+	#[test]
+	fn protocol_bandwidth_limits_are_parsed() {
+		let params = Cli::try_parse_from([
+			"",
+			"--protocol-bandwidth-limit",
+			"/genesis/sync/2=:512",
+			"--protocol-bandwidth-limit",
+			"/genesis/block-announces/1=64:128",
+		])
+		.expect("Parses network params");
+
+		assert_eq!(
+			params.network_params.protocol_bandwidth_limit,
+			vec![
+				(
+					ProtocolName::from("/genesis/sync/2"),
+					ProtocolBandwidthLimit { inbound: None, outbound: NonZeroU64::new(512 * KIB) },
+				),
+				(
+					ProtocolName::from("/genesis/block-announces/1"),
+					ProtocolBandwidthLimit {
+						inbound: NonZeroU64::new(64 * KIB),
+						outbound: NonZeroU64::new(128 * KIB),
+					},
+				),
+			],
+		);
+
+		assert!(Cli::try_parse_from(["", "--protocol-bandwidth-limit", "/genesis/sync/2"]).is_err());
+		assert!(
+			Cli::try_parse_from(["", "--protocol-bandwidth-limit", "/genesis/sync/2=0:1"]).is_err()
+		);
+	}
+
 	#[test]
 	fn sync_ignores_case() {
 		let params = Cli::try_parse_from(["", "--sync", "wArP"]).expect("Parses network params");
@@ -45,6 +45,24 @@ pub struct PruningParams {
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
 	pub state_pruning: Option<DatabasePruningMode>,
 
+	/// Keep the state of every N-th finalized block when pruning the state.
+	///
+	/// Historical queries at these checkpoint blocks keep working, while the state of the blocks
+	/// in between is pruned according to `--state-pruning`. Can be changed between runs, the
+	/// checkpoints kept so far are not pruned.
+	#[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+	pub state_pruning_checkpoints: Option<u32>,
+
+	/// Limit the state kept for the recent finalized blocks to roughly this many MiB.
+	///
+	/// The size is an estimate: it counts the encoded trie nodes inserted by the kept blocks,
+	/// without the database overhead and compression. Combined with a NUMBER `--state-pruning`
+	/// mode, the blocks are pruned as soon as either limit is reached. Used on its own, the
+	/// number of blocks kept is only bounded by this size, which requires `--database paritydb`:
+	/// RocksDB keeps the pruning window in memory and refuses to start in that mode.
+	#[arg(long, value_name = "MiB")]
+	pub state_pruning_max_size: Option<u64>,
+
 	/// Specify the blocks pruning mode.
 	///
 	/// This mode specifies when the block's body (including justifications)
@@ -69,7 +87,28 @@ pub struct PruningParams {
 impl PruningParams {
 	/// Get the pruning value from the parameters
 	pub fn state_pruning(&self) -> error::Result<Option<PruningMode>> {
-		Ok(self.state_pruning.map(|v| v.into()))
+		if self.state_pruning_checkpoints.is_none() && self.state_pruning_max_size.is_none() {
+			return Ok(self.state_pruning.map(|v| v.into()))
+		}
+
+		let mut mode = self.state_pruning.map_or_else(PruningMode::default, Into::into);
+		match mode {
+			PruningMode::Constrained(ref mut constraints) => {
+				if self.state_pruning.is_none() && self.state_pruning_max_size.is_some() {
+					constraints.max_blocks = None;
+				}
+				constraints.max_window_bytes =
+					self.state_pruning_max_size.map(|mib| mib.saturating_mul(1024 * 1024));
+				constraints.checkpoint_interval = self.state_pruning_checkpoints;
+			},
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical =>
+				return Err(error::Error::Input(
+					"`--state-pruning-checkpoints` and `--state-pruning-max-size` can't be used \
+					 with an archive `--state-pruning` mode"
+						.into(),
+				)),
+		}
+		Ok(Some(mode))
 	}
 
 	/// Get the block pruning value from the parameters
@@ -162,4 +201,31 @@ mod tests {
 		assert!(matches!(dbg!(pruning.state_pruning), Some(DatabasePruningMode::ArchiveCanonical)));
 		assert!(matches!(pruning.blocks_pruning, DatabasePruningMode::ArchiveCanonical));
 	}
+
+	#[test]
+	fn state_pruning_constraints_work() {
+		let Cli { pruning } = Cli::parse_from([
+			"",
+			"--state-pruning=1000",
+			"--state-pruning-checkpoints=500",
+			"--state-pruning-max-size=64",
+		]);
+		let Some(PruningMode::Constrained(constraints)) = pruning.state_pruning().unwrap() else {
+			panic!("Expected a constrained pruning mode");
+		};
+		assert_eq!(constraints.max_blocks, Some(1000));
+		assert_eq!(constraints.checkpoint_interval, Some(500));
+		assert_eq!(constraints.max_window_bytes, Some(64 * 1024 * 1024));
+
+		// the size limit alone doesn't bound the number of blocks.
+		let Cli { pruning } = Cli::parse_from(["", "--state-pruning-max-size=64"]);
+		let Some(PruningMode::Constrained(constraints)) = pruning.state_pruning().unwrap() else {
+			panic!("Expected a constrained pruning mode");
+		};
+		assert_eq!(constraints.max_blocks, None);
+
+		let Cli { pruning } =
+			Cli::parse_from(["", "--state-pruning=archive", "--state-pruning-checkpoints=500"]);
+		assert!(pruning.state_pruning().is_err());
+	}
 }
@@ -100,6 +100,13 @@ pub struct RpcParams {
 	#[arg(long)]
 	pub rpc_rate_limit_trust_proxy_headers: bool,
 
+	/// Index the events and extrinsics of finalized blocks.
+	///
+	/// The index serves the `archive_v1_findEvents` and `archive_v1_findExtrinsics` RPC methods.
+	/// It is stored in the database and only available on archive nodes.
+	#[arg(long)]
+	pub rpc_archive_index: bool,
+
 	/// Set the maximum RPC request payload size for both HTTP and WS in megabytes.
 	#[arg(long, default_value_t = RPC_DEFAULT_MAX_REQUEST_SIZE_MB)]
 	pub rpc_max_request_size: u32,
@@ -59,6 +59,22 @@ pub struct TransactionPoolParams {
 	/// The type of transaction pool to be instantiated.
 	#[arg(long, value_enum, default_value_t = TransactionPoolType::SingleState)]
 	pub pool_type: TransactionPoolType,
+
+	/// Maximum number of transactions in the pool from a single sender.
+	///
+	/// The sender is identified by the transaction tags. Unlimited by default.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_limit: Option<usize>,
+
+	/// Minimal priority increase, in percent, required to replace a pooled transaction.
+	#[arg(long, value_name = "PERCENT", default_value_t = 0)]
+	pub pool_min_priority_bump: u64,
+
+	/// Maximum number of pending transactions persisted across node restarts.
+	///
+	/// The journal is stored next to the client database. Set to `0` to disable it.
+	#[arg(long, value_name = "COUNT", default_value_t = 0)]
+	pub pool_journal_limit: usize,
 }
 
 impl TransactionPoolParams {
@@ -71,5 +87,8 @@ impl TransactionPoolParams {
 			self.pool_type.into(),
 			is_dev,
 		)
+		.with_sender_limit(self.pool_sender_limit)
+		.with_min_priority_bump(self.pool_min_priority_bump)
+		.with_journal_limit(self.pool_journal_limit)
 	}
 }
@@ -282,6 +282,7 @@ mod tests {
 					rate_limit: None,
 					rate_limit_whitelisted_ips: Default::default(),
 					rate_limit_trust_proxy_headers: Default::default(),
+					archive_index: false,
 				},
 				prometheus_config: None,
 				telemetry_endpoints: None,
@@ -493,6 +493,7 @@ where
 pub(crate) mod tests {
 	use super::*;
 	use crate::{communication::peers::PeerReport, keystore::BeefyKeystore};
+	use sc_network::peer_store::{BanTarget, BannedPeer};
 	use sc_network_test::Block;
 	use sp_application_crypto::key_types::BEEFY as BEEFY_KEY_TYPE;
 	use sp_consensus_beefy::{
@@ -593,6 +594,18 @@ pub(crate) mod tests {
 		async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
 			unimplemented!();
 		}
+
+		fn ban_peer(&self, _target: BanTarget, _duration: Duration) {
+			unimplemented!();
+		}
+
+		fn unban_peer(&self, _target: &BanTarget) -> bool {
+			unimplemented!();
+		}
+
+		fn banned_peers(&self) -> Vec<BannedPeer> {
+			unimplemented!();
+		}
 	}
 
 	struct TestContext;
@@ -124,6 +124,9 @@ pub enum StorageChanges<Block: BlockT> {
 	Changes(sp_state_machine::StorageChanges<HashingFor<Block>>),
 	/// Whole new state.
 	Import(ImportedState<Block>),
+	/// Whole new state, whose trie nodes were already written to the backend with
+	/// `Backend::import_state_nodes`.
+	Imported,
 }
 
 /// Imported state data. A vector of key-value pairs that should form a trie.
@@ -299,7 +302,10 @@ impl<Block: BlockT> BlockImportParams<Block> {
 
 	/// Check if this block contains state import action
 	pub fn with_state(&self) -> bool {
-		matches!(self.state_action, StateAction::ApplyChanges(StorageChanges::Import(_)))
+		matches!(
+			self.state_action,
+			StateAction::ApplyChanges(StorageChanges::Import(_) | StorageChanges::Imported)
+		)
 	}
 }
 
@@ -91,8 +91,18 @@ pub struct IncomingBlock<B: BlockT> {
 	pub skip_execution: bool,
 	/// Re-validate existing block.
 	pub import_existing: bool,
-	/// Do not compute new state, but rather set it to the given set.
-	pub state: Option<ImportedState<B>>,
+	/// Do not compute new state, but rather set it to the given one.
+	pub state: Option<IncomingState<B>>,
+}
+
+/// State of a block imported without computing it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IncomingState<B: BlockT> {
+	/// Whole new state, given as key-value pairs.
+	KeyValues(ImportedState<B>),
+	/// Whole new state, whose trie nodes were already written to the backend with
+	/// `Backend::import_state_nodes`.
+	Imported,
 }
 
 /// Verify a justification of a block
@@ -349,7 +359,10 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 	import_block.indexed_body = block.indexed_body;
 
 	if let Some(state) = block.state {
-		let changes = crate::block_import::StorageChanges::Import(state);
+		let changes = match state {
+			IncomingState::KeyValues(state) => crate::block_import::StorageChanges::Import(state),
+			IncomingState::Imported => crate::block_import::StorageChanges::Imported,
+		};
 		import_block.state_action = StateAction::ApplyChanges(changes);
 	} else if block.skip_execution {
 		import_block.state_action = StateAction::Skip;
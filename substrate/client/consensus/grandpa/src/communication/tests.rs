@@ -28,6 +28,7 @@ use futures::prelude::*;
 use sc_network::{
 	config::{MultiaddrWithPeerId, Role},
 	event::Event as NetworkEvent,
+	peer_store::{BanTarget, BannedPeer},
 	service::traits::{Direction, MessageSink, NotificationEvent, NotificationService},
 	types::ProtocolName,
 	Multiaddr, NetworkBlock, NetworkEventStream, NetworkPeers, NetworkSyncForkRequest,
@@ -47,6 +48,7 @@ use std::{
 	pin::Pin,
 	sync::Arc,
 	task::{Context, Poll},
+	time::Duration,
 };
 
 #[derive(Debug)]
@@ -137,6 +139,18 @@ impl NetworkPeers for TestNetwork {
 	async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
 		unimplemented!();
 	}
+
+	fn ban_peer(&self, _target: BanTarget, _duration: Duration) {
+		unimplemented!();
+	}
+
+	fn unban_peer(&self, _target: &BanTarget) -> bool {
+		unimplemented!();
+	}
+
+	fn banned_peers(&self) -> Vec<BannedPeer> {
+		unimplemented!();
+	}
 }
 
 impl NetworkEventStream for TestNetwork {
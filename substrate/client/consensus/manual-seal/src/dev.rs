@@ -0,0 +1,227 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Chain manipulation utilities backing the development rpcs: time travel, storage overrides,
+//! impersonation and snapshots.
+
+use crate::Error;
+use parking_lot::Mutex;
+use sc_client_api::backend::Backend as ClientBackend;
+use sp_blockchain::HeaderBackend;
+use sp_inherents::{CreateInherentDataProviders, InherentData, InherentIdentifier};
+use sp_runtime::traits::{Block as BlockT, NumberFor, Zero};
+use std::{collections::HashMap, sync::Arc};
+
+/// The identifier of the dev storage inherent.
+///
+/// Storage changes queued with `engine_setStorage` are passed to the runtime as inherent data
+/// under this identifier, encoded as [`DevStorageChanges`]. They are written by
+/// `pallet-manual-seal-dev`, runtimes without the pallet ignore them.
+pub const DEV_STORAGE_INHERENT_IDENTIFIER: InherentIdentifier = *b"devstore";
+
+/// The identifier of the dev impersonation inherent.
+///
+/// Calls queued with `engine_impersonate` are passed to the runtime as inherent data under this
+/// identifier, encoded as [`DevImpersonatedCalls`]. They are dispatched by
+/// `pallet-manual-seal-dev`, runtimes without the pallet ignore them.
+pub const DEV_IMPERSONATE_INHERENT_IDENTIFIER: InherentIdentifier = *b"devimper";
+
+/// Storage changes carried by the dev storage inherent, `None` values remove the key.
+pub type DevStorageChanges = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+/// Calls carried by the dev impersonation inherent, each one the encoded
+/// `(AccountId, RuntimeCall)` of the runtime.
+pub type DevImpersonatedCalls = Vec<Vec<u8>>;
+
+/// State of the development rpcs, shared between the authorship task and the inherent data
+/// providers of the blocks it seals.
+#[derive(Clone, Default)]
+pub(crate) struct DevState {
+	/// Timestamp requested for the next block.
+	next_timestamp: Option<u64>,
+	/// Milliseconds added to every timestamp inherent since the last time travel.
+	timestamp_offset: u64,
+	/// Storage changes to include in the next block.
+	storage_changes: DevStorageChanges,
+	/// Impersonated calls to include in the next block.
+	impersonated_calls: DevImpersonatedCalls,
+}
+
+impl DevState {
+	/// Use `timestamp` for the next block, all following blocks keep the resulting offset.
+	pub(crate) fn set_next_timestamp(&mut self, timestamp: u64) {
+		self.next_timestamp = Some(timestamp);
+	}
+
+	/// Queue storage changes for the next block, later changes to a key override earlier ones.
+	pub(crate) fn set_storage(&mut self, changes: DevStorageChanges) {
+		self.storage_changes.extend(changes);
+	}
+
+	/// Queue a call to dispatch on behalf of the encoded account `who` in the next block.
+	pub(crate) fn impersonate(&mut self, who: Vec<u8>, call: Vec<u8>) {
+		self.impersonated_calls.push([who, call].concat());
+	}
+}
+
+/// Wraps the inherent data providers of the authorship task to apply the [`DevState`].
+pub(crate) struct DevInherentDataProviders<CIDP> {
+	inner: CIDP,
+	state: Arc<Mutex<DevState>>,
+}
+
+impl<CIDP> DevInherentDataProviders<CIDP> {
+	pub(crate) fn new(inner: CIDP, state: Arc<Mutex<DevState>>) -> Self {
+		Self { inner, state }
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, CIDP> CreateInherentDataProviders<B, ()> for DevInherentDataProviders<CIDP>
+where
+	B: BlockT,
+	CIDP: CreateInherentDataProviders<B, ()>,
+{
+	type InherentDataProviders = (CIDP::InherentDataProviders, DevInherentDataProvider);
+
+	async fn create_inherent_data_providers(
+		&self,
+		parent: B::Hash,
+		extra_args: (),
+	) -> Result<Self::InherentDataProviders, Box<dyn std::error::Error + Send + Sync>> {
+		let inner = self.inner.create_inherent_data_providers(parent, extra_args).await?;
+		Ok((inner, DevInherentDataProvider { state: self.state.clone() }))
+	}
+}
+
+/// Applies the [`DevState`] on top of the inherent data provided by the wrapped providers.
+pub(crate) struct DevInherentDataProvider {
+	state: Arc<Mutex<DevState>>,
+}
+
+#[async_trait::async_trait]
+impl sp_inherents::InherentDataProvider for DevInherentDataProvider {
+	async fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		let mut state = self.state.lock();
+
+		if let Some(timestamp) = inherent_data
+			.get_data::<sp_timestamp::InherentType>(&sp_timestamp::INHERENT_IDENTIFIER)?
+		{
+			// Time can only move forward, a requested timestamp behind the clock resets the
+			// offset.
+			if let Some(next) = state.next_timestamp.take() {
+				state.timestamp_offset = next.saturating_sub(*timestamp);
+			}
+			inherent_data.replace_data(
+				sp_timestamp::INHERENT_IDENTIFIER,
+				&(timestamp + state.timestamp_offset),
+			);
+		}
+
+		if !state.storage_changes.is_empty() {
+			let changes = std::mem::take(&mut state.storage_changes);
+			inherent_data.put_data(DEV_STORAGE_INHERENT_IDENTIFIER, &changes)?;
+		}
+
+		if !state.impersonated_calls.is_empty() {
+			let calls = std::mem::take(&mut state.impersonated_calls);
+			inherent_data.put_data(DEV_IMPERSONATE_INHERENT_IDENTIFIER, &calls)?;
+		}
+
+		Ok(())
+	}
+
+	async fn try_handle_error(
+		&self,
+		_: &InherentIdentifier,
+		_: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		None
+	}
+}
+
+/// Snapshots taken by the authorship task, keyed by the snapshotted block.
+pub(crate) struct Snapshots<B: BlockT> {
+	snapshots: HashMap<B::Hash, (NumberFor<B>, DevState)>,
+}
+
+impl<B: BlockT> Default for Snapshots<B> {
+	fn default() -> Self {
+		Self { snapshots: HashMap::new() }
+	}
+}
+
+impl<B: BlockT> Snapshots<B> {
+	/// Snapshot the best block along with the current dev state.
+	pub(crate) fn take(&mut self, best: &B::Header, state: &DevState) -> B::Hash {
+		use sp_runtime::traits::Header as _;
+
+		let hash = best.hash();
+		self.snapshots.insert(hash, (*best.number(), state.clone()));
+		hash
+	}
+
+	/// Revert the chain to the snapshotted block `hash` and restore the dev state.
+	///
+	/// The snapshot stays valid, so the chain can be reverted to it any number of times. All
+	/// snapshots taken on top of it are discarded.
+	pub(crate) fn revert<C, CB>(
+		&mut self,
+		hash: B::Hash,
+		client: &C,
+		backend: Option<&CB>,
+		state: &mut DevState,
+	) -> Result<(), Error>
+	where
+		C: HeaderBackend<B>,
+		CB: ClientBackend<B>,
+	{
+		let backend = backend.ok_or(Error::RevertUnsupported)?;
+		let (number, snapshot) = self
+			.snapshots
+			.get(&hash)
+			.cloned()
+			.ok_or_else(|| Error::SnapshotNotFound(format!("{}", hash)))?;
+
+		// the snapshotted block might have been abandoned by building on an older parent.
+		if client.hash(number)? != Some(hash) {
+			return Err(Error::SnapshotNotFound(format!("{}", hash)))
+		}
+
+		let to_revert = client.info().best_number - number;
+		if !to_revert.is_zero() {
+			// reverting stops early when the state of an ancestor has already been pruned.
+			let (reverted, _) = backend.revert(to_revert, true)?;
+			if client.info().best_hash != hash {
+				return Err(Error::StringError(format!(
+					"Reverted {} out of {} blocks, snapshot {} is no longer reachable",
+					reverted, to_revert, hash,
+				)))
+			}
+		}
+
+		self.snapshots.retain(|_, (n, _)| *n <= number);
+		*state = snapshot;
+
+		log::info!(target: crate::LOG_TARGET, "⏪ Reverted to snapshot #{} ({})", number, hash);
+		Ok(())
+	}
+}
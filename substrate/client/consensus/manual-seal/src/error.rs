@@ -35,6 +35,8 @@ mod codes {
 	pub const CONSENSUS_ERROR: i32 = 14_000;
 	pub const INHERENTS_ERROR: i32 = 15_000;
 	pub const BLOCKCHAIN_ERROR: i32 = 16_000;
+	pub const SNAPSHOT_NOT_FOUND: i32 = 17_000;
+	pub const REVERT_UNSUPPORTED: i32 = 18_000;
 	pub const UNKNOWN_ERROR: i32 = 20_000;
 }
 
@@ -61,6 +63,12 @@ pub enum Error {
 	/// Supplied parent_hash doesn't exist in chain
 	#[error("Supplied parent_hash: {0} doesn't exist in chain")]
 	BlockNotFound(String),
+	/// Supplied snapshot is unknown or no longer part of the best chain
+	#[error("Snapshot {0} is unknown or no longer part of the best chain")]
+	SnapshotNotFound(String),
+	/// The authorship task was started without a backend to revert
+	#[error("Reverting is not supported, the authorship task has no backend")]
+	RevertUnsupported,
 	/// Some string error
 	#[error("{0}")]
 	StringError(String),
@@ -93,6 +101,8 @@ impl Error {
 		match self {
 			BlockImportError(_) => codes::BLOCK_IMPORT_FAILED,
 			BlockNotFound(_) => codes::BLOCK_NOT_FOUND,
+			SnapshotNotFound(_) => codes::SNAPSHOT_NOT_FOUND,
+			RevertUnsupported => codes::REVERT_UNSUPPORTED,
 			EmptyTransactionPool => codes::EMPTY_TRANSACTION_POOL,
 			ConsensusError(_) => codes::CONSENSUS_ERROR,
 			InherentError(_) => codes::INHERENTS_ERROR,
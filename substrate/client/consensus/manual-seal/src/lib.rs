@@ -21,6 +21,7 @@
 
 use futures::prelude::*;
 use futures_timer::Delay;
+use parking_lot::Mutex;
 use prometheus_endpoint::Registry;
 use sc_client_api::{
 	backend::{Backend as ClientBackend, Finalizer},
@@ -37,6 +38,7 @@ use sp_inherents::CreateInherentDataProviders;
 use sp_runtime::{traits::Block as BlockT, ConsensusEngineId};
 use std::{marker::PhantomData, sync::Arc, time::Duration};
 
+mod dev;
 mod error;
 mod finalize_block;
 mod seal_block;
@@ -46,6 +48,10 @@ pub mod rpc;
 
 pub use self::{
 	consensus::ConsensusDataProvider,
+	dev::{
+		DevImpersonatedCalls, DevStorageChanges, DEV_IMPERSONATE_INHERENT_IDENTIFIER,
+		DEV_STORAGE_INHERENT_IDENTIFIER,
+	},
 	error::Error,
 	finalize_block::{finalize_block, FinalizeBlockParams},
 	rpc::{CreatedBlock, EngineCommand},
@@ -87,7 +93,7 @@ where
 }
 
 /// Params required to start the manual sealing authorship task.
-pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, CS, CIDP, P> {
+pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, CS, CIDP, P, CB> {
 	/// Block import instance.
 	pub block_import: BI,
 
@@ -112,6 +118,11 @@ pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, C
 
 	/// Something that can create the inherent data providers.
 	pub create_inherent_data_providers: CIDP,
+
+	/// Backend used to revert the chain on [`EngineCommand::Revert`].
+	///
+	/// Reverting to snapshots is unsupported if this is `None`.
+	pub backend: Option<Arc<CB>>,
 }
 
 /// Params required to start the instant sealing authorship task.
@@ -161,7 +172,8 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
-	}: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+		backend,
+	}: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P, CB>,
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
@@ -175,6 +187,11 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	CIDP: CreateInherentDataProviders<B, ()>,
 	P: codec::Encode + Send + Sync + 'static,
 {
+	let dev_state = Arc::new(Mutex::new(dev::DevState::default()));
+	let mut snapshots = dev::Snapshots::<B>::default();
+	let create_inherent_data_providers =
+		dev::DevInherentDataProviders::new(create_inherent_data_providers, dev_state.clone());
+
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock { create_empty, finalize, parent_hash, sender } => {
@@ -204,6 +221,31 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 				})
 				.await
 			},
+			EngineCommand::SetNextBlockTimestamp { timestamp, mut sender } => {
+				dev_state.lock().set_next_timestamp(timestamp);
+				rpc::send_result(&mut sender, Ok(()))
+			},
+			EngineCommand::SetStorage { changes, mut sender } => {
+				dev_state.lock().set_storage(changes);
+				rpc::send_result(&mut sender, Ok(()))
+			},
+			EngineCommand::Impersonate { who, call, mut sender } => {
+				dev_state.lock().impersonate(who, call);
+				rpc::send_result(&mut sender, Ok(()))
+			},
+			EngineCommand::Snapshot { mut sender } => {
+				let result = select_chain
+					.best_chain()
+					.await
+					.map(|best| snapshots.take(&best, &dev_state.lock()))
+					.map_err(Into::into);
+				rpc::send_result(&mut sender, result)
+			},
+			EngineCommand::Revert { hash, mut sender } => {
+				let result =
+					snapshots.revert(hash, &*client, backend.as_deref(), &mut dev_state.lock());
+				rpc::send_result(&mut sender, result)
+			},
 		}
 	}
 }
@@ -251,6 +293,7 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
+		backend: None,
 	})
 	.await
 }
@@ -301,6 +344,7 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
+		backend: None,
 	})
 	.await
 }
@@ -346,11 +390,12 @@ pub async fn run_delayed_finalize<B, CB, C, S>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use rpc::{ManualSeal, ManualSealApiServer};
 	use sc_basic_authorship::ProposerFactory;
 	use sc_consensus::ImportedAux;
 	use sc_transaction_pool::{BasicPool, FullChainApi, Options, RevalidationType};
 	use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool, TransactionSource};
-	use sp_inherents::InherentData;
+	use sp_inherents::{InherentData, InherentDataProvider};
 	use sp_runtime::generic::{Digest, DigestItem};
 	use substrate_test_runtime_client::{
 		DefaultTestClientBuilderExt, Sr25519Keyring::*, TestClientBuilder, TestClientBuilderExt,
@@ -440,6 +485,7 @@ mod tests {
 			select_chain,
 			create_inherent_data_providers: |_, _| async { Ok(()) },
 			consensus_data_provider: None,
+			backend: None,
 		}));
 
 		// submit a transaction to pool.
@@ -518,6 +564,7 @@ mod tests {
 			select_chain,
 			create_inherent_data_providers: |_, _| async { Ok(()) },
 			consensus_data_provider: None,
+			backend: None,
 		}));
 
 		let delay_sec = 5;
@@ -594,6 +641,7 @@ mod tests {
 			select_chain,
 			consensus_data_provider: None,
 			create_inherent_data_providers: |_, _| async { Ok(()) },
+			backend: None,
 		}));
 
 		// submit a transaction to pool.
@@ -678,6 +726,7 @@ mod tests {
 			select_chain,
 			consensus_data_provider: None,
 			create_inherent_data_providers: |_, _| async { Ok(()) },
+			backend: None,
 		}));
 
 		// submit a transaction to pool.
@@ -783,6 +832,7 @@ mod tests {
 			// use a provider that pushes some post digest data
 			consensus_data_provider: Some(Box::new(TestDigestProvider { _client: client.clone() })),
 			create_inherent_data_providers: |_, _| async { Ok(()) },
+			backend: None,
 		}));
 
 		let (tx, rx) = futures::channel::oneshot::channel();
@@ -800,4 +850,191 @@ mod tests {
 		let header = client.header(created_block.hash).unwrap().unwrap();
 		assert_eq!(header.number, 1);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_snapshot_and_revert() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool_api = Arc::new(FullChainApi::new(client.clone(), None, &spawner.clone()));
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			pool_api,
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+
+		// spawn the background authorship task
+		tokio::spawn(run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+			backend: Some(backend),
+		}));
+		let rpc = ManualSeal::new(sink);
+
+		let created = rpc.mine_blocks(1, false).await.unwrap();
+		let snapshot = rpc.snapshot().await.unwrap();
+		assert_eq!(snapshot, created[0].hash);
+
+		// finalized blocks are reverted as well.
+		let created = rpc.mine_blocks(3, true).await.unwrap();
+		assert_eq!(created.len(), 3);
+		assert_eq!(client.info().best_number, 4);
+		assert_eq!(client.info().finalized_number, 4);
+		let later_snapshot = rpc.snapshot().await.unwrap();
+
+		assert!(rpc.revert(snapshot).await.unwrap());
+		assert_eq!(client.info().best_hash, snapshot);
+		assert_eq!(client.info().finalized_number, 1);
+
+		// the snapshot can be reverted to more than once.
+		rpc.mine_blocks(2, false).await.unwrap();
+		assert_eq!(client.info().best_number, 3);
+		assert!(rpc.revert(snapshot).await.unwrap());
+		assert_eq!(client.info().best_hash, snapshot);
+
+		// snapshots on top of the reverted one are discarded.
+		assert!(matches!(rpc.revert(later_snapshot).await, Err(Error::SnapshotNotFound(_))));
+	}
+
+	#[tokio::test]
+	async fn manual_seal_set_storage_and_impersonate() {
+		use codec::Encode;
+		use sc_client_api::StorageProvider;
+		use sp_core::{
+			storage::{StorageData, StorageKey},
+			Bytes,
+		};
+		use substrate_test_runtime_client::{
+			runtime::{BalancesCall, RuntimeCall, TestAPI},
+			Sr25519Keyring,
+		};
+
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool_api = Arc::new(FullChainApi::new(client.clone(), None, &spawner.clone()));
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			pool_api,
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+
+		// spawn the background authorship task
+		tokio::spawn(run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+			backend: None,
+		}));
+		let rpc = ManualSeal::new(sink);
+
+		let key = StorageKey(b"dev_key".to_vec());
+		let balance_of = |hash, who: Sr25519Keyring| {
+			client.runtime_api().balance_of(hash, who.public()).unwrap()
+		};
+		let transfer = RuntimeCall::Balances(BalancesCall::transfer_allow_death {
+			dest: Bob.public(),
+			value: 100,
+		});
+
+		assert!(rpc
+			.set_storage(vec![(key.clone(), Some(StorageData(b"value".to_vec())))])
+			.await
+			.unwrap());
+		assert!(rpc
+			.impersonate(Bytes(Alice.public().encode()), Bytes(transfer.encode()))
+			.await
+			.unwrap());
+		let created = rpc.mine_blocks(1, false).await.unwrap();
+		let hash = created[0].hash;
+
+		// the storage is written and the transfer dispatched without a signature of Alice.
+		assert_eq!(client.storage(hash, &key).unwrap(), Some(StorageData(b"value".to_vec())));
+		assert_eq!(balance_of(hash, Alice), balance_of(genesis_hash, Alice) - 100);
+		assert_eq!(balance_of(hash, Bob), balance_of(genesis_hash, Bob) + 100);
+
+		// the changes are applied once, the storage is removed with a `None` value.
+		assert!(rpc.set_storage(vec![(key.clone(), None)]).await.unwrap());
+		let created = rpc.mine_blocks(1, false).await.unwrap();
+		assert_eq!(client.storage(created[0].hash, &key).unwrap(), None);
+		assert_eq!(balance_of(created[0].hash, Alice), balance_of(hash, Alice));
+
+		assert!(rpc.mine_blocks(rpc::MAX_MINED_BLOCKS + 1, false).await.is_err());
+	}
+
+	#[tokio::test]
+	async fn dev_inherent_data_provider_time_travel_and_storage() {
+		use substrate_test_runtime_client::runtime::Block;
+
+		async fn inherent_data(cidp: &impl CreateInherentDataProviders<Block, ()>) -> InherentData {
+			let providers =
+				cidp.create_inherent_data_providers(Default::default(), ()).await.unwrap();
+			providers.create_inherent_data().await.unwrap()
+		}
+		fn timestamp(data: &InherentData) -> u64 {
+			*data
+				.get_data::<sp_timestamp::InherentType>(&sp_timestamp::INHERENT_IDENTIFIER)
+				.unwrap()
+				.unwrap()
+		}
+		fn storage(data: &InherentData) -> Option<DevStorageChanges> {
+			data.get_data(&DEV_STORAGE_INHERENT_IDENTIFIER).unwrap()
+		}
+
+		let state = Arc::new(Mutex::new(dev::DevState::default()));
+		let create_inherent_data_providers = dev::DevInherentDataProviders::new(
+			|_: <Block as BlockT>::Hash, ()| async {
+				Ok(sp_timestamp::InherentDataProvider::new(1_000.into()))
+			},
+			state.clone(),
+		);
+
+		state.lock().set_next_timestamp(5_000);
+		state.lock().set_storage(vec![(b"key".to_vec(), Some(b"value".to_vec()))]);
+		let data = inherent_data(&create_inherent_data_providers).await;
+		assert_eq!(timestamp(&data), 5_000);
+		assert_eq!(storage(&data), Some(vec![(b"key".to_vec(), Some(b"value".to_vec()))]));
+
+		// the offset is kept while the storage changes are only applied once.
+		let data = inherent_data(&create_inherent_data_providers).await;
+		assert_eq!(timestamp(&data), 5_000);
+		assert_eq!(storage(&data), None);
+
+		// travelling back in time resets the offset.
+		state.lock().set_next_timestamp(10);
+		let data = inherent_data(&create_inherent_data_providers).await;
+		assert_eq!(timestamp(&data), 1_000);
+	}
 }
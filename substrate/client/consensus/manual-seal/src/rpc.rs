@@ -18,7 +18,7 @@
 
 //! RPC interface for the `ManualSeal` Engine.
 
-use crate::error::Error;
+use crate::{dev::DevStorageChanges, error::Error};
 use futures::{
 	channel::{mpsc, oneshot},
 	SinkExt,
@@ -26,8 +26,15 @@ use futures::{
 use jsonrpsee::{core::async_trait, proc_macros::rpc};
 use sc_consensus::ImportedAux;
 use serde::{Deserialize, Serialize};
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
 use sp_runtime::EncodedJustification;
 
+/// The maximum number of blocks created by a single `engine_mineBlocks` call.
+pub const MAX_MINED_BLOCKS: u32 = 1024;
+
 /// Sender passed to the authorship task to report errors or successes.
 pub type Sender<T> = Option<oneshot::Sender<std::result::Result<T, Error>>>;
 
@@ -59,6 +66,49 @@ pub enum EngineCommand<Hash> {
 		/// finalization justification
 		justification: Option<EncodedJustification>,
 	},
+	/// Tells the engine to use the supplied timestamp for the next block.
+	///
+	/// Following blocks keep the offset to the wall clock, a timestamp in the past resets it.
+	SetNextBlockTimestamp {
+		/// unix timestamp in milliseconds
+		timestamp: u64,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to write the supplied storage changes in the next block.
+	///
+	/// The changes are handed to the runtime through the dev storage inherent, see
+	/// [`DEV_STORAGE_INHERENT_IDENTIFIER`](crate::DEV_STORAGE_INHERENT_IDENTIFIER).
+	SetStorage {
+		/// storage changes, `None` removes the key
+		changes: DevStorageChanges,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to dispatch the supplied call on behalf of an account in the next block.
+	///
+	/// The call is handed to the runtime through the dev impersonation inherent, see
+	/// [`DEV_IMPERSONATE_INHERENT_IDENTIFIER`](crate::DEV_IMPERSONATE_INHERENT_IDENTIFIER).
+	Impersonate {
+		/// SCALE encoded account id
+		who: Vec<u8>,
+		/// SCALE encoded runtime call
+		call: Vec<u8>,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to snapshot the best block, reports the hash identifying the snapshot.
+	Snapshot {
+		/// sender to report errors/success to the rpc.
+		sender: Sender<Hash>,
+	},
+	/// Tells the engine to revert the chain to a snapshot taken earlier.
+	Revert {
+		/// hash returned when taking the snapshot
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -80,6 +130,41 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<EncodedJustification>,
 	) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to use the given unix timestamp in milliseconds
+	/// for the next block
+	#[method(name = "engine_setNextBlockTimestamp")]
+	async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to create `count` blocks on top of the best
+	/// block, whether or not there are transactions in the pool. At most [`MAX_MINED_BLOCKS`]
+	/// blocks are created per call
+	#[method(name = "engine_mineBlocks")]
+	async fn mine_blocks(
+		&self,
+		count: u32,
+		finalize: bool,
+	) -> Result<Vec<CreatedBlock<Hash>>, Error>;
+
+	/// Instructs the manual-seal authorship task to snapshot the best block
+	#[method(name = "engine_snapshot")]
+	async fn snapshot(&self) -> Result<Hash, Error>;
+
+	/// Instructs the manual-seal authorship task to revert the chain to a snapshot
+	#[method(name = "engine_revert")]
+	async fn revert(&self, snapshot: Hash) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to write the given storage in the next block
+	#[method(name = "engine_setStorage")]
+	async fn set_storage(
+		&self,
+		changes: Vec<(StorageKey, Option<StorageData>)>,
+	) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to dispatch the SCALE encoded `call` with the
+	/// signed origin of the SCALE encoded account `who` in the next block
+	#[method(name = "engine_impersonate")]
+	async fn impersonate(&self, who: Bytes, call: Bytes) -> Result<bool, Error>;
 }
 
 /// A struct that implements the [`ManualSealApiServer`].
@@ -103,6 +188,17 @@ impl<Hash> ManualSeal<Hash> {
 	pub fn new(import_block_channel: mpsc::Sender<EngineCommand<Hash>>) -> Self {
 		Self { import_block_channel }
 	}
+
+	/// Send the command built by `command` to the authorship task and wait for its result.
+	async fn send_command<T>(
+		&self,
+		command: impl FnOnce(Sender<T>) -> EngineCommand<Hash>,
+	) -> Result<T, Error> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		sink.send(command(Some(sender))).await?;
+		receiver.await?
+	}
 }
 
 #[async_trait]
@@ -143,6 +239,64 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		sink.send(command).await?;
 		receiver.await.map(|_| true).map_err(Into::into)
 	}
+
+	async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<bool, Error> {
+		self.send_command(|sender| EngineCommand::SetNextBlockTimestamp { timestamp, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn mine_blocks(
+		&self,
+		count: u32,
+		finalize: bool,
+	) -> Result<Vec<CreatedBlock<Hash>>, Error> {
+		if count > MAX_MINED_BLOCKS {
+			return Err(Error::StringError(format!(
+				"Cannot mine {count} blocks, at most {MAX_MINED_BLOCKS} blocks can be mined at once"
+			)))
+		}
+
+		let mut blocks = Vec::with_capacity(count as usize);
+		for _ in 0..count {
+			let block = self
+				.send_command(|sender| EngineCommand::SealNewBlock {
+					create_empty: true,
+					finalize,
+					parent_hash: None,
+					sender,
+				})
+				.await?;
+			blocks.push(block);
+		}
+		Ok(blocks)
+	}
+
+	async fn snapshot(&self) -> Result<Hash, Error> {
+		self.send_command(|sender| EngineCommand::Snapshot { sender }).await
+	}
+
+	async fn revert(&self, snapshot: Hash) -> Result<bool, Error> {
+		self.send_command(|sender| EngineCommand::Revert { hash: snapshot, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn set_storage(
+		&self,
+		changes: Vec<(StorageKey, Option<StorageData>)>,
+	) -> Result<bool, Error> {
+		let changes = changes.into_iter().map(|(key, value)| (key.0, value.map(|v| v.0))).collect();
+		self.send_command(|sender| EngineCommand::SetStorage { changes, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn impersonate(&self, who: Bytes, call: Bytes) -> Result<bool, Error> {
+		self.send_command(|sender| EngineCommand::Impersonate { who: who.0, call: call.0, sender })
+			.await
+			.map(|_| true)
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
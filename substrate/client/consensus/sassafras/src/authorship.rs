@@ -0,0 +1,774 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! SASSAFRAS slot claiming, tickets generation and the block authoring worker.
+
+use super::*;
+
+use std::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+use futures::prelude::*;
+use log::{error, info};
+use sc_client_api::BlockchainEvents;
+use sc_consensus::block_import::{BlockImportParams, StateAction};
+use sc_consensus_epochs::{EpochIdentifier, EpochIdentifierPosition};
+use sc_consensus_slots::{BackoffAuthoringBlocksStrategy, SlotInfo, StorageChanges};
+use sp_application_crypto::AppCrypto;
+use sp_consensus::{BlockOrigin, Environment, Proposer, SelectChain};
+use sp_consensus_sassafras::ticket_id_threshold;
+use sp_core::{
+	crypto::{ByteArray, Pair},
+	ed25519::Pair as EphemeralPair,
+	U256,
+};
+use sp_keystore::KeystorePtr;
+use sp_runtime::{traits::One, DigestItem};
+
+/// Get the index of the authority expected to author the given slot when no ticket
+/// is assigned to it.
+///
+/// This should always assign the slot to some authority unless the authorities list
+/// is empty.
+pub(crate) fn secondary_authority_index(slot: Slot, epoch: &Epoch) -> Option<AuthorityIndex> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let rand = U256::from_big_endian(
+		&(epoch.randomness, slot).using_encoded(sp_crypto_hashing::blake2_256),
+	);
+
+	let authorities_len = U256::from(epoch.authorities.len());
+	let idx = rand % authorities_len;
+
+	Some(idx.as_u32())
+}
+
+/// Message signed with the ticket erased key to claim the slot assigned to the ticket.
+pub(crate) fn ticket_claim_message(
+	randomness: &Randomness,
+	slot: Slot,
+	epoch_index: u64,
+) -> Vec<u8> {
+	(b"sassafras-ticket-claim", randomness, slot, epoch_index).encode()
+}
+
+/// Tries to claim the given slot.
+///
+/// If the runtime assigned a ticket to the slot (`maybe_ticket`) then the slot can be
+/// claimed only if the ticket was generated by one of our authorities. Otherwise we
+/// fall back to the deterministic assignment (see [`crate`] documentation).
+///
+/// Returns the slot claim to be included in the block pre-digest together with the
+/// authority expected to seal the block.
+pub fn claim_slot(
+	slot: Slot,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+	keystore: &KeystorePtr,
+) -> Option<(SlotClaim, AuthorityId)> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let mut epoch_index = epoch.index;
+	if epoch.end_slot() <= slot {
+		// Slot doesn't strictly belong to the epoch, create a clone with fixed values.
+		epoch_index = epoch.clone_for_slot(slot).index;
+	}
+
+	let (authority_idx, ticket_claim) = match maybe_ticket {
+		Some((ticket_id, ticket_body)) => {
+			let (authority_idx, ticket_secret) = epoch.tickets_aux.get(&ticket_id)?;
+			debug!(
+				target: LOG_TARGET,
+				"Claiming slot {} using ticket {:032x} (attempt {})",
+				slot,
+				ticket_id,
+				ticket_body.attempt_idx,
+			);
+			let erased_pair = EphemeralPair::from_seed(&ticket_secret.erased_secret);
+			let message = ticket_claim_message(&epoch.randomness, slot, epoch_index);
+			let erased_signature = erased_pair.sign(&message);
+			(*authority_idx, Some(TicketClaim { erased_signature }))
+		},
+		None => (secondary_authority_index(slot, epoch)?, None),
+	};
+
+	let authority_id = epoch.authorities.get(authority_idx as usize)?;
+
+	let data = vrf::slot_claim_sign_data(&epoch.randomness, slot, epoch_index);
+	let vrf_signature = keystore
+		.bandersnatch_vrf_sign(AuthorityId::ID, authority_id.as_ref(), &data)
+		.ok()
+		.flatten()?;
+
+	let claim = SlotClaim { authority_idx, slot, vrf_signature, ticket_claim };
+
+	Some((claim, authority_id.clone()))
+}
+
+/// Generate the tickets of our authorities for the given epoch.
+///
+/// Only the tickets whose identifier is below the epoch threshold are returned, ready to
+/// be submitted on-chain. The secrets of the returned tickets are stored in
+/// [`Epoch::tickets_aux`] and are later used to claim the slots the tickets are assigned to.
+pub fn generate_epoch_tickets(
+	epoch: &mut Epoch,
+	ring_context: &vrf::RingContext,
+	keystore: &KeystorePtr,
+) -> Vec<TicketEnvelope> {
+	let mut tickets = Vec::new();
+
+	let threshold = ticket_id_threshold(
+		epoch.config.redundancy_factor,
+		epoch.length,
+		epoch.config.attempts_number,
+		epoch.authorities.len() as u32,
+	);
+	debug!(
+		target: LOG_TARGET,
+		"Generating tickets for epoch {} @ slot {}, threshold: {:032x}",
+		epoch.index,
+		epoch.start,
+		threshold,
+	);
+
+	let randomness = epoch.randomness;
+	let epoch_index = epoch.index;
+	let authorities = epoch.authorities.clone();
+	let pks: Vec<sp_core::bandersnatch::Public> =
+		authorities.iter().map(|authority_id| authority_id.clone().into()).collect();
+
+	for (authority_idx, authority_id) in authorities.iter().enumerate() {
+		if !keystore.has_keys(&[(authority_id.to_raw_vec(), AuthorityId::ID)]) {
+			continue
+		}
+
+		debug!(target: LOG_TARGET, "Building ring prover for authority {}", authority_idx);
+		let prover = ring_context.prover(&pks, authority_idx);
+
+		let make_ticket = |attempt_idx| {
+			let ticket_id_input = vrf::ticket_id_input(&randomness, attempt_idx, epoch_index);
+
+			let pre_output = keystore
+				.bandersnatch_vrf_pre_output(
+					AuthorityId::ID,
+					authority_id.as_ref(),
+					&ticket_id_input,
+				)
+				.ok()??;
+			let ticket_id = vrf::make_ticket_id(&pre_output);
+			if ticket_id >= threshold {
+				return None
+			}
+
+			let (erased_pair, erased_secret) = EphemeralPair::generate();
+			let (revealed_pair, revealed_secret) = EphemeralPair::generate();
+			let body = TicketBody {
+				attempt_idx,
+				erased_public: erased_pair.public(),
+				revealed_public: revealed_pair.public(),
+			};
+
+			let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+			let signature = keystore
+				.bandersnatch_ring_vrf_sign(
+					AuthorityId::ID,
+					authority_id.as_ref(),
+					&sign_data,
+					&prover,
+				)
+				.ok()??;
+			debug_assert_eq!(pre_output, signature.pre_output);
+
+			let secret = TicketSecret { attempt_idx, erased_secret, revealed_secret };
+			Some((ticket_id, TicketEnvelope { body, signature }, secret))
+		};
+
+		for attempt_idx in 0..epoch.config.attempts_number {
+			if let Some((ticket_id, envelope, secret)) = make_ticket(attempt_idx) {
+				debug!(target: LOG_TARGET, "  → {:032x}", ticket_id);
+				tickets.push(envelope);
+				epoch.tickets_aux.insert(ticket_id, (authority_idx as AuthorityIndex, secret));
+			}
+		}
+	}
+
+	tickets
+}
+
+/// Parameters for SASSAFRAS.
+pub struct SassafrasParams<B: BlockT, C, SC, E, I, SO, L, CIDP, BS> {
+	/// The keystore that manages the keys of the node.
+	pub keystore: KeystorePtr,
+
+	/// The client to use
+	pub client: Arc<C>,
+
+	/// The SelectChain Strategy
+	pub select_chain: SC,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// The underlying block-import object to supply our produced blocks to.
+	/// This must be a `SassafrasBlockImport` or a wrapper of it, otherwise
+	/// critical consensus logic will be omitted.
+	pub block_import: I,
+
+	/// A sync oracle
+	pub sync_oracle: SO,
+
+	/// Hook into the sync module to control the justification sync process.
+	pub justification_sync_link: L,
+
+	/// Something that can create the inherent data providers.
+	pub create_inherent_data_providers: CIDP,
+
+	/// Force authoring of blocks even if we are offline
+	pub force_authoring: bool,
+
+	/// Strategy and parameters for backing off block production.
+	pub backoff_authoring_blocks: Option<BS>,
+
+	/// The source of timestamps for relative slots
+	pub sassafras_link: SassafrasLink<B>,
+
+	/// The proportion of the slot dedicated to proposing.
+	///
+	/// The block proposing will be limited to this proportion of the slot from the starting of the
+	/// slot. However, the proposing can still take longer when there is some lenience factor
+	/// applied, because there were no blocks produced for some slots.
+	pub block_proposal_slot_portion: SlotProportion,
+
+	/// The maximum proportion of the slot dedicated to proposing with any lenience factor applied
+	/// due to no blocks being produced.
+	pub max_block_proposal_slot_portion: Option<SlotProportion>,
+
+	/// Handle use to report telemetries.
+	pub telemetry: Option<TelemetryHandle>,
+
+	/// The offchain transaction pool factory.
+	///
+	/// Used to submit the generated tickets.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+}
+
+/// Start the SASSAFRAS worker.
+///
+/// The returned future drives both the slot worker, which claims slots and authors blocks,
+/// and the tickets worker, which generates and submits our tickets every time a new epoch
+/// is announced.
+pub fn start_sassafras<B, C, SC, E, I, SO, CIDP, BS, L, Error>(
+	SassafrasParams {
+		keystore,
+		client,
+		select_chain,
+		env,
+		block_import,
+		sync_oracle,
+		justification_sync_link,
+		create_inherent_data_providers,
+		force_authoring,
+		backoff_authoring_blocks,
+		sassafras_link,
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+		offchain_tx_pool_factory,
+	}: SassafrasParams<B, C, SC, E, I, SO, L, CIDP, BS>,
+) -> Result<SassafrasWorker, ConsensusError>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ BlockchainEvents<B>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+	E: Environment<B, Error = Error> + Send + Sync + 'static,
+	E::Proposer: Proposer<B, Error = Error>,
+	I: BlockImport<B, Error = ConsensusError> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Sync + Clone + 'static,
+	L: sc_consensus::JustificationSyncLink<B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync + 'static,
+	Error: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	let worker = SassafrasSlotWorker {
+		client: client.clone(),
+		block_import,
+		env,
+		sync_oracle: sync_oracle.clone(),
+		justification_sync_link,
+		force_authoring,
+		backoff_authoring_blocks,
+		keystore: keystore.clone(),
+		epoch_changes: sassafras_link.epoch_changes.clone(),
+		config: sassafras_link.config.clone(),
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+	};
+
+	info!(target: LOG_TARGET, "🌳 Starting SASSAFRAS Authorship worker");
+
+	let slot_worker = sc_consensus_slots::start_slot_worker(
+		sassafras_link.config.slot_duration,
+		select_chain,
+		sc_consensus_slots::SimpleSlotWorkerToSlotWorker(worker),
+		sync_oracle,
+		create_inherent_data_providers,
+	);
+
+	let tickets_worker =
+		tickets_worker(client, keystore, sassafras_link.epoch_changes, offchain_tx_pool_factory);
+
+	let inner = future::join(slot_worker, tickets_worker).map(|_| ());
+
+	Ok(SassafrasWorker { inner: Box::pin(inner) })
+}
+
+/// Generate and submit our tickets every time an epoch is announced.
+///
+/// Tickets are generated for the announced epoch, i.e. the one after the current one, and
+/// are submitted via the runtime at the block announcing the epoch.
+async fn tickets_worker<B, C>(
+	client: Arc<C>,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+) where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + BlockchainEvents<B> + AuxStore,
+	C::Api: SassafrasApi<B>,
+{
+	let mut notifications = client.import_notification_stream();
+
+	while let Some(notification) = notifications.next().await {
+		// Tickets of already elapsed epochs are useless.
+		if notification.origin == BlockOrigin::NetworkInitialSync {
+			continue
+		}
+
+		match find_next_epoch_digest::<B>(&notification.header) {
+			Ok(Some(_)) => {},
+			Ok(None) => continue,
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Error fetching next epoch digest: {}", err);
+				continue
+			},
+		}
+
+		let hash = notification.hash;
+		let number = *notification.header.number();
+
+		// Block #1 announces the epoch following the genesis one, and both of them
+		// are tracked under the same fork-tree node.
+		let position = if number == One::one() {
+			EpochIdentifierPosition::Genesis1
+		} else {
+			EpochIdentifierPosition::Regular
+		};
+		let epoch_identifier = EpochIdentifier { position, hash, number };
+
+		let ring_context = match client.runtime_api().ring_context(hash) {
+			Ok(Some(ring_context)) => ring_context,
+			Ok(None) => {
+				debug!(target: LOG_TARGET, "Ring context not available, skipping tickets generation");
+				continue
+			},
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Error fetching ring context: {}", err);
+				continue
+			},
+		};
+
+		let Some(mut epoch) = epoch_changes.shared_data().epoch(&epoch_identifier).cloned() else {
+			warn!(target: LOG_TARGET, "Unexpected missing epoch data for {:?}", hash);
+			continue
+		};
+
+		// Ring signatures are expensive, don't hold the epoch changes lock meanwhile.
+		let tickets = generate_epoch_tickets(&mut epoch, &ring_context, &keystore);
+		if tickets.is_empty() {
+			continue
+		}
+		let epoch_index = epoch.index;
+
+		{
+			let mut epoch_changes = epoch_changes.shared_data();
+			let Some(target) = epoch_changes.epoch_mut(&epoch_identifier) else {
+				warn!(target: LOG_TARGET, "Epoch data for {:?} pruned meanwhile", hash);
+				continue
+			};
+			target.tickets_aux.extend(epoch.tickets_aux);
+
+			if let Err(err) = aux_schema::write_epoch_changes::<B, _, _>(&epoch_changes, |insert| {
+				client.insert_aux(insert, [])
+			}) {
+				warn!(target: LOG_TARGET, "Unable to persist tickets secrets: {}", err);
+			}
+		}
+
+		// Register the offchain tx pool to be able to use it from the runtime.
+		let mut runtime_api = client.runtime_api();
+		runtime_api.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(hash));
+
+		let count = tickets.len();
+		match runtime_api.submit_tickets_unsigned_extrinsic(hash, tickets) {
+			Ok(true) =>
+				info!(target: LOG_TARGET, "🎫 Submitted {} tickets for epoch {}", count, epoch_index),
+			Ok(false) =>
+				error!(target: LOG_TARGET, "Unable to submit tickets: rejected by the runtime"),
+			Err(err) => error!(target: LOG_TARGET, "Unable to submit tickets: {}", err),
+		}
+	}
+}
+
+/// Worker for SASSAFRAS which implements `Future<Output=()>`. This must be polled.
+#[must_use]
+pub struct SassafrasWorker {
+	inner: Pin<Box<dyn Future<Output = ()> + Send + 'static>>,
+}
+
+impl Future for SassafrasWorker {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+		self.inner.as_mut().poll(cx)
+	}
+}
+
+struct SassafrasSlotWorker<B: BlockT, C, E, I, SO, L, BS> {
+	client: Arc<C>,
+	block_import: I,
+	env: E,
+	sync_oracle: SO,
+	justification_sync_link: L,
+	force_authoring: bool,
+	backoff_authoring_blocks: Option<BS>,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	config: SassafrasConfiguration,
+	block_proposal_slot_portion: SlotProportion,
+	max_block_proposal_slot_portion: Option<SlotProportion>,
+	telemetry: Option<TelemetryHandle>,
+}
+
+#[async_trait::async_trait]
+impl<B, C, E, I, Error, SO, L, BS> sc_consensus_slots::SimpleSlotWorker<B>
+	for SassafrasSlotWorker<B, C, E, I, SO, L, BS>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error = ClientError>,
+	C::Api: SassafrasApi<B>,
+	E: Environment<B, Error = Error> + Send + Sync,
+	E::Proposer: Proposer<B, Error = Error>,
+	I: BlockImport<B> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Clone + Sync,
+	L: sc_consensus::JustificationSyncLink<B>,
+	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync,
+	Error: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	type Claim = (SlotClaim, AuthorityId);
+	type SyncOracle = SO;
+	type JustificationSyncLink = L;
+	type CreateProposer =
+		Pin<Box<dyn Future<Output = Result<E::Proposer, ConsensusError>> + Send + 'static>>;
+	type Proposer = E::Proposer;
+	type BlockImport = I;
+	type AuxData = ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>;
+
+	fn logging_target(&self) -> &'static str {
+		LOG_TARGET
+	}
+
+	fn block_import(&mut self) -> &mut Self::BlockImport {
+		&mut self.block_import
+	}
+
+	fn aux_data(&self, parent: &B::Header, slot: Slot) -> Result<Self::AuxData, ConsensusError> {
+		self.epoch_changes
+			.shared_data()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot,
+			)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?
+			.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	}
+
+	fn authorities_len(&self, epoch_descriptor: &Self::AuxData) -> Option<usize> {
+		self.epoch_changes
+			.shared_data()
+			.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+			.map(|epoch| epoch.as_ref().authorities.len())
+	}
+
+	async fn claim_slot(
+		&mut self,
+		parent_header: &B::Header,
+		slot: Slot,
+		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+	) -> Option<Self::Claim> {
+		debug!(target: LOG_TARGET, "Attempting to claim slot {}", slot);
+
+		let maybe_ticket = self
+			.client
+			.runtime_api()
+			.slot_ticket(parent_header.hash(), slot)
+			.map_err(|err| warn!(target: LOG_TARGET, "Error fetching slot ticket: {}", err))
+			.ok()?;
+
+		let claim = claim_slot(
+			slot,
+			self.epoch_changes
+				.shared_data()
+				.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))?
+				.as_ref(),
+			maybe_ticket,
+			&self.keystore,
+		);
+
+		if claim.is_some() {
+			debug!(target: LOG_TARGET, "Claimed slot {}", slot);
+		}
+
+		claim
+	}
+
+	fn pre_digest_data(&self, _slot: Slot, claim: &Self::Claim) -> Vec<DigestItem> {
+		vec![DigestItem::from(&claim.0)]
+	}
+
+	async fn block_import_params(
+		&self,
+		header: B::Header,
+		header_hash: &B::Hash,
+		body: Vec<B::Extrinsic>,
+		storage_changes: StorageChanges<B>,
+		(_, public): Self::Claim,
+		epoch_descriptor: Self::AuxData,
+	) -> Result<BlockImportParams<B>, ConsensusError> {
+		let signature: AuthoritySignature = self
+			.keystore
+			.bandersnatch_sign(
+				<AuthorityId as AppCrypto>::ID,
+				public.as_ref(),
+				header_hash.as_ref(),
+			)
+			.map_err(|e| ConsensusError::CannotSign(format!("{}. Key: {:?}", e, public)))?
+			.ok_or_else(|| {
+				ConsensusError::CannotSign(format!(
+					"Could not find key in keystore. Key: {:?}",
+					public
+				))
+			})?
+			.into();
+
+		let digest_item = DigestItem::from(&signature);
+
+		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
+		import_block.post_digests.push(digest_item);
+		import_block.body = Some(body);
+		import_block.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
+		import_block
+			.insert_intermediate(INTERMEDIATE_KEY, SassafrasIntermediate::<B> { epoch_descriptor });
+
+		Ok(import_block)
+	}
+
+	fn force_authoring(&self) -> bool {
+		self.force_authoring
+	}
+
+	fn should_backoff(&self, slot: Slot, chain_head: &B::Header) -> bool {
+		if let Some(ref strategy) = self.backoff_authoring_blocks {
+			if let Ok(chain_head_slot) = find_slot_claim::<B>(chain_head).map(|claim| claim.slot) {
+				return strategy.should_backoff(
+					*chain_head.number(),
+					chain_head_slot,
+					self.client.info().finalized_number,
+					slot,
+					self.logging_target(),
+				)
+			}
+		}
+		false
+	}
+
+	fn sync_oracle(&mut self) -> &mut Self::SyncOracle {
+		&mut self.sync_oracle
+	}
+
+	fn justification_sync_link(&mut self) -> &mut Self::JustificationSyncLink {
+		&mut self.justification_sync_link
+	}
+
+	fn proposer(&mut self, block: &B::Header) -> Self::CreateProposer {
+		Box::pin(self.env.init(block).map_err(|e| ConsensusError::ClientImport(e.to_string())))
+	}
+
+	fn telemetry(&self) -> Option<TelemetryHandle> {
+		self.telemetry.clone()
+	}
+
+	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<B>) -> Duration {
+		let parent_slot = find_slot_claim::<B>(&slot_info.chain_head).ok().map(|c| c.slot);
+
+		sc_consensus_slots::proposing_remaining_duration(
+			parent_slot,
+			slot_info,
+			&self.block_proposal_slot_portion,
+			self.max_block_proposal_slot_portion.as_ref(),
+			sc_consensus_slots::SlotLenienceType::Exponential,
+			self.logging_target(),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_consensus_sassafras::{EpochConfiguration, KEY_TYPE};
+	use sp_core::crypto::DEV_PHRASE;
+	use sp_keystore::{testing::MemoryKeystore, Keystore};
+
+	fn make_epoch(authorities: Vec<AuthorityId>) -> Epoch {
+		sp_consensus_sassafras::Epoch {
+			index: 10,
+			start: 0.into(),
+			length: 20,
+			randomness: Default::default(),
+			authorities,
+			config: EpochConfiguration { redundancy_factor: 1, attempts_number: 8 },
+		}
+		.into()
+	}
+
+	#[test]
+	fn claim_secondary_slot_works() {
+		let keystore: KeystorePtr = MemoryKeystore::new().into();
+		let valid_public_key =
+			keystore.bandersnatch_generate_new(KEY_TYPE, Some(DEV_PHRASE)).unwrap();
+
+		let authorities = vec![
+			AuthorityId::from(AuthorityPair::generate().0.public()),
+			AuthorityId::from(AuthorityPair::generate().0.public()),
+		];
+		let mut epoch = make_epoch(authorities);
+
+		assert!((0u64..20).all(|slot| claim_slot(slot.into(), &epoch, None, &keystore).is_none()));
+
+		epoch.authorities.push(valid_public_key.into());
+		let claimed: Vec<_> = (0u64..20)
+			.filter_map(|slot| claim_slot(slot.into(), &epoch, None, &keystore))
+			.collect();
+		assert!(!claimed.is_empty());
+		for (claim, author) in claimed {
+			assert_eq!(author, valid_public_key.into());
+			assert_eq!(claim.authority_idx, 2);
+			assert_eq!(secondary_authority_index(claim.slot, &epoch), Some(2));
+			assert!(claim.ticket_claim.is_none());
+		}
+	}
+
+	#[test]
+	fn claim_ticket_slot_requires_ticket_ownership() {
+		let keystore: KeystorePtr = MemoryKeystore::new().into();
+		let public = keystore.bandersnatch_generate_new(KEY_TYPE, Some(DEV_PHRASE)).unwrap();
+		let mut epoch = make_epoch(vec![public.into()]);
+
+		let (erased_pair, erased_secret) = EphemeralPair::generate();
+		let body = TicketBody {
+			attempt_idx: 0,
+			erased_public: erased_pair.public(),
+			revealed_public: erased_pair.public(),
+		};
+
+		// Ticket not generated by us.
+		assert!(claim_slot(3.into(), &epoch, Some((42, body.clone())), &keystore).is_none());
+
+		let secret = TicketSecret { attempt_idx: 0, erased_secret, revealed_secret: [0; 32] };
+		epoch.tickets_aux.insert(42, (0, secret));
+
+		let (claim, author) = claim_slot(3.into(), &epoch, Some((42, body)), &keystore).unwrap();
+		assert_eq!(author, public.into());
+
+		let message = ticket_claim_message(&epoch.randomness, 3.into(), epoch.index);
+		let ticket_claim = claim.ticket_claim.expect("slot claimed using a ticket");
+		assert!(EphemeralPair::verify(
+			&ticket_claim.erased_signature,
+			&message,
+			&erased_pair.public()
+		));
+	}
+
+	#[test]
+	fn generate_epoch_tickets_works() {
+		let keystore: KeystorePtr = MemoryKeystore::new().into();
+		let public = keystore.bandersnatch_generate_new(KEY_TYPE, Some(DEV_PHRASE)).unwrap();
+		let other = AuthorityId::from(AuthorityPair::generate().0.public());
+
+		// With a redundancy factor equal to the number of attempts every ticket is below the
+		// threshold.
+		let mut epoch = make_epoch(vec![other, public.into()]);
+		epoch.config = EpochConfiguration { redundancy_factor: 8, attempts_number: 8 };
+		epoch.length = 2;
+
+		let ring_context = vrf::RingContext::new_testing();
+		let tickets = generate_epoch_tickets(&mut epoch, &ring_context, &keystore);
+
+		assert_eq!(tickets.len(), 8);
+		assert_eq!(epoch.tickets_aux.len(), 8);
+		assert!(epoch.tickets_aux.values().all(|(authority_idx, _)| *authority_idx == 1));
+
+		let pks: Vec<sp_core::bandersnatch::Public> =
+			epoch.authorities.iter().map(|a| a.clone().into()).collect();
+		let verifier = ring_context.verifier(&pks);
+		for ticket in tickets {
+			let ticket_id = vrf::make_ticket_id(&ticket.signature.pre_output);
+			let (_, secret) = epoch.tickets_aux.get(&ticket_id).expect("ticket secret is stored");
+			assert_eq!(secret.attempt_idx, ticket.body.attempt_idx);
+			assert_eq!(
+				EphemeralPair::from_seed(&secret.erased_secret).public(),
+				ticket.body.erased_public,
+			);
+
+			let input =
+				vrf::ticket_id_input(&epoch.randomness, ticket.body.attempt_idx, epoch.index);
+			let sign_data = vrf::ticket_body_sign_data(&ticket.body, input);
+			assert!(ticket.signature.ring_vrf_verify(&sign_data, &verifier));
+		}
+	}
+}
@@ -0,0 +1,207 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Schema for SASSAFRAS epoch changes in the aux-db.
+
+use codec::{Decode, Encode};
+use log::info;
+
+use crate::{Epoch, LOG_TARGET};
+use sc_client_api::backend::AuxStore;
+use sc_consensus_epochs::{EpochChangesFor, SharedEpochChanges};
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_consensus_sassafras::SassafrasBlockWeight;
+use sp_runtime::traits::Block as BlockT;
+
+const SASSAFRAS_EPOCH_CHANGES_VERSION: &[u8] = b"sassafras_epoch_changes_version";
+const SASSAFRAS_EPOCH_CHANGES_KEY: &[u8] = b"sassafras_epoch_changes";
+const SASSAFRAS_EPOCH_CHANGES_CURRENT_VERSION: u32 = 1;
+
+/// The aux storage key used to store the block weight of the given block hash.
+pub fn block_weight_key<H: Encode>(block_hash: H) -> Vec<u8> {
+	(b"sassafras_block_weight", block_hash).encode()
+}
+
+fn load_decode<B, T>(backend: &B, key: &[u8]) -> ClientResult<Option<T>>
+where
+	B: AuxStore,
+	T: Decode,
+{
+	let corrupt = |e: codec::Error| {
+		ClientError::Backend(format!("SASSAFRAS DB is corrupted. Decode error: {}", e))
+	};
+	match backend.get_aux(key)? {
+		None => Ok(None),
+		Some(t) => T::decode(&mut &t[..]).map(Some).map_err(corrupt),
+	}
+}
+
+/// Load or initialize persistent epoch change data from backend.
+pub fn load_epoch_changes<Block: BlockT, B: AuxStore>(
+	backend: &B,
+) -> ClientResult<SharedEpochChanges<Block, Epoch>> {
+	let version = load_decode::<_, u32>(backend, SASSAFRAS_EPOCH_CHANGES_VERSION)?;
+
+	let maybe_epoch_changes = match version {
+		None => None,
+		Some(SASSAFRAS_EPOCH_CHANGES_CURRENT_VERSION) =>
+			load_decode::<_, EpochChangesFor<Block, Epoch>>(backend, SASSAFRAS_EPOCH_CHANGES_KEY)?,
+		Some(other) =>
+			return Err(ClientError::Backend(format!(
+				"Unsupported SASSAFRAS DB version: {:?}",
+				other
+			))),
+	};
+
+	let epoch_changes =
+		SharedEpochChanges::<Block, Epoch>::new(maybe_epoch_changes.unwrap_or_else(|| {
+			info!(
+				target: LOG_TARGET,
+				"🌳 Creating empty SASSAFRAS epoch changes on what appears to be first startup.",
+			);
+			EpochChangesFor::<Block, Epoch>::default()
+		}));
+
+	Ok(epoch_changes)
+}
+
+/// Update the epoch changes on disk after a change.
+pub(crate) fn write_epoch_changes<Block: BlockT, F, R>(
+	epoch_changes: &EpochChangesFor<Block, Epoch>,
+	write_aux: F,
+) -> R
+where
+	F: FnOnce(&[(&'static [u8], &[u8])]) -> R,
+{
+	SASSAFRAS_EPOCH_CHANGES_CURRENT_VERSION.using_encoded(|version| {
+		let encoded_epoch_changes = epoch_changes.encode();
+		write_aux(&[
+			(SASSAFRAS_EPOCH_CHANGES_KEY, encoded_epoch_changes.as_slice()),
+			(SASSAFRAS_EPOCH_CHANGES_VERSION, version),
+		])
+	})
+}
+
+/// Write the cumulative chain-weight of a block ot aux storage.
+pub(crate) fn write_block_weight<H: Encode, F, R>(
+	block_hash: H,
+	block_weight: SassafrasBlockWeight,
+	write_aux: F,
+) -> R
+where
+	F: FnOnce(&[(Vec<u8>, &[u8])]) -> R,
+{
+	let key = block_weight_key(block_hash);
+	block_weight.using_encoded(|s| write_aux(&[(key, s)]))
+}
+
+/// Load the cumulative chain-weight associated with a block.
+pub fn load_block_weight<H: Encode, B: AuxStore>(
+	backend: &B,
+	block_hash: H,
+) -> ClientResult<Option<SassafrasBlockWeight>> {
+	load_decode(backend, block_weight_key(block_hash).as_slice())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::TicketSecret;
+	use fork_tree::ForkTree;
+	use sc_consensus_epochs::{
+		migration::EpochChangesV0For, EpochHeader, EpochIdentifier, EpochIdentifierPosition,
+		PersistedEpoch, PersistedEpochHeader,
+	};
+	use sc_network_test::Block as TestBlock;
+	use sp_consensus::Error as ConsensusError;
+	use sp_consensus_sassafras::EpochConfiguration;
+	use sp_core::H256;
+	use sp_runtime::traits::NumberFor;
+	use substrate_test_runtime_client;
+
+	#[test]
+	fn epoch_changes_roundtrip_keeps_tickets_aux() {
+		let mut epoch: Epoch = sp_consensus_sassafras::Epoch {
+			index: 1,
+			start: 0.into(),
+			length: 100,
+			randomness: [0; 32],
+			authorities: vec![],
+			config: EpochConfiguration { redundancy_factor: 1, attempts_number: 4 },
+		}
+		.into();
+		let secret =
+			TicketSecret { attempt_idx: 3, erased_secret: [1; 32], revealed_secret: [2; 32] };
+		epoch.tickets_aux.insert(42, (0, secret));
+
+		let client = substrate_test_runtime_client::new();
+		assert_eq!(load_decode::<_, u32>(&client, SASSAFRAS_EPOCH_CHANGES_VERSION).unwrap(), None);
+		assert_eq!(
+			load_epoch_changes::<TestBlock, _>(&client)
+				.unwrap()
+				.shared_data()
+				.tree()
+				.iter()
+				.count(),
+			0
+		);
+
+		let mut tree = ForkTree::<H256, NumberFor<TestBlock>, _>::new();
+		tree.import::<_, ConsensusError>(
+			Default::default(),
+			Default::default(),
+			PersistedEpoch::Regular(epoch.clone()),
+			&|_, _| Ok(false), // Test is single item only so this can be set to false.
+		)
+		.unwrap();
+		let epoch_changes = EpochChangesV0For::<TestBlock, Epoch>::from_raw(tree).migrate();
+
+		write_epoch_changes::<TestBlock, _, _>(&epoch_changes, |values| {
+			client.insert_aux(values, &[]).unwrap();
+		});
+
+		assert_eq!(
+			load_decode::<_, u32>(&client, SASSAFRAS_EPOCH_CHANGES_VERSION).unwrap(),
+			Some(SASSAFRAS_EPOCH_CHANGES_CURRENT_VERSION),
+		);
+
+		let epoch_changes = load_epoch_changes::<TestBlock, _>(&client).unwrap();
+		let epoch_changes = epoch_changes.shared_data();
+
+		assert!(
+			epoch_changes
+				.tree()
+				.iter()
+				.map(|(_, _, epoch)| epoch.clone())
+				.collect::<Vec<_>>() ==
+				vec![PersistedEpochHeader::Regular(EpochHeader {
+					start_slot: 0.into(),
+					end_slot: 100.into(),
+				})],
+		); // PersistedEpochHeader does not implement Debug, so we use assert! directly.
+
+		let stored = epoch_changes
+			.epoch(&EpochIdentifier {
+				position: EpochIdentifierPosition::Regular,
+				hash: Default::default(),
+				number: Default::default(),
+			})
+			.expect("epoch was stored above");
+		assert_eq!(stored, &epoch);
+	}
+}
@@ -0,0 +1,685 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! # SASSAFRAS (Semi Anonymous Sortition of Staked Assignees For Fixed-time Rhythmic Assignment
+//! of Slots)
+//!
+//! Client side of the SASSAFRAS block production protocol, the runtime side is
+//! implemented by `pallet-sassafras`.
+//!
+//! SASSAFRAS is a slot-based block production mechanism which assigns each slot
+//! of an epoch to exactly one block producer. During the first half of an epoch
+//! every authority generates a set of tickets for the next epoch. A ticket
+//! identifier is the output of a VRF evaluated over the next epoch randomness,
+//! and only tickets whose identifier is below a threshold (derived from the
+//! epoch configuration and the number of authorities) are submitted on-chain.
+//! Tickets are submitted together with a ring-VRF proof, which shows that the
+//! ticket was produced by one of the authorities without revealing which one.
+//!
+//! The runtime sorts the received tickets and assigns them to the next epoch
+//! slots. When a slot comes, the owner of the assigned ticket claims it by
+//! signing with the ephemeral key that was committed to in the ticket body.
+//! Slots without an assigned ticket fall back to a deterministic assignment
+//! which picks the authority at index:
+//!
+//! `blake2_256(epoch_randomness ++ slot_number) % authorities_len`.
+//!
+//! Epoch changes are announced by the runtime in the first block of each epoch,
+//! which carries the parameters of the epoch after the current one. The client
+//! keeps track of the announced epochs in a fork-aware tree persisted in the
+//! auxiliary database (see [`aux_schema`]).
+//!
+//! The fork choice rule is weight-based, where weight equals the number of
+//! blocks produced through a ticket claim. We will pick the heaviest chain and
+//! will go with the longest one in case of a tie.
+//!
+//! An in-depth description of the protocol can be found here:
+//! <https://research.web3.foundation/Polkadot/protocols/block-production/SASSAFRAS>
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::{
+	collections::{BTreeMap, HashSet},
+	ops::{Deref, DerefMut},
+	sync::Arc,
+};
+
+use codec::{Decode, Encode};
+use log::{debug, trace, warn};
+use prometheus_endpoint::Registry;
+
+use sc_client_api::{
+	backend::AuxStore, AuxDataOperations, Backend as BackendT, FinalityNotification,
+	PreCommitActions, UsageProvider,
+};
+use sc_consensus::{
+	block_import::BlockImport,
+	import_queue::{BasicQueue, BoxJustificationImport, DefaultImportQueue},
+};
+use sc_consensus_epochs::{
+	descendent_query, Epoch as EpochT, EpochChangesFor, SharedEpochChanges, ViableEpochDescriptor,
+};
+use sc_consensus_slots::InherentDataProviderExt;
+use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder as BlockBuilderApi;
+use sp_blockchain::{
+	Backend as _, Error as ClientError, ForkBackend, HeaderBackend, HeaderMetadata,
+	Result as ClientResult,
+};
+use sp_consensus::Error as ConsensusError;
+use sp_core::traits::SpawnEssentialNamed;
+use sp_inherents::CreateInherentDataProviders;
+use sp_runtime::{
+	generic::OpaqueDigestItemId,
+	traits::{Block as BlockT, Header, NumberFor, SaturatedConversion, Zero},
+};
+
+pub use sc_consensus_slots::SlotProportion;
+pub use sp_consensus::SyncOracle;
+pub use sp_consensus_sassafras::{
+	digests::{ConsensusLog, NextEpochDescriptor, SlotClaim},
+	vrf, AuthorityId, AuthorityIndex, AuthorityPair, AuthoritySignature, EpochConfiguration,
+	Randomness, SassafrasApi, SassafrasBlockWeight, Slot, SlotDuration, TicketBody, TicketClaim,
+	TicketEnvelope, TicketId, SASSAFRAS_ENGINE_ID,
+};
+
+pub use authorship::{start_sassafras, SassafrasParams, SassafrasWorker};
+pub use aux_schema::load_block_weight as block_weight;
+pub use block_import::SassafrasBlockImport;
+pub use verification::SassafrasVerifier;
+
+mod block_import;
+mod verification;
+
+pub mod authorship;
+pub mod aux_schema;
+#[cfg(test)]
+mod tests;
+
+const LOG_TARGET: &str = "sassafras";
+
+/// Secret data of a ticket generated by one of the local authorities.
+///
+/// Used to claim the slot the ticket gets assigned to.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct TicketSecret {
+	/// Attempt index the ticket was generated with.
+	pub attempt_idx: u32,
+	/// Seed of the ephemeral key committed to via [`TicketBody::erased_public`].
+	pub erased_secret: [u8; 32],
+	/// Seed of the ephemeral key committed to via [`TicketBody::revealed_public`].
+	pub revealed_secret: [u8; 32],
+}
+
+/// SASSAFRAS epoch information.
+///
+/// Along with the parameters announced by the runtime, this holds the secrets of the
+/// tickets generated by the local authorities for the epoch.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct Epoch {
+	inner: sp_consensus_sassafras::Epoch,
+	/// Tickets generated by the local authorities, together with the index of the
+	/// authority owning the ticket.
+	pub tickets_aux: BTreeMap<TicketId, (AuthorityIndex, TicketSecret)>,
+}
+
+impl Deref for Epoch {
+	type Target = sp_consensus_sassafras::Epoch;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+impl DerefMut for Epoch {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.inner
+	}
+}
+
+impl From<sp_consensus_sassafras::Epoch> for Epoch {
+	fn from(epoch: sp_consensus_sassafras::Epoch) -> Self {
+		Epoch { inner: epoch, tickets_aux: BTreeMap::new() }
+	}
+}
+
+impl EpochT for Epoch {
+	type NextEpochDescriptor = (NextEpochDescriptor, EpochConfiguration);
+	type Slot = Slot;
+
+	fn increment(&self, (descriptor, config): (NextEpochDescriptor, EpochConfiguration)) -> Epoch {
+		sp_consensus_sassafras::Epoch {
+			index: self.index + 1,
+			start: self.start + self.length as u64,
+			length: self.length,
+			randomness: descriptor.randomness,
+			authorities: descriptor.authorities,
+			config,
+		}
+		.into()
+	}
+
+	fn start_slot(&self) -> Slot {
+		self.start
+	}
+
+	fn end_slot(&self) -> Slot {
+		self.start + self.length as u64
+	}
+}
+
+impl Epoch {
+	/// Create the genesis epoch (epoch #0).
+	///
+	/// This is defined to start at the slot of the first block, so that has to be provided.
+	pub fn genesis(genesis_config: &SassafrasConfiguration, slot: Slot) -> Epoch {
+		sp_consensus_sassafras::Epoch {
+			index: 0,
+			start: slot,
+			length: genesis_config.epoch_length,
+			randomness: genesis_config.randomness,
+			authorities: genesis_config.authorities.clone(),
+			config: genesis_config.epoch_config,
+		}
+		.into()
+	}
+
+	/// Clone and tweak epoch information to refer to the specified slot.
+	///
+	/// All the information which depends on the slot value is recomputed and assigned
+	/// to the returned epoch instance.
+	///
+	/// The `slot` must be greater than or equal the original epoch start slot,
+	/// if is less this operation is equivalent to a simple clone.
+	pub fn clone_for_slot(&self, slot: Slot) -> Epoch {
+		let mut epoch = self.clone();
+
+		let skipped_epochs = *slot.saturating_sub(self.start) / self.length as u64;
+
+		let index = epoch.index.checked_add(skipped_epochs).expect(
+			"epoch number is u64; it should be strictly smaller than number of slots; \
+				slots relate in some way to wall clock time; \
+				if u64 is not enough we should crash for safety; qed.",
+		);
+
+		let start = skipped_epochs
+			.checked_mul(epoch.length as u64)
+			.and_then(|skipped_slots| epoch.start.checked_add(skipped_slots))
+			.expect(
+				"slot number is u64; it should relate in some way to wall clock time; \
+				 if u64 is not enough we should crash for safety; qed.",
+			);
+
+		epoch.index = index;
+		epoch.start = Slot::from(start);
+
+		epoch
+	}
+}
+
+/// SASSAFRAS client configuration.
+///
+/// Holds the slot duration and the parameters of the genesis epoch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SassafrasConfiguration {
+	/// The slot duration.
+	pub slot_duration: SlotDuration,
+	/// The number of slots in an epoch.
+	pub epoch_length: u32,
+	/// The authorities of the genesis epoch.
+	pub authorities: Vec<AuthorityId>,
+	/// The randomness of the genesis epoch.
+	pub randomness: Randomness,
+	/// The tickets configuration of the genesis epoch.
+	pub epoch_config: EpochConfiguration,
+}
+
+/// Errors encountered by the sassafras authorship task.
+#[derive(Debug, thiserror::Error)]
+pub enum Error<B: BlockT> {
+	/// Multiple SASSAFRAS pre-runtime digests
+	#[error("Multiple SASSAFRAS pre-runtime digests, rejecting!")]
+	MultiplePreRuntimeDigests,
+	/// No SASSAFRAS pre-runtime digest found
+	#[error("No SASSAFRAS pre-runtime digest found")]
+	NoPreRuntimeDigest,
+	/// Multiple SASSAFRAS epoch change digests
+	#[error("Multiple SASSAFRAS epoch change digests, rejecting!")]
+	MultipleEpochChangeDigests,
+	/// Could not fetch epoch
+	#[error("Could not fetch epoch at {0:?}")]
+	FetchEpoch(B::Hash),
+	/// Header rejected: too far in the future
+	#[error("Header {0:?} rejected: too far in the future")]
+	TooFarInFuture(B::Hash),
+	/// Parent unavailable. Cannot import
+	#[error("Parent ({0}) of {1} unavailable. Cannot import")]
+	ParentUnavailable(B::Hash, B::Hash),
+	/// Slot number must increase
+	#[error("Slot number must increase: parent slot: {0}, this slot: {1}")]
+	SlotMustIncrease(Slot, Slot),
+	/// Header has a bad seal
+	#[error("Header {0:?} has a bad seal")]
+	HeaderBadSeal(B::Hash),
+	/// Header is unsealed
+	#[error("Header {0:?} is unsealed")]
+	HeaderUnsealed(B::Hash),
+	/// Slot author not found
+	#[error("Slot author not found")]
+	SlotAuthorNotFound,
+	/// Bad signature
+	#[error("Bad signature on {0:?}")]
+	BadSignature(B::Hash),
+	/// Invalid author: Expected fallback author
+	#[error("Invalid author: Expected fallback author: {0:?}, got: {1:?}.")]
+	InvalidAuthor(AuthorityId, AuthorityId),
+	/// VRF verification failed
+	#[error("VRF verification failed")]
+	VrfVerificationFailed,
+	/// Slot is assigned to a ticket but the header has no ticket claim
+	#[error("Slot {0} is assigned to a ticket, but the header has no ticket claim")]
+	MissingTicketClaim(Slot),
+	/// Slot is not assigned to a ticket but the header has a ticket claim
+	#[error("Slot {0} is not assigned to a ticket, but the header has a ticket claim")]
+	UnexpectedTicketClaim(Slot),
+	/// Ticket claim signature verification failed
+	#[error("Bad ticket claim for slot {0}")]
+	BadTicketClaim(Slot),
+	/// Could not fetch parent header
+	#[error("Could not fetch parent header: {0}")]
+	FetchParentHeader(sp_blockchain::Error),
+	/// Expected epoch change to happen.
+	#[error("Expected epoch change to happen at {0:?}, s{1}")]
+	ExpectedEpochChange(B::Hash, Slot),
+	/// Unexpected epoch change
+	#[error("Unexpected epoch change")]
+	UnexpectedEpochChange,
+	/// Parent block has no associated weight
+	#[error("Parent block of {0} has no associated weight")]
+	ParentBlockNoAssociatedWeight(B::Hash),
+	/// Check inherents error
+	#[error("Checking inherents failed: {0}")]
+	CheckInherents(sp_inherents::Error),
+	/// Unhandled check inherents error
+	#[error("Checking inherents unhandled error: {}", String::from_utf8_lossy(.0))]
+	CheckInherentsUnhandled(sp_inherents::InherentIdentifier),
+	/// Create inherents error.
+	#[error("Creating inherents failed: {0}")]
+	CreateInherents(sp_inherents::Error),
+	/// Client error
+	#[error(transparent)]
+	Client(sp_blockchain::Error),
+	/// Runtime Api error.
+	#[error(transparent)]
+	RuntimeApi(sp_api::ApiError),
+	/// Fork tree error
+	#[error(transparent)]
+	ForkTree(Box<fork_tree::Error<sp_blockchain::Error>>),
+}
+
+impl<B: BlockT> From<Error<B>> for String {
+	fn from(error: Error<B>) -> String {
+		error.to_string()
+	}
+}
+
+fn sassafras_err<B: BlockT>(error: Error<B>) -> Error<B> {
+	debug!(target: LOG_TARGET, "{}", error);
+	error
+}
+
+/// Intermediate value passed to block importer.
+pub struct SassafrasIntermediate<B: BlockT> {
+	/// The epoch descriptor.
+	pub epoch_descriptor: ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+}
+
+/// Intermediate key for Sassafras engine.
+pub static INTERMEDIATE_KEY: &[u8] = b"sass1";
+
+/// Read configuration from the runtime state at current best block.
+///
+/// The runtime doesn't expose the slot duration, so this has to be provided by the caller
+/// (e.g. read from the chain specification).
+pub fn configuration<B: BlockT, C>(
+	client: &C,
+	slot_duration: SlotDuration,
+) -> ClientResult<SassafrasConfiguration>
+where
+	C: ProvideRuntimeApi<B> + UsageProvider<B>,
+	C::Api: SassafrasApi<B>,
+{
+	let at_hash = if client.usage_info().chain.finalized_state.is_some() {
+		client.usage_info().chain.best_hash
+	} else {
+		debug!(target: LOG_TARGET, "No finalized state is available. Reading config from genesis");
+		client.usage_info().chain.genesis_hash
+	};
+
+	let epoch = client.runtime_api().current_epoch(at_hash)?;
+
+	Ok(SassafrasConfiguration {
+		slot_duration,
+		epoch_length: epoch.length,
+		authorities: epoch.authorities,
+		randomness: epoch.randomness,
+		epoch_config: epoch.config,
+	})
+}
+
+/// Extract the SASSAFRAS slot claim from the given header.
+///
+/// Slot claims are mandatory, the function will return `Err` if none is found.
+pub fn find_slot_claim<B: BlockT>(header: &B::Header) -> Result<SlotClaim, Error<B>> {
+	// genesis block doesn't contain a slot claim so let's generate a
+	// dummy one to not break any invariants in the rest of the code
+	if header.number().is_zero() {
+		use sp_core::crypto::{Pair, VrfSecret};
+		let pair = AuthorityPair::from_seed(&[0; 32]);
+		let data = vrf::slot_claim_sign_data(&Default::default(), 0.into(), 0);
+		return Ok(SlotClaim {
+			authority_idx: 0,
+			slot: 0.into(),
+			vrf_signature: pair.as_ref().vrf_sign(&data),
+			ticket_claim: None,
+		})
+	}
+
+	let mut claim: Option<_> = None;
+	for log in header.digest().logs() {
+		trace!(target: LOG_TARGET, "Checking log {:?}, looking for slot claim digest", log);
+		match (SlotClaim::try_from(log), claim.is_some()) {
+			(Ok(_), true) => return Err(sassafras_err(Error::MultiplePreRuntimeDigests)),
+			(Err(_), _) => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
+			(Ok(c), false) => claim = Some(c),
+		}
+	}
+	claim.ok_or_else(|| sassafras_err(Error::NoPreRuntimeDigest))
+}
+
+/// Extract the SASSAFRAS epoch change digest from the given header, if it exists.
+fn find_next_epoch_digest<B: BlockT>(
+	header: &B::Header,
+) -> Result<Option<NextEpochDescriptor>, Error<B>> {
+	let mut epoch_digest: Option<_> = None;
+	for log in header.digest().logs() {
+		trace!(target: LOG_TARGET, "Checking log {:?}, looking for epoch change digest.", log);
+		let log = log.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(&SASSAFRAS_ENGINE_ID));
+		match (log, epoch_digest.is_some()) {
+			(Some(ConsensusLog::NextEpochData(_)), true) =>
+				return Err(sassafras_err(Error::MultipleEpochChangeDigests)),
+			(Some(ConsensusLog::NextEpochData(epoch)), false) => epoch_digest = Some(epoch),
+			_ => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
+		}
+	}
+
+	Ok(epoch_digest)
+}
+
+/// State that must be shared between the import queue and the authoring logic.
+#[derive(Clone)]
+pub struct SassafrasLink<Block: BlockT> {
+	epoch_changes: SharedEpochChanges<Block, Epoch>,
+	config: SassafrasConfiguration,
+}
+
+impl<Block: BlockT> SassafrasLink<Block> {
+	/// Get the epoch changes of this link.
+	pub fn epoch_changes(&self) -> &SharedEpochChanges<Block, Epoch> {
+		&self.epoch_changes
+	}
+
+	/// Get the config of this link.
+	pub fn config(&self) -> &SassafrasConfiguration {
+		&self.config
+	}
+}
+
+// Remove obsolete block's weight data by leveraging finality notifications.
+// This includes data for all finalized blocks (excluding the most recent one)
+// and all stale branches.
+fn aux_storage_cleanup<C: HeaderMetadata<Block> + HeaderBackend<Block>, Block: BlockT>(
+	client: &C,
+	notification: &FinalityNotification<Block>,
+) -> AuxDataOperations {
+	let mut hashes = HashSet::new();
+
+	let first = notification.tree_route.first().unwrap_or(&notification.hash);
+	match client.header_metadata(*first) {
+		Ok(meta) => {
+			hashes.insert(meta.parent);
+		},
+		Err(err) => {
+			warn!(target: LOG_TARGET, "Failed to lookup metadata for block `{:?}`: {}", first, err,)
+		},
+	}
+
+	// Cleans data for finalized block's ancestors
+	hashes.extend(
+		notification
+			.tree_route
+			.iter()
+			// Ensure we don't prune latest finalized block.
+			// This should not happen, but better be safe than sorry!
+			.filter(|h| **h != notification.hash),
+	);
+
+	// Cleans data for stale forks.
+	let stale_forks = match client.expand_forks(&notification.stale_heads) {
+		Ok(stale_forks) => stale_forks,
+		Err(e) => {
+			warn!(target: LOG_TARGET, "{:?}", e);
+
+			Default::default()
+		},
+	};
+	hashes.extend(stale_forks.iter());
+
+	hashes
+		.into_iter()
+		.map(|val| (aux_schema::block_weight_key(val), None))
+		.collect()
+}
+
+/// Produce a SASSAFRAS block-import object to be used later on in the construction of
+/// an import-queue.
+///
+/// Also returns a link object used to correctly instantiate the import queue
+/// and background worker.
+pub fn block_import<Client, Block: BlockT, I>(
+	config: SassafrasConfiguration,
+	wrapped_block_import: I,
+	client: Arc<Client>,
+) -> ClientResult<(SassafrasBlockImport<Block, Client, I>, SassafrasLink<Block>)>
+where
+	Client: AuxStore
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ PreCommitActions<Block>
+		+ 'static,
+{
+	let epoch_changes = aux_schema::load_epoch_changes::<Block, _>(&*client)?;
+	let link = SassafrasLink { epoch_changes: epoch_changes.clone(), config: config.clone() };
+
+	block_import::prune_finalized(client.clone(), &mut epoch_changes.shared_data())?;
+
+	let client_weak = Arc::downgrade(&client);
+	let on_finality = move |summary: &FinalityNotification<Block>| {
+		if let Some(client) = client_weak.upgrade() {
+			aux_storage_cleanup(client.as_ref(), summary)
+		} else {
+			Default::default()
+		}
+	};
+	client.register_finality_action(Box::new(on_finality));
+
+	let import = SassafrasBlockImport::new(client, epoch_changes, wrapped_block_import, config);
+
+	Ok((import, link))
+}
+
+/// Parameters passed to [`import_queue`].
+pub struct ImportQueueParams<'a, Block: BlockT, BI, Client, CIDP, SelectChain, Spawn> {
+	/// The SASSAFRAS link that is created by [`block_import`].
+	pub link: SassafrasLink<Block>,
+	/// The block import that should be wrapped.
+	pub block_import: BI,
+	/// Optional justification import.
+	pub justification_import: Option<BoxJustificationImport<Block>>,
+	/// The client to interact with the internals of the node.
+	pub client: Arc<Client>,
+	/// A [`SelectChain`](sp_consensus::SelectChain) implementation.
+	///
+	/// Used to determine the best block that should be used as basis when sending an equivocation
+	/// report.
+	pub select_chain: SelectChain,
+	/// Used to crate the inherent data providers.
+	///
+	/// These inherent data providers are then used to create the inherent data that is
+	/// passed to the `check_inherents` runtime call.
+	pub create_inherent_data_providers: CIDP,
+	/// Spawner for spawning futures.
+	pub spawner: &'a Spawn,
+	/// Registry for prometheus metrics.
+	pub registry: Option<&'a Registry>,
+	/// Optional telemetry handle to report telemetry events.
+	pub telemetry: Option<TelemetryHandle>,
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when sending equivocation reports.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+}
+
+/// Start an import queue for the SASSAFRAS consensus algorithm.
+///
+/// The block import object provided must be the `SassafrasBlockImport` or a wrapper
+/// of it, otherwise crucial import logic will be omitted.
+pub fn import_queue<Block: BlockT, Client, SelectChain, BI, CIDP, Spawn>(
+	ImportQueueParams {
+		link,
+		block_import,
+		justification_import,
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		spawner,
+		registry,
+		telemetry,
+		offchain_tx_pool_factory,
+	}: ImportQueueParams<'_, Block, BI, Client, CIDP, SelectChain, Spawn>,
+) -> ClientResult<DefaultImportQueue<Block>>
+where
+	BI: BlockImport<Block, Error = ConsensusError> + Send + Sync + 'static,
+	Client: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: BlockBuilderApi<Block> + SassafrasApi<Block> + ApiExt<Block>,
+	SelectChain: sp_consensus::SelectChain<Block> + 'static,
+	CIDP: CreateInherentDataProviders<Block, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
+	Spawn: SpawnEssentialNamed,
+{
+	let verifier = SassafrasVerifier::new(
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		link.config,
+		link.epoch_changes,
+		telemetry,
+		offchain_tx_pool_factory,
+	);
+
+	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+}
+
+/// Reverts protocol aux data to at most the last finalized block.
+/// In particular, epoch-changes and block weights announced after the revert
+/// point are removed.
+pub fn revert<Block, Client, Backend>(
+	client: Arc<Client>,
+	backend: Arc<Backend>,
+	blocks: NumberFor<Block>,
+) -> ClientResult<()>
+where
+	Block: BlockT,
+	Client: AuxStore + HeaderMetadata<Block, Error = sp_blockchain::Error> + HeaderBackend<Block>,
+	Backend: BackendT<Block>,
+{
+	let best_number = client.info().best_number;
+	let finalized = client.info().finalized_number;
+
+	let revertible = blocks.min(best_number - finalized);
+	if revertible == Zero::zero() {
+		return Ok(())
+	}
+
+	let revert_up_to_number = best_number - revertible;
+	let revert_up_to_hash = client.hash(revert_up_to_number)?.ok_or(ClientError::Backend(
+		format!("Unexpected hash lookup failure for block number: {}", revert_up_to_number),
+	))?;
+
+	// Revert epoch changes tree.
+
+	let epoch_changes = aux_schema::load_epoch_changes::<Block, Client>(&*client)?;
+	let mut epoch_changes = epoch_changes.shared_data();
+
+	if revert_up_to_number == Zero::zero() {
+		// Special case, no epoch changes data were present on genesis.
+		*epoch_changes = EpochChangesFor::<Block, Epoch>::default();
+	} else {
+		epoch_changes.revert(descendent_query(&*client), revert_up_to_hash, revert_up_to_number);
+	}
+
+	// Remove block weights added after the revert point.
+
+	let mut weight_keys = HashSet::with_capacity(revertible.saturated_into());
+
+	let leaves = backend.blockchain().leaves()?.into_iter().filter(|&leaf| {
+		sp_blockchain::tree_route(&*client, revert_up_to_hash, leaf)
+			.map(|route| route.retracted().is_empty())
+			.unwrap_or_default()
+	});
+
+	for leaf in leaves {
+		let mut hash = leaf;
+		loop {
+			let meta = client.header_metadata(hash)?;
+			if meta.number <= revert_up_to_number ||
+				!weight_keys.insert(aux_schema::block_weight_key(hash))
+			{
+				// We've reached the revert point or an already processed branch, stop here.
+				break
+			}
+			hash = meta.parent;
+		}
+	}
+
+	let weight_keys: Vec<_> = weight_keys.iter().map(|val| val.as_slice()).collect();
+
+	// Write epoch changes and remove weights in one shot.
+	aux_schema::write_epoch_changes::<Block, _, _>(&epoch_changes, |values| {
+		client.insert_aux(values, weight_keys.iter())
+	})
+}
@@ -33,6 +33,8 @@ pub mod offchain;
 pub mod bench;
 
 mod children;
+#[cfg(feature = "rocksdb")]
+mod migration;
 mod parity_db;
 mod pinned_blocks_cache;
 mod record_stats_state;
@@ -99,6 +101,8 @@ pub use sc_state_db::PruningMode;
 pub use sp_database::Database;
 
 pub use bench::BenchmarkingState;
+#[cfg(feature = "rocksdb")]
+pub use migration::{migrate_database, MigrationError};
 
 const CACHE_HEADERS: usize = 8;
 
@@ -2166,6 +2170,32 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		}
 	}
 
+	fn import_state_nodes(
+		&self,
+		mut nodes: PrefixedMemoryDB<HashingFor<Block>>,
+	) -> ClientResult<()> {
+		// The nodes would be mixed with the state of the blocks already imported.
+		if let Some((_, number)) = self.blockchain.info().finalized_state {
+			if !number.is_zero() {
+				return Err(sp_blockchain::Error::Backend(format!(
+					"Can't import trie nodes, the database already holds the state of block #{}",
+					number,
+				)))
+			}
+		}
+
+		let mut transaction = Transaction::new();
+		for (mut key, (val, rc)) in nodes.drain() {
+			self.storage.db.sanitize_key(&mut key);
+			// Every write adds a reference in databases with reference counting.
+			for _ in 0..rc {
+				transaction.set_from_vec(columns::STATE, &key, val.to_vec());
+			}
+		}
+		self.storage.db.commit(transaction)?;
+		Ok(())
+	}
+
 	fn finalize_block(
 		&self,
 		hash: Block::Hash,
@@ -0,0 +1,1218 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Migration of a database to another backend.
+
+use std::{
+	cell::RefCell,
+	collections::{BTreeSet, HashMap, HashSet},
+	fmt, io,
+	path::Path,
+};
+
+use crate::{
+	columns,
+	utils::{meta_keys, DatabaseType, NUM_COLUMNS},
+	DatabaseSource, DbExtrinsic, DbHash, DB_HASH_LEN,
+};
+use codec::{Decode, DecodeAll, Encode};
+use kvdb::DBTransaction;
+use kvdb_rocksdb::{Database, DatabaseConfig};
+use sc_state_db::{ChangeSet, MetaDb};
+use sp_core::storage::{well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX, ChildInfo};
+use sp_runtime::traits::{Block as BlockT, Hash, HashingFor, Header as HeaderT, NumberFor, Zero};
+use sp_trie::DBValue;
+use trie_db::{
+	node::{Node, NodeHandle, Value},
+	NibbleSlice, NodeCodec as _,
+};
+
+/// Size of the values copied before the progress is committed.
+const BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+/// Steps of a migration to RocksDB, named after the main column they copy.
+const ROCKSDB_STEPS: [u32; 3] = [columns::KEY_LOOKUP, columns::TRANSACTION, columns::STATE];
+
+/// Keys of the meta column that don't belong to a block.
+const META_KEYS: [&[u8]; 8] = [
+	meta_keys::TYPE,
+	meta_keys::BEST_BLOCK,
+	meta_keys::FINALIZED_BLOCK,
+	meta_keys::FINALIZED_STATE,
+	meta_keys::BLOCK_GAP,
+	meta_keys::BLOCK_GAP_VERSION,
+	meta_keys::GENESIS_HASH,
+	meta_keys::LEAF_PREFIX,
+];
+
+/// Database migration errors.
+#[derive(Debug)]
+pub enum MigrationError {
+	/// Migration between the given database backends is not supported.
+	Unsupported(&'static str),
+	/// The target database already exists and isn't the result of an interrupted migration.
+	TargetNotEmpty,
+	/// The source database couldn't be upgraded to the current version.
+	Upgrade(String),
+	/// The state database journals couldn't be converted.
+	StateDb(String),
+	/// A value of the target database doesn't match the source database.
+	Mismatch {
+		/// Column of the value.
+		column: u32,
+		/// Key of the value in the source database.
+		key: Vec<u8>,
+	},
+	/// A value referenced by the source database is missing from it.
+	Missing {
+		/// Column of the value.
+		column: u32,
+		/// Key of the value in the source database.
+		key: Vec<u8>,
+	},
+	/// Invalid data in one of the databases.
+	Decoding(codec::Error),
+	/// Error of the source database.
+	Io(io::Error),
+	/// Error of the target database.
+	ParityDb(parity_db::Error),
+}
+
+impl From<io::Error> for MigrationError {
+	fn from(err: io::Error) -> Self {
+		MigrationError::Io(err)
+	}
+}
+
+impl From<parity_db::Error> for MigrationError {
+	fn from(err: parity_db::Error) -> Self {
+		MigrationError::ParityDb(err)
+	}
+}
+
+impl From<codec::Error> for MigrationError {
+	fn from(err: codec::Error) -> Self {
+		MigrationError::Decoding(err)
+	}
+}
+
+impl<E: fmt::Debug> From<sc_state_db::Error<E>> for MigrationError {
+	fn from(err: sc_state_db::Error<E>) -> Self {
+		MigrationError::StateDb(format!("{:?}", err))
+	}
+}
+
+impl fmt::Display for MigrationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MigrationError::Unsupported(reason) => write!(f, "Unsupported migration: {}", reason),
+			MigrationError::TargetNotEmpty => write!(f, "Target database already exists"),
+			MigrationError::Upgrade(err) => write!(f, "Source database upgrade failed: {}", err),
+			MigrationError::StateDb(err) => write!(f, "State journal migration failed: {}", err),
+			MigrationError::Mismatch { column, key } => write!(
+				f,
+				"Value of key {} in column {} doesn't match the source database",
+				sp_core::hexdisplay::HexDisplay::from(key),
+				column,
+			),
+			MigrationError::Missing { column, key } => write!(
+				f,
+				"Key {} is missing from column {} of the source database",
+				sp_core::hexdisplay::HexDisplay::from(key),
+				column,
+			),
+			MigrationError::Decoding(err) => write!(f, "Invalid database data: {}", err),
+			MigrationError::Io(err) => write!(f, "Source database error: {}", err),
+			MigrationError::ParityDb(err) => write!(f, "Target database error: {}", err),
+		}
+	}
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Progress of a migration, kept in the meta column of the target database until it completes.
+#[derive(Encode, Decode)]
+struct Progress {
+	/// Column being copied, `NUM_COLUMNS` once all columns are copied.
+	column: u32,
+	/// Last key copied from the column, or the index of the last block copied when migrating to
+	/// RocksDB.
+	last_key: Option<Vec<u8>>,
+}
+
+/// Read access to the state meta column of the source database.
+struct StateMetaDb<'a>(&'a Database);
+
+impl sc_state_db::MetaDb for StateMetaDb<'_> {
+	type Error = io::Error;
+
+	fn get_meta(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.0.get(columns::STATE_META, key)
+	}
+}
+
+/// Copy the database at `source` into a new database at `target`, from RocksDB to ParityDB or
+/// from ParityDB to RocksDB.
+///
+/// Columns are copied one by one and checked against the source afterwards. The progress is
+/// committed together with the copied values, so an interrupted migration continues where it
+/// stopped when run again with the same target. The source database is only read, apart from
+/// the version upgrade applied when opening a RocksDB database. Once the state is copied, the
+/// state database journals are converted to the reference counting done by the target database.
+///
+/// ParityDB doesn't keep the keys of the auxiliary and offchain columns, so they can't be copied
+/// to RocksDB: moving a database from ParityDB to RocksDB is refused unless `discard_aux_data`
+/// is set, in which case these columns are left empty.
+pub fn migrate_database<Block: BlockT>(
+	source: &DatabaseSource,
+	target: &DatabaseSource,
+	discard_aux_data: bool,
+) -> Result<(), MigrationError> {
+	match (source, target) {
+		(
+			DatabaseSource::RocksDb { path: source, .. },
+			DatabaseSource::ParityDb { path: target },
+		) => migrate_to_paritydb::<Block>(source, target),
+		(
+			DatabaseSource::ParityDb { path: source },
+			DatabaseSource::RocksDb { path: target, .. },
+		) if discard_aux_data => migrate_to_rocksdb::<Block>(source, target),
+		(DatabaseSource::ParityDb { .. }, DatabaseSource::RocksDb { .. }) =>
+			Err(MigrationError::Unsupported(
+				"ParityDB doesn't store the keys of the auxiliary and offchain columns, they are \
+				lost when migrating to RocksDB",
+			)),
+		_ => Err(MigrationError::Unsupported(
+			"only RocksDB and ParityDB databases can be migrated to each other",
+		)),
+	}
+}
+
+fn migrate_to_paritydb<Block: BlockT>(
+	source_path: &Path,
+	target_path: &Path,
+) -> Result<(), MigrationError> {
+	crate::upgrade::upgrade_db::<Block>(source_path, DatabaseType::Full)
+		.map_err(|e| MigrationError::Upgrade(e.to_string()))?;
+	let mut config = DatabaseConfig::with_columns(NUM_COLUMNS);
+	config.create_if_missing = false;
+	let source = Database::open(&config, source_path)?;
+	let target =
+		parity_db::Db::open_or_create(&crate::parity_db::options(target_path, DatabaseType::Full))?;
+
+	let mut progress = match target.get(columns::META as u8, meta_keys::MIGRATION)? {
+		Some(progress) => Progress::decode(&mut progress.as_slice())?,
+		None if target.get(columns::META as u8, meta_keys::TYPE)?.is_some() =>
+			return Err(MigrationError::TargetNotEmpty),
+		None => Progress { column: 0, last_key: None },
+	};
+
+	while progress.column < NUM_COLUMNS {
+		let column = progress.column;
+		log::info!("Copying column {} of {}.", column + 1, NUM_COLUMNS);
+		copy_column(&source, &target, &mut progress)?;
+		let entries = verify_column(&source, &target, column)?;
+		log::info!("Copied and verified {} entries of column {}.", entries, column + 1);
+
+		progress = Progress { column: column + 1, last_key: None };
+		target.commit([(
+			columns::META as u8,
+			meta_keys::MIGRATION.to_vec(),
+			Some(progress.encode()),
+		)])?;
+	}
+
+	log::info!("Converting state journals.");
+	let read_error = RefCell::new(None);
+	let state_meta = sc_state_db::migrate_to_ref_counting::<Block::Hash, _>(
+		&StateMetaDb(&source),
+		|key| state_key(key).to_vec(),
+		|key| {
+			source
+				.get(columns::STATE, key)
+				.map(|value| value.is_some())
+				.unwrap_or_else(|e| {
+					read_error.borrow_mut().get_or_insert(e);
+					false
+				})
+		},
+	)
+	.map_err(|e| MigrationError::StateDb(format!("{:?}", e)))?;
+	if let Some(err) = read_error.into_inner() {
+		return Err(err.into())
+	}
+
+	target.commit(
+		state_meta
+			.meta
+			.inserted
+			.into_iter()
+			.map(|(key, value)| (columns::STATE_META as u8, key, Some(value)))
+			.chain(std::iter::once((columns::META as u8, meta_keys::MIGRATION.to_vec(), None))),
+	)?;
+	log::info!("Database migrated to {}.", target_path.display());
+	Ok(())
+}
+
+/// Copy the column of `progress` that hasn't been copied yet, committing the progress along
+/// with the values.
+fn copy_column(
+	source: &Database,
+	target: &parity_db::Db,
+	progress: &mut Progress,
+) -> Result<(), MigrationError> {
+	let column = progress.column;
+	let mut batch = Vec::new();
+	let mut batch_bytes = 0;
+
+	for entry in source.iter(column) {
+		let (key, value) = entry?;
+		if progress.last_key.as_ref().map_or(false, |last| &key[..] <= last.as_slice()) {
+			continue
+		}
+
+		let Some(references) = references(source, column, &key)? else { continue };
+		let target_key =
+			if column == columns::STATE { state_key(&key).to_vec() } else { key.to_vec() };
+		batch_bytes += value.len() * references as usize;
+		for _ in 1..references {
+			batch.push((column as u8, target_key.clone(), Some(value.clone())));
+		}
+		batch.push((column as u8, target_key, Some(value)));
+		progress.last_key = Some(key.to_vec());
+
+		if batch_bytes >= BATCH_BYTES {
+			commit_batch(target, &mut batch, progress)?;
+			batch_bytes = 0;
+		}
+	}
+
+	commit_batch(target, &mut batch, progress)
+}
+
+fn commit_batch(
+	target: &parity_db::Db,
+	batch: &mut Vec<(u8, Vec<u8>, Option<Vec<u8>>)>,
+	progress: &Progress,
+) -> Result<(), MigrationError> {
+	batch.push((columns::META as u8, meta_keys::MIGRATION.to_vec(), Some(progress.encode())));
+	target.commit(batch.drain(..))?;
+	Ok(())
+}
+
+/// Check that every value of the column is in the target database, returns the number of
+/// values.
+fn verify_column(
+	source: &Database,
+	target: &parity_db::Db,
+	column: u32,
+) -> Result<u64, MigrationError> {
+	let mut entries = 0;
+	for entry in source.iter(column) {
+		let (key, value) = entry?;
+		if column == columns::TRANSACTION && is_counter_key(&key) {
+			continue
+		}
+
+		let target_key = if column == columns::STATE { state_key(&key) } else { &key[..] };
+		if target.get(column as u8, target_key)?.as_ref() != Some(&value) {
+			return Err(MigrationError::Mismatch { column, key: key.to_vec() })
+		}
+		entries += 1;
+	}
+	Ok(entries)
+}
+
+/// Number of times a value is inserted into the target database, `None` if it isn't copied.
+///
+/// RocksDB counts the references of transactions under the key with a zero byte appended,
+/// ParityDB counts one reference per insertion of the value. Every node of the state is
+/// referenced once per prefixed key.
+fn references(source: &Database, column: u32, key: &[u8]) -> Result<Option<u32>, MigrationError> {
+	if column != columns::TRANSACTION {
+		return Ok(Some(1))
+	}
+	if is_counter_key(key) {
+		return Ok(None)
+	}
+
+	let mut counter_key = key.to_vec();
+	counter_key.push(0);
+	match source.get(column, &counter_key)? {
+		Some(counter) => Ok(Some(u32::decode(&mut counter.as_slice())?)),
+		None => Ok(Some(1)),
+	}
+}
+
+fn is_counter_key(key: &[u8]) -> bool {
+	key.len() == DB_HASH_LEN + 1 && key.last() == Some(&0)
+}
+
+/// Key of a state node in ParityDB, which doesn't use the prefix.
+fn state_key(key: &[u8]) -> &[u8] {
+	&key[key.len().saturating_sub(DB_HASH_LEN)..]
+}
+
+/// Copy the ParityDB database at `source` into a new RocksDB database at `target`.
+///
+/// ParityDB only keeps the hashes of the keys of most columns, so the keys are rebuilt from the
+/// blocks found through the canonical chain, the leaves and the children of each block. RocksDB
+/// stores state nodes under their path in the trie, so the state is copied by walking the tries
+/// of the states kept by the source database. The auxiliary and offchain columns are left empty.
+fn migrate_to_rocksdb<Block: BlockT>(
+	source_path: &Path,
+	target_path: &Path,
+) -> Result<(), MigrationError> {
+	let source = Source::<Block>::open(source_path)?;
+	let db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), target_path)?;
+
+	let mut progress = match db.get(columns::META, meta_keys::MIGRATION)? {
+		Some(progress) => Progress::decode(&mut progress.as_slice())?,
+		None if db.get(columns::META, meta_keys::TYPE)?.is_some() =>
+			return Err(MigrationError::TargetNotEmpty),
+		None => Progress { column: ROCKSDB_STEPS[0], last_key: None },
+	};
+	crate::upgrade::update_version(target_path)?;
+	let mut target =
+		Target { db: &db, batch: DBTransaction::new(), bytes: 0, keys: HashSet::new() };
+
+	let blocks = source.blocks()?;
+	log::info!("Found {} blocks.", blocks.len());
+	while let Some(step) = ROCKSDB_STEPS.iter().position(|column| *column == progress.column) {
+		match progress.column {
+			columns::KEY_LOOKUP => {
+				log::info!("Copying blocks.");
+				copy_blocks(&source, &mut target, &blocks, &mut progress)?;
+				let entries = verify_blocks(&source, &target, &blocks)?;
+				log::info!("Copied and verified {} entries of {} blocks.", entries, blocks.len());
+			},
+			columns::TRANSACTION => {
+				log::info!("Copying indexed transactions.");
+				let references = transaction_references(&source, &blocks)?;
+				copy_transactions(&source, &mut target, &references, &progress)?;
+				let entries = verify_transactions(&source, &target, &references)?;
+				log::info!("Copied and verified {} transactions.", entries);
+			},
+			_ => {
+				log::info!("Copying states.");
+				let roots = copy_state(&source, &mut target, &blocks, &mut progress)?;
+				verify_state(&target, &roots)?;
+				log::info!("Copied and verified {} states.", roots.len());
+			},
+		}
+
+		let column = ROCKSDB_STEPS.get(step + 1).copied().unwrap_or(NUM_COLUMNS);
+		progress = Progress { column, last_key: None };
+		target.commit(&progress)?;
+	}
+
+	log::info!("Converting state journals.");
+	let state_meta = sc_state_db::migrate_from_ref_counting::<Block::Hash, _, MigrationError>(
+		&source,
+		|hash, inserted| {
+			source.overlay.borrow_mut().extend(inserted);
+			source.block_changes(hash)
+		},
+	)?;
+
+	let mut batch = DBTransaction::new();
+	for (key, value) in state_meta.meta.inserted {
+		batch.put_vec(columns::STATE_META, &key, value);
+	}
+	for key in META_KEYS {
+		if let Some(value) = source.get(columns::META, key)? {
+			batch.put_vec(columns::META, key, value);
+		}
+	}
+	batch.delete(columns::META, meta_keys::MIGRATION);
+	db.write(batch)?;
+	log::info!("Database migrated to {}.", target_path.display());
+	Ok(())
+}
+
+/// Copy the values of the blocks that haven't been copied yet, committing the progress along
+/// with the values.
+fn copy_blocks<Block: BlockT>(
+	source: &Source<Block>,
+	target: &mut Target,
+	blocks: &[(u32, Block::Hash)],
+	progress: &mut Progress,
+) -> Result<(), MigrationError> {
+	let start = match progress.last_key {
+		Some(ref index) => u64::decode(&mut index.as_slice())? as usize + 1,
+		None => 0,
+	};
+	for (index, (number, hash)) in blocks.iter().enumerate().skip(start) {
+		for (column, key) in block_keys(source, *number, hash)? {
+			if let Some(value) = source.get(column, &key)? {
+				target.put(column, key, value);
+			}
+		}
+		progress.last_key = Some((index as u64).encode());
+		target.commit_if_full(progress)?;
+	}
+	target.commit(progress)
+}
+
+/// Check that every value of the blocks is in the target database, returns the number of values.
+fn verify_blocks<Block: BlockT>(
+	source: &Source<Block>,
+	target: &Target,
+	blocks: &[(u32, Block::Hash)],
+) -> Result<u64, MigrationError> {
+	let mut entries = 0;
+	for (number, hash) in blocks {
+		for (column, key) in block_keys(source, *number, hash)? {
+			let value = source.get(column, &key)?;
+			if target.db.get(column, &key)? != value {
+				return Err(MigrationError::Mismatch { column, key })
+			}
+			entries += value.is_some() as u64;
+		}
+	}
+	Ok(entries)
+}
+
+/// Keys of the values of a block: its lookup keys, the values stored under its lookup key and the
+/// list of its children.
+fn block_keys<Block: BlockT>(
+	source: &Source<Block>,
+	number: u32,
+	hash: &Block::Hash,
+) -> Result<Vec<(u32, Vec<u8>)>, MigrationError> {
+	let number_key = number.to_be_bytes().to_vec();
+	let lookup_key = [&number_key[..], hash.as_ref()].concat();
+	let mut keys = vec![(columns::KEY_LOOKUP, hash.as_ref().to_vec())];
+	if source.get(columns::KEY_LOOKUP, &number_key)?.as_ref() == Some(&lookup_key) {
+		keys.push((columns::KEY_LOOKUP, number_key));
+	}
+	for column in [columns::HEADER, columns::BODY, columns::BODY_INDEX, columns::JUSTIFICATIONS] {
+		keys.push((column, lookup_key.clone()));
+	}
+	keys.push((columns::META, children_key(hash)));
+	Ok(keys)
+}
+
+/// Number of references of each indexed transaction: one per block body that indexes it.
+fn transaction_references<Block: BlockT>(
+	source: &Source<Block>,
+	blocks: &[(u32, Block::Hash)],
+) -> Result<HashMap<DbHash, u32>, MigrationError> {
+	let mut references = HashMap::new();
+	for (number, hash) in blocks {
+		let lookup_key = [&number.to_be_bytes()[..], hash.as_ref()].concat();
+		let Some(index) = source.get(columns::BODY_INDEX, &lookup_key)? else { continue };
+		for extrinsic in Vec::<DbExtrinsic<Block>>::decode(&mut index.as_slice())? {
+			if let DbExtrinsic::Indexed { hash, .. } = extrinsic {
+				*references.entry(hash).or_default() += 1;
+			}
+		}
+	}
+	Ok(references)
+}
+
+/// Copy the indexed transactions along with the counter of their references, RocksDB keeps it
+/// under the key of the transaction with a zero byte appended.
+fn copy_transactions<Block: BlockT>(
+	source: &Source<Block>,
+	target: &mut Target,
+	references: &HashMap<DbHash, u32>,
+	progress: &Progress,
+) -> Result<(), MigrationError> {
+	for (hash, count) in references {
+		let Some(value) = source.get(columns::TRANSACTION, hash.as_ref())? else { continue };
+		target.put(columns::TRANSACTION, hash.as_ref().to_vec(), value);
+		target.put(columns::TRANSACTION, counter_key(hash), count.to_le_bytes().to_vec());
+		target.commit_if_full(progress)?;
+	}
+	target.commit(progress)
+}
+
+/// Check that every indexed transaction is in the target database with the right number of
+/// references, returns the number of transactions.
+fn verify_transactions<Block: BlockT>(
+	source: &Source<Block>,
+	target: &Target,
+	references: &HashMap<DbHash, u32>,
+) -> Result<u64, MigrationError> {
+	let mut entries = 0;
+	for (hash, count) in references {
+		let Some(value) = source.get(columns::TRANSACTION, hash.as_ref())? else { continue };
+		if target.db.get(columns::TRANSACTION, hash.as_ref())? != Some(value) ||
+			target.db.get(columns::TRANSACTION, &counter_key(hash))? !=
+				Some(count.to_le_bytes().to_vec())
+		{
+			return Err(MigrationError::Mismatch {
+				column: columns::TRANSACTION,
+				key: hash.as_ref().to_vec(),
+			})
+		}
+		entries += 1;
+	}
+	Ok(entries)
+}
+
+/// Copy the nodes of the states of the blocks that haven't been copied yet, returns the roots of
+/// the states copied by this run.
+///
+/// States that were pruned or that belong to non-canonical blocks are not in the state column and
+/// are skipped. A node is only written once all the nodes below it are, so that a node found in
+/// the target database is the root of a complete trie which doesn't need to be walked again.
+fn copy_state<Block: BlockT>(
+	source: &Source<Block>,
+	target: &mut Target,
+	blocks: &[(u32, Block::Hash)],
+	progress: &mut Progress,
+) -> Result<Vec<NodeRef<Block::Hash>>, MigrationError> {
+	let start = match progress.last_key {
+		Some(ref index) => u64::decode(&mut index.as_slice())? as usize + 1,
+		None => 0,
+	};
+	let mut roots = Vec::new();
+	for (index, (_, hash)) in blocks.iter().enumerate().skip(start) {
+		let Some(header) = source.try_header(hash)? else { continue };
+		let root = NodeRef::root(*header.state_root());
+		if source.is_empty(&root) || source.get(columns::STATE, root.hash.as_ref())?.is_none() {
+			continue
+		}
+
+		if !target.contains_node(&root)? {
+			let mut stack = vec![source.expand(NodeRef::root(root.hash))?];
+			while let Some((_, _, children)) = stack.last_mut() {
+				match children.pop() {
+					Some(child) =>
+						if !source.is_empty(&child) && !target.contains_node(&child)? {
+							stack.push(source.expand(child)?);
+						},
+					None => {
+						let (node, value, _) = stack.pop().expect("stack is not empty; qed");
+						target.put(columns::STATE, node.key(), value);
+						target.commit_if_full(progress)?;
+					},
+				}
+			}
+		}
+		roots.push(root);
+		progress.last_key = Some((index as u64).encode());
+		target.commit_if_full(progress)?;
+	}
+	target.commit(progress)?;
+	Ok(roots)
+}
+
+/// Check that the root of every copied state is in the target database. Nodes are written after
+/// the nodes below them, so the tries are complete.
+fn verify_state<Hash: AsRef<[u8]>>(
+	target: &Target,
+	roots: &[NodeRef<Hash>],
+) -> Result<(), MigrationError> {
+	for root in roots {
+		if !target.contains_node(root)? {
+			return Err(MigrationError::Mismatch { column: columns::STATE, key: root.key() })
+		}
+	}
+	Ok(())
+}
+
+fn children_key<Hash: Encode>(hash: &Hash) -> Vec<u8> {
+	let mut key = meta_keys::CHILDREN_PREFIX.to_vec();
+	hash.using_encoded(|hash| key.extend(hash));
+	key
+}
+
+fn counter_key(hash: &DbHash) -> Vec<u8> {
+	let mut key = hash.as_ref().to_vec();
+	key.push(0);
+	key
+}
+
+/// The RocksDB database a migration writes to.
+struct Target<'a> {
+	db: &'a Database,
+	batch: DBTransaction,
+	/// Size of the values in `batch`.
+	bytes: usize,
+	/// Keys of the state nodes in `batch`.
+	keys: HashSet<Vec<u8>>,
+}
+
+impl Target<'_> {
+	fn put(&mut self, column: u32, key: Vec<u8>, value: Vec<u8>) {
+		self.bytes += value.len();
+		if column == columns::STATE {
+			self.keys.insert(key.clone());
+		}
+		self.batch.put_vec(column, &key, value);
+	}
+
+	fn contains_node<Hash: AsRef<[u8]>>(
+		&self,
+		node: &NodeRef<Hash>,
+	) -> Result<bool, MigrationError> {
+		let key = node.key();
+		Ok(self.keys.contains(&key) || self.db.get(columns::STATE, &key)?.is_some())
+	}
+
+	fn commit_if_full(&mut self, progress: &Progress) -> Result<(), MigrationError> {
+		if self.bytes >= BATCH_BYTES {
+			self.commit(progress)?;
+		}
+		Ok(())
+	}
+
+	fn commit(&mut self, progress: &Progress) -> Result<(), MigrationError> {
+		let mut batch = std::mem::take(&mut self.batch);
+		batch.put_vec(columns::META, meta_keys::MIGRATION, progress.encode());
+		self.db.write(batch)?;
+		self.bytes = 0;
+		self.keys.clear();
+		Ok(())
+	}
+}
+
+/// A state node, along with its position in the state.
+struct NodeRef<Hash> {
+	hash: Hash,
+	/// Keyspace of the child trie of the node, `None` in the top trie.
+	keyspace: Option<Vec<u8>>,
+	/// Nibbles of the path of the node in its trie.
+	path: Vec<u8>,
+	/// Whether the node is a value stored apart from the trie node of its key.
+	value: bool,
+}
+
+impl<Hash: AsRef<[u8]>> NodeRef<Hash> {
+	fn root(hash: Hash) -> Self {
+		NodeRef { hash, keyspace: None, path: Vec::new(), value: false }
+	}
+
+	/// Key of the node in a database without reference counting, which prefixes the hash with the
+	/// keyspace and the path of the node, see `sp_trie::KeySpacedDB` and `sp_trie::prefixed_key`.
+	fn key(&self) -> Vec<u8> {
+		let mut key = self.keyspace.clone().unwrap_or_default();
+		key.extend(
+			self.path
+				.chunks(2)
+				.map(|nibbles| nibbles[0] << 4 | nibbles.get(1).copied().unwrap_or(0)),
+		);
+		key.extend_from_slice(self.hash.as_ref());
+		key
+	}
+}
+
+/// The ParityDB database a migration reads from.
+struct Source<Block: BlockT> {
+	db: parity_db::Db,
+	/// Nodes of the non-canonical blocks, which are only in the state journal.
+	overlay: RefCell<HashMap<Vec<u8>, DBValue>>,
+	/// Hash of the empty trie node, which isn't stored.
+	empty_node: Block::Hash,
+}
+
+impl<Block: BlockT> MetaDb for Source<Block> {
+	type Error = parity_db::Error;
+
+	fn get_meta(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.db.get(columns::STATE_META as u8, key)
+	}
+}
+
+impl<Block: BlockT> Source<Block> {
+	fn open(path: &Path) -> Result<Self, MigrationError> {
+		let db = parity_db::Db::open(&crate::parity_db::options(path, DatabaseType::Full))?;
+		let empty_node =
+			HashingFor::<Block>::hash(sp_trie::NodeCodec::<HashingFor<Block>>::empty_node());
+		Ok(Source { db, overlay: Default::default(), empty_node })
+	}
+
+	fn get(&self, column: u32, key: &[u8]) -> Result<Option<Vec<u8>>, MigrationError> {
+		Ok(self.db.get(column as u8, key)?)
+	}
+
+	fn try_header(&self, hash: &Block::Hash) -> Result<Option<Block::Header>, MigrationError> {
+		let Some(lookup_key) = self.get(columns::KEY_LOOKUP, hash.as_ref())? else {
+			return Ok(None)
+		};
+		match self.get(columns::HEADER, &lookup_key)? {
+			Some(header) => Ok(Some(Block::Header::decode(&mut header.as_slice())?)),
+			None => Ok(None),
+		}
+	}
+
+	fn header(&self, hash: &Block::Hash) -> Result<Block::Header, MigrationError> {
+		self.try_header(hash)?.ok_or_else(|| MigrationError::Missing {
+			column: columns::HEADER,
+			key: hash.as_ref().to_vec(),
+		})
+	}
+
+	/// Numbers and hashes of the blocks of the database, ordered by number.
+	fn blocks(&self) -> Result<Vec<(u32, Block::Hash)>, MigrationError> {
+		let Some(best) = self.get(columns::META, meta_keys::BEST_BLOCK)? else {
+			return Ok(Vec::new())
+		};
+		let mut pending = Vec::new();
+		for number in 0..=lookup_number(&best)? {
+			if let Some(lookup_key) = self.get(columns::KEY_LOOKUP, &number.to_be_bytes())? {
+				pending.push(Block::Hash::decode(&mut lookup_key.get(4..).unwrap_or_default())?);
+			}
+		}
+		if let Some(leaves) = self.get(columns::META, meta_keys::LEAF_PREFIX)? {
+			let leaves =
+				Vec::<(NumberFor<Block>, Vec<Block::Hash>)>::decode(&mut leaves.as_slice())?;
+			pending.extend(leaves.into_iter().flat_map(|(_, hashes)| hashes));
+		}
+
+		let mut blocks = BTreeSet::new();
+		while let Some(hash) = pending.pop() {
+			let Some(lookup_key) = self.get(columns::KEY_LOOKUP, hash.as_ref())? else { continue };
+			if !blocks.insert((lookup_number(&lookup_key)?, hash)) {
+				continue
+			}
+			if let Some(header) = self.get(columns::HEADER, &lookup_key)? {
+				pending.push(*Block::Header::decode(&mut header.as_slice())?.parent_hash());
+			}
+			if let Some(children) = self.get(columns::META, &children_key(&hash))? {
+				pending.extend(Vec::<Block::Hash>::decode(&mut children.as_slice())?);
+			}
+		}
+		Ok(blocks.into_iter().collect())
+	}
+
+	/// Nodes inserted and deleted by the block with the given hash, keyed by their position.
+	fn block_changes(&self, hash: &Block::Hash) -> Result<ChangeSet<Vec<u8>>, MigrationError> {
+		let header = self.header(hash)?;
+		let root = *header.state_root();
+		let parent = match header.number().is_zero() {
+			true => None,
+			false => Some(*self.header(header.parent_hash())?.state_root()),
+		};
+
+		let mut changes = ChangeSet::default();
+		if parent != Some(root) {
+			self.new_nodes(root, parent, |key, value| changes.inserted.push((key, value)))?;
+			if let Some(parent) = parent {
+				self.new_nodes(parent, Some(root), |key, _| changes.deleted.push(key))?;
+			}
+		}
+		Ok(changes)
+	}
+
+	/// Call `f` with the key and the value of the nodes of the trie of `root` that are not at the
+	/// same position in the trie of `other`.
+	fn new_nodes(
+		&self,
+		root: Block::Hash,
+		other: Option<Block::Hash>,
+		mut f: impl FnMut(Vec<u8>, DBValue),
+	) -> Result<(), MigrationError> {
+		let mut pending = vec![NodeRef::root(root)];
+		while let Some(node) = pending.pop() {
+			if self.is_empty(&node) {
+				continue
+			}
+			if let Some(other) = other {
+				if self.locate(other, &node)? {
+					continue
+				}
+			}
+			let (node, value, children) = self.expand(node)?;
+			pending.extend(children);
+			f(node.key(), value);
+		}
+		Ok(())
+	}
+
+	/// Whether the state with the given root has `node` at the same position.
+	fn locate(
+		&self,
+		root: Block::Hash,
+		node: &NodeRef<Block::Hash>,
+	) -> Result<bool, MigrationError> {
+		// the keyspace of a default child trie is its storage key.
+		let storage_key = node
+			.keyspace
+			.as_ref()
+			.map(|keyspace| nibbles(&[DEFAULT_CHILD_STORAGE_KEY_PREFIX, keyspace].concat()));
+		let mut current = NodeRef::root(root);
+		loop {
+			if current.keyspace == node.keyspace &&
+				current.path == node.path &&
+				current.value == node.value
+			{
+				return Ok(current.hash == node.hash)
+			}
+			if current.value || self.is_empty(&current) {
+				return Ok(false)
+			}
+
+			let in_trie = current.keyspace == node.keyspace;
+			let (_, _, children) = self.expand(current)?;
+			let next = children.into_iter().find(|child| match storage_key {
+				// the node itself or a trie node on its path.
+				_ if in_trie =>
+					child.keyspace == node.keyspace &&
+						(child.path == node.path && child.value == node.value ||
+							!child.value && node.path.starts_with(&child.path)),
+				// the root of the child trie of the node or a top trie node on its path.
+				Some(ref storage_key) =>
+					child.keyspace == node.keyspace ||
+						child.keyspace.is_none() &&
+							!child.value && storage_key.starts_with(&child.path),
+				None => false,
+			});
+			match next {
+				Some(next) => current = next,
+				None => return Ok(false),
+			}
+		}
+	}
+
+	fn is_empty(&self, node: &NodeRef<Block::Hash>) -> bool {
+		!node.value && node.hash == self.empty_node
+	}
+
+	/// Get the value of `node` along with the nodes it references.
+	fn expand(
+		&self,
+		node: NodeRef<Block::Hash>,
+	) -> Result<(NodeRef<Block::Hash>, DBValue, Vec<NodeRef<Block::Hash>>), MigrationError> {
+		let value = self.node(&node.hash)?;
+		let mut children = Vec::new();
+		if !node.value {
+			self.push_children(&node.keyspace, node.path.clone(), &value, &mut children)?;
+		}
+		Ok((node, value, children))
+	}
+
+	fn node(&self, hash: &Block::Hash) -> Result<DBValue, MigrationError> {
+		if let Some(value) = self.overlay.borrow().get(hash.as_ref()) {
+			return Ok(value.clone())
+		}
+		self.get(columns::STATE, hash.as_ref())?.ok_or_else(|| MigrationError::Missing {
+			column: columns::STATE,
+			key: hash.as_ref().to_vec(),
+		})
+	}
+
+	/// Add the nodes referenced by the encoded trie node at `path` to `children`, including the
+	/// nodes of inline children and the roots of child tries.
+	fn push_children(
+		&self,
+		keyspace: &Option<Vec<u8>>,
+		mut path: Vec<u8>,
+		encoded: &[u8],
+		children: &mut Vec<NodeRef<Block::Hash>>,
+	) -> Result<(), MigrationError> {
+		let node = sp_trie::NodeCodec::<HashingFor<Block>>::decode(encoded)
+			.map_err(|_| codec::Error::from("Invalid trie node"))?;
+		let (branches, value) = match node {
+			Node::Empty => return Ok(()),
+			Node::Leaf(partial, value) => {
+				extend_path(&mut path, &partial);
+				(None, Some(value))
+			},
+			Node::Branch(branches, value) => (Some(branches), value),
+			Node::NibbledBranch(partial, branches, value) => {
+				extend_path(&mut path, &partial);
+				(Some(branches), value)
+			},
+			Node::Extension(..) =>
+				return Err(codec::Error::from("Unexpected extension node").into()),
+		};
+
+		if let Some(value) = value {
+			self.push_value(keyspace, path.clone(), value, children)?;
+		}
+		for (index, branch) in branches.into_iter().flatten().enumerate() {
+			let mut branch_path = path.clone();
+			branch_path.push(index as u8);
+			match branch {
+				Some(NodeHandle::Hash(hash)) => children.push(NodeRef {
+					hash: Block::Hash::decode_all(&mut &hash[..])?,
+					keyspace: keyspace.clone(),
+					path: branch_path,
+					value: false,
+				}),
+				Some(NodeHandle::Inline(encoded)) =>
+					self.push_children(keyspace, branch_path, encoded, children)?,
+				None => (),
+			}
+		}
+		Ok(())
+	}
+
+	fn push_value(
+		&self,
+		keyspace: &Option<Vec<u8>>,
+		path: Vec<u8>,
+		value: Value,
+		children: &mut Vec<NodeRef<Block::Hash>>,
+	) -> Result<(), MigrationError> {
+		let child_keyspace = match keyspace {
+			None => child_trie_keyspace(&path),
+			Some(_) => None,
+		};
+		let value = match value {
+			Value::Inline(value) => child_keyspace.is_some().then(|| value.to_vec()),
+			Value::Node(hash) => {
+				let hash = Block::Hash::decode_all(&mut &hash[..])?;
+				children.push(NodeRef { hash, keyspace: keyspace.clone(), path, value: true });
+				child_keyspace.is_some().then(|| self.node(&hash)).transpose()?
+			},
+		};
+		if let (Some(keyspace), Some(root)) = (child_keyspace, value) {
+			children.push(NodeRef {
+				hash: Block::Hash::decode_all(&mut root.as_slice())?,
+				keyspace: Some(keyspace),
+				path: Vec::new(),
+				value: false,
+			});
+		}
+		Ok(())
+	}
+}
+
+/// Number of the block of a lookup key.
+fn lookup_number(lookup_key: &[u8]) -> Result<u32, MigrationError> {
+	let number = lookup_key.get(..4).ok_or_else(|| codec::Error::from("Invalid lookup key"))?;
+	Ok(u32::from_be_bytes(number.try_into().expect("slice has 4 bytes; qed")))
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+	key.iter().flat_map(|byte| [byte >> 4, byte & 0xf]).collect()
+}
+
+fn extend_path(path: &mut Vec<u8>, partial: &NibbleSlice) {
+	path.extend((0..partial.len()).map(|index| partial.at(index)));
+}
+
+/// Keyspace of the child trie whose root is stored under the top trie key at `path`, if any.
+fn child_trie_keyspace(path: &[u8]) -> Option<Vec<u8>> {
+	if path.len() % 2 != 0 {
+		return None
+	}
+	let key = path.chunks(2).map(|nibbles| nibbles[0] << 4 | nibbles[1]).collect::<Vec<_>>();
+	let storage_key = key.strip_prefix(DEFAULT_CHILD_STORAGE_KEY_PREFIX)?;
+	Some(ChildInfo::new_default(storage_key).keyspace().to_vec())
+}
+
+#[cfg(all(test, feature = "rocksdb"))]
+mod tests {
+	use super::*;
+	use crate::{
+		tests::{insert_block, insert_header, Block},
+		Backend, BlocksPruning, DatabaseSettings, PruningMode,
+	};
+	use sc_client_api::{
+		backend::{AuxStore, Backend as _, BlockImportOperation as _, NewBlockState},
+		blockchain::Backend as _,
+	};
+	use sp_blockchain::HeaderBackend;
+	use sp_core::{offchain::OffchainStorage, H256};
+	use sp_database::IndexOperation;
+	use sp_runtime::{
+		testing::{Header, MockCallU64, TestXt},
+		StateVersion,
+	};
+	use sp_state_machine::Backend as _;
+	use std::iter;
+
+	fn open_backend(source: &DatabaseSource) -> Backend<Block> {
+		let settings = DatabaseSettings {
+			trie_cache_maximum_size: None,
+			state_pruning: Some(PruningMode::blocks_pruning(2)),
+			source: source.clone(),
+			blocks_pruning: BlocksPruning::KeepAll,
+		};
+		Backend::new(settings, 2).unwrap()
+	}
+
+	/// Insert a genesis block indexing a transaction, returns the hash of the block, the
+	/// transaction and its hash.
+	fn insert_genesis(backend: &Backend<Block>) -> (H256, Vec<u8>, H256) {
+		let extrinsic = TestXt::<MockCallU64, ()>::new_transaction(0.into(), ());
+		let transaction = extrinsic.encode()[1..].to_vec();
+		let transaction_hash = <HashingFor<Block> as Hash>::hash(&transaction);
+		let index = vec![IndexOperation::Insert {
+			extrinsic: 0,
+			hash: transaction_hash.as_ref().to_vec(),
+			size: transaction.len() as u32,
+		}];
+		let genesis = insert_block(
+			backend,
+			0,
+			Default::default(),
+			None,
+			Default::default(),
+			vec![extrinsic],
+			Some(index),
+		)
+		.unwrap();
+		(genesis, transaction, transaction_hash)
+	}
+
+	/// Insert a block setting `value` under a key of the top trie and a key of a child trie.
+	fn insert_child_block(
+		backend: &Backend<Block>,
+		number: u64,
+		parent_hash: H256,
+		value: &[u8],
+	) -> H256 {
+		let child_info = ChildInfo::new_default(b"child");
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, parent_hash).unwrap();
+		let (state_root, overlay) = op.old_state.full_storage_root(
+			iter::once((&b"top"[..], Some(value))),
+			iter::once((&child_info, iter::once((&b"key"[..], Some(value))))),
+			StateVersion::V1,
+		);
+		op.update_db_storage(overlay).unwrap();
+		let header = Header {
+			number,
+			parent_hash,
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		op.set_block_data(header.clone(), Some(Vec::new()), None, None, NewBlockState::Best)
+			.unwrap();
+		backend.commit_operation(op).unwrap();
+		header.hash()
+	}
+
+	#[test]
+	fn rocksdb_migrates_to_paritydb() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let rocksdb = DatabaseSource::RocksDb { path: dir.path().join("db"), cache_size: 16 };
+		let paritydb = DatabaseSource::ParityDb { path: dir.path().join("paritydb") };
+
+		let backend = open_backend(&rocksdb);
+		let (genesis, transaction, transaction_hash) = insert_genesis(&backend);
+		let mut hashes = vec![genesis];
+		for number in 1..8 {
+			let parent = *hashes.last().unwrap();
+			hashes.push(insert_header(&backend, number, parent, None, Default::default()));
+		}
+		backend.finalize_block(hashes[4], None).unwrap();
+		backend.insert_aux(&[(&b"aux"[..], &b"value"[..])], &[]).unwrap();
+		backend.offchain_storage().unwrap().set(b"prefix", b"key", b"value");
+		drop(backend);
+
+		migrate_database::<Block>(&rocksdb, &paritydb, false).unwrap();
+		assert!(matches!(
+			migrate_database::<Block>(&rocksdb, &paritydb, false),
+			Err(MigrationError::TargetNotEmpty)
+		));
+
+		let backend = open_backend(&paritydb);
+		let blockchain = backend.blockchain();
+		assert_eq!(blockchain.info().best_hash, hashes[7]);
+		assert_eq!(blockchain.info().finalized_hash, hashes[4]);
+		assert_eq!(
+			blockchain.indexed_transaction(transaction_hash).unwrap(),
+			Some(transaction.clone())
+		);
+		assert_eq!(backend.get_aux(b"aux").unwrap(), Some(b"value".to_vec()));
+		assert_eq!(
+			backend.offchain_storage().unwrap().get(b"prefix", b"key"),
+			Some(b"value".to_vec())
+		);
+		for number in 5..8 {
+			let parent = hashes[number - 1];
+			let state = backend.state_at(hashes[number]).unwrap();
+			assert_eq!(state.storage(parent.as_ref()).unwrap(), Some(parent.as_ref().to_vec()));
+		}
+
+		// Pruning continues with the converted journals.
+		for number in 8..12 {
+			let parent = *hashes.last().unwrap();
+			hashes.push(insert_header(&backend, number, parent, None, Default::default()));
+		}
+		backend.finalize_block(hashes[9], None).unwrap();
+		let state = backend.state_at(hashes[11]).unwrap();
+		assert_eq!(state.storage(hashes[10].as_ref()).unwrap(), Some(hashes[10].as_ref().to_vec()));
+	}
+
+	#[test]
+	fn paritydb_migrates_back_to_rocksdb() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let rocksdb = DatabaseSource::RocksDb { path: dir.path().join("db"), cache_size: 16 };
+		let paritydb = DatabaseSource::ParityDb { path: dir.path().join("paritydb") };
+		let migrated =
+			DatabaseSource::RocksDb { path: dir.path().join("migrated"), cache_size: 16 };
+		let child_info = ChildInfo::new_default(b"child");
+
+		let backend = open_backend(&rocksdb);
+		let (genesis, transaction, transaction_hash) = insert_genesis(&backend);
+		let mut hashes = vec![genesis];
+		for number in 1..7 {
+			let parent = *hashes.last().unwrap();
+			hashes.push(match number {
+				3 => insert_child_block(&backend, number, parent, &[1; 64]),
+				_ => insert_header(&backend, number, parent, None, Default::default()),
+			});
+		}
+		let fork = insert_child_block(&backend, 7, hashes[6], &[2; 64]);
+		hashes.push(insert_header(&backend, 7, hashes[6], None, Default::default()));
+		backend.finalize_block(hashes[4], None).unwrap();
+		backend.insert_aux(&[(&b"aux"[..], &b"value"[..])], &[]).unwrap();
+		backend.offchain_storage().unwrap().set(b"prefix", b"key", b"value");
+		drop(backend);
+		migrate_database::<Block>(&rocksdb, &paritydb, false).unwrap();
+
+		assert!(matches!(
+			migrate_database::<Block>(&paritydb, &migrated, false),
+			Err(MigrationError::Unsupported(_))
+		));
+		migrate_database::<Block>(&paritydb, &migrated, true).unwrap();
+		assert!(matches!(
+			migrate_database::<Block>(&paritydb, &migrated, true),
+			Err(MigrationError::TargetNotEmpty)
+		));
+
+		let backend = open_backend(&migrated);
+		let blockchain = backend.blockchain();
+		assert_eq!(blockchain.info().best_hash, hashes[7]);
+		assert_eq!(blockchain.info().finalized_hash, hashes[4]);
+		assert_eq!(blockchain.children(hashes[6]).unwrap().len(), 2);
+		assert_eq!(blockchain.indexed_transaction(transaction_hash).unwrap(), Some(transaction));
+		assert_eq!(backend.get_aux(b"aux").unwrap(), None);
+		for number in 5..8 {
+			let parent = hashes[number - 1];
+			let state = backend.state_at(hashes[number]).unwrap();
+			assert_eq!(state.storage(parent.as_ref()).unwrap(), Some(parent.as_ref().to_vec()));
+			assert_eq!(state.storage(b"top").unwrap(), Some(vec![1; 64]));
+			assert_eq!(state.child_storage(&child_info, b"key").unwrap(), Some(vec![1; 64]));
+		}
+		let state = backend.state_at(fork).unwrap();
+		assert_eq!(state.storage(b"top").unwrap(), Some(vec![2; 64]));
+		assert_eq!(state.child_storage(&child_info, b"key").unwrap(), Some(vec![2; 64]));
+
+		// Canonicalization and pruning continue with the converted journals.
+		for number in 8..12 {
+			let parent = *hashes.last().unwrap();
+			hashes.push(insert_header(&backend, number, parent, None, Default::default()));
+		}
+		backend.finalize_block(hashes[9], None).unwrap();
+		let state = backend.state_at(hashes[11]).unwrap();
+		assert_eq!(state.storage(hashes[10].as_ref()).unwrap(), Some(hashes[10].as_ref().to_vec()));
+		assert_eq!(state.child_storage(&child_info, b"key").unwrap(), Some(vec![1; 64]));
+		assert!(backend.state_at(fork).is_err());
+	}
+}
@@ -356,6 +356,7 @@ mod tests {
 	use quickcheck::{Arbitrary, Gen, QuickCheck};
 	use sc_network::{
 		config::MultiaddrWithPeerId,
+		peer_store::{BanTarget, BannedPeer},
 		service::traits::{Direction, MessageSink, NotificationEvent},
 		Event, NetworkBlock, NetworkEventStream, NetworkPeers, NotificationService, Roles,
 	};
@@ -369,6 +370,7 @@ mod tests {
 	use std::{
 		collections::HashSet,
 		sync::{Arc, Mutex},
+		time::Duration,
 	};
 	use substrate_test_runtime_client::runtime::Block;
 
@@ -452,6 +454,18 @@ mod tests {
 		async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
 			unimplemented!();
 		}
+
+		fn ban_peer(&self, _target: BanTarget, _duration: Duration) {
+			unimplemented!();
+		}
+
+		fn unban_peer(&self, _target: &BanTarget) -> bool {
+			unimplemented!();
+		}
+
+		fn banned_peers(&self) -> Vec<BannedPeer> {
+			unimplemented!();
+		}
 	}
 
 	impl NetworkEventStream for TestNetwork {
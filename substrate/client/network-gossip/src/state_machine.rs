@@ -544,8 +544,11 @@ mod tests {
 	use super::*;
 	use futures::prelude::*;
 	use sc_network::{
-		config::MultiaddrWithPeerId, event::Event, service::traits::NotificationEvent, MessageSink,
-		NetworkBlock, NetworkEventStream, NetworkPeers, ReputationChange,
+		config::MultiaddrWithPeerId,
+		event::Event,
+		peer_store::{BanTarget, BannedPeer},
+		service::traits::NotificationEvent,
+		MessageSink, NetworkBlock, NetworkEventStream, NetworkPeers, ReputationChange,
 	};
 	use sc_network_types::multiaddr::Multiaddr;
 	use sp_runtime::{
@@ -556,6 +559,7 @@ mod tests {
 		collections::HashSet,
 		pin::Pin,
 		sync::{Arc, Mutex},
+		time::Duration,
 	};
 
 	type Block = RawBlock<TestXt<MockCallU64, ()>>;
@@ -684,6 +688,18 @@ mod tests {
 		async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
 			unimplemented!();
 		}
+
+		fn ban_peer(&self, _target: BanTarget, _duration: Duration) {
+			unimplemented!();
+		}
+
+		fn unban_peer(&self, _target: &BanTarget) -> bool {
+			unimplemented!();
+		}
+
+		fn banned_peers(&self) -> Vec<BannedPeer> {
+			unimplemented!();
+		}
 	}
 
 	impl NetworkEventStream for NoOpNetwork {
@@ -0,0 +1,544 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bandwidth limiting.
+//!
+//! Limits are enforced with token buckets which refill at the configured rate and hold at most
+//! one second worth of traffic. Transfers are charged after the fact: a transfer is never split
+//! or rejected, instead the bucket goes into debt and the next transfer going through the same
+//! bucket is delayed until the debt has been paid off.
+//!
+//! The node-wide limit is applied to all traffic of the node. With the `libp2p` backend it is
+//! enforced on the raw connections built by [`crate::transport`] and thus also covers protocol
+//! overhead. `litep2p` doesn't allow wrapping its transports, so there the node-wide limit is
+//! shared by all notification and request-response protocols instead.
+//!
+//! Per-protocol limits apply to the payloads of a single notification or request-response
+//! protocol.
+
+use crate::{error::Error, service::traits::MessageSink, types::ProtocolName};
+
+use futures::{
+	io::{AsyncRead, AsyncWrite},
+	ready, FutureExt,
+};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use prometheus_endpoint::{
+	self as prometheus, Counter, CounterVec, Opts, PrometheusError, Registry, U64,
+};
+
+use std::{
+	collections::HashMap,
+	io,
+	num::NonZeroU64,
+	pin::Pin,
+	sync::{Arc, OnceLock},
+	task::{Context, Poll},
+	time::{Duration, Instant},
+};
+
+/// Label used in metrics for the node-wide limits.
+const NODE_LABEL: &str = "node";
+
+/// Bandwidth limits of the node.
+///
+/// All rates are in bytes per second. `None` means unlimited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BandwidthLimits {
+	/// Node-wide limit of inbound traffic.
+	pub inbound: Option<NonZeroU64>,
+
+	/// Node-wide limit of outbound traffic.
+	pub outbound: Option<NonZeroU64>,
+
+	/// Limits of individual notification and request-response protocols, by main protocol name.
+	pub protocols: HashMap<ProtocolName, ProtocolBandwidthLimit>,
+}
+
+impl BandwidthLimits {
+	/// Returns `true` if no limit is configured.
+	pub fn is_unlimited(&self) -> bool {
+		self.inbound.is_none() &&
+			self.outbound.is_none() &&
+			self.protocols
+				.values()
+				.all(|limit| limit.inbound.is_none() && limit.outbound.is_none())
+	}
+}
+
+/// Bandwidth limit of a single protocol.
+///
+/// All rates are in bytes per second. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolBandwidthLimit {
+	/// Limit of received payloads.
+	pub inbound: Option<NonZeroU64>,
+
+	/// Limit of sent payloads.
+	pub outbound: Option<NonZeroU64>,
+}
+
+/// Prometheus metrics of the bandwidth limiters.
+struct Metrics {
+	throttled_total: CounterVec<U64>,
+	throttled_bytes_total: CounterVec<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			throttled_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_bandwidth_throttled_total",
+						"Total number of transfers delayed by a bandwidth limit",
+					),
+					&["protocol", "direction"],
+				)?,
+				registry,
+			)?,
+			throttled_bytes_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_bandwidth_throttled_bytes_total",
+						"Total number of bytes in transfers delayed by a bandwidth limit",
+					),
+					&["protocol", "direction"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// State of a token bucket.
+#[derive(Debug)]
+struct Bucket {
+	/// Available tokens (bytes). Negative if the bucket is in debt.
+	tokens: f64,
+
+	/// Last time the bucket was refilled.
+	refilled_at: Instant,
+}
+
+/// Token bucket limiting the rate of a single traffic direction.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+	/// Rate in bytes per second, which is also the capacity of the bucket.
+	rate: f64,
+
+	/// Bucket state.
+	bucket: Mutex<Bucket>,
+
+	/// Counters for throttled transfers and their bytes.
+	metrics: Option<(Counter<U64>, Counter<U64>)>,
+}
+
+impl RateLimiter {
+	/// Create new [`RateLimiter`] with a full bucket.
+	fn new(rate: NonZeroU64, metrics: Option<(Counter<U64>, Counter<U64>)>) -> Self {
+		let rate = rate.get() as f64;
+
+		Self {
+			rate,
+			bucket: Mutex::new(Bucket { tokens: rate, refilled_at: Instant::now() }),
+			metrics,
+		}
+	}
+
+	/// Charge `bytes` to the bucket and return how long the next transfer must wait for.
+	pub(crate) fn reserve(&self, bytes: usize) -> Duration {
+		self.reserve_at(bytes, Instant::now())
+	}
+
+	fn reserve_at(&self, bytes: usize, now: Instant) -> Duration {
+		let mut bucket = self.bucket.lock();
+
+		let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+		bucket.refilled_at = std::cmp::max(bucket.refilled_at, now);
+		bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.rate) - bytes as f64;
+
+		if bucket.tokens >= 0.0 {
+			return Duration::ZERO
+		}
+
+		if let Some((throttled, throttled_bytes)) = &self.metrics {
+			throttled.inc();
+			throttled_bytes.inc_by(bytes as u64);
+		}
+
+		Duration::from_secs_f64(-bucket.tokens / self.rate)
+	}
+}
+
+/// Rate limiters applying to the traffic of a single protocol.
+///
+/// Every transfer is charged to all limiters of its direction and waits for the slowest one.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProtocolLimiters {
+	inbound: Vec<Arc<RateLimiter>>,
+	outbound: Vec<Arc<RateLimiter>>,
+}
+
+impl ProtocolLimiters {
+	/// Returns `true` if no limit applies to the protocol.
+	pub(crate) fn is_empty(&self) -> bool {
+		self.inbound.is_empty() && self.outbound.is_empty()
+	}
+
+	/// Charge `bytes` of inbound traffic and return how long to delay processing it.
+	pub(crate) fn inbound_delay(&self, bytes: usize) -> Duration {
+		Self::delay(&self.inbound, bytes)
+	}
+
+	/// Charge `bytes` of outbound traffic and return how long to delay the transfer.
+	pub(crate) fn outbound_delay(&self, bytes: usize) -> Duration {
+		Self::delay(&self.outbound, bytes)
+	}
+
+	/// Charge `bytes` of inbound traffic and wait until it's allowed to be processed.
+	pub(crate) async fn throttle_inbound(&self, bytes: usize) {
+		wait(self.inbound_delay(bytes)).await
+	}
+
+	/// Charge `bytes` of outbound traffic and wait until it's allowed to be sent.
+	pub(crate) async fn throttle_outbound(&self, bytes: usize) {
+		wait(self.outbound_delay(bytes)).await
+	}
+
+	fn delay(limiters: &[Arc<RateLimiter>], bytes: usize) -> Duration {
+		limiters
+			.iter()
+			.map(|limiter| limiter.reserve(bytes))
+			.max()
+			.unwrap_or(Duration::ZERO)
+	}
+}
+
+/// Wait for `delay` unless it's zero.
+async fn wait(delay: Duration) {
+	if !delay.is_zero() {
+		Delay::new(delay).await
+	}
+}
+
+/// Rate limiters of the node, built by the network backend from [`BandwidthLimits`].
+#[derive(Debug, Default)]
+pub struct BandwidthLimiters {
+	inbound: Option<Arc<RateLimiter>>,
+	outbound: Option<Arc<RateLimiter>>,
+	protocols: HashMap<ProtocolName, ProtocolLimiters>,
+}
+
+impl BandwidthLimiters {
+	/// Create new [`BandwidthLimiters`], registering the throttling counters if `registry` is
+	/// given.
+	pub fn new(
+		limits: &BandwidthLimits,
+		registry: Option<&Registry>,
+	) -> Result<Self, PrometheusError> {
+		if limits.is_unlimited() {
+			return Ok(Self::default())
+		}
+
+		let metrics = registry.map(Metrics::register).transpose()?;
+		let limiter = |label: &str, direction: &str, rate: Option<NonZeroU64>| {
+			rate.map(|rate| {
+				let counters = metrics.as_ref().map(|metrics| {
+					(
+						metrics.throttled_total.with_label_values(&[label, direction]),
+						metrics.throttled_bytes_total.with_label_values(&[label, direction]),
+					)
+				});
+
+				Arc::new(RateLimiter::new(rate, counters))
+			})
+		};
+
+		Ok(Self {
+			inbound: limiter(NODE_LABEL, "in", limits.inbound),
+			outbound: limiter(NODE_LABEL, "out", limits.outbound),
+			protocols: limits
+				.protocols
+				.iter()
+				.map(|(protocol, limit)| {
+					let limiters = ProtocolLimiters {
+						inbound: limiter(protocol, "in", limit.inbound).into_iter().collect(),
+						outbound: limiter(protocol, "out", limit.outbound).into_iter().collect(),
+					};
+
+					(protocol.clone(), limiters)
+				})
+				.collect(),
+		})
+	}
+
+	/// Get the limiters of `protocol`, not including the node-wide limits.
+	pub(crate) fn protocol(&self, protocol: &ProtocolName) -> ProtocolLimiters {
+		self.protocols.get(protocol).cloned().unwrap_or_default()
+	}
+
+	/// Get the limiters of `protocol`, including the node-wide limits.
+	pub(crate) fn protocol_with_node(&self, protocol: &ProtocolName) -> ProtocolLimiters {
+		let mut limiters = self.protocol(protocol);
+		limiters.inbound.extend(self.inbound.clone());
+		limiters.outbound.extend(self.outbound.clone());
+		limiters
+	}
+
+	/// Get the node-wide limiters for wrapping connections.
+	pub(crate) fn node(&self) -> NodeLimiters {
+		NodeLimiters { inbound: self.inbound.clone(), outbound: self.outbound.clone() }
+	}
+}
+
+/// Node-wide rate limiters, applied to raw connections.
+#[derive(Clone, Debug)]
+pub(crate) struct NodeLimiters {
+	inbound: Option<Arc<RateLimiter>>,
+	outbound: Option<Arc<RateLimiter>>,
+}
+
+impl NodeLimiters {
+	/// Wrap `stream` into a [`ThrottledStream`] enforcing the node-wide limits.
+	pub(crate) fn throttle<S>(&self, stream: S) -> ThrottledStream<S> {
+		ThrottledStream {
+			inner: stream,
+			inbound: self.inbound.clone(),
+			outbound: self.outbound.clone(),
+			read_delay: None,
+			write_delay: None,
+		}
+	}
+}
+
+/// Limiters of a notification protocol, shared between the user-facing notification handle and
+/// the network backend which installs them once it has been started.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LimiterSlot(Arc<OnceLock<ProtocolLimiters>>);
+
+impl LimiterSlot {
+	/// Install `limiters`. Only the first call has any effect.
+	pub(crate) fn install(&self, limiters: ProtocolLimiters) {
+		let _ = self.0.set(limiters);
+	}
+
+	/// Get the installed limiters, if any limit applies to the protocol.
+	pub(crate) fn get(&self) -> Option<&ProtocolLimiters> {
+		self.0.get().filter(|limiters| !limiters.is_empty())
+	}
+
+	/// Wrap `sink` so that notifications sent through it are subject to the protocol's limits.
+	pub(crate) fn wrap_sink(&self, sink: Box<dyn MessageSink>) -> Box<dyn MessageSink> {
+		match self.get() {
+			Some(limiters) =>
+				Box::new(ThrottledMessageSink { inner: sink, limiters: limiters.clone() }),
+			None => sink,
+		}
+	}
+}
+
+/// [`MessageSink`] charging sent notifications to the limits of the protocol.
+struct ThrottledMessageSink {
+	inner: Box<dyn MessageSink>,
+	limiters: ProtocolLimiters,
+}
+
+#[async_trait::async_trait]
+impl MessageSink for ThrottledMessageSink {
+	/// Send synchronous `notification` to the peer associated with this [`MessageSink`].
+	///
+	/// Synchronous notifications can't be delayed, they are only charged to the limits.
+	fn send_sync_notification(&self, notification: Vec<u8>) {
+		let _ = self.limiters.outbound_delay(notification.len());
+		self.inner.send_sync_notification(notification)
+	}
+
+	/// Send an asynchronous `notification` to the peer associated with this [`MessageSink`],
+	/// waiting for the protocol's limits first.
+	async fn send_async_notification(&self, notification: Vec<u8>) -> Result<(), Error> {
+		self.limiters.throttle_outbound(notification.len()).await;
+		self.inner.send_async_notification(notification).await
+	}
+}
+
+/// Stream enforcing node-wide bandwidth limits on a connection.
+///
+/// Bytes read or written are charged to the respective limiter and the next read or write is
+/// delayed until the limiter allows it. Delaying reads leaves the data in the socket buffers,
+/// which eventually makes the remote slow down.
+#[pin_project::pin_project]
+pub(crate) struct ThrottledStream<S> {
+	#[pin]
+	inner: S,
+	inbound: Option<Arc<RateLimiter>>,
+	outbound: Option<Arc<RateLimiter>>,
+	read_delay: Option<Delay>,
+	write_delay: Option<Delay>,
+}
+
+/// Wait for a pending delay to expire.
+fn poll_delay(delay: &mut Option<Delay>, cx: &mut Context<'_>) -> Poll<()> {
+	if let Some(pending) = delay {
+		ready!(pending.poll_unpin(cx));
+		*delay = None;
+	}
+
+	Poll::Ready(())
+}
+
+/// Charge `bytes` to `limiter`, arming `delay` if the limiter is in debt.
+fn charge(limiter: &Option<Arc<RateLimiter>>, bytes: usize, delay: &mut Option<Delay>) {
+	if let Some(limiter) = limiter {
+		let wait = limiter.reserve(bytes);
+
+		if !wait.is_zero() {
+			*delay = Some(Delay::new(wait));
+		}
+	}
+}
+
+impl<S: AsyncRead> AsyncRead for ThrottledStream<S> {
+	fn poll_read(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut [u8],
+	) -> Poll<io::Result<usize>> {
+		let this = self.project();
+
+		ready!(poll_delay(this.read_delay, cx));
+		let read = ready!(this.inner.poll_read(cx, buf))?;
+		charge(this.inbound, read, this.read_delay);
+
+		Poll::Ready(Ok(read))
+	}
+}
+
+impl<S: AsyncWrite> AsyncWrite for ThrottledStream<S> {
+	fn poll_write(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		let this = self.project();
+
+		ready!(poll_delay(this.write_delay, cx));
+		let written = ready!(this.inner.poll_write(cx, buf))?;
+		charge(this.outbound, written, this.write_delay);
+
+		Poll::Ready(Ok(written))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		self.project().inner.poll_flush(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		self.project().inner.poll_close(cx)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn limiter(rate: u64) -> RateLimiter {
+		RateLimiter::new(NonZeroU64::new(rate).unwrap(), None)
+	}
+
+	#[test]
+	fn full_bucket_allows_one_second_burst() {
+		let limiter = limiter(1000);
+		let now = limiter.bucket.lock().refilled_at;
+
+		assert_eq!(limiter.reserve_at(600, now), Duration::ZERO);
+		assert_eq!(limiter.reserve_at(400, now), Duration::ZERO);
+		assert_eq!(limiter.reserve_at(500, now), Duration::from_millis(500));
+	}
+
+	#[test]
+	fn debt_is_paid_off_over_time() {
+		let limiter = limiter(1000);
+		let now = limiter.bucket.lock().refilled_at;
+
+		assert_eq!(limiter.reserve_at(3000, now), Duration::from_secs(2));
+		assert_eq!(limiter.reserve_at(0, now + Duration::from_secs(1)), Duration::from_secs(1));
+		assert_eq!(limiter.reserve_at(0, now + Duration::from_secs(2)), Duration::ZERO);
+	}
+
+	#[test]
+	fn idle_time_does_not_grow_burst() {
+		let limiter = limiter(1000);
+		let now = limiter.bucket.lock().refilled_at;
+
+		assert_eq!(limiter.reserve_at(1000, now + Duration::from_secs(60)), Duration::ZERO);
+		assert_eq!(limiter.reserve_at(1000, now + Duration::from_secs(60)), Duration::from_secs(1));
+	}
+
+	#[test]
+	fn protocol_waits_for_slowest_limiter() {
+		let limits = BandwidthLimits {
+			inbound: NonZeroU64::new(1000),
+			outbound: None,
+			protocols: HashMap::from([(
+				ProtocolName::from("/test/1"),
+				ProtocolBandwidthLimit { inbound: NonZeroU64::new(100), outbound: None },
+			)]),
+		};
+		let limiters = BandwidthLimiters::new(&limits, None).unwrap();
+
+		let protocol = limiters.protocol(&"/test/1".into());
+		assert_eq!(protocol.inbound.len(), 1);
+		assert!(protocol.outbound.is_empty());
+
+		let with_node = limiters.protocol_with_node(&"/test/1".into());
+		assert_eq!(with_node.inbound.len(), 2);
+		assert!(with_node.inbound_delay(600) >= Duration::from_secs(4));
+		assert_eq!(with_node.outbound_delay(usize::MAX), Duration::ZERO);
+
+		assert!(limiters.protocol(&"/other/1".into()).is_empty());
+	}
+
+	#[test]
+	fn throttled_transfers_are_counted() {
+		let registry = Registry::new();
+		let limits = BandwidthLimits {
+			protocols: HashMap::from([(
+				ProtocolName::from("/test/1"),
+				ProtocolBandwidthLimit { inbound: None, outbound: NonZeroU64::new(100) },
+			)]),
+			..Default::default()
+		};
+		let limiters = BandwidthLimiters::new(&limits, Some(&registry)).unwrap();
+		let protocol = limiters.protocol(&"/test/1".into());
+
+		assert_eq!(protocol.outbound_delay(100), Duration::ZERO);
+		assert!(!protocol.outbound_delay(50).is_zero());
+
+		let metrics = registry.gather();
+		let value = |name: &str| {
+			metrics
+				.iter()
+				.find(|family| family.get_name() == name)
+				.map(|family| family.get_metric()[0].get_counter().get_value())
+		};
+		assert_eq!(value("substrate_sub_libp2p_bandwidth_throttled_total"), Some(1.0));
+		assert_eq!(value("substrate_sub_libp2p_bandwidth_throttled_bytes_total"), Some(50.0));
+	}
+}
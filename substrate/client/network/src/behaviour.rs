@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+	bandwidth::BandwidthLimiters,
 	discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
 	event::DhtEvent,
 	peer_info,
@@ -183,6 +184,7 @@ impl<B: BlockT> Behaviour<B> {
 		disco_config: DiscoveryConfig,
 		request_response_protocols: Vec<ProtocolConfig>,
 		peer_store_handle: Arc<dyn PeerStoreProvider>,
+		bandwidth_limiters: &BandwidthLimiters,
 		external_addresses: Arc<Mutex<HashSet<Multiaddr>>>,
 		public_addresses: Vec<Multiaddr>,
 		connection_limits: ConnectionLimits,
@@ -199,6 +201,7 @@ impl<B: BlockT> Behaviour<B> {
 			request_responses: request_responses::RequestResponsesBehaviour::new(
 				request_response_protocols.into_iter(),
 				peer_store_handle,
+				bandwidth_limiters,
 			)?,
 			connection_limits: libp2p::connection_limits::Behaviour::new(connection_limits),
 		})
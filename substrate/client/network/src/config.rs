@@ -22,6 +22,7 @@
 //! See the documentation of [`Params`].
 
 pub use crate::{
+	bandwidth::{BandwidthLimits, ProtocolBandwidthLimit},
 	discovery::DEFAULT_KADEMLIA_REPLICATION_FACTOR,
 	peer_store::PeerStoreProvider,
 	protocol::{notification_service, NotificationsSink, ProtocolHandlePair},
@@ -41,7 +42,10 @@ use sc_network_types::{
 	PeerId,
 };
 
-use crate::service::{ensure_addresses_consistent_with_transport, traits::NetworkBackend};
+use crate::{
+	peer_store::PEER_STORE_FILE,
+	service::{ensure_addresses_consistent_with_transport, traits::NetworkBackend},
+};
 use codec::Encode;
 use prometheus_endpoint::Registry;
 use zeroize::Zeroize;
@@ -655,6 +659,9 @@ pub struct NetworkConfiguration {
 
 	/// Networking backend used for P2P communication.
 	pub network_backend: Option<NetworkBackendType>,
+
+	/// Node-wide and per-protocol bandwidth limits.
+	pub bandwidth_limits: BandwidthLimits,
 }
 
 impl NetworkConfiguration {
@@ -688,6 +695,7 @@ impl NetworkConfiguration {
 				.expect("value is a constant; constant is non-zero; qed."),
 			ipfs_server: false,
 			network_backend: None,
+			bandwidth_limits: BandwidthLimits::default(),
 		}
 	}
 
@@ -781,7 +789,9 @@ impl<B: BlockT + 'static, H: ExHashT, N: NetworkBackend<B, H>> FullNetworkConfig
 	/// Create new [`FullNetworkConfiguration`].
 	pub fn new(network_config: &NetworkConfiguration, metrics_registry: Option<Registry>) -> Self {
 		let bootnodes = network_config.boot_nodes.iter().map(|bootnode| bootnode.peer_id).collect();
-		let peer_store = N::peer_store(bootnodes, metrics_registry.clone());
+		let persistence_path =
+			network_config.net_config_path.as_ref().map(|path| path.join(PEER_STORE_FILE));
+		let peer_store = N::peer_store(bootnodes, metrics_registry.clone(), persistence_path);
 		let peer_store_handle = peer_store.handle();
 
 		Self {
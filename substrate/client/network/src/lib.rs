@@ -250,6 +250,7 @@ mod protocol;
 #[cfg(test)]
 mod mock;
 
+pub mod bandwidth;
 pub mod config;
 pub mod discovery;
 pub mod error;
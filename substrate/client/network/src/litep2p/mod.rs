@@ -19,6 +19,7 @@
 //! `NetworkBackend` implementation for `litep2p`.
 
 use crate::{
+	bandwidth::BandwidthLimiters,
 	config::{
 		FullNetworkConfiguration, IncomingRequest, NodeKeyConfig, NotificationHandshake, Params,
 		SetConfig, TransportConfig,
@@ -86,6 +87,7 @@ use std::{
 	fs,
 	future::Future,
 	iter,
+	path::PathBuf,
 	pin::Pin,
 	sync::{
 		atomic::{AtomicUsize, Ordering},
@@ -402,12 +404,22 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			..
 		} = params.network_config;
 
+		// `litep2p` doesn't allow throttling its transports so the node-wide limits are shared by
+		// all notification and request-response protocols instead
+		let bandwidth_limiters = BandwidthLimiters::new(
+			&network_config.bandwidth_limits,
+			params.metrics_registry.as_ref(),
+		)?;
+
 		// initialize notification protocols
 		//
 		// pass the protocol configuration to `Litep2pConfigBuilder` and save the TX channel
 		// to the protocol's `Peerset` together with the protocol name to allow other subsystems
 		// of Polkadot SDK to control connectivity of the notification protocol
 		let block_announce_protocol = params.block_announce_config.protocol_name().clone();
+		params.block_announce_config.set_bandwidth_limiters(
+			bandwidth_limiters.protocol_with_node(&block_announce_protocol),
+		);
 		let mut notif_protocols = HashMap::from_iter([(
 			params.block_announce_config.protocol_name().clone(),
 			params.block_announce_config.handle,
@@ -418,6 +430,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			.into_iter()
 			.fold(config_builder, |config_builder, mut config| {
 				config.config.set_handshake(Roles::from(&params.role).encode());
+				config.set_bandwidth_limiters(
+					bandwidth_limiters.protocol_with_node(&config.protocol_name),
+				);
 				notif_protocols.insert(config.protocol_name, config.handle);
 
 				config_builder.with_notification_protocol(config.config)
@@ -467,6 +482,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 						.expect("receiver exists as it was just added and there are no duplicate protocols; qed"),
 					request_response_senders.clone(),
 					metrics.clone(),
+					bandwidth_limiters.protocol_with_node(&config.protocol_name),
 				);
 
 				executor.run(Box::pin(async move {
@@ -593,8 +609,14 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 	fn peer_store(
 		bootnodes: Vec<sc_network_types::PeerId>,
 		metrics_registry: Option<Registry>,
+		persistence_path: Option<PathBuf>,
 	) -> Self::PeerStore {
-		Peerstore::new(bootnodes, metrics_registry)
+		let peerstore = Peerstore::new(bootnodes, metrics_registry);
+
+		match persistence_path {
+			Some(path) => peerstore.with_persistence(path),
+			None => peerstore,
+		}
 	}
 
 	fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics {
@@ -773,6 +795,18 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 
 							let _ = handle.tx.unbounded_send(PeersetCommand::RemoveReservedPeers { peers });
 						}
+						NetworkServiceCommand::EnforceNetworkBan => {
+							for (peer, context) in &self.peers {
+								let banned = context.endpoints.values().any(|endpoint| {
+									self.peerstore_handle
+										.check_address_ban((*peer).into(), &endpoint.address().clone().into())
+								});
+
+								if banned {
+									log::debug!(target: LOG_TARGET, "{peer:?} is connected through a banned network");
+								}
+							}
+						}
 					}
 				},
 				event = self.discovery.next() => match event {
@@ -1026,9 +1060,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 				},
 				event = self.litep2p.next_event() => match event {
 					Some(Litep2pEvent::ConnectionEstablished { peer, endpoint }) => {
-						let Some(metrics) = &self.metrics else {
-							continue;
-						};
+						if self.peerstore_handle.check_address_ban(peer.into(), &endpoint.address().clone().into()) {
+							log::debug!(target: LOG_TARGET, "{peer:?} connected through a banned address");
+						}
 
 						let direction = match endpoint {
 							Endpoint::Dialer { .. } => "out",
@@ -1037,33 +1071,39 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 								//
 								// Note: For litep2p these are represented by established negotiated connections,
 								// while for libp2p (legacy) these represent not-yet-negotiated connections.
-								metrics.incoming_connections_total.inc();
+								if let Some(metrics) = &self.metrics {
+									metrics.incoming_connections_total.inc();
+								}
 
 								"in"
 							},
 						};
-						metrics.connections_opened_total.with_label_values(&[direction]).inc();
 
-						match self.peers.entry(peer) {
+						let first_connection = match self.peers.entry(peer) {
 							Entry::Vacant(entry) => {
 								entry.insert(ConnectionContext {
 									endpoints: HashMap::from_iter([(endpoint.connection_id(), endpoint)]),
 									num_connections: 1usize,
 								});
-								metrics.distinct_peers_connections_opened_total.inc();
+								true
 							}
 							Entry::Occupied(entry) => {
 								let entry = entry.into_mut();
 								entry.num_connections += 1;
 								entry.endpoints.insert(endpoint.connection_id(), endpoint);
+								false
+							}
+						};
+
+						if let Some(metrics) = &self.metrics {
+							metrics.connections_opened_total.with_label_values(&[direction]).inc();
+
+							if first_connection {
+								metrics.distinct_peers_connections_opened_total.inc();
 							}
 						}
 					}
 					Some(Litep2pEvent::ConnectionClosed { peer, connection_id }) => {
-						let Some(metrics) = &self.metrics else {
-							continue;
-						};
-
 						let Some(context) = self.peers.get_mut(&peer) else {
 							log::debug!(target: LOG_TARGET, "unknown peer disconnected: {peer:?} ({connection_id:?})");
 							continue
@@ -1084,11 +1124,17 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 							}
 						};
 
-						metrics.connections_closed_total.with_label_values(&[direction, "actively-closed"]).inc();
-
-						if context.num_connections == 0 {
+						let last_connection = context.num_connections == 0;
+						if last_connection {
 							self.peers.remove(&peer);
-							metrics.distinct_peers_connections_closed_total.inc();
+						}
+
+						if let Some(metrics) = &self.metrics {
+							metrics.connections_closed_total.with_label_values(&[direction, "actively-closed"]).inc();
+
+							if last_connection {
+								metrics.distinct_peers_connections_closed_total.inc();
+							}
 						}
 					}
 					Some(Litep2pEvent::DialFailure { address, error }) => {
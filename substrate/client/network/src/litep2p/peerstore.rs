@@ -22,7 +22,10 @@
 //! such as their addresses, reputations, supported protocols etc.
 
 use crate::{
-	peer_store::{PeerStoreProvider, ProtocolHandle},
+	peer_store::{
+		BanList, BanTarget, BannedPeer, PeerStoreProvider, PersistedPeerStore, ProtocolHandle,
+		PERSIST_INTERVAL,
+	},
 	service::{metrics::PeerStoreMetrics, traits::PeerStore},
 	ObservedRole, ReputationChange,
 };
@@ -31,12 +34,13 @@ use parking_lot::Mutex;
 use prometheus_endpoint::Registry;
 use wasm_timer::Delay;
 
-use sc_network_types::PeerId;
+use sc_network_types::{multiaddr::Multiaddr, PeerId};
 
 use std::{
 	collections::{HashMap, HashSet},
+	path::PathBuf,
 	sync::Arc,
-	time::{Duration, Instant},
+	time::{Duration, Instant, SystemTime},
 };
 
 /// Logging target for the file.
@@ -120,6 +124,53 @@ pub struct PeerstoreHandleInner {
 	peers: HashMap<PeerId, PeerInfo>,
 	protocols: Vec<Arc<dyn ProtocolHandle>>,
 	metrics: Option<PeerStoreMetrics>,
+	bans: BanList,
+	persistence_path: Option<PathBuf>,
+}
+
+impl PeerstoreHandleInner {
+	fn is_banned(&self, peer: &PeerId) -> bool {
+		self.bans.is_peer_banned(peer) ||
+			self.peers.get(peer).map_or(false, |info| info.is_banned())
+	}
+
+	/// Restore the reputations and bans persisted at `path` and keep persisting them there.
+	fn restore(&mut self, path: PathBuf) {
+		if let Some(persisted) = PersistedPeerStore::load(&path) {
+			// Decaying for longer than `FORGET_AFTER` drives any reputation value to zero.
+			let seconds_passed = persisted.seconds_since_saved().min(FORGET_AFTER.as_secs());
+
+			for (peer, reputation) in persisted.reputations() {
+				let mut info = PeerInfo { reputation, ..Default::default() };
+				info.decay_reputation(seconds_passed);
+				if info.reputation != 0 {
+					self.peers.entry(peer).or_default().reputation = info.reputation;
+				}
+			}
+			self.bans = persisted.ban_list();
+
+			log::debug!(
+				target: LOG_TARGET,
+				"restored {} reputations and {} bans from {}",
+				self.peers.values().filter(|info| info.reputation != 0).count(),
+				self.bans.banned().len(),
+				path.display(),
+			);
+		}
+
+		self.persistence_path = Some(path);
+	}
+
+	fn persist(&mut self) {
+		let Some(path) = &self.persistence_path else { return };
+
+		PersistedPeerStore::new(
+			self.peers.iter().map(|(peer, info)| (*peer, info.reputation)),
+			&self.bans,
+		)
+		.save(path);
+		self.bans.mark_persisted();
+	}
 }
 
 #[derive(Debug, Clone, Default)]
@@ -132,7 +183,13 @@ impl PeerstoreHandle {
 		protocols: Vec<Arc<dyn ProtocolHandle>>,
 		metrics: Option<PeerStoreMetrics>,
 	) -> Self {
-		Self(Arc::new(Mutex::new(PeerstoreHandleInner { peers, protocols, metrics })))
+		Self(Arc::new(Mutex::new(PeerstoreHandleInner {
+			peers,
+			protocols,
+			metrics,
+			bans: BanList::default(),
+			persistence_path: None,
+		})))
 	}
 
 	/// Add known peer to [`Peerstore`].
@@ -153,17 +210,21 @@ impl PeerstoreHandle {
 		}
 
 		let mut lock = self.0.lock();
+		let lock = &mut *lock;
 
 		// Drive reputation values towards 0.
 		lock.peers
 			.iter_mut()
 			.for_each(|(_, info)| info.decay_reputation(seconds_passed));
 
+		lock.bans.prune();
+
 		// Retain only entries with non-zero reputation values or not expired ones.
 		let now = Instant::now();
 		let mut num_banned_peers = 0;
-		lock.peers.retain(|_, info| {
-			if info.is_banned() {
+		let bans = &lock.bans;
+		lock.peers.retain(|peer, info| {
+			if info.is_banned() || bans.is_peer_banned(peer) {
 				num_banned_peers += 1;
 			}
 			info.reputation != 0 || info.last_updated + FORGET_AFTER > now
@@ -178,7 +239,7 @@ impl PeerstoreHandle {
 
 impl PeerStoreProvider for PeerstoreHandle {
 	fn is_banned(&self, peer: &PeerId) -> bool {
-		self.0.lock().peers.get(peer).map_or(false, |info| info.is_banned())
+		self.0.lock().is_banned(peer)
 	}
 
 	/// Register a protocol handle to disconnect peers whose reputation drops below the threshold.
@@ -275,7 +336,8 @@ impl PeerStoreProvider for PeerstoreHandle {
 			.peers
 			.iter()
 			.filter_map(|(peer, info)| {
-				(!ignored.contains(&peer) && !info.is_banned()).then_some((*peer, info.reputation))
+				(!ignored.contains(&peer) && !handle.is_banned(peer))
+					.then_some((*peer, info.reputation))
 			})
 			.collect::<Vec<(PeerId, _)>>();
 		candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
@@ -290,6 +352,43 @@ impl PeerStoreProvider for PeerstoreHandle {
 	fn add_known_peer(&self, peer: PeerId) {
 		self.0.lock().peers.entry(peer).or_default().last_updated = Instant::now();
 	}
+
+	/// Ban `target` until `until`, disconnecting the matching peers from all protocols.
+	fn ban(&self, target: BanTarget, until: SystemTime) {
+		let mut lock = self.0.lock();
+		log::info!(target: LOG_TARGET, "banning {target} until {until:?}");
+		lock.bans.insert(target, until);
+
+		if let BanTarget::Peer(peer) = target {
+			lock.protocols.iter().for_each(|handle| handle.disconnect_peer(peer));
+		}
+	}
+
+	/// Lift a ban.
+	fn unban(&self, target: &BanTarget) -> bool {
+		let removed = self.0.lock().bans.remove(target);
+		if removed {
+			log::info!(target: LOG_TARGET, "ban of {target} lifted");
+		}
+		removed
+	}
+
+	/// Get the bans currently in effect.
+	fn banned(&self) -> Vec<BannedPeer> {
+		self.0.lock().bans.banned()
+	}
+
+	/// Check whether `address` the peer is connected through belongs to a banned network.
+	fn check_address_ban(&self, peer: PeerId, address: &Multiaddr) -> bool {
+		let mut lock = self.0.lock();
+		if !lock.bans.check_address(peer, address) {
+			return false
+		}
+
+		log::debug!(target: LOG_TARGET, "{peer:?} connected through banned {address}");
+		lock.protocols.iter().for_each(|handle| handle.disconnect_peer(peer));
+		true
+	}
 }
 
 /// `Peerstore` handle for testing.
@@ -329,6 +428,12 @@ impl Peerstore {
 		Self { peerstore_handle }
 	}
 
+	/// Persist reputations and bans to `path`, restoring the ones persisted there before.
+	pub fn with_persistence(self, path: PathBuf) -> Self {
+		self.peerstore_handle.0.lock().restore(path);
+		self
+	}
+
 	/// Get mutable reference to the underlying [`PeerstoreHandle`].
 	pub fn handle(&mut self) -> &mut PeerstoreHandle {
 		&mut self.peerstore_handle
@@ -343,6 +448,7 @@ impl Peerstore {
 	async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_persisted = started;
 
 		loop {
 			let now = Instant::now();
@@ -356,11 +462,26 @@ impl Peerstore {
 			};
 
 			self.peerstore_handle.progress_time(seconds_passed);
+
+			{
+				let mut lock = self.peerstore_handle.0.lock();
+				if lock.bans.is_changed() || now - latest_persisted >= PERSIST_INTERVAL {
+					lock.persist();
+					latest_persisted = now;
+				}
+			}
+
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
 }
 
+impl Drop for Peerstore {
+	fn drop(&mut self) {
+		self.peerstore_handle.0.lock().persist();
+	}
+}
+
 #[async_trait::async_trait]
 impl PeerStore for Peerstore {
 	/// Get handle to `PeerStore`.
@@ -376,7 +497,8 @@ impl PeerStore for Peerstore {
 
 #[cfg(test)]
 mod tests {
-	use super::{PeerInfo, PeerStoreProvider, Peerstore};
+	use super::{BanTarget, PeerInfo, PeerStoreProvider, Peerstore};
+	use std::time::{Duration, SystemTime};
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -478,4 +600,29 @@ mod tests {
 		assert_eq!(metrics.num_discovered.get(), 3);
 		assert_eq!(metrics.num_banned_peers.get(), 2);
 	}
+
+	#[test]
+	fn manual_bans_are_enforced() {
+		let peer_a = sc_network_types::PeerId::random();
+		let peer_b = sc_network_types::PeerId::random();
+
+		let mut peerstore = Peerstore::new(vec![peer_a, peer_b], None);
+		let handle = peerstore.handle();
+		let until = SystemTime::now() + Duration::from_secs(60);
+
+		handle.ban(BanTarget::Peer(peer_a), until);
+		assert!(handle.is_banned(&peer_a));
+		assert_eq!(handle.outgoing_candidates(2, Default::default()), vec![peer_b]);
+
+		let network: BanTarget = "192.0.2.0/24".parse().unwrap();
+		handle.ban(network, until);
+		assert!(handle.check_address_ban(peer_b, &"/ip4/192.0.2.7/tcp/30333".parse().unwrap()));
+		assert!(handle.is_banned(&peer_b));
+		assert_eq!(handle.banned().len(), 2);
+
+		assert!(handle.unban(&BanTarget::Peer(peer_a)));
+		assert!(handle.unban(&network));
+		assert!(!handle.is_banned(&peer_a));
+		assert!(!handle.is_banned(&peer_b));
+	}
 }
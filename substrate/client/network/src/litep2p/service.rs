@@ -25,7 +25,7 @@ use crate::{
 		request_response::OutboundRequest,
 	},
 	network_state::NetworkState,
-	peer_store::PeerStoreProvider,
+	peer_store::{BanTarget, BannedPeer, PeerStoreProvider, MAX_BAN_DURATION},
 	service::out_events,
 	Event, IfDisconnected, NetworkDHTProvider, NetworkEventStream, NetworkPeers, NetworkRequest,
 	NetworkSigner, NetworkStateInfo, NetworkStatus, NetworkStatusProvider, OutboundFailure,
@@ -55,7 +55,7 @@ use sc_utils::mpsc::TracingUnboundedSender;
 use std::{
 	collections::{HashMap, HashSet},
 	sync::{atomic::Ordering, Arc},
-	time::Instant,
+	time::{Duration, Instant, SystemTime},
 };
 
 /// Logging target for the file.
@@ -178,6 +178,9 @@ pub enum NetworkServiceCommand {
 		/// Sender for the events.
 		tx: out_events::Sender,
 	},
+
+	/// Disconnect the peers connected through an address of a newly banned network.
+	EnforceNetworkBan,
 }
 
 /// `NetworkService` implementation for `litep2p`.
@@ -497,6 +500,23 @@ impl NetworkPeers for Litep2pNetworkService {
 		// the channel can only be closed if `Peerset` no longer exists
 		rx.await.map_err(|_| ())
 	}
+
+	fn ban_peer(&self, target: BanTarget, duration: Duration) {
+		self.peer_store_handle
+			.ban(target, SystemTime::now() + duration.min(MAX_BAN_DURATION));
+
+		if let BanTarget::Network(_) = target {
+			let _ = self.cmd_tx.unbounded_send(NetworkServiceCommand::EnforceNetworkBan);
+		}
+	}
+
+	fn unban_peer(&self, target: &BanTarget) -> bool {
+		self.peer_store_handle.unban(target)
+	}
+
+	fn banned_peers(&self) -> Vec<BannedPeer> {
+		self.peer_store_handle.banned()
+	}
 }
 
 impl NetworkEventStream for Litep2pNetworkService {
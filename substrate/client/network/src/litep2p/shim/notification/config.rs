@@ -19,6 +19,7 @@
 //! `litep2p` notification protocol configuration.
 
 use crate::{
+	bandwidth::{LimiterSlot, ProtocolLimiters},
 	config::{MultiaddrWithPeerId, NonReservedPeerMode, NotificationHandshake, SetConfig},
 	litep2p::shim::notification::{
 		peerset::{Peerset, PeersetCommand},
@@ -73,6 +74,9 @@ pub struct NotificationProtocolConfig {
 
 	/// Handle for controlling the notification protocol.
 	pub handle: ProtocolControlHandle,
+
+	/// Bandwidth limits of the protocol, shared with [`NotificationProtocol`].
+	limiters: LimiterSlot,
 }
 
 impl NotificationProtocolConfig {
@@ -114,7 +118,14 @@ impl NotificationProtocolConfig {
 		// initialize the actual object implementing `NotificationService` and combine the
 		// `litep2p::NotificationHandle` with `Peerset` to implement a full and independent
 		// notification protocol runner
-		let protocol = NotificationProtocol::new(protocol_name.clone(), handle, peerset, metrics);
+		let limiters = LimiterSlot::default();
+		let protocol = NotificationProtocol::new(
+			protocol_name.clone(),
+			handle,
+			peerset,
+			metrics,
+			limiters.clone(),
+		);
 
 		(
 			Self {
@@ -123,6 +134,7 @@ impl NotificationProtocolConfig {
 				set_config,
 				config,
 				handle: ProtocolControlHandle::new(peerset_tx, connected_peers),
+				limiters,
 			},
 			Box::new(protocol),
 		)
@@ -133,6 +145,11 @@ impl NotificationProtocolConfig {
 		&self.protocol_name
 	}
 
+	/// Set the bandwidth limits enforced by the protocol's [`NotificationProtocol`].
+	pub(crate) fn set_bandwidth_limiters(&self, limiters: ProtocolLimiters) {
+		self.limiters.install(limiters);
+	}
+
 	/// Get reference to `SetConfig`.
 	pub fn set_config(&self) -> &SetConfig {
 		&self.set_config
@@ -20,6 +20,7 @@
 //! with `NotificationService`.
 
 use crate::{
+	bandwidth::LimiterSlot,
 	error::Error,
 	litep2p::shim::notification::peerset::{OpenResult, Peerset, PeersetNotificationCommand},
 	service::{
@@ -142,6 +143,9 @@ pub struct NotificationProtocol {
 
 	/// Notification metrics.
 	metrics: NotificationMetrics,
+
+	/// Bandwidth limits of the protocol.
+	limiters: LimiterSlot,
 }
 
 impl fmt::Debug for NotificationProtocol {
@@ -160,12 +164,14 @@ impl NotificationProtocol {
 		handle: NotificationHandle,
 		peerset: Peerset,
 		metrics: NotificationMetrics,
+		limiters: LimiterSlot,
 	) -> Self {
 		Self {
 			protocol,
 			handle,
 			peerset,
 			metrics,
+			limiters,
 			pending_cancels: HashSet::new(),
 			pending_validations: FuturesUnordered::new(),
 		}
@@ -201,6 +207,11 @@ impl NotificationService for NotificationProtocol {
 	fn send_sync_notification(&mut self, peer: &PeerId, notification: Vec<u8>) {
 		let size = notification.len();
 
+		// synchronous notifications can't be delayed, only charged to the limits
+		if let Some(limiters) = self.limiters.get() {
+			let _ = limiters.outbound_delay(size);
+		}
+
 		if let Ok(_) = self.handle.send_sync_notification(peer.into(), notification) {
 			self.metrics.register_notification_sent(&self.protocol, size);
 		}
@@ -213,6 +224,10 @@ impl NotificationService for NotificationProtocol {
 	) -> Result<(), Error> {
 		let size = notification.len();
 
+		if let Some(limiters) = self.limiters.get() {
+			limiters.throttle_outbound(size).await;
+		}
+
 		match self.handle.send_async_notification(peer.into(), notification).await {
 			Ok(_) => {
 				self.metrics.register_notification_sent(&self.protocol, size);
@@ -259,7 +274,7 @@ impl NotificationService for NotificationProtocol {
 				sink,
 				self.metrics.clone(),
 			));
-			sink
+			self.limiters.wrap_sink(sink)
 		})
 	}
 
@@ -347,6 +362,10 @@ impl NotificationService for NotificationProtocol {
 					} => {
 						self.metrics.register_notification_received(&self.protocol, notification.len());
 
+						if let Some(limiters) = self.limiters.get() {
+							limiters.throttle_inbound(notification.len()).await;
+						}
+
 						if !self.pending_cancels.contains(&peer) {
 							return Some(SubstrateNotificationEvent::NotificationReceived {
 								peer: peer.into(),
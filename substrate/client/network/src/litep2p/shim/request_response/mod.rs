@@ -20,6 +20,7 @@
 //! request-response API.
 
 use crate::{
+	bandwidth::ProtocolLimiters,
 	litep2p::shim::request_response::metrics::RequestResponseMetrics,
 	peer_store::PeerStoreProvider,
 	request_responses::{IncomingRequest, OutgoingResponse},
@@ -196,6 +197,12 @@ pub struct RequestResponseProtocol {
 
 	/// Metrics, if enabled.
 	metrics: RequestResponseMetrics,
+
+	/// Bandwidth limits of the protocol.
+	///
+	/// Received requests and responses are throttled by delaying the event loop of the protocol
+	/// whereas sent responses are delayed individually.
+	limiters: ProtocolLimiters,
 }
 
 impl RequestResponseProtocol {
@@ -208,6 +215,7 @@ impl RequestResponseProtocol {
 		request_rx: TracingUnboundedReceiver<OutboundRequest>,
 		request_tx: HashMap<ProtocolName, TracingUnboundedSender<OutboundRequest>>,
 		metrics: Option<Metrics>,
+		limiters: ProtocolLimiters,
 	) -> Self {
 		Self {
 			handle,
@@ -219,6 +227,7 @@ impl RequestResponseProtocol {
 			pending_inbound_responses: HashMap::new(),
 			pending_outbound_responses: FuturesUnordered::new(),
 			metrics: RequestResponseMetrics::new(metrics, protocol),
+			limiters,
 		}
 	}
 
@@ -245,6 +254,8 @@ impl RequestResponseProtocol {
 			dial_options,
 		);
 
+		self.limiters.throttle_outbound(request.len()).await;
+
 		match self.handle.try_send_request(peer.into(), request, dial_options) {
 			Ok(request_id) => {
 				self.pending_inbound_responses
@@ -311,8 +322,16 @@ impl RequestResponseProtocol {
 			pending_response: tx,
 		}) {
 			Ok(_) => {
+				let limiters = self.limiters.clone();
+
 				self.pending_outbound_responses.push(Box::pin(async move {
-					(peer, request_id, rx.await.map_err(|_| ()), Instant::now())
+					let response = rx.await.map_err(|_| ());
+
+					if let Ok(OutgoingResponse { result: Ok(payload), .. }) = &response {
+						limiters.throttle_outbound(payload.len()).await;
+					}
+
+					(peer, request_id, response, Instant::now())
 				}));
 			},
 			Err(error) => {
@@ -534,8 +553,12 @@ impl RequestResponseProtocol {
 						fallback,
 						request_id,
 						request,
-					}) => self.on_inbound_request(peer, fallback, request_id, request),
+					}) => {
+						self.limiters.throttle_inbound(request.len()).await;
+						self.on_inbound_request(peer, fallback, request_id, request);
+					},
 					Some(RequestResponseEvent::ResponseReceived { peer, request_id, fallback, response }) => {
+						self.limiters.throttle_inbound(response.len()).await;
 						self.on_inbound_response(peer, request_id, fallback, response);
 					},
 					Some(RequestResponseEvent::RequestFailed { peer, request_id, error }) => {
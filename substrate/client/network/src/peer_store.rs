@@ -18,21 +18,31 @@
 
 //! [`PeerStore`] manages peer reputations and provides connection candidates to
 //! [`crate::protocol_controller::ProtocolController`].
+//!
+//! Reputations and manual bans can optionally be persisted to disk, so that they survive a
+//! restart of the node.
 
 use crate::service::{metrics::PeerStoreMetrics, traits::PeerStore as PeerStoreT};
 
+use ip_network::IpNetwork;
 use libp2p::PeerId;
 use log::trace;
 use parking_lot::Mutex;
 use partial_sort::PartialSort;
 use prometheus_endpoint::Registry;
 use sc_network_common::{role::ObservedRole, types::ReputationChange};
+use sc_network_types::multiaddr::{Multiaddr, Protocol};
+use serde::{Deserialize, Serialize};
 use std::{
 	cmp::{Ord, Ordering, PartialOrd},
-	collections::{hash_map::Entry, HashMap, HashSet},
-	fmt::Debug,
+	collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+	fmt::{self, Debug},
+	fs, io,
+	net::IpAddr,
+	path::{Path, PathBuf},
+	str::FromStr,
 	sync::Arc,
-	time::{Duration, Instant},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use wasm_timer::Delay;
 
@@ -59,6 +69,13 @@ const INVERSE_DECREMENT: i32 = 200;
 /// Amount of time between the moment we last updated the [`PeerStore`] entry and the moment we
 /// remove it, once the reputation value reaches 0.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
+/// Name of the file in the network configuration directory that reputations and bans are
+/// persisted to.
+pub const PEER_STORE_FILE: &str = "peer_store.json";
+/// How often reputations are persisted. Changes to the ban list are persisted right away.
+pub(crate) const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+/// Longer manual bans are capped to this duration.
+pub const MAX_BAN_DURATION: Duration = Duration::from_secs(100 * 365 * 24 * 3600);
 
 /// Trait describing the required functionality from a `Peerset` handle.
 pub trait ProtocolHandle: Debug + Send + Sync {
@@ -98,6 +115,281 @@ pub trait PeerStoreProvider: Debug + Send + Sync {
 
 	/// Add known peer.
 	fn add_known_peer(&self, peer_id: sc_network_types::PeerId);
+
+	/// Ban `target` until `until`, disconnecting the matching peers from all protocols.
+	fn ban(&self, target: BanTarget, until: SystemTime);
+
+	/// Lift a ban. Returns `false` if `target` wasn't banned.
+	fn unban(&self, target: &BanTarget) -> bool;
+
+	/// Get the bans currently in effect.
+	fn banned(&self) -> Vec<BannedPeer>;
+
+	/// Check whether `address` the peer is connected through belongs to a banned network.
+	///
+	/// If it does, the peer is disconnected from all protocols and treated as banned for as long
+	/// as the network ban lasts.
+	fn check_address_ban(&self, peer_id: sc_network_types::PeerId, address: &Multiaddr) -> bool;
+}
+
+/// Target of a manual ban.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BanTarget {
+	/// A single peer.
+	Peer(sc_network_types::PeerId),
+	/// All peers connecting through an address within the IP network.
+	Network(IpNetwork),
+}
+
+impl BanTarget {
+	fn contains_address(&self, address: &Multiaddr) -> bool {
+		let BanTarget::Network(network) = self else { return false };
+
+		address.iter().any(|protocol| match protocol {
+			Protocol::Ip4(ip) => network.contains(ip),
+			Protocol::Ip6(ip) => network.contains(ip),
+			_ => false,
+		})
+	}
+}
+
+impl fmt::Display for BanTarget {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			BanTarget::Peer(peer_id) => write!(f, "{peer_id}"),
+			BanTarget::Network(network) => write!(f, "{network}"),
+		}
+	}
+}
+
+impl FromStr for BanTarget {
+	type Err = String;
+
+	/// Parse a base58-encoded peer ID, an IP address or an IP network in CIDR notation.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Ok(peer_id) = s.parse::<sc_network_types::PeerId>() {
+			return Ok(BanTarget::Peer(peer_id))
+		}
+
+		match s.parse::<IpAddr>() {
+			Ok(IpAddr::V4(ip)) => return Ok(BanTarget::Network(IpNetwork::from(ip))),
+			Ok(IpAddr::V6(ip)) => return Ok(BanTarget::Network(IpNetwork::from(ip))),
+			Err(_) => {},
+		}
+
+		s.parse::<IpNetwork>().map(BanTarget::Network).map_err(|_| {
+			format!("`{s}` is neither a peer ID, nor an IP address, nor an IP network")
+		})
+	}
+}
+
+/// Manual ban currently in effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BannedPeer {
+	/// Banned peer or network.
+	pub target: BanTarget,
+	/// Time the ban expires at.
+	pub until: SystemTime,
+}
+
+/// Manual bans, shared by the `libp2p` and `litep2p` peer stores.
+#[derive(Debug, Default)]
+pub(crate) struct BanList {
+	/// Bans and their expiry time.
+	bans: HashMap<BanTarget, SystemTime>,
+	/// Peers that connected through an address of a banned network.
+	via_network: HashMap<sc_network_types::PeerId, IpNetwork>,
+	/// Whether the bans changed since they were last persisted.
+	changed: bool,
+}
+
+impl BanList {
+	/// Ban `target` until `until`, replacing the previous ban of `target`, if any.
+	pub(crate) fn insert(&mut self, target: BanTarget, until: SystemTime) {
+		self.bans.insert(target, until);
+		self.changed = true;
+	}
+
+	/// Lift a ban. Returns `false` if `target` wasn't banned.
+	pub(crate) fn remove(&mut self, target: &BanTarget) -> bool {
+		if let BanTarget::Network(network) = target {
+			self.via_network.retain(|_, banned| banned != network);
+		}
+
+		let removed = self.bans.remove(target).is_some();
+		self.changed |= removed;
+		removed
+	}
+
+	/// Check whether the peer is banned, either directly or by the network it connected from.
+	pub(crate) fn is_peer_banned(&self, peer_id: &sc_network_types::PeerId) -> bool {
+		self.is_active(&BanTarget::Peer(*peer_id)) ||
+			self.via_network
+				.get(peer_id)
+				.map_or(false, |network| self.is_active(&BanTarget::Network(*network)))
+	}
+
+	/// Check whether `address` belongs to a banned network and, if it does, treat the peer as
+	/// banned for as long as the network ban lasts.
+	pub(crate) fn check_address(
+		&mut self,
+		peer_id: sc_network_types::PeerId,
+		address: &Multiaddr,
+	) -> bool {
+		let now = SystemTime::now();
+		let network = self.bans.iter().find_map(|(target, until)| match target {
+			BanTarget::Network(network) if *until > now && target.contains_address(address) =>
+				Some(*network),
+			_ => None,
+		});
+
+		match network {
+			Some(network) => {
+				self.via_network.insert(peer_id, network);
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Remove expired bans.
+	pub(crate) fn prune(&mut self) {
+		let now = SystemTime::now();
+		let num_bans = self.bans.len();
+		self.bans.retain(|_, until| *until > now);
+
+		let bans = &self.bans;
+		self.via_network
+			.retain(|_, network| bans.contains_key(&BanTarget::Network(*network)));
+		self.changed |= self.bans.len() != num_bans;
+	}
+
+	/// Get the bans currently in effect.
+	pub(crate) fn banned(&self) -> Vec<BannedPeer> {
+		let now = SystemTime::now();
+		self.bans
+			.iter()
+			.filter(|(_, until)| **until > now)
+			.map(|(target, until)| BannedPeer { target: *target, until: *until })
+			.collect()
+	}
+
+	/// Whether the bans changed since they were last persisted.
+	pub(crate) fn is_changed(&self) -> bool {
+		self.changed
+	}
+
+	/// Note that the bans were persisted.
+	pub(crate) fn mark_persisted(&mut self) {
+		self.changed = false;
+	}
+
+	fn is_active(&self, target: &BanTarget) -> bool {
+		self.bans.get(target).map_or(false, |until| *until > SystemTime::now())
+	}
+}
+
+/// Peer store contents persisted to disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PersistedPeerStore {
+	/// Unix time in seconds when the peer store was persisted.
+	saved_at: u64,
+	/// Non-zero reputations by base58-encoded peer ID.
+	reputations: BTreeMap<String, i32>,
+	/// Expiry of the manual bans as unix time in seconds, by ban target.
+	bans: BTreeMap<String, u64>,
+}
+
+impl PersistedPeerStore {
+	/// Create a snapshot of the reputations and bans to be persisted.
+	pub(crate) fn new(
+		reputations: impl Iterator<Item = (sc_network_types::PeerId, i32)>,
+		bans: &BanList,
+	) -> Self {
+		Self {
+			saved_at: unix_secs(SystemTime::now()),
+			reputations: reputations
+				.filter(|(_, reputation)| *reputation != 0)
+				.map(|(peer_id, reputation)| (peer_id.to_base58(), reputation))
+				.collect(),
+			bans: bans
+				.banned()
+				.into_iter()
+				.map(|ban| (ban.target.to_string(), unix_secs(ban.until)))
+				.collect(),
+		}
+	}
+
+	/// Load the peer store persisted at `path`, if any.
+	///
+	/// A missing or corrupted file is not an error: the peer store then just starts empty.
+	pub(crate) fn load(path: &Path) -> Option<Self> {
+		let data = match fs::read(path) {
+			Ok(data) => data,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+			Err(err) => {
+				log::warn!(target: LOG_TARGET, "Failed to read {}: {err}", path.display());
+				return None
+			},
+		};
+
+		serde_json::from_slice(&data)
+			.map_err(|err| {
+				log::warn!(target: LOG_TARGET, "Ignoring corrupted {}: {err}", path.display());
+			})
+			.ok()
+	}
+
+	/// Persist the peer store to `path`.
+	pub(crate) fn save(&self, path: &Path) {
+		if let Err(err) = self.write(path) {
+			log::warn!(target: LOG_TARGET, "Failed to persist peer store to {}: {err}", path.display());
+		}
+	}
+
+	/// Number of seconds passed since the peer store was persisted.
+	pub(crate) fn seconds_since_saved(&self) -> u64 {
+		unix_secs(SystemTime::now()).saturating_sub(self.saved_at)
+	}
+
+	/// Persisted reputations.
+	pub(crate) fn reputations(&self) -> impl Iterator<Item = (sc_network_types::PeerId, i32)> + '_ {
+		self.reputations
+			.iter()
+			.filter_map(|(peer_id, reputation)| Some((peer_id.parse().ok()?, *reputation)))
+	}
+
+	/// Persisted bans that haven't expired yet.
+	pub(crate) fn ban_list(&self) -> BanList {
+		let mut ban_list = BanList::default();
+		for (target, until) in &self.bans {
+			let Some(until) = UNIX_EPOCH.checked_add(Duration::from_secs(*until)) else { continue };
+
+			match target.parse::<BanTarget>() {
+				Ok(target) => ban_list.insert(target, until),
+				Err(err) => log::warn!(target: LOG_TARGET, "Ignoring persisted ban: {err}"),
+			}
+		}
+		ban_list.prune();
+		ban_list.mark_persisted();
+		ban_list
+	}
+
+	fn write(&self, path: &Path) -> io::Result<()> {
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+
+		// Write to a temporary file first, so that a crash never leaves a truncated file behind.
+		let tmp_path = path.with_extension("json.tmp");
+		fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+		fs::rename(tmp_path, path)
+	}
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
 }
 
 /// Actual implementation of peer reputations and connection candidates provider.
@@ -154,6 +446,22 @@ impl PeerStoreProvider for PeerStoreHandle {
 	fn add_known_peer(&self, peer_id: sc_network_types::PeerId) {
 		self.inner.lock().add_known_peer(peer_id.into());
 	}
+
+	fn ban(&self, target: BanTarget, until: SystemTime) {
+		self.inner.lock().ban(target, until);
+	}
+
+	fn unban(&self, target: &BanTarget) -> bool {
+		self.inner.lock().unban(target)
+	}
+
+	fn banned(&self) -> Vec<BannedPeer> {
+		self.inner.lock().bans.banned()
+	}
+
+	fn check_address_ban(&self, peer_id: sc_network_types::PeerId, address: &Multiaddr) -> bool {
+		self.inner.lock().check_address_ban(peer_id, address)
+	}
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -234,11 +542,14 @@ struct PeerStoreInner {
 	peers: HashMap<PeerId, PeerInfo>,
 	protocols: Vec<Arc<dyn ProtocolHandle>>,
 	metrics: Option<PeerStoreMetrics>,
+	bans: BanList,
+	persistence_path: Option<PathBuf>,
 }
 
 impl PeerStoreInner {
 	fn is_banned(&self, peer_id: &PeerId) -> bool {
-		self.peers.get(peer_id).map_or(false, |info| info.is_banned())
+		self.bans.is_peer_banned(&(*peer_id).into()) ||
+			self.peers.get(peer_id).map_or(false, |info| info.is_banned())
 	}
 
 	fn register_protocol(&mut self, protocol_handle: Arc<dyn ProtocolHandle>) {
@@ -342,7 +653,8 @@ impl PeerStoreInner {
 			.peers
 			.iter()
 			.filter_map(|(peer_id, info)| {
-				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, *info))
+				(!self.is_banned(peer_id) && !ignored.contains(peer_id))
+					.then_some((*peer_id, *info))
 			})
 			.collect::<Vec<_>>();
 		let count = std::cmp::min(count, candidates.len());
@@ -362,11 +674,14 @@ impl PeerStoreInner {
 			.iter_mut()
 			.for_each(|(_, info)| info.decay_reputation(seconds_passed));
 
+		self.bans.prune();
+
 		// Retain only entries with non-zero reputation values or not expired ones.
 		let now = Instant::now();
 		let mut num_banned_peers: u64 = 0;
-		self.peers.retain(|_, info| {
-			if info.is_banned() {
+		let bans = &self.bans;
+		self.peers.retain(|peer_id, info| {
+			if info.is_banned() || bans.is_peer_banned(&(*peer_id).into()) {
 				num_banned_peers += 1;
 			}
 
@@ -394,6 +709,75 @@ impl PeerStoreInner {
 			},
 		}
 	}
+
+	fn ban(&mut self, target: BanTarget, until: SystemTime) {
+		log::info!(target: LOG_TARGET, "Banning {target} until {until:?}.");
+		self.bans.insert(target, until);
+
+		if let BanTarget::Peer(peer_id) = target {
+			self.protocols.iter().for_each(|handle| handle.disconnect_peer(peer_id));
+		}
+	}
+
+	fn unban(&mut self, target: &BanTarget) -> bool {
+		let removed = self.bans.remove(target);
+		if removed {
+			log::info!(target: LOG_TARGET, "Ban of {target} lifted.");
+		}
+		removed
+	}
+
+	fn check_address_ban(
+		&mut self,
+		peer_id: sc_network_types::PeerId,
+		address: &Multiaddr,
+	) -> bool {
+		if !self.bans.check_address(peer_id, address) {
+			return false
+		}
+
+		log::debug!(target: LOG_TARGET, "Peer {peer_id} connected through banned {address}.");
+		self.protocols.iter().for_each(|handle| handle.disconnect_peer(peer_id));
+		true
+	}
+
+	/// Restore the reputations and bans persisted at `path` and keep persisting them there.
+	fn restore(&mut self, path: PathBuf) {
+		if let Some(persisted) = PersistedPeerStore::load(&path) {
+			// Decaying for longer than `FORGET_AFTER` drives any reputation value to zero.
+			let seconds_passed = persisted.seconds_since_saved().min(FORGET_AFTER.as_secs());
+
+			for (peer_id, reputation) in persisted.reputations() {
+				let mut info = PeerInfo { reputation, ..Default::default() };
+				info.decay_reputation(seconds_passed);
+				if info.reputation != 0 {
+					self.peers.entry(peer_id.into()).or_default().reputation = info.reputation;
+				}
+			}
+			self.bans = persisted.ban_list();
+
+			log::debug!(
+				target: LOG_TARGET,
+				"Restored {} reputations and {} bans from {}.",
+				self.peers.values().filter(|info| info.reputation != 0).count(),
+				self.bans.banned().len(),
+				path.display(),
+			);
+		}
+
+		self.persistence_path = Some(path);
+	}
+
+	fn persist(&mut self) {
+		let Some(path) = &self.persistence_path else { return };
+
+		PersistedPeerStore::new(
+			self.peers.iter().map(|(peer_id, info)| ((*peer_id).into(), info.reputation)),
+			&self.bans,
+		)
+		.save(path);
+		self.bans.mark_persisted();
+	}
 }
 
 /// Worker part of [`PeerStoreHandle`]
@@ -424,10 +808,18 @@ impl PeerStore {
 					.collect(),
 				protocols: Vec::new(),
 				metrics,
+				bans: BanList::default(),
+				persistence_path: None,
 			})),
 		}
 	}
 
+	/// Persist reputations and bans to `path`, restoring the ones persisted there before.
+	pub fn with_persistence(self, path: PathBuf) -> Self {
+		self.inner.lock().restore(path);
+		self
+	}
+
 	/// Get `PeerStoreHandle`.
 	pub fn handle(&self) -> PeerStoreHandle {
 		PeerStoreHandle { inner: self.inner.clone() }
@@ -437,6 +829,7 @@ impl PeerStore {
 	pub async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_persisted = started;
 
 		loop {
 			let now = Instant::now();
@@ -449,12 +842,27 @@ impl PeerStore {
 				elapsed_now.as_secs() - elapsed_latest.as_secs()
 			};
 
-			self.inner.lock().progress_time(seconds_passed);
+			{
+				let mut inner = self.inner.lock();
+				inner.progress_time(seconds_passed);
+
+				if inner.bans.is_changed() || now - latest_persisted >= PERSIST_INTERVAL {
+					inner.persist();
+					latest_persisted = now;
+				}
+			}
+
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
 }
 
+impl Drop for PeerStore {
+	fn drop(&mut self) {
+		self.inner.lock().persist();
+	}
+}
+
 #[async_trait::async_trait]
 impl PeerStoreT for PeerStore {
 	fn handle(&self) -> Arc<dyn PeerStoreProvider> {
@@ -468,7 +876,8 @@ impl PeerStoreT for PeerStore {
 
 #[cfg(test)]
 mod tests {
-	use super::{PeerInfo, PeerStore, PeerStoreProvider};
+	use super::{BanTarget, PeerInfo, PeerStore, PeerStoreProvider, PEER_STORE_FILE};
+	use std::time::{Duration, SystemTime};
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -570,4 +979,94 @@ mod tests {
 		assert_eq!(metrics.num_discovered.get(), 3);
 		assert_eq!(metrics.num_banned_peers.get(), 2);
 	}
+
+	#[test]
+	fn ban_target_parsing() {
+		let peer_id = sc_network_types::PeerId::random();
+		assert_eq!(peer_id.to_base58().parse(), Ok(BanTarget::Peer(peer_id)));
+
+		let single: BanTarget = "192.0.2.1".parse().unwrap();
+		assert_eq!(single.to_string(), "192.0.2.1/32");
+
+		let network: BanTarget = "2001:db8::/32".parse().unwrap();
+		assert_eq!(network.to_string(), "2001:db8::/32");
+
+		assert!("not a target".parse::<BanTarget>().is_err());
+	}
+
+	#[test]
+	fn manual_ban_is_enforced_until_lifted() {
+		let peer = sc_network_types::PeerId::random();
+		let peerstore = PeerStore::new(vec![peer.into()], None);
+		let handle = peerstore.handle();
+
+		let until = SystemTime::now() + Duration::from_secs(60);
+		handle.ban(BanTarget::Peer(peer), until);
+		assert!(handle.is_banned(&peer));
+		assert!(handle.outgoing_candidates(1, Default::default()).is_empty());
+		assert_eq!(handle.banned().len(), 1);
+
+		assert!(handle.unban(&BanTarget::Peer(peer)));
+		assert!(!handle.unban(&BanTarget::Peer(peer)));
+		assert!(!handle.is_banned(&peer));
+		assert_eq!(handle.outgoing_candidates(1, Default::default()), vec![peer]);
+	}
+
+	#[test]
+	fn expired_ban_is_pruned() {
+		let peer = sc_network_types::PeerId::random();
+		let peerstore = PeerStore::new(vec![], None);
+		let handle = peerstore.handle();
+
+		handle.ban(BanTarget::Peer(peer), SystemTime::now() - Duration::from_secs(1));
+		assert!(!handle.is_banned(&peer));
+
+		handle.inner.lock().progress_time(1);
+		assert!(handle.banned().is_empty());
+	}
+
+	#[test]
+	fn network_ban_applies_to_peers_connecting_through_it() {
+		let banned_peer = sc_network_types::PeerId::random();
+		let other_peer = sc_network_types::PeerId::random();
+		let peerstore = PeerStore::new(vec![], None);
+		let handle = peerstore.handle();
+
+		let network: BanTarget = "10.0.0.0/8".parse().unwrap();
+		handle.ban(network, SystemTime::now() + Duration::from_secs(60));
+
+		assert!(handle.check_address_ban(banned_peer, &"/ip4/10.1.2.3/tcp/30333".parse().unwrap()));
+		assert!(!handle.check_address_ban(other_peer, &"/ip4/192.0.2.1/tcp/30333".parse().unwrap()));
+		assert!(handle.is_banned(&banned_peer));
+		assert!(!handle.is_banned(&other_peer));
+
+		// Lifting the network ban lifts the ban of the peers that connected through it.
+		assert!(handle.unban(&network));
+		assert!(!handle.is_banned(&banned_peer));
+	}
+
+	#[test]
+	fn reputations_and_bans_survive_restart() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join(PEER_STORE_FILE);
+		let reported = sc_network_types::PeerId::random();
+		let banned = sc_network_types::PeerId::random();
+
+		{
+			let peerstore = PeerStore::new(vec![], None).with_persistence(path.clone());
+			let handle = peerstore.handle();
+			handle.report_peer(
+				reported,
+				sc_network_common::types::ReputationChange { value: -1000, reason: "test".into() },
+			);
+			handle.ban(BanTarget::Peer(banned), SystemTime::now() + Duration::from_secs(3600));
+		}
+
+		let peerstore = PeerStore::new(vec![], None).with_persistence(path);
+		let handle = peerstore.handle();
+		// The reputation decays for the seconds passed since it was persisted.
+		assert!((-1000..-900).contains(&handle.peer_reputation(&reported)));
+		assert!(handle.is_banned(&banned));
+		assert_eq!(handle.banned().len(), 1);
+	}
 }
@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+	bandwidth::BandwidthLimiters,
 	config, error,
 	peer_store::PeerStoreProvider,
 	protocol_controller::{self, SetId},
@@ -82,6 +83,7 @@ impl<B: BlockT> Protocol<B> {
 		peer_store_handle: Arc<dyn PeerStoreProvider>,
 		protocol_controller_handles: Vec<protocol_controller::ProtocolHandle>,
 		from_protocol_controllers: TracingUnboundedReceiver<protocol_controller::Message>,
+		bandwidth_limiters: &BandwidthLimiters,
 	) -> error::Result<(Self, Vec<ProtocolHandle>)> {
 		let (behaviour, notification_protocols, handles) = {
 			let installed_protocols = iter::once(block_announces_protocol.protocol_name().clone())
@@ -99,8 +101,9 @@ impl<B: BlockT> Protocol<B> {
 					max_notification_size: block_announces_protocol.max_notification_size(),
 				};
 
-				let (handle, command_stream) =
-					block_announces_protocol.take_protocol_handle().split();
+				let handle_pair = block_announces_protocol.take_protocol_handle();
+				handle_pair.set_bandwidth_limiters(bandwidth_limiters.protocol(&config.name));
+				let (handle, command_stream) = handle_pair.split();
 
 				((config, handle.clone(), command_stream), handle)
 			})
@@ -112,7 +115,9 @@ impl<B: BlockT> Protocol<B> {
 					max_notification_size: s.max_notification_size(),
 				};
 
-				let (handle, command_stream) = s.take_protocol_handle().split();
+				let handle_pair = s.take_protocol_handle();
+				handle_pair.set_bandwidth_limiters(bandwidth_limiters.protocol(&config.name));
+				let (handle, command_stream) = handle_pair.split();
 
 				((config, handle.clone(), command_stream), handle)
 			}))
@@ -19,6 +19,7 @@
 //! Notification service implementation.
 
 use crate::{
+	bandwidth::{LimiterSlot, ProtocolLimiters},
 	error,
 	protocol::notifications::handler::NotificationsSink,
 	service::{
@@ -205,6 +206,15 @@ pub struct NotificationHandle {
 
 	/// Connected peers.
 	peers: HashMap<PeerId, PeerContext>,
+
+	/// Bandwidth limits of the protocol.
+	limiters: LimiterSlot,
+
+	/// Whether received notifications are charged to the inbound limits.
+	///
+	/// Every subscriber receives a copy of each notification, so only the original handle is
+	/// charged and its clones are not throttled.
+	throttle_inbound: bool,
 }
 
 impl NotificationHandle {
@@ -214,8 +224,17 @@ impl NotificationHandle {
 		tx: mpsc::Sender<NotificationCommand>,
 		rx: TracingUnboundedReceiver<InnerNotificationEvent>,
 		subscribers: Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>,
+		limiters: LimiterSlot,
 	) -> Self {
-		Self { protocol, tx, rx, subscribers, peers: HashMap::new() }
+		Self {
+			protocol,
+			tx,
+			rx,
+			subscribers,
+			peers: HashMap::new(),
+			limiters,
+			throttle_inbound: true,
+		}
 	}
 }
 
@@ -234,6 +253,11 @@ impl NotificationService for NotificationHandle {
 	/// Send synchronous `notification` to `peer`.
 	fn send_sync_notification(&mut self, peer: &sc_network_types::PeerId, notification: Vec<u8>) {
 		if let Some(info) = self.peers.get(&((*peer).into())) {
+			// synchronous notifications can't be delayed, only charged to the limits
+			if let Some(limiters) = self.limiters.get() {
+				let _ = limiters.outbound_delay(notification.len());
+			}
+
 			metrics::register_notification_sent(
 				info.sink.metrics(),
 				&self.protocol,
@@ -257,6 +281,10 @@ impl NotificationService for NotificationHandle {
 			.ok_or_else(|| error::Error::PeerDoesntExist((*peer).into()))?
 			.sink;
 
+		if let Some(limiters) = self.limiters.get() {
+			limiters.throttle_outbound(notification_len).await;
+		}
+
 		sink.reserve_notification()
 			.await
 			.map_err(|_| error::Error::ConnectionClosed)?
@@ -322,11 +350,16 @@ impl NotificationService for NotificationHandle {
 					self.peers.remove(&peer);
 					return Some(NotificationEvent::NotificationStreamClosed { peer: peer.into() })
 				},
-				InnerNotificationEvent::NotificationReceived { peer, notification } =>
+				InnerNotificationEvent::NotificationReceived { peer, notification } => {
+					if let Some(limiters) = self.limiters.get().filter(|_| self.throttle_inbound) {
+						limiters.throttle_inbound(notification.len()).await;
+					}
+
 					return Some(NotificationEvent::NotificationReceived {
 						peer: peer.into(),
 						notification,
-					}),
+					})
+				},
 				InnerNotificationEvent::NotificationSinkReplaced { peer, sink } => {
 					match self.peers.get_mut(&peer) {
 						None => log::error!(
@@ -356,6 +389,8 @@ impl NotificationService for NotificationHandle {
 			rx: event_rx,
 			peers: self.peers.clone(),
 			subscribers: self.subscribers.clone(),
+			limiters: self.limiters.clone(),
+			throttle_inbound: false,
 		}))
 	}
 
@@ -367,7 +402,7 @@ impl NotificationService for NotificationHandle {
 	/// Get message sink of the peer.
 	fn message_sink(&self, peer: &sc_network_types::PeerId) -> Option<Box<dyn MessageSink>> {
 		match self.peers.get(&peer.into()) {
-			Some(context) => Some(Box::new(context.shared_sink.clone())),
+			Some(context) => Some(self.limiters.wrap_sink(Box::new(context.shared_sink.clone()))),
 			None => None,
 		}
 	}
@@ -384,6 +419,9 @@ pub struct ProtocolHandlePair {
 
 	// Receiver for notification commands received from the protocol implementation.
 	rx: mpsc::Receiver<NotificationCommand>,
+
+	/// Bandwidth limits of the protocol, shared with [`NotificationHandle`].
+	limiters: LimiterSlot,
 }
 
 impl ProtocolHandlePair {
@@ -392,8 +430,14 @@ impl ProtocolHandlePair {
 		protocol: ProtocolName,
 		subscribers: Subscribers,
 		rx: mpsc::Receiver<NotificationCommand>,
+		limiters: LimiterSlot,
 	) -> Self {
-		Self { protocol, subscribers, rx }
+		Self { protocol, subscribers, rx, limiters }
+	}
+
+	/// Set the bandwidth limits enforced by the protocol's [`NotificationHandle`].
+	pub(crate) fn set_bandwidth_limiters(&self, limiters: ProtocolLimiters) {
+		self.limiters.install(limiters);
 	}
 
 	/// Consume `self` and split [`ProtocolHandlePair`] into a handle which allows it to send events
@@ -637,10 +681,17 @@ pub fn notification_service(
 	let (event_tx, event_rx) =
 		tracing_unbounded(metric_label_for_protocol(&protocol).leak(), 100_000);
 	let subscribers = Arc::new(Mutex::new(vec![event_tx]));
+	let limiters = LimiterSlot::default();
 
 	(
-		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), cmd_rx),
-		Box::new(NotificationHandle::new(protocol.clone(), cmd_tx, event_rx, subscribers)),
+		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), cmd_rx, limiters.clone()),
+		Box::new(NotificationHandle::new(
+			protocol.clone(),
+			cmd_tx,
+			event_rx,
+			subscribers,
+			limiters,
+		)),
 	)
 }
 
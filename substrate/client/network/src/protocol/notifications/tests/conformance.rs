@@ -92,7 +92,8 @@ fn setup_libp2p(
 			command_stream,
 		)),
 	);
-	let transport = crate::transport::build_transport(local_key.clone().into(), false);
+	let transport =
+		crate::transport::build_transport(local_key.clone().into(), false, &Default::default());
 
 	let mut swarm = {
 		struct SpawnImpl {}
@@ -857,13 +857,13 @@ impl ProtocolController {
 mod tests {
 	use super::*;
 	use crate::{
-		peer_store::{PeerStoreProvider, ProtocolHandle as ProtocolHandleT},
+		peer_store::{BanTarget, BannedPeer, PeerStoreProvider, ProtocolHandle as ProtocolHandleT},
 		ReputationChange,
 	};
 	use libp2p::PeerId;
 	use sc_network_common::role::ObservedRole;
 	use sc_utils::mpsc::{tracing_unbounded, TryRecvError};
-	use std::collections::HashSet;
+	use std::{collections::HashSet, time::SystemTime};
 
 	mockall::mock! {
 		#[derive(Debug)]
@@ -879,6 +879,10 @@ mod tests {
 			fn peer_role(&self, peer_id: &sc_network_types::PeerId) -> Option<ObservedRole>;
 			fn outgoing_candidates(&self, count: usize, ignored: HashSet<sc_network_types::PeerId>) -> Vec<sc_network_types::PeerId>;
 			fn add_known_peer(&self, peer_id: sc_network_types::PeerId);
+			fn ban(&self, target: BanTarget, until: SystemTime);
+			fn unban(&self, target: &BanTarget) -> bool;
+			fn banned(&self) -> Vec<BannedPeer>;
+			fn check_address_ban(&self, peer_id: sc_network_types::PeerId, address: &sc_network_types::multiaddr::Multiaddr) -> bool;
 		}
 	}
 
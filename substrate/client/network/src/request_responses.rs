@@ -31,10 +31,14 @@
 //! - Requests have a certain time limit before they time out. This time includes the time it
 //! takes to send/receive the request and response.
 //!
+//! - Requests and responses are subject to the [bandwidth limits](crate::bandwidth) of their
+//! protocol. Time spent waiting for a limit counts towards the time limit above.
+//!
 //! - If provided, a ["requests processing"](ProtocolConfig::inbound_queue) channel
 //! is used to handle incoming requests.
 
 use crate::{
+	bandwidth::{BandwidthLimiters, ProtocolLimiters},
 	peer_store::{PeerStoreProvider, BANNED_THRESHOLD},
 	service::traits::RequestResponseConfig as RequestResponseConfigT,
 	types::ProtocolName,
@@ -403,9 +407,13 @@ struct RequestProcessingOutcome {
 impl RequestResponsesBehaviour {
 	/// Creates a new behaviour. Must be passed a list of supported protocols. Returns an error if
 	/// the same protocol is passed twice.
+	///
+	/// Requests and responses are throttled according to the per-protocol limits of
+	/// `bandwidth_limiters`.
 	pub fn new(
 		list: impl Iterator<Item = ProtocolConfig>,
 		peer_store: Arc<dyn PeerStoreProvider>,
+		bandwidth_limiters: &BandwidthLimiters,
 	) -> Result<Self, RegisterError> {
 		let mut protocols = HashMap::new();
 		for protocol in list {
@@ -421,6 +429,7 @@ impl RequestResponsesBehaviour {
 				GenericCodec {
 					max_request_size: protocol.max_request_size,
 					max_response_size: protocol.max_response_size,
+					limiters: bandwidth_limiters.protocol(&protocol.name),
 				},
 				iter::once(protocol.name.clone())
 					.chain(protocol.fallback_names)
@@ -1035,6 +1044,7 @@ pub enum ResponseFailure {
 pub struct GenericCodec {
 	max_request_size: u64,
 	max_response_size: u64,
+	limiters: ProtocolLimiters,
 }
 
 #[async_trait::async_trait]
@@ -1065,6 +1075,8 @@ impl Codec for GenericCodec {
 		// Read the payload.
 		let mut buffer = vec![0; length];
 		io.read_exact(&mut buffer).await?;
+
+		self.limiters.throttle_inbound(length).await;
 		Ok(buffer)
 	}
 
@@ -1100,6 +1112,8 @@ impl Codec for GenericCodec {
 		// Read the payload.
 		let mut buffer = vec![0; length];
 		io.read_exact(&mut buffer).await?;
+
+		self.limiters.throttle_inbound(length).await;
 		Ok(Ok(buffer))
 	}
 
@@ -1112,6 +1126,8 @@ impl Codec for GenericCodec {
 	where
 		T: AsyncWrite + Unpin + Send,
 	{
+		self.limiters.throttle_outbound(req.len()).await;
+
 		// TODO: check the length?
 		// Write the length.
 		{
@@ -1137,6 +1153,8 @@ impl Codec for GenericCodec {
 	{
 		// If `res` is an `Err`, we jump to closing the substream without writing anything on it.
 		if let Ok(res) = res {
+			self.limiters.throttle_outbound(res.len()).await;
+
 			// TODO: check the length?
 			// Write the length.
 			{
@@ -1190,7 +1208,9 @@ mod tests {
 			.multiplex(libp2p::yamux::Config::default())
 			.boxed();
 
-		let behaviour = RequestResponsesBehaviour::new(list, Arc::new(MockPeerStore {})).unwrap();
+		let behaviour =
+			RequestResponsesBehaviour::new(list, Arc::new(MockPeerStore {}), &Default::default())
+				.unwrap();
 
 		let mut swarm = Swarm::new(
 			transport,
@@ -28,6 +28,7 @@
 //! which is then processed by [`NetworkWorker::next_action`].
 
 use crate::{
+	bandwidth::BandwidthLimiters,
 	behaviour::{self, Behaviour, BehaviourOut},
 	bitswap::BitswapRequestHandler,
 	config::{
@@ -40,7 +41,7 @@ use crate::{
 	network_state::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
-	peer_store::{PeerStore, PeerStoreProvider},
+	peer_store::{BanTarget, BannedPeer, PeerStore, PeerStoreProvider, MAX_BAN_DURATION},
 	protocol::{self, Protocol, Ready},
 	protocol_controller::{self, ProtoSetConfig, ProtocolController, SetId},
 	request_responses::{IfDisconnected, ProtocolConfig as RequestResponseConfig, RequestFailure},
@@ -95,13 +96,14 @@ use std::{
 	fs, iter,
 	marker::PhantomData,
 	num::NonZeroUsize,
+	path::PathBuf,
 	pin::Pin,
 	str,
 	sync::{
 		atomic::{AtomicUsize, Ordering},
 		Arc,
 	},
-	time::{Duration, Instant},
+	time::{Duration, Instant, SystemTime},
 };
 
 pub(crate) mod metrics;
@@ -189,8 +191,15 @@ where
 	fn peer_store(
 		bootnodes: Vec<sc_network_types::PeerId>,
 		metrics_registry: Option<Registry>,
+		persistence_path: Option<PathBuf>,
 	) -> Self::PeerStore {
-		PeerStore::new(bootnodes.into_iter().map(From::from).collect(), metrics_registry)
+		let peer_store =
+			PeerStore::new(bootnodes.into_iter().map(From::from).collect(), metrics_registry);
+
+		match persistence_path {
+			Some(path) => peer_store.with_persistence(path),
+			None => peer_store,
+		}
 	}
 
 	fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics {
@@ -338,13 +347,22 @@ where
 		);
 		info!(target: LOG_TARGET, "Running libp2p network backend");
 
+		let bandwidth_limiters = BandwidthLimiters::new(
+			&network_config.bandwidth_limits,
+			params.metrics_registry.as_ref(),
+		)?;
+
 		let (transport, bandwidth) = {
 			let config_mem = match network_config.transport {
 				TransportConfig::MemoryOnly => true,
 				TransportConfig::Normal { .. } => false,
 			};
 
-			transport::build_transport(local_identity.clone().into(), config_mem)
+			transport::build_transport(
+				local_identity.clone().into(),
+				config_mem,
+				&bandwidth_limiters,
+			)
 		};
 
 		let (to_notifications, from_protocol_controllers) =
@@ -464,6 +482,7 @@ where
 			Arc::clone(&peer_store_handle),
 			protocol_handles.clone(),
 			from_protocol_controllers,
+			&bandwidth_limiters,
 		)?;
 
 		// Build the swarm.
@@ -518,6 +537,7 @@ where
 					discovery_config,
 					request_response_protocols,
 					Arc::clone(&peer_store_handle),
+					&bandwidth_limiters,
 					external_addresses.clone(),
 					network_config.public_addresses.iter().cloned().map(Into::into).collect(),
 					ConnectionLimits::default()
@@ -1167,6 +1187,20 @@ where
 			.map(|peers| peers.into_iter().map(From::from).collect())
 			.map_err(|_| ())
 	}
+
+	fn ban_peer(&self, target: BanTarget, duration: Duration) {
+		self.peer_store_handle
+			.ban(target, SystemTime::now() + duration.min(MAX_BAN_DURATION));
+		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::EnforceBan(target));
+	}
+
+	fn unban_peer(&self, target: &BanTarget) -> bool {
+		self.peer_store_handle.unban(target)
+	}
+
+	fn banned_peers(&self) -> Vec<BannedPeer> {
+		self.peer_store_handle.banned()
+	}
 }
 
 impl<B, H> NetworkEventStream for NetworkService<B, H>
@@ -1327,6 +1361,7 @@ enum ServiceToWorkerMsg {
 		pending_response: oneshot::Sender<Result<NetworkState, RequestFailure>>,
 	},
 	DisconnectPeer(PeerId, ProtocolName),
+	EnforceBan(BanTarget),
 }
 
 /// Main network worker. Must be polled in order for the network to advance.
@@ -1480,6 +1515,36 @@ where
 				.behaviour_mut()
 				.user_protocol_mut()
 				.disconnect_peer(&who, protocol_name),
+			ServiceToWorkerMsg::EnforceBan(target) => self.enforce_ban(target),
+		}
+	}
+
+	/// Close the connections to the peers matching a newly added ban.
+	fn enforce_ban(&mut self, target: BanTarget) {
+		let banned_peers: Vec<PeerId> = match target {
+			BanTarget::Peer(peer_id) => vec![peer_id.into()],
+			BanTarget::Network(_) => self
+				.network_service
+				.connected_peers()
+				.filter(|peer_id| {
+					self.network_service
+						.behaviour()
+						.node(peer_id)
+						.and_then(|node| node.endpoint())
+						.map_or(false, |endpoint| {
+							self.peer_store_handle.check_address_ban(
+								(**peer_id).into(),
+								&endpoint.get_remote_address().clone().into(),
+							)
+						})
+				})
+				.copied()
+				.collect(),
+		};
+
+		for peer_id in banned_peers {
+			debug!(target: LOG_TARGET, "Disconnecting banned peer {peer_id:?}");
+			let _ = self.network_service.disconnect_peer_id(peer_id);
 		}
 	}
 
@@ -1681,6 +1746,7 @@ where
 			},
 			SwarmEvent::ConnectionEstablished {
 				peer_id,
+				connection_id,
 				endpoint,
 				num_established,
 				concurrent_dial_errors,
@@ -1692,6 +1758,14 @@ where
 					debug!(target: LOG_TARGET, "Libp2p => Connected({:?})", peer_id);
 				}
 
+				if self.peer_store_handle.check_address_ban(
+					peer_id.into(),
+					&endpoint.get_remote_address().clone().into(),
+				) {
+					debug!(target: LOG_TARGET, "Libp2p => Closing connection to {peer_id:?}: address is banned");
+					self.network_service.close_connection(connection_id);
+				}
+
 				if let Some(metrics) = self.metrics.as_ref() {
 					let direction = match endpoint {
 						ConnectedPoint::Dialer { .. } => "out",
@@ -25,6 +25,7 @@ use crate::{
 	error::{self, Error},
 	event::Event,
 	network_state::NetworkState,
+	peer_store::{BanTarget, BannedPeer},
 	request_responses::{IfDisconnected, RequestFailure},
 	service::{metrics::NotificationMetrics, signature::Signature, PeerStoreProvider},
 	types::ProtocolName,
@@ -47,6 +48,7 @@ use std::{
 	collections::HashSet,
 	fmt::Debug,
 	future::Future,
+	path::PathBuf,
 	pin::Pin,
 	sync::Arc,
 	time::{Duration, Instant},
@@ -138,7 +140,14 @@ pub trait NetworkBackend<B: BlockT + 'static, H: ExHashT>: Send + 'static {
 	fn network_service(&self) -> Arc<dyn NetworkService>;
 
 	/// Create [`PeerStore`].
-	fn peer_store(bootnodes: Vec<PeerId>, metrics_registry: Option<Registry>) -> Self::PeerStore;
+	///
+	/// If `persistence_path` is set, reputations and bans are persisted to that file and
+	/// restored from it.
+	fn peer_store(
+		bootnodes: Vec<PeerId>,
+		metrics_registry: Option<Registry>,
+		persistence_path: Option<PathBuf>,
+	) -> Self::PeerStore;
 
 	/// Register metrics that are used by the notification protocols.
 	fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics;
@@ -465,6 +474,20 @@ pub trait NetworkPeers {
 	///
 	/// Returns an error if the `NetworkWorker` is no longer running.
 	async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()>;
+
+	/// Ban a peer or all peers connecting from an IP network for `duration`.
+	///
+	/// Matching peers are disconnected and refused until the ban expires or is lifted with
+	/// [`NetworkPeers::unban_peer`]. Bans are persisted along with the peer reputations.
+	fn ban_peer(&self, target: BanTarget, duration: Duration);
+
+	/// Lift a ban set with [`NetworkPeers::ban_peer`].
+	///
+	/// Returns `false` if `target` wasn't banned.
+	fn unban_peer(&self, target: &BanTarget) -> bool;
+
+	/// Get the bans currently in effect.
+	fn banned_peers(&self) -> Vec<BannedPeer>;
 }
 
 // Manual implementation to avoid extra boxing here
@@ -555,6 +578,18 @@ where
 	{
 		T::reserved_peers(self)
 	}
+
+	fn ban_peer(&self, target: BanTarget, duration: Duration) {
+		T::ban_peer(self, target, duration)
+	}
+
+	fn unban_peer(&self, target: &BanTarget) -> bool {
+		T::unban_peer(self, target)
+	}
+
+	fn banned_peers(&self) -> Vec<BannedPeer> {
+		T::banned_peers(self)
+	}
 }
 
 /// Provides access to network-level event stream.
@@ -18,6 +18,8 @@
 
 //! Transport that serves as a common ground for all connections.
 
+use crate::bandwidth::BandwidthLimiters;
+
 use either::Either;
 use libp2p::{
 	core::{
@@ -38,12 +40,15 @@ pub use libp2p::bandwidth::BandwidthSinks;
 /// If `memory_only` is true, then only communication within the same process are allowed. Only
 /// addresses with the format `/memory/...` are allowed.
 ///
+/// The node-wide limits of `bandwidth_limiters` are enforced on every connection.
+///
 /// Returns a `BandwidthSinks` object that allows querying the average bandwidth produced by all
 /// the connections spawned with this transport.
 #[allow(deprecated)]
 pub fn build_transport(
 	keypair: identity::Keypair,
 	memory_only: bool,
+	bandwidth_limiters: &BandwidthLimiters,
 ) -> (Boxed<(PeerId, StreamMuxerBox)>, Arc<BandwidthSinks>) {
 	// Build the base layer of the transport.
 	let transport = if !memory_only {
@@ -73,6 +78,10 @@ pub fn build_transport(
 		Either::Right(OptionalTransport::some(libp2p::core::transport::MemoryTransport::default()))
 	};
 
+	// Throttle the raw connections so that the limits also cover encryption and multiplexing.
+	let node_limiters = bandwidth_limiters.node();
+	let transport = transport.map(move |stream, _| node_limiters.throttle(stream));
+
 	let authentication_config = noise::Config::new(&keypair).expect("Can create noise config. qed");
 	let multiplexing_config = libp2p::yamux::Config::default();
 
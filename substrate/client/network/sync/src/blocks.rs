@@ -195,6 +195,37 @@ impl<B: BlockT> BlockCollection<B> {
 		Some(range)
 	}
 
+	/// Marks the range being downloaded from the `stalled` peer as also being downloaded from
+	/// `who`, so the range can be speculatively re-requested from a faster peer.
+	///
+	/// The range is re-issued only if it is still being downloaded by less than `max_parallel`
+	/// peers and `who` has all of its blocks. Returns the re-issued range.
+	pub fn reissue_range(
+		&mut self,
+		who: PeerId,
+		stalled: &PeerId,
+		peer_best: NumberFor<B>,
+		common: NumberFor<B>,
+		max_parallel: u32,
+	) -> Option<Range<NumberFor<B>>> {
+		let start = *self.peer_requests.get(stalled)?;
+		let Some(BlockRangeState::Downloading { len, downloading }) = self.blocks.get_mut(&start)
+		else {
+			return None
+		};
+		let range = start..start + *len;
+		if *downloading >= max_parallel ||
+			range.start <= common ||
+			range.end > peer_best + One::one()
+		{
+			return None
+		}
+
+		*downloading += 1;
+		self.peer_requests.insert(who, range.start);
+		Some(range)
+	}
+
 	/// Get a valid chain of blocks ordered in descending order and ready for importing into
 	/// the blockchain.
 	/// `from` is the maximum block number for the start of the range that we are interested in.
@@ -298,6 +329,28 @@ mod test {
 		assert!(is_empty(&bc));
 	}
 
+	#[test]
+	fn reissue_stalled_range() {
+		let mut bc = BlockCollection::new();
+		let (slow, fast, behind) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+		assert_eq!(bc.needed_blocks(slow, 40, 150, 0, 1, 200), Some(1..41));
+		assert_eq!(bc.reissue_range(behind, &slow, 30, 0, 2), None);
+		assert_eq!(bc.reissue_range(fast, &slow, 150, 0, 2), Some(1..41));
+		assert!(matches!(bc.blocks[&1], BlockRangeState::Downloading { len: 40, downloading: 2 }));
+		// Already requested from `max_parallel` peers.
+		assert_eq!(bc.reissue_range(behind, &slow, 150, 0, 2), None);
+
+		// The first response completes the range, the late one is ignored.
+		let blocks = generate_blocks(41);
+		bc.clear_peer_download(&fast);
+		bc.insert(1, blocks[1..41].to_vec(), fast);
+		bc.clear_peer_download(&slow);
+		bc.insert(1, blocks[1..41].to_vec(), slow);
+		assert!(bc.ready_blocks(1).iter().all(|b| b.origin == Some(fast)));
+		assert_eq!(bc.reissue_range(behind, &slow, 150, 0, 2), None);
+	}
+
 	#[test]
 	fn insert_blocks() {
 		let mut bc = BlockCollection::new();
@@ -21,6 +21,7 @@ use futures::channel::oneshot;
 use sc_consensus::{BlockImportError, BlockImportStatus};
 use sc_network::{
 	config::MultiaddrWithPeerId,
+	peer_store::{BanTarget, BannedPeer},
 	request_responses::{IfDisconnected, RequestFailure},
 	types::ProtocolName,
 	NetworkPeers, NetworkRequest, NetworkSyncForkRequest, ReputationChange,
@@ -29,7 +30,7 @@ use sc_network_common::role::ObservedRole;
 use sc_network_types::{multiaddr::Multiaddr, PeerId};
 use sp_runtime::traits::{Block as BlockT, NumberFor};
 
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 mockall::mock! {
 	pub ChainSyncInterface<B: BlockT> {
@@ -109,6 +110,9 @@ mockall::mock! {
 		fn sync_num_connected(&self) -> usize;
 		fn peer_role(&self, peer_id: PeerId, handshake: Vec<u8>) -> Option<ObservedRole>;
 		async fn reserved_peers(&self) -> Result<Vec<sc_network_types::PeerId>, ()>;
+		fn ban_peer(&self, target: BanTarget, duration: Duration);
+		fn unban_peer(&self, target: &BanTarget) -> bool;
+		fn banned_peers(&self) -> Vec<BannedPeer>;
 	}
 
 	#[async_trait::async_trait]
@@ -36,7 +36,8 @@ use crate::{
 	service::network::NetworkServiceHandle,
 	strategy::{
 		disconnected_peers::DisconnectedPeers,
-		state_sync::{ImportResult, StateSync, StateSyncProvider},
+		download_stats::DownloadStats,
+		state_sync::{saved_state_sync_target, ImportResult, StateSync, StateSyncProvider},
 		warp::{WarpSyncPhase, WarpSyncProgress},
 		StrategyKey, SyncingAction, SyncingStrategy,
 	},
@@ -46,9 +47,11 @@ use crate::{
 
 use futures::{channel::oneshot, FutureExt};
 use log::{debug, error, info, trace, warn};
-use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use prometheus_endpoint::{
+	register, Counter, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64,
+};
 use prost::Message;
-use sc_client_api::{blockchain::BlockGap, BlockBackend, ProofProvider};
+use sc_client_api::{blockchain::BlockGap, AuxStore, BlockBackend, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus, IncomingBlock};
 use sc_network::{IfDisconnected, ProtocolName};
 use sc_network_common::sync::message::{
@@ -70,6 +73,7 @@ use std::{
 	collections::{HashMap, HashSet},
 	ops::Range,
 	sync::Arc,
+	time::Instant,
 };
 
 #[cfg(test)]
@@ -136,6 +140,9 @@ mod rep {
 struct Metrics {
 	queued_blocks: Gauge<U64>,
 	fork_targets: Gauge<U64>,
+	peer_download_rate: GaugeVec<U64>,
+	peer_request_size: GaugeVec<U64>,
+	reissued_requests: Counter<U64>,
 }
 
 impl Metrics {
@@ -150,8 +157,41 @@ impl Metrics {
 				let g = Gauge::new("substrate_sync_fork_targets", "Number of fork sync targets")?;
 				register(g, r)?
 			},
+			peer_download_rate: {
+				let g = GaugeVec::new(
+					Opts::new(
+						"substrate_sync_peer_download_rate",
+						"Average block download rate of a peer, in blocks per second",
+					),
+					&["peer"],
+				)?;
+				register(g, r)?
+			},
+			peer_request_size: {
+				let g = GaugeVec::new(
+					Opts::new(
+						"substrate_sync_peer_request_size",
+						"Number of blocks requested from a peer in a single block request",
+					),
+					&["peer"],
+				)?;
+				register(g, r)?
+			},
+			reissued_requests: {
+				let c = Counter::new(
+					"substrate_sync_reissued_block_requests",
+					"Number of stalled block requests re-issued to faster peers",
+				)?;
+				register(c, r)?
+			},
 		})
 	}
+
+	fn remove_peer(&self, peer_id: &PeerId) {
+		let peer_id = peer_id.to_string();
+		let _ = self.peer_download_rate.remove_label_values(&[&peer_id]);
+		let _ = self.peer_request_size.remove_label_values(&[&peer_id]);
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -325,7 +365,11 @@ pub struct ChainSync<B: BlockT, Client> {
 	/// Maximum number of peers to ask the same blocks in parallel.
 	max_parallel_downloads: u32,
 	/// Maximum blocks per request.
+	///
+	/// The actual number of blocks requested from a peer is adapted to the throughput of the peer.
 	max_blocks_per_request: u32,
+	/// Block download statistics of the peers, used to size the block requests.
+	download_stats: DownloadStats,
 	/// Protocol name used to send out state requests
 	state_request_protocol_name: ProtocolName,
 	/// Total number of downloaded blocks.
@@ -352,6 +396,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -373,6 +418,11 @@ where
 			gap_sync.blocks.clear_peer_download(peer_id)
 		}
 
+		self.download_stats.remove_peer(peer_id);
+		if let Some(metrics) = &self.metrics {
+			metrics.remove_peer(peer_id);
+		}
+
 		if let Some(state) = self.peers.remove(peer_id) {
 			if !state.state.is_available() {
 				if let Some(bad_peer) =
@@ -711,6 +761,9 @@ where
 					let state_sync_complete =
 						self.state_sync.as_ref().map_or(false, |s| s.target_hash() == hash);
 					if state_sync_complete {
+						if let Some(state_sync) = &mut self.state_sync {
+							state_sync.on_state_imported();
+						}
 						info!(
 							target: LOG_TARGET,
 							"State sync is complete ({} MiB), restarting block sync.",
@@ -932,6 +985,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -965,6 +1019,7 @@ where
 			allowed_requests: Default::default(),
 			max_parallel_downloads,
 			max_blocks_per_request,
+			download_stats: DownloadStats::new(),
 			state_request_protocol_name,
 			downloaded_blocks: 0,
 			state_sync: None,
@@ -1155,6 +1210,19 @@ where
 			}
 			self.allowed_requests.add(peer_id);
 			if let Some(request) = request {
+				if matches!(
+					peer.state,
+					PeerSyncState::DownloadingNew(_) | PeerSyncState::DownloadingGap(_)
+				) {
+					let rate =
+						self.download_stats.on_response(peer_id, blocks.len(), Instant::now());
+					if let (Some(metrics), Some(rate)) = (&self.metrics, rate) {
+						metrics
+							.peer_download_rate
+							.with_label_values(&[&peer_id.to_string()])
+							.set(rate as u64);
+					}
+				}
 				match &mut peer.state {
 					PeerSyncState::DownloadingNew(_) => {
 						self.blocks.clear_peer_download(peer_id);
@@ -1819,6 +1887,9 @@ where
 		let gap_sync = &mut self.gap_sync;
 		let disconnected_peers = &mut self.disconnected_peers;
 		let metrics = self.metrics.as_ref();
+		let now = Instant::now();
+		let stalled_peers = self.download_stats.stalled_peers(now);
+		let download_stats = &mut self.download_stats;
 		let requests = self
 			.peers
 			.iter_mut()
@@ -1855,17 +1926,42 @@ where
 						state: AncestorSearchState::ExponentialBackoff(One::one()),
 					};
 					Some((id, ancestry_request::<B>(current)))
+				} else if let Some((range, req)) = stalled_peers.iter().find_map(|stalled| {
+					if *stalled == id || !download_stats.is_faster(&id, stalled) {
+						return None
+					}
+					peer_reissued_block_request(&id, stalled, peer, blocks, attrs, max_parallel + 1)
+				}) {
+					peer.state = PeerSyncState::DownloadingNew(range.start);
+					download_stats.on_request(id, now);
+					if let Some(metrics) = metrics {
+						metrics.reissued_requests.inc();
+					}
+					debug!(
+						target: LOG_TARGET,
+						"Re-issued stalled block request to faster peer {}: {:?}",
+						id,
+						req,
+					);
+					Some((id, req))
 				} else if let Some((range, req)) = peer_block_request(
 					&id,
 					peer,
 					blocks,
 					attrs,
 					max_parallel,
-					max_blocks_per_request,
+					download_stats.request_size(&id, max_blocks_per_request),
 					last_finalized,
 					best_queued,
 				) {
 					peer.state = PeerSyncState::DownloadingNew(range.start);
+					download_stats.on_request(id, now);
+					if let Some(metrics) = metrics {
+						metrics
+							.peer_request_size
+							.with_label_values(&[&id.to_string()])
+							.set(range.end.saturating_sub(range.start).saturated_into());
+					}
 					trace!(
 						target: LOG_TARGET,
 						"New block request for {}, (best:{}, common:{}) {:?}",
@@ -1902,10 +1998,11 @@ where
 						attrs,
 						sync.target,
 						sync.best_queued_number,
-						max_blocks_per_request,
+						download_stats.request_size(&id, max_blocks_per_request),
 					)
 				}) {
 					peer.state = PeerSyncState::DownloadingGap(range.start);
+					download_stats.on_request(id, now);
 					trace!(
 						target: LOG_TARGET,
 						"New gap block request for {}, (best:{}, common:{}) {:?}",
@@ -2029,6 +2126,26 @@ where
 		finalized_number: NumberFor<B>,
 		skip_proofs: bool,
 	) {
+		// Resume an interrupted state sync instead of starting over at a newer block. Only state
+		// sync with proofs is resumed.
+		if let Some(target) = saved_state_sync_target::<B>(&*self.client).filter(|_| !skip_proofs) {
+			log::debug!(
+				target: LOG_TARGET,
+				"Resuming state sync for #{} ({})",
+				target.header.number(),
+				target.header.hash(),
+			);
+			self.state_sync = Some(StateSync::new(
+				self.client.clone(),
+				target.header,
+				target.body,
+				target.justifications,
+				skip_proofs,
+			));
+			self.allowed_requests.set_all();
+			return
+		}
+
 		let mut heads: Vec<_> = self.peers.values().map(|peer| peer.best_number).collect();
 		heads.sort();
 		let median = heads[heads.len() / 2];
@@ -2200,6 +2317,43 @@ fn peer_block_request<B: BlockT>(
 	Some((range, request))
 }
 
+/// Get a block request for the range being downloaded from the `stalled` peer, if the peer can
+/// provide it.
+fn peer_reissued_block_request<B: BlockT>(
+	id: &PeerId,
+	stalled: &PeerId,
+	peer: &PeerSync<B>,
+	blocks: &mut BlockCollection<B>,
+	attrs: BlockAttributes,
+	max_parallel_downloads: u32,
+) -> Option<(Range<NumberFor<B>>, BlockRequest<B>)> {
+	let range = blocks.reissue_range(
+		*id,
+		stalled,
+		peer.best_number,
+		peer.common_number,
+		max_parallel_downloads,
+	)?;
+
+	// The end is not part of the range.
+	let last = range.end.saturating_sub(One::one());
+	let from = if peer.best_number == last {
+		FromBlock::Hash(peer.best_hash)
+	} else {
+		FromBlock::Number(last)
+	};
+
+	let request = BlockRequest::<B> {
+		id: 0,
+		fields: attrs,
+		from,
+		direction: Direction::Descending,
+		max: Some((range.end - range.start).saturated_into::<u32>()),
+	};
+
+	Some((range, request))
+}
+
 /// Get a new block request for the peer if any.
 fn peer_gap_block_request<B: BlockT>(
 	id: &PeerId,
@@ -0,0 +1,225 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-peer block download statistics.
+//!
+//! The statistics are used to size the block requests sent to each peer according to the
+//! throughput the peer achieved so far, and to detect requests which stall, so the requested
+//! range can be re-issued to a faster peer.
+
+use sc_network_types::PeerId;
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+/// The minimum number of blocks requested from a peer.
+pub(crate) const MIN_BLOCKS_PER_REQUEST: u32 = 8;
+
+/// The duration a single block request is expected to take.
+///
+/// The request size of a peer is chosen so that, given its observed throughput, the response
+/// arrives within this duration.
+const TARGET_REQUEST_DURATION: Duration = Duration::from_secs(2);
+
+/// The minimum duration after which a request is considered stalled.
+const MIN_STALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A request is considered stalled once it takes this many times the average latency of the
+/// peer.
+const STALL_LATENCY_FACTOR: u32 = 3;
+
+/// The weight of the latest sample in the moving averages.
+const SAMPLE_WEIGHT: f64 = 0.3;
+
+/// A request in flight.
+#[derive(Debug, Clone, Copy)]
+struct InFlight {
+	/// The time the request was sent at.
+	sent_at: Instant,
+}
+
+/// Download statistics of a single peer.
+#[derive(Debug, Default, Clone)]
+struct PeerStats {
+	/// The request in flight, if any.
+	in_flight: Option<InFlight>,
+	/// Moving average of the request latency.
+	latency: Option<Duration>,
+	/// Moving average of the download throughput, in blocks per second.
+	throughput: Option<f64>,
+}
+
+impl PeerStats {
+	/// Returns the moving average updated with the given sample.
+	fn average(current: Option<f64>, sample: f64) -> f64 {
+		match current {
+			Some(current) => current + (sample - current) * SAMPLE_WEIGHT,
+			None => sample,
+		}
+	}
+}
+
+/// Block download statistics of the connected peers.
+#[derive(Debug, Default)]
+pub(crate) struct DownloadStats {
+	peers: HashMap<PeerId, PeerStats>,
+}
+
+impl DownloadStats {
+	/// Create a new `DownloadStats`.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the number of blocks which should be requested from the peer.
+	///
+	/// Peers without statistics are asked for `max_blocks_per_request` blocks.
+	pub fn request_size(&self, peer: &PeerId, max_blocks_per_request: u32) -> u32 {
+		let min = MIN_BLOCKS_PER_REQUEST.min(max_blocks_per_request);
+		match self.peers.get(peer).and_then(|stats| stats.throughput) {
+			Some(throughput) => ((throughput * TARGET_REQUEST_DURATION.as_secs_f64()) as u32)
+				.clamp(min, max_blocks_per_request),
+			None => max_blocks_per_request,
+		}
+	}
+
+	/// Register a block request sent to the peer.
+	pub fn on_request(&mut self, peer: PeerId, now: Instant) {
+		self.peers.entry(peer).or_default().in_flight = Some(InFlight { sent_at: now });
+	}
+
+	/// Register a block response received from the peer.
+	///
+	/// Returns the updated throughput of the peer, in blocks per second.
+	pub fn on_response(&mut self, peer: &PeerId, blocks: usize, now: Instant) -> Option<f64> {
+		let stats = self.peers.get_mut(peer)?;
+		let in_flight = stats.in_flight.take()?;
+
+		let elapsed = now.saturating_duration_since(in_flight.sent_at);
+		let latency = PeerStats::average(
+			stats.latency.map(|latency| latency.as_secs_f64()),
+			elapsed.as_secs_f64(),
+		);
+		stats.latency = Some(Duration::from_secs_f64(latency));
+
+		// Guard against responses received within the timer resolution.
+		let elapsed = elapsed.as_secs_f64().max(f64::EPSILON);
+		let throughput = PeerStats::average(stats.throughput, blocks as f64 / elapsed);
+		stats.throughput = Some(throughput);
+
+		Some(throughput)
+	}
+
+	/// Forget the statistics of a disconnected peer.
+	pub fn remove_peer(&mut self, peer: &PeerId) {
+		self.peers.remove(peer);
+	}
+
+	/// Returns `true` if the request in flight to the peer takes longer than expected.
+	pub fn is_stalled(&self, peer: &PeerId, now: Instant) -> bool {
+		let Some(stats) = self.peers.get(peer) else { return false };
+		let Some(in_flight) = stats.in_flight else { return false };
+
+		let timeout = stats.latency.map_or(MIN_STALL_TIMEOUT, |latency| {
+			(latency * STALL_LATENCY_FACTOR).max(MIN_STALL_TIMEOUT)
+		});
+		now.saturating_duration_since(in_flight.sent_at) > timeout
+	}
+
+	/// Returns the peers with a stalled request in flight.
+	pub fn stalled_peers(&self, now: Instant) -> Vec<PeerId> {
+		self.peers.keys().filter(|peer| self.is_stalled(peer, now)).copied().collect()
+	}
+
+	/// Returns `true` if `candidate` is expected to download faster than `stalled`.
+	///
+	/// A stalled peer which never completed a request is slower than any other peer.
+	pub fn is_faster(&self, candidate: &PeerId, stalled: &PeerId) -> bool {
+		let throughput = |peer| self.peers.get(peer).and_then(|stats| stats.throughput);
+		match (throughput(candidate), throughput(stalled)) {
+			(_, None) => true,
+			(Some(candidate), Some(stalled)) => candidate > stalled,
+			(None, Some(_)) => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unknown_peers_use_max_request_size() {
+		let stats = DownloadStats::new();
+		assert_eq!(stats.request_size(&PeerId::random(), 64), 64);
+	}
+
+	#[test]
+	fn request_size_follows_throughput() {
+		let mut stats = DownloadStats::new();
+		let (fast, slow) = (PeerId::random(), PeerId::random());
+		let now = Instant::now();
+
+		stats.on_request(fast, now);
+		stats.on_request(slow, now);
+		// 64 blocks per second.
+		stats.on_response(&fast, 64, now + Duration::from_secs(1));
+		// 5 blocks per second.
+		stats.on_response(&slow, 10, now + Duration::from_secs(2));
+
+		assert_eq!(stats.request_size(&fast, 64), 64);
+		assert_eq!(stats.request_size(&slow, 64), 10);
+		assert_eq!(stats.request_size(&slow, 4), 4);
+	}
+
+	#[test]
+	fn request_size_is_bounded_below() {
+		let mut stats = DownloadStats::new();
+		let peer = PeerId::random();
+		let now = Instant::now();
+
+		stats.on_request(peer, now);
+		stats.on_response(&peer, 0, now + Duration::from_secs(10));
+
+		assert_eq!(stats.request_size(&peer, 64), MIN_BLOCKS_PER_REQUEST);
+	}
+
+	#[test]
+	fn stalled_requests_are_detected() {
+		let mut stats = DownloadStats::new();
+		let (fast, slow) = (PeerId::random(), PeerId::random());
+		let now = Instant::now();
+
+		stats.on_request(fast, now);
+		stats.on_response(&fast, 64, now + Duration::from_secs(1));
+		stats.on_request(slow, now);
+
+		assert!(!stats.is_stalled(&slow, now + MIN_STALL_TIMEOUT));
+		assert!(stats.is_stalled(&slow, now + MIN_STALL_TIMEOUT + Duration::from_secs(1)));
+		assert_eq!(stats.stalled_peers(now + MIN_STALL_TIMEOUT * 2), vec![slow]);
+		assert!(stats.is_faster(&fast, &slow));
+		assert!(!stats.is_faster(&slow, &fast));
+
+		stats.on_response(&slow, 1, now + MIN_STALL_TIMEOUT * 2);
+		assert!(stats.stalled_peers(now + MIN_STALL_TIMEOUT * 3).is_empty());
+
+		stats.remove_peer(&slow);
+		assert!(stats.is_faster(&fast, &slow));
+	}
+}
@@ -26,6 +26,7 @@ use crate::{
 	strategy::{
 		chain_sync::{ChainSync, ChainSyncMode},
 		state::StateStrategy,
+		state_sync::saved_state_sync_target,
 		warp::{WarpSync, WarpSyncConfig},
 		StrategyKey, SyncingAction, SyncingStrategy,
 	},
@@ -34,7 +35,7 @@ use crate::{
 };
 use log::{debug, error, info, warn};
 use prometheus_endpoint::Registry;
-use sc_client_api::{BlockBackend, ProofProvider};
+use sc_client_api::{AuxStore, BlockBackend, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus};
 use sc_network::ProtocolName;
 use sc_network_common::sync::{message::BlockAnnounce, SyncMode};
@@ -99,6 +100,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -335,6 +337,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -355,6 +358,33 @@ where
 		}
 
 		if let SyncMode::Warp = config.mode {
+			// The warp proofs of an interrupted state sync target were already verified, so only
+			// the state download is resumed.
+			if let Some(target) = saved_state_sync_target::<B>(&*client) {
+				info!(
+					target: LOG_TARGET,
+					"Resuming interrupted state sync of #{}, skipping warp sync.",
+					target.header.number(),
+				);
+				let state_sync = StateStrategy::new(
+					client.clone(),
+					target.header,
+					target.body,
+					target.justifications,
+					false,
+					std::iter::empty(),
+					config.state_request_protocol_name.clone(),
+				);
+				return Ok(Self {
+					config,
+					client,
+					warp: None,
+					state: Some(state_sync),
+					chain_sync: None,
+					peer_best_blocks: Default::default(),
+				})
+			}
+
 			let warp_sync_config = warp_sync_config
 				.expect("Warp sync configuration must be supplied in warp sync mode.");
 			let warp_sync = WarpSync::new(
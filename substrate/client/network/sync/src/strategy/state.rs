@@ -32,7 +32,7 @@ use crate::{
 use futures::{channel::oneshot, FutureExt};
 use log::{debug, error, trace};
 use prost::Message;
-use sc_client_api::ProofProvider;
+use sc_client_api::{AuxStore, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus, IncomingBlock};
 use sc_network::{IfDisconnected, ProtocolName};
 use sc_network_common::sync::message::BlockAnnounce;
@@ -95,7 +95,7 @@ impl<B: BlockT> StateStrategy<B> {
 		protocol_name: ProtocolName,
 	) -> Self
 	where
-		Client: ProofProvider<B> + Send + Sync + 'static,
+		Client: ProofProvider<B> + AuxStore + Send + Sync + 'static,
 	{
 		let peers = initial_peers
 			.map(|(peer_id, best_number)| {
@@ -278,6 +278,9 @@ impl<B: BlockT> StateStrategy<B> {
 				);
 			});
 			self.succeeded |= results.into_iter().any(|result| result.is_ok());
+			if self.succeeded {
+				self.state_sync.on_state_imported();
+			}
 			self.actions.push(SyncingAction::Finished);
 		}
 	}
@@ -401,7 +404,7 @@ mod test {
 	use codec::Decode;
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_client_api::KeyValueStates;
-	use sc_consensus::{ImportedAux, ImportedState};
+	use sc_consensus::{ImportedAux, ImportedState, IncomingState};
 	use sp_core::H256;
 	use sp_runtime::traits::Zero;
 	use substrate_test_runtime_client::{
@@ -419,6 +422,7 @@ mod test {
 			fn target_number(&self) -> NumberFor<B>;
 			fn target_hash(&self) -> B::Hash;
 			fn progress(&self) -> StateSyncProgress;
+			fn on_state_imported(&mut self);
 		}
 	}
 
@@ -711,7 +715,10 @@ mod test {
 		let header = block.header().clone();
 		let hash = header.hash();
 		let body = Some(block.extrinsics().iter().cloned().collect::<Vec<_>>());
-		let state = ImportedState { block: hash, state: KeyValueStates(Vec::new()) };
+		let state = IncomingState::KeyValues(ImportedState {
+			block: hash,
+			state: KeyValueStates(Vec::new()),
+		});
 		let justifications = Some(Justifications::from((*b"FRNK", Vec::new())));
 
 		// Prepare `StateSync`
@@ -796,6 +803,7 @@ mod test {
 		let target_hash = Hash::random();
 		let mut state_sync_provider = MockStateSync::<Block>::new();
 		state_sync_provider.expect_target_hash().return_const(target_hash);
+		state_sync_provider.expect_on_state_imported().times(1).return_const(());
 
 		let mut state_strategy = StateStrategy::new_with_provider(
 			Box::new(state_sync_provider),
@@ -23,9 +23,9 @@ use crate::{
 	LOG_TARGET,
 };
 use codec::{Decode, Encode};
-use log::debug;
-use sc_client_api::{CompactProof, KeyValueStates, ProofProvider};
-use sc_consensus::ImportedState;
+use log::{debug, info, warn};
+use sc_client_api::{AuxStore, CompactProof, ProofProvider};
+use sc_consensus::{ImportedState, IncomingState};
 use smallvec::SmallVec;
 use sp_core::storage::well_known_keys;
 use sp_runtime::{
@@ -48,6 +48,9 @@ pub trait StateSyncProvider<B: BlockT>: Send + Sync {
 	fn target_hash(&self) -> B::Hash;
 	/// Returns state sync estimated progress.
 	fn progress(&self) -> StateSyncProgress;
+	/// Notify that the downloaded state was imported, so the persisted progress is not needed
+	/// anymore.
+	fn on_state_imported(&mut self);
 }
 
 // Reported state sync phase.
@@ -82,13 +85,67 @@ pub struct StateSyncProgress {
 /// Import state chunk result.
 pub enum ImportResult<B: BlockT> {
 	/// State is complete and ready for import.
-	Import(B::Hash, B::Header, ImportedState<B>, Option<Vec<B::Extrinsic>>, Option<Justifications>),
+	Import(B::Hash, B::Header, IncomingState<B>, Option<Vec<B::Extrinsic>>, Option<Justifications>),
 	/// Continue downloading.
 	Continue,
 	/// Bad state chunk.
 	BadResponse,
 }
 
+/// Auxiliary storage key of the state sync progress.
+const PROGRESS_KEY: &[u8] = b"sync_state_progress";
+
+/// State sync progress persisted to the auxiliary storage, so an interrupted state sync can be
+/// resumed after a restart.
+///
+/// The verified state is written to the state database as it is downloaded, so only the key
+/// cursor has to be persisted.
+#[derive(Encode, Decode)]
+struct SavedProgress<B: BlockT> {
+	target_header: B::Header,
+	target_body: Option<Vec<B::Extrinsic>>,
+	target_justifications: Option<Justifications>,
+	/// The key cursor of the last imported response.
+	last_key: Vec<Vec<u8>>,
+	imported_bytes: u64,
+}
+
+/// The target of an interrupted state sync which can be resumed.
+///
+/// Only state sync with proofs is resumed.
+pub struct SavedStateSyncTarget<B: BlockT> {
+	/// The header of the target block.
+	pub header: B::Header,
+	/// The body of the target block, if it was downloaded.
+	pub body: Option<Vec<B::Extrinsic>>,
+	/// The justifications of the target block, if they were downloaded.
+	pub justifications: Option<Justifications>,
+}
+
+fn load_progress<B: BlockT>(client: &impl AuxStore) -> Option<SavedProgress<B>> {
+	match client.get_aux(PROGRESS_KEY) {
+		Ok(Some(encoded)) => SavedProgress::decode(&mut &encoded[..])
+			.inspect_err(|e| warn!(target: LOG_TARGET, "Failed to decode state sync progress: {e}"))
+			.ok(),
+		Ok(None) => None,
+		Err(e) => {
+			warn!(target: LOG_TARGET, "Failed to read state sync progress: {e}");
+			None
+		},
+	}
+}
+
+/// Returns the target of an interrupted state sync stored in the auxiliary storage, if any.
+pub fn saved_state_sync_target<B: BlockT>(
+	client: &impl AuxStore,
+) -> Option<SavedStateSyncTarget<B>> {
+	load_progress::<B>(client).map(|progress| SavedStateSyncTarget {
+		header: progress.target_header,
+		body: progress.target_body,
+		justifications: progress.target_justifications,
+	})
+}
+
 struct StateSyncMetadata<B: BlockT> {
 	last_key: SmallVec<[Vec<u8>; 2]>,
 	target_header: B::Header,
@@ -138,9 +195,18 @@ impl<B: BlockT> StateSyncMetadata<B> {
 
 /// State sync state machine.
 ///
-/// Accumulates partial state data until it is ready to be imported.
+/// The trie nodes of every verified state response are written to the state database right away,
+/// and the key cursor is persisted to the auxiliary storage, so the sync of the same target
+/// resumes from the last verified key after a restart. Once the download is complete, the target
+/// block is imported on top of the already written state.
+///
+/// Without proofs there are no trie nodes to write, so the state is accumulated in memory until
+/// it is ready to be imported, and the sync starts over after a restart.
+///
+/// The trie nodes written for a target that is abandoned are left in the database.
 pub struct StateSync<B: BlockT, Client> {
 	metadata: StateSyncMetadata<B>,
+	/// The state downloaded without proofs.
 	state: HashMap<Vec<u8>, (Vec<(Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>)>,
 	client: Arc<Client>,
 }
@@ -148,9 +214,12 @@ pub struct StateSync<B: BlockT, Client> {
 impl<B, Client> StateSync<B, Client>
 where
 	B: BlockT,
-	Client: ProofProvider<B> + Send + Sync + 'static,
+	Client: ProofProvider<B> + AuxStore + Send + Sync + 'static,
 {
 	///  Create a new instance.
+	///
+	/// If the progress of a previous state sync of the same target is stored in the auxiliary
+	/// storage, the sync with proofs is resumed from it. Any other progress is discarded.
 	pub fn new(
 		client: Arc<Client>,
 		target_header: B::Header,
@@ -158,7 +227,7 @@ where
 		target_justifications: Option<Justifications>,
 		skip_proof: bool,
 	) -> Self {
-		Self {
+		let mut sync = Self {
 			client,
 			metadata: StateSyncMetadata {
 				last_key: SmallVec::default(),
@@ -170,6 +239,60 @@ where
 				skip_proof,
 			},
 			state: HashMap::default(),
+		};
+		sync.restore_progress();
+		sync
+	}
+
+	/// Restores the persisted progress of the state sync, if it has the same target.
+	fn restore_progress(&mut self) {
+		let Some(progress) = load_progress::<B>(&*self.client) else { return };
+
+		if progress.target_header.hash() != self.metadata.target_hash() || self.metadata.skip_proof
+		{
+			debug!(
+				target: LOG_TARGET,
+				"Discarding state sync progress of #{}",
+				progress.target_header.number(),
+			);
+			self.clear_progress();
+			return
+		}
+
+		self.metadata.last_key = progress.last_key.into();
+		self.metadata.imported_bytes = progress.imported_bytes;
+		info!(
+			target: LOG_TARGET,
+			"Resuming state sync of #{} ({} MiB already downloaded)",
+			self.metadata.target_number(),
+			self.metadata.imported_bytes / (1024 * 1024),
+		);
+	}
+
+	/// Persists the key cursor of the state sync.
+	///
+	/// The trie nodes of the responses up to the cursor are already written, so a failure only
+	/// means a restart resumes from an older cursor, and downloads some of the state again.
+	fn persist_progress(&self) {
+		let progress = SavedProgress::<B> {
+			target_header: self.metadata.target_header.clone(),
+			target_body: self.metadata.target_body.clone(),
+			target_justifications: self.metadata.target_justifications.clone(),
+			last_key: self.metadata.last_key.to_vec(),
+			imported_bytes: self.metadata.imported_bytes,
+		}
+		.encode();
+
+		if let Err(e) = self.client.insert_aux(&[(PROGRESS_KEY, &progress[..])], std::iter::empty())
+		{
+			warn!(target: LOG_TARGET, "Failed to persist state sync progress: {e}");
+		}
+	}
+
+	/// Removes the progress from the auxiliary storage.
+	fn clear_progress(&self) {
+		if let Err(e) = self.client.insert_aux(&[], &[PROGRESS_KEY]) {
+			warn!(target: LOG_TARGET, "Failed to clear state sync progress: {e}");
 		}
 	}
 
@@ -205,12 +328,6 @@ where
 		}
 	}
 
-	fn process_state_verified(&mut self, values: KeyValueStates) {
-		for values in values.0 {
-			self.process_state_key_values(values.state_root, values.key_values);
-		}
-	}
-
 	fn process_state_unverified(&mut self, response: StateResponse) -> bool {
 		let mut complete = true;
 		// if the trie is a child trie and one of its parent trie is empty,
@@ -251,7 +368,7 @@ where
 impl<B, Client> StateSyncProvider<B> for StateSync<B, Client>
 where
 	B: BlockT,
-	Client: ProofProvider<B> + Send + Sync + 'static,
+	Client: ProofProvider<B> + AuxStore + Send + Sync + 'static,
 {
 	///  Validate and import a state response.
 	fn import(&mut self, response: StateResponse) -> ImportResult<B> {
@@ -273,7 +390,7 @@ where
 					return ImportResult::BadResponse
 				},
 			};
-			let (values, completed) = match self.client.verify_range_proof(
+			let (values, completed) = match self.client.import_range_proof(
 				self.metadata.target_root(),
 				proof,
 				self.metadata.last_key.as_slice(),
@@ -295,19 +412,31 @@ where
 				debug!(target: LOG_TARGET, "Error updating key cursor, depth: {}", completed);
 			};
 
-			self.process_state_verified(values);
 			self.metadata.imported_bytes += proof_size;
+			if !complete {
+				self.persist_progress();
+			}
 			complete
 		} else {
 			self.process_state_unverified(response)
 		};
 		if complete {
+			// The persisted progress is kept until the state is imported, so a failed or
+			// interrupted import resumes the download from the last persisted cursor.
 			self.metadata.complete = true;
 			let target_hash = self.metadata.target_hash();
+			let state = if self.metadata.skip_proof {
+				IncomingState::KeyValues(ImportedState {
+					block: target_hash,
+					state: std::mem::take(&mut self.state).into(),
+				})
+			} else {
+				IncomingState::Imported
+			};
 			ImportResult::Import(
 				target_hash,
 				self.metadata.target_header.clone(),
-				ImportedState { block: target_hash, state: std::mem::take(&mut self.state).into() },
+				state,
 				self.metadata.target_body.clone(),
 				self.metadata.target_justifications.clone(),
 			)
@@ -340,4 +469,165 @@ where
 	fn progress(&self) -> StateSyncProgress {
 		self.metadata.progress()
 	}
+
+	/// Removes the persisted progress once the downloaded state is imported.
+	fn on_state_imported(&mut self) {
+		self.clear_progress();
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use futures::executor::block_on;
+	use sc_block_builder::BlockBuilderBuilder;
+	use sc_client_api::StorageProvider;
+	use sc_consensus::{
+		BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction, StorageChanges,
+	};
+	use sp_consensus::BlockOrigin;
+	use sp_core::storage::StorageKey;
+	use substrate_test_runtime_client::{
+		runtime::{Block, Header},
+		ClientBlockImportExt, DefaultTestClientBuilderExt, TestClient, TestClientBuilder,
+		TestClientBuilderExt,
+	};
+
+	/// Size limit of the proofs, small enough to split the state into several responses.
+	const PROOF_SIZE_LIMIT: usize = 1024;
+
+	fn response(keys: &[&str], complete: bool) -> StateResponse {
+		StateResponse {
+			entries: vec![KeyValueStateEntry {
+				state_root: Vec::new(),
+				entries: keys
+					.iter()
+					.map(|key| StateEntry {
+						key: key.as_bytes().to_vec(),
+						value: b"value".to_vec(),
+					})
+					.collect(),
+				complete,
+			}],
+			proof: Vec::new(),
+		}
+	}
+
+	/// Build a client with an imported block to download the state of.
+	fn source_client() -> (TestClient, Header) {
+		let client = TestClientBuilder::new().build();
+		let block = BlockBuilderBuilder::new(&client)
+			.on_parent_block(client.chain_info().best_hash)
+			.with_parent_block_number(client.chain_info().best_number)
+			.build()
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
+		block_on(client.import(BlockOrigin::Own, block.clone())).unwrap();
+		(client, block.header)
+	}
+
+	fn proof_response(
+		source: &TestClient,
+		target: &Header,
+		request: &StateRequest,
+	) -> StateResponse {
+		let (proof, _) = source
+			.read_proof_collection(target.hash(), &request.start, PROOF_SIZE_LIMIT)
+			.unwrap();
+		StateResponse { entries: Vec::new(), proof: proof.encode() }
+	}
+
+	#[test]
+	fn verified_state_sync_resumes_from_persisted_progress() {
+		let (source, target_header) = source_client();
+		let client = Arc::new(TestClientBuilder::new().build());
+
+		let mut state_sync =
+			StateSync::<Block, _>::new(client.clone(), target_header.clone(), None, None, false);
+		let response = proof_response(&source, &target_header, &state_sync.next_request());
+		assert!(matches!(state_sync.import(response), ImportResult::Continue));
+		let expected_request = state_sync.next_request();
+		let expected_progress = state_sync.progress();
+		assert!(!expected_request.start.is_empty());
+		drop(state_sync);
+
+		// Progress of the same target is restored.
+		let saved = saved_state_sync_target::<Block>(&*client).unwrap();
+		assert_eq!(saved.header, target_header);
+		let mut state_sync =
+			StateSync::<Block, _>::new(client.clone(), target_header.clone(), None, None, false);
+		assert_eq!(state_sync.next_request(), expected_request);
+		assert_eq!(state_sync.progress(), expected_progress);
+
+		loop {
+			let response = proof_response(&source, &target_header, &state_sync.next_request());
+			match state_sync.import(response) {
+				ImportResult::Continue => continue,
+				ImportResult::Import(hash, _, state, _, _) => {
+					assert_eq!(hash, target_header.hash());
+					assert_eq!(state, IncomingState::Imported);
+					break
+				},
+				ImportResult::BadResponse => panic!("Proof must be valid"),
+			}
+		}
+		// The progress is kept until the state is imported.
+		assert!(saved_state_sync_target::<Block>(&*client).is_some());
+
+		// The downloaded trie nodes are enough to import the block.
+		let mut block =
+			BlockImportParams::new(BlockOrigin::NetworkInitialSync, target_header.clone());
+		block.state_action = StateAction::ApplyChanges(StorageChanges::Imported);
+		block.finalized = true;
+		block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+		block_on(client.import_block(block)).unwrap();
+		state_sync.on_state_imported();
+		assert!(saved_state_sync_target::<Block>(&*client).is_none());
+
+		let code = StorageKey(well_known_keys::CODE.to_vec());
+		assert_eq!(
+			client.storage(target_header.hash(), &code).unwrap(),
+			source.storage(target_header.hash(), &code).unwrap(),
+		);
+	}
+
+	#[test]
+	fn progress_of_other_target_is_discarded() {
+		let (source, target_header) = source_client();
+		let client = Arc::new(TestClientBuilder::new().build());
+		let mut other_header = target_header.clone();
+		other_header.number += 1;
+
+		let mut state_sync =
+			StateSync::<Block, _>::new(client.clone(), target_header.clone(), None, None, false);
+		let response = proof_response(&source, &target_header, &state_sync.next_request());
+		assert!(matches!(state_sync.import(response), ImportResult::Continue));
+
+		let state_sync =
+			StateSync::<Block, _>::new(client.clone(), other_header, None, None, false);
+		assert_eq!(state_sync.next_request().start, Vec::<Vec<u8>>::new());
+		assert!(saved_state_sync_target::<Block>(&*client).is_none());
+	}
+
+	#[test]
+	fn state_sync_without_proofs_is_not_persisted() {
+		let (_, target_header) = source_client();
+		let client = Arc::new(TestClientBuilder::new().build());
+
+		let mut state_sync =
+			StateSync::<Block, _>::new(client.clone(), target_header.clone(), None, None, true);
+		assert!(matches!(state_sync.import(response(&["a", "b"], false)), ImportResult::Continue));
+		assert!(saved_state_sync_target::<Block>(&*client).is_none());
+
+		match state_sync.import(response(&["c"], true)) {
+			ImportResult::Import(hash, _, IncomingState::KeyValues(state), _, _) => {
+				assert_eq!(hash, target_header.hash());
+				let keys = state.state.0.into_iter().flat_map(|level| level.key_values).count();
+				assert_eq!(keys, 3);
+			},
+			_ => panic!("State sync must be complete"),
+		}
+	}
 }
@@ -222,11 +222,13 @@ mod tests {
 	use super::*;
 	use sc_client_db::offchain::LocalStorage;
 	use sc_network::{
-		config::MultiaddrWithPeerId, types::ProtocolName, NetworkPeers, NetworkStateInfo,
-		ObservedRole, ReputationChange,
+		config::MultiaddrWithPeerId,
+		peer_store::{BanTarget, BannedPeer},
+		types::ProtocolName,
+		NetworkPeers, NetworkStateInfo, ObservedRole, ReputationChange,
 	};
 	use sp_core::offchain::{storage::OffchainDb, DbExternalities, Externalities, StorageKind};
-	use std::time::SystemTime;
+	use std::time::{Duration, SystemTime};
 
 	pub(super) struct TestNetwork();
 
@@ -307,6 +309,18 @@ mod tests {
 		async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
 			unimplemented!();
 		}
+
+		fn ban_peer(&self, _target: BanTarget, _duration: Duration) {
+			unimplemented!();
+		}
+
+		fn unban_peer(&self, _target: &BanTarget) -> bool {
+			unimplemented!();
+		}
+
+		fn banned_peers(&self) -> Vec<BannedPeer> {
+			unimplemented!();
+		}
 	}
 
 	impl NetworkStateInfo for TestNetwork {
@@ -330,14 +330,17 @@ mod tests {
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_client_api::Backend as _;
 	use sc_network::{
-		config::MultiaddrWithPeerId, types::ProtocolName, Multiaddr, ObservedRole, ReputationChange,
+		config::MultiaddrWithPeerId,
+		peer_store::{BanTarget, BannedPeer},
+		types::ProtocolName,
+		Multiaddr, ObservedRole, ReputationChange,
 	};
 	use sc_network_types::PeerId;
 	use sc_transaction_pool::BasicPool;
 	use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
 	use sp_consensus::BlockOrigin;
 	use sp_runtime::traits::Block as BlockT;
-	use std::{collections::HashSet, sync::Arc};
+	use std::{collections::HashSet, sync::Arc, time::Duration};
 	use substrate_test_runtime_client::{
 		runtime::{
 			substrate_test_pallet::pallet::Call as PalletCall, ExtrinsicBuilder, RuntimeCall,
@@ -438,6 +441,18 @@ mod tests {
 		async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
 			unimplemented!();
 		}
+
+		fn ban_peer(&self, _target: BanTarget, _duration: Duration) {
+			unimplemented!();
+		}
+
+		fn unban_peer(&self, _target: &BanTarget) -> bool {
+			unimplemented!();
+		}
+
+		fn banned_peers(&self) -> Vec<BannedPeer> {
+			unimplemented!();
+		}
 	}
 
 	#[test]
@@ -0,0 +1,97 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Connection-related types.
+//!
+//! Contains a `ConnectionClosed` type that can be used by the RPC methods to release the
+//! resources held for a connection once it is closed.
+
+use std::sync::{Arc, Mutex};
+
+/// A callback run when the connection is closed.
+type OnClose = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct Inner {
+	closed: bool,
+	callbacks: Vec<OnClose>,
+}
+
+/// Notifies the RPC methods that the connection which issued the call was closed.
+///
+/// The substrate rpc server sets this extension for WebSocket connections only, since HTTP
+/// connections do not outlive the call.
+#[derive(Clone, Default)]
+pub struct ConnectionClosed(Arc<Mutex<Inner>>);
+
+impl ConnectionClosed {
+	/// Run the given callback when the connection is closed, or immediately if it already is.
+	pub fn on_close(&self, callback: impl FnOnce() + Send + 'static) {
+		let mut inner = self.0.lock().expect("Lock is never poisoned; qed");
+		if inner.closed {
+			drop(inner);
+			callback();
+		} else {
+			inner.callbacks.push(Box::new(callback));
+		}
+	}
+
+	/// Mark the connection as closed, and run the registered callbacks.
+	pub fn close(&self) {
+		let callbacks = {
+			let mut inner = self.0.lock().expect("Lock is never poisoned; qed");
+			inner.closed = true;
+			std::mem::take(&mut inner.callbacks)
+		};
+		callbacks.into_iter().for_each(|callback| callback());
+	}
+}
+
+impl std::fmt::Debug for ConnectionClosed {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ConnectionClosed").finish_non_exhaustive()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	#[test]
+	fn callbacks_run_once_on_close() {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let closed = ConnectionClosed::default();
+
+		let c = calls.clone();
+		closed.on_close(move || {
+			c.fetch_add(1, Ordering::SeqCst);
+		});
+		assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+		closed.close();
+		closed.close();
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+		// Callbacks registered after the close run immediately.
+		let c = calls.clone();
+		closed.on_close(move || {
+			c.fetch_add(1, Ordering::SeqCst);
+		});
+		assert_eq!(calls.load(Ordering::SeqCst), 2);
+	}
+}
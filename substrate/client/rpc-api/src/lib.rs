@@ -22,9 +22,11 @@
 
 #![warn(missing_docs)]
 
+mod connection;
 mod error;
 mod policy;
 
+pub use connection::ConnectionClosed;
 pub use policy::{check_if_safe, DenyUnsafe, UnsafeRpcError};
 
 pub mod author;
@@ -33,6 +35,8 @@ pub mod child_state;
 pub mod dev;
 pub mod mixnet;
 pub mod offchain;
+pub mod snapshot;
 pub mod state;
 pub mod statement;
 pub mod system;
+pub mod txpool;
@@ -0,0 +1,48 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate snapshot API.
+
+pub mod error;
+
+use error::Error;
+use jsonrpsee::proc_macros::rpc;
+use serde::{Deserialize, Serialize};
+
+/// Block a snapshot was taken at, returned by the `snapshot_export` RPC.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedSnapshot<Hash, Number> {
+	/// Hash of the snapshotted block.
+	pub hash: Hash,
+	/// Number of the snapshotted block.
+	pub number: Number,
+}
+
+/// Substrate snapshot RPC API
+#[rpc(client, server)]
+pub trait SnapshotApi<Hash, Number> {
+	/// Export a snapshot of the last finalized block to `path` on the file system of the node.
+	///
+	/// The snapshot has the format written by the `export-snapshot` command and is read by the
+	/// `import-snapshot` command. The node keeps running while the snapshot is exported, the
+	/// snapshotted block is pinned until the export is complete. The file is overwritten if it
+	/// already exists.
+	#[method(name = "snapshot_export", blocking, with_extensions)]
+	fn export(&self, path: String) -> Result<ExportedSnapshot<Hash, Number>, Error>;
+}
@@ -80,6 +80,16 @@ pub enum NodeRole {
 	Authority,
 }
 
+/// Manual ban of a peer or an IP network.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedPeer {
+	/// Banned PeerId or IP network in CIDR notation
+	pub target: String,
+	/// Unix time in seconds when the ban expires
+	pub until: u64,
+}
+
 /// The state of the syncing of the node.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -145,4 +155,16 @@ mod tests {
 			r#"{"startingBlock":12,"currentBlock":50,"highestBlock":50}"#,
 		);
 	}
+
+	#[test]
+	fn should_serialize_banned_peer() {
+		assert_eq!(
+			::serde_json::to_string(&BannedPeer {
+				target: "192.0.2.0/24".into(),
+				until: 1_700_000_000,
+			})
+			.unwrap(),
+			r#"{"target":"192.0.2.0/24","until":1700000000}"#,
+		);
+	}
 }
@@ -23,7 +23,7 @@ pub mod helpers;
 
 use jsonrpsee::{core::JsonValue, proc_macros::rpc};
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{BannedPeer, Health, NodeRole, PeerInfo, SyncState, SystemInfo};
 pub use error::Error;
 
 /// Substrate system RPC API
@@ -98,6 +98,22 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_reservedPeers")]
 	async fn system_reserved_peers(&self) -> Result<Vec<String>, Error>;
 
+	/// Bans a peer or an IP network for `duration` seconds.
+	///
+	/// The target is either a PeerId e.g. `QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV`, an
+	/// IP address or an IP network in CIDR notation e.g. `198.51.100.0/24`. Matching peers are
+	/// disconnected and refused until the ban expires or is lifted. Bans survive node restarts.
+	#[method(name = "system_banPeer", with_extensions)]
+	async fn system_ban_peer(&self, target: String, duration: u64) -> Result<(), Error>;
+
+	/// Lifts a ban set with `system_banPeer`. Returns `false` if the target wasn't banned.
+	#[method(name = "system_unbanPeer", with_extensions)]
+	async fn system_unban_peer(&self, target: String) -> Result<bool, Error>;
+
+	/// Returns the bans currently in effect.
+	#[method(name = "system_bannedPeers", with_extensions)]
+	async fn system_banned_peers(&self) -> Result<Vec<BannedPeer>, Error>;
+
 	/// Returns the roles the node is running as.
 	#[method(name = "system_nodeRoles")]
 	async fn system_node_roles(&self) -> Result<Vec<NodeRole>, Error>;
@@ -0,0 +1,164 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate transaction pool API allowing to inspect the pool content and to manually evict
+//! transactions. The endpoints in this RPC module are not meant to be available to non-local
+//! users and are all marked `unsafe`.
+
+pub mod error;
+
+use error::Error;
+use jsonrpsee::proc_macros::rpc;
+use sc_transaction_pool_api::{PooledTransactionInfo, PooledTransactionQueue, TransactionSource};
+use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
+use std::time::UNIX_EPOCH;
+
+/// The source a pooled transaction was submitted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+	/// Transaction was re-submitted from a retracted block.
+	InBlock,
+	/// Transaction was submitted by the node itself.
+	Local,
+	/// Transaction was received from the network or the RPC.
+	External,
+}
+
+impl From<TransactionSource> for Source {
+	fn from(source: TransactionSource) -> Self {
+		match source {
+			TransactionSource::InBlock => Self::InBlock,
+			TransactionSource::Local => Self::Local,
+			TransactionSource::External => Self::External,
+		}
+	}
+}
+
+/// The queue of a view which holds a pooled transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Queue {
+	/// The transaction is ready to be included in a block.
+	Ready,
+	/// The transaction waits for the tags it requires.
+	Future,
+}
+
+impl From<PooledTransactionQueue> for Queue {
+	fn from(queue: PooledTransactionQueue) -> Self {
+		match queue {
+			PooledTransactionQueue::Ready => Self::Ready,
+			PooledTransactionQueue::Future => Self::Future,
+		}
+	}
+}
+
+/// The placement of a pooled transaction in a view of the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewPlacement<BlockHash> {
+	/// The block the view was built at.
+	pub block_hash: BlockHash,
+	/// The queue holding the transaction.
+	pub queue: Queue,
+}
+
+/// A transaction in the pool returned by the `txpool_content` RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolTransaction<Hash, BlockHash> {
+	/// Hash of the transaction.
+	pub hash: Hash,
+	/// Size of the transaction encoding in bytes.
+	pub bytes: u64,
+	/// Priority of the transaction, if it was already validated.
+	pub priority: Option<u64>,
+	/// Block number at which the transaction becomes invalid, if it was already validated.
+	pub valid_till: Option<u64>,
+	/// Tags required by the transaction.
+	pub requires: Vec<Bytes>,
+	/// Tags provided by the transaction.
+	pub provides: Vec<Bytes>,
+	/// The source the transaction was submitted from.
+	pub source: Source,
+	/// The time the transaction was submitted at, in milliseconds since the UNIX epoch.
+	pub submitted_at: Option<u64>,
+	/// Indicates if the transaction is watched.
+	pub watched: bool,
+	/// The views holding the transaction.
+	///
+	/// Empty if the transaction was not yet validated against any view.
+	pub views: Vec<ViewPlacement<BlockHash>>,
+}
+
+impl<Hash, BlockHash> From<PooledTransactionInfo<Hash, BlockHash>>
+	for PoolTransaction<Hash, BlockHash>
+{
+	fn from(info: PooledTransactionInfo<Hash, BlockHash>) -> Self {
+		Self {
+			hash: info.hash,
+			bytes: info.bytes as u64,
+			priority: info.priority,
+			valid_till: info.valid_till,
+			requires: info.requires.into_iter().map(Into::into).collect(),
+			provides: info.provides.into_iter().map(Into::into).collect(),
+			source: info.source.into(),
+			submitted_at: info
+				.submitted_at
+				.and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+				.map(|since_epoch| since_epoch.as_millis() as u64),
+			watched: info.watched,
+			views: info
+				.views
+				.into_iter()
+				.map(|(block_hash, queue)| ViewPlacement { block_hash, queue: queue.into() })
+				.collect(),
+		}
+	}
+}
+
+/// Substrate transaction pool API.
+///
+/// This API exposes the pool internals and allows to evict transactions, so all of its methods
+/// are flagged as unsafe.
+#[rpc(client, server)]
+pub trait TxPoolApi<Hash, BlockHash> {
+	/// Returns all transactions in the pool.
+	#[method(name = "txpool_content", with_extensions)]
+	fn content(&self) -> Result<Vec<PoolTransaction<Hash, BlockHash>>, Error>;
+
+	/// Returns the transaction with the given hash, if it is in the pool.
+	#[method(name = "txpool_transaction", with_extensions)]
+	fn transaction(&self, hash: Hash) -> Result<Option<PoolTransaction<Hash, BlockHash>>, Error>;
+
+	/// Removes the transactions with the given hashes from the pool.
+	///
+	/// Transactions depending on the removed ones are removed as well. Returns the hashes of all
+	/// removed transactions.
+	#[method(name = "txpool_removeByHash", with_extensions)]
+	fn remove_by_hash(&self, hashes: Vec<Hash>) -> Result<Vec<Hash>, Error>;
+
+	/// Removes the transactions providing the given tag from the pool.
+	///
+	/// Transactions depending on the removed ones are removed as well. Returns the hashes of all
+	/// removed transactions.
+	#[method(name = "txpool_removeByTag", with_extensions)]
+	fn remove_by_tag(&self, tag: Bytes) -> Result<Vec<Hash>, Error>;
+}
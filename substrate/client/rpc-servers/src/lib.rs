@@ -32,6 +32,7 @@ use jsonrpsee::{
 	},
 	Methods, RpcModule,
 };
+use sc_rpc_api::ConnectionClosed;
 use tower::Service;
 use utils::{
 	build_rpc_api, deny_unsafe, format_listen_addrs, get_proxy_ip, ListenAddrError, RpcSettings,
@@ -237,6 +238,11 @@ where
 
 						let is_websocket = ws::is_upgrade_request(&req);
 						let transport_label = if is_websocket { "ws" } else { "http" };
+						let connection_closed = is_websocket.then(|| {
+							let connection_closed = ConnectionClosed::default();
+							req.extensions_mut().insert(connection_closed.clone());
+							connection_closed
+						});
 
 						let middleware_layer = match (metrics, rate_limit_cfg) {
 							(None, None) => None,
@@ -269,6 +275,9 @@ where
 									let now = std::time::Instant::now();
 									middleware_layer.as_ref().map(|m| m.ws_connect());
 									on_disconnect.await;
+									if let Some(connection_closed) = connection_closed {
+										connection_closed.close();
+									}
 									middleware_layer.as_ref().map(|m| m.ws_disconnect(now));
 								});
 							}
@@ -21,7 +21,7 @@
 use crate::{
 	archive::{
 		error::{Error, Infallible},
-		types::MethodResult,
+		types::{IndexQueryResult, MethodResult},
 	},
 	common::events::{
 		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageEvent, StorageQuery,
@@ -130,4 +130,41 @@ pub trait ArchiveApi<Hash> {
 		items: Vec<ArchiveStorageDiffItem<String>>,
 		previous_hash: Option<Hash>,
 	);
+
+	/// Find the events emitted by the pallet `pallet` with the name `event`.
+	///
+	/// Returns up to `limit` occurrences, starting at the position `cursor` of the list of all
+	/// occurrences in finalized blocks. Use the `nextCursor` of the result to fetch the next page.
+	///
+	/// Requires the node to index events, otherwise an error is returned.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and can change in minor or patch releases.
+	#[method(name = "archive_v1_findEvents")]
+	fn archive_v1_find_events(
+		&self,
+		pallet: String,
+		event: String,
+		cursor: Option<u64>,
+		limit: Option<u32>,
+	) -> Result<IndexQueryResult, Error>;
+
+	/// Find the extrinsics signed by the hexadecimal-encoded SCALE-encoded address `signer`.
+	///
+	/// Returns up to `limit` occurrences, starting at the position `cursor` of the list of all
+	/// occurrences in finalized blocks. Use the `nextCursor` of the result to fetch the next page.
+	///
+	/// Requires the node to index extrinsics, otherwise an error is returned.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and can change in minor or patch releases.
+	#[method(name = "archive_v1_findExtrinsics")]
+	fn archive_v1_find_extrinsics(
+		&self,
+		signer: String,
+		cursor: Option<u64>,
+		limit: Option<u32>,
+	) -> Result<IndexQueryResult, Error>;
 }
@@ -20,9 +20,10 @@
 
 use crate::{
 	archive::{
+		archive_index::{ArchiveIndex, IndexKey},
 		archive_storage::ArchiveStorageDiff,
 		error::{Error as ArchiveError, Infallible},
-		types::MethodResult,
+		types::{IndexQueryResult, MethodResult},
 		ArchiveApiServer,
 	},
 	common::{
@@ -63,6 +64,12 @@ pub(crate) const LOG_TARGET: &str = "rpc-spec-v2::archive";
 /// its down buffer capacity per connection as well.
 const STORAGE_QUERY_BUF: usize = 16;
 
+/// The number of index items returned by a query if no limit is given.
+const INDEX_QUERY_DEFAULT_LIMIT: u32 = 64;
+
+/// The maximum number of index items returned by a query.
+const INDEX_QUERY_MAX_LIMIT: u32 = 256;
+
 /// An API for archive RPC calls.
 pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	/// Substrate client.
@@ -73,6 +80,8 @@ pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	executor: SubscriptionTaskExecutor,
 	/// The hexadecimal encoded hash of the genesis block.
 	genesis_hash: String,
+	/// The index of events and extrinsics, if enabled.
+	index: Option<Arc<ArchiveIndex<BE, Block, Client>>>,
 	/// Phantom member to pin the block type.
 	_phantom: PhantomData<Block>,
 }
@@ -86,7 +95,47 @@ impl<BE: Backend<Block>, Block: BlockT, Client> Archive<BE, Block, Client> {
 		executor: SubscriptionTaskExecutor,
 	) -> Self {
 		let genesis_hash = hex_string(&genesis_hash.as_ref());
-		Self { client, backend, executor, genesis_hash, _phantom: PhantomData }
+		Self { client, backend, executor, genesis_hash, index: None, _phantom: PhantomData }
+	}
+
+	/// Serve the `archive_v1_findEvents` and `archive_v1_findExtrinsics` queries from `index`.
+	///
+	/// The index must be kept up to date by running [`ArchiveIndex::run`].
+	pub fn with_index(mut self, index: Arc<ArchiveIndex<BE, Block, Client>>) -> Self {
+		self.index = Some(index);
+		self
+	}
+
+	/// Query `key` from the index.
+	fn query_index(
+		&self,
+		key: IndexKey,
+		cursor: Option<u64>,
+		limit: Option<u32>,
+	) -> Result<IndexQueryResult, ArchiveError>
+	where
+		Block: 'static,
+		BE: 'static,
+		Client: BlockBackend<Block>
+			+ ExecutorProvider<Block>
+			+ HeaderBackend<Block>
+			+ BlockchainEvents<Block>
+			+ CallApiAt<Block>
+			+ StorageProvider<Block, BE>
+			+ 'static,
+	{
+		let Some(index) = &self.index else { return Err(ArchiveError::IndexDisabled) };
+
+		let limit = limit.unwrap_or(INDEX_QUERY_DEFAULT_LIMIT);
+		if limit == 0 || limit > INDEX_QUERY_MAX_LIMIT {
+			return Err(ArchiveError::InvalidParam(format!(
+				"limit must be between 1 and {INDEX_QUERY_MAX_LIMIT}"
+			)))
+		}
+
+		index
+			.query(&key, cursor.unwrap_or_default(), limit)
+			.map_err(ArchiveError::Index)
 	}
 }
 
@@ -214,6 +263,26 @@ where
 		})
 	}
 
+	fn archive_v1_find_events(
+		&self,
+		pallet: String,
+		event: String,
+		cursor: Option<u64>,
+		limit: Option<u32>,
+	) -> Result<IndexQueryResult, ArchiveError> {
+		self.query_index(IndexKey::Event { pallet, event }, cursor, limit)
+	}
+
+	fn archive_v1_find_extrinsics(
+		&self,
+		signer: String,
+		cursor: Option<u64>,
+		limit: Option<u32>,
+	) -> Result<IndexQueryResult, ArchiveError> {
+		let signer = parse_hex_param(signer)?;
+		self.query_index(IndexKey::Signer(signer), cursor, limit)
+	}
+
 	fn archive_v1_storage(
 		&self,
 		pending: PendingSubscriptionSink,
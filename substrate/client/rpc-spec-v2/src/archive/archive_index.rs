@@ -0,0 +1,581 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Node-side index of the events and extrinsics of finalized blocks.
+//!
+//! The index maps every `(pallet, event)` pair and every extrinsic signer to the list of
+//! places where they occur. It is filled in the background as blocks are finalized, and is
+//! stored in the auxiliary database of the backend, so it survives restarts.
+//!
+//! Events and extrinsics are decoded with the help of the runtime metadata (version 15), which
+//! keeps the index independent of the runtime. Items that can't be decoded are skipped, and
+//! recorded in a separate list that is returned by [`ArchiveIndex::skipped`].
+
+use crate::{
+	archive::{
+		archive::LOG_TARGET,
+		types::{IndexQueryResult, IndexedItem},
+	},
+	hex_string,
+};
+
+use codec::{Compact, Decode, Encode};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use sc_client_api::{
+	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutorProvider, StorageKey,
+	StorageProvider,
+};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_api::{CallApiAt, CallContext};
+use sp_blockchain::HeaderBackend;
+use sp_core::twox_128;
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT, NumberFor},
+	SaturatedConversion,
+};
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+/// Prefix of all the keys the index stores in the auxiliary database.
+const INDEX_PREFIX: &[u8] = b"archive_index";
+/// Key under which the height of the last indexed block is stored.
+const INDEXED_HEIGHT_KEY: &[u8] = b"archive_index_height";
+/// The number of items stored in a single entry of the auxiliary database.
+const PAGE_SIZE: u64 = 64;
+/// The metadata version used to decode events and extrinsics.
+const METADATA_VERSION: u32 = 15;
+
+/// A key of the index.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum IndexKey {
+	/// An event, identified by the name of the pallet and the name of the event.
+	Event { pallet: String, event: String },
+	/// The SCALE encoded address that signed an extrinsic.
+	Signer(Vec<u8>),
+	/// An event or an extrinsic that couldn't be decoded.
+	Skipped,
+}
+
+impl IndexKey {
+	fn prefix(&self) -> Vec<u8> {
+		let mut key = INDEX_PREFIX.to_vec();
+		match self {
+			Self::Event { pallet, event } => {
+				key.push(0);
+				(pallet, event).encode_to(&mut key);
+			},
+			Self::Signer(signer) => {
+				key.push(1);
+				signer.encode_to(&mut key);
+			},
+			Self::Skipped => key.push(2),
+		}
+		key
+	}
+
+	fn len_key(&self) -> Vec<u8> {
+		let mut key = self.prefix();
+		key.extend_from_slice(b"len");
+		key
+	}
+
+	fn page_key(&self, page: u64) -> Vec<u8> {
+		let mut key = self.prefix();
+		key.extend_from_slice(b"page");
+		key.extend_from_slice(&page.to_be_bytes());
+		key
+	}
+}
+
+/// An item of the index, as stored in the auxiliary database.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+struct Entry<Hash> {
+	number: u64,
+	hash: Hash,
+	extrinsic_index: Option<u32>,
+	event_index: Option<u32>,
+}
+
+/// The parts of the runtime metadata needed to decode events and extrinsics.
+struct IndexMetadata {
+	spec_version: u32,
+	types: PortableRegistry,
+	event_ty: u32,
+	address_ty: u32,
+}
+
+/// Indexes the events and extrinsics of finalized blocks.
+///
+/// Use [`ArchiveIndex::run`] to keep the index up to date, and pass it to
+/// [`Archive::with_index`](crate::archive::Archive::with_index) to serve queries from it.
+pub struct ArchiveIndex<BE, Block: BlockT, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Backend of the chain.
+	backend: Arc<BE>,
+	/// The metadata of the runtime the last block was indexed with.
+	metadata: Mutex<Option<Arc<IndexMetadata>>>,
+	/// Phantom member to pin the block type.
+	_phantom: PhantomData<Block>,
+}
+
+impl<BE, Block, Client> ArchiveIndex<BE, Block, Client>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: BlockBackend<Block>
+		+ ExecutorProvider<Block>
+		+ HeaderBackend<Block>
+		+ BlockchainEvents<Block>
+		+ CallApiAt<Block>
+		+ StorageProvider<Block, BE>
+		+ 'static,
+{
+	/// Create a new [`ArchiveIndex`].
+	pub fn new(client: Arc<Client>, backend: Arc<BE>) -> Self {
+		Self { client, backend, metadata: Mutex::new(None), _phantom: PhantomData }
+	}
+
+	/// Index all finalized blocks, and keep on indexing newly finalized blocks.
+	///
+	/// Indexing resumes from the last indexed block after a restart.
+	pub async fn run(self: Arc<Self>) {
+		let mut finality_stream = self.client.finality_notification_stream();
+		self.catch_up(self.client.info().finalized_number);
+
+		while let Some(notification) = finality_stream.next().await {
+			self.catch_up(*notification.header.number());
+		}
+	}
+
+	/// The height of the last indexed block, or `None` if no block was indexed yet.
+	pub fn indexed_height(&self) -> Option<u64> {
+		let encoded = self.backend.get_aux(INDEXED_HEIGHT_KEY).ok()??;
+		u64::decode(&mut &encoded[..]).ok()
+	}
+
+	/// Returns up to `limit` events and extrinsics that were skipped because they couldn't be
+	/// decoded, starting at the position `start`.
+	///
+	/// The events of a block are encoded back to back, so the events following an undecodable
+	/// one are skipped as well: only the first one is returned.
+	pub fn skipped(&self, start: u64, limit: u32) -> Result<IndexQueryResult, String> {
+		self.query(&IndexKey::Skipped, start, limit)
+	}
+
+	/// Returns up to `limit` items stored under `key`, starting at the position `start`.
+	pub(crate) fn query(
+		&self,
+		key: &IndexKey,
+		start: u64,
+		limit: u32,
+	) -> Result<IndexQueryResult, String> {
+		let len = self.len(key)?;
+		let end = len.min(start.saturating_add(limit as u64));
+
+		let mut items = Vec::new();
+		let mut position = start;
+		while position < end {
+			let page = self.page(key, position / PAGE_SIZE)?;
+			let offset = (position % PAGE_SIZE) as usize;
+			let take = ((end - position) as usize).min(page.len().saturating_sub(offset));
+			if take == 0 {
+				return Err(format!("Index page {} is missing", position / PAGE_SIZE))
+			}
+
+			items.extend(page[offset..offset + take].iter().map(|entry| IndexedItem {
+				block_hash: hex_string(&entry.hash.as_ref()),
+				block_number: entry.number,
+				extrinsic_index: entry.extrinsic_index,
+				event_index: entry.event_index,
+			}));
+			position += take as u64;
+		}
+
+		Ok(IndexQueryResult {
+			items,
+			next_cursor: (end < len).then_some(end),
+			indexed_height: self.indexed_height(),
+		})
+	}
+
+	/// Index all blocks up to and including the `finalized` one.
+	fn catch_up(&self, finalized: NumberFor<Block>) {
+		let finalized: u64 = finalized.saturated_into();
+		let mut next = self.indexed_height().map_or(0, |height| height + 1);
+
+		while next <= finalized {
+			let hash = match self.client.hash(next.saturated_into()) {
+				Ok(Some(hash)) => hash,
+				Ok(None) => {
+					log::warn!(target: LOG_TARGET, "Indexing stopped: block #{next} not found");
+					return
+				},
+				Err(error) => {
+					log::warn!(target: LOG_TARGET, "Indexing stopped at #{next}: {error}");
+					return
+				},
+			};
+
+			if let Err(error) = self.index_block(next, hash) {
+				log::warn!(target: LOG_TARGET, "Indexing stopped at #{next} ({hash}): {error}");
+				return
+			}
+			next += 1;
+		}
+	}
+
+	/// Index the block `hash` at height `number`.
+	fn index_block(&self, number: u64, hash: Block::Hash) -> Result<(), String> {
+		let mut entries = BTreeMap::<IndexKey, Vec<Entry<Block::Hash>>>::new();
+
+		match self.metadata_at(hash) {
+			Ok(Some(metadata)) => {
+				self.index_extrinsics(&metadata, number, hash, &mut entries)?;
+				self.index_events(&metadata, number, hash, &mut entries)?;
+			},
+			Ok(None) => log::debug!(
+				target: LOG_TARGET,
+				"Metadata V{METADATA_VERSION} not available, skipping block #{number}",
+			),
+			Err(error) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Skipping block #{number} ({hash}), failed to get its metadata: {error}",
+				);
+				entries.entry(IndexKey::Skipped).or_default().push(Entry {
+					number,
+					hash,
+					extrinsic_index: None,
+					event_index: None,
+				});
+			},
+		}
+
+		let mut insert = Vec::new();
+		for (key, new_entries) in entries {
+			let mut len = self.len(&key)?;
+			let mut page_number = len / PAGE_SIZE;
+			let mut page = self.page(&key, page_number)?;
+
+			for entry in new_entries {
+				if len / PAGE_SIZE != page_number {
+					insert.push((key.page_key(page_number), page.encode()));
+					page_number = len / PAGE_SIZE;
+					page = Vec::new();
+				}
+				page.push(entry);
+				len += 1;
+			}
+			insert.push((key.page_key(page_number), page.encode()));
+			insert.push((key.len_key(), len.encode()));
+		}
+		insert.push((INDEXED_HEIGHT_KEY.to_vec(), number.encode()));
+
+		let insert = insert.iter().map(|(key, value)| (&key[..], &value[..])).collect::<Vec<_>>();
+		self.backend.insert_aux(&insert, &[]).map_err(|error| error.to_string())
+	}
+
+	/// Index the signers of the extrinsics of the block.
+	fn index_extrinsics(
+		&self,
+		metadata: &IndexMetadata,
+		number: u64,
+		hash: Block::Hash,
+		entries: &mut BTreeMap<IndexKey, Vec<Entry<Block::Hash>>>,
+	) -> Result<(), String> {
+		let Some(block) = self.client.block(hash).map_err(|error| error.to_string())? else {
+			return Err("Block body not found".into())
+		};
+
+		for (index, extrinsic) in block.block.extrinsics().iter().enumerate() {
+			let encoded = extrinsic.encode();
+			let key = match decode_signer(metadata, &encoded) {
+				Ok(Some(signer)) => IndexKey::Signer(signer),
+				Ok(None) => continue,
+				Err(error) => {
+					log::warn!(
+						target: LOG_TARGET,
+						"Skipping extrinsic {index} of block #{number} ({hash}): {error}",
+					);
+					IndexKey::Skipped
+				},
+			};
+
+			entries.entry(key).or_default().push(Entry {
+				number,
+				hash,
+				extrinsic_index: Some(index as u32),
+				event_index: None,
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Index the events emitted in the block.
+	fn index_events(
+		&self,
+		metadata: &IndexMetadata,
+		number: u64,
+		hash: Block::Hash,
+		entries: &mut BTreeMap<IndexKey, Vec<Entry<Block::Hash>>>,
+	) -> Result<(), String> {
+		let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+		let Some(events) = self.client.storage(hash, &key).map_err(|error| error.to_string())?
+		else {
+			return Ok(())
+		};
+
+		let input = &mut &events.0[..];
+		let mut event_index = 0;
+		let result =
+			Compact::<u32>::decode(input)
+				.map_err(|error| error.to_string())
+				.and_then(|count| {
+					while event_index < count.0 {
+						let (extrinsic_index, pallet, event) =
+							decode_event_record::<Block::Hash>(metadata, input)?;
+						entries.entry(IndexKey::Event { pallet, event }).or_default().push(Entry {
+							number,
+							hash,
+							extrinsic_index,
+							event_index: Some(event_index),
+						});
+						event_index += 1;
+					}
+					Ok(())
+				});
+
+		if let Err(error) = result {
+			// The length of an event is only known once it is decoded, so the events following
+			// an undecodable one can't be found either.
+			log::warn!(
+				target: LOG_TARGET,
+				"Skipping the events of block #{number} ({hash}) from event {event_index}: {error}",
+			);
+			entries.entry(IndexKey::Skipped).or_default().push(Entry {
+				number,
+				hash,
+				extrinsic_index: None,
+				event_index: Some(event_index),
+			});
+		}
+
+		Ok(())
+	}
+
+	/// The metadata of the runtime at `hash`, or `None` if the runtime doesn't provide it in the
+	/// required version.
+	fn metadata_at(&self, hash: Block::Hash) -> Result<Option<Arc<IndexMetadata>>, String> {
+		let spec_version = self
+			.client
+			.runtime_version_at(hash)
+			.map_err(|error| error.to_string())?
+			.spec_version;
+
+		let mut cached = self.metadata.lock();
+		if let Some(metadata) = cached.as_ref().filter(|m| m.spec_version == spec_version) {
+			return Ok(Some(metadata.clone()))
+		}
+
+		let result = self
+			.client
+			.executor()
+			.call(
+				hash,
+				"Metadata_metadata_at_version",
+				&METADATA_VERSION.encode(),
+				CallContext::Offchain,
+			)
+			.map_err(|error| error.to_string())?;
+		let Some(encoded) =
+			Option::<Vec<u8>>::decode(&mut &result[..]).map_err(|error| error.to_string())?
+		else {
+			return Ok(None)
+		};
+
+		let RuntimeMetadataPrefixed(_, RuntimeMetadata::V15(metadata)) =
+			RuntimeMetadataPrefixed::decode(&mut &encoded[..])
+				.map_err(|error| error.to_string())?
+		else {
+			return Ok(None)
+		};
+
+		let metadata = Arc::new(IndexMetadata {
+			spec_version,
+			types: metadata.types,
+			event_ty: metadata.outer_enums.event_enum_ty.id,
+			address_ty: metadata.extrinsic.address_ty.id,
+		});
+		*cached = Some(metadata.clone());
+		Ok(Some(metadata))
+	}
+
+	fn len(&self, key: &IndexKey) -> Result<u64, String> {
+		let Some(encoded) =
+			self.backend.get_aux(&key.len_key()).map_err(|error| error.to_string())?
+		else {
+			return Ok(0)
+		};
+		u64::decode(&mut &encoded[..]).map_err(|error| error.to_string())
+	}
+
+	fn page(&self, key: &IndexKey, page: u64) -> Result<Vec<Entry<Block::Hash>>, String> {
+		let Some(encoded) =
+			self.backend.get_aux(&key.page_key(page)).map_err(|error| error.to_string())?
+		else {
+			return Ok(Vec::new())
+		};
+		Decode::decode(&mut &encoded[..]).map_err(|error| error.to_string())
+	}
+}
+
+/// Returns the SCALE encoded address that signed the SCALE encoded `extrinsic`, or `None` if
+/// the extrinsic is not signed.
+fn decode_signer(metadata: &IndexMetadata, extrinsic: &[u8]) -> Result<Option<Vec<u8>>, String> {
+	// Signed extrinsics have the highest bit of the version set and the next one unset.
+	const SIGNED_MASK: u8 = 0b1100_0000;
+	const SIGNED: u8 = 0b1000_0000;
+
+	let input = &mut &extrinsic[..];
+	Compact::<u32>::decode(input).map_err(|error| error.to_string())?;
+	let version = u8::decode(input).map_err(|error| error.to_string())?;
+	if version & SIGNED_MASK != SIGNED {
+		return Ok(None)
+	}
+
+	let address = *input;
+	skip_type(&metadata.types, metadata.address_ty, input)?;
+	Ok(Some(address[..address.len() - input.len()].to_vec()))
+}
+
+/// Decodes a `frame_system::EventRecord` and returns the index of the extrinsic that emitted
+/// the event, along with the name of the pallet and of the event.
+fn decode_event_record<Hash: Decode>(
+	metadata: &IndexMetadata,
+	input: &mut &[u8],
+) -> Result<(Option<u32>, String, String), String> {
+	// `frame_system::Phase`: `ApplyExtrinsic(u32)`, `Finalization` or `Initialization`.
+	let extrinsic_index = match u8::decode(input).map_err(|error| error.to_string())? {
+		0 => Some(u32::decode(input).map_err(|error| error.to_string())?),
+		1 | 2 => None,
+		phase => return Err(format!("Unknown phase {phase}")),
+	};
+
+	// The outer event enum has one variant per pallet, wrapping the events of the pallet.
+	let (pallet, fields) = decode_variant(&metadata.types, metadata.event_ty, input)?;
+	let [field] = fields[..] else { return Err(format!("Unexpected event of pallet {pallet}")) };
+	let (event, fields) = decode_variant(&metadata.types, field, input)?;
+	for field in fields {
+		skip_type(&metadata.types, field, input)?;
+	}
+
+	Vec::<Hash>::decode(input).map_err(|error| error.to_string())?;
+	Ok((extrinsic_index, pallet, event))
+}
+
+/// Decodes the index of an enum and returns the name and the field types of the variant.
+fn decode_variant(
+	types: &PortableRegistry,
+	ty: u32,
+	input: &mut &[u8],
+) -> Result<(String, Vec<u32>), String> {
+	let TypeDef::Variant(def) = &resolve(types, ty)?.type_def else {
+		return Err(format!("Type {ty} is not an enum"))
+	};
+	let index = u8::decode(input).map_err(|error| error.to_string())?;
+	let variant = def
+		.variants
+		.iter()
+		.find(|variant| variant.index == index)
+		.ok_or_else(|| format!("Unknown variant {index} of type {ty}"))?;
+
+	Ok((variant.name.clone(), variant.fields.iter().map(|field| field.ty.id).collect()))
+}
+
+/// Advances `input` past a SCALE encoded value of type `ty`.
+fn skip_type(types: &PortableRegistry, ty: u32, input: &mut &[u8]) -> Result<(), String> {
+	match &resolve(types, ty)?.type_def {
+		TypeDef::Composite(def) =>
+			for field in &def.fields {
+				skip_type(types, field.ty.id, input)?;
+			},
+		TypeDef::Variant(_) =>
+			for field in decode_variant(types, ty, input)?.1 {
+				skip_type(types, field, input)?;
+			},
+		TypeDef::Sequence(def) => {
+			let len = Compact::<u32>::decode(input).map_err(|error| error.to_string())?.0;
+			for _ in 0..len {
+				skip_type(types, def.type_param.id, input)?;
+			}
+		},
+		TypeDef::Array(def) =>
+			for _ in 0..def.len {
+				skip_type(types, def.type_param.id, input)?;
+			},
+		TypeDef::Tuple(def) =>
+			for field in &def.fields {
+				skip_type(types, field.id, input)?;
+			},
+		TypeDef::Primitive(def) => {
+			let len = match def {
+				TypeDefPrimitive::Bool | TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => 1,
+				TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => 2,
+				TypeDefPrimitive::Char | TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => 4,
+				TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => 8,
+				TypeDefPrimitive::U128 | TypeDefPrimitive::I128 => 16,
+				TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => 32,
+				TypeDefPrimitive::Str =>
+					Compact::<u32>::decode(input).map_err(|error| error.to_string())?.0 as usize,
+			};
+			skip_bytes(input, len)?;
+		},
+		TypeDef::Compact(_) => {
+			Compact::<u128>::decode(input).map_err(|error| error.to_string())?;
+		},
+		TypeDef::BitSequence(def) => {
+			let bits = Compact::<u32>::decode(input).map_err(|error| error.to_string())?.0;
+			let store_len = match &resolve(types, def.bit_store_type.id)?.type_def {
+				TypeDef::Primitive(TypeDefPrimitive::U8) => 1,
+				TypeDef::Primitive(TypeDefPrimitive::U16) => 2,
+				TypeDef::Primitive(TypeDefPrimitive::U32) => 4,
+				TypeDef::Primitive(TypeDefPrimitive::U64) => 8,
+				_ => return Err(format!("Unsupported bit store type of type {ty}")),
+			};
+			let store_bits = store_len * 8;
+			skip_bytes(input, (bits as usize).div_ceil(store_bits) * store_len)?;
+		},
+	}
+
+	Ok(())
+}
+
+fn resolve(types: &PortableRegistry, ty: u32) -> Result<&scale_info::Type<PortableForm>, String> {
+	types.resolve(ty).ok_or_else(|| format!("Type {ty} not found in metadata"))
+}
+
+fn skip_bytes(input: &mut &[u8], len: usize) -> Result<(), String> {
+	if input.len() < len {
+		return Err("Not enough data to decode".into())
+	}
+	*input = &input[len..];
+	Ok(())
+}
@@ -32,6 +32,12 @@ pub enum Error {
 	/// Failed to fetch leaves.
 	#[error("Failed to fetch leaves of the chain: {0}")]
 	FetchLeaves(String),
+	/// The events and extrinsics index is not enabled on this node.
+	#[error("The archive index is not enabled")]
+	IndexDisabled,
+	/// Failed to query the events and extrinsics index.
+	#[error("Failed to query the archive index: {0}")]
+	Index(String),
 }
 
 // Base code for all `archive` errors.
@@ -42,6 +48,10 @@ const INVALID_PARAM_ERROR: i32 = BASE_ERROR + 1;
 const RUNTIME_CALL_ERROR: i32 = BASE_ERROR + 2;
 /// Failed to fetch leaves.
 const FETCH_LEAVES_ERROR: i32 = BASE_ERROR + 3;
+/// The archive index is not enabled.
+const INDEX_DISABLED_ERROR: i32 = BASE_ERROR + 4;
+/// Failed to query the archive index.
+const INDEX_ERROR: i32 = BASE_ERROR + 5;
 
 impl From<Error> for ErrorObject<'static> {
 	fn from(e: Error) -> Self {
@@ -51,6 +61,8 @@ impl From<Error> for ErrorObject<'static> {
 			Error::InvalidParam(_) => ErrorObject::owned(INVALID_PARAM_ERROR, msg, None::<()>),
 			Error::RuntimeCall(_) => ErrorObject::owned(RUNTIME_CALL_ERROR, msg, None::<()>),
 			Error::FetchLeaves(_) => ErrorObject::owned(FETCH_LEAVES_ERROR, msg, None::<()>),
+			Error::IndexDisabled => ErrorObject::owned(INDEX_DISABLED_ERROR, msg, None::<()>),
+			Error::Index(_) => ErrorObject::owned(INDEX_ERROR, msg, None::<()>),
 		}
 		.into()
 	}
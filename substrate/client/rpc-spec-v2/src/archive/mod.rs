@@ -25,6 +25,7 @@
 #[cfg(test)]
 mod tests;
 
+mod archive_index;
 mod archive_storage;
 mod types;
 
@@ -34,4 +35,5 @@ pub mod error;
 
 pub use api::ArchiveApiServer;
 pub use archive::Archive;
-pub use types::{MethodResult, MethodResultErr, MethodResultOk};
+pub use archive_index::ArchiveIndex;
+pub use types::{IndexQueryResult, IndexedItem, MethodResult, MethodResultErr, MethodResultOk};
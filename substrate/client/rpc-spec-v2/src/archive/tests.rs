@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-	archive::MethodResult,
+	archive::{ArchiveIndex, IndexQueryResult, MethodResult},
 	common::events::{
 		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffOperationType,
 		ArchiveStorageDiffResult, ArchiveStorageDiffType, ArchiveStorageEvent, StorageQuery,
@@ -91,6 +91,158 @@ async fn get_next_event<T: serde::de::DeserializeOwned>(sub: &mut RpcSubscriptio
 	event
 }
 
+#[tokio::test]
+async fn archive_find_events_without_index() {
+	let (_client, api) = setup_api();
+
+	let err = api
+		.call::<_, IndexQueryResult>(
+			"archive_v1_findEvents",
+			rpc_params!["Balances", "Transfer", None::<u64>, None::<u32>],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err, Error::JsonRpc(err) if err.code() == 3004);
+}
+
+#[tokio::test]
+async fn archive_index_works() {
+	let builder = TestClientBuilder::new();
+	let backend = builder.backend();
+	let client = Arc::new(builder.build());
+
+	let index = Arc::new(ArchiveIndex::new(client.clone(), backend.clone()));
+	tokio::spawn(index.clone().run());
+
+	let api = Archive::new(
+		client.clone(),
+		backend,
+		CHAIN_GENESIS,
+		Arc::new(TokioTestExecutor::default()),
+	)
+	.with_index(index.clone())
+	.into_rpc();
+
+	// Two blocks with a transfer from Alice each.
+	let mut hashes = Vec::new();
+	for nonce in 0..2 {
+		let mut block_builder = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(client.chain_info().best_hash)
+			.with_parent_block_number(client.chain_info().best_number)
+			.build()
+			.unwrap();
+		block_builder
+			.push_transfer(Transfer {
+				from: Sr25519Keyring::Alice.into(),
+				to: Sr25519Keyring::Ferdie.into(),
+				amount: 1,
+				nonce,
+			})
+			.unwrap();
+		let block = block_builder.build().unwrap().block;
+		let hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).await.unwrap();
+		client.finalize_block(hash, None).unwrap();
+		hashes.push(format!("{:?}", hash));
+	}
+
+	tokio::time::timeout(std::time::Duration::from_secs(60), async {
+		while index.indexed_height() != Some(2) {
+			tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+		}
+	})
+	.await
+	.unwrap();
+
+	// Paginate through the transfer events.
+	let page: IndexQueryResult = api
+		.call("archive_v1_findEvents", rpc_params!["Balances", "Transfer", None::<u64>, 1])
+		.await
+		.unwrap();
+	assert_eq!(page.indexed_height, Some(2));
+	assert_eq!(page.items.len(), 1);
+	assert_eq!(page.items[0].block_hash, hashes[0]);
+	assert_eq!(page.items[0].block_number, 1);
+	assert_eq!(page.items[0].extrinsic_index, Some(0));
+	assert_eq!(page.next_cursor, Some(1));
+
+	let page: IndexQueryResult = api
+		.call("archive_v1_findEvents", rpc_params!["Balances", "Transfer", page.next_cursor, 1])
+		.await
+		.unwrap();
+	assert_eq!(page.items.len(), 1);
+	assert_eq!(page.items[0].block_hash, hashes[1]);
+	assert_eq!(page.next_cursor, None);
+
+	// Extrinsics signed by Alice.
+	let alice = hex_string(&Sr25519Keyring::Alice.public().encode());
+	let page: IndexQueryResult = api
+		.call("archive_v1_findExtrinsics", rpc_params![&alice, None::<u64>, None::<u32>])
+		.await
+		.unwrap();
+	let blocks = page.items.iter().map(|item| item.block_hash.clone()).collect::<Vec<_>>();
+	assert_eq!(blocks, hashes);
+	assert!(page.items.iter().all(|item| item.extrinsic_index == Some(0)));
+
+	let bob = hex_string(&Sr25519Keyring::Bob.public().encode());
+	let page: IndexQueryResult = api
+		.call("archive_v1_findExtrinsics", rpc_params![&bob, None::<u64>, None::<u32>])
+		.await
+		.unwrap();
+	assert!(page.items.is_empty());
+
+	// Invalid limit.
+	let err = api
+		.call::<_, IndexQueryResult>(
+			"archive_v1_findExtrinsics",
+			rpc_params![&alice, None::<u64>, 0],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err, Error::JsonRpc(err) if err.code() == 3001);
+}
+
+#[tokio::test]
+async fn archive_index_skips_undecodable_events() {
+	let builder = TestClientBuilder::new();
+	let backend = builder.backend();
+	let client = Arc::new(builder.build());
+
+	let index = Arc::new(ArchiveIndex::new(client.clone(), backend));
+	tokio::spawn(index.clone().run());
+
+	// A block whose first event has an invalid phase.
+	let events_key = [sp_core::twox_128(b"System"), sp_core::twox_128(b"Events")].concat();
+	let mut block_builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().best_hash)
+		.with_parent_block_number(client.chain_info().best_number)
+		.build()
+		.unwrap();
+	block_builder
+		.push_storage_change(events_key, Some((codec::Compact(1u32), 0xffu8).encode()))
+		.unwrap();
+	let block = block_builder.build().unwrap().block;
+	let hash = block.header.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	client.finalize_block(hash, None).unwrap();
+
+	// The index moves on, and records the skipped events.
+	tokio::time::timeout(std::time::Duration::from_secs(60), async {
+		while index.indexed_height() != Some(1) {
+			tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+		}
+	})
+	.await
+	.unwrap();
+
+	let skipped = index.skipped(0, 10).unwrap();
+	assert_eq!(skipped.items.len(), 1);
+	assert_eq!(skipped.items[0].block_hash, format!("{:?}", hash));
+	assert_eq!(skipped.items[0].block_number, 1);
+	assert_eq!(skipped.items[0].extrinsic_index, None);
+	assert_eq!(skipped.items[0].event_index, Some(0));
+}
+
 #[tokio::test]
 async fn archive_genesis() {
 	let (_client, api) = setup_api();
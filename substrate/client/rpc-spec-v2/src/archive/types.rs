@@ -60,6 +60,33 @@ pub struct MethodResultErr {
 	pub error: String,
 }
 
+/// A page of items returned by the index query methods.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexQueryResult {
+	/// The items of the page, in the order of the blocks they occur in.
+	pub items: Vec<IndexedItem>,
+	/// The cursor to pass to the next query to continue after this page, or `None` if
+	/// there are no more items.
+	pub next_cursor: Option<u64>,
+	/// The height of the last indexed block, or `None` if no block was indexed yet.
+	pub indexed_height: Option<u64>,
+}
+
+/// A place in a block where an event or an extrinsic occurs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedItem {
+	/// The hexadecimal encoded hash of the block.
+	pub block_hash: String,
+	/// The height of the block.
+	pub block_number: u64,
+	/// The index of the extrinsic inside the block, if any.
+	pub extrinsic_index: Option<u32>,
+	/// The index of the event inside the block, if any.
+	pub event_index: Option<u32>,
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -87,4 +114,25 @@ mod tests {
 		let ok_dec: MethodResult = serde_json::from_str(exp).unwrap();
 		assert_eq!(ok_dec, ok);
 	}
+
+	#[test]
+	fn index_query_result() {
+		let result = IndexQueryResult {
+			items: vec![IndexedItem {
+				block_hash: "0x01".into(),
+				block_number: 1,
+				extrinsic_index: Some(2),
+				event_index: None,
+			}],
+			next_cursor: Some(1),
+			indexed_height: Some(10),
+		};
+
+		let ser = serde_json::to_string(&result).unwrap();
+		let exp = r#"{"items":[{"blockHash":"0x01","blockNumber":1,"extrinsicIndex":2,"eventIndex":null}],"nextCursor":1,"indexedHeight":10}"#;
+		assert_eq!(ser, exp);
+
+		let dec: IndexQueryResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, result);
+	}
 }
@@ -94,6 +94,28 @@ pub trait ChainHeadApi<Hash> {
 		child_trie: Option<String>,
 	) -> ResponsePayload<'static, MethodResponse>;
 
+	/// Watch storage entries for changes introduced by new blocks.
+	///
+	/// Each item either watches the value of a single key (`value`) or the values of all
+	/// the descendants of a key (`descendantsValues`). An `operationStorageChanged` event
+	/// is generated on the follow subscription for every block that modifies a watched
+	/// entry. Changes of the best block are reported when the block is imported, while
+	/// changes of other blocks are reported once the block is finalized.
+	///
+	/// The operation lasts until `chainHead_v1_stopOperation` is called or the follow
+	/// subscription ends.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "chainHead_v1_storageWatch", with_extensions)]
+	async fn chain_head_unstable_storage_watch(
+		&self,
+		follow_subscription: String,
+		items: Vec<StorageQuery<String>>,
+		child_trie: Option<String>,
+	) -> ResponsePayload<'static, MethodResponse>;
+
 	/// Call into the Runtime API at a specified block's state.
 	///
 	/// # Unstable
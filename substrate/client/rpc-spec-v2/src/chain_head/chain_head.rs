@@ -20,6 +20,7 @@
 
 use super::{
 	chain_head_storage::ChainHeadStorage,
+	chain_head_storage_watch::ChainHeadStorageWatch,
 	event::{MethodResponseStarted, OperationBodyDone, OperationCallDone},
 };
 use crate::{
@@ -31,7 +32,10 @@ use crate::{
 		subscription::{StopHandle, SubscriptionManagement, SubscriptionManagementError},
 		FollowEventSendError, FollowEventSender,
 	},
-	common::{events::StorageQuery, storage::QueryResult},
+	common::{
+		events::{StorageQuery, StorageQueryType},
+		storage::QueryResult,
+	},
 	hex_string, SubscriptionTaskExecutor,
 };
 use codec::Encode;
@@ -460,6 +464,96 @@ where
 		rp
 	}
 
+	async fn chain_head_unstable_storage_watch(
+		&self,
+		ext: &Extensions,
+		follow_subscription: String,
+		items: Vec<StorageQuery<String>>,
+		child_trie: Option<String>,
+	) -> ResponsePayload<'static, MethodResponse> {
+		let conn_id = ext
+			.get::<ConnectionId>()
+			.copied()
+			.expect("ConnectionId is always set by jsonrpsee; qed");
+
+		if !self.subscriptions.contains_subscription(conn_id, &follow_subscription) {
+			// The spec says to return `LimitReached` if the follow subscription is invalid or
+			// stale.
+			return ResponsePayload::success(MethodResponse::LimitReached);
+		}
+
+		if items.is_empty() {
+			return ResponsePayload::error(ChainHeadRpcError::InvalidParam(
+				"At least one storage item must be watched".into(),
+			));
+		}
+
+		// Gain control over parameter parsing and returned error.
+		let items = match items
+			.into_iter()
+			.map(|query| {
+				if !matches!(
+					query.query_type,
+					StorageQueryType::Value | StorageQueryType::DescendantsValues
+				) {
+					return Err(ChainHeadRpcError::InvalidParam(format!(
+						"Unsupported watch type {:?}",
+						query.query_type
+					)));
+				}
+
+				let key = StorageKey(parse_hex_param(query.key)?);
+				Ok(StorageQuery { key, query_type: query.query_type })
+			})
+			.collect::<Result<Vec<_>, ChainHeadRpcError>>()
+		{
+			Ok(items) => items,
+			Err(err) => {
+				return ResponsePayload::error(err);
+			},
+		};
+
+		let child_trie = match child_trie.map(|child_trie| parse_hex_param(child_trie)).transpose()
+		{
+			Ok(c) => c.map(ChildInfo::new_default_from_vec),
+			Err(e) => return ResponsePayload::error(e),
+		};
+
+		// The watch is a single long-lived operation regardless of the number of items.
+		let (operation, response_sender) =
+			match self.subscriptions.register_operation(&follow_subscription, 1) {
+				Ok(registered) => registered,
+				Err(_) => return ResponsePayload::success(MethodResponse::LimitReached),
+			};
+
+		let storage_watch = ChainHeadStorageWatch::new(
+			self.client.clone(),
+			self.subscriptions.clone(),
+			follow_subscription,
+			items,
+			child_trie,
+		);
+
+		// Watched items are never discarded.
+		let (rp, rp_fut) = method_started_response(operation.operation_id(), Some(0));
+
+		let fut = async move {
+			// Wait for the server to send out the response and if it produces an error no event
+			// should be generated.
+			if rp_fut.await.is_err() {
+				return;
+			}
+
+			// May fail if the follow subscription is closed, which is okay to ignore.
+			let _ = storage_watch
+				.generate_events(operation.operation_id(), operation.stop_handle(), response_sender)
+				.await;
+		};
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+
+		rp
+	}
+
 	async fn chain_head_unstable_call(
 		&self,
 		ext: &Extensions,
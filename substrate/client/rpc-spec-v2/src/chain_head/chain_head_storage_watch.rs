@@ -0,0 +1,264 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the `chainHead_storageWatch` method.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use futures::{SinkExt, StreamExt};
+use log::debug;
+use sc_client_api::{Backend, BlockchainEvents, ChildInfo, StorageKey, StorageNotification};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+	chain_head::{
+		chain_head::LOG_TARGET,
+		event::{FollowEvent, OperationError, OperationStorageChanged, StorageChange},
+		subscription::{StopHandle, SubscriptionManagement},
+		FollowEventSendError, FollowEventSender,
+	},
+	common::events::{StorageQuery, StorageQueryType},
+	hex_string,
+};
+
+/// The maximum number of non-best blocks whose changes are kept until they are finalized.
+const MAX_PENDING_BLOCKS: usize = 512;
+
+/// Generates the events of the `chainHead_storageWatch` method.
+///
+/// Changes introduced by the best block are reported as soon as the block is imported.
+/// Changes introduced by other blocks are kept until the block is finalized, and are
+/// discarded if the block is pruned.
+///
+/// Changes are only reported for blocks pinned by the follow subscription, so that the user
+/// can query them. The follow subscription pins a block when it reports it, which may happen
+/// after its changes are received: changes of the best block are then reported when the follow
+/// subscription pins the block.
+pub struct ChainHeadStorageWatch<Client, Block: BlockT, BE: Backend<Block>> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Subscriptions of the `chainHead_follow` method.
+	subscriptions: SubscriptionManagement<Block, BE>,
+	/// The ID of the follow subscription the watch belongs to.
+	sub_id: String,
+	/// The watched keys.
+	items: Vec<StorageQuery<StorageKey>>,
+	/// The child trie of the watched keys.
+	child_trie: Option<ChildInfo>,
+	/// Changes of non-best blocks waiting for finality.
+	pending: HashMap<Block::Hash, (NumberFor<Block>, Vec<StorageChange>)>,
+	/// Changes of best blocks waiting to be pinned by the follow subscription.
+	awaiting_pin: HashMap<Block::Hash, (NumberFor<Block>, Vec<StorageChange>)>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Client, Block, BE> ChainHeadStorageWatch<Client, Block, BE>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: BlockchainEvents<Block> + HeaderBackend<Block> + 'static,
+{
+	/// Constructs a new [`ChainHeadStorageWatch`].
+	///
+	/// The provided items must be either [`StorageQueryType::Value`] or
+	/// [`StorageQueryType::DescendantsValues`] queries.
+	pub fn new(
+		client: Arc<Client>,
+		subscriptions: SubscriptionManagement<Block, BE>,
+		sub_id: String,
+		items: Vec<StorageQuery<StorageKey>>,
+		child_trie: Option<ChildInfo>,
+	) -> Self {
+		Self {
+			client,
+			subscriptions,
+			sub_id,
+			items,
+			child_trie,
+			pending: Default::default(),
+			awaiting_pin: Default::default(),
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Generate the events of the `chainHead_storageWatch` method until the operation is
+	/// stopped or the follow subscription is closed.
+	pub async fn generate_events(
+		mut self,
+		operation_id: String,
+		stop_handle: &StopHandle,
+		mut sender: FollowEventSender<Block::Hash>,
+	) -> Result<(), FollowEventSendError> {
+		// Descendant queries cannot be expressed as storage notification filters.
+		let keys = if self.items.iter().any(|item| item.query_type.is_descendant_query()) {
+			None
+		} else {
+			Some(self.items.iter().map(|item| item.key.clone()).collect::<Vec<_>>())
+		};
+
+		let stream = match &self.child_trie {
+			Some(child_trie) => {
+				let child_filter = [(StorageKey(child_trie.storage_key().to_vec()), keys)];
+				self.client
+					.storage_changes_notification_stream(Some(&[][..]), Some(&child_filter[..]))
+			},
+			None => self.client.storage_changes_notification_stream(keys.as_deref(), None),
+		};
+		let mut storage_changes = match stream {
+			Ok(stream) => stream,
+			Err(error) =>
+				return sender
+					.send(FollowEvent::OperationError(OperationError {
+						operation_id,
+						error: error.to_string(),
+					}))
+					.await,
+		};
+		let Some(mut pinned_blocks) = self.subscriptions.subscribe_pinned_blocks(&self.sub_id)
+		else {
+			return Ok(())
+		};
+		let mut finality = self.client.finality_notification_stream();
+
+		loop {
+			tokio::select! {
+				_ = stop_handle.stopped() => break,
+
+				notification = storage_changes.next() => {
+					let Some(notification) = notification else { break };
+					let block_hash = notification.block;
+					let items = self.matching_changes(&notification);
+					if items.is_empty() {
+						continue
+					}
+
+					let is_best = block_hash == self.client.info().best_hash;
+					if is_best && self.subscriptions.contains_block(&self.sub_id, block_hash) {
+						sender
+							.send(FollowEvent::OperationStorageChanged(OperationStorageChanged {
+								operation_id: operation_id.clone(),
+								block_hash,
+								items,
+							}))
+							.await?;
+					} else if self.pending.len() + self.awaiting_pin.len() < MAX_PENDING_BLOCKS {
+						let Ok(Some(number)) = self.client.number(block_hash) else { continue };
+						if is_best {
+							self.awaiting_pin.insert(block_hash, (number, items));
+						} else {
+							self.pending.insert(block_hash, (number, items));
+						}
+					} else {
+						debug!(
+							target: LOG_TARGET,
+							"[storageWatch][id={:?}] Too many pending blocks, dropping changes of {:?}",
+							operation_id,
+							block_hash,
+						);
+					}
+				},
+
+				pinned = pinned_blocks.recv() => {
+					let pinned = match pinned {
+						Ok(block_hash) => vec![block_hash],
+						// Some pinned blocks were missed, check all the awaiting blocks instead.
+						Err(RecvError::Lagged(_)) => self
+							.awaiting_pin
+							.keys()
+							.filter(|hash| self.subscriptions.contains_block(&self.sub_id, **hash))
+							.copied()
+							.collect(),
+						Err(RecvError::Closed) => break,
+					};
+					for block_hash in pinned {
+						let Some((_, items)) = self.awaiting_pin.remove(&block_hash) else { continue };
+						sender
+							.send(FollowEvent::OperationStorageChanged(OperationStorageChanged {
+								operation_id: operation_id.clone(),
+								block_hash,
+								items,
+							}))
+							.await?;
+					}
+				},
+
+				notification = finality.next() => {
+					let Some(notification) = notification else { break };
+
+					let finalized = notification.tree_route.iter().chain(std::iter::once(&notification.hash));
+					for hash in finalized {
+						let changes =
+							self.pending.remove(hash).or_else(|| self.awaiting_pin.remove(hash));
+						let Some((_, items)) = changes else { continue };
+						if !self.subscriptions.contains_block(&self.sub_id, *hash) {
+							debug!(
+								target: LOG_TARGET,
+								"[storageWatch][id={:?}] Block {:?} is not pinned, dropping its changes",
+								operation_id,
+								hash,
+							);
+							continue
+						}
+
+						sender
+							.send(FollowEvent::OperationStorageChanged(OperationStorageChanged {
+								operation_id: operation_id.clone(),
+								block_hash: *hash,
+								items,
+							}))
+							.await?;
+					}
+
+					// Blocks at or below the finalized height are either reported or pruned.
+					let finalized_number = *notification.header.number();
+					self.pending.retain(|_, (number, _)| *number > finalized_number);
+					self.awaiting_pin.retain(|_, (number, _)| *number > finalized_number);
+				},
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Extract the changes of the notification that match the watched keys.
+	fn matching_changes(
+		&self,
+		notification: &StorageNotification<Block::Hash>,
+	) -> Vec<StorageChange> {
+		let child_trie_key = self.child_trie.as_ref().map(|child| child.storage_key());
+
+		notification
+			.changes
+			.iter()
+			.filter(|(child_key, _, _)| child_key.map(|key| &key.0[..]) == child_trie_key)
+			.filter(|(_, key, _)| {
+				self.items.iter().any(|item| match item.query_type {
+					StorageQueryType::DescendantsValues => key.0.starts_with(&item.key.0),
+					_ => key.0 == item.key.0,
+				})
+			})
+			.map(|(child_key, key, value)| StorageChange {
+				key: hex_string(&key.0),
+				value: value.map(|value| hex_string(&value.0)),
+				child_trie_key: child_key.map(|child_key| hex_string(&child_key.0)),
+			})
+			.collect()
+	}
+}
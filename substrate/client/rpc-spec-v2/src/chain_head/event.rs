@@ -245,6 +245,32 @@ pub struct OperationStorageItems {
 	pub items: Vec<StorageResult>,
 }
 
+/// A change of a watched storage entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+	/// The hex-encoded key of the entry.
+	pub key: String,
+	/// The hex-encoded new value of the entry, or `None` if the entry was removed.
+	pub value: Option<String>,
+	/// The child trie key if provided.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub child_trie_key: Option<String>,
+}
+
+/// The response of the `chainHead_storageWatch` method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationStorageChanged<Hash> {
+	/// The operation id of the event.
+	pub operation_id: String,
+	/// The block that introduced the changes.
+	pub block_hash: Hash,
+	/// The watched entries that changed in the block.
+	pub items: Vec<StorageChange>,
+}
+
 /// Indicate a problem during the operation.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -271,6 +297,8 @@ pub struct OperationError {
 /// - OperationWaitingForContinue: Generated after OperationStorageItems and requires the user to
 ///   call `chainHead_continue`
 /// - OperationStorageDone: The `chianHead_storage` method has produced all the results
+/// - OperationStorageChanged: Watched entries changed in a block reported by
+///   `chainHead_storageWatch`
 /// - OperationInaccessible: The server was unable to provide the result, retries might succeed in
 ///   the future
 /// - OperationError: The server encountered an error, retries will not succeed
@@ -302,6 +330,8 @@ pub enum FollowEvent<Hash> {
 	OperationWaitingForContinue(OperationId),
 	/// The responses of the `chainHead_storage` method have been produced.
 	OperationStorageDone(OperationId),
+	/// Watched storage entries of the `chainHead_storageWatch` method changed in a block.
+	OperationStorageChanged(OperationStorageChanged<Hash>),
 	/// The RPC server was unable to provide the response of the following operation id.
 	///
 	/// Repeating the same operation in the future might succeed.
@@ -548,6 +578,34 @@ mod tests {
 		assert_eq!(event_dec, event);
 	}
 
+	#[test]
+	fn follow_op_storage_changed_event() {
+		let event: FollowEvent<String> =
+			FollowEvent::OperationStorageChanged(OperationStorageChanged {
+				operation_id: "123".into(),
+				block_hash: "0x1".into(),
+				items: vec![
+					StorageChange {
+						key: "0x2".into(),
+						value: Some("0x123".into()),
+						child_trie_key: None,
+					},
+					StorageChange {
+						key: "0x3".into(),
+						value: None,
+						child_trie_key: Some("0x4".into()),
+					},
+				],
+			});
+
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp = r#"{"event":"operationStorageChanged","operationId":"123","blockHash":"0x1","items":[{"key":"0x2","value":"0x123"},{"key":"0x3","value":null,"childTrieKey":"0x4"}]}"#;
+		assert_eq!(ser, exp);
+
+		let event_dec: FollowEvent<String> = serde_json::from_str(exp).unwrap();
+		assert_eq!(event_dec, event);
+	}
+
 	#[test]
 	fn follow_op_wait_event() {
 		let event: FollowEvent<String> =
@@ -34,13 +34,14 @@ pub mod event;
 
 mod chain_head_follow;
 mod chain_head_storage;
+mod chain_head_storage_watch;
 mod subscription;
 
 pub use api::ChainHeadApiServer;
 pub use chain_head::{ChainHead, ChainHeadConfig};
 pub use event::{
-	BestBlockChanged, ErrorEvent, Finalized, FollowEvent, Initialized, NewBlock, RuntimeEvent,
-	RuntimeVersionEvent,
+	BestBlockChanged, ErrorEvent, Finalized, FollowEvent, Initialized, NewBlock,
+	OperationStorageChanged, RuntimeEvent, RuntimeVersionEvent, StorageChange,
 };
 
 /// Follow event sender.
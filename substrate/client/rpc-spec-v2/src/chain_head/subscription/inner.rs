@@ -39,6 +39,9 @@ type SharedOperations = Arc<Mutex<HashMap<String, (NotifyOnDrop, StopHandle)>>>;
 /// buffer per connection and this a extra buffer.
 const BUF_CAP_PER_SUBSCRIPTION: usize = 16;
 
+/// The maximum number of pinned block notifications buffered per receiver.
+const BUF_CAP_PINNED_BLOCKS: usize = 64;
+
 /// The state machine of a block of a single subscription ID.
 ///
 /// # Motivation
@@ -291,6 +294,8 @@ struct SubscriptionState<Block: BlockT> {
 	response_sender: FollowEventSender<Block::Hash>,
 	/// The ongoing operations of a subscription.
 	operations: Operations,
+	/// Notifies the blocks pinned for the first time by the subscription.
+	pinned_blocks: tokio::sync::broadcast::Sender<Block::Hash>,
 	/// Track the block hashes available for this subscription.
 	///
 	/// This implementation assumes:
@@ -537,6 +542,7 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionsInner<Block, BE> {
 				response_sender,
 				blocks: Default::default(),
 				operations: Operations::new(self.max_ongoing_operations),
+				pinned_blocks: tokio::sync::broadcast::channel(BUF_CAP_PINNED_BLOCKS).0,
 			};
 			entry.insert(state);
 
@@ -652,9 +658,19 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionsInner<Block, BE> {
 		}
 
 		self.global_register_block(hash)?;
+		if let Some(sub) = self.subs.get(sub_id) {
+			let _ = sub.pinned_blocks.send(hash);
+		}
 		Ok(true)
 	}
 
+	pub fn subscribe_pinned_blocks(
+		&self,
+		sub_id: &str,
+	) -> Option<tokio::sync::broadcast::Receiver<Block::Hash>> {
+		self.subs.get(sub_id).map(|sub| sub.pinned_blocks.subscribe())
+	}
+
 	/// Register the block internally.
 	///
 	/// If the block is present the reference counter is increased.
@@ -745,6 +761,10 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionsInner<Block, BE> {
 		Ok(())
 	}
 
+	pub fn contains_block(&self, sub_id: &str, hash: Block::Hash) -> bool {
+		self.subs.get(sub_id).is_some_and(|sub| sub.contains_block(hash))
+	}
+
 	pub fn lock_block(
 		&mut self,
 		sub_id: &str,
@@ -773,6 +793,28 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionsInner<Block, BE> {
 		)
 	}
 
+	/// Register an operation that is not tied to a pinned block.
+	///
+	/// Returns the registered operation together with the sender of the subscription's
+	/// method responses.
+	pub fn register_operation(
+		&mut self,
+		sub_id: &str,
+		to_reserve: usize,
+	) -> Result<(RegisteredOperation, FollowEventSender<Block::Hash>), SubscriptionManagementError>
+	{
+		let Some(sub) = self.subs.get_mut(sub_id) else {
+			return Err(SubscriptionManagementError::SubscriptionAbsent)
+		};
+
+		let Some(operation) = sub.register_operation(to_reserve) else {
+			// Error when the server cannot execute at least one operation.
+			return Err(SubscriptionManagementError::ExceededLimits)
+		};
+
+		Ok((operation, sub.response_sender.clone()))
+	}
+
 	pub fn get_operation(&mut self, sub_id: &str, id: &str) -> Option<OperationState> {
 		let state = self.subs.get(sub_id)?;
 		state.get_operation(id)
@@ -915,6 +957,7 @@ mod tests {
 			response_sender,
 			operations: Operations::new(MAX_OPERATIONS_PER_SUB),
 			blocks: Default::default(),
+			pinned_blocks: tokio::sync::broadcast::channel(1).0,
 		};
 
 		let hash = H256::random();
@@ -943,6 +986,7 @@ mod tests {
 			response_sender,
 			blocks: Default::default(),
 			operations: Operations::new(MAX_OPERATIONS_PER_SUB),
+			pinned_blocks: tokio::sync::broadcast::channel(1).0,
 		};
 
 		let hash = H256::random();
@@ -1072,6 +1116,28 @@ mod tests {
 		assert_eq!(err, SubscriptionManagementError::BlockHashAbsent);
 	}
 
+	#[test]
+	fn subscription_notifies_pinned_blocks() {
+		let (backend, client) = init_backend();
+
+		let hashes = produce_blocks(client, 1);
+		let hash = hashes[0];
+
+		let mut subs =
+			SubscriptionsInner::new(10, Duration::from_secs(10), MAX_OPERATIONS_PER_SUB, backend);
+		let id = "abc".to_string();
+
+		let _stop = subs.insert_subscription(id.clone(), true).unwrap();
+		assert!(subs.subscribe_pinned_blocks("abc-invalid").is_none());
+		let mut pinned = subs.subscribe_pinned_blocks(&id).unwrap();
+
+		// Only the first registration of the block is notified.
+		assert_eq!(subs.pin_block(&id, hash).unwrap(), true);
+		assert_eq!(subs.pin_block(&id, hash).unwrap(), false);
+		assert_eq!(pinned.try_recv().unwrap(), hash);
+		assert!(pinned.try_recv().is_err());
+	}
+
 	#[test]
 	fn subscription_ref_count() {
 		let (backend, client) = init_backend();
@@ -26,7 +26,7 @@ mod error;
 mod inner;
 
 use crate::{
-	chain_head::chain_head::LOG_TARGET,
+	chain_head::{chain_head::LOG_TARGET, FollowEventSender},
 	common::connections::{RegisteredConnection, ReservedConnection, RpcConnections},
 };
 
@@ -34,7 +34,7 @@ use self::inner::SubscriptionsInner;
 
 pub use self::inner::OperationState;
 pub use error::SubscriptionManagementError;
-pub use inner::{BlockGuard, InsertedSubscriptionData, StopHandle};
+pub use inner::{BlockGuard, InsertedSubscriptionData, RegisteredOperation, StopHandle};
 
 /// Manage block pinning / unpinning for subscription IDs.
 pub struct SubscriptionManagement<Block: BlockT, BE: Backend<Block>> {
@@ -159,6 +159,25 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
 		inner.unpin_blocks(sub_id, hashes)
 	}
 
+	/// Check if the block is pinned for the subscription, and was not unpinned yet.
+	///
+	/// Returns `false` if the subscription ID is invalid.
+	pub fn contains_block(&self, sub_id: &str, hash: Block::Hash) -> bool {
+		let inner = self.inner.read();
+		inner.contains_block(sub_id, hash)
+	}
+
+	/// Subscribe to the blocks pinned for the first time by the subscription.
+	///
+	/// Returns `None` if the subscription ID is invalid.
+	pub fn subscribe_pinned_blocks(
+		&self,
+		sub_id: &str,
+	) -> Option<tokio::sync::broadcast::Receiver<Block::Hash>> {
+		let inner = self.inner.read();
+		inner.subscribe_pinned_blocks(sub_id)
+	}
+
 	/// Ensure the block remains pinned until the return object is dropped.
 	///
 	/// Returns a [`BlockGuard`] that pins and unpins the block hash in RAII manner
@@ -176,6 +195,21 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
 		inner.lock_block(sub_id, hash, to_reserve)
 	}
 
+	/// Register an operation that spans multiple blocks of the subscription, such as
+	/// `chainHead_storageWatch`.
+	///
+	/// Returns an error if the subscription ID is invalid or the limit of ongoing
+	/// operations was exceeded.
+	pub fn register_operation(
+		&self,
+		sub_id: &str,
+		to_reserve: usize,
+	) -> Result<(RegisteredOperation, FollowEventSender<Block::Hash>), SubscriptionManagementError>
+	{
+		let mut inner = self.inner.write();
+		inner.register_operation(sub_id, to_reserve)
+	}
+
 	/// Get the operation state.
 	pub fn get_operation(&self, sub_id: &str, operation_id: &str) -> Option<OperationState> {
 		let mut inner = self.inner.write();
@@ -3162,6 +3162,213 @@ async fn stop_storage_operation() {
 	.await;
 }
 
+#[tokio::test]
+async fn storage_watch_reports_changes() {
+	let (client, api, mut sub, sub_id, block) = setup_api().await;
+	let block_1_hash = block.hash();
+
+	/// Skip the block events until the next storage watch event.
+	async fn next_storage_changed(sub: &mut RpcSubscription) -> OperationStorageChanged<String> {
+		loop {
+			if let FollowEvent::OperationStorageChanged(changed) = get_next_event(sub).await {
+				return changed
+			}
+		}
+	}
+
+	/// Collect the events produced until the subscription is idle.
+	async fn collect_events(sub: &mut RpcSubscription) -> Vec<FollowEvent<String>> {
+		let mut events = Vec::new();
+		while let Ok(event) = tokio::time::timeout(
+			std::time::Duration::from_secs(DOES_NOT_PRODUCE_EVENTS_SECONDS),
+			sub.next::<FollowEvent<String>>(),
+		)
+		.await
+		{
+			events.push(event.unwrap().unwrap().0);
+		}
+		events
+	}
+
+	let items = vec![
+		StorageQuery { key: hex_string(&KEY), query_type: StorageQueryType::Value },
+		StorageQuery { key: hex_string(b":A"), query_type: StorageQueryType::DescendantsValues },
+	];
+
+	// Subscription ID is invalid.
+	let response: MethodResponse = api
+		.call("chainHead_v1_storageWatch", rpc_params!["invalid_sub_id", items.clone()])
+		.await
+		.unwrap();
+	assert_matches!(response, MethodResponse::LimitReached);
+
+	// Hash queries cannot be watched.
+	let err = api
+		.call::<_, serde_json::Value>(
+			"chainHead_v1_storageWatch",
+			rpc_params![
+				&sub_id,
+				vec![StorageQuery { key: hex_string(&KEY), query_type: StorageQueryType::Hash }]
+			],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::JsonRpc(ref err) if err.code() == super::error::json_rpc_spec::INVALID_PARAM_ERROR
+	);
+
+	let response: MethodResponse = api
+		.call("chainHead_v1_storageWatch", rpc_params![&sub_id, items])
+		.await
+		.unwrap();
+	let operation_id = match response {
+		MethodResponse::Started(started) => started.operation_id,
+		MethodResponse::LimitReached => panic!("Expected started response"),
+	};
+
+	// Changes of the best block are reported on import.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block_1_hash)
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(KEY.to_vec(), Some(VALUE.to_vec())).unwrap();
+	builder.push_storage_change(b":AB".to_vec(), Some(b"a".to_vec())).unwrap();
+	builder.push_storage_change(b":B".to_vec(), Some(b"b".to_vec())).unwrap();
+	let block_2 = builder.build().unwrap().block;
+	client.import(BlockOrigin::Own, block_2.clone()).await.unwrap();
+
+	let changed = next_storage_changed(&mut sub).await;
+	assert_eq!(changed.operation_id, operation_id);
+	assert_eq!(changed.block_hash, format!("{:?}", block_2.hash()));
+	let mut items = changed.items;
+	items.sort_by(|a, b| a.key.cmp(&b.key));
+	assert_eq!(
+		items,
+		vec![
+			StorageChange {
+				key: hex_string(b":AB"),
+				value: Some(hex_string(b"a")),
+				child_trie_key: None
+			},
+			StorageChange {
+				key: hex_string(&KEY),
+				value: Some(hex_string(&VALUE)),
+				child_trie_key: None
+			},
+		]
+	);
+
+	// Changes of a fork are reported once the fork is finalized.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block_1_hash)
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":AC".to_vec(), None).unwrap();
+	let fork_block = builder.build().unwrap().block;
+	client.import(BlockOrigin::Own, fork_block.clone()).await.unwrap();
+	assert_ne!(client.info().best_hash, fork_block.hash());
+
+	let events = collect_events(&mut sub).await;
+	assert!(!events
+		.iter()
+		.any(|event| matches!(event, FollowEvent::OperationStorageChanged(_))));
+
+	client.finalize_block(fork_block.hash(), None).unwrap();
+
+	let changed = next_storage_changed(&mut sub).await;
+	assert_eq!(changed.operation_id, operation_id);
+	assert_eq!(changed.block_hash, format!("{:?}", fork_block.hash()));
+	assert_eq!(
+		changed.items,
+		vec![StorageChange { key: hex_string(b":AC"), value: None, child_trie_key: None }]
+	);
+
+	// No changes are reported after the operation is stopped.
+	let _res: () = api.call("chainHead_v1_stopOperation", [&sub_id, &operation_id]).await.unwrap();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(fork_block.hash())
+		.with_parent_block_number(2)
+		.build()
+		.unwrap();
+	builder.push_storage_change(KEY.to_vec(), None).unwrap();
+	let block_3 = builder.build().unwrap().block;
+	client.import(BlockOrigin::Own, block_3.clone()).await.unwrap();
+
+	let events = collect_events(&mut sub).await;
+	assert!(events.iter().any(|event| matches!(event, FollowEvent::NewBlock(_))));
+	assert!(!events
+		.iter()
+		.any(|event| matches!(event, FollowEvent::OperationStorageChanged(_))));
+}
+
+#[tokio::test]
+async fn storage_watch_skips_unpinned_blocks() {
+	let (client, api, mut sub, sub_id, block) = setup_api().await;
+	let block_1_hash = block.hash();
+
+	let items = vec![StorageQuery { key: hex_string(&KEY), query_type: StorageQueryType::Value }];
+	let response: MethodResponse = api
+		.call("chainHead_v1_storageWatch", rpc_params![&sub_id, items])
+		.await
+		.unwrap();
+	assert_matches!(response, MethodResponse::Started(_));
+
+	// Make a fork, so that the changes are only reported on finality.
+	let block_2 = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block_1_hash)
+		.with_parent_block_number(1)
+		.build()
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	client.import(BlockOrigin::Own, block_2.clone()).await.unwrap();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block_1_hash)
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(KEY.to_vec(), Some(VALUE.to_vec())).unwrap();
+	let fork_block = builder.build().unwrap().block;
+	client.import(BlockOrigin::Own, fork_block.clone()).await.unwrap();
+	assert_ne!(client.info().best_hash, fork_block.hash());
+
+	// Wait for the fork to be pinned, then unpin it.
+	loop {
+		if let FollowEvent::NewBlock(new_block) =
+			get_next_event::<FollowEvent<String>>(&mut sub).await
+		{
+			if new_block.block_hash == format!("{:?}", fork_block.hash()) {
+				break
+			}
+		}
+	}
+	let _res: () = api
+		.call("chainHead_v1_unpin", rpc_params![&sub_id, format!("{:?}", fork_block.hash())])
+		.await
+		.unwrap();
+
+	client.finalize_block(fork_block.hash(), None).unwrap();
+
+	let mut events = Vec::new();
+	while let Ok(event) = tokio::time::timeout(
+		std::time::Duration::from_secs(DOES_NOT_PRODUCE_EVENTS_SECONDS),
+		sub.next::<FollowEvent<String>>(),
+	)
+	.await
+	{
+		events.push(event.unwrap().unwrap().0);
+	}
+	assert!(events.iter().any(|event| matches!(event, FollowEvent::Finalized(_))));
+	assert!(!events
+		.iter()
+		.any(|event| matches!(event, FollowEvent::OperationStorageChanged(_))));
+}
+
 #[tokio::test]
 async fn storage_closest_merkle_value() {
 	let (client, api, mut sub, sub_id, block) = setup_api().await;
@@ -18,7 +18,10 @@
 
 //! API trait for transactions.
 
-use crate::transaction::{error::ErrorBroadcast, event::TransactionEvent};
+use crate::transaction::{
+	error::ErrorBroadcast,
+	event::{DetailedTransactionEvent, TransactionEvent},
+};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use sp_core::Bytes;
 
@@ -38,6 +41,23 @@ pub trait TransactionApi<Hash: Clone> {
 		item = TransactionEvent<Hash>,
 	)]
 	fn submit_and_watch(&self, bytes: Bytes);
+
+	/// Submit an extrinsic to watch, reporting detailed events.
+	///
+	/// The events are the ones of `transactionWatch_v1_submitAndWatch`, extended with the
+	/// reason why the transaction was dropped or invalidated, the hash of the replacing
+	/// transaction and the fork views keeping the transaction valid. See
+	/// [`DetailedTransactionEvent`](crate::transaction::event::DetailedTransactionEvent).
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[subscription(
+		name = "transactionWatch_v1_submitAndWatchDetailed" => "transactionWatch_v1_watchEventDetailed",
+		unsubscribe = "transactionWatch_v1_unwatchDetailed",
+		item = DetailedTransactionEvent<Hash>,
+	)]
+	fn submit_and_watch_detailed(&self, bytes: Bytes);
 }
 
 #[rpc(client, server)]
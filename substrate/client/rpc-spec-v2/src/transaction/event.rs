@@ -18,6 +18,7 @@
 
 //! The transaction's event returned as json compatible object.
 
+pub use sc_transaction_pool_api::TransactionDropReason;
 use serde::{Deserialize, Serialize};
 
 /// The transaction was included in a block of the chain.
@@ -95,6 +96,44 @@ pub enum TransactionEvent<Hash> {
 	Dropped(TransactionDropped),
 }
 
+/// A transaction event extended with the details known by the transaction pool.
+///
+/// The details are reported next to the fields of the [`TransactionEvent`]:
+///
+/// ```json
+/// { event: "invalid", error: "...", reason: { type: "invalid", error: "Stale" }, views: ["0xFF"] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(
+	serialize = "Hash: Serialize + Clone",
+	deserialize = "Hash: Deserialize<'de> + Clone"
+))]
+pub struct DetailedTransactionEvent<Hash> {
+	/// The transaction event.
+	#[serde(flatten)]
+	pub event: TransactionEvent<Hash>,
+	/// The reason why the transaction was dropped or invalidated, if known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub reason: Option<TransactionDropReason>,
+	/// The hex-encoded hash of the transaction that replaced this transaction.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub usurped_by: Option<String>,
+	/// The fork views, identified by their block hash, that keep the transaction valid
+	/// or included at the time of the event.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	#[serde(default)]
+	pub views: Vec<Hash>,
+}
+
+impl<Hash> From<TransactionEvent<Hash>> for DetailedTransactionEvent<Hash> {
+	fn from(event: TransactionEvent<Hash>) -> Self {
+		Self { event, reason: None, usurped_by: None, views: Vec::new() }
+	}
+}
+
 /// Intermediate representation (IR) for the transaction events
 /// that handles block events only.
 ///
@@ -287,4 +326,30 @@ mod tests {
 		let event_dec: TransactionEvent<()> = serde_json::from_str(exp).unwrap();
 		assert_eq!(event_dec, event);
 	}
+	#[test]
+	fn detailed_event() {
+		let event: DetailedTransactionEvent<H256> = DetailedTransactionEvent {
+			event: TransactionEvent::Invalid(TransactionError { error: "abc".to_string() }),
+			reason: Some(TransactionDropReason::Usurped),
+			usurped_by: Some("0x01".into()),
+			views: vec![H256::from_low_u64_be(1)],
+		};
+		let ser = serde_json::to_string(&event).unwrap();
+
+		let exp = r#"{"event":"invalid","error":"abc","reason":{"type":"usurped"},"usurpedBy":"0x01","views":["0x0000000000000000000000000000000000000000000000000000000000000001"]}"#;
+		assert_eq!(ser, exp);
+
+		let event_dec: DetailedTransactionEvent<H256> = serde_json::from_str(exp).unwrap();
+		assert_eq!(event_dec, event);
+
+		let event: DetailedTransactionEvent<H256> =
+			TransactionEvent::BestChainBlockIncluded(None).into();
+		let ser = serde_json::to_string(&event).unwrap();
+
+		let exp = r#"{"event":"bestChainBlockIncluded","block":null}"#;
+		assert_eq!(ser, exp);
+
+		let event_dec: DetailedTransactionEvent<H256> = serde_json::from_str(exp).unwrap();
+		assert_eq!(event_dec, event);
+	}
 }
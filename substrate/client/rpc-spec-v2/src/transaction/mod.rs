@@ -35,6 +35,9 @@ pub mod transaction;
 pub mod transaction_broadcast;
 
 pub use api::{TransactionApiServer, TransactionBroadcastApiServer};
-pub use event::{TransactionBlock, TransactionDropped, TransactionError, TransactionEvent};
+pub use event::{
+	DetailedTransactionEvent, TransactionBlock, TransactionDropReason, TransactionDropped,
+	TransactionError, TransactionEvent,
+};
 pub use transaction::Transaction;
 pub use transaction_broadcast::TransactionBroadcast;
@@ -18,7 +18,7 @@
 
 use crate::{
 	hex_string,
-	transaction::{TransactionBlock, TransactionEvent},
+	transaction::{DetailedTransactionEvent, TransactionBlock, TransactionEvent},
 };
 use assert_matches::assert_matches;
 use codec::Encode;
@@ -85,6 +85,62 @@ async fn tx_in_finalized() {
 	assert_eq!(event, TransactionEvent::Finalized(TransactionBlock { hash: block_2, index: 0 }));
 }
 
+#[tokio::test]
+async fn tx_detailed_in_finalized() {
+	let (api, pool, client, tx_api, _exec_middleware, _pool_middleware) = setup_api_tx();
+	let block_1_header = api.push_block(1, vec![], true);
+	client.set_best_block(block_1_header.hash(), 1);
+
+	// Invalid bytes are reported without details.
+	let mut sub = tx_api
+		.subscribe_unbounded(
+			"transactionWatch_v1_submitAndWatchDetailed",
+			rpc_params![&"0xdeadbeef"],
+		)
+		.await
+		.unwrap();
+
+	let event: DetailedTransactionEvent<H256> = get_next_event_sub!(&mut sub);
+	assert_matches!(event.event, TransactionEvent::Invalid(_));
+	assert_eq!(event.reason, None);
+
+	let uxt = uxt(Alice, ALICE_NONCE);
+	let xt = hex_string(&uxt.encode());
+
+	let mut sub = tx_api
+		.subscribe_unbounded("transactionWatch_v1_submitAndWatchDetailed", rpc_params![&xt])
+		.await
+		.unwrap();
+
+	let event: DetailedTransactionEvent<H256> = get_next_event_sub!(&mut sub);
+	assert_eq!(event, TransactionEvent::Validated.into());
+
+	// Import block 2 with the transaction included.
+	let block_2_header = api.push_block(2, vec![uxt.clone()], true);
+	let block_2 = block_2_header.hash();
+
+	// Announce block 2 to the pool.
+	let event = ChainEvent::NewBestBlock { hash: block_2, tree_route: None };
+	pool.inner_pool.maintain(event).await;
+	let event = ChainEvent::Finalized { hash: block_2, tree_route: Arc::from(vec![]) };
+	pool.inner_pool.maintain(event).await;
+
+	let event: DetailedTransactionEvent<H256> = get_next_event_sub!(&mut sub);
+	assert_eq!(
+		event,
+		TransactionEvent::BestChainBlockIncluded(Some(TransactionBlock {
+			hash: block_2,
+			index: 0
+		}))
+		.into()
+	);
+	let event: DetailedTransactionEvent<H256> = get_next_event_sub!(&mut sub);
+	assert_eq!(
+		event,
+		TransactionEvent::Finalized(TransactionBlock { hash: block_2, index: 0 }).into()
+	);
+}
+
 #[tokio::test]
 async fn tx_with_pruned_best_block() {
 	let (api, pool, client, tx_api, _exec_middleware, _pool_middleware) = setup_api_tx();
@@ -19,21 +19,25 @@
 //! API implementation for submitting transactions.
 
 use crate::{
+	hex_string,
 	transaction::{
 		api::TransactionApiServer,
 		error::Error,
-		event::{TransactionBlock, TransactionDropped, TransactionError, TransactionEvent},
+		event::{
+			DetailedTransactionEvent, TransactionBlock, TransactionDropped, TransactionError,
+			TransactionEvent,
+		},
 	},
 	SubscriptionTaskExecutor,
 };
 
-use codec::Decode;
+use codec::{Decode, Encode};
 use futures::{StreamExt, TryFutureExt};
 use jsonrpsee::{core::async_trait, PendingSubscriptionSink};
 use sc_rpc::utils::{RingBuffer, Subscription};
 use sc_transaction_pool_api::{
-	error::IntoPoolError, BlockHash, TransactionFor, TransactionPool, TransactionSource,
-	TransactionStatus,
+	error::IntoPoolError, BlockHash, DetailedTransactionStatus, TransactionFor, TransactionPool,
+	TransactionSource, TransactionStatus,
 };
 use sp_blockchain::HeaderBackend;
 use sp_core::Bytes;
@@ -129,6 +133,62 @@ where
 
 		sc_rpc::utils::spawn_subscription_task(&self.executor, fut);
 	}
+
+	fn submit_and_watch_detailed(&self, pending: PendingSubscriptionSink, xt: Bytes) {
+		let client = self.client.clone();
+		let pool = self.pool.clone();
+
+		let fut = async move {
+			let decoded_extrinsic = match TransactionFor::<Pool>::decode(&mut &xt[..]) {
+				Ok(decoded_extrinsic) => decoded_extrinsic,
+				Err(e) => {
+					log::debug!(target: LOG_TARGET, "Extrinsic bytes cannot be decoded: {:?}", e);
+
+					let Ok(sink) = pending.accept().await.map(Subscription::from) else { return };
+
+					// The transaction is invalid.
+					let event = TransactionEvent::Invalid::<BlockHash<Pool>>(TransactionError {
+						error: "Extrinsic bytes cannot be decoded".into(),
+					});
+					let _ = sink.send(&DetailedTransactionEvent::from(event)).await;
+					return
+				},
+			};
+
+			let best_block_hash = client.info().best_hash;
+
+			let submit = pool
+				.submit_and_watch_detailed(best_block_hash, TX_SOURCE, decoded_extrinsic)
+				.map_err(|e| {
+					e.into_pool_error()
+						.map(Error::from)
+						.unwrap_or_else(|e| Error::Verification(Box::new(e)))
+				});
+
+			let Ok(sink) = pending.accept().await.map(Subscription::from) else {
+				return;
+			};
+
+			match submit.await {
+				Ok(stream) => {
+					let stream = stream
+						.filter_map(move |event| async move { handle_detailed_event(event) })
+						.boxed();
+
+					// If the subscription is too slow older events will be overwritten.
+					sink.pipe_from_stream(stream, RingBuffer::new(3)).await;
+				},
+				Err(err) => {
+					// We have not created an `Watcher` for the tx. Make sure the
+					// error is still propagated as an event.
+					let event: TransactionEvent<<Pool::Block as BlockT>::Hash> = err.into();
+					_ = sink.send(&DetailedTransactionEvent::from(event)).await;
+				},
+			};
+		};
+
+		sc_rpc::utils::spawn_subscription_task(&self.executor, fut);
+	}
 }
 
 /// Handle events generated by the transaction-pool and convert them
@@ -162,3 +222,20 @@ pub fn handle_event<Hash: Clone, BlockHash: Clone>(
 		TransactionStatus::Broadcast(_) => None,
 	}
 }
+
+/// Handle detailed events generated by the transaction-pool and convert them
+/// to the new API expected state.
+///
+/// See [`handle_event`] for the conversion of the status.
+#[inline]
+pub fn handle_detailed_event<Hash: Clone + Encode, BlockHash: Clone>(
+	event: DetailedTransactionStatus<Hash, BlockHash>,
+) -> Option<DetailedTransactionEvent<BlockHash>> {
+	let DetailedTransactionStatus { status, reason, views } = event;
+	let usurped_by = match &status {
+		TransactionStatus::Usurped(by) => Some(hex_string(&by.encode())),
+		_ => None,
+	};
+
+	handle_event(status).map(|event| DetailedTransactionEvent { event, reason, usurped_by, views })
+}
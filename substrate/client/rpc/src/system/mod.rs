@@ -31,7 +31,7 @@ use sc_tracing::logging;
 use sc_utils::mpsc::TracingUnboundedSender;
 use sp_runtime::traits::{self, Header as HeaderT};
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{BannedPeer, Health, NodeRole, PeerInfo, SyncState, SystemInfo};
 pub use sc_rpc_api::system::*;
 
 /// System API implementation
@@ -59,6 +59,12 @@ pub enum Request<B: traits::Block> {
 	NetworkRemoveReservedPeer(String, oneshot::Sender<error::Result<()>>),
 	/// Must return the list of reserved peers
 	NetworkReservedPeers(oneshot::Sender<Vec<String>>),
+	/// Must return any potential parse error.
+	NetworkBanPeer(String, u64, oneshot::Sender<error::Result<()>>),
+	/// Must return whether the target was banned, or any potential parse error.
+	NetworkUnbanPeer(String, oneshot::Sender<error::Result<bool>>),
+	/// Must return the bans currently in effect.
+	NetworkBannedPeers(oneshot::Sender<Vec<BannedPeer>>),
 	/// Must return the node role.
 	NodeRoles(oneshot::Sender<Vec<NodeRole>>),
 	/// Must return the state of the node syncing.
@@ -164,6 +170,38 @@ impl<B: traits::Block> SystemApiServer<B::Hash, <B::Header as HeaderT>::Number>
 		rx.await.map_err(|e| Error::Internal(e.to_string()))
 	}
 
+	async fn system_ban_peer(
+		&self,
+		ext: &Extensions,
+		target: String,
+		duration: u64,
+	) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBanPeer(target, duration, tx));
+		match rx.await {
+			Ok(result) => result,
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_unban_peer(&self, ext: &Extensions, target: String) -> Result<bool, Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkUnbanPeer(target, tx));
+		match rx.await {
+			Ok(result) => result,
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_banned_peers(&self, ext: &Extensions) -> Result<Vec<BannedPeer>, Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBannedPeers(tx));
+		rx.await.map_err(|e| Error::Internal(e.to_string()))
+	}
+
 	async fn system_node_roles(&self) -> Result<Vec<NodeRole>, Error> {
 		let (tx, rx) = oneshot::channel();
 		let _ = self.send_back.unbounded_send(Request::NodeRoles(tx));
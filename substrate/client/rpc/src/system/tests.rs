@@ -21,7 +21,7 @@ use crate::DenyUnsafe;
 use assert_matches::assert_matches;
 use futures::prelude::*;
 use jsonrpsee::{core::EmptyServerParams as EmptyParams, MethodsError as RpcError, RpcModule};
-use sc_network::{self, config::Role, PeerId};
+use sc_network::{self, config::Role, peer_store::BanTarget, PeerId};
 use sc_rpc_api::system::helpers::PeerInfo;
 use sc_utils::mpsc::tracing_unbounded;
 use sp_core::H256;
@@ -113,6 +113,28 @@ fn api<T: Into<Option<Status>>>(sync: T) -> RpcModule<System<Block>> {
 					let _ = sender
 						.send(vec!["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string()]);
 				},
+				Request::NetworkBanPeer(target, _duration, sender) => {
+					let _ = sender.send(
+						target
+							.parse::<BanTarget>()
+							.map(|_| ())
+							.map_err(error::Error::MalformattedPeerArg),
+					);
+				},
+				Request::NetworkUnbanPeer(target, sender) => {
+					let _ = sender.send(
+						target
+							.parse::<BanTarget>()
+							.map(|target| matches!(target, BanTarget::Network(_)))
+							.map_err(error::Error::MalformattedPeerArg),
+					);
+				},
+				Request::NetworkBannedPeers(sender) => {
+					let _ = sender.send(vec![BannedPeer {
+						target: "198.51.100.0/24".to_string(),
+						until: 1_700_000_000,
+					}]);
+				},
 				Request::NodeRoles(sender) => {
 					let _ = sender.send(vec![NodeRole::Authority]);
 				},
@@ -337,6 +359,55 @@ async fn system_network_reserved_peers() {
 	assert_eq!(reserved_peers, vec!["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string()],);
 }
 
+#[tokio::test]
+async fn system_network_ban_peer() {
+	let _: () = api(None)
+		.call("system_banPeer", ("QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV", 60))
+		.await
+		.expect("banning a peer id works");
+	let _: () = api(None)
+		.call("system_banPeer", ("198.51.100.0/24", 60))
+		.await
+		.expect("banning a network works");
+
+	assert_matches!(
+		api(None).call::<_, ()>("system_banPeer", ("not a peer", 60)).await,
+		Err(RpcError::JsonRpc(err)) if err.message().contains("is neither a peer ID")
+	);
+}
+
+#[tokio::test]
+async fn system_network_unban_peer() {
+	let unbanned: bool = api(None).call("system_unbanPeer", ["198.51.100.19"]).await.unwrap();
+	assert!(unbanned);
+
+	assert_matches!(
+		api(None).call::<_, bool>("system_unbanPeer", ["not a peer"]).await,
+		Err(RpcError::JsonRpc(err)) if err.message().contains("is neither a peer ID")
+	);
+}
+
+#[tokio::test]
+async fn system_network_banned_peers() {
+	let banned_peers: Vec<BannedPeer> =
+		api(None).call("system_bannedPeers", EmptyParams::new()).await.unwrap();
+	assert_eq!(
+		banned_peers,
+		vec![BannedPeer { target: "198.51.100.0/24".to_string(), until: 1_700_000_000 }],
+	);
+}
+
+#[tokio::test]
+async fn system_network_ban_peer_is_unsafe() {
+	let mut api = api(None);
+	api.extensions_mut().insert(DenyUnsafe::Yes);
+
+	assert_matches!(
+		api.call::<_, ()>("system_banPeer", ("198.51.100.0/24", 60)).await,
+		Err(RpcError::JsonRpc(err)) if err.message().contains("RPC call is unsafe")
+	);
+}
+
 #[test]
 fn test_add_reset_log_filter() {
 	const EXPECTED_BEFORE_ADD: &'static str = "EXPECTED_BEFORE_ADD";
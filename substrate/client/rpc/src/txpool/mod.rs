@@ -0,0 +1,114 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+
+//! Implementation of the [`TxPoolApiServer`] trait allowing to inspect and manually evict the
+//! content of the transaction pool.
+
+#[cfg(test)]
+mod tests;
+
+use jsonrpsee::Extensions;
+use sc_rpc_api::{check_if_safe, txpool::error::Error};
+use sc_transaction_pool_api::{
+	BlockHash, InPoolTransaction, PooledTransactionInfo, TransactionPool, TxHash,
+	TxInvalidityReportMap,
+};
+use sp_core::Bytes;
+use std::sync::Arc;
+
+pub use sc_rpc_api::txpool::{PoolTransaction, Queue, Source, TxPoolApiServer, ViewPlacement};
+
+/// The TxPool API. All methods are unsafe.
+pub struct TxPool<P> {
+	pool: Arc<P>,
+}
+
+impl<P> TxPool<P> {
+	/// Create a new TxPool API.
+	pub fn new(pool: Arc<P>) -> Self {
+		Self { pool }
+	}
+}
+
+impl<P: TransactionPool> TxPool<P> {
+	/// Returns the detailed information about every transaction in the pool.
+	fn inspect(&self) -> Result<Vec<PooledTransactionInfo<TxHash<P>, BlockHash<P>>>, Error> {
+		self.pool.inspect().ok_or(Error::Unsupported)
+	}
+
+	/// Forcibly removes the given transactions and returns the hashes of all removed ones.
+	fn remove(&self, hashes: impl IntoIterator<Item = TxHash<P>>) -> Vec<TxHash<P>> {
+		let hashes = hashes
+			.into_iter()
+			.map(|hash| (hash, None))
+			.collect::<TxInvalidityReportMap<_>>();
+		if hashes.is_empty() {
+			return Vec::new()
+		}
+
+		self.pool
+			.report_invalid(None, hashes)
+			.into_iter()
+			.map(|tx| tx.hash().clone())
+			.collect()
+	}
+}
+
+impl<P> TxPoolApiServer<TxHash<P>, BlockHash<P>> for TxPool<P>
+where
+	P: TransactionPool + Sync + Send + 'static,
+{
+	fn content(
+		&self,
+		ext: &Extensions,
+	) -> Result<Vec<PoolTransaction<TxHash<P>, BlockHash<P>>>, Error> {
+		check_if_safe(ext)?;
+
+		Ok(self.inspect()?.into_iter().map(Into::into).collect())
+	}
+
+	fn transaction(
+		&self,
+		ext: &Extensions,
+		hash: TxHash<P>,
+	) -> Result<Option<PoolTransaction<TxHash<P>, BlockHash<P>>>, Error> {
+		check_if_safe(ext)?;
+
+		Ok(self.inspect()?.into_iter().find(|tx| tx.hash == hash).map(Into::into))
+	}
+
+	fn remove_by_hash(
+		&self,
+		ext: &Extensions,
+		hashes: Vec<TxHash<P>>,
+	) -> Result<Vec<TxHash<P>>, Error> {
+		check_if_safe(ext)?;
+
+		Ok(self.remove(hashes))
+	}
+
+	fn remove_by_tag(&self, ext: &Extensions, tag: Bytes) -> Result<Vec<TxHash<P>>, Error> {
+		check_if_safe(ext)?;
+
+		let hashes = self
+			.inspect()?
+			.into_iter()
+			.filter(|tx| tx.provides.contains(&tag.0))
+			.map(|tx| tx.hash);
+		Ok(self.remove(hashes))
+	}
+}
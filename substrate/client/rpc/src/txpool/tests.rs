@@ -0,0 +1,157 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+
+use codec::Encode;
+use jsonrpsee::{core::EmptyServerParams as EmptyParams, RpcModule};
+use sc_client_api::HeaderBackend;
+use sc_rpc_api::DenyUnsafe;
+use sc_transaction_pool::{BasicPool, FullChainApi};
+use sc_transaction_pool_api::TransactionSource;
+use sp_core::H256;
+use substrate_test_runtime_client::{
+	runtime::{Block, Extrinsic, ExtrinsicBuilder, Transfer},
+	Backend, Client, Sr25519Keyring,
+};
+
+type FullTransactionPool = BasicPool<FullChainApi<Client<Backend>, Block>, Block>;
+
+fn uxt(sender: Sr25519Keyring, nonce: u64) -> Extrinsic {
+	let tx = Transfer {
+		amount: Default::default(),
+		nonce,
+		from: sender.into(),
+		to: Sr25519Keyring::Bob.into(),
+	};
+	ExtrinsicBuilder::new_transfer(tx).build()
+}
+
+struct TestSetup {
+	pub client: Arc<Client<Backend>>,
+	pub pool: Arc<FullTransactionPool>,
+}
+
+impl TestSetup {
+	fn new() -> Self {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let pool = Arc::from(BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			sp_core::testing::TaskExecutor::new(),
+			client.clone(),
+		));
+		TestSetup { client, pool }
+	}
+
+	fn to_rpc(&self, deny_unsafe: DenyUnsafe) -> RpcModule<TxPool<FullTransactionPool>> {
+		let mut module = TxPool::new(self.pool.clone()).into_rpc();
+		module.extensions_mut().insert(deny_unsafe);
+		module
+	}
+
+	async fn submit(&self, xt: Extrinsic) -> H256 {
+		let at = self.client.info().best_hash;
+		self.pool.submit_one(at, TransactionSource::External, xt).await.unwrap()
+	}
+}
+
+#[tokio::test]
+async fn txpool_content_lists_ready_and_future_transactions() {
+	let setup = TestSetup::new();
+	let api = setup.to_rpc(DenyUnsafe::No);
+
+	let ready = setup.submit(uxt(Sr25519Keyring::Alice, 0)).await;
+	let future = setup.submit(uxt(Sr25519Keyring::Bob, 5)).await;
+
+	let mut content: Vec<PoolTransaction<H256, H256>> =
+		api.call("txpool_content", EmptyParams::new()).await.unwrap();
+	content.sort_by_key(|tx| tx.hash != ready);
+
+	assert_eq!(content.len(), 2);
+	assert_eq!(content[0].hash, ready);
+	assert_eq!(content[0].bytes, uxt(Sr25519Keyring::Alice, 0).encode().len() as u64);
+	assert_eq!(content[0].source, Source::External);
+	assert!(content[0].submitted_at.is_some());
+	assert!(!content[0].watched);
+	assert_eq!(content[0].views.len(), 1);
+	assert_eq!(content[0].views[0].queue, Queue::Ready);
+	assert_eq!(content[1].hash, future);
+	assert!(!content[1].requires.is_empty());
+	assert_eq!(content[1].views[0].queue, Queue::Future);
+
+	let single: Option<PoolTransaction<H256, H256>> =
+		api.call("txpool_transaction", [future]).await.unwrap();
+	assert_eq!(single, Some(content[1].clone()));
+	let missing: Option<PoolTransaction<H256, H256>> =
+		api.call("txpool_transaction", [H256::zero()]).await.unwrap();
+	assert_eq!(missing, None);
+}
+
+#[tokio::test]
+async fn txpool_remove_by_hash_removes_dependent_transactions() {
+	let setup = TestSetup::new();
+	let api = setup.to_rpc(DenyUnsafe::No);
+
+	let xt0 = setup.submit(uxt(Sr25519Keyring::Alice, 0)).await;
+	let xt1 = setup.submit(uxt(Sr25519Keyring::Alice, 1)).await;
+	let other = setup.submit(uxt(Sr25519Keyring::Bob, 0)).await;
+
+	let mut removed: Vec<H256> = api.call("txpool_removeByHash", [vec![xt0]]).await.unwrap();
+	removed.sort();
+	let mut expected = vec![xt0, xt1];
+	expected.sort();
+
+	assert_eq!(removed, expected);
+	assert_eq!(setup.pool.status().ready, 1);
+	assert!(setup.pool.ready_transaction(&other).is_some());
+}
+
+#[tokio::test]
+async fn txpool_remove_by_tag_works() {
+	let setup = TestSetup::new();
+	let api = setup.to_rpc(DenyUnsafe::No);
+
+	let xt0 = setup.submit(uxt(Sr25519Keyring::Alice, 0)).await;
+	let _ = setup.submit(uxt(Sr25519Keyring::Bob, 0)).await;
+
+	let tx: PoolTransaction<H256, H256> =
+		api.call::<_, Option<_>>("txpool_transaction", [xt0]).await.unwrap().unwrap();
+	let removed: Vec<H256> =
+		api.call("txpool_removeByTag", [tx.provides[0].clone()]).await.unwrap();
+
+	assert_eq!(removed, vec![xt0]);
+	assert_eq!(setup.pool.status().ready, 1);
+}
+
+#[tokio::test]
+async fn deny_unsafe_works() {
+	let setup = TestSetup::new();
+	let api = setup.to_rpc(DenyUnsafe::Yes);
+	let _ = setup.submit(uxt(Sr25519Keyring::Alice, 0)).await;
+
+	let request = r#"{"jsonrpc":"2.0","method":"txpool_content","params":[],"id":1}"#;
+	let (resp, _) = api.raw_json_request(request, 1).await.expect("Raw calls should succeed");
+
+	assert_eq!(
+		resp,
+		r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"RPC call is unsafe to be called externally"}}"#
+	);
+	assert_eq!(setup.pool.status().ready, 1);
+}
@@ -18,6 +18,7 @@
 
 use crate::{
 	build_network_future, build_system_rpc_future,
+	chain_ops::Snapshot,
 	client::{Client, ClientConfig},
 	config::{Configuration, ExecutorConfiguration, KeystoreConfig, Multiaddr, PrometheusConfig},
 	error::Error,
@@ -27,11 +28,11 @@ use crate::{
 };
 use futures::{select, FutureExt, StreamExt};
 use jsonrpsee::RpcModule;
-use log::info;
+use log::{info, warn};
 use prometheus_endpoint::Registry;
 use sc_chain_spec::{get_extension, ChainSpec};
 use sc_client_api::{
-	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, BadBlocks,
+	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, AuxStore, BadBlocks,
 	BlockBackend, BlockchainEvents, ExecutorProvider, ForkBlocks, StorageProvider, UsageProvider,
 };
 use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, PruningMode};
@@ -71,8 +72,10 @@ use sc_rpc::{
 	offchain::OffchainApiServer,
 	state::{ChildStateApiServer, StateApiServer},
 	system::SystemApiServer,
+	txpool::TxPoolApiServer,
 	DenyUnsafe, SubscriptionTaskExecutor,
 };
+use sc_rpc_api::snapshot::SnapshotApiServer;
 use sc_rpc_spec_v2::{
 	archive::ArchiveApiServer,
 	chain_head::ChainHeadApiServer,
@@ -514,6 +517,19 @@ where
 
 	let rpc_id_provider = config.rpc.id_provider.take();
 
+	// Index events and extrinsics of finalized blocks for the `archive` RPC-v2 queries.
+	let archive_index = if !config.rpc.archive_index {
+		None
+	} else if is_archive_node(&config.state_pruning, config.blocks_pruning) {
+		let index =
+			Arc::new(sc_rpc_spec_v2::archive::ArchiveIndex::new(client.clone(), backend.clone()));
+		spawn_handle.spawn_blocking("archive-index", Some("rpc"), index.clone().run());
+		Some(index)
+	} else {
+		warn!("The archive index is only available on archive nodes, not indexing");
+		None
+	};
+
 	// jsonrpsee RPC
 	let gen_rpc_module = || {
 		gen_rpc_module(
@@ -528,6 +544,7 @@ where
 			&config.state_pruning,
 			config.blocks_pruning,
 			backend.clone(),
+			archive_index.clone(),
 			&*rpc_builder,
 		)
 	};
@@ -662,6 +679,13 @@ where
 	Ok(telemetry.handle())
 }
 
+/// Returns whether the node can respond to the `archive` RPC-v2 queries, which requires:
+/// - state pruning in archive mode: The storage of blocks is kept around
+/// - block pruning in archive mode: The block's body is kept around
+fn is_archive_node(state_pruning: &Option<PruningMode>, blocks_pruning: BlocksPruning) -> bool {
+	state_pruning.as_ref().map(|sp| sp.is_archive()).unwrap_or(false) && blocks_pruning.is_archive()
+}
+
 /// Generate RPC module using provided configuration
 pub fn gen_rpc_module<TBl, TBackend, TCl, TRpc, TExPool>(
 	spawn_handle: SpawnTaskHandle,
@@ -675,6 +699,7 @@ pub fn gen_rpc_module<TBl, TBackend, TCl, TRpc, TExPool>(
 	state_pruning: &Option<PruningMode>,
 	blocks_pruning: BlocksPruning,
 	backend: Arc<TBackend>,
+	archive_index: Option<Arc<sc_rpc_spec_v2::archive::ArchiveIndex<TBackend, TBl, TCl>>>,
 	rpc_builder: &(dyn Fn(SubscriptionTaskExecutor) -> Result<RpcModule<TRpc>, Error>),
 ) -> Result<RpcModule<()>, Error>
 where
@@ -744,21 +769,18 @@ where
 	.into_rpc();
 
 	// Part of the RPC v2 spec.
-	// An archive node that can respond to the `archive` RPC-v2 queries is a node with:
-	// - state pruning in archive mode: The storage of blocks is kept around
-	// - block pruning in archive mode: The block's body is kept around
-	let is_archive_node = state_pruning.as_ref().map(|sp| sp.is_archive()).unwrap_or(false) &&
-		blocks_pruning.is_archive();
 	let genesis_hash = client.hash(Zero::zero()).ok().flatten().expect("Genesis block exists; qed");
-	if is_archive_node {
-		let archive_v2 = sc_rpc_spec_v2::archive::Archive::new(
+	if is_archive_node(state_pruning, blocks_pruning) {
+		let mut archive_v2 = sc_rpc_spec_v2::archive::Archive::new(
 			client.clone(),
 			backend.clone(),
 			genesis_hash,
 			task_executor.clone(),
-		)
-		.into_rpc();
-		rpc_api.merge(archive_v2).map_err(|e| Error::Application(e.into()))?;
+		);
+		if let Some(index) = archive_index {
+			archive_v2 = archive_v2.with_index(index);
+		}
+		rpc_api.merge(archive_v2.into_rpc()).map_err(|e| Error::Application(e.into()))?;
 	}
 
 	// ChainSpec RPC-v2.
@@ -769,6 +791,8 @@ where
 	)
 	.into_rpc();
 
+	let txpool = sc_rpc::txpool::TxPool::new(transaction_pool.clone()).into_rpc();
+
 	let author = sc_rpc::author::Author::new(
 		client.clone(),
 		transaction_pool,
@@ -779,6 +803,8 @@ where
 
 	let system = sc_rpc::system::System::new(system_info, system_rpc_tx).into_rpc();
 
+	let snapshot = Snapshot::new(client.clone(), backend.clone()).into_rpc();
+
 	if let Some(storage) = backend.offchain_storage() {
 		let offchain = sc_rpc::offchain::Offchain::new(storage).into_rpc();
 
@@ -799,6 +825,8 @@ where
 	rpc_api.merge(system).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(state).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(child_state).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(txpool).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(snapshot).map_err(|e| Error::Application(e.into()))?;
 	// Additional [`RpcModule`]s defined in the node to fit the specific blockchain
 	let extra_rpcs = rpc_builder(task_executor.clone())?;
 	rpc_api.merge(extra_rpcs).map_err(|e| Error::Application(e.into()))?;
@@ -857,6 +885,7 @@ where
 		+ BlockBackend<Block>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ HeaderBackend<Block>
 		+ BlockchainEvents<Block>
 		+ 'static,
@@ -1018,6 +1047,7 @@ where
 		+ BlockBackend<Block>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ HeaderBackend<Block>
 		+ BlockchainEvents<Block>
 		+ 'static,
@@ -1203,6 +1233,7 @@ where
 		+ BlockBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1316,6 +1347,7 @@ where
 		+ BlockBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
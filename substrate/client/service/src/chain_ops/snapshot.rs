@@ -0,0 +1,613 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::Error;
+use codec::{Decode, Encode, IoReader};
+use jsonrpsee::Extensions;
+use log::{info, warn};
+use sc_client_api::{
+	backend::{Backend, BackendTransaction},
+	BlockBackend, HeaderBackend, StorageProvider,
+};
+use sc_consensus::{
+	BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, StateAction, StorageChanges,
+};
+use sc_rpc_api::{
+	check_if_safe,
+	snapshot::{error::Error as SnapshotError, ExportedSnapshot, SnapshotApiServer},
+};
+use sp_api::CallApiAt;
+use sp_consensus::BlockOrigin;
+use sp_core::{
+	storage::{well_known_keys, ChildInfo, StateVersion},
+	Hasher,
+};
+use sp_runtime::{
+	traits::{Block as BlockT, HashingFor, Header as HeaderT, NumberFor, One, Zero},
+	Justifications,
+};
+use sp_trie::{trie_visit, ChildReference, HashDBT, LayoutV0, LayoutV1, ProcessEncodedNode};
+use std::{
+	fs::File,
+	io::{BufWriter, Read, Write},
+	marker::PhantomData,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+};
+
+/// Magic bytes every snapshot starts with.
+const SNAPSHOT_MAGIC: [u8; 8] = *b"substsnp";
+
+/// Version of the snapshot format written by [`export_snapshot`].
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Auxiliary storage key of the hash of the snapshotted block while a snapshot is imported.
+const SNAPSHOT_IMPORT_KEY: &[u8] = b"snapshot_import";
+
+/// Size of the trie nodes an import buffers before writing them to the database.
+const STATE_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+/// A storage entry of a snapshot.
+#[derive(Encode, Decode)]
+enum SnapshotEntry {
+	/// Key and value of the top trie.
+	Top(Vec<u8>, Vec<u8>),
+	/// Unprefixed storage key of a default child trie, key and value.
+	Child(Vec<u8>, Vec<u8>, Vec<u8>),
+	/// Marks the end of the state.
+	End,
+}
+
+/// Export a snapshot of the last finalized block: its header, its state and the headers of all
+/// blocks between genesis and the block. Returns the hash and the number of the block.
+///
+/// The block is pinned while it is exported, so this can be used with the client of a running
+/// node, see [`Snapshot`].
+///
+/// A snapshot starts with magic bytes and the little-endian `u32` format version, followed by a
+/// zstd compressed stream of SCALE encoded items: the genesis hash, the header of the
+/// snapshotted block, its justifications, the state version of its runtime, its storage entries
+/// in key order terminated by an end marker and finally the headers from block `1` up to the
+/// parent of the snapshotted block, in ascending order. Every header is followed by the
+/// justifications stored for its block, if any.
+pub fn export_snapshot<B, BA, C>(
+	client: Arc<C>,
+	backend: Arc<BA>,
+	output: impl Write,
+) -> Result<(B::Hash, NumberFor<B>), Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: HeaderBackend<B> + BlockBackend<B> + StorageProvider<B, BA> + CallApiAt<B>,
+{
+	let info = client.info();
+	if info.finalized_number.is_zero() {
+		return Err("No block has been finalized yet, there is nothing to snapshot.".into())
+	}
+
+	let hash = info.finalized_hash;
+	backend.pin_block(hash)?;
+	let result = write_snapshot(&*client, info.genesis_hash, hash, output);
+	backend.unpin_block(hash);
+	result.map(|()| (hash, info.finalized_number))
+}
+
+/// Snapshot RPC API, exporting snapshots from the client of the running node.
+pub struct Snapshot<B, BA, C> {
+	client: Arc<C>,
+	backend: Arc<BA>,
+	/// Set while a snapshot is exported.
+	exporting: AtomicBool,
+	_phantom: PhantomData<B>,
+}
+
+impl<B, BA, C> Snapshot<B, BA, C> {
+	/// Create new instance of the snapshot RPC API.
+	pub fn new(client: Arc<C>, backend: Arc<BA>) -> Self {
+		Snapshot { client, backend, exporting: AtomicBool::new(false), _phantom: PhantomData }
+	}
+}
+
+impl<B, BA, C> SnapshotApiServer<B::Hash, NumberFor<B>> for Snapshot<B, BA, C>
+where
+	B: BlockT,
+	BA: Backend<B> + 'static,
+	C: HeaderBackend<B>
+		+ BlockBackend<B>
+		+ StorageProvider<B, BA>
+		+ CallApiAt<B>
+		+ Send
+		+ Sync
+		+ 'static,
+{
+	fn export(
+		&self,
+		ext: &Extensions,
+		path: String,
+	) -> Result<ExportedSnapshot<B::Hash, NumberFor<B>>, SnapshotError> {
+		check_if_safe(ext)?;
+
+		if self.exporting.swap(true, Ordering::SeqCst) {
+			return Err(SnapshotError::ExportInProgress)
+		}
+		let result = File::create(&path).map_err(Error::from).and_then(|file| {
+			export_snapshot(self.client.clone(), self.backend.clone(), BufWriter::new(file))
+		});
+		self.exporting.store(false, Ordering::SeqCst);
+
+		let (hash, number) = result.map_err(|e| SnapshotError::Export(e.to_string()))?;
+		Ok(ExportedSnapshot { hash, number })
+	}
+}
+
+fn write_snapshot<B, BA, C>(
+	client: &C,
+	genesis_hash: B::Hash,
+	hash: B::Hash,
+	mut output: impl Write,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: HeaderBackend<B> + BlockBackend<B> + StorageProvider<B, BA> + CallApiAt<B>,
+{
+	let header = client.expect_header(hash)?;
+	let number = *header.number();
+	let state_version = client
+		.runtime_version_at(hash)
+		.map_err(sp_blockchain::Error::RuntimeApiError)?
+		.state_version();
+	info!("Exporting snapshot of block #{} ({})", number, hash);
+
+	output.write_all(&SNAPSHOT_MAGIC)?;
+	output.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+
+	let mut output = zstd::Encoder::new(output, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+	output.write_all(&genesis_hash.encode())?;
+	output.write_all(&header.encode())?;
+	output.write_all(&client.justifications(hash)?.encode())?;
+	output.write_all(&state_version.encode())?;
+
+	let mut entries = 0u64;
+	for (key, value) in client.storage_pairs(hash, None, None)? {
+		// Child trie roots are recomputed on import, only the child tries themselves are written.
+		if let Some(child_key) =
+			key.0.strip_prefix(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX)
+		{
+			let child_info = ChildInfo::new_default(child_key);
+			for child_entry in client.child_storage_keys(hash, child_info.clone(), None, None)? {
+				if let Some(child_value) = client.child_storage(hash, &child_info, &child_entry)? {
+					let entry =
+						SnapshotEntry::Child(child_key.to_vec(), child_entry.0, child_value.0);
+					output.write_all(&entry.encode())?;
+					entries += 1;
+				}
+			}
+			continue
+		}
+
+		output.write_all(&SnapshotEntry::Top(key.0, value.0).encode())?;
+		entries += 1;
+	}
+	output.write_all(&SnapshotEntry::End.encode())?;
+	info!("Exported {} storage entries", entries);
+
+	let mut block = NumberFor::<B>::one();
+	while block < number {
+		let block_hash = client
+			.hash(block)?
+			.ok_or_else(|| format!("Header of block #{} not found", block))?;
+		output.write_all(&client.expect_header(block_hash)?.encode())?;
+		output.write_all(&client.justifications(block_hash)?.encode())?;
+
+		if (block % 10_000u32.into()).is_zero() {
+			info!("#{}", block);
+		}
+		block += One::one();
+	}
+
+	output.finish()?.flush()?;
+	info!("Exported snapshot of block #{} ({})", number, hash);
+	Ok(())
+}
+
+/// Import a snapshot written by [`export_snapshot`] into an empty database.
+///
+/// The state is written to the database in chunks of trie nodes while it is read, so the whole
+/// state never has to be held in memory. Its trie root is checked against the state root of the
+/// snapshotted header, which is then imported as the finalized block. The headers leading up to
+/// it are imported afterwards into the resulting block gap, each one checked to extend the chain
+/// from the local genesis block. Block bodies are not part of a snapshot.
+///
+/// Blocks are imported through `block_import`, so consensus engines can reset their data from
+/// the imported state the same way they do after warp sync.
+///
+/// The database must not hold the state of any block but genesis. The trie nodes written by a
+/// failed import can't be told apart from the genesis state, so they are not removed: the import
+/// is marked in the auxiliary storage and the database has to be purged before retrying.
+pub async fn import_snapshot<B, BA, C, BI>(
+	client: Arc<C>,
+	backend: Arc<BA>,
+	block_import: BI,
+	mut input: impl Read,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: HeaderBackend<B>,
+	BI: BlockImport<B, Error = sp_consensus::Error>,
+{
+	let info = client.info();
+	if backend.get_aux(SNAPSHOT_IMPORT_KEY)?.is_some() {
+		return Err("A previous snapshot import failed, purge the database before retrying.".into())
+	}
+	if let Some((_, number)) = info.finalized_state.filter(|(_, number)| !number.is_zero()) {
+		return Err(format!(
+			"A snapshot can only be imported into an empty database, the state of block #{} is \
+			already imported.",
+			number,
+		)
+		.into())
+	}
+	if !info.best_number.is_zero() {
+		return Err(format!(
+			"A snapshot can only be imported into an empty database, best block is #{}.",
+			info.best_number,
+		)
+		.into())
+	}
+
+	let mut magic = [0u8; 8];
+	input.read_exact(&mut magic)?;
+	if magic != SNAPSHOT_MAGIC {
+		return Err("Input is not a snapshot.".into())
+	}
+
+	let mut version = [0u8; 4];
+	input.read_exact(&mut version)?;
+	let version = u32::from_le_bytes(version);
+	if version != SNAPSHOT_VERSION {
+		return Err(format!(
+			"Unsupported snapshot version {}, expected {}.",
+			version, SNAPSHOT_VERSION,
+		)
+		.into())
+	}
+
+	let mut input = IoReader(zstd::Decoder::new(input)?);
+
+	let genesis_hash: B::Hash = decode(&mut input)?;
+	if genesis_hash != info.genesis_hash {
+		return Err(format!(
+			"Snapshot belongs to a different chain, genesis {} doesn't match {}.",
+			genesis_hash, info.genesis_hash,
+		)
+		.into())
+	}
+
+	let header: B::Header = decode(&mut input)?;
+	let justifications: Option<Justifications> = decode(&mut input)?;
+	let state_version: StateVersion = decode(&mut input)?;
+	let hash = header.hash();
+	info!("Importing snapshot of block #{} ({})", header.number(), hash);
+
+	// Nothing was written so far, from now on a failure leaves the database unusable.
+	backend.insert_aux(&[(SNAPSHOT_IMPORT_KEY, &hash.encode()[..])], &[])?;
+	let result = import_snapshot_data(
+		&*backend,
+		block_import,
+		&mut input,
+		genesis_hash,
+		header,
+		justifications,
+		state_version,
+	)
+	.await;
+	if result.is_err() {
+		warn!("Failed to import the snapshot, purge the database before retrying.");
+		return result
+	}
+	backend.insert_aux(&[], &[SNAPSHOT_IMPORT_KEY])?;
+	Ok(())
+}
+
+/// Import the state and the headers of a snapshot, see [`import_snapshot`].
+async fn import_snapshot_data<B, BA, BI>(
+	backend: &BA,
+	block_import: BI,
+	input: &mut impl codec::Input,
+	genesis_hash: B::Hash,
+	header: B::Header,
+	justifications: Option<Justifications>,
+	state_version: StateVersion,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	BI: BlockImport<B, Error = sp_consensus::Error>,
+{
+	let hash = header.hash();
+	let number = *header.number();
+	let mut entries = TopEntries {
+		reader: EntryReader { input: &mut *input, pending: None, error: None, entries: 0 },
+		backend,
+		state_version,
+		_phantom: PhantomData,
+	};
+	let state_root = write_trie::<B, _>(backend, state_version, None, &mut entries);
+	if let Some(error) = entries.reader.error.take() {
+		return Err(error)
+	}
+	let state_root = state_root?;
+	info!("Imported {} storage entries", entries.reader.entries);
+
+	if state_root != *header.state_root() {
+		return Err(format!(
+			"State root {} of the snapshot doesn't match the header of block #{}.",
+			state_root, number,
+		)
+		.into())
+	}
+
+	// The headers below the block are missing until they are imported into the gap.
+	let mut block = BlockImportParams::new(BlockOrigin::File, header.clone());
+	block.justifications = justifications;
+	block.state_action = StateAction::ApplyChanges(StorageChanges::Imported);
+	block.finalized = true;
+	block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+	block.create_gap = number > One::one();
+	ensure_imported(block_import.import_block(block).await?, hash)?;
+
+	let mut parent_hash = genesis_hash;
+	let mut block_number = NumberFor::<B>::one();
+	while block_number < number {
+		let gap_header: B::Header = decode(&mut *input)?;
+		let justifications: Option<Justifications> = decode(&mut *input)?;
+		if *gap_header.number() != block_number || *gap_header.parent_hash() != parent_hash {
+			return Err(format!(
+				"Header #{} of the snapshot doesn't extend the chain.",
+				block_number,
+			)
+			.into())
+		}
+		parent_hash = gap_header.hash();
+
+		let mut block = BlockImportParams::new(BlockOrigin::File, gap_header);
+		block.justifications = justifications;
+		block.state_action = StateAction::Skip;
+		block.fork_choice = Some(ForkChoiceStrategy::Custom(false));
+		ensure_imported(block_import.import_block(block).await?, parent_hash)?;
+
+		if (block_number % 10_000u32.into()).is_zero() {
+			info!("#{}", block_number);
+		}
+		block_number += One::one();
+	}
+
+	if *header.parent_hash() != parent_hash {
+		return Err(
+			format!("Snapshotted block #{} doesn't extend the imported headers.", number,).into()
+		)
+	}
+
+	info!("Imported snapshot of block #{} ({})", number, hash);
+	Ok(())
+}
+
+/// Write the trie built from `entries` to the database and return its root.
+///
+/// The entries have to be sorted by key.
+fn write_trie<B: BlockT, BA: Backend<B>>(
+	backend: &BA,
+	state_version: StateVersion,
+	keyspace: Option<&[u8]>,
+	entries: impl Iterator<Item = (Vec<u8>, Vec<u8>)>,
+) -> Result<B::Hash, Error> {
+	let mut writer = NodeWriter::<B, BA> {
+		backend,
+		keyspace,
+		nodes: Default::default(),
+		size: 0,
+		root: None,
+		error: None,
+	};
+	match state_version {
+		StateVersion::V0 => trie_visit::<LayoutV0<HashingFor<B>>, _, _, _, _>(entries, &mut writer),
+		StateVersion::V1 => trie_visit::<LayoutV1<HashingFor<B>>, _, _, _, _>(entries, &mut writer),
+	}
+	writer.flush();
+
+	match (writer.error, writer.root) {
+		(Some(error), _) => Err(error.into()),
+		(None, Some(root)) => Ok(root),
+		(None, None) => Err("Trie root was not computed.".into()),
+	}
+}
+
+/// Collects the nodes of a trie built by [`trie_visit`] and writes them to the database in
+/// chunks of [`STATE_CHUNK_SIZE`].
+struct NodeWriter<'a, B: BlockT, BA> {
+	backend: &'a BA,
+	/// Keyspace of a child trie, prepended to the prefixes of its nodes.
+	keyspace: Option<&'a [u8]>,
+	nodes: BackendTransaction<HashingFor<B>>,
+	size: usize,
+	root: Option<B::Hash>,
+	error: Option<sp_blockchain::Error>,
+}
+
+impl<B: BlockT, BA: Backend<B>> NodeWriter<'_, B, BA> {
+	fn insert(&mut self, prefix: (&[u8], Option<u8>), value: &[u8]) -> B::Hash {
+		let hash = match self.keyspace {
+			Some(keyspace) => {
+				let key = [keyspace, prefix.0].concat();
+				self.nodes.insert((&key, prefix.1), value)
+			},
+			None => self.nodes.insert(prefix, value),
+		};
+
+		self.size += value.len();
+		if self.size >= STATE_CHUNK_SIZE {
+			self.flush();
+		}
+		hash
+	}
+
+	fn flush(&mut self) {
+		self.size = 0;
+		let nodes = std::mem::take(&mut self.nodes);
+		if self.error.is_none() {
+			self.error = self.backend.import_state_nodes(nodes).err();
+		}
+	}
+}
+
+impl<B: BlockT, BA: Backend<B>> ProcessEncodedNode<B::Hash> for NodeWriter<'_, B, BA> {
+	fn process(
+		&mut self,
+		prefix: (&[u8], Option<u8>),
+		encoded_node: Vec<u8>,
+		is_root: bool,
+	) -> ChildReference<B::Hash> {
+		let len = encoded_node.len();
+		// Nodes shorter than a hash are inlined into their parent.
+		if !is_root && len < <HashingFor<B> as Hasher>::LENGTH {
+			let mut hash = B::Hash::default();
+			hash.as_mut()[..len].copy_from_slice(&encoded_node);
+			return ChildReference::Inline(hash, len)
+		}
+
+		let hash = self.insert(prefix, &encoded_node);
+		if is_root {
+			self.root = Some(hash);
+		}
+		ChildReference::Hash(hash)
+	}
+
+	fn process_inner_hashed_value(&mut self, prefix: (&[u8], Option<u8>), value: &[u8]) -> B::Hash {
+		self.insert(prefix, value)
+	}
+}
+
+/// Reads the storage entries of a snapshot one by one.
+struct EntryReader<'a, R> {
+	input: &'a mut R,
+	/// An entry that was read but not consumed.
+	pending: Option<SnapshotEntry>,
+	error: Option<Error>,
+	entries: u64,
+}
+
+impl<R: codec::Input> EntryReader<'_, R> {
+	fn next(&mut self) -> Option<SnapshotEntry> {
+		if let Some(entry) = self.pending.take() {
+			return Some(entry)
+		}
+		if self.error.is_some() {
+			return None
+		}
+
+		match decode(&mut *self.input) {
+			Ok(entry) => {
+				if !matches!(entry, SnapshotEntry::End) {
+					self.entries += 1;
+				}
+				Some(entry)
+			},
+			Err(e) => {
+				self.error = Some(e);
+				None
+			},
+		}
+	}
+}
+
+/// Iterates over the top trie entries of a snapshot. The child tries are written to the
+/// database as they are reached, yielding the entries of their roots.
+struct TopEntries<'a, 'b, R, B, BA> {
+	reader: EntryReader<'a, R>,
+	backend: &'b BA,
+	state_version: StateVersion,
+	_phantom: PhantomData<B>,
+}
+
+impl<R: codec::Input, B: BlockT, BA: Backend<B>> Iterator for TopEntries<'_, '_, R, B, BA> {
+	type Item = (Vec<u8>, Vec<u8>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.reader.next()? {
+			SnapshotEntry::Top(key, value) => Some((key, value)),
+			SnapshotEntry::Child(child_key, key, value) => {
+				let child_info = ChildInfo::new_default(&child_key);
+				self.reader.pending = Some(SnapshotEntry::Child(child_key.clone(), key, value));
+				let entries = ChildEntries { reader: &mut self.reader, child_key };
+				match write_trie::<B, _>(
+					self.backend,
+					self.state_version,
+					Some(child_info.keyspace()),
+					entries,
+				) {
+					Ok(root) =>
+						Some((child_info.prefixed_storage_key().into_inner(), root.encode())),
+					Err(e) => {
+						self.reader.error.get_or_insert(e);
+						None
+					},
+				}
+			},
+			SnapshotEntry::End => {
+				self.reader.pending = Some(SnapshotEntry::End);
+				None
+			},
+		}
+	}
+}
+
+/// Iterates over the consecutive entries of a single child trie.
+struct ChildEntries<'a, 'b, R> {
+	reader: &'a mut EntryReader<'b, R>,
+	child_key: Vec<u8>,
+}
+
+impl<R: codec::Input> Iterator for ChildEntries<'_, '_, R> {
+	type Item = (Vec<u8>, Vec<u8>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.reader.next()? {
+			SnapshotEntry::Child(child_key, key, value) if child_key == self.child_key =>
+				Some((key, value)),
+			entry => {
+				self.reader.pending = Some(entry);
+				None
+			},
+		}
+	}
+}
+
+fn decode<T: Decode>(input: &mut impl codec::Input) -> Result<T, Error> {
+	T::decode(input).map_err(|e| format!("Invalid snapshot: {}", e).into())
+}
+
+fn ensure_imported<H: std::fmt::Display>(result: ImportResult, hash: H) -> Result<(), Error> {
+	match result {
+		ImportResult::Imported(_) => Ok(()),
+		other => Err(format!("Failed to import block {}: {:?}", hash, other).into()),
+	}
+}
@@ -77,7 +77,9 @@ use sp_state_machine::{
 	ChildStorageCollection, KeyValueStates, KeyValueStorageLevel, StorageCollection,
 	MAX_NESTED_TRIE_DEPTH,
 };
-use sp_trie::{proof_size_extension::ProofSizeExt, CompactProof, MerkleValue, StorageProof};
+use sp_trie::{
+	proof_size_extension::ProofSizeExt, CompactProof, MerkleValue, PrefixedMemoryDB, StorageProof,
+};
 use std::{
 	collections::{HashMap, HashSet},
 	marker::PhantomData,
@@ -658,6 +660,13 @@ where
 						}
 						None
 					},
+					sc_consensus::StorageChanges::Imported => {
+						// The trie nodes are already in the database, the block only has to be
+						// committed with its state.
+						self.backend
+							.begin_state_operation(&mut operation.op, Default::default())?;
+						None
+					},
 				};
 
 				storage_changes
@@ -1386,6 +1395,27 @@ where
 
 		Ok(state)
 	}
+
+	fn import_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: CompactProof,
+		start_key: &[Vec<u8>],
+	) -> sp_blockchain::Result<(KeyValueStates, usize)> {
+		// Child trie nodes are decoded into their key space, the way they are stored in the
+		// database.
+		let mut nodes = PrefixedMemoryDB::<HashingFor<Block>>::default();
+		sp_trie::decode_compact_keyspaced::<sp_state_machine::LayoutV0<HashingFor<Block>>, _, _>(
+			&mut nodes,
+			proof.iter_compact_encoded_nodes(),
+			Some(&root),
+		)
+		.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))?;
+		let state = self.verify_range_proof(root, proof, start_key)?;
+		self.backend.import_state_nodes(nodes)?;
+
+		Ok(state)
+	}
 }
 
 impl<B, E, Block, RA> ExecutorProvider<Block> for Client<B, E, Block, RA>
@@ -321,6 +321,11 @@ pub struct RpcConfiguration {
 	pub rate_limit_whitelisted_ips: Vec<IpNetwork>,
 	/// RPC rate limit trust proxy headers.
 	pub rate_limit_trust_proxy_headers: bool,
+	/// Index the events and extrinsics of finalized blocks to serve the `archive_v1_find*`
+	/// queries.
+	///
+	/// Only takes effect on archive nodes.
+	pub archive_index: bool,
 }
 
 /// Runtime executor configuration.
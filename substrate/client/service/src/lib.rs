@@ -35,6 +35,7 @@ use crate::config::Multiaddr;
 use std::{
 	collections::HashMap,
 	net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+	time::{Duration, UNIX_EPOCH},
 };
 
 use codec::{Decode, Encode};
@@ -43,8 +44,8 @@ use jsonrpsee::RpcModule;
 use log::{debug, error, trace, warn};
 use sc_client_api::{blockchain::HeaderBackend, BlockBackend, BlockchainEvents, ProofProvider};
 use sc_network::{
-	config::MultiaddrWithPeerId, service::traits::NetworkService, NetworkBackend, NetworkBlock,
-	NetworkPeers, NetworkStateInfo,
+	config::MultiaddrWithPeerId, peer_store::BanTarget, service::traits::NetworkService,
+	NetworkBackend, NetworkBlock, NetworkPeers, NetworkStateInfo,
 };
 use sc_network_sync::SyncingService;
 use sc_network_types::PeerId;
@@ -348,6 +349,36 @@ pub async fn build_system_rpc_future<
 				let _ =
 					sender.send(reserved_peers.iter().map(|peer_id| peer_id.to_base58()).collect());
 			},
+			sc_rpc::system::Request::NetworkBanPeer(target, duration, sender) => {
+				let result = target
+					.parse::<BanTarget>()
+					.map(|target| network_service.ban_peer(target, Duration::from_secs(duration)))
+					.map_err(sc_rpc::system::error::Error::MalformattedPeerArg);
+				let _ = sender.send(result);
+			},
+			sc_rpc::system::Request::NetworkUnbanPeer(target, sender) => {
+				let result = target
+					.parse::<BanTarget>()
+					.map(|target| network_service.unban_peer(&target))
+					.map_err(sc_rpc::system::error::Error::MalformattedPeerArg);
+				let _ = sender.send(result);
+			},
+			sc_rpc::system::Request::NetworkBannedPeers(sender) => {
+				use sc_rpc::system::BannedPeer;
+
+				let banned_peers = network_service
+					.banned_peers()
+					.into_iter()
+					.map(|ban| BannedPeer {
+						target: ban.target.to_string(),
+						until: ban
+							.until
+							.duration_since(UNIX_EPOCH)
+							.map_or(0, |until| until.as_secs()),
+					})
+					.collect();
+				let _ = sender.send(banned_peers);
+			},
 			sc_rpc::system::Request::NodeRoles(sender) => {
 				use sc_rpc::system::NodeRole;
 
@@ -29,7 +29,14 @@ use sc_consensus::{
 	BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
 };
 use sc_executor::WasmExecutor;
-use sc_service::client::{new_with_backend, Client, LocalCallExecutor};
+use sc_rpc_api::{
+	snapshot::{ExportedSnapshot, SnapshotApiServer},
+	DenyUnsafe,
+};
+use sc_service::{
+	chain_ops::Snapshot,
+	client::{new_with_backend, Client, LocalCallExecutor},
+};
 use sp_api::ProvideRuntimeApi;
 use sp_consensus::{BlockOrigin, Error as ConsensusError, SelectChain};
 use sp_core::{testing::TaskExecutor, traits::CallContext, H256};
@@ -2307,3 +2314,117 @@ fn finalize_after_best_block_updates_best() {
 	assert_eq!(client.chain_info().finalized_hash, a3.hash());
 	assert_eq!(client.chain_info().best_hash, a3.hash());
 }
+
+#[test]
+fn snapshot_export_and_import_works() {
+	let child_info = ChildInfo::new_default(b"child");
+	let builder = || {
+		TestClientBuilder::new().add_extra_child_storage(&child_info, b"key".to_vec(), vec![1; 32])
+	};
+	let (client, backend) = builder().build_with_backend();
+	let client = Arc::new(client);
+
+	// G -> A1 -> A2 -> A3 -> A4, A3 finalized
+	let mut blocks = Vec::new();
+	let mut parent = client.chain_info().genesis_hash;
+	for number in 0..4 {
+		let mut block_builder = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(parent)
+			.with_parent_block_number(number)
+			.build()
+			.unwrap();
+		if number == 0 {
+			block_builder
+				.push_transfer(Transfer {
+					from: Sr25519Keyring::Alice.into(),
+					to: Sr25519Keyring::Ferdie.into(),
+					amount: 1 * DOLLARS,
+					nonce: 0,
+				})
+				.unwrap();
+		}
+		let block = block_builder.build().unwrap().block;
+		block_on(client.import(BlockOrigin::Own, block.clone())).unwrap();
+		parent = block.hash();
+		blocks.push(parent);
+	}
+	client.finalize_block(blocks[2], None).unwrap();
+
+	let mut snapshot = Vec::new();
+	let exported =
+		sc_service::chain_ops::export_snapshot(client.clone(), backend.clone(), &mut snapshot)
+			.unwrap();
+	assert_eq!(exported, (blocks[2], 3));
+
+	// the RPC exports the same snapshot from the running client.
+	let file = tempfile::NamedTempFile::new().unwrap();
+	let mut ext = jsonrpsee::Extensions::new();
+	ext.insert(DenyUnsafe::No);
+	let exported = Snapshot::new(client.clone(), backend)
+		.export(&ext, file.path().to_str().unwrap().into())
+		.unwrap();
+	assert_eq!(exported, ExportedSnapshot { hash: blocks[2], number: 3 });
+	assert_eq!(std::fs::read(file.path()).unwrap(), snapshot);
+
+	// a chain with a different genesis refuses the snapshot.
+	let (other, other_backend) = TestClientBuilder::new().build_with_backend();
+	let other = Arc::new(other);
+	assert!(block_on(sc_service::chain_ops::import_snapshot(
+		other.clone(),
+		other_backend,
+		other.clone(),
+		&snapshot[..]
+	))
+	.is_err());
+
+	// a failed import has to be purged before retrying.
+	let (failed, failed_backend) = builder().build_with_backend();
+	let failed = Arc::new(failed);
+	for input in [&snapshot[..snapshot.len() / 2], &snapshot[..]] {
+		assert!(block_on(sc_service::chain_ops::import_snapshot(
+			failed.clone(),
+			failed_backend.clone(),
+			failed.clone(),
+			input,
+		))
+		.is_err());
+	}
+
+	let (target, target_backend) = builder().build_with_backend();
+	let target = Arc::new(target);
+	block_on(sc_service::chain_ops::import_snapshot(
+		target.clone(),
+		target_backend.clone(),
+		target.clone(),
+		&snapshot[..],
+	))
+	.unwrap();
+
+	let info = target.chain_info();
+	assert_eq!(info.finalized_hash, blocks[2]);
+	assert_eq!(info.best_hash, blocks[2]);
+	assert_eq!(info.block_gap, None);
+	assert_eq!(target.hash(1).unwrap(), Some(blocks[0]));
+	assert_eq!(target.header(blocks[1]).unwrap(), client.header(blocks[1]).unwrap());
+	assert_eq!(target.header(blocks[3]).unwrap(), None);
+
+	let pairs = |client: &substrate_test_runtime_client::TestClient| {
+		client.storage_pairs(blocks[2], None, None).unwrap().collect::<Vec<_>>()
+	};
+	assert_eq!(pairs(&target), pairs(&client));
+	assert_eq!(
+		target
+			.child_storage(blocks[2], &child_info, &StorageKey(b"key".to_vec()))
+			.unwrap(),
+		Some(sp_storage::StorageData(vec![1; 32])),
+	);
+
+	// the snapshot can only be imported into an empty database.
+	assert!(block_on(sc_service::chain_ops::import_snapshot(
+		target.clone(),
+		target_backend,
+		target.clone(),
+		&snapshot[..]
+	))
+	.is_err());
+}
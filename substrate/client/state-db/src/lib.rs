@@ -39,7 +39,9 @@
 //!
 //! # Pruning.
 //! See `RefWindow` for pruning algorithm details. `StateDb` prunes on each canonicalization until
-//! pruning constraints are satisfied.
+//! pruning constraints are satisfied. The pruning window can be bounded by a number of blocks, by
+//! the size of the nodes it holds, or both. Optionally, the full state of periodic checkpoint
+//! blocks is kept indefinitely while everything in between is pruned.
 
 mod noncanonical;
 mod pruning;
@@ -145,6 +147,9 @@ pub enum StateDbError {
 	BlockUnavailable,
 	/// Block record is missing from the pruning window
 	BlockMissing,
+	/// The pruning window is only bounded by size, which requires a database with reference
+	/// counting.
+	UnboundedPruningWindow,
 }
 
 impl<E> From<StateDbError> for Error<E> {
@@ -196,6 +201,11 @@ impl fmt::Debug for StateDbError {
 				write!(f, "Trying to get a block record from db while it is not commit to db yet")
 			},
 			Self::BlockMissing => write!(f, "Block record is missing from the pruning window"),
+			Self::UnboundedPruningWindow => write!(
+				f,
+				"A pruning window bounded by size only requires a database with reference \
+				 counting, bound it by a number of blocks as well"
+			),
 		}
 	}
 }
@@ -222,8 +232,17 @@ pub struct CommitSet<H: Hash> {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Constraints {
 	/// Maximum blocks. Defaults to 0 when unspecified, effectively keeping only non-canonical
-	/// states.
+	/// states, unless `max_window_bytes` is specified.
 	pub max_blocks: Option<u32>,
+	/// Maximum size of the trie nodes inserted by the blocks of the pruning window.
+	///
+	/// This is an estimate of the disk space the window takes: it counts the encoded size of
+	/// the inserted nodes, without the keys, the database overhead and compression. Without
+	/// `max_blocks`, it is only supported by databases with reference counting, since the
+	/// window of other databases is kept in memory.
+	pub max_window_bytes: Option<u64>,
+	/// Keep the full state of every canonical block whose number is a multiple of this interval.
+	pub checkpoint_interval: Option<u32>,
 }
 
 /// Pruning mode.
@@ -240,7 +259,7 @@ pub enum PruningMode {
 impl PruningMode {
 	/// Create a mode that keeps given number of blocks.
 	pub fn blocks_pruning(n: u32) -> PruningMode {
-		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
+		PruningMode::Constrained(Constraints { max_blocks: Some(n), ..Default::default() })
 	}
 
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
@@ -278,10 +297,85 @@ impl Default for PruningMode {
 
 impl Default for Constraints {
 	fn default() -> Self {
-		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT) }
+		Self {
+			max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT),
+			max_window_bytes: None,
+			checkpoint_interval: None,
+		}
+	}
+}
+
+impl Constraints {
+	/// Is the pruning window larger than allowed, after pruning `window_size` blocks holding
+	/// `window_bytes` bytes?
+	fn exceeded(&self, window_size: u64, window_bytes: u64) -> bool {
+		let max_blocks = match (self.max_blocks, self.max_window_bytes) {
+			(Some(max_blocks), _) => Some(max_blocks),
+			(None, Some(_)) => None,
+			(None, None) => Some(0),
+		};
+		max_blocks.map_or(false, |max_blocks| window_size > max_blocks as u64) ||
+			self.max_window_bytes.map_or(false, |max_bytes| window_bytes > max_bytes)
 	}
 }
 
+/// Rewrite the journals of a state database that does its own reference counting for a backend
+/// database that counts references, e.g. when moving a database from RocksDB to ParityDB.
+///
+/// `map_key` converts node keys to the format of the new database and `is_stored` tells whether
+/// a node key is present in the original database. The returned meta changes are meant to be
+/// applied on top of a copy of the original metadata.
+pub fn migrate_to_ref_counting<BlockHash: Hash, D: MetaDb>(
+	db: &D,
+	map_key: impl Fn(&[u8]) -> Vec<u8>,
+	is_stored: impl Fn(&[u8]) -> bool,
+) -> Result<CommitSet<Vec<u8>>, Error<D::Error>> {
+	let mut commit = CommitSet::default();
+	noncanonical::migrate_journal::<BlockHash, _>(db, &map_key, &mut commit)?;
+	pruning::migrate_journal_to_ref_counting::<BlockHash, _>(
+		db,
+		&map_key,
+		&is_stored,
+		&mut commit,
+	)?;
+	Ok(commit)
+}
+
+/// Rewrite the journals of a state database whose backend database counts references for a
+/// backend database that doesn't, e.g. when moving a database from ParityDB to RocksDB.
+///
+/// Without reference counting nodes are stored under their path in the trie, which the journals
+/// of a reference counting database don't keep. `changes` returns the nodes inserted and deleted
+/// by the block with the given hash, in the format of the new database. It is given the nodes
+/// the block inserted that aren't in the original database yet, for non-canonical blocks, and is
+/// called for parents before their children. The returned meta changes hold all the metadata of
+/// the new database. Checkpoint marks are not converted: nodes without a mark are kept when they
+/// are pruned through, so no node of a checkpoint state is lost.
+pub fn migrate_from_ref_counting<BlockHash: Hash, D: MetaDb, E: From<Error<D::Error>>>(
+	db: &D,
+	mut changes: impl FnMut(&BlockHash, Vec<(Vec<u8>, DBValue)>) -> Result<ChangeSet<Vec<u8>>, E>,
+) -> Result<CommitSet<Vec<u8>>, E> {
+	let mut commit = CommitSet::default();
+	let keys = [
+		PRUNING_MODE,
+		noncanonical::LAST_CANONICAL,
+		pruning::LAST_PRUNED,
+		pruning::PRUNING_WINDOW_BYTES,
+	];
+	for key in keys.into_iter().map(|suffix| to_meta_key(suffix, &())) {
+		if let Some(value) = db.get_meta(&key).map_err(Error::Db)? {
+			commit.meta.inserted.push((key, value));
+		}
+	}
+	noncanonical::migrate_journal_from_ref_counting::<BlockHash, _, _>(
+		db,
+		&mut changes,
+		&mut commit,
+	)?;
+	pruning::migrate_journal_from_ref_counting::<BlockHash, _, _>(db, &mut changes, &mut commit)?;
+	Ok(commit)
+}
+
 fn to_meta_key<S: Codec>(suffix: &[u8], data: &S) -> Vec<u8> {
 	let mut buffer = data.encode();
 	buffer.extend(suffix);
@@ -315,10 +409,22 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	) -> Result<StateDbSync<BlockHash, Key, D>, Error<D::Error>> {
 		trace!(target: LOG_TARGET, "StateDb settings: {:?}. Ref-counting: {}", mode, ref_counting);
 
+		if let PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_window_bytes: Some(_),
+			..
+		}) = mode
+		{
+			// without reference counting in the database, the whole window is kept in memory.
+			if ref_counting {
+				return Err(StateDbError::UnboundedPruningWindow.into())
+			}
+		}
+
 		let non_canonical: NonCanonicalOverlay<BlockHash, Key> = NonCanonicalOverlay::new(&db)?;
 		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
-			PruningMode::Constrained(Constraints { max_blocks }) =>
-				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+			PruningMode::Constrained(ref constraints) =>
+				Some(RefWindow::new(db, constraints, ref_counting)?),
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
@@ -412,7 +518,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 			(&mut self.pruning, &self.mode)
 		{
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				if !constraints.exceeded(pruning.window_size(), pruning.window_bytes()) {
 					break
 				}
 
@@ -788,8 +894,7 @@ mod tests {
 
 	#[test]
 	fn block_record_unavailable() {
-		let (mut db, state_db) =
-			make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (mut db, state_db) = make_test_db(PruningMode::blocks_pruning(1));
 		// import 2 blocks
 		for i in &[5, 6] {
 			db.commit(
@@ -823,13 +928,13 @@ mod tests {
 
 	#[test]
 	fn prune_window_0() {
-		let (db, _) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(0) }));
+		let (db, _) = make_test_db(PruningMode::blocks_pruning(0));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
 
 	#[test]
 	fn prune_window_1() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (db, sdb) = make_test_db(PruningMode::blocks_pruning(1));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
@@ -839,7 +944,7 @@ mod tests {
 
 	#[test]
 	fn prune_window_2() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(2) }));
+		let (db, sdb) = make_test_db(PruningMode::blocks_pruning(2));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
@@ -847,6 +952,43 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn prune_window_bytes() {
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_window_bytes: Some(2 * 32),
+			checkpoint_interval: None,
+		}));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
+	}
+
+	#[test]
+	fn size_only_window_requires_ref_counting() {
+		let mode = PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_window_bytes: Some(2 * 32),
+			checkpoint_interval: None,
+		});
+		let result: Result<(_, StateDb<H256, H256, TestDb>), _> =
+			StateDb::open(make_db(&[]), Some(mode), true, true);
+		assert!(matches!(result, Err(Error::StateDb(StateDbError::UnboundedPruningWindow))));
+	}
+
+	#[test]
+	fn prune_keeps_checkpoints() {
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			max_window_bytes: None,
+			checkpoint_interval: Some(2),
+		}));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::MaybePruned);
+		// the states of #0 and #2 are kept, only node 1 inserted and deleted in between is pruned.
+		assert!(db.data_eq(&make_db(&[21, 3, 91, 921, 922, 93, 94])));
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -863,7 +1005,7 @@ mod tests {
 				)
 				.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2) });
+		let new_mode = PruningMode::blocks_pruning(2);
 		let state_db_open_result: Result<(_, StateDb<H256, H256, TestDb>), _> =
 			StateDb::open(db.clone(), Some(new_mode), false, false);
 		assert!(state_db_open_result.is_err());
@@ -81,6 +81,78 @@ fn to_journal_key(block: u64, index: u64) -> Vec<u8> {
 	to_meta_key(NON_CANONICAL_JOURNAL, &(block, index))
 }
 
+/// Rewrite the journal with the node keys converted by `map_key`, see
+/// [`crate::migrate_to_ref_counting`].
+pub(crate) fn migrate_journal<BlockHash: Hash, D: MetaDb>(
+	db: &D,
+	map_key: &impl Fn(&[u8]) -> Vec<u8>,
+	commit: &mut CommitSet<Vec<u8>>,
+) -> Result<(), Error<D::Error>> {
+	for_each_journal_record::<BlockHash, _, Error<D::Error>>(db, |journal_key, record| {
+		let record = JournalRecord {
+			hash: record.hash,
+			parent_hash: record.parent_hash,
+			inserted: record.inserted.into_iter().map(|(k, v)| (map_key(&k), v)).collect(),
+			deleted: record.deleted.iter().map(|k| map_key(k)).collect(),
+		};
+		commit.meta.inserted.push((journal_key, record.encode()));
+		Ok(())
+	})
+}
+
+/// Rewrite the journal with the changes of each block given by `changes`, see
+/// [`crate::migrate_from_ref_counting`].
+pub(crate) fn migrate_journal_from_ref_counting<BlockHash: Hash, D: MetaDb, E>(
+	db: &D,
+	changes: &mut impl FnMut(&BlockHash, Vec<(Vec<u8>, DBValue)>) -> Result<ChangeSet<Vec<u8>>, E>,
+	commit: &mut CommitSet<Vec<u8>>,
+) -> Result<(), E>
+where
+	E: From<Error<D::Error>>,
+{
+	for_each_journal_record::<BlockHash, _, E>(db, |journal_key, record| {
+		let changes = changes(&record.hash, record.inserted)?;
+		let record = JournalRecord {
+			hash: record.hash,
+			parent_hash: record.parent_hash,
+			inserted: changes.inserted,
+			deleted: changes.deleted,
+		};
+		commit.meta.inserted.push((journal_key, record.encode()));
+		Ok(())
+	})
+}
+
+/// Call `f` with the key and the record of every journal entry, parents first.
+fn for_each_journal_record<BlockHash: Hash, D: MetaDb, E: From<Error<D::Error>>>(
+	db: &D,
+	mut f: impl FnMut(Vec<u8>, JournalRecord<BlockHash, Vec<u8>>) -> Result<(), E>,
+) -> Result<(), E> {
+	let Some(last_canonicalized) =
+		db.get_meta(&to_meta_key(LAST_CANONICAL, &())).map_err(Error::Db)?
+	else {
+		return Ok(())
+	};
+	let (_, mut block) = <(BlockHash, u64)>::decode(&mut last_canonicalized.as_slice())
+		.map_err(Error::<D::Error>::from)?;
+	loop {
+		block += 1;
+		let mut level_empty = true;
+		for index in 0..MAX_BLOCKS_PER_LEVEL {
+			let journal_key = to_journal_key(block, index);
+			if let Some(record) = db.get_meta(&journal_key).map_err(Error::Db)? {
+				let record =
+					Decode::decode(&mut record.as_slice()).map_err(Error::<D::Error>::from)?;
+				f(journal_key, record)?;
+				level_empty = false;
+			}
+		}
+		if level_empty {
+			return Ok(())
+		}
+	}
+}
+
 #[cfg_attr(test, derive(PartialEq, Debug))]
 struct BlockOverlay<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
@@ -23,10 +23,16 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! With checkpoints enabled, nodes that belong to the state of the last checkpoint are kept
+//! when pruning the blocks that follow it. A node deleted by such a block is only removed if it
+//! was inserted after the checkpoint. Each node is marked in the meta column when it is pruned
+//! through: either with the number of times it was inserted since the checkpoint, or as kept for
+//! good. The marks are read back on demand, so nothing has to be rebuilt after a restart.
 
 use crate::{
-	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
-	DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
+	noncanonical::LAST_CANONICAL, to_meta_key, ChangeSet, CommitSet, Constraints, DBValue, Error,
+	Hash, MetaDb, StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
 };
 use codec::{Decode, Encode};
 use log::trace;
@@ -34,6 +40,8 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 pub(crate) const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+pub(crate) const PRUNING_WINDOW_BYTES: &[u8] = b"pruning_window_bytes";
+const CHECKPOINT_MARK: &[u8] = b"checkpoint_mark";
 
 /// See module documentation.
 pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
@@ -42,6 +50,137 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// Size of the trie nodes inserted by the blocks in the pruning window.
+	window_bytes: u64,
+	/// Checkpoint tracking, if checkpoints are enabled.
+	checkpoints: Option<Checkpoints<Key>>,
+}
+
+/// Keeps track of the nodes inserted since the last checkpoint, see module documentation.
+struct Checkpoints<Key: Hash> {
+	/// Number of blocks between two checkpoints.
+	interval: u64,
+	/// The checkpoint the tracked nodes were inserted after.
+	current: Option<u64>,
+	/// Marks of the nodes pruned through since the last checkpoint, cached from the database.
+	marks: HashMap<Key, Option<CheckpointMark>>,
+	/// Nodes whose mark was removed since the last checkpoint.
+	removed: HashSet<Key>,
+}
+
+/// Mark of a node pruned through, stored in the meta column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+enum CheckpointMark {
+	/// The node was inserted `count` times since `checkpoint` and not deleted as many times yet.
+	Inserted { checkpoint: u64, count: u32 },
+	/// The node belongs to the state of a checkpoint and is never removed.
+	Kept,
+}
+
+fn to_checkpoint_mark_key<Key: Hash>(key: &Key) -> Vec<u8> {
+	to_meta_key(CHECKPOINT_MARK, key)
+}
+
+impl<Key: Hash> Checkpoints<Key> {
+	fn new(interval: u32) -> Self {
+		Checkpoints {
+			interval: interval.max(1) as u64,
+			current: None,
+			marks: Default::default(),
+			removed: Default::default(),
+		}
+	}
+
+	fn is_checkpoint(&self, number: u64) -> bool {
+		number % self.interval == 0
+	}
+
+	/// Get the mark of `key` for the current checkpoint.
+	fn mark<D: MetaDb>(
+		&mut self,
+		db: &D,
+		key: &Key,
+	) -> Result<Option<CheckpointMark>, Error<D::Error>> {
+		if let Some(mark) = self.marks.get(key) {
+			return Ok(*mark)
+		}
+		let mark = match db.get_meta(&to_checkpoint_mark_key(key)).map_err(Error::Db)? {
+			Some(mark) => match CheckpointMark::decode(&mut mark.as_slice())? {
+				// insertions counted for an older checkpoint are part of the current one.
+				CheckpointMark::Inserted { checkpoint, .. } if Some(checkpoint) != self.current =>
+					None,
+				mark => Some(mark),
+			},
+			None => None,
+		};
+		self.marks.insert(key.clone(), mark);
+		Ok(mark)
+	}
+
+	fn set_mark(&mut self, key: Key, mark: Option<CheckpointMark>, commit: &mut CommitSet<Key>) {
+		let mark_key = to_checkpoint_mark_key(&key);
+		match mark {
+			Some(mark) => {
+				// the removal may be part of the same commit, where deletions are applied last.
+				if self.removed.remove(&key) {
+					commit.meta.deleted.retain(|deleted| *deleted != mark_key);
+				}
+				commit.meta.inserted.push((mark_key, mark.encode()));
+			},
+			None => {
+				self.removed.insert(key.clone());
+				commit.meta.deleted.push(mark_key);
+			},
+		}
+		self.marks.insert(key, mark);
+	}
+
+	/// Filter the nodes deleted by block `number` down to the ones that can be removed from the
+	/// database without affecting the state of the last checkpoint, and add them to `commit`.
+	fn prune<D: MetaDb>(
+		&mut self,
+		db: &D,
+		number: u64,
+		deleted: HashSet<Key>,
+		inserted: Vec<Key>,
+		commit: &mut CommitSet<Key>,
+	) -> Result<(), Error<D::Error>> {
+		// Pruning the death row of `number` discards the state of its parent.
+		let Some(parent) = number.checked_sub(1) else {
+			self.current = None;
+			commit.data.deleted.extend(deleted);
+			return Ok(())
+		};
+		let checkpoint = parent / self.interval * self.interval;
+		if self.current != Some(checkpoint) {
+			self.current = Some(checkpoint);
+			self.marks.clear();
+			self.removed.clear();
+		}
+
+		for key in deleted {
+			match self.mark(db, &key)? {
+				Some(CheckpointMark::Inserted { checkpoint, count }) => {
+					let mark = (count > 1)
+						.then(|| CheckpointMark::Inserted { checkpoint, count: count - 1 });
+					self.set_mark(key.clone(), mark, commit);
+					commit.data.deleted.push(key);
+				},
+				Some(CheckpointMark::Kept) => (),
+				None => self.set_mark(key, Some(CheckpointMark::Kept), commit),
+			}
+		}
+		for key in inserted {
+			let count = match self.mark(db, &key)? {
+				// a kept node that is inserted again is still part of a checkpoint state.
+				Some(CheckpointMark::Kept) => continue,
+				Some(CheckpointMark::Inserted { count, .. }) => count + 1,
+				None => 1,
+			};
+			self.set_mark(key, Some(CheckpointMark::Inserted { checkpoint, count }), commit);
+		}
+		Ok(())
+	}
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -51,6 +190,8 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 /// 	a few number of blocks in memory and load more blocks on demand
 enum DeathRowQueue<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	Mem {
+		// The backend database
+		db: D,
 		/// A queue of keys that should be deleted for each block in the pruning window.
 		death_rows: VecDeque<DeathRow<BlockHash, Key>>,
 		/// An index that maps each key from `death_rows` to block number.
@@ -72,12 +213,13 @@ enum DeathRowQueue<BlockHash: Hash, Key: Hash, D: MetaDb> {
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 	/// Return a `DeathRowQueue` that all blocks are keep in memory
-	fn new_mem(db: &D, base: u64) -> Result<DeathRowQueue<BlockHash, Key, D>, Error<D::Error>> {
+	fn new_mem(
+		db: D,
+		base: u64,
+		keep_inserted: bool,
+	) -> Result<DeathRowQueue<BlockHash, Key, D>, Error<D::Error>> {
 		let mut block = base;
-		let mut queue = DeathRowQueue::<BlockHash, Key, D>::Mem {
-			death_rows: VecDeque::new(),
-			death_index: HashMap::new(),
-		};
+		let mut records = Vec::new();
 		// read the journal
 		trace!(
 			target: LOG_TARGET,
@@ -88,8 +230,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 			let journal_key = to_journal_key(block);
 			match db.get_meta(&journal_key).map_err(Error::Db)? {
 				Some(record) => {
-					let record: JournalRecord<BlockHash, Key> =
-						Decode::decode(&mut record.as_slice())?;
+					let (record, bytes) = decode_journal_record::<BlockHash, Key>(&record)?;
 					trace!(
 						target: LOG_TARGET,
 						"Pruning journal entry {} ({} inserted, {} deleted)",
@@ -97,12 +238,20 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 						record.inserted.len(),
 						record.deleted.len(),
 					);
-					queue.import(base, block, record);
+					records.push((block, record, bytes));
 				},
 				None => break,
 			}
 			block += 1;
 		}
+		let mut queue = DeathRowQueue::<BlockHash, Key, D>::Mem {
+			db,
+			death_rows: VecDeque::new(),
+			death_index: HashMap::new(),
+		};
+		for (block, record, bytes) in records {
+			queue.import(base, block, record, bytes, keep_inserted);
+		}
 		Ok(queue)
 	}
 
@@ -127,7 +276,16 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 	}
 
 	/// import a new block to the back of the queue
-	fn import(&mut self, base: u64, num: u64, journal_record: JournalRecord<BlockHash, Key>) {
+	///
+	/// `keep_inserted` keeps the inserted keys in memory, as needed for checkpoints.
+	fn import(
+		&mut self,
+		base: u64,
+		num: u64,
+		journal_record: JournalRecord<BlockHash, Key>,
+		bytes: u64,
+		keep_inserted: bool,
+	) {
 		let JournalRecord { hash, inserted, deleted } = journal_record;
 		trace!(target: LOG_TARGET, "Importing {}, base={}", num, base);
 		match self {
@@ -136,15 +294,24 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				// cache.
 				if num == base + cache.len() as u64 && cache.len() < *cache_capacity {
 					trace!(target: LOG_TARGET, "Adding to DB backed cache {:?} (#{})", hash, num);
-					cache.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+					cache.push_back(DeathRow {
+						hash,
+						deleted: deleted.into_iter().collect(),
+						inserted: if keep_inserted { inserted } else { Vec::new() },
+						bytes,
+					});
 				}
 				*last = Some(num);
 			},
-			DeathRowQueue::Mem { death_rows, death_index } => {
-				// remove all re-inserted keys from death rows
+			DeathRowQueue::Mem { death_rows, death_index, .. } => {
+				// remove all re-inserted keys from death rows, they are not new to the checkpoint
+				// state either.
+				let mut new_keys = Vec::new();
 				for k in inserted {
 					if let Some(block) = death_index.remove(&k) {
 						death_rows[(block - base) as usize].deleted.remove(&k);
+					} else if keep_inserted {
+						new_keys.push(k);
 					}
 				}
 				// add new keys
@@ -152,7 +319,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				for k in deleted.iter() {
 					death_index.insert(k.clone(), imported_block);
 				}
-				death_rows.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+				death_rows.push_back(DeathRow {
+					hash,
+					deleted: deleted.into_iter().collect(),
+					inserted: new_keys,
+					bytes,
+				});
 			},
 		}
 	}
@@ -170,7 +342,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				}
 				Ok(cache.pop_front())
 			},
-			DeathRowQueue::Mem { death_rows, death_index } => match death_rows.pop_front() {
+			DeathRowQueue::Mem { death_rows, death_index, .. } => match death_rows.pop_front() {
 				Some(row) => {
 					for k in row.deleted.iter() {
 						death_index.remove(k);
@@ -219,6 +391,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 		}
 	}
 
+	/// The backend database
+	fn db(&self) -> &D {
+		match self {
+			DeathRowQueue::DbBacked { db, .. } | DeathRowQueue::Mem { db, .. } => db,
+		}
+	}
+
 	/// Return the number of block in the pruning window
 	fn len(&self, base: u64) -> u64 {
 		match self {
@@ -233,7 +412,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 	) -> Option<(&VecDeque<DeathRow<BlockHash, Key>>, &HashMap<Key, u64>)> {
 		match self {
 			DeathRowQueue::DbBacked { .. } => None,
-			DeathRowQueue::Mem { death_rows, death_index } => Some((death_rows, death_index)),
+			DeathRowQueue::Mem { death_rows, death_index, .. } => Some((death_rows, death_index)),
 		}
 	}
 
@@ -255,8 +434,9 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 	let journal_key = to_journal_key(block);
 	match db.get_meta(&journal_key).map_err(Error::Db)? {
 		Some(record) => {
-			let JournalRecord { hash, deleted, .. } = Decode::decode(&mut record.as_slice())?;
-			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect() }))
+			let (JournalRecord { hash, inserted, deleted }, bytes) =
+				decode_journal_record(&record)?;
+			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect(), inserted, bytes }))
 		},
 		None => Ok(None),
 	}
@@ -266,6 +446,10 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	deleted: HashSet<Key>,
+	/// Keys inserted by the block, only tracked with checkpoints enabled.
+	inserted: Vec<Key>,
+	/// Size of the values inserted by the block.
+	bytes: u64,
 }
 
 #[derive(Encode, Decode, Default)]
@@ -275,10 +459,109 @@ struct JournalRecord<BlockHash: Hash, Key: Hash> {
 	deleted: Vec<Key>,
 }
 
+/// Decode a journal record along with the size of the block's inserted values.
+///
+/// The size is appended after the record so that journals stay readable in both directions, it
+/// is zero for records written before it was introduced.
+fn decode_journal_record<BlockHash: Hash, Key: Hash>(
+	mut record: &[u8],
+) -> Result<(JournalRecord<BlockHash, Key>, u64), codec::Error> {
+	let journal_record = JournalRecord::decode(&mut record)?;
+	let bytes = if record.is_empty() { 0 } else { u64::decode(&mut record)? };
+	Ok((journal_record, bytes))
+}
+
 fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+/// Rewrite the pruning journal for a backend database that counts references, see
+/// [`crate::migrate_to_ref_counting`].
+///
+/// Without reference counting a key is deleted once, unless a later block of the window inserts
+/// it again. With reference counting every deleted key releases a reference, so the journal is
+/// replayed the way the in-memory queue does and only keeps the keys that would actually be
+/// deleted, once each.
+pub(crate) fn migrate_journal_to_ref_counting<BlockHash: Hash, D: MetaDb>(
+	db: &D,
+	map_key: &impl Fn(&[u8]) -> Vec<u8>,
+	is_stored: &impl Fn(&[u8]) -> bool,
+	commit: &mut CommitSet<Vec<u8>>,
+) -> Result<(), Error<D::Error>> {
+	let base = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
+		Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
+		None => 0,
+	};
+
+	let mut death_rows = Vec::<(JournalRecord<BlockHash, Vec<u8>>, HashSet<Vec<u8>>, u64)>::new();
+	let mut death_index = HashMap::new();
+	while let Some(record) = db
+		.get_meta(&to_journal_key(base + death_rows.len() as u64))
+		.map_err(Error::Db)?
+	{
+		let (record, bytes) = decode_journal_record::<BlockHash, Vec<u8>>(&record)?;
+		for key in &record.inserted {
+			if let Some(row) = death_index.remove(key) {
+				death_rows[row].1.remove(key);
+			}
+		}
+		// a key still waiting to be deleted by an earlier block is only deleted by that block.
+		let deleted = record
+			.deleted
+			.iter()
+			.filter(|key| !death_index.contains_key(*key))
+			.cloned()
+			.collect::<HashSet<_>>();
+		for key in &deleted {
+			death_index.insert(key.clone(), death_rows.len());
+		}
+		death_rows.push((record, deleted, bytes));
+	}
+
+	for (offset, (record, deleted, bytes)) in death_rows.into_iter().enumerate() {
+		let record = JournalRecord {
+			hash: record.hash,
+			inserted: record.inserted.iter().map(|key| map_key(key)).collect(),
+			deleted: deleted.iter().filter(|key| is_stored(key)).map(|key| map_key(key)).collect(),
+		};
+		let mut encoded = record.encode();
+		bytes.encode_to(&mut encoded);
+		commit.meta.inserted.push((to_journal_key(base + offset as u64), encoded));
+	}
+	Ok(())
+}
+
+/// Rewrite the pruning journal with the changes of each block given by `changes`, see
+/// [`crate::migrate_from_ref_counting`].
+pub(crate) fn migrate_journal_from_ref_counting<BlockHash: Hash, D: MetaDb, E>(
+	db: &D,
+	changes: &mut impl FnMut(&BlockHash, Vec<(Vec<u8>, DBValue)>) -> Result<ChangeSet<Vec<u8>>, E>,
+	commit: &mut CommitSet<Vec<u8>>,
+) -> Result<(), E>
+where
+	E: From<Error<D::Error>>,
+{
+	let mut block = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
+		Some(buffer) => u64::decode(&mut buffer.as_slice()).map_err(Error::<D::Error>::from)? + 1,
+		None => 0,
+	};
+	while let Some(record) = db.get_meta(&to_journal_key(block)).map_err(Error::Db)? {
+		let (record, bytes) = decode_journal_record::<BlockHash, Vec<u8>>(&record)
+			.map_err(Error::<D::Error>::from)?;
+		let changes = changes(&record.hash, Vec::new())?;
+		let record = JournalRecord {
+			hash: record.hash,
+			inserted: changes.inserted.into_iter().map(|(key, _)| key).collect(),
+			deleted: changes.deleted,
+		};
+		let mut encoded = record.encode();
+		bytes.encode_to(&mut encoded);
+		commit.meta.inserted.push((to_journal_key(block), encoded));
+		block += 1;
+	}
+	Ok(())
+}
+
 /// The result return by `RefWindow::have_block`
 #[derive(Debug, PartialEq, Eq)]
 pub enum HaveBlock {
@@ -303,9 +586,14 @@ impl From<bool> for HaveBlock {
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 	pub fn new(
 		db: D,
-		window_size: u32,
+		constraints: &Constraints,
 		count_insertions: bool,
 	) -> Result<RefWindow<BlockHash, Key, D>, Error<D::Error>> {
+		let window_size = constraints.max_blocks.unwrap_or(
+			// a window bounded by size only is likely to span many blocks.
+			if constraints.max_window_bytes.is_some() { DEFAULT_MAX_BLOCK_CONSTRAINT } else { 0 },
+		);
+		let checkpoints = constraints.checkpoint_interval.map(Checkpoints::new);
 		// the block number of the first block in the queue or the next block number if the queue is
 		// empty
 		let base = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
@@ -318,7 +606,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				Some(buffer) => Some(<(BlockHash, u64)>::decode(&mut buffer.as_slice())?.1),
 				None => None,
 			};
-
+		let window_bytes =
+			match db.get_meta(&to_meta_key(PRUNING_WINDOW_BYTES, &())).map_err(Error::Db)? {
+				Some(buffer) => u64::decode(&mut buffer.as_slice())?,
+				None => 0,
+			};
 		let queue = if count_insertions {
 			// Highly scientific crafted number for deciding when to print the warning!
 			//
@@ -332,7 +624,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				);
 			}
 
-			DeathRowQueue::new_mem(&db, base)?
+			DeathRowQueue::new_mem(db, base, checkpoints.is_some())?
 		} else {
 			let last = match last_canonicalized_number {
 				Some(last_canonicalized_number) => {
@@ -347,13 +639,18 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			DeathRowQueue::new_db_backed(db, base, last, window_size)?
 		};
 
-		Ok(RefWindow { queue, base })
+		Ok(RefWindow { queue, base, window_bytes, checkpoints })
 	}
 
 	pub fn window_size(&self) -> u64 {
 		self.queue.len(self.base) as u64
 	}
 
+	/// Size of the trie nodes inserted by the blocks in the pruning window.
+	pub fn window_bytes(&self) -> u64 {
+		self.window_bytes
+	}
+
 	/// Get the hash of the next pruning block
 	pub fn next_hash(&mut self) -> Result<Option<BlockHash>, Error<D::Error>> {
 		let res = match &mut self.queue {
@@ -376,6 +673,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 	pub fn have_block(&self, hash: &BlockHash, number: u64) -> HaveBlock {
 		// if the queue is empty or the block number exceed the pruning window, we definitely
 		// do not have this block
+		if number < self.base &&
+			self.checkpoints.as_ref().map_or(false, |c| c.is_checkpoint(number))
+		{
+			// the state of canonical checkpoints is kept, but forks at the same height aren't.
+			return HaveBlock::Maybe
+		}
 		if self.is_empty() || number < self.base || number >= self.base + self.window_size() {
 			return HaveBlock::No
 		}
@@ -387,9 +690,21 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		if let Some(pruned) = self.queue.pop_front(self.base)? {
 			trace!(target: LOG_TARGET, "Pruning {:?} ({} deleted)", pruned.hash, pruned.deleted.len());
 			let index = self.base;
-			commit.data.deleted.extend(pruned.deleted.into_iter());
+			let DeathRow { deleted, inserted, bytes, .. } = pruned;
+			match self.checkpoints {
+				Some(ref mut checkpoints) =>
+					checkpoints.prune(self.queue.db(), index, deleted, inserted, commit)?,
+				None => commit.data.deleted.extend(deleted.into_iter()),
+			}
+			commit.meta.deleted.push(to_journal_key(index));
+			if bytes != 0 {
+				self.window_bytes = self.window_bytes.saturating_sub(bytes);
+				commit
+					.meta
+					.inserted
+					.push((to_meta_key(PRUNING_WINDOW_BYTES, &()), self.window_bytes.encode()));
+			}
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
-			commit.meta.deleted.push(to_journal_key(self.base));
 			self.base += 1;
 			Ok(())
 		} else {
@@ -424,26 +739,42 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			commit.data.inserted.len(),
 			commit.data.deleted.len(),
 		);
-		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) {
+		let keep_inserted = self.checkpoints.is_some();
+		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) || keep_inserted {
 			commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
 		} else {
 			Default::default()
 		};
+		let bytes = commit.data.inserted.iter().map(|(_, v)| v.len() as u64).sum::<u64>();
 		let deleted = std::mem::take(&mut commit.data.deleted);
 		let journal_record = JournalRecord { hash: hash.clone(), inserted, deleted };
-		commit.meta.inserted.push((to_journal_key(number), journal_record.encode()));
-		self.queue.import(self.base, number, journal_record);
+		let mut encoded = journal_record.encode();
+		bytes.encode_to(&mut encoded);
+		commit.meta.inserted.push((to_journal_key(number), encoded));
+		if bytes != 0 {
+			self.window_bytes += bytes;
+			commit
+				.meta
+				.inserted
+				.push((to_meta_key(PRUNING_WINDOW_BYTES, &()), self.window_bytes.encode()));
+		}
+		self.queue.import(self.base, number, journal_record, bytes, keep_inserted);
 		Ok(())
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{to_journal_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow, LAST_PRUNED};
+	use super::{
+		decode_journal_record, migrate_journal_from_ref_counting, migrate_journal_to_ref_counting,
+		to_checkpoint_mark_key, to_journal_key, CheckpointMark, DeathRowQueue, HaveBlock,
+		JournalRecord, RefWindow, LAST_PRUNED,
+	};
 	use crate::{
 		noncanonical::LAST_CANONICAL,
 		test::{make_commit, make_db, TestDb},
-		to_meta_key, CommitSet, Error, Hash, StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT,
+		to_meta_key, ChangeSet, CommitSet, Constraints, Error, Hash, MetaDb, StateDbError,
+		DEFAULT_MAX_BLOCK_CONSTRAINT,
 	};
 	use codec::Encode;
 	use sp_core::H256;
@@ -451,7 +782,7 @@ mod tests {
 	fn check_journal(pruning: &RefWindow<H256, H256, TestDb>, db: &TestDb) {
 		let count_insertions = matches!(pruning.queue, DeathRowQueue::Mem { .. });
 		let restored: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), count_insertions).unwrap();
 		assert_eq!(pruning.base, restored.base);
 		assert_eq!(pruning.queue.get_mem_queue_state(), restored.queue.get_mem_queue_state());
 	}
@@ -460,7 +791,7 @@ mod tests {
 	fn created_from_empty_db() {
 		let db = make_db(&[]);
 		let pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db, &Default::default(), true).unwrap();
 		assert_eq!(pruning.base, 0);
 		let (death_rows, death_index) = pruning.queue.get_mem_queue_state().unwrap();
 		assert!(death_rows.is_empty());
@@ -471,7 +802,7 @@ mod tests {
 	fn prune_empty() {
 		let db = make_db(&[]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db, &Default::default(), true).unwrap();
 		let mut commit = CommitSet::default();
 		assert_eq!(
 			Err(Error::StateDb(StateDbError::BlockUnavailable)),
@@ -487,7 +818,7 @@ mod tests {
 	fn prune_one() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), true).unwrap();
 		let mut commit = make_commit(&[4, 5], &[1, 3]);
 		let hash = H256::random();
 		pruning.note_canonical(&hash, 0, &mut commit).unwrap();
@@ -517,7 +848,7 @@ mod tests {
 	fn prune_two() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
 		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
 		db.commit(&commit);
@@ -543,7 +874,7 @@ mod tests {
 	fn prune_two_pending() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
 		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
 		db.commit(&commit);
@@ -566,7 +897,7 @@ mod tests {
 	fn reinserted_survives() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
 		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
 		db.commit(&commit);
@@ -598,7 +929,7 @@ mod tests {
 	fn reinserted_survive_pending() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
 		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
 		db.commit(&commit);
@@ -628,7 +959,7 @@ mod tests {
 	fn reinserted_ignores() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), false).unwrap();
 		let mut commit = make_commit(&[], &[2]);
 		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
 		db.commit(&commit);
@@ -666,7 +997,7 @@ mod tests {
 
 		fn load_pruning_from_db(db: TestDb) -> (usize, u64) {
 			let pruning: RefWindow<u64, H256, TestDb> =
-				RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+				RefWindow::new(db, &Default::default(), false).unwrap();
 			let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
 			(cache.len(), pruning.base)
 		}
@@ -720,7 +1051,7 @@ mod tests {
 	fn db_backed_queue() {
 		let mut db = make_db(&[]);
 		let mut pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), false).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as usize;
 
 		// start as an empty queue
@@ -764,7 +1095,7 @@ mod tests {
 
 		// revert the last add that no apply yet
 		// NOTE: do not commit the previous `CommitSet` to db
-		pruning = RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+		pruning = RefWindow::new(db.clone(), &Default::default(), false).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as usize;
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 10);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
@@ -785,7 +1116,7 @@ mod tests {
 		// load a new queue from db
 		// `cache` is full again but the content of the queue should be the same
 		let pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db, &Default::default(), false).unwrap();
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 9);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
 		assert_eq!(cache.len(), cache_capacity);
@@ -798,7 +1129,7 @@ mod tests {
 	fn load_block_from_db() {
 		let mut db = make_db(&[]);
 		let mut pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), false).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as usize;
 
 		// import blocks
@@ -835,7 +1166,7 @@ mod tests {
 		// load a new queue from db
 		// `cache` should be the same
 		let pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db, &Default::default(), false).unwrap();
 		assert_eq!(pruning.window_size(), 10);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
 		assert_eq!(cache.len(), 10);
@@ -848,7 +1179,7 @@ mod tests {
 	fn get_block_from_queue() {
 		let mut db = make_db(&[]);
 		let mut pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), &Default::default(), false).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as u64;
 
 		// import blocks and commit to db
@@ -890,7 +1221,7 @@ mod tests {
 		for count_insertions in [true, false] {
 			let mut db = make_db(&[]);
 			let mut pruning: RefWindow<u64, H256, TestDb> =
-				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
+				RefWindow::new(db.clone(), &Default::default(), count_insertions).unwrap();
 			let block = 10000;
 
 			// import blocks
@@ -902,9 +1233,259 @@ mod tests {
 			// load a new queue from db
 			// `cache` should be the same
 			let pruning: RefWindow<u64, H256, TestDb> =
-				RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
+				RefWindow::new(db, &Default::default(), count_insertions).unwrap();
 
 			assert_eq!(HaveBlock::Yes, pruning.have_block(&block, block));
 		}
 	}
+
+	#[test]
+	fn checkpoint_state_is_kept() {
+		for count_insertions in [true, false] {
+			let mut db = make_db(&[]);
+			let constraints = Constraints { checkpoint_interval: Some(2), ..Default::default() };
+			let mut pruning: RefWindow<H256, H256, TestDb> =
+				RefWindow::new(db.clone(), &constraints, count_insertions).unwrap();
+			let blocks = [(&[1, 2, 3][..], &[][..]), (&[4], &[1]), (&[5], &[4, 2]), (&[6], &[5])];
+			let hashes: Vec<_> = (0..blocks.len()).map(|_| H256::random()).collect();
+			for (number, (inserted, deleted)) in blocks.iter().enumerate() {
+				let mut commit = make_commit(inserted, deleted);
+				pruning.note_canonical(&hashes[number], number as u64, &mut commit).unwrap();
+				db.commit(&commit);
+			}
+			assert_eq!(pruning.window_bytes(), 6 * 32);
+
+			for _ in 0..blocks.len() {
+				let mut commit = CommitSet::default();
+				pruning.prune_one(&mut commit).unwrap();
+				db.commit(&commit);
+			}
+			assert_eq!(pruning.window_bytes(), 0);
+
+			// the states of checkpoints #0 and #2 are kept, only node 4 of block #1 is pruned.
+			assert!(db.data_eq(&make_db(&[1, 2, 3, 5, 6])));
+			assert_eq!(pruning.have_block(&hashes[0], 0), HaveBlock::Maybe);
+			assert_eq!(pruning.have_block(&hashes[1], 1), HaveBlock::No);
+			assert_eq!(pruning.have_block(&hashes[2], 2), HaveBlock::Maybe);
+		}
+	}
+
+	#[test]
+	fn checkpoint_tracking_survives_restart() {
+		for count_insertions in [true, false] {
+			let mut db = make_db(&[]);
+			let constraints = Constraints { checkpoint_interval: Some(4), ..Default::default() };
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), &constraints, count_insertions).unwrap();
+			let blocks = [
+				(&[1, 2][..], &[][..]),
+				(&[3], &[1]),
+				(&[4], &[3]),
+				(&[5], &[4]),
+				(&[6], &[2, 5]),
+				(&[7], &[6]),
+			];
+			for (number, (inserted, deleted)) in blocks.iter().enumerate() {
+				let mut commit = make_commit(inserted, deleted);
+				pruning.note_canonical(&(number as u64), number as u64, &mut commit).unwrap();
+				push_last_canonicalized(number as u64, &mut commit);
+				db.commit(&commit);
+			}
+
+			let prune = |pruning: &mut RefWindow<u64, H256, TestDb>, db: &mut TestDb| {
+				let mut commit = CommitSet::default();
+				pruning.prune_one(&mut commit).unwrap();
+				db.commit(&commit);
+			};
+			for _ in 0..4 {
+				prune(&mut pruning, &mut db);
+			}
+
+			// node 5 was inserted after checkpoint #0, so it is pruned along with block #4 after
+			// a restart.
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), &constraints, count_insertions).unwrap();
+			prune(&mut pruning, &mut db);
+			assert!(db.data_eq(&make_db(&[1, 2, 6, 7])));
+
+			// node 6 is part of checkpoint #4.
+			prune(&mut pruning, &mut db);
+			assert!(db.data_eq(&make_db(&[1, 2, 6, 7])));
+			for number in 0..6 {
+				assert!(db.get_meta(&to_journal_key(number)).unwrap().is_none());
+			}
+			for node in [1, 2, 6] {
+				let mark = db.get_meta(&to_checkpoint_mark_key(&H256::from_low_u64_be(node)));
+				assert_eq!(mark.unwrap(), Some(CheckpointMark::Kept.encode()));
+			}
+		}
+	}
+
+	#[test]
+	fn node_of_older_checkpoint_is_kept_when_inserted_again() {
+		for count_insertions in [true, false] {
+			let mut db = make_db(&[]);
+			let constraints = Constraints { checkpoint_interval: Some(4), ..Default::default() };
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), &constraints, count_insertions).unwrap();
+			let blocks = [
+				(&[1][..], &[][..]),
+				(&[2], &[1]),
+				(&[3], &[]),
+				(&[4], &[]),
+				(&[5], &[]),
+				(&[1], &[]),
+				(&[6], &[1]),
+			];
+			let note =
+				|pruning: &mut RefWindow<u64, H256, TestDb>, db: &mut TestDb, number: usize| {
+					let (inserted, deleted) = blocks[number];
+					let mut commit = make_commit(inserted, deleted);
+					pruning.note_canonical(&(number as u64), number as u64, &mut commit).unwrap();
+					db.commit(&commit);
+				};
+			let prune = |pruning: &mut RefWindow<u64, H256, TestDb>, db: &mut TestDb| {
+				let mut commit = CommitSet::default();
+				pruning.prune_one(&mut commit).unwrap();
+				db.commit(&commit);
+			};
+			// the blocks re-inserting node 1 are only added once its deletion is pruned.
+			for number in 0..5 {
+				note(&mut pruning, &mut db, number);
+			}
+			for _ in 0..5 {
+				prune(&mut pruning, &mut db);
+			}
+			for number in 5..7 {
+				note(&mut pruning, &mut db, number);
+			}
+			for _ in 5..7 {
+				prune(&mut pruning, &mut db);
+			}
+
+			// node 1 is part of checkpoint #0, even though it was deleted after being inserted
+			// again.
+			assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5, 6])));
+		}
+	}
+
+	#[test]
+	fn window_bytes_survive_restart() {
+		let mut db = make_db(&[]);
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), &Default::default(), false).unwrap();
+		let mut commit = make_commit(&[1, 2], &[]);
+		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[3], &[1]);
+		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.window_bytes(), 3 * 32);
+
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), &Default::default(), false).unwrap();
+		assert_eq!(pruning.window_bytes(), 3 * 32);
+		let mut commit = CommitSet::default();
+		pruning.prune_one(&mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.window_bytes(), 32);
+	}
+
+	#[test]
+	fn journal_migrates_to_ref_counting() {
+		let mut db = make_db(&[]);
+		let record = |block: u64, inserted: &[u8], deleted: &[u8]| {
+			let record = JournalRecord::<H256, Vec<u8>> {
+				hash: H256::from_low_u64_be(block),
+				inserted: inserted.iter().map(|k| vec![0, *k]).collect(),
+				deleted: deleted.iter().map(|k| vec![0, *k]).collect(),
+			};
+			let mut encoded = record.encode();
+			10u64.encode_to(&mut encoded);
+			(to_journal_key(block), encoded)
+		};
+		let mut commit = make_commit(&[], &[]);
+		commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), 0u64.encode()));
+		commit.meta.inserted.extend([
+			record(1, &[1], &[2]),
+			// 2 is inserted again, block 1 no longer deletes it.
+			record(2, &[2], &[3]),
+			// 3 is already deleted by block 2 and 4 isn't stored.
+			record(3, &[], &[2, 3, 4]),
+		]);
+		db.commit(&commit);
+
+		let mut migrated = CommitSet::default();
+		migrate_journal_to_ref_counting::<H256, _>(
+			&db,
+			&|key: &[u8]| key[1..].to_vec(),
+			&|key: &[u8]| key != [0, 4],
+			&mut migrated,
+		)
+		.unwrap();
+		db.commit(&CommitSet { data: Default::default(), meta: migrated.meta });
+
+		let journal = |block| {
+			let record = db.get_meta(&to_journal_key(block)).unwrap().unwrap();
+			let (record, bytes) = decode_journal_record::<H256, Vec<u8>>(&record).unwrap();
+			assert_eq!(bytes, 10);
+			(record.inserted, record.deleted)
+		};
+		assert_eq!(journal(1), (vec![vec![1]], vec![]));
+		assert_eq!(journal(2), (vec![vec![2]], vec![vec![3]]));
+		assert_eq!(journal(3), (vec![], vec![vec![2]]));
+	}
+
+	#[test]
+	fn journal_migrates_from_ref_counting() {
+		let mut db = make_db(&[]);
+		let record = |block: u64, inserted: &[u8], deleted: &[u8]| {
+			let record = JournalRecord::<H256, Vec<u8>> {
+				hash: H256::from_low_u64_be(block),
+				inserted: inserted.iter().map(|k| vec![*k]).collect(),
+				deleted: deleted.iter().map(|k| vec![*k]).collect(),
+			};
+			let mut encoded = record.encode();
+			10u64.encode_to(&mut encoded);
+			(to_journal_key(block), encoded)
+		};
+		let mut commit = make_commit(&[], &[]);
+		commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), 0u64.encode()));
+		commit.meta.inserted.extend([record(1, &[1], &[]), record(2, &[2], &[1])]);
+		db.commit(&commit);
+
+		// node 1 is stored under two paths, only one of them is changed by block 2.
+		let mut blocks = Vec::new();
+		let mut migrated = CommitSet::default();
+		migrate_journal_from_ref_counting::<H256, _, Error<()>>(
+			&db,
+			&mut |hash, inserted| {
+				assert!(inserted.is_empty());
+				blocks.push(*hash);
+				Ok(match hash.to_low_u64_be() {
+					1 => ChangeSet {
+						inserted: vec![(vec![0, 1], vec![1]), (vec![1, 1], vec![1])],
+						deleted: vec![],
+					},
+					_ => ChangeSet {
+						inserted: vec![(vec![1, 2], vec![2])],
+						deleted: vec![vec![1, 1]],
+					},
+				})
+			},
+			&mut migrated,
+		)
+		.unwrap();
+		assert_eq!(blocks, vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)]);
+		db.commit(&CommitSet { data: Default::default(), meta: migrated.meta });
+
+		let journal = |block| {
+			let record = db.get_meta(&to_journal_key(block)).unwrap().unwrap();
+			let (record, bytes) = decode_journal_record::<H256, Vec<u8>>(&record).unwrap();
+			assert_eq!(bytes, 10);
+			(record.inserted, record.deleted)
+		};
+		assert_eq!(journal(1), (vec![vec![0, 1], vec![1, 1]], vec![]));
+		assert_eq!(journal(2), (vec![vec![1, 2]], vec![vec![1, 1]]));
+	}
 }
@@ -23,11 +23,13 @@ pub mod error;
 
 use async_trait::async_trait;
 use codec::Codec;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sp_core::offchain::TransactionPoolExt;
 use sp_runtime::traits::{Block as BlockT, Member};
-use std::{collections::HashMap, hash::Hash, marker::PhantomData, pin::Pin, sync::Arc};
+use std::{
+	collections::HashMap, hash::Hash, marker::PhantomData, pin::Pin, sync::Arc, time::SystemTime,
+};
 
 const LOG_TARGET: &str = "txpool::api";
 
@@ -56,6 +58,40 @@ impl PoolStatus {
 	}
 }
 
+/// The queue of a pool view which holds a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PooledTransactionQueue {
+	/// The transaction is ready to be included in a block.
+	Ready,
+	/// The transaction waits for the tags it requires.
+	Future,
+}
+
+/// Detailed information about a transaction in the pool, used for inspection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PooledTransactionInfo<Hash, BlockHash> {
+	/// Hash of the transaction.
+	pub hash: Hash,
+	/// Size of the transaction encoding.
+	pub bytes: usize,
+	/// Priority of the transaction, if it was already validated.
+	pub priority: Option<TransactionPriority>,
+	/// Block number at which the transaction becomes invalid, if it was already validated.
+	pub valid_till: Option<TransactionLongevity>,
+	/// Tags required by the transaction.
+	pub requires: Vec<TransactionTag>,
+	/// Tags provided by the transaction.
+	pub provides: Vec<TransactionTag>,
+	/// The source the transaction was submitted from.
+	pub source: TransactionSource,
+	/// The time the transaction was submitted at, if known.
+	pub submitted_at: Option<SystemTime>,
+	/// Indicates if the transaction is watched.
+	pub watched: bool,
+	/// The views (blocks) holding the transaction, along with the queue it is placed in.
+	pub views: Vec<(BlockHash, PooledTransactionQueue)>,
+}
+
 /// Possible transaction status events.
 ///
 /// These events are being emitted by `TransactionPool` watchers,
@@ -189,10 +225,72 @@ impl<Hash, BlockHash> TransactionStatus<Hash, BlockHash> {
 	}
 }
 
+/// The reason why a transaction left the pool without being finalized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum TransactionDropReason {
+	/// The transaction was replaced by another transaction providing the same tags.
+	Usurped,
+	/// The transaction was removed to enforce the limits of the pool.
+	LimitsEnforced,
+	/// The transaction is no longer valid.
+	Invalid {
+		/// The validity error reported by the runtime, if known.
+		#[serde(skip_serializing_if = "Option::is_none")]
+		#[serde(default)]
+		error: Option<String>,
+	},
+}
+
+impl TransactionDropReason {
+	/// Returns the generic drop reason conveyed by the given status, if any.
+	pub fn from_status<Hash, BlockHash>(
+		status: &TransactionStatus<Hash, BlockHash>,
+	) -> Option<Self> {
+		match status {
+			TransactionStatus::Usurped(_) => Some(Self::Usurped),
+			TransactionStatus::Dropped => Some(Self::LimitsEnforced),
+			TransactionStatus::Invalid => Some(Self::Invalid { error: None }),
+			_ => None,
+		}
+	}
+}
+
+/// A transaction status event enriched with details known by the pool.
+///
+/// Provided by [`TransactionPool::submit_and_watch_detailed`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedTransactionStatus<Hash, BlockHash> {
+	/// The status of the transaction.
+	pub status: TransactionStatus<Hash, BlockHash>,
+	/// The reason of a `Usurped`, `Dropped` or `Invalid` status.
+	pub reason: Option<TransactionDropReason>,
+	/// The fork views, identified by their block hash, that keep the transaction valid or
+	/// included at the time of the event.
+	///
+	/// Empty if the pool does not maintain per-fork views.
+	pub views: Vec<BlockHash>,
+}
+
+impl<Hash, BlockHash> From<TransactionStatus<Hash, BlockHash>>
+	for DetailedTransactionStatus<Hash, BlockHash>
+{
+	fn from(status: TransactionStatus<Hash, BlockHash>) -> Self {
+		let reason = TransactionDropReason::from_status(&status);
+		Self { status, reason, views: Vec::new() }
+	}
+}
+
 /// The stream of transaction events.
 pub type TransactionStatusStream<Hash, BlockHash> =
 	dyn Stream<Item = TransactionStatus<Hash, BlockHash>> + Send;
 
+/// The stream of detailed transaction events.
+pub type DetailedTransactionStatusStream<Hash, BlockHash> =
+	dyn Stream<Item = DetailedTransactionStatus<Hash, BlockHash>> + Send;
+
 /// The import notification event stream.
 pub type ImportNotificationStream<H> = futures::channel::mpsc::Receiver<H>;
 
@@ -204,6 +302,9 @@ pub type BlockHash<P> = <<P as TransactionPool>::Block as BlockT>::Hash;
 pub type TransactionFor<P> = <<P as TransactionPool>::Block as BlockT>::Extrinsic;
 /// Type of transactions event stream for a pool.
 pub type TransactionStatusStreamFor<P> = TransactionStatusStream<TxHash<P>, BlockHash<P>>;
+/// Type of detailed transactions event stream for a pool.
+pub type DetailedTransactionStatusStreamFor<P> =
+	DetailedTransactionStatusStream<TxHash<P>, BlockHash<P>>;
 /// Transaction type for a local pool.
 pub type LocalTransactionFor<P> = <<P as LocalTransactionPool>::Block as BlockT>::Extrinsic;
 /// Transaction's index within the block in which it was included.
@@ -280,6 +381,21 @@ pub trait TransactionPool: Send + Sync {
 		xt: TransactionFor<Self>,
 	) -> Result<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error>;
 
+	/// Asynchronously imports a single transaction and starts to watch its progress in the
+	/// pool, reporting [detailed](DetailedTransactionStatus) events.
+	///
+	/// The default implementation derives the details from the events of
+	/// [`Self::submit_and_watch`] and reports no views.
+	async fn submit_and_watch_detailed(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> Result<Pin<Box<DetailedTransactionStatusStreamFor<Self>>>, Self::Error> {
+		let stream = self.submit_and_watch(at, source, xt).await?;
+		Ok(stream.map(DetailedTransactionStatus::from).boxed())
+	}
+
 	// *** Block production / Networking
 	/// Get an iterator for ready transactions ordered by priority.
 	///
@@ -347,6 +463,23 @@ pub trait TransactionPool: Send + Sync {
 		at: <Self::Block as BlockT>::Hash,
 		timeout: std::time::Duration,
 	) -> Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send>;
+
+	// *** RPC / inspection
+	/// Returns detailed information about every transaction in the pool.
+	///
+	/// Returns `None` if the implementation does not support inspection.
+	fn inspect(&self) -> Option<Vec<PooledTransactionInfo<TxHash<Self>, BlockHash<Self>>>> {
+		None
+	}
+
+	/// Returns every transaction held by the pool, along with the source it was submitted from.
+	///
+	/// Unlike [`Self::ready`] and [`Self::futures`], this includes the transactions which are not
+	/// part of the best block's queues. Returns `None` if the implementation does not keep such
+	/// transactions.
+	fn pending_transactions(&self) -> Option<Vec<(TransactionSource, Arc<TransactionFor<Self>>)>> {
+		None
+	}
 }
 
 /// An iterator of ready transactions.
@@ -604,4 +737,23 @@ mod tests {
 		let event_dec: TransactionStatus<u8, u8> = serde_json::from_str(exp).unwrap();
 		assert_eq!(event_dec, TransactionStatus::Finalized((1, 0)));
 	}
+	#[test]
+	fn detailed_tx_status_reason() {
+		let event: DetailedTransactionStatus<u8, u8> = TransactionStatus::Usurped(3).into();
+		assert_eq!(event.reason, Some(TransactionDropReason::Usurped));
+
+		let event: DetailedTransactionStatus<u8, u8> = DetailedTransactionStatus {
+			status: TransactionStatus::Invalid,
+			reason: Some(TransactionDropReason::Invalid { error: Some("Stale".into()) }),
+			views: vec![1, 2],
+		};
+		let ser = serde_json::to_string(&event).unwrap();
+
+		let exp =
+			r#"{"status":"invalid","reason":{"type":"invalid","error":"Stale"},"views":[1,2]}"#;
+		assert_eq!(ser, exp);
+
+		let event_dec: DetailedTransactionStatus<u8, u8> = serde_json::from_str(exp).unwrap();
+		assert_eq!(event_dec, event);
+	}
 }
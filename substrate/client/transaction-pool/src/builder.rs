@@ -22,6 +22,7 @@ use crate::{
 	common::api::FullChainApi,
 	fork_aware_txpool::ForkAwareTxPool as ForkAwareFullPool,
 	graph::{base_pool::Transaction, ChainApi, ExtrinsicFor, ExtrinsicHash, IsValidator, Options},
+	journal::Journal,
 	single_state_txpool::BasicPool as SingleStateFullPool,
 	TransactionPoolWrapper, LOG_TARGET,
 };
@@ -29,7 +30,12 @@ use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_transaction_pool_api::{LocalTransactionPool, MaintainedTransactionPool};
 use sp_core::traits::SpawnEssentialNamed;
 use sp_runtime::traits::Block as BlockT;
-use std::{marker::PhantomData, sync::Arc, time::Duration};
+use std::{
+	marker::PhantomData,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
 
 /// The type of transaction pool.
 #[derive(Debug, Clone)]
@@ -45,11 +51,18 @@ pub enum TransactionPoolType {
 pub struct TransactionPoolOptions {
 	txpool_type: TransactionPoolType,
 	options: Options,
+	journal_limit: usize,
+	journal_path: Option<PathBuf>,
 }
 
 impl Default for TransactionPoolOptions {
 	fn default() -> Self {
-		Self { txpool_type: TransactionPoolType::SingleState, options: Default::default() }
+		Self {
+			txpool_type: TransactionPoolType::SingleState,
+			options: Default::default(),
+			journal_limit: 0,
+			journal_path: None,
+		}
 	}
 }
 
@@ -81,7 +94,43 @@ impl TransactionPoolOptions {
 			Duration::from_secs(30 * 60)
 		};
 
-		TransactionPoolOptions { options, txpool_type }
+		TransactionPoolOptions { options, txpool_type, journal_limit: 0, journal_path: None }
+	}
+
+	/// Sets the maximum number of transactions (ready and future) of a single sender.
+	///
+	/// The sender is identified by the prefix of the first `provides` tag of the transaction.
+	/// The fork-aware pool applies the limit to its mempool as well, scaled by the same factor as
+	/// the mempool count limit. `None` disables the limit.
+	pub fn with_sender_limit(mut self, limit: Option<usize>) -> Self {
+		self.options.policy.sender_limit = limit;
+		self
+	}
+
+	/// Sets the minimal priority increase (in percent) required to replace a transaction
+	/// providing the same tags.
+	pub fn with_min_priority_bump(mut self, percent: u64) -> Self {
+		self.options.policy.min_priority_bump_percent = percent;
+		self
+	}
+
+	/// Sets the maximum number of transactions kept in the persistent journal.
+	///
+	/// The journal is disabled if the limit is `0` or if no journal path is provided.
+	pub fn with_journal_limit(mut self, limit: usize) -> Self {
+		self.journal_limit = limit;
+		self
+	}
+
+	/// Sets the path of the file storing the persistent journal.
+	pub fn with_journal_path(mut self, path: PathBuf) -> Self {
+		self.journal_path = Some(path);
+		self
+	}
+
+	/// The path of the file storing the persistent journal, if set.
+	pub fn journal_path(&self) -> Option<&Path> {
+		self.journal_path.as_deref()
 	}
 
 	/// Creates predefined options for benchmarking
@@ -98,8 +147,11 @@ impl TransactionPoolOptions {
 				},
 				reject_future_transactions: false,
 				ban_time: Duration::from_secs(30 * 60),
+				policy: Default::default(),
 			},
 			txpool_type: TransactionPoolType::SingleState,
+			journal_limit: 0,
+			journal_path: None,
 		}
 	}
 }
@@ -225,7 +277,15 @@ where
 	/// Creates an instance of transaction pool.
 	pub fn build(self) -> TransactionPoolHandle<Block, Client> {
 		log::info!(target:LOG_TARGET, " creating {:?} txpool {:?}/{:?}.", self.options.txpool_type, self.options.options.ready, self.options.options.future);
-		TransactionPoolWrapper::<Block, Client>(match self.options.txpool_type {
+		let journal = match self.options.journal_path {
+			Some(path) if self.options.journal_limit > 0 => {
+				log::info!(target:LOG_TARGET, " using txpool journal {:?} (limit: {}).", path, self.options.journal_limit);
+				Some(Journal::new(path, self.options.journal_limit, &self.spawner))
+			},
+			_ => None,
+		};
+		let pool: Box<dyn FullClientTransactionPool<Block, Client>> = match self.options.txpool_type
+		{
 			TransactionPoolType::SingleState => Box::new(SingleStateFullPool::new_full(
 				self.options.options,
 				self.is_validator,
@@ -240,6 +300,7 @@ where
 				self.spawner,
 				self.client,
 			)),
-		})
+		};
+		TransactionPoolWrapper::<Block, Client>(pool, journal)
 	}
 }
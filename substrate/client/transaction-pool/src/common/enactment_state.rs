@@ -87,6 +87,11 @@ where
 		self.recent_finalized_block
 	}
 
+	/// Returns the recently updated best block.
+	pub fn recent_best_block(&self) -> Block::Hash {
+		self.recent_best_block
+	}
+
 	/// Updates the state according to the given `ChainEvent`, returning
 	/// `Some(tree_route)` with a tree route including the blocks that need to
 	/// be enacted/retracted. If no enactment is needed then `None` is returned.
@@ -19,7 +19,7 @@
 //! Transaction pool Prometheus metrics for implementation of Chain API.
 
 use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
 
 use crate::LOG_TARGET;
 
@@ -44,6 +44,12 @@ impl<M: MetricsRegistrant> Clone for GenericMetricsLink<M> {
 	}
 }
 
+impl<M: MetricsRegistrant> fmt::Debug for GenericMetricsLink<M> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("GenericMetricsLink").field(&self.0.is_some()).finish()
+	}
+}
+
 impl<M: MetricsRegistrant> GenericMetricsLink<M> {
 	pub fn new(registry: Option<&Registry>) -> Self {
 		Self(Arc::new(registry.and_then(|registry| {
@@ -103,3 +109,41 @@ impl ApiMetricsExt for Option<Arc<ApiMetrics>> {
 		}
 	}
 }
+
+/// A link to the Prometheus metrics of the pool admission and eviction policies.
+pub(crate) type PolicyMetricsLink = GenericMetricsLink<PolicyMetrics>;
+
+/// Transaction pool admission and eviction policies Prometheus metrics.
+pub struct PolicyMetrics {
+	pub sender_limit_rejected: Counter<U64>,
+	pub priority_bump_rejected: Counter<U64>,
+	pub priority_per_byte_evicted: Counter<U64>,
+}
+
+impl MetricsRegistrant for PolicyMetrics {
+	fn register(registry: &Registry) -> Result<Box<Self>, PrometheusError> {
+		Ok(Box::from(Self {
+			sender_limit_rejected: register(
+				Counter::new(
+					"substrate_sub_txpool_sender_limit_rejected",
+					"Total number of transactions rejected because their sender exceeded its quota",
+				)?,
+				registry,
+			)?,
+			priority_bump_rejected: register(
+				Counter::new(
+					"substrate_sub_txpool_priority_bump_rejected",
+					"Total number of replacement transactions rejected because of too low priority bump",
+				)?,
+				registry,
+			)?,
+			priority_per_byte_evicted: register(
+				Counter::new(
+					"substrate_sub_txpool_priority_per_byte_evicted",
+					"Total number of ready transactions evicted by priority per byte to enforce limits",
+				)?,
+				registry,
+			)?,
+		}))
+	}
+}
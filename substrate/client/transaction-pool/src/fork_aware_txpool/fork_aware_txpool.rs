@@ -25,11 +25,14 @@ use super::{
 	multi_view_listener::MultiViewListener,
 	tx_mem_pool::{InsertionInfo, TxMemPool, TXMEMPOOL_TRANSACTION_LIMIT_MULTIPLIER},
 	view::View,
-	view_store::ViewStore,
+	view_store::{ViewStore, ViewStoreSubmitOutcome},
 };
 use crate::{
 	api::FullChainApi,
-	common::tracing_log_xt::{log_xt_debug, log_xt_trace},
+	common::{
+		metrics::PolicyMetricsLink,
+		tracing_log_xt::{log_xt_debug, log_xt_trace},
+	},
 	enactment_state::{EnactmentAction, EnactmentState},
 	fork_aware_txpool::{
 		dropped_watcher::{DroppedReason, DroppedTransaction},
@@ -52,16 +55,17 @@ use futures::{
 use parking_lot::Mutex;
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_transaction_pool_api::{
-	error::Error as TxPoolApiError, ChainEvent, ImportNotificationStream,
-	MaintainedTransactionPool, PoolStatus, TransactionFor, TransactionPool, TransactionPriority,
-	TransactionSource, TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
+	error::Error as TxPoolApiError, ChainEvent, DetailedTransactionStatusStreamFor,
+	ImportNotificationStream, MaintainedTransactionPool, PoolStatus, PooledTransactionInfo,
+	TransactionFor, TransactionPool, TransactionPriority, TransactionSource,
+	TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
 };
 use sp_blockchain::{HashAndNumber, TreeRoute};
 use sp_core::traits::SpawnEssentialNamed;
 use sp_runtime::{
 	generic::BlockId,
 	traits::{Block as BlockT, NumberFor},
-	transaction_validity::{TransactionValidityError, ValidTransaction},
+	transaction_validity::{TransactionTag, TransactionValidityError, ValidTransaction},
 	Saturating,
 };
 use std::{
@@ -165,6 +169,9 @@ where
 	/// Externally provided pool options.
 	options: Options,
 
+	/// Prometheus's metrics endpoint of the admission and eviction policies, shared by all views.
+	policy_metrics: PolicyMetricsLink,
+
 	/// Is node the validator.
 	is_validator: IsValidator,
 
@@ -220,6 +227,29 @@ where
 		future_limits: crate::PoolLimit,
 		mempool_max_transactions_count: usize,
 		finality_timeout_threshold: Option<usize>,
+	) -> (Self, ForkAwareTxPoolTask) {
+		let options = Options { ready: ready_limits, future: future_limits, ..Default::default() };
+		Self::new_test_with_options(
+			pool_api,
+			best_block_hash,
+			finalized_hash,
+			options,
+			mempool_max_transactions_count,
+			finality_timeout_threshold,
+		)
+	}
+
+	/// Create new fork aware transaction pool with given options and with provided shared instance
+	/// of `ChainApi` intended for tests.
+	///
+	/// The per-sender limit of the policy is applied to the mempool as is.
+	pub fn new_test_with_options(
+		pool_api: Arc<ChainApi>,
+		best_block_hash: Block::Hash,
+		finalized_hash: Block::Hash,
+		options: Options,
+		mempool_max_transactions_count: usize,
+		finality_timeout_threshold: Option<usize>,
 	) -> (Self, ForkAwareTxPoolTask) {
 		let (listener, listener_task) = MultiViewListener::new_with_worker(Default::default());
 		let listener = Arc::new(listener);
@@ -232,7 +262,8 @@ where
 			listener.clone(),
 			Default::default(),
 			mempool_max_transactions_count,
-			ready_limits.total_bytes + future_limits.total_bytes,
+			options.ready.total_bytes + options.future.total_bytes,
+			options.policy.sender_limit,
 		));
 
 		let (dropped_stream_controller, dropped_stream) =
@@ -257,8 +288,6 @@ where
 		}
 		.boxed();
 
-		let options = Options { ready: ready_limits, future: future_limits, ..Default::default() };
-
 		(
 			Self {
 				mempool,
@@ -272,6 +301,7 @@ where
 				revalidation_queue: Arc::from(revalidation_worker::RevalidationQueue::new()),
 				import_notification_sink,
 				options,
+				policy_metrics: Default::default(),
 				is_validator: false.into(),
 				metrics: Default::default(),
 				events_metrics_collector: EventsMetricsCollector::default(),
@@ -367,6 +397,10 @@ where
 			metrics.clone(),
 			TXMEMPOOL_TRANSACTION_LIMIT_MULTIPLIER * options.total_count(),
 			options.ready.total_bytes + options.future.total_bytes,
+			options
+				.policy
+				.sender_limit
+				.map(|limit| TXMEMPOOL_TRANSACTION_LIMIT_MULTIPLIER * limit),
 		));
 
 		let (dropped_stream_controller, dropped_stream) =
@@ -406,6 +440,7 @@ where
 			revalidation_queue: Arc::from(revalidation_queue),
 			import_notification_sink,
 			options,
+			policy_metrics: PolicyMetricsLink::new(prometheus),
 			metrics,
 			events_metrics_collector,
 			is_validator,
@@ -790,8 +825,10 @@ where
 		// the mempool, and the final result recorded in the vector for this transaction is the
 		// view_store submission error.
 		//
-		// - If the view_store submission is successful, the transaction priority is updated in the
-		// mempool.
+		// - If the view_store submission is successful, the transaction priority and sender are
+		// updated in the mempool. If the sender exceeds the per-sender limit of the mempool, the
+		// transaction is removed from the mempool and the view_store, and `ImmediatelyDropped`
+		// error is recorded.
 		//
 		// Finally, it collects the hashes of updated transactions or submission errors (either
 		// from the mempool or view_store) into a returned vector.
@@ -807,9 +844,9 @@ where
 				})
 			})
 			.map(|r| {
-				r.map(|r| {
-					mempool.update_transaction_priority(&r);
-					r.hash()
+				r.and_then(|r| {
+					self.update_mempool_transaction_info(&r)?;
+					Ok(r.hash())
 				})
 			})
 			.collect::<Vec<_>>())
@@ -847,6 +884,21 @@ where
 		source: TransactionSource,
 		xt: TransactionFor<Self>,
 	) -> Result<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
+		self.submit_and_watch_detailed(at, source, xt)
+			.await
+			.map(|stream| stream.map(|event| event.status).boxed())
+	}
+
+	/// Submits a transaction and starts to watch its progress in the pool.
+	///
+	/// The reported events carry the views keeping the transaction valid, and the reason when
+	/// the transaction is dropped, usurped or invalidated.
+	async fn submit_and_watch_detailed(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> Result<Pin<Box<DetailedTransactionStatusStreamFor<Self>>>, Self::Error> {
 		trace!(
 			target: LOG_TARGET,
 			tx_hash = ?self.tx_hash(&xt),
@@ -871,9 +923,9 @@ where
 			.inspect_err(|_| {
 				self.mempool.remove_transactions(&[insertion.hash]);
 			})
-			.map(|mut outcome| {
-				self.mempool.update_transaction_priority(&outcome);
-				outcome.expect_watcher()
+			.and_then(|mut outcome| {
+				self.update_mempool_transaction_info(&outcome)?;
+				Ok(outcome.expect_watcher())
 			})
 	}
 
@@ -993,6 +1045,52 @@ where
 	) -> ReadyIteratorFor<ChainApi> {
 		self.ready_at_with_timeout_internal(at, timeout).await
 	}
+
+	/// Returns detailed information about every transaction in the mempool.
+	///
+	/// The validity details and the queue placement are taken from the active views. For
+	/// transactions not yet included in any active view only the mempool details are known.
+	fn inspect(&self) -> Option<Vec<PooledTransactionInfo<TxHash<Self>, Block::Hash>>> {
+		let mut in_views = self.view_store.inspect();
+		let transactions = self
+			.mempool
+			.clone_transactions()
+			.into_iter()
+			.map(|(hash, mempool_tx)| {
+				let source = mempool_tx.source();
+				match in_views.remove(&hash) {
+					Some((tx, views)) => PooledTransactionInfo {
+						source: source.source,
+						submitted_at: source.submitted_at(),
+						..tx.pooled_info(mempool_tx.is_watched(), views)
+					},
+					None => PooledTransactionInfo {
+						hash,
+						bytes: mempool_tx.bytes(),
+						priority: mempool_tx.priority(),
+						valid_till: None,
+						requires: Vec::new(),
+						provides: Vec::new(),
+						source: source.source,
+						submitted_at: source.submitted_at(),
+						watched: mempool_tx.is_watched(),
+						views: Vec::new(),
+					},
+				}
+			})
+			.collect();
+		Some(transactions)
+	}
+
+	fn pending_transactions(&self) -> Option<Vec<(TransactionSource, Arc<TransactionFor<Self>>)>> {
+		let transactions = self
+			.mempool
+			.clone_transactions()
+			.into_values()
+			.map(|mempool_tx| (mempool_tx.source().source, mempool_tx.tx()))
+			.collect();
+		Some(transactions)
+	}
 }
 
 impl<ChainApi, Block> sc_transaction_pool_api::LocalTransactionPool
@@ -1035,11 +1133,10 @@ where
 			.inspect_err(|_| {
 				self.mempool.remove_transactions(&[insertion.hash]);
 			})
-			.map(|outcome| {
-				self.mempool.update_transaction_priority(&outcome);
-				outcome.hash()
+			.map_or(Ok(insertion.hash), |outcome| {
+				self.update_mempool_transaction_info(&outcome)?;
+				Ok(outcome.hash())
 			})
-			.or_else(|_| Ok(insertion.hash))
 	}
 }
 
@@ -1206,6 +1303,7 @@ where
 					at.clone(),
 					self.options.clone(),
 					self.metrics.clone(),
+					self.policy_metrics.clone(),
 					self.is_validator.clone(),
 				)
 			};
@@ -1364,8 +1462,12 @@ where
 			.into_iter()
 			.zip(hashes)
 			.map(|(result, tx_hash)| {
+				// The transaction is already counted against its sender, so the per-sender limit
+				// cannot be exceeded here.
 				result
-					.map(|outcome| self.mempool.update_transaction_priority(&outcome.into()))
+					.map(|outcome| {
+						let _ = self.mempool.update_transaction_info(&outcome.into());
+					})
 					.or_else(|_| Err(tx_hash))
 			})
 			.collect::<Vec<_>>();
@@ -1595,8 +1697,19 @@ where
 		let Some(priority) = validated_tx.priority() else {
 			return Err(TxPoolApiError::ImmediatelyDropped)
 		};
+		let sender = self.sender_key(validated_tx.provides());
 
-		self.attempt_transaction_replacement_inner(xt, xt_hash, priority, source, watched)
+		self.attempt_transaction_replacement_inner(xt, xt_hash, priority, sender, source, watched)
+	}
+
+	/// Returns the sender key of a transaction providing given tags, if the per-sender limit is
+	/// enabled.
+	fn sender_key(&self, provides: &[TransactionTag]) -> Option<Vec<u8>> {
+		let policy = &self.options.policy;
+		policy
+			.sender_limit
+			.and_then(|_| policy.sender_key(provides))
+			.map(<[u8]>::to_vec)
 	}
 
 	/// Sync version of [`Self::attempt_transaction_replacement`].
@@ -1612,7 +1725,7 @@ where
 			.read()
 			.ok_or(TxPoolApiError::ImmediatelyDropped)?;
 
-		let ValidTransaction { priority, .. } = self
+		let ValidTransaction { priority, provides, .. } = self
 			.api
 			.validate_transaction_blocking(at, TransactionSource::Local, Arc::from(xt.clone()))
 			.map_err(|_| TxPoolApiError::ImmediatelyDropped)?
@@ -1621,7 +1734,23 @@ where
 				TransactionValidityError::Unknown(u) => TxPoolApiError::UnknownTransaction(u),
 			})?;
 		let xt_hash = self.hash_of(&xt);
-		self.attempt_transaction_replacement_inner(xt, xt_hash, priority, source, watched)
+		let sender = self.sender_key(&provides);
+		self.attempt_transaction_replacement_inner(xt, xt_hash, priority, sender, source, watched)
+	}
+
+	/// Updates the priority and the sender of the submitted transaction in the mempool.
+	///
+	/// If the sender of the transaction exceeds the per-sender limit of the mempool, the
+	/// transaction is also removed from the view_store and `ImmediatelyDropped` error is returned.
+	fn update_mempool_transaction_info(
+		&self,
+		outcome: &ViewStoreSubmitOutcome<ChainApi>,
+	) -> Result<(), TxPoolApiError> {
+		self.mempool.update_transaction_info(outcome).inspect_err(|_| {
+			self.view_store.remove_transaction_subtree(outcome.hash(), |listener, tx_hash| {
+				listener.limits_enforced(&tx_hash);
+			});
+		})
 	}
 
 	fn attempt_transaction_replacement_inner(
@@ -1629,11 +1758,13 @@ where
 		xt: ExtrinsicFor<ChainApi>,
 		tx_hash: ExtrinsicHash<ChainApi>,
 		priority: TransactionPriority,
+		sender: Option<Vec<u8>>,
 		source: TransactionSource,
 		watched: bool,
 	) -> Result<InsertionInfo<ExtrinsicHash<ChainApi>>, TxPoolApiError> {
-		let insertion_info =
-			self.mempool.try_insert_with_replacement(xt, priority, source, watched)?;
+		let insertion_info = self
+			.mempool
+			.try_insert_with_replacement(xt, priority, sender, source, watched)?;
 
 		for worst_hash in &insertion_info.removed {
 			trace!(
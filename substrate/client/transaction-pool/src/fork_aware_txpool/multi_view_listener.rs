@@ -28,7 +28,10 @@ use crate::{
 };
 use futures::{Future, FutureExt, Stream, StreamExt};
 use parking_lot::RwLock;
-use sc_transaction_pool_api::{TransactionStatus, TransactionStatusStream, TxIndex};
+use sc_transaction_pool_api::{
+	DetailedTransactionStatus, DetailedTransactionStatusStream, TransactionDropReason,
+	TransactionStatus, TransactionStatusStream, TxIndex,
+};
 use sc_utils::mpsc;
 use sp_runtime::traits::Block as BlockT;
 use std::{
@@ -60,6 +63,10 @@ type CommandReceiver<T> = mpsc::TracingUnboundedReceiver<T>;
 /// It can represent both a single view's stream and an external watcher stream.
 pub type TxStatusStream<T> = Pin<Box<TransactionStatusStream<ExtrinsicHash<T>, BlockHash<T>>>>;
 
+/// The stream of the detailed transaction events provided by the external watcher.
+pub type DetailedTxStatusStream<T> =
+	Pin<Box<DetailedTransactionStatusStream<ExtrinsicHash<T>, BlockHash<T>>>>;
+
 /// An aggregated stream providing events for all transactions from the view.
 ///
 /// This stream delivers updates for all transactions in the view, rather than for individual
@@ -83,8 +90,9 @@ enum ControllerCommand<ChainApi: graph::ChainApi> {
 enum TransactionStatusUpdate<ChainApi: graph::ChainApi> {
 	/// Marks a transaction as invalidated.
 	///
-	/// If all pre-conditions are met, an external invalid event will be sent out.
-	Invalidated(ExtrinsicHash<ChainApi>),
+	/// If all pre-conditions are met, an external invalid event will be sent out. The validity
+	/// error is attached if known.
+	Invalidated(ExtrinsicHash<ChainApi>, Option<String>),
 
 	/// Notifies that a transaction was finalized in a specific block hash and transaction index.
 	///
@@ -113,13 +121,27 @@ where
 {
 	fn hash(&self) -> ExtrinsicHash<ChainApi> {
 		match self {
-			Self::Invalidated(hash) |
+			Self::Invalidated(hash, _) |
 			Self::Finalized(hash, _, _) |
 			Self::Broadcasted(hash, _) |
 			Self::Dropped(hash, _) => *hash,
 			Self::FinalityTimeout(hash, _) => *hash,
 		}
 	}
+
+	/// Returns the reason of the update, if it removes the transaction from the pool.
+	fn reason(&self) -> Option<TransactionDropReason> {
+		match self {
+			Self::Invalidated(_, error) =>
+				Some(TransactionDropReason::Invalid { error: error.clone() }),
+			Self::Dropped(_, DroppedReason::Invalid) =>
+				Some(TransactionDropReason::Invalid { error: None }),
+			Self::Dropped(_, DroppedReason::Usurped(_)) => Some(TransactionDropReason::Usurped),
+			Self::Dropped(_, DroppedReason::LimitsEnforced) =>
+				Some(TransactionDropReason::LimitsEnforced),
+			Self::Finalized(..) | Self::Broadcasted(..) | Self::FinalityTimeout(..) => None,
+		}
+	}
 }
 
 impl<ChainApi> Into<TransactionStatus<ExtrinsicHash<ChainApi>, BlockHash<ChainApi>>>
@@ -129,7 +151,7 @@ where
 {
 	fn into(self) -> TransactionStatus<ExtrinsicHash<ChainApi>, BlockHash<ChainApi>> {
 		match self {
-			TransactionStatusUpdate::Invalidated(_) => TransactionStatus::Invalid,
+			TransactionStatusUpdate::Invalidated(..) => TransactionStatus::Invalid,
 			TransactionStatusUpdate::Finalized(_, hash, index) =>
 				TransactionStatus::Finalized((*hash, *index)),
 			TransactionStatusUpdate::Broadcasted(_, peers) =>
@@ -152,8 +174,8 @@ where
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			Self::Invalidated(h) => {
-				write!(f, "Invalidated({h})")
+			Self::Invalidated(h, e) => {
+				write!(f, "Invalidated({h},{e:?})")
 			},
 			Self::Finalized(h, b, i) => {
 				write!(f, "Finalized({h},{b},{i})")
@@ -192,8 +214,10 @@ where
 {
 	/// Creates new instance of a command requesting [`TransactionStatus::Invalid`] transaction
 	/// status.
-	fn new_invalidated(tx_hash: ExtrinsicHash<ChainApi>) -> Self {
-		ControllerCommand::TransactionStatusRequest(TransactionStatusUpdate::Invalidated(tx_hash))
+	fn new_invalidated(tx_hash: ExtrinsicHash<ChainApi>, error: Option<String>) -> Self {
+		ControllerCommand::TransactionStatusRequest(TransactionStatusUpdate::Invalidated(
+			tx_hash, error,
+		))
 	}
 	/// Creates new instance of a command requesting [`TransactionStatus::Broadcast`] transaction
 	/// status.
@@ -332,10 +356,20 @@ where
 	fn handle_pool_transaction_status(
 		&mut self,
 		request: TransactionStatusUpdate<ChainApi>,
-	) -> Option<TransactionStatus<ExtrinsicHash<ChainApi>, BlockHash<ChainApi>>> {
+	) -> Option<DetailedTransactionStatus<ExtrinsicHash<ChainApi>, BlockHash<ChainApi>>> {
 		let status = Into::<TransactionStatus<_, _>>::into(&request);
 		status.is_final().then(|| self.terminate = true);
-		return Some(status);
+		return Some(self.detailed(status, request.reason()));
+	}
+
+	/// Attaches the reason and the views keeping the transaction valid to the `status`.
+	fn detailed(
+		&self,
+		status: TransactionStatus<ExtrinsicHash<ChainApi>, BlockHash<ChainApi>>,
+		reason: Option<TransactionDropReason>,
+	) -> DetailedTransactionStatus<ExtrinsicHash<ChainApi>, BlockHash<ChainApi>> {
+		let views = self.views_keeping_tx_valid.iter().copied().collect();
+		DetailedTransactionStatus { status, reason, views }
 	}
 
 	/// Handles various transaction status updates from individual views and manages internal states
@@ -568,6 +602,19 @@ where
 		&self,
 		tx_hash: ExtrinsicHash<ChainApi>,
 	) -> Option<TxStatusStream<ChainApi>> {
+		self.create_detailed_external_watcher_for_tx(tx_hash)
+			.map(|stream| stream.map(|event| event.status).boxed())
+	}
+
+	/// Creates an external stream of detailed events for given transaction.
+	///
+	/// Every event carries the views keeping the transaction valid at the time of the event, and
+	/// the reason for the events removing the transaction from the pool. See
+	/// [`Self::create_external_watcher_for_tx`] for more details.
+	pub(crate) fn create_detailed_external_watcher_for_tx(
+		&self,
+		tx_hash: ExtrinsicHash<ChainApi>,
+	) -> Option<DetailedTxStatusStream<ChainApi>> {
 		let external_ctx = match self.external_controllers.write().entry(tx_hash) {
 			Entry::Occupied(_) => return None,
 			Entry::Vacant(entry) => {
@@ -605,7 +652,7 @@ where
 											?new_status,
 											"mvl sending out"
 										);
-										return Some((new_status, ctx))
+										return Some((ctx.detailed(new_status, None), ctx))
 									}
 								},
 								ExternalWatcherCommand::PoolTransactionStatus(request) => {
@@ -686,9 +733,23 @@ where
 	/// `Future`.
 	pub(crate) fn transactions_invalidated(&self, invalid_hashes: &[ExtrinsicHash<ChainApi>]) {
 		log_xt_trace!(target: LOG_TARGET, invalid_hashes, "transactions_invalidated");
-		for tx_hash in invalid_hashes {
-			if let Err(error) =
-				self.controller.unbounded_send(ControllerCommand::new_invalidated(*tx_hash))
+		self.transactions_invalidated_with_errors(
+			invalid_hashes.iter().map(|tx_hash| (*tx_hash, None)),
+		);
+	}
+
+	/// Invalidate given transactions, attaching the validity error to the external event.
+	///
+	/// See [`Self::transactions_invalidated`] for more details.
+	pub(crate) fn transactions_invalidated_with_errors(
+		&self,
+		invalid: impl IntoIterator<Item = (ExtrinsicHash<ChainApi>, Option<String>)>,
+	) {
+		for (tx_hash, error) in invalid {
+			trace!(target: LOG_TARGET, ?tx_hash, ?error, "transaction_invalidated");
+			if let Err(error) = self
+				.controller
+				.unbounded_send(ControllerCommand::new_invalidated(tx_hash, error))
 			{
 				trace!(
 					target: LOG_TARGET,
@@ -1098,6 +1159,73 @@ mod tests {
 		assert!(out.iter().all(|v| vec![TransactionStatus::Invalid].contains(v)));
 		assert_eq!(out.len(), 1);
 
+		let _ = terminate_listener.send(());
+		let _ = listener_task.await.unwrap();
+	}
+	#[tokio::test]
+	async fn test06() {
+		sp_tracing::try_init_simple();
+		let (listener, terminate_listener, listener_task) = create_multi_view_listener();
+
+		let block_hash0 = H256::repeat_byte(0x01);
+		let events0 = vec![TransactionStatus::Ready];
+
+		let tx_hash = H256::repeat_byte(0x0a);
+		let external_watcher = listener.create_detailed_external_watcher_for_tx(tx_hash).unwrap();
+
+		let view_stream0 = futures::stream::iter(std::iter::repeat(tx_hash).zip(events0.clone()))
+			.chain(stream::pending().boxed());
+
+		let handle = tokio::spawn(async move { external_watcher.collect::<Vec<_>>().await });
+
+		listener.add_view_aggregated_stream(block_hash0, view_stream0.boxed());
+		listener.transactions_invalidated_with_errors([(tx_hash, Some("Stale".to_string()))]);
+
+		let out = handle.await.unwrap();
+		debug!("out: {:#?}", out);
+
+		assert_eq!(
+			out,
+			vec![
+				DetailedTransactionStatus {
+					status: TransactionStatus::Ready,
+					reason: None,
+					views: vec![block_hash0],
+				},
+				DetailedTransactionStatus {
+					status: TransactionStatus::Invalid,
+					reason: Some(TransactionDropReason::Invalid { error: Some("Stale".into()) }),
+					views: vec![block_hash0],
+				},
+			]
+		);
+
+		let _ = terminate_listener.send(());
+		let _ = listener_task.await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test07() {
+		sp_tracing::try_init_simple();
+		let (listener, terminate_listener, listener_task) = create_multi_view_listener();
+
+		let tx_hash = H256::repeat_byte(0x0a);
+		let by_hash = H256::repeat_byte(0x0b);
+		let external_watcher = listener.create_detailed_external_watcher_for_tx(tx_hash).unwrap();
+		let handle = tokio::spawn(async move { external_watcher.collect::<Vec<_>>().await });
+
+		listener.transaction_dropped(DroppedTransaction::new_usurped(tx_hash, by_hash));
+
+		let out = handle.await.unwrap();
+		assert_eq!(
+			out,
+			vec![DetailedTransactionStatus {
+				status: TransactionStatus::Usurped(by_hash),
+				reason: Some(TransactionDropReason::Usurped),
+				views: vec![],
+			}]
+		);
+
 		let _ = terminate_listener.send(());
 		let _ = listener_task.await.unwrap();
 	}
@@ -94,6 +94,9 @@ where
 	/// Priority of transaction at some block. It is assumed it will not be changed often. None if
 	/// not known.
 	priority: RwLock<Option<TransactionPriority>>,
+	/// Sender key of the transaction, known once the transaction was validated and the
+	/// per-sender limit is enabled.
+	sender: RwLock<Option<Vec<u8>>>,
 }
 
 impl<ChainApi, Block> TxInMemPool<ChainApi, Block>
@@ -128,15 +131,19 @@ where
 		Self::new_with_optional_priority(watched, source, tx, bytes, None)
 	}
 
-	/// Creates a new instance of wrapper for a transaction with given priority.
+	/// Creates a new instance of wrapper for a validated transaction with given priority and
+	/// optional sender key.
 	fn new_with_priority(
 		watched: bool,
 		source: TransactionSource,
 		tx: ExtrinsicFor<ChainApi>,
 		bytes: usize,
 		priority: TransactionPriority,
+		sender: Option<Vec<u8>>,
 	) -> Self {
-		Self::new_with_optional_priority(watched, source, tx, bytes, Some(priority))
+		let tx = Self::new_with_optional_priority(watched, source, tx, bytes, Some(priority));
+		*tx.sender.write() = sender;
+		tx
 	}
 
 	/// Creates a new instance of wrapper for a transaction with optional priority.
@@ -154,6 +161,7 @@ where
 			validated_at: AtomicU64::new(0),
 			bytes,
 			priority: priority.into(),
+			sender: Default::default(),
 		}
 	}
 
@@ -173,6 +181,16 @@ where
 	pub(crate) fn priority(&self) -> Option<TransactionPriority> {
 		*self.priority.read()
 	}
+
+	/// Returns the size of the transaction encoding.
+	pub(crate) fn bytes(&self) -> usize {
+		self.bytes
+	}
+
+	/// Returns the sender key of the transaction, if known.
+	pub(crate) fn sender(&self) -> Option<Vec<u8>> {
+		self.sender.read().clone()
+	}
 }
 
 impl<ChainApi, Block> Size for Arc<TxInMemPool<ChainApi, Block>>
@@ -224,6 +242,10 @@ where
 
 	/// Maximal size of encodings of all transactions in the memory pool.
 	max_transactions_total_bytes: usize,
+
+	/// Maximum number of transactions from a single sender kept in the memory pool, `None` if
+	/// unlimited.
+	sender_limit: Option<usize>,
 }
 
 /// Helper structure to encapsulate a result of [`TxMemPool::try_insert`].
@@ -250,13 +272,14 @@ where
 	<Block as BlockT>::Hash: Unpin,
 {
 	/// Creates a new `TxMemPool` instance with the given API, listener, metrics,
-	/// and max transaction count.
+	/// max transaction count and per-sender limit.
 	pub(super) fn new(
 		api: Arc<ChainApi>,
 		listener: Arc<MultiViewListener<ChainApi>>,
 		metrics: PrometheusMetrics,
 		max_transactions_count: usize,
 		max_transactions_total_bytes: usize,
+		sender_limit: Option<usize>,
 	) -> Self {
 		Self {
 			api,
@@ -265,6 +288,7 @@ where
 			metrics,
 			max_transactions_count,
 			max_transactions_total_bytes,
+			sender_limit,
 		}
 	}
 
@@ -282,9 +306,17 @@ where
 			metrics: Default::default(),
 			max_transactions_count,
 			max_transactions_total_bytes,
+			sender_limit: None,
 		}
 	}
 
+	/// Sets the per-sender limit, for testing purposes.
+	#[cfg(test)]
+	fn with_sender_limit(mut self, sender_limit: usize) -> Self {
+		self.sender_limit = Some(sender_limit);
+		self
+	}
+
 	/// Retrieves a transaction by its hash if it exists in the memory pool.
 	pub(super) fn get_by_hash(
 		&self,
@@ -317,8 +349,26 @@ where
 			current_total_bytes > self.max_transactions_total_bytes
 	}
 
+	/// Returns true if the given transactions already contain as many transactions from
+	/// `sender` as the per-sender limit allows.
+	///
+	/// This is a linear scan, it is only executed when the per-sender limit is enabled.
+	fn is_sender_limit_exceeded<'a>(
+		&self,
+		transactions: impl Iterator<Item = &'a Arc<TxInMemPool<ChainApi, Block>>>,
+		sender: &[u8],
+	) -> bool
+	where
+		ChainApi: 'a,
+		Block: 'a,
+	{
+		let Some(limit) = self.sender_limit else { return false };
+		transactions.filter(|tx| tx.sender.read().as_deref() == Some(sender)).count() >= limit
+	}
+
 	/// Attempts to insert a transaction into the memory pool, ensuring it does not
-	/// exceed the maximum allowed transaction count.
+	/// exceed the maximum allowed transaction count, nor the per-sender limit if the sender of
+	/// the transaction is known.
 	fn try_insert(
 		&self,
 		tx_hash: ExtrinsicHash<ChainApi>,
@@ -328,8 +378,13 @@ where
 
 		let bytes = self.transactions.bytes();
 
+		let sender_limit_exceeded = tx.sender.read().as_deref().is_some_and(|sender| {
+			self.is_sender_limit_exceeded(transactions.iter().map(|(_, t)| t), sender)
+		});
+
 		let result = match (
-			self.is_limit_exceeded(transactions.len() + 1, bytes + tx.bytes),
+			self.is_limit_exceeded(transactions.len() + 1, bytes + tx.bytes) ||
+				sender_limit_exceeded,
 			transactions.contains_key(&tx_hash),
 		) {
 			(false, false) => {
@@ -360,17 +415,21 @@ where
 	/// size of removed transactions will be equal (or greated) then size of newly inserted
 	/// transaction.
 	///
+	/// The transaction is rejected if its sender already reached the per-sender limit.
+	///
 	/// Returns a `Result` containing `InsertionInfo` if the new transaction is successfully
 	/// inserted; otherwise, returns an appropriate error indicating the failure.
 	pub(super) fn try_insert_with_replacement(
 		&self,
 		new_tx: ExtrinsicFor<ChainApi>,
 		priority: TransactionPriority,
+		sender: Option<Vec<u8>>,
 		source: TransactionSource,
 		watched: bool,
 	) -> Result<InsertionInfo<ExtrinsicHash<ChainApi>>, sc_transaction_pool_api::error::Error> {
 		let (hash, length) = self.api.hash_and_length(&new_tx);
-		let new_tx = TxInMemPool::new_with_priority(watched, source, new_tx, length, priority);
+		let new_tx =
+			TxInMemPool::new_with_priority(watched, source, new_tx, length, priority, sender);
 		if new_tx.bytes > self.max_transactions_total_bytes {
 			return Err(sc_transaction_pool_api::error::Error::ImmediatelyDropped);
 		}
@@ -381,6 +440,12 @@ where
 			return Err(sc_transaction_pool_api::error::Error::AlreadyImported(Box::new(hash)));
 		}
 
+		if new_tx.sender.read().as_deref().is_some_and(|sender| {
+			self.is_sender_limit_exceeded(transactions.iter().map(|(_, t)| t), sender)
+		}) {
+			return Err(sc_transaction_pool_api::error::Error::ImmediatelyDropped);
+		}
+
 		let mut sorted = transactions
 			.iter()
 			.filter_map(|(h, v)| v.priority().map(|_| (*h, v.clone())))
@@ -479,8 +544,11 @@ where
 
 	/// Revalidates a batch of transactions against the provided finalized block.
 	///
-	/// Returns a vector of invalid transaction hashes.
-	async fn revalidate_inner(&self, finalized_block: HashAndNumber<Block>) -> Vec<Block::Hash> {
+	/// Returns a map of invalid transaction hashes to the description of their validation error.
+	async fn revalidate_inner(
+		&self,
+		finalized_block: HashAndNumber<Block>,
+	) -> HashMap<Block::Hash, String> {
 		trace!(
 			target: LOG_TARGET,
 			?finalized_block,
@@ -525,19 +593,26 @@ where
 			.filter_map(|(tx_hash, validation_result)| match validation_result {
 				Ok(Ok(_)) |
 				Ok(Err(TransactionValidityError::Invalid(InvalidTransaction::Future))) => None,
-				Err(_) |
-				Ok(Err(TransactionValidityError::Unknown(_))) |
-				Ok(Err(TransactionValidityError::Invalid(_))) => {
+				Err(error) => {
+					trace!(
+						target: LOG_TARGET,
+						?tx_hash,
+						%error,
+						"mempool::revalidate_inner invalid"
+					);
+					Some((tx_hash, error.to_string()))
+				},
+				Ok(Err(error)) => {
 					trace!(
 						target: LOG_TARGET,
 						?tx_hash,
-						?validation_result,
+						?error,
 						"mempool::revalidate_inner invalid"
 					);
-					Some(tx_hash)
+					Some((tx_hash, format!("{error:?}")))
 				},
 			})
-			.collect::<Vec<_>>();
+			.collect::<HashMap<_, _>>();
 
 		debug!(
 			target: LOG_TARGET,
@@ -579,8 +654,8 @@ where
 		let revalidated_invalid_hashes = self.revalidate_inner(finalized_block.clone()).await;
 
 		let mut invalid_hashes_subtrees =
-			revalidated_invalid_hashes.clone().into_iter().collect::<HashSet<_>>();
-		for tx in &revalidated_invalid_hashes {
+			revalidated_invalid_hashes.keys().copied().collect::<HashSet<_>>();
+		for tx in revalidated_invalid_hashes.keys() {
 			invalid_hashes_subtrees.extend(
 				view_store
 					.remove_transaction_subtree(*tx, |_, _| {})
@@ -605,8 +680,12 @@ where
 		let revalidated_invalid_hashes_len = revalidated_invalid_hashes.len();
 		let invalid_hashes_subtrees_len = invalid_hashes_subtrees.len();
 
-		self.listener
-			.transactions_invalidated(&invalid_hashes_subtrees.into_iter().collect::<Vec<_>>());
+		// Descendants of the invalid transactions are reported without a validity error.
+		self.listener.transactions_invalidated_with_errors(
+			invalid_hashes_subtrees
+				.into_iter()
+				.map(|tx_hash| (tx_hash, revalidated_invalid_hashes.get(&tx_hash).cloned())),
+		);
 
 		trace!(
 			target: LOG_TARGET,
@@ -617,15 +696,34 @@ where
 		);
 	}
 
-	/// Updates the priority of transaction stored in mempool using provided view_store submission
-	/// outcome.
-	pub(super) fn update_transaction_priority(&self, outcome: &ViewStoreSubmitOutcome<ChainApi>) {
-		outcome.priority().map(|priority| {
-			self.transactions
-				.write()
-				.get_mut(&outcome.hash())
-				.map(|p| *p.priority.write() = Some(priority))
-		});
+	/// Updates the priority and the sender of transaction stored in mempool using provided
+	/// view_store submission outcome.
+	///
+	/// If the sender of the transaction already has as many other transactions in the mempool as
+	/// the per-sender limit allows, the transaction is removed from the mempool and
+	/// `ImmediatelyDropped` error is returned.
+	pub(super) fn update_transaction_info(
+		&self,
+		outcome: &ViewStoreSubmitOutcome<ChainApi>,
+	) -> Result<(), sc_transaction_pool_api::error::Error> {
+		let tx_hash = outcome.hash();
+		let mut transactions = self.transactions.write();
+		let Some(tx) = transactions.get_mut(&tx_hash).cloned() else { return Ok(()) };
+
+		if let Some(priority) = outcome.priority() {
+			*tx.priority.write() = Some(priority);
+		}
+
+		let Some(sender) = outcome.sender() else { return Ok(()) };
+		*tx.sender.write() = Some(sender.to_vec());
+
+		let others = transactions.iter().filter(|(h, _)| **h != tx_hash).map(|(_, t)| t);
+		if self.is_sender_limit_exceeded(others, sender) {
+			transactions.remove(&tx_hash);
+			trace!(target: LOG_TARGET, ?tx_hash, "mempool::update_transaction_info: sender limit");
+			return Err(sc_transaction_pool_api::error::Error::ImmediatelyDropped);
+		}
+		Ok(())
 	}
 
 	/// Counts the number of transactions in the provided iterator of hashes
@@ -825,12 +923,12 @@ mod tx_mem_pool_tests {
 
 		submit_outcomes
 			.into_iter()
-			.for_each(|o| mempool.update_transaction_priority(&o));
+			.for_each(|o| mempool.update_transaction_info(&o).unwrap());
 
 		let xt = Arc::from(large_uxt(98));
 		let hash = api.hash_and_length(&xt).0;
 		let result = mempool
-			.try_insert_with_replacement(xt, hi_prio, TransactionSource::External, false)
+			.try_insert_with_replacement(xt, hi_prio, None, TransactionSource::External, false)
 			.unwrap();
 
 		assert_eq!(result.hash, hash);
@@ -865,14 +963,14 @@ mod tx_mem_pool_tests {
 
 		submit_outcomes
 			.into_iter()
-			.for_each(|o| mempool.update_transaction_priority(&o));
+			.for_each(|o| mempool.update_transaction_info(&o).unwrap());
 
 		//this one should drop 2 xts (size: 1130):
 		let xt = Arc::from(ExtrinsicBuilder::new_include_data(vec![98 as u8; 1025]).build());
 		let (hash, length) = api.hash_and_length(&xt);
 		assert_eq!(length, 1130);
 		let result = mempool
-			.try_insert_with_replacement(xt, hi_prio, TransactionSource::External, false)
+			.try_insert_with_replacement(xt, hi_prio, None, TransactionSource::External, false)
 			.unwrap();
 
 		assert_eq!(result.hash, hash);
@@ -906,7 +1004,7 @@ mod tx_mem_pool_tests {
 
 		submit_outcomes
 			.into_iter()
-			.for_each(|o| mempool.update_transaction_priority(&o));
+			.for_each(|o| mempool.update_transaction_info(&o).unwrap());
 
 		//this one should drop 3 xts (each of size 1129)
 		let xt = Arc::from(ExtrinsicBuilder::new_include_data(vec![98 as u8; 2154]).build());
@@ -914,7 +1012,7 @@ mod tx_mem_pool_tests {
 		// overhead is 105, thus length: 105 + 2154
 		assert_eq!(length, 2 * LARGE_XT_SIZE + 1);
 		let result = mempool
-			.try_insert_with_replacement(xt, hi_prio, TransactionSource::External, false)
+			.try_insert_with_replacement(xt, hi_prio, None, TransactionSource::External, false)
 			.unwrap();
 
 		assert_eq!(result.hash, hash);
@@ -948,11 +1046,16 @@ mod tx_mem_pool_tests {
 
 		submit_outcomes
 			.into_iter()
-			.for_each(|o| mempool.update_transaction_priority(&o));
+			.for_each(|o| mempool.update_transaction_info(&o).unwrap());
 
 		let xt = Arc::from(large_uxt(98));
-		let result =
-			mempool.try_insert_with_replacement(xt, low_prio, TransactionSource::External, false);
+		let result = mempool.try_insert_with_replacement(
+			xt,
+			low_prio,
+			None,
+			TransactionSource::External,
+			false,
+		);
 
 		// lower prio tx is rejected immediately
 		assert!(matches!(
@@ -984,13 +1087,67 @@ mod tx_mem_pool_tests {
 		// overhead is 105, thus length: 105 + 2154
 		assert_eq!(length, 2 * LARGE_XT_SIZE + 1);
 
-		let result =
-			mempool.try_insert_with_replacement(xt, hi_prio, TransactionSource::External, false);
+		let result = mempool.try_insert_with_replacement(
+			xt,
+			hi_prio,
+			None,
+			TransactionSource::External,
+			false,
+		);
 
-		// we did not update priorities (update_transaction_priority was not called):
+		// we did not update priorities (update_transaction_info was not called):
 		assert!(matches!(
 			result.unwrap_err(),
 			sc_transaction_pool_api::error::Error::ImmediatelyDropped
 		));
 	}
+
+	#[test]
+	fn sender_limit_is_applied_on_insertion() {
+		sp_tracing::try_init_simple();
+		let api = Arc::from(TestApi::default());
+		let mempool = TxMemPool::new_test(api.clone(), usize::MAX, usize::MAX).with_sender_limit(2);
+
+		let sender = vec![1u8; 32];
+		let xts = (0..3).map(|x| Arc::from(uxt(x))).collect::<Vec<_>>();
+
+		let results = mempool.extend_unwatched(TransactionSource::External, &xts);
+		assert!(results.iter().all(Result::is_ok));
+
+		let mut outcomes = xts.iter().map(|t| {
+			ViewStoreSubmitOutcome::new(api.hash_and_length(t).0, Some(10))
+				.with_sender(Some(sender.clone()))
+		});
+		assert!(mempool.update_transaction_info(&outcomes.next().unwrap()).is_ok());
+		assert!(mempool.update_transaction_info(&outcomes.next().unwrap()).is_ok());
+
+		// the third transaction from the same sender exceeds the limit and is removed
+		assert!(matches!(
+			mempool.update_transaction_info(&outcomes.next().unwrap()).unwrap_err(),
+			sc_transaction_pool_api::error::Error::ImmediatelyDropped
+		));
+		assert_eq!(mempool.len(), 2);
+
+		// replacement of a transaction from the same sender is rejected too
+		let xt = Arc::from(uxt(3));
+		let result = mempool.try_insert_with_replacement(
+			xt,
+			100,
+			Some(sender),
+			TransactionSource::External,
+			false,
+		);
+		assert!(matches!(
+			result.unwrap_err(),
+			sc_transaction_pool_api::error::Error::ImmediatelyDropped
+		));
+
+		// transactions from other senders are not affected
+		let xt = Arc::from(uxt(4));
+		let outcome = ViewStoreSubmitOutcome::new(api.hash_and_length(&xt).0, Some(10))
+			.with_sender(Some(vec![2u8; 32]));
+		assert!(mempool.push_watched(TransactionSource::External, xt).is_ok());
+		assert!(mempool.update_transaction_info(&outcome).is_ok());
+		assert_eq!(mempool.len(), 3);
+	}
 }
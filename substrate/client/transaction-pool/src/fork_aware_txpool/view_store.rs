@@ -19,7 +19,7 @@
 //! Transaction pool view store. Basically block hash to view map with some utility methods.
 
 use super::{
-	multi_view_listener::{MultiViewListener, TxStatusStream},
+	multi_view_listener::{DetailedTxStatusStream, MultiViewListener},
 	view::{View, ViewPoolObserver},
 };
 use crate::{
@@ -34,7 +34,9 @@ use crate::{
 };
 use itertools::Itertools;
 use parking_lot::RwLock;
-use sc_transaction_pool_api::{error::Error as PoolError, PoolStatus, TxInvalidityReportMap};
+use sc_transaction_pool_api::{
+	error::Error as PoolError, PoolStatus, PooledTransactionQueue, TxInvalidityReportMap,
+};
 use sp_blockchain::{HashAndNumber, TreeRoute};
 use sp_runtime::{
 	generic::BlockId,
@@ -179,13 +181,13 @@ where
 
 /// Type alias to outcome of submission to `ViewStore`.
 pub(super) type ViewStoreSubmitOutcome<ChainApi> =
-	BaseSubmitOutcome<ChainApi, TxStatusStream<ChainApi>>;
+	BaseSubmitOutcome<ChainApi, DetailedTxStatusStream<ChainApi>>;
 
 impl<ChainApi: graph::ChainApi> From<ValidatedPoolSubmitOutcome<ChainApi>>
 	for ViewStoreSubmitOutcome<ChainApi>
 {
 	fn from(value: ValidatedPoolSubmitOutcome<ChainApi>) -> Self {
-		Self::new(value.hash(), value.priority())
+		Self::new(value.hash(), value.priority()).with_sender(value.sender().map(<[u8]>::to_vec))
 	}
 }
 
@@ -290,7 +292,8 @@ where
 		xt: ExtrinsicFor<ChainApi>,
 	) -> Result<ViewStoreSubmitOutcome<ChainApi>, ChainApi::Error> {
 		let tx_hash = self.api.hash_and_length(&xt).0;
-		let Some(external_watcher) = self.listener.create_external_watcher_for_tx(tx_hash) else {
+		let Some(external_watcher) = self.listener.create_detailed_external_watcher_for_tx(tx_hash)
+		else {
 			return Err(PoolError::AlreadyImported(Box::new(tx_hash)).into())
 		};
 		let submit_futures = {
@@ -413,6 +416,32 @@ where
 			.map(|(v, _)| v.pool.validated_pool().pool.read().futures().cloned().collect())
 	}
 
+	/// Returns every transaction held by the active views, along with the views and queues
+	/// holding it.
+	pub(super) fn inspect(
+		&self,
+	) -> HashMap<
+		ExtrinsicHash<ChainApi>,
+		(TransactionFor<ChainApi>, Vec<(Block::Hash, PooledTransactionQueue)>),
+	> {
+		let mut transactions = HashMap::<_, (TransactionFor<ChainApi>, Vec<_>)>::new();
+		for (at, view) in self.active_views.read().iter() {
+			let pool = view.pool.validated_pool().pool.read();
+			let ready = pool.ready().map(|tx| (tx, PooledTransactionQueue::Ready));
+			let future = pool
+				.futures()
+				.map(|tx| (Arc::new(tx.duplicate()), PooledTransactionQueue::Future));
+			for (tx, queue) in ready.chain(future) {
+				transactions
+					.entry(tx.hash)
+					.or_insert_with(|| (tx, Vec::new()))
+					.1
+					.push((*at, queue));
+			}
+		}
+		transactions
+	}
+
 	/// Collects all the transactions included in the blocks on the provided `tree_route` and
 	/// triggers finalization event for them.
 	///
@@ -668,6 +697,7 @@ where
 	) -> Vec<TransactionFor<ChainApi>> {
 		let mut remove_from_view = vec![];
 		let mut remove_from_pool = vec![];
+		let mut errors = vec![];
 
 		invalid_tx_errors.into_iter().for_each(|(hash, e)| match e {
 			Some(TransactionValidityError::Invalid(
@@ -677,6 +707,7 @@ where
 			},
 			_ => {
 				remove_from_pool.push(hash);
+				errors.push(e.map(|e| format!("{e:?}")));
 			},
 		});
 
@@ -696,7 +727,8 @@ where
 				.map(|tx| removed.push(tx.clone()));
 		}
 
-		self.listener.transactions_invalidated(&remove_from_pool);
+		self.listener
+			.transactions_invalidated_with_errors(remove_from_pool.into_iter().zip(errors));
 
 		removed
 	}
@@ -20,11 +20,19 @@
 //!
 //! For a more full-featured pool, have a look at the `pool` module.
 
-use std::{cmp::Ordering, collections::HashSet, fmt, hash, sync::Arc, time::Instant};
+use std::{
+	cmp::Ordering,
+	collections::{hash_map::Entry, HashMap, HashSet},
+	fmt, hash,
+	sync::Arc,
+	time::{Instant, SystemTime},
+};
 
-use crate::LOG_TARGET;
+use crate::{common::metrics::PolicyMetricsLink, LOG_TARGET};
 use log::{trace, warn};
-use sc_transaction_pool_api::{error, InPoolTransaction, PoolStatus};
+use sc_transaction_pool_api::{
+	error, InPoolTransaction, PoolStatus, PooledTransactionInfo, PooledTransactionQueue,
+};
 use serde::Serialize;
 use sp_core::hexdisplay::HexDisplay;
 use sp_runtime::{
@@ -119,6 +127,12 @@ impl TimedTransactionSource {
 	pub fn from_transaction_source(source: TransactionSource, with_timestamp: bool) -> Self {
 		Self { source, timestamp: with_timestamp.then(Instant::now) }
 	}
+
+	/// Returns the wall-clock time at which the transaction was submitted, if known.
+	pub fn submitted_at(&self) -> Option<SystemTime> {
+		self.timestamp
+			.and_then(|timestamp| SystemTime::now().checked_sub(timestamp.elapsed()))
+	}
 }
 
 /// Immutable transaction
@@ -202,6 +216,26 @@ impl<Hash: Clone, Extrinsic: Clone> Transaction<Hash, Extrinsic> {
 			propagate: self.propagate,
 		}
 	}
+
+	/// Returns the inspection details of the transaction placed in the given views.
+	pub fn pooled_info<BlockHash>(
+		&self,
+		watched: bool,
+		views: Vec<(BlockHash, PooledTransactionQueue)>,
+	) -> PooledTransactionInfo<Hash, BlockHash> {
+		PooledTransactionInfo {
+			hash: self.hash.clone(),
+			bytes: self.bytes,
+			priority: Some(self.priority),
+			valid_till: Some(self.valid_till),
+			requires: self.requires.clone(),
+			provides: self.provides.clone(),
+			source: self.source.source,
+			submitted_at: self.source.submitted_at(),
+			watched,
+			views,
+		}
+	}
 }
 
 impl<Hash, Extrinsic> fmt::Debug for Transaction<Hash, Extrinsic>
@@ -256,6 +290,13 @@ pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	/// transactions to future in case they were just stuck in verification.
 	recently_pruned: [HashSet<Tag>; RECENTLY_PRUNED_TAGS],
 	recently_pruned_index: usize,
+	/// Admission and eviction policies.
+	policy: Policy,
+	/// Number of transactions (ready and future) of each sender, if the number of transactions
+	/// per sender is limited.
+	sender_counts: HashMap<Vec<u8>, usize>,
+	/// Prometheus metrics of the admission and eviction policies.
+	metrics: PolicyMetricsLink,
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for BasePool<Hash, Ex> {
@@ -273,9 +314,24 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			ready: Default::default(),
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
+			policy: Default::default(),
+			sender_counts: Default::default(),
+			metrics: Default::default(),
 		}
 	}
 
+	/// Sets the admission and eviction policies of the pool.
+	pub(crate) fn with_policy(mut self, policy: Policy) -> Self {
+		self.ready.set_min_priority_bump_percent(policy.min_priority_bump_percent);
+		self.policy = policy;
+		self
+	}
+
+	/// Sets the Prometheus metrics link used to report the admission and eviction policies.
+	pub(crate) fn set_policy_metrics(&mut self, metrics: PolicyMetricsLink) {
+		self.metrics = metrics;
+	}
+
 	/// Clears buffer keeping recently pruned transaction.
 	pub fn clear_recently_pruned(&mut self) {
 		self.recently_pruned = Default::default();
@@ -315,6 +371,12 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			return Err(error::Error::AlreadyImported(Box::new(tx.hash)))
 		}
 
+		if self.is_sender_limit_exceeded(&tx) {
+			trace!(target: LOG_TARGET, "[{:?}] Sender limit exceeded", tx.hash);
+			self.metrics.report(|metrics| metrics.sender_limit_rejected.inc());
+			return Err(error::Error::ImmediatelyDropped)
+		}
+
 		let tx = WaitingTransaction::new(tx, self.ready.provided_tags(), &self.recently_pruned);
 		trace!(
 			target: LOG_TARGET,
@@ -331,6 +393,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			}
 
 			let hash = tx.transaction.hash.clone();
+			self.update_sender_counts([&*tx.transaction], true);
 			self.future.import(tx);
 			return Ok(Imported::Future { hash })
 		}
@@ -356,13 +419,17 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		// take first transaction from the list
 		while let Some(tx) = to_import.pop() {
 			// find transactions in Future that it unlocks
-			to_import.append(&mut self.future.satisfy_tags(&tx.transaction.provides));
+			let mut unlocked = self.future.satisfy_tags(&tx.transaction.provides);
+			self.update_sender_counts(unlocked.iter().map(|tx| &*tx.transaction), false);
+			to_import.append(&mut unlocked);
 
 			// import this transaction
 			let current_hash = tx.transaction.hash.clone();
 			let current_tx = tx.transaction.clone();
 			match self.ready.import(tx) {
 				Ok(mut replaced) => {
+					self.update_sender_counts([&*current_tx], true);
+					self.update_sender_counts(replaced.iter().map(|tx| &**tx), false);
 					if !first {
 						promoted.push(current_hash.clone());
 					}
@@ -373,7 +440,8 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 					// re-import them.
 					removed.append(&mut replaced);
 				},
-				Err(e @ error::Error::TooLowPriority { .. }) =>
+				Err(e @ error::Error::TooLowPriority { .. }) => {
+					self.metrics.report(|metrics| metrics.priority_bump_rejected.inc());
 					if first {
 						trace!(target: LOG_TARGET, "[{:?}] Error importing {first}: {:?}", current_tx.hash, e);
 						return Err(e)
@@ -381,7 +449,8 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 						trace!(target: LOG_TARGET, "[{:?}] Error importing {first}: {:?}", current_tx.hash, e);
 						removed.push(current_tx);
 						promoted.retain(|hash| *hash != current_hash);
-					},
+					}
+				},
 				// transaction failed to be imported.
 				Err(e) =>
 					if first {
@@ -403,7 +472,8 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		if removed.iter().any(|tx| tx.hash == hash) {
 			// We still need to remove all transactions that we promoted
 			// since they depend on each other and will never get to the best iterator.
-			self.ready.remove_subtree(&promoted);
+			let cycle = self.ready.remove_subtree(&promoted);
+			self.update_sender_counts(cycle.iter().map(|tx| &**tx), false);
 
 			trace!(target: LOG_TARGET, "[{:?}] Cycle detected, bailing.", hash);
 			return Err(error::Error::CycleDetected)
@@ -438,12 +508,69 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.ready.by_hash(hash)
 	}
 
+	/// Returns the sender key of the transaction, see [`Policy::sender_key`].
+	fn sender_key<'a>(&self, tx: &'a Transaction<Hash, Ex>) -> Option<&'a [u8]> {
+		self.policy.sender_key(&tx.provides)
+	}
+
+	/// Returns true if importing the transaction would exceed the per-sender limit.
+	///
+	/// Transactions replacing ready transactions which provide the same tags are not limited, as
+	/// they do not increase the number of transactions of the sender.
+	fn is_sender_limit_exceeded(&self, tx: &Transaction<Hash, Ex>) -> bool {
+		let (Some(limit), Some(sender)) = (self.policy.sender_limit, self.sender_key(tx)) else {
+			return false
+		};
+
+		let provided_tags = self.ready.provided_tags();
+		if tx.provides.iter().any(|tag| provided_tags.contains_key(tag)) {
+			return false
+		}
+
+		self.sender_counts.get(sender).is_some_and(|count| *count >= limit)
+	}
+
+	/// Updates the number of transactions of the senders of the transactions `added` to or
+	/// removed from the pool.
+	///
+	/// Must be called whenever a transaction enters or leaves one of the queues.
+	fn update_sender_counts<'a>(
+		&mut self,
+		txs: impl IntoIterator<Item = &'a Transaction<Hash, Ex>>,
+		added: bool,
+	) where
+		Hash: 'a,
+		Ex: 'a,
+	{
+		if self.policy.sender_limit.is_none() {
+			return
+		}
+
+		for tx in txs {
+			let Some(sender) = self.sender_key(tx).map(<[u8]>::to_vec) else { continue };
+			match self.sender_counts.entry(sender) {
+				Entry::Occupied(mut entry) if !added => {
+					*entry.get_mut() -= 1;
+					if *entry.get() == 0 {
+						entry.remove();
+					}
+				},
+				Entry::Occupied(mut entry) => *entry.get_mut() += 1,
+				Entry::Vacant(entry) =>
+					if added {
+						entry.insert(1);
+					},
+			}
+		}
+	}
+
 	/// Makes sure that the transactions in the queues stay within provided limits.
 	///
 	/// Removes and returns worst transactions from the queues and all transactions that depend on
 	/// them. Technically the worst transaction should be evaluated by computing the entire pending
-	/// set. We use a simplified approach to remove transactions with the lowest priority first or
-	/// those that occupy the pool for the longest time in case priority is the same.
+	/// set. We use a simplified approach to remove ready transactions with the lowest priority per
+	/// byte first or those that occupy the pool for the longest time in case the score is the
+	/// same.
 	pub fn enforce_limits(
 		&mut self,
 		ready: &Limit,
@@ -462,8 +589,10 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 							// while it prefers priority like need here, it also prefers older
 							// transactions for inclusion purposes and limit enforcement needs to
 							// prefer newer transactions instead and drop the older ones.
-							match worst.transaction.priority.cmp(&transaction.transaction.priority)
-							{
+							match cmp_priority_per_byte(
+								&worst.transaction,
+								&transaction.transaction,
+							) {
 								Ordering::Less => worst,
 								Ordering::Equal =>
 									if worst.insertion_id > transaction.insertion_id {
@@ -478,7 +607,11 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 				});
 
 			if let Some(worst) = worst {
-				removed.append(&mut self.remove_subtree(&[worst.transaction.hash.clone()]))
+				let mut evicted = self.remove_subtree(&[worst.transaction.hash.clone()]);
+				self.metrics.report(|metrics| {
+					metrics.priority_per_byte_evicted.inc_by(evicted.len() as u64)
+				});
+				removed.append(&mut evicted)
 			} else {
 				break
 			}
@@ -529,12 +662,15 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	pub fn remove_subtree(&mut self, hashes: &[Hash]) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = self.ready.remove_subtree(hashes);
 		removed.extend(self.future.remove(hashes));
+		self.update_sender_counts(removed.iter().map(|tx| &**tx), false);
 		removed
 	}
 
 	/// Removes and returns all transactions from the future queue.
 	pub fn clear_future(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
-		self.future.clear()
+		let removed = self.future.clear();
+		self.update_sender_counts(removed.iter().map(|tx| &**tx), false);
+		removed
 	}
 
 	/// Prunes transactions that provide given list of tags.
@@ -562,6 +698,11 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			recently_pruned.insert(tag);
 		}
 
+		// Promoted transactions are counted again once imported to ready.
+		let left = futures_removed.iter().chain(&pruned).map(|tx| &**tx);
+		let left = left.chain(to_import.iter().map(|tx| &*tx.transaction)).collect::<Vec<_>>();
+		self.update_sender_counts(left, false);
+
 		let mut promoted = vec![];
 		let mut failed = vec![];
 		for tx in futures_removed {
@@ -596,6 +737,46 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	}
 }
 
+/// Compares transactions by their priority per byte of encoding.
+fn cmp_priority_per_byte<Hash, Ex>(
+	a: &Transaction<Hash, Ex>,
+	b: &Transaction<Hash, Ex>,
+) -> Ordering {
+	let a_score = a.priority as u128 * b.bytes.max(1) as u128;
+	let b_score = b.priority as u128 * a.bytes.max(1) as u128;
+	a_score.cmp(&b_score)
+}
+
+/// Admission and eviction policies of the pool.
+#[derive(Debug, Clone)]
+pub struct Policy {
+	/// Maximal number of transactions (ready and future) of a single sender.
+	///
+	/// `None` disables the limit.
+	pub sender_limit: Option<usize>,
+	/// Number of leading bytes of the first `provides` tag identifying the sender.
+	///
+	/// The default matches `(AccountId32, Nonce)` tags provided by FRAME transactions.
+	pub sender_tag_prefix_len: usize,
+	/// Minimal priority increase (in percent) required to replace transactions providing the
+	/// same tags.
+	pub min_priority_bump_percent: u64,
+}
+
+impl Default for Policy {
+	fn default() -> Self {
+		Self { sender_limit: None, sender_tag_prefix_len: 32, min_priority_bump_percent: 0 }
+	}
+}
+
+impl Policy {
+	/// Returns the sender key of a transaction providing the given tags, i.e. the prefix of its
+	/// first `provides` tag.
+	pub fn sender_key<'a>(&self, provides: &'a [Tag]) -> Option<&'a [u8]> {
+		provides.first().map(|tag| &tag[..tag.len().min(self.sender_tag_prefix_len)])
+	}
+}
+
 /// Queue limits
 #[derive(Debug, Clone)]
 pub struct Limit {
@@ -1256,4 +1437,125 @@ source: TimedTransactionSource { source: TransactionSource::External, timestamp:
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	#[test]
+	fn should_limit_transactions_per_sender() {
+		// given
+		let policy =
+			Policy { sender_limit: Some(2), sender_tag_prefix_len: 1, ..Default::default() };
+		let mut pool = pool().with_policy(policy);
+		let sender_tx = |hash: u64, sender: u8, nonce: u8| Transaction {
+			data: vec![hash as u8],
+			hash,
+			provides: vec![vec![sender, nonce]],
+			..default_tx().clone()
+		};
+		pool.import(sender_tx(1, 1, 0)).unwrap();
+		pool.import(Transaction { requires: vec![vec![1, 5]], ..sender_tx(2, 1, 6) })
+			.unwrap();
+
+		// when
+		let err = pool.import(sender_tx(3, 1, 1)).unwrap_err();
+
+		// then
+		assert!(matches!(err, error::Error::ImmediatelyDropped));
+		pool.import(sender_tx(4, 2, 0)).unwrap();
+		// replacement does not increase the number of transactions of the sender
+		pool.import(Transaction { priority: 10, ..sender_tx(5, 1, 0) }).unwrap();
+		assert_eq!(pool.ready().count(), 2);
+		assert_eq!(pool.future.len(), 1);
+	}
+
+	#[test]
+	fn should_update_sender_counts_when_transactions_leave_the_pool() {
+		// given
+		let policy =
+			Policy { sender_limit: Some(2), sender_tag_prefix_len: 1, ..Default::default() };
+		let mut pool = pool().with_policy(policy);
+		let sender_tx = |hash: u64, nonce: u8| Transaction {
+			data: vec![hash as u8],
+			hash,
+			provides: vec![vec![1, nonce]],
+			requires: if nonce == 0 { vec![] } else { vec![vec![1, nonce - 1]] },
+			..default_tx().clone()
+		};
+		// a future transaction promoted to ready is counted once
+		pool.import(sender_tx(2, 1)).unwrap();
+		pool.import(sender_tx(1, 0)).unwrap();
+		assert_eq!(pool.ready().count(), 2);
+		assert_eq!(pool.sender_counts.get(&[1u8][..]), Some(&2));
+		assert!(matches!(pool.import(sender_tx(3, 2)), Err(error::Error::ImmediatelyDropped)));
+
+		// when
+		pool.prune_tags(vec![vec![1, 0]]);
+
+		// then
+		assert_eq!(pool.sender_counts.get(&[1u8][..]), Some(&1));
+		pool.import(sender_tx(3, 2)).unwrap();
+		pool.import(sender_tx(5, 4)).unwrap_err();
+
+		pool.remove_subtree(&[3]);
+		assert_eq!(pool.sender_counts.get(&[1u8][..]), Some(&1));
+		pool.remove_subtree(&[2]);
+		assert!(pool.sender_counts.is_empty());
+	}
+
+	#[test]
+	fn should_require_min_priority_bump_for_replacement() {
+		// given
+		let policy = Policy { min_priority_bump_percent: 10, ..Default::default() };
+		let mut pool = pool().with_policy(policy);
+		pool.import(Transaction { priority: 100, provides: vec![vec![1]], ..default_tx().clone() })
+			.unwrap();
+
+		// when
+		let err = pool
+			.import(Transaction {
+				hash: 2,
+				priority: 105,
+				provides: vec![vec![1]],
+				..default_tx().clone()
+			})
+			.unwrap_err();
+
+		// then
+		assert!(matches!(err, error::Error::TooLowPriority { old: 100, new: 105 }));
+		let res = pool
+			.import(Transaction {
+				hash: 3,
+				priority: 110,
+				provides: vec![vec![1]],
+				..default_tx().clone()
+			})
+			.unwrap();
+		assert!(matches!(res, Imported::Ready { hash: 3, ref removed, .. } if removed.len() == 1));
+	}
+
+	#[test]
+	fn should_evict_lowest_priority_per_byte() {
+		// given
+		let mut pool = pool();
+		let tx = |hash: u64, priority: u64, bytes: usize| Transaction {
+			data: vec![hash as u8],
+			hash,
+			priority,
+			bytes,
+			provides: vec![vec![hash as u8]],
+			..default_tx().clone()
+		};
+		pool.import(tx(1, 10, 1)).unwrap();
+		// highest priority, but lowest priority per byte
+		pool.import(tx(2, 50, 10)).unwrap();
+		pool.import(tx(3, 20, 1)).unwrap();
+
+		// when
+		let removed = pool.enforce_limits(
+			&Limit { count: 2, total_bytes: 100 },
+			&Limit { count: 10, total_bytes: 100 },
+		);
+
+		// then
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![2]);
+		assert_eq!(pool.ready().count(), 2);
+	}
 }
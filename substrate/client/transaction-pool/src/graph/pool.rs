@@ -16,7 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{common::tracing_log_xt::log_xt_trace, LOG_TARGET};
+use crate::{
+	common::{metrics::PolicyMetricsLink, tracing_log_xt::log_xt_trace},
+	LOG_TARGET,
+};
 use futures::{channel::mpsc::Receiver, Future};
 use indexmap::IndexMap;
 use sc_transaction_pool_api::error;
@@ -144,6 +147,8 @@ pub struct Options {
 	pub reject_future_transactions: bool,
 	/// How long the extrinsic is banned for.
 	pub ban_time: Duration,
+	/// Admission and eviction policies.
+	pub policy: base::Policy,
 }
 
 impl Default for Options {
@@ -153,6 +158,7 @@ impl Default for Options {
 			future: base::Limit { count: 512, total_bytes: 1 * 1024 * 1024 },
 			reject_future_transactions: false,
 			ban_time: Duration::from_secs(60 * 30),
+			policy: Default::default(),
 		}
 	}
 }
@@ -506,6 +512,12 @@ impl<B: ChainApi, L: EventHandler<B>> Pool<B, L> {
 	pub fn clear_recently_pruned(&mut self) {
 		self.validated_pool.pool.write().clear_recently_pruned();
 	}
+
+	/// Sets the Prometheus metrics link used to report the admission and eviction policies.
+	pub(crate) fn with_policy_metrics(self, metrics: PolicyMetricsLink) -> Self {
+		self.validated_pool.pool.write().set_policy_metrics(metrics);
+		self
+	}
 }
 
 impl<B: ChainApi, L: EventHandler<B>> Pool<B, L> {
@@ -118,6 +118,9 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	/// Best transactions that are ready to be included to the block without any other previous
 	/// transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Minimal priority increase (in percent) required to replace transactions providing the
+	/// same tags.
+	min_priority_bump_percent: u64,
 }
 
 impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
@@ -133,11 +136,18 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			min_priority_bump_percent: 0,
 		}
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
+	/// Sets the minimal priority increase (in percent) required to replace transactions providing
+	/// the same tags.
+	pub fn set_min_priority_bump_percent(&mut self, percent: u64) {
+		self.min_priority_bump_percent = percent;
+	}
+
 	/// Borrows a map of tags that are provided by transactions in this queue.
 	pub fn provided_tags(&self) -> &HashMap<Tag, Hash> {
 		&self.provided_tags
@@ -445,7 +455,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
+			let required_priority =
+				old_priority as u128 * (100 + self.min_priority_bump_percent as u128) / 100;
+			if old_priority >= tx.priority || required_priority > tx.priority as u128 {
 				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
 			}
 
@@ -86,6 +86,14 @@ impl<Hash, Ex, Error> ValidatedTransaction<Hash, Ex, Error> {
 			_ => None,
 		}
 	}
+
+	/// Returns the tags provided by the transaction, empty if it is not valid.
+	pub fn provides(&self) -> &[Tag] {
+		match self {
+			ValidatedTransaction::Valid(base::Transaction { provides, .. }) => provides,
+			_ => &[],
+		}
+	}
 }
 
 /// A type of validated transaction stored in the validated pool.
@@ -120,6 +128,9 @@ pub struct BaseSubmitOutcome<B: ChainApi, W> {
 
 	/// The priority of the transaction. Defaults to None if unknown.
 	priority: Option<TransactionPriority>,
+
+	/// The sender key of the transaction, set when the per-sender limit is enabled.
+	sender: Option<Vec<u8>>,
 }
 
 /// Type alias to outcome of submission to `ValidatedPool`.
@@ -129,7 +140,13 @@ pub type ValidatedPoolSubmitOutcome<B> =
 impl<B: ChainApi, W> BaseSubmitOutcome<B, W> {
 	/// Creates a new instance with given hash and priority.
 	pub fn new(hash: ExtrinsicHash<B>, priority: Option<TransactionPriority>) -> Self {
-		Self { hash, priority, watcher: None }
+		Self { hash, priority, watcher: None, sender: None }
+	}
+
+	/// Sets the sender key of the transaction.
+	pub fn with_sender(mut self, sender: Option<Vec<u8>>) -> Self {
+		self.sender = sender;
+		self
 	}
 
 	/// Sets the transaction watcher.
@@ -148,6 +165,11 @@ impl<B: ChainApi, W> BaseSubmitOutcome<B, W> {
 		self.hash
 	}
 
+	/// Provides the sender key of submitted transaction, if known.
+	pub fn sender(&self) -> Option<&[u8]> {
+		self.sender.as_deref()
+	}
+
 	/// Provides a watcher. Should only be called on outcomes of `submit_and_watch`. Otherwise will
 	/// panic (that would mean logical error in program).
 	pub fn expect_watcher(&mut self) -> W {
@@ -238,7 +260,8 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 		rotator: PoolRotator<ExtrinsicHash<B>>,
 		event_handler: Option<L>,
 	) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::new(options.reject_future_transactions)
+			.with_policy(options.policy.clone());
 		Self {
 			is_validator,
 			options,
@@ -314,6 +337,11 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 		match tx {
 			ValidatedTransaction::Valid(tx) => {
 				let priority = tx.priority;
+				let policy = &self.options.policy;
+				let sender = policy
+					.sender_limit
+					.and_then(|_| policy.sender_key(&tx.provides))
+					.map(<[u8]>::to_vec);
 				log::trace!(target: LOG_TARGET, "[{:?}] ValidatedPool::submit_one", tx.hash);
 				if !tx.propagate && !(self.is_validator.0)() {
 					return Err(error::Error::Unactionable.into())
@@ -341,7 +369,8 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 
 				let mut event_dispatcher = self.event_dispatcher.write();
 				fire_events(&mut *event_dispatcher, &imported);
-				Ok(ValidatedPoolSubmitOutcome::new(*imported.hash(), Some(priority)))
+				Ok(ValidatedPoolSubmitOutcome::new(*imported.hash(), Some(priority))
+					.with_sender(sender))
 			},
 			ValidatedTransaction::Invalid(hash, err) => {
 				log::trace!(target: LOG_TARGET, "[{:?}] ValidatedPool::submit_one invalid: {:?}", hash, err);
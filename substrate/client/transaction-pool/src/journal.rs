@@ -0,0 +1,397 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transaction pool journal.
+//!
+//! The journal keeps the pending transactions of the pool on disk, so they survive node
+//! restarts. It is written on every finalized block and when the pool is dropped, and replayed
+//! into the pool on its first maintenance, i.e. once the pool knows the current best block.
+//! Writes on finalized blocks are done by a background task: if it falls behind, only the latest
+//! snapshot of the pool is written.
+//! Replayed transactions are validated as any other submitted transaction, so entries which
+//! became invalid or expired while the node was down are dropped.
+
+use crate::LOG_TARGET;
+use codec::{Decode, Encode};
+use futures::{channel::mpsc, FutureExt, StreamExt};
+use parking_lot::Mutex;
+use sc_transaction_pool_api::{TransactionPool, TransactionSource};
+use sp_core::traits::SpawnEssentialNamed;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fs, io,
+	marker::PhantomData,
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		Arc,
+	},
+};
+
+/// Version of the journal file format.
+const JOURNAL_VERSION: u32 = 1;
+
+/// A single transaction stored in the journal.
+#[derive(Encode, Decode)]
+struct JournalEntry {
+	/// The source the transaction was originally submitted from.
+	source: TransactionSource,
+	/// The encoded extrinsic.
+	extrinsic: Vec<u8>,
+}
+
+/// The content of the journal file.
+#[derive(Encode, Decode)]
+struct JournalFile {
+	version: u32,
+	entries: Vec<JournalEntry>,
+}
+
+/// A snapshot of the pool to be written to the journal.
+struct Snapshot {
+	/// Snapshots are numbered in the order they are taken.
+	id: u64,
+	entries: Vec<JournalEntry>,
+}
+
+/// Writes the snapshots to the journal file.
+struct JournalWriter {
+	/// Path of the journal file.
+	path: PathBuf,
+	/// The id of the next snapshot.
+	next_id: AtomicU64,
+	/// The id of the last written snapshot, which also serializes the writes.
+	written: Mutex<u64>,
+}
+
+impl JournalWriter {
+	/// Takes a snapshot of the given entries.
+	fn snapshot(&self, entries: Vec<JournalEntry>) -> Snapshot {
+		Snapshot { id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1, entries }
+	}
+
+	/// Overwrites the journal file with the given snapshot, unless a more recent snapshot was
+	/// already written.
+	fn write(&self, snapshot: Snapshot) -> io::Result<()> {
+		let mut written = self.written.lock();
+		if snapshot.id <= *written {
+			return Ok(())
+		}
+
+		let count = snapshot.entries.len();
+		let file = JournalFile { version: JOURNAL_VERSION, entries: snapshot.entries };
+		if let Some(dir) = self.path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		// Write to a temporary file first, so a crash never leaves a truncated journal behind.
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, file.encode())?;
+		fs::rename(&tmp_path, &self.path)?;
+		*written = snapshot.id;
+
+		log::trace!(target: LOG_TARGET, "[journal] Stored {count} transactions to {:?}", self.path);
+		Ok(())
+	}
+}
+
+/// Persistent journal of the pending transactions of the pool.
+pub(crate) struct Journal<Block: BlockT> {
+	/// Writes the journal file.
+	writer: Arc<JournalWriter>,
+	/// The maximum number of transactions kept in the journal.
+	limit: usize,
+	/// Indicates if the journal was already replayed into the pool.
+	///
+	/// The journal is not written before it is replayed, so an early shutdown does not wipe it.
+	replayed: AtomicBool,
+	/// The latest snapshot waiting to be written by the background task.
+	pending: Arc<Mutex<Option<Snapshot>>>,
+	/// Wakes up the background task when a snapshot is pending.
+	notify: mpsc::Sender<()>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block: BlockT> Journal<Block> {
+	/// Creates a new journal stored at the given path, and spawns the task writing it in the
+	/// background.
+	pub(crate) fn new(path: PathBuf, limit: usize, spawner: &impl SpawnEssentialNamed) -> Self {
+		let writer =
+			Arc::new(JournalWriter { path, next_id: AtomicU64::new(0), written: Mutex::new(0) });
+		let pending = Arc::new(Mutex::new(None::<Snapshot>));
+		let (notify, mut notifications) = mpsc::channel(1);
+
+		let (task_writer, task_pending) = (writer.clone(), pending.clone());
+		spawner.spawn_essential_blocking(
+			"txpool-journal",
+			Some("transaction-pool"),
+			async move {
+				while notifications.next().await.is_some() {
+					let Some(snapshot) = task_pending.lock().take() else { continue };
+					if let Err(e) = task_writer.write(snapshot) {
+						log::warn!(target: LOG_TARGET, "[journal] Failed to store transactions: {e}");
+					}
+				}
+			}
+			.boxed(),
+		);
+
+		Self {
+			writer,
+			limit,
+			replayed: AtomicBool::new(false),
+			pending,
+			notify,
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Returns `true` if the journal was already replayed into the pool.
+	pub(crate) fn is_replayed(&self) -> bool {
+		self.replayed.load(Ordering::Relaxed)
+	}
+
+	/// Reads the transactions stored in the journal.
+	///
+	/// Entries which cannot be decoded are skipped. A missing or corrupted journal is treated as
+	/// empty.
+	pub(crate) fn load(&self) -> Vec<(TransactionSource, Block::Extrinsic)> {
+		let bytes = match fs::read(&self.writer.path) {
+			Ok(bytes) => bytes,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+			Err(e) => {
+				log::warn!(target: LOG_TARGET, "[journal] Failed to read {:?}: {e}", self.writer.path);
+				return Vec::new()
+			},
+		};
+
+		let file = match JournalFile::decode(&mut &bytes[..]) {
+			Ok(file) if file.version == JOURNAL_VERSION => file,
+			Ok(file) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"[journal] Unsupported version {} of {:?}, ignoring",
+					file.version,
+					self.writer.path
+				);
+				return Vec::new()
+			},
+			Err(e) => {
+				log::warn!(target: LOG_TARGET, "[journal] Failed to decode {:?}: {e}", self.writer.path);
+				return Vec::new()
+			},
+		};
+
+		file.entries
+			.into_iter()
+			.take(self.limit)
+			.filter_map(|entry| {
+				Block::Extrinsic::decode(&mut &entry.extrinsic[..])
+					.map(|xt| (entry.source, xt))
+					.inspect_err(
+						|e| log::debug!(target: LOG_TARGET, "[journal] Skipping undecodable entry: {e}"),
+					)
+					.ok()
+			})
+			.collect()
+	}
+
+	/// Overwrites the journal with the given transactions.
+	///
+	/// At most `limit` transactions are stored, transactions included in blocks are skipped.
+	/// Nothing is written until the journal is replayed.
+	pub(crate) fn store<'a>(
+		&self,
+		transactions: impl IntoIterator<Item = (TransactionSource, &'a Block::Extrinsic)>,
+	) -> io::Result<()> {
+		let Some(snapshot) = self.snapshot(transactions) else { return Ok(()) };
+		// A pending snapshot is older than this one, there is no need to write it anymore.
+		self.pending.lock().take();
+		self.writer.write(snapshot)
+	}
+
+	/// Same as [`Self::store`], but the journal is written by the background task.
+	///
+	/// Only the latest snapshot is written if the background task falls behind.
+	pub(crate) fn store_in_background<'a>(
+		&self,
+		transactions: impl IntoIterator<Item = (TransactionSource, &'a Block::Extrinsic)>,
+	) {
+		let Some(snapshot) = self.snapshot(transactions) else { return };
+		*self.pending.lock() = Some(snapshot);
+		// The channel is full when the background task was already notified.
+		let _ = self.notify.clone().try_send(());
+	}
+
+	/// Takes a snapshot of the given transactions, or returns `None` if the journal was not
+	/// replayed yet.
+	fn snapshot<'a>(
+		&self,
+		transactions: impl IntoIterator<Item = (TransactionSource, &'a Block::Extrinsic)>,
+	) -> Option<Snapshot> {
+		if !self.is_replayed() {
+			return None
+		}
+
+		let entries = transactions
+			.into_iter()
+			.filter(|(source, _)| *source != TransactionSource::InBlock)
+			.take(self.limit)
+			.map(|(source, xt)| JournalEntry { source, extrinsic: xt.encode() })
+			.collect();
+		Some(self.writer.snapshot(entries))
+	}
+
+	/// Submits the transactions stored in the journal to the pool at the given block.
+	///
+	/// The journal is replayed only once, subsequent calls do nothing.
+	pub(crate) async fn replay<P>(&self, pool: &P, at: Block::Hash)
+	where
+		P: TransactionPool<Block = Block> + ?Sized,
+	{
+		if self.replayed.swap(true, Ordering::Relaxed) {
+			return
+		}
+
+		let entries = self.load();
+		if entries.is_empty() {
+			return
+		}
+		let total = entries.len();
+
+		let mut restored = 0;
+		for source in [TransactionSource::Local, TransactionSource::External] {
+			let xts = entries
+				.iter()
+				.filter(|(s, _)| *s == source)
+				.map(|(_, xt)| xt.clone())
+				.collect::<Vec<_>>();
+			if xts.is_empty() {
+				continue
+			}
+
+			match pool.submit_at(at, source, xts).await {
+				Ok(results) => restored += results.iter().filter(|r| r.is_ok()).count(),
+				Err(e) => log::warn!(target: LOG_TARGET, "[journal] Failed to replay: {e}"),
+			}
+		}
+
+		log::info!(
+			target: LOG_TARGET,
+			"[journal] Restored {restored} of {total} transactions at {at:?}, dropped {} invalid or expired",
+			total - restored,
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::testing::TaskExecutor;
+	use substrate_test_runtime::{Block, Extrinsic, ExtrinsicBuilder};
+
+	fn journal(dir: &tempfile::TempDir, limit: usize) -> Journal<Block> {
+		let journal = Journal::new(dir.path().join("txpool_journal"), limit, &TaskExecutor::new());
+		journal.replayed.store(true, Ordering::Relaxed);
+		journal
+	}
+
+	fn xt(i: u8) -> Extrinsic {
+		ExtrinsicBuilder::new_include_data(vec![i]).build()
+	}
+
+	#[test]
+	fn store_and_load_roundtrip() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = journal(&dir, 10);
+		let (xt0, xt1, xt2) = (xt(0), xt(1), xt(2));
+
+		journal
+			.store([
+				(TransactionSource::Local, &xt0),
+				(TransactionSource::InBlock, &xt1),
+				(TransactionSource::External, &xt2),
+			])
+			.unwrap();
+
+		assert_eq!(
+			journal.load(),
+			vec![(TransactionSource::Local, xt0), (TransactionSource::External, xt2)]
+		);
+	}
+
+	#[test]
+	fn store_respects_limit() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = journal(&dir, 2);
+		let xts = (0..5).map(xt).collect::<Vec<_>>();
+
+		journal.store(xts.iter().map(|xt| (TransactionSource::External, xt))).unwrap();
+
+		assert_eq!(journal.load().len(), 2);
+	}
+
+	#[test]
+	fn store_is_noop_before_replay() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal =
+			Journal::<Block>::new(dir.path().join("txpool_journal"), 10, &TaskExecutor::new());
+		let xt0 = xt(0);
+
+		journal.store([(TransactionSource::External, &xt0)]).unwrap();
+
+		assert!(!journal.writer.path.exists());
+	}
+
+	#[test]
+	fn stale_snapshot_is_not_written() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = journal(&dir, 10);
+		let (xt0, xt1) = (xt(0), xt(1));
+
+		let stale = journal.snapshot([(TransactionSource::External, &xt0)]).unwrap();
+		journal.store([(TransactionSource::External, &xt1)]).unwrap();
+		journal.writer.write(stale).unwrap();
+
+		assert_eq!(journal.load(), vec![(TransactionSource::External, xt1)]);
+	}
+
+	#[test]
+	fn store_in_background_works() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = journal(&dir, 10);
+		let xt0 = xt(0);
+
+		journal.store_in_background([(TransactionSource::External, &xt0)]);
+
+		let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+		while journal.load().is_empty() && std::time::Instant::now() < deadline {
+			std::thread::sleep(std::time::Duration::from_millis(10));
+		}
+		assert_eq!(journal.load(), vec![(TransactionSource::External, xt0)]);
+	}
+
+	#[test]
+	fn corrupted_journal_is_ignored() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = journal(&dir, 10);
+
+		fs::write(&journal.writer.path, b"garbage").unwrap();
+
+		assert!(journal.load().is_empty());
+	}
+}
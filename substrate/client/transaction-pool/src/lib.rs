@@ -26,6 +26,7 @@ mod builder;
 mod common;
 mod fork_aware_txpool;
 mod graph;
+mod journal;
 mod single_state_txpool;
 mod transaction_pool_wrapper;
 
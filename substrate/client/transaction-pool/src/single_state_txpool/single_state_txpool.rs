@@ -28,6 +28,7 @@ use crate::{
 		enactment_state::{EnactmentAction, EnactmentState},
 		error,
 		log_xt::log_xt_trace,
+		metrics::PolicyMetricsLink,
 	},
 	graph::{self, base_pool::TimedTransactionSource, EventHandler, ExtrinsicHash, IsValidator},
 	ReadyIteratorFor, LOG_TARGET,
@@ -38,8 +39,8 @@ use parking_lot::Mutex;
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_transaction_pool_api::{
 	error::Error as TxPoolError, ChainEvent, ImportNotificationStream, MaintainedTransactionPool,
-	PoolStatus, TransactionFor, TransactionPool, TransactionSource, TransactionStatusStreamFor,
-	TxHash, TxInvalidityReportMap,
+	PoolStatus, PooledTransactionInfo, PooledTransactionQueue, TransactionFor, TransactionPool,
+	TransactionSource, TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
 };
 use sp_blockchain::{HashAndNumber, TreeRoute};
 use sp_core::traits::SpawnEssentialNamed;
@@ -184,11 +185,10 @@ where
 		best_block_hash: Block::Hash,
 		finalized_hash: Block::Hash,
 	) -> Self {
-		let pool = Arc::new(graph::Pool::new_with_staticly_sized_rotator(
-			options,
-			is_validator,
-			pool_api.clone(),
-		));
+		let pool = Arc::new(
+			graph::Pool::new_with_staticly_sized_rotator(options, is_validator, pool_api.clone())
+				.with_policy_metrics(PolicyMetricsLink::new(prometheus)),
+		);
 		let (revalidation_queue, background_task) = match revalidation_type {
 			RevalidationType::Light =>
 				(revalidation::RevalidationQueue::new(pool_api.clone(), pool.clone()), None),
@@ -404,6 +404,26 @@ where
 	) -> ReadyIteratorFor<PoolApi> {
 		self.ready_at_with_timeout_internal(at, timeout).await
 	}
+
+	/// Returns detailed information about every transaction in the pool.
+	///
+	/// The single state pool holds a single view, built at the recently notified best block.
+	fn inspect(&self) -> Option<Vec<PooledTransactionInfo<TxHash<Self>, Block::Hash>>> {
+		let at = self.enactment_state.lock().recent_best_block();
+		let validated_pool = self.pool.validated_pool();
+		let watched = validated_pool.watched_transactions().into_iter().collect::<HashSet<_>>();
+		let pool = validated_pool.pool.read();
+
+		let ready = pool.ready().map(|tx| (tx, PooledTransactionQueue::Ready));
+		let future = pool
+			.futures()
+			.map(|tx| (Arc::new(tx.duplicate()), PooledTransactionQueue::Future));
+		let transactions = ready
+			.chain(future)
+			.map(|(tx, queue)| tx.pooled_info(watched.contains(&tx.hash), vec![(at, queue)]))
+			.collect();
+		Some(transactions)
+	}
 }
 
 impl<Block, Client> BasicPool<FullChainApi<Client, Block>, Block>
@@ -22,13 +22,15 @@
 use crate::{
 	builder::FullClientTransactionPool,
 	graph::{base_pool::Transaction, ExtrinsicFor, ExtrinsicHash},
-	ChainApi, FullChainApi, ReadyIteratorFor,
+	journal::Journal,
+	ChainApi, FullChainApi, ReadyIteratorFor, LOG_TARGET,
 };
 use async_trait::async_trait;
 use sc_transaction_pool_api::{
-	ChainEvent, ImportNotificationStream, LocalTransactionFor, LocalTransactionPool,
-	MaintainedTransactionPool, PoolStatus, ReadyTransactions, TransactionFor, TransactionPool,
-	TransactionSource, TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
+	ChainEvent, DetailedTransactionStatusStreamFor, ImportNotificationStream, LocalTransactionFor,
+	LocalTransactionPool, MaintainedTransactionPool, PoolStatus, PooledTransactionInfo,
+	ReadyTransactions, TransactionFor, TransactionPool, TransactionSource,
+	TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
 };
 use sp_runtime::traits::Block as BlockT;
 use std::{collections::HashMap, pin::Pin, sync::Arc};
@@ -36,8 +38,10 @@ use std::{collections::HashMap, pin::Pin, sync::Arc};
 /// The wrapper for actual object providing implementation of TransactionPool.
 ///
 /// This wraps actual implementation of the TransactionPool, e.g. fork-aware or single-state.
+/// If enabled, the wrapper also maintains the persistent journal of pending transactions.
 pub struct TransactionPoolWrapper<Block, Client>(
 	pub Box<dyn FullClientTransactionPool<Block, Client>>,
+	pub(crate) Option<Journal<Block>>,
 )
 where
 	Block: BlockT,
@@ -96,6 +100,15 @@ where
 		self.0.submit_and_watch(at, source, xt).await
 	}
 
+	async fn submit_and_watch_detailed(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> Result<Pin<Box<DetailedTransactionStatusStreamFor<Self>>>, Self::Error> {
+		self.0.submit_and_watch_detailed(at, source, xt).await
+	}
+
 	async fn ready_at(
 		&self,
 		at: <Self::Block as BlockT>::Hash,
@@ -119,6 +132,14 @@ where
 		self.0.futures()
 	}
 
+	fn inspect(&self) -> Option<Vec<PooledTransactionInfo<TxHash<Self>, Block::Hash>>> {
+		self.0.inspect()
+	}
+
+	fn pending_transactions(&self) -> Option<Vec<(TransactionSource, Arc<TransactionFor<Self>>)>> {
+		self.0.pending_transactions()
+	}
+
 	fn status(&self) -> PoolStatus {
 		self.0.status()
 	}
@@ -161,7 +182,68 @@ where
 	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
 {
 	async fn maintain(&self, event: ChainEvent<Self::Block>) {
+		let is_finalized = event.is_finalized();
+		let hash = event.hash();
 		self.0.maintain(event).await;
+
+		let Some(journal) = &self.1 else { return };
+		if !journal.is_replayed() {
+			journal.replay(&*self.0, hash).await;
+		} else if !is_finalized {
+			return
+		}
+
+		let transactions = self.journal_transactions();
+		journal.store_in_background(transactions.iter().map(|(source, xt)| (*source, &**xt)));
+	}
+}
+
+impl<Block, Client> TransactionPoolWrapper<Block, Client>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	/// Returns the transactions of the pool to be stored in the journal.
+	///
+	/// These are all the transactions held by the pool if it supports it, e.g. the fork-aware
+	/// pool also stores the transactions that are not valid at the best block. Otherwise, these
+	/// are the ready and future transactions.
+	fn journal_transactions(&self) -> Vec<(TransactionSource, Arc<TransactionFor<Self>>)> {
+		if let Some(transactions) = self.0.pending_transactions() {
+			return transactions
+		}
+
+		let ready = self.0.ready().map(|tx| (tx.source.source, tx.data.clone()));
+		let futures = self.0.futures().into_iter().map(|tx| (tx.source.source, tx.data));
+		ready.chain(futures).collect()
+	}
+}
+
+impl<Block, Client> Drop for TransactionPoolWrapper<Block, Client>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	fn drop(&mut self) {
+		if let Some(journal) = &self.1 {
+			let transactions = self.journal_transactions();
+			if let Err(e) = journal.store(transactions.iter().map(|(source, xt)| (*source, &**xt)))
+			{
+				log::warn!(target: LOG_TARGET, "[journal] Failed to store transactions: {e}");
+			}
+		}
 	}
 }
 
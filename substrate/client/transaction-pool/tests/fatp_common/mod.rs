@@ -18,7 +18,7 @@
 
 //! Tests for fork-aware transaction pool.
 
-use sc_transaction_pool::{ChainApi, PoolLimit};
+use sc_transaction_pool::{ChainApi, Options, PoolLimit};
 use sc_transaction_pool_api::ChainEvent;
 use sp_runtime::transaction_validity::TransactionSource;
 use std::sync::Arc;
@@ -71,6 +71,7 @@ pub struct TestPoolBuilder {
 	future_limits: sc_transaction_pool::PoolLimit,
 	mempool_max_transactions_count: usize,
 	finality_timeout_threshold: Option<usize>,
+	sender_limit: Option<usize>,
 }
 
 impl Default for TestPoolBuilder {
@@ -82,6 +83,7 @@ impl Default for TestPoolBuilder {
 			future_limits: PoolLimit { count: 512, total_bytes: 1 * 1024 * 1024 },
 			mempool_max_transactions_count: usize::MAX,
 			finality_timeout_threshold: None,
+			sender_limit: None,
 		}
 	}
 }
@@ -131,6 +133,16 @@ impl TestPoolBuilder {
 		self
 	}
 
+	/// Sets the per-sender limit of views and mempool.
+	///
+	/// Transfers of the test runtime provide `[sender, nonce]` tags, so the sender is identified
+	/// by the first byte of the tag.
+	pub fn with_sender_limit(mut self, sender_limit: usize) -> Self {
+		self.sender_limit = Some(sender_limit);
+		self.use_default_limits = false;
+		self
+	}
+
 	pub fn build(
 		self,
 	) -> (ForkAwareTxPool<TestApi, Block>, Arc<TestApi>, futures::executor::ThreadPool) {
@@ -154,12 +166,18 @@ impl TestPoolBuilder {
 				self.finality_timeout_threshold,
 			)
 		} else {
-			ForkAwareTxPool::new_test_with_limits(
+			let mut options = Options {
+				ready: self.ready_limits,
+				future: self.future_limits,
+				..Default::default()
+			};
+			options.policy.sender_limit = self.sender_limit;
+			options.policy.sender_tag_prefix_len = 1;
+			ForkAwareTxPool::new_test_with_options(
 				api.clone(),
 				genesis_hash,
 				genesis_hash,
-				self.ready_limits,
-				self.future_limits,
+				options,
 				self.mempool_max_transactions_count,
 				self.finality_timeout_threshold,
 			)
@@ -829,3 +829,46 @@ fn fatp_limits_watcher_future_transactions_are_droped_when_view_is_dropped() {
 		assert_eq!(x_status, vec![TransactionStatus::Future]);
 	}
 }
+
+#[test]
+fn fatp_limits_sender_limit_is_applied_to_mempool() {
+	sp_tracing::try_init_simple();
+
+	let builder = TestPoolBuilder::new();
+	let (pool, api, _) = builder.with_sender_limit(2).build();
+
+	let header01 = api.push_block(1, vec![], true);
+	let event = new_best_block_event(&pool, None, header01.hash());
+	block_on(pool.maintain(event));
+
+	let xt0 = uxt(Alice, 200);
+	let xt1 = uxt(Alice, 201);
+	let xt2 = uxt(Alice, 202);
+
+	block_on(pool.submit_one(invalid_hash(), SOURCE, xt0.clone())).unwrap();
+	block_on(pool.submit_one(invalid_hash(), SOURCE, xt1.clone())).unwrap();
+	assert_pool_status!(header01.hash(), &pool, 2, 0);
+
+	// included, but not yet finalized transactions are still kept in the mempool
+	let header02 = api.push_block(2, vec![xt0, xt1], true);
+	api.set_nonce(header02.hash(), Alice.into(), 202);
+	let event = new_best_block_event(&pool, Some(header01.hash()), header02.hash());
+	block_on(pool.maintain(event));
+	assert_pool_status!(header02.hash(), &pool, 0, 0);
+	assert_eq!(pool.mempool_len(), (2, 0));
+
+	// the view accepts the transaction, but the sender already has two transactions in mempool
+	let result = block_on(pool.submit_one(invalid_hash(), SOURCE, xt2.clone()));
+	assert!(matches!(result.unwrap_err().0, TxPoolError::ImmediatelyDropped));
+	assert_pool_status!(header02.hash(), &pool, 0, 0);
+	assert_eq!(pool.mempool_len(), (2, 0));
+
+	// finalization removes included transactions from the mempool
+	let event = finalized_block_event(&pool, header01.hash(), header02.hash());
+	block_on(pool.maintain(event));
+	assert_eq!(pool.mempool_len(), (0, 0));
+
+	block_on(pool.submit_one(invalid_hash(), SOURCE, xt2)).unwrap();
+	assert_pool_status!(header02.hash(), &pool, 1, 0);
+	assert_eq!(pool.mempool_len(), (1, 0));
+}
@@ -1687,3 +1687,36 @@ fn best_block_after_finalization_does_not_retract() {
 		assert_eq!(stream.next(), None);
 	}
 }
+
+#[test]
+fn journal_restores_transactions_after_restart() {
+	sp_tracing::try_init_simple();
+	let dir = tempfile::tempdir().unwrap();
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let best_hash = client.info().best_hash;
+	let options = TransactionPoolOptions::default()
+		.with_journal_limit(16)
+		.with_journal_path(dir.path().join("txpool_journal"));
+	let build_pool = || {
+		Builder::new(sp_core::testing::TaskExecutor::new(), client.clone(), true.into())
+			.with_options(options.clone())
+			.build()
+	};
+	let best_block_event = || ChainEvent::NewBestBlock { hash: best_hash, tree_route: None };
+
+	let pool = build_pool();
+	block_on(pool.maintain(best_block_event()));
+	block_on(pool.submit_one(best_hash, SOURCE, uxt(Alice, 0))).expect("1. Imported");
+	block_on(pool.submit_one(best_hash, SOURCE, uxt(Bob, 0))).expect("2. Imported");
+	assert_eq!(pool.status().ready, 2);
+
+	// Dropping the pool persists the pending transactions.
+	drop(pool);
+
+	let pool = build_pool();
+	assert_eq!(pool.status().ready, 0);
+
+	// The journal is replayed on the first maintenance.
+	block_on(pool.maintain(best_block_event()));
+	assert_eq!(pool.status().ready, 2);
+}
@@ -0,0 +1,175 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Manual Seal Dev Pallet
+//!
+//! Runtime side of the development RPCs of `sc-consensus-manual-seal`.
+//!
+//! The manual seal authorship task passes the storage changes queued with `engine_setStorage`
+//! and the calls queued with `engine_impersonate` to the runtime as inherent data. This pallet
+//! turns them into the [`Call::apply`] inherent, which writes the storage changes and then
+//! dispatches every call with the signed origin of the impersonated account.
+//!
+//! Blocks without queued changes don't contain the inherent.
+//!
+//! **The block author can write arbitrary storage and dispatch calls on behalf of any account,
+//! only add this pallet to development runtimes.**
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec::Vec};
+use sp_inherents::InherentIdentifier;
+
+pub use pallet::*;
+
+const LOG_TARGET: &str = "runtime::manual-seal-dev";
+
+/// The identifier of the storage changes inherent data, encoded as [`StorageChanges`].
+///
+/// Matches `sc_consensus_manual_seal::DEV_STORAGE_INHERENT_IDENTIFIER`.
+pub const STORAGE_INHERENT_IDENTIFIER: InherentIdentifier = *b"devstore";
+
+/// The identifier of the impersonated calls inherent data, encoded as [`ImpersonatedCalls`].
+///
+/// Matches `sc_consensus_manual_seal::DEV_IMPERSONATE_INHERENT_IDENTIFIER`.
+pub const IMPERSONATE_INHERENT_IDENTIFIER: InherentIdentifier = *b"devimper";
+
+/// Storage changes to write, `None` values remove the key.
+pub type StorageChanges = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+/// Calls to dispatch on behalf of an account, each one the encoded `(AccountId, RuntimeCall)`.
+pub type ImpersonatedCalls = Vec<Vec<u8>>;
+
+/// The maximum nesting depth of an impersonated call, same as for extrinsics.
+const MAX_CALL_DEPTH: u32 = 256;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use codec::DecodeLimit;
+	use frame_support::{dispatch::GetDispatchInfo, pallet_prelude::*};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Dispatchable;
+
+	/// A call dispatched on behalf of an account.
+	type ImpersonatedCall<T> =
+		(<T as frame_system::Config>::AccountId, <T as frame_system::Config>::RuntimeCall);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config<RuntimeEvent: From<Event<Self>>> {}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A call was dispatched on behalf of `who`.
+		Impersonated { who: T::AccountId, result: DispatchResult },
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Write `storage` and dispatch `calls` with the signed origin of their account.
+		///
+		/// Only included by the block author as an inherent.
+		#[pallet::call_index(0)]
+		#[pallet::weight((
+			calls.iter().fold(Weight::zero(), |weight, (_, call)| {
+				weight.saturating_add(call.get_dispatch_info().call_weight)
+			}),
+			DispatchClass::Mandatory,
+		))]
+		pub fn apply(
+			origin: OriginFor<T>,
+			storage: StorageChanges,
+			calls: Vec<(T::AccountId, Box<<T as frame_system::Config>::RuntimeCall>)>,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+
+			for (key, value) in storage {
+				match value {
+					Some(value) => sp_io::storage::set(&key, &value),
+					None => sp_io::storage::clear(&key),
+				}
+			}
+
+			for (who, call) in calls {
+				let result = call.dispatch(frame_system::RawOrigin::Signed(who.clone()).into());
+				Self::deposit_event(Event::Impersonated {
+					who,
+					result: result.map(|_| ()).map_err(|e| e.error),
+				});
+			}
+
+			Ok(())
+		}
+	}
+
+	#[pallet::inherent]
+	impl<T: Config> ProvideInherent for Pallet<T> {
+		type Call = Call<T>;
+		type Error = sp_inherents::MakeFatalError<()>;
+		const INHERENT_IDENTIFIER: InherentIdentifier = STORAGE_INHERENT_IDENTIFIER;
+
+		fn create_inherent(data: &InherentData) -> Option<Self::Call> {
+			let storage = data
+				.get_data::<StorageChanges>(&STORAGE_INHERENT_IDENTIFIER)
+				.inspect_err(|e| log::warn!(target: LOG_TARGET, "Invalid storage changes: {e:?}"))
+				.ok()
+				.flatten()
+				.unwrap_or_default();
+			let calls = data
+				.get_data::<ImpersonatedCalls>(&IMPERSONATE_INHERENT_IDENTIFIER)
+				.inspect_err(
+					|e| log::warn!(target: LOG_TARGET, "Invalid impersonated calls: {e:?}"),
+				)
+				.ok()
+				.flatten()
+				.unwrap_or_default()
+				.into_iter()
+				.filter_map(|encoded| {
+					ImpersonatedCall::<T>::decode_all_with_depth_limit(
+						MAX_CALL_DEPTH,
+						&mut &encoded[..],
+					)
+					.inspect_err(
+						|e| log::warn!(target: LOG_TARGET, "Skipping undecodable call: {e:?}"),
+					)
+					.ok()
+				})
+				.map(|(who, call)| (who, Box::new(call)))
+				.collect::<Vec<_>>();
+
+			if storage.is_empty() && calls.is_empty() {
+				return None
+			}
+			Some(Call::apply { storage, calls })
+		}
+
+		fn is_inherent(call: &Self::Call) -> bool {
+			matches!(call, Call::apply { .. })
+		}
+	}
+}
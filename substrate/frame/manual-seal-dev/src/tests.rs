@@ -0,0 +1,89 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the manual seal dev pallet.
+
+use super::*;
+use crate::mock::*;
+use codec::Encode;
+use frame_support::{assert_ok, inherent::ProvideInherent};
+use sp_inherents::InherentData;
+use sp_runtime::traits::Dispatchable;
+
+fn inherent_data(storage: StorageChanges, calls: ImpersonatedCalls) -> InherentData {
+	let mut data = InherentData::new();
+	data.put_data(STORAGE_INHERENT_IDENTIFIER, &storage).unwrap();
+	data.put_data(IMPERSONATE_INHERENT_IDENTIFIER, &calls).unwrap();
+	data
+}
+
+fn apply(data: &InherentData) {
+	let call = ManualSealDev::create_inherent(data).expect("inherent is created");
+	assert!(ManualSealDev::is_inherent(&call));
+	assert_ok!(RuntimeCall::from(call).dispatch(RuntimeOrigin::none()));
+}
+
+#[test]
+fn no_inherent_without_changes() {
+	new_test_ext().execute_with(|| {
+		assert!(ManualSealDev::create_inherent(&InherentData::new()).is_none());
+		assert!(ManualSealDev::create_inherent(&inherent_data(vec![], vec![])).is_none());
+	});
+}
+
+#[test]
+fn storage_changes_are_written() {
+	new_test_ext().execute_with(|| {
+		sp_io::storage::set(b"removed", b"value");
+
+		apply(&inherent_data(
+			vec![(b"key".to_vec(), Some(b"value".to_vec())), (b"removed".to_vec(), None)],
+			vec![],
+		));
+
+		assert_eq!(sp_io::storage::get(b"key").as_deref(), Some(&b"value"[..]));
+		assert_eq!(sp_io::storage::get(b"removed"), None);
+	});
+}
+
+#[test]
+fn impersonated_calls_are_dispatched_as_signed() {
+	new_test_ext().execute_with(|| {
+		let remark = RuntimeCall::System(frame_system::Call::remark_with_event {
+			remark: b"hello".to_vec(),
+		});
+
+		apply(&inherent_data(vec![], vec![(42u64, remark).encode(), b"garbage".to_vec()]));
+
+		System::assert_has_event(
+			frame_system::Event::<Test>::Remarked {
+				sender: 42,
+				hash: sp_io::hashing::blake2_256(b"hello").into(),
+			}
+			.into(),
+		);
+		System::assert_last_event(Event::<Test>::Impersonated { who: 42, result: Ok(()) }.into());
+	});
+}
+
+#[test]
+fn apply_requires_none_origin() {
+	new_test_ext().execute_with(|| {
+		let call = Call::<Test>::apply { storage: vec![(b"key".to_vec(), None)], calls: vec![] };
+		assert!(RuntimeCall::from(call).dispatch(RuntimeOrigin::signed(1)).is_err());
+	});
+}
@@ -20,5 +20,11 @@ pub use debug_apis::*;
 mod execution_apis;
 pub use execution_apis::*;
 
+mod filter_apis;
+pub use filter_apis::*;
+
 mod health_api;
 pub use health_api::*;
+
+mod pubsub_apis;
+pub use pubsub_apis::*;
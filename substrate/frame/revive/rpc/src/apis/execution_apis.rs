@@ -32,26 +32,50 @@ pub trait EthRpc {
 	async fn block_number(&self) -> RpcResult<U256>;
 
 	/// Executes a new message call immediately without creating a transaction on the block chain.
+	///
+	/// The `code` of a state override must be a PolkaVM program, EVM bytecode is rejected.
 	#[method(name = "eth_call")]
 	async fn call(
 		&self,
 		transaction: GenericTransaction,
 		block: Option<BlockNumberOrTagOrHash>,
+		state_overrides: Option<StateOverrideSet>,
 	) -> RpcResult<Bytes>;
 
 	/// Returns the chain ID of the current network.
 	#[method(name = "eth_chainId")]
 	async fn chain_id(&self) -> RpcResult<U256>;
 
+	/// Generates an access list for a transaction, along with the gas it uses when executed
+	/// with that access list.
+	#[method(name = "eth_createAccessList")]
+	async fn create_access_list(
+		&self,
+		transaction: GenericTransaction,
+		block: Option<BlockNumberOrTag>,
+	) -> RpcResult<AccessListResult>;
+
 	/// Generates and returns an estimate of how much gas is necessary to allow the transaction to
 	/// complete.
+	///
+	/// The `code` of a state override must be a PolkaVM program, EVM bytecode is rejected.
 	#[method(name = "eth_estimateGas")]
 	async fn estimate_gas(
 		&self,
 		transaction: GenericTransaction,
 		block: Option<BlockNumberOrTag>,
+		state_overrides: Option<StateOverrideSet>,
 	) -> RpcResult<U256>;
 
+	/// Returns the collection of historical gas information.
+	#[method(name = "eth_feeHistory")]
+	async fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumberOrTag,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> RpcResult<FeeHistoryResult>;
+
 	/// Returns the current price per gas in wei.
 	#[method(name = "eth_gasPrice")]
 	async fn gas_price(&self) -> RpcResult<U256>;
@@ -77,6 +101,13 @@ pub trait EthRpc {
 		hydrated_transactions: bool,
 	) -> RpcResult<Option<Block>>;
 
+	/// Returns the receipts of a block by number or hash.
+	#[method(name = "eth_getBlockReceipts")]
+	async fn get_block_receipts(
+		&self,
+		block: BlockNumberOrTagOrHash,
+	) -> RpcResult<Option<Vec<ReceiptInfo>>>;
+
 	/// Returns the number of transactions in a block from a block matching the given block hash.
 	#[method(name = "eth_getBlockTransactionCountByHash")]
 	async fn get_block_transaction_count_by_hash(
@@ -0,0 +1,137 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Ethereum filter JSON-RPC methods.
+
+use crate::*;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, ConnectionId, Extensions};
+use sc_rpc_api::ConnectionClosed;
+
+/// Ethereum filter JSON-RPC apis.
+///
+/// Filters installed over WebSocket are bound to their connection: they can only be polled and
+/// uninstalled by that connection, and are uninstalled when it is closed. Filters installed over
+/// HTTP can be polled by any request, and are only uninstalled when they expire.
+#[rpc(server, client)]
+pub trait EthFilterRpc {
+	/// Install a filter based on the given filter criteria, to notify when new matching logs are
+	/// available.
+	#[method(name = "eth_newFilter", with_extensions)]
+	async fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
+
+	/// Install a filter to notify when a new block arrives.
+	#[method(name = "eth_newBlockFilter", with_extensions)]
+	async fn new_block_filter(&self) -> RpcResult<U256>;
+
+	/// Install a filter to notify when new pending transactions arrive.
+	///
+	/// The transaction pool of the node is polled periodically, so transactions that are
+	/// included in a block between two polls are not reported.
+	#[method(name = "eth_newPendingTransactionFilter", with_extensions)]
+	async fn new_pending_transaction_filter(&self) -> RpcResult<U256>;
+
+	/// Poll the filter with the given id, and return the changes since the last poll.
+	#[method(name = "eth_getFilterChanges", with_extensions)]
+	async fn get_filter_changes(&self, filter_id: U256) -> RpcResult<FilterResults>;
+
+	/// Return all the logs matching the log filter with the given id.
+	#[method(name = "eth_getFilterLogs", with_extensions)]
+	async fn get_filter_logs(&self, filter_id: U256) -> RpcResult<FilterResults>;
+
+	/// Uninstall the filter with the given id.
+	#[method(name = "eth_uninstallFilter", with_extensions)]
+	async fn uninstall_filter(&self, filter_id: U256) -> RpcResult<bool>;
+}
+
+pub struct EthFilterRpcServerImpl {
+	client: client::Client,
+	filters: FilterManager,
+}
+
+impl EthFilterRpcServerImpl {
+	pub fn new(client: client::Client, filters: FilterManager) -> Self {
+		Self { client, filters }
+	}
+
+	/// Install a filter, bound to the connection of the request if it is a WebSocket.
+	async fn install(&self, ext: &Extensions, kind: FilterKind) -> RpcResult<U256> {
+		let connection_id = connection_id(ext);
+		// The connection closed notification is only set for WebSocket connections.
+		let connection = ext.get::<ConnectionClosed>().map(|closed| (connection_id, closed));
+
+		let id = self.filters.install(connection, kind).await?;
+		log::debug!(target: LOG_TARGET, "Installed filter {id:?} for connection {connection_id:?}");
+		Ok(id)
+	}
+}
+
+/// Return the id of the connection of the request.
+fn connection_id(ext: &Extensions) -> ConnectionId {
+	ext.get::<ConnectionId>()
+		.copied()
+		.expect("ConnectionId is always set by jsonrpsee; qed")
+}
+
+#[async_trait]
+impl EthFilterRpcServer for EthFilterRpcServerImpl {
+	async fn new_filter(&self, ext: &Extensions, filter: Filter) -> RpcResult<U256> {
+		if filter.block_hash.is_some() {
+			return Err(EthRpcError::BlockHashFilterNotSupported.into());
+		}
+
+		let next_block = match filter.from_block {
+			Some(BlockNumberOrTag::U256(from_block)) => from_block,
+			_ => U256::from(self.client.block_number().await?) + 1,
+		};
+
+		self.install(ext, FilterKind::Logs { filter, next_block }).await
+	}
+
+	async fn new_block_filter(&self, ext: &Extensions) -> RpcResult<U256> {
+		self.install(ext, FilterKind::Blocks(vec![])).await
+	}
+
+	async fn new_pending_transaction_filter(&self, ext: &Extensions) -> RpcResult<U256> {
+		self.install(ext, FilterKind::PendingTransactions(vec![])).await
+	}
+
+	async fn get_filter_changes(
+		&self,
+		ext: &Extensions,
+		filter_id: U256,
+	) -> RpcResult<FilterResults> {
+		let latest_block = self.client.block_number().await?.into();
+		match self.filters.changes(connection_id(ext), &filter_id, latest_block).await? {
+			FilterChanges::Hashes(hashes) => Ok(FilterResults::Hashes(hashes)),
+			FilterChanges::Logs(None) => Ok(FilterResults::Logs(vec![])),
+			FilterChanges::Logs(Some(filter)) => {
+				let logs = self.client.logs(Some(filter)).await?;
+				Ok(FilterResults::Logs(logs))
+			},
+		}
+	}
+
+	async fn get_filter_logs(&self, ext: &Extensions, filter_id: U256) -> RpcResult<FilterResults> {
+		let latest_block = self.client.block_number().await?.into();
+		let filter = self.filters.log_filter(connection_id(ext), &filter_id, latest_block).await?;
+		let logs = self.client.logs(Some(filter)).await?;
+		Ok(FilterResults::Logs(logs))
+	}
+
+	async fn uninstall_filter(&self, ext: &Extensions, filter_id: U256) -> RpcResult<bool> {
+		Ok(self.filters.uninstall(connection_id(ext), &filter_id).await)
+	}
+}
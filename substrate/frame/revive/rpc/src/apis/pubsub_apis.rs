@@ -0,0 +1,160 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Ethereum publish/subscribe JSON-RPC methods.
+
+use crate::{client::BlockNotification, *};
+use jsonrpsee::{
+	core::SubscriptionResult, proc_macros::rpc, PendingSubscriptionSink, SubscriptionMessage,
+	SubscriptionSink,
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+#[rpc(server, client)]
+pub trait EthPubSubRpc {
+	/// Subscribe to new block headers, logs matching the given filter, or pending transactions.
+	///
+	/// Pending transactions are read from the transaction pool of the node, which is polled
+	/// periodically, so transactions that are included in a block between two polls are not
+	/// reported.
+	///
+	/// ## References
+	///
+	/// - <https://geth.ethereum.org/docs/interacting-with-geth/rpc/pubsub>
+	#[subscription(
+		name = "eth_subscribe" => "eth_subscription",
+		unsubscribe = "eth_unsubscribe",
+		item = SubscriptionItem
+	)]
+	async fn subscribe(&self, kind: SubscriptionKind, filter: Option<Filter>)
+		-> SubscriptionResult;
+}
+
+pub struct EthPubSubRpcServerImpl {
+	client: client::Client,
+}
+
+impl EthPubSubRpcServerImpl {
+	pub fn new(client: client::Client) -> Self {
+		Self { client }
+	}
+}
+
+#[async_trait]
+impl EthPubSubRpcServer for EthPubSubRpcServerImpl {
+	async fn subscribe(
+		&self,
+		pending: PendingSubscriptionSink,
+		kind: SubscriptionKind,
+		filter: Option<Filter>,
+	) -> SubscriptionResult {
+		log::debug!(target: LOG_TARGET, "eth_subscribe: {kind:?} filter: {filter:?}");
+		if filter.is_some() && kind != SubscriptionKind::Logs {
+			let err = EthRpcError::InvalidSubscriptionParams(kind);
+			pending.reject(ErrorObjectOwned::from(err)).await;
+			return Ok(());
+		}
+
+		let sink = pending.accept().await?;
+		match kind {
+			SubscriptionKind::NewHeads => {
+				let client = &self.client;
+				pipe_notifications(
+					sink,
+					client.subscribe_block_notifications(),
+					|notification| async move {
+						match notification {
+							BlockNotification::NewBlock { block, .. } => {
+								let header = client.evm_block(block, false).await;
+								vec![SubscriptionItem::Header(Box::new(header))]
+							},
+							BlockNotification::Retracted { .. } => vec![],
+						}
+					},
+				)
+				.await
+			},
+			SubscriptionKind::Logs => {
+				let filter = filter.unwrap_or_default();
+				let filter = &filter;
+				pipe_notifications(
+					sink,
+					self.client.subscribe_block_notifications(),
+					|notification| {
+						let logs = match notification {
+							BlockNotification::NewBlock { logs, .. } |
+							BlockNotification::Retracted { logs, .. } => logs,
+						};
+
+						let items = logs
+							.into_iter()
+							.filter(|log| filter.matches(log))
+							.map(SubscriptionItem::Log)
+							.collect();
+						async move { items }
+					},
+				)
+				.await
+			},
+			SubscriptionKind::NewPendingTransactions =>
+				pipe_notifications(
+					sink,
+					self.client.subscribe_pending_transactions(),
+					|hash| async move { vec![SubscriptionItem::TransactionHash(hash)] },
+				)
+				.await,
+		}
+	}
+}
+
+/// Forward the items produced by `to_items` for each received notification to the subscriber,
+/// until either the subscription or the notification channel is closed.
+///
+/// The subscription is terminated with an error if the subscriber can't keep up with the
+/// notifications, so that it does not silently miss some of them.
+async fn pipe_notifications<T, F, Fut>(
+	sink: SubscriptionSink,
+	mut receiver: broadcast::Receiver<T>,
+	to_items: F,
+) -> SubscriptionResult
+where
+	T: Clone,
+	F: Fn(T) -> Fut,
+	Fut: std::future::Future<Output = Vec<SubscriptionItem>>,
+{
+	loop {
+		let notification = tokio::select! {
+			_ = sink.closed() => return Ok(()),
+			notification = receiver.recv() => notification,
+		};
+
+		let notification = match notification {
+			Ok(notification) => notification,
+			Err(RecvError::Closed) => return Ok(()),
+			Err(RecvError::Lagged(skipped)) => {
+				log::debug!(target: LOG_TARGET, "Subscription {:?} lagged behind by {skipped} notifications", sink.subscription_id());
+				return Err(format!("Subscription lagged behind by {skipped} notifications").into());
+			},
+		};
+
+		for item in to_items(notification).await {
+			let message = SubscriptionMessage::from_json(&item)?;
+			if sink.send(message).await.is_err() {
+				return Ok(());
+			}
+		}
+	}
+}
@@ -23,7 +23,9 @@ use crate::{
 use jsonrpsee::core::async_trait;
 use sp_core::H256;
 use std::{
+	cmp::Ordering,
 	collections::{HashMap, VecDeque},
+	future::Future,
 	sync::Arc,
 };
 use subxt::{backend::legacy::LegacyRpcMethods, OnlineClient};
@@ -33,11 +35,18 @@ use tokio::sync::RwLock;
 #[async_trait]
 pub trait BlockInfoProvider: Send + Sync {
 	/// Cache a new block and return the pruned block hash.
-	async fn cache_block(&self, block: SubstrateBlock) -> Option<H256>;
+	async fn cache_block(&self, block: Arc<SubstrateBlock>) -> Option<H256>;
+
+	/// Mark a cached block as no longer part of the best chain, so that it is not returned
+	/// anymore when querying blocks by number.
+	async fn retract_block(&self, hash: &H256);
 
 	/// Return the latest ingested block.
 	async fn latest_block(&self) -> Option<Arc<SubstrateBlock>>;
 
+	/// Return the cached blocks of the best chain, ordered by number.
+	async fn cached_blocks(&self) -> Vec<Arc<SubstrateBlock>>;
+
 	/// Return the latest block number
 	async fn latest_block_number(&self) -> Option<SubstrateBlockNumber> {
 		return self.latest_block().await.map(|block| block.number());
@@ -78,14 +87,25 @@ impl BlockInfoProviderImpl {
 
 #[async_trait]
 impl BlockInfoProvider for BlockInfoProviderImpl {
-	async fn cache_block(&self, block: SubstrateBlock) -> Option<H256> {
+	async fn cache_block(&self, block: Arc<SubstrateBlock>) -> Option<H256> {
 		self.cache.write().await.insert(block)
 	}
 
+	async fn retract_block(&self, hash: &H256) {
+		self.cache.write().await.retract(hash)
+	}
+
 	async fn latest_block(&self) -> Option<Arc<SubstrateBlock>> {
 		self.cache.read().await.buffer.back().cloned()
 	}
 
+	async fn cached_blocks(&self) -> Vec<Arc<SubstrateBlock>> {
+		let mut blocks =
+			self.cache.read().await.blocks_by_number.values().cloned().collect::<Vec<_>>();
+		blocks.sort_by_key(|block| block.number());
+		blocks
+	}
+
 	async fn block_by_number(
 		&self,
 		block_number: SubstrateBlockNumber,
@@ -133,11 +153,13 @@ struct BlockCache<Block> {
 /// Provides information about a block,
 /// This is an abstratction on top of [`SubstrateBlock`] used to test the [`BlockCache`].
 /// Can be removed once https://github.com/paritytech/subxt/issues/1883 is fixed.
-trait BlockInfo {
+pub(crate) trait BlockInfo {
 	/// Returns the block hash.
 	fn hash(&self) -> H256;
 	/// Returns the block number.
 	fn number(&self) -> SubstrateBlockNumber;
+	/// Returns the hash of the parent block.
+	fn parent_hash(&self) -> H256;
 }
 
 impl BlockInfo for SubstrateBlock {
@@ -147,6 +169,110 @@ impl BlockInfo for SubstrateBlock {
 	fn number(&self) -> u32 {
 		SubstrateBlock::number(self)
 	}
+	fn parent_hash(&self) -> H256 {
+		self.header().parent_hash
+	}
+}
+
+/// The route from a previous best block to a new one, going through their common ancestor.
+pub(crate) struct TreeRoute<B> {
+	/// The blocks removed from the best chain, starting with the most recent one.
+	pub retracted: Vec<Arc<B>>,
+	/// The blocks added to the best chain, starting with the oldest one.
+	pub enacted: Vec<Arc<B>>,
+}
+
+/// Compute the [`TreeRoute`] from the `from` block to the `to` block, using `parent` to fetch the
+/// parent of a block by hash.
+///
+/// Returns `None` if the common ancestor could not be found.
+pub(crate) async fn tree_route<B, F, Fut>(
+	from: Arc<B>,
+	to: Arc<B>,
+	parent: F,
+) -> Option<TreeRoute<B>>
+where
+	B: BlockInfo,
+	F: Fn(H256) -> Fut,
+	Fut: Future<Output = Option<Arc<B>>>,
+{
+	let mut retracted = Vec::new();
+	let mut enacted = Vec::new();
+	let (mut old, mut new) = (from, to);
+
+	// Walk back both branches until we reach their common ancestor.
+	loop {
+		let (next_old, next_new) = match old.number().cmp(&new.number()) {
+			Ordering::Less => {
+				let next_new = parent(new.parent_hash()).await?;
+				enacted.push(new);
+				(old, next_new)
+			},
+			Ordering::Greater => {
+				let next_old = parent(old.parent_hash()).await?;
+				retracted.push(old);
+				(next_old, new)
+			},
+			Ordering::Equal if old.hash() == new.hash() => break,
+			Ordering::Equal => {
+				let next_old = parent(old.parent_hash()).await?;
+				let next_new = parent(new.parent_hash()).await?;
+				retracted.push(old);
+				enacted.push(new);
+				(next_old, next_new)
+			},
+		};
+		old = next_old;
+		new = next_new;
+	}
+
+	enacted.reverse();
+	Some(TreeRoute { retracted, enacted })
+}
+
+/// Compute the [`TreeRoute`] from the `cached` best chain, ordered by number, to the block
+/// `to_number` of the canonical chain, using `canonical` to fetch a canonical block by number.
+///
+/// This is used when [`tree_route`] can't find the common ancestor, e.g. because the parent of a
+/// block is not available anymore. The cached blocks are compared with the canonical chain from
+/// the highest one down to the last height where they agree: the cached blocks above that height
+/// are retracted, and the canonical blocks above it are enacted. When no cached block is
+/// canonical, they are all retracted.
+pub(crate) async fn resync_route<B, F, Fut>(
+	cached: Vec<Arc<B>>,
+	to_number: SubstrateBlockNumber,
+	canonical: F,
+) -> TreeRoute<B>
+where
+	B: BlockInfo,
+	F: Fn(SubstrateBlockNumber) -> Fut,
+	Fut: Future<Output = Option<Arc<B>>>,
+{
+	let mut retracted = Vec::new();
+	let mut common_number = None;
+	for block in cached.into_iter().rev() {
+		if block.number() <= to_number &&
+			canonical(block.number()).await.is_some_and(|c| c.hash() == block.hash())
+		{
+			common_number = Some(block.number());
+			break;
+		}
+		retracted.push(block);
+	}
+
+	let first_number = match (common_number, retracted.last()) {
+		(Some(number), _) => number + 1,
+		(None, Some(oldest)) => oldest.number(),
+		(None, None) => return TreeRoute { retracted, enacted: Vec::new() },
+	};
+
+	let mut enacted = Vec::new();
+	for number in first_number..=to_number {
+		let Some(block) = canonical(number).await else { break };
+		enacted.push(block);
+	}
+
+	TreeRoute { retracted, enacted }
 }
 
 impl<B: BlockInfo> BlockCache<B> {
@@ -161,23 +287,46 @@ impl<B: BlockInfo> BlockCache<B> {
 	}
 
 	/// Insert an entry into the cache, and prune the oldest entry if the cache is full.
-	pub fn insert(&mut self, block: B) -> Option<H256> {
+	///
+	/// A block that is already cached, e.g. a retracted block that is enacted again, is moved to
+	/// the back of the buffer and indexed by number again.
+	pub fn insert(&mut self, block: impl Into<Arc<B>>) -> Option<H256> {
+		let block = block.into();
+		if self.blocks_by_hash.contains_key(&block.hash()) {
+			self.buffer.retain(|cached| cached.hash() != block.hash());
+		}
+
 		let mut pruned_block_hash = None;
 		if self.buffer.len() >= self.max_cache_size {
 			if let Some(block) = self.buffer.pop_front() {
 				let hash = block.hash();
 				self.blocks_by_hash.remove(&hash);
-				self.blocks_by_number.remove(&block.number());
+				let number = block.number();
+				if self.blocks_by_number.get(&number).is_some_and(|block| block.hash() == hash) {
+					self.blocks_by_number.remove(&number);
+				}
 				pruned_block_hash = Some(hash);
 			}
 		}
 
-		let block = Arc::new(block);
 		self.buffer.push_back(block.clone());
 		self.blocks_by_number.insert(block.number(), block.clone());
 		self.blocks_by_hash.insert(block.hash(), block);
 		pruned_block_hash
 	}
+
+	/// Remove a retracted block from the blocks indexed by number.
+	///
+	/// The block is kept in the buffer, and can still be fetched by hash until it is pruned.
+	pub fn retract(&mut self, hash: &H256) {
+		let Some(number) = self.blocks_by_hash.get(hash).map(|block| block.number()) else {
+			return;
+		};
+
+		if self.blocks_by_number.get(&number).is_some_and(|block| block.hash() == *hash) {
+			self.blocks_by_number.remove(&number);
+		}
+	}
 }
 
 #[cfg(test)]
@@ -187,6 +336,17 @@ pub mod test {
 	struct MockBlock {
 		block_number: SubstrateBlockNumber,
 		block_hash: H256,
+		parent_hash: H256,
+	}
+
+	impl MockBlock {
+		fn new(block_number: SubstrateBlockNumber, block_hash: u8, parent_hash: u8) -> Self {
+			Self {
+				block_number,
+				block_hash: H256::from([block_hash; 32]),
+				parent_hash: H256::from([parent_hash; 32]),
+			}
+		}
 	}
 
 	impl BlockInfo for MockBlock {
@@ -197,19 +357,23 @@ pub mod test {
 		fn number(&self) -> u32 {
 			self.block_number
 		}
+
+		fn parent_hash(&self) -> H256 {
+			self.parent_hash
+		}
 	}
 
 	#[test]
 	fn cache_insert_works() {
 		let mut cache = BlockCache::<MockBlock>::new(2);
 
-		let pruned = cache.insert(MockBlock { block_number: 1, block_hash: H256::from([1; 32]) });
+		let pruned = cache.insert(MockBlock::new(1, 1, 0));
 		assert_eq!(pruned, None);
 
-		let pruned = cache.insert(MockBlock { block_number: 2, block_hash: H256::from([2; 32]) });
+		let pruned = cache.insert(MockBlock::new(2, 2, 1));
 		assert_eq!(pruned, None);
 
-		let pruned = cache.insert(MockBlock { block_number: 3, block_hash: H256::from([3; 32]) });
+		let pruned = cache.insert(MockBlock::new(3, 3, 2));
 		assert_eq!(pruned, Some(H256::from([1; 32])));
 
 		assert_eq!(cache.buffer.len(), 2);
@@ -217,19 +381,156 @@ pub mod test {
 		assert_eq!(cache.blocks_by_hash.len(), 2);
 	}
 
+	#[test]
+	fn cache_retract_works() {
+		let mut cache = BlockCache::<MockBlock>::new(3);
+		cache.insert(MockBlock::new(1, 1, 0));
+		cache.insert(MockBlock::new(2, 2, 1));
+
+		// Block 2 is replaced by a fork.
+		cache.retract(&H256::from([2; 32]));
+		assert!(cache.blocks_by_number.get(&2).is_none());
+		cache.insert(MockBlock::new(2, 12, 1));
+		assert_eq!(cache.blocks_by_number[&2].hash(), H256::from([12; 32]));
+
+		// Retracting an unknown block, or pruning the retracted one, keeps the canonical block.
+		cache.retract(&H256::from([42; 32]));
+		cache.insert(MockBlock::new(3, 13, 12));
+		cache.insert(MockBlock::new(4, 14, 13));
+		assert_eq!(cache.blocks_by_number[&2].hash(), H256::from([12; 32]));
+		assert!(cache.blocks_by_hash.get(&H256::from([2; 32])).is_none());
+	}
+
+	#[test]
+	fn cache_reenact_works() {
+		let mut cache = BlockCache::<MockBlock>::new(4);
+		cache.insert(MockBlock::new(1, 1, 0));
+		cache.insert(MockBlock::new(2, 2, 1));
+
+		// Switch to the fork at block 2, then back to the original block.
+		cache.retract(&H256::from([2; 32]));
+		cache.insert(MockBlock::new(2, 12, 1));
+		cache.retract(&H256::from([12; 32]));
+		cache.insert(MockBlock::new(2, 2, 1));
+
+		assert_eq!(cache.blocks_by_number[&2].hash(), H256::from([2; 32]));
+		assert_eq!(cache.buffer.back().unwrap().hash(), H256::from([2; 32]));
+		assert_eq!(cache.buffer.len(), 3);
+	}
+
+	#[tokio::test]
+	async fn tree_route_works() {
+		// A stand-in for the node's chain, with a fork at block 1:
+		//
+		// 0 -> 1 -> 2 -> 3
+		//       \-> 12 -> 13 -> 14
+		let chain: HashMap<H256, Arc<MockBlock>> = [
+			MockBlock::new(0, 0, 0),
+			MockBlock::new(1, 1, 0),
+			MockBlock::new(2, 2, 1),
+			MockBlock::new(3, 3, 2),
+			MockBlock::new(2, 12, 1),
+			MockBlock::new(3, 13, 12),
+			MockBlock::new(4, 14, 13),
+		]
+		.into_iter()
+		.map(|block| (block.hash(), Arc::new(block)))
+		.collect();
+		let block = |hash: u8| chain[&H256::from([hash; 32])].clone();
+		let parent = |hash: H256| {
+			let block = chain.get(&hash).cloned();
+			async move { block }
+		};
+		let hashes = |blocks: Vec<Arc<MockBlock>>| {
+			blocks.iter().map(|block| block.hash().0[0]).collect::<Vec<_>>()
+		};
+
+		// Extending the best chain.
+		let route = tree_route(block(2), block(3), parent).await.unwrap();
+		assert_eq!(hashes(route.retracted), Vec::<u8>::new());
+		assert_eq!(hashes(route.enacted), vec![3]);
+
+		// Switching to the longer fork.
+		let route = tree_route(block(3), block(14), parent).await.unwrap();
+		assert_eq!(hashes(route.retracted), vec![3, 2]);
+		assert_eq!(hashes(route.enacted), vec![12, 13, 14]);
+
+		// Switching back to a shorter fork.
+		let route = tree_route(block(14), block(2), parent).await.unwrap();
+		assert_eq!(hashes(route.retracted), vec![14, 13, 12]);
+		assert_eq!(hashes(route.enacted), vec![2]);
+
+		// The common ancestor can't be found.
+		let orphan = Arc::new(MockBlock::new(3, 23, 22));
+		assert!(tree_route(block(3), orphan, parent).await.is_none());
+	}
+
+	#[tokio::test]
+	async fn resync_route_works() {
+		// The canonical chain of the node, forked at block 1 from the cached chain:
+		//
+		// 0 -> 1 -> 2 -> 3        (cached)
+		//       \-> 12 -> 13 -> 14 (canonical)
+		let canonical_chain: HashMap<SubstrateBlockNumber, Arc<MockBlock>> = [
+			MockBlock::new(0, 0, 0),
+			MockBlock::new(1, 1, 0),
+			MockBlock::new(2, 12, 1),
+			MockBlock::new(3, 13, 12),
+			MockBlock::new(4, 14, 13),
+		]
+		.into_iter()
+		.map(|block| (block.number(), Arc::new(block)))
+		.collect();
+		let canonical = |number: SubstrateBlockNumber| {
+			let block = canonical_chain.get(&number).cloned();
+			async move { block }
+		};
+		let cached = |blocks: &[(u32, u8, u8)]| {
+			blocks
+				.iter()
+				.map(|&(number, hash, parent)| Arc::new(MockBlock::new(number, hash, parent)))
+				.collect::<Vec<_>>()
+		};
+		let hashes = |blocks: Vec<Arc<MockBlock>>| {
+			blocks.iter().map(|block| block.hash().0[0]).collect::<Vec<_>>()
+		};
+
+		// The cached blocks above the fork are retracted, the canonical ones are enacted.
+		let route =
+			resync_route(cached(&[(0, 0, 0), (1, 1, 0), (2, 2, 1), (3, 3, 2)]), 3, canonical).await;
+		assert_eq!(hashes(route.retracted), vec![3, 2]);
+		assert_eq!(hashes(route.enacted), vec![12, 13]);
+
+		// None of the cached blocks is canonical.
+		let route = resync_route(cached(&[(2, 2, 1), (3, 3, 2)]), 4, canonical).await;
+		assert_eq!(hashes(route.retracted), vec![3, 2]);
+		assert_eq!(hashes(route.enacted), vec![12, 13, 14]);
+
+		// The cached blocks above the new parent are retracted, even when they are canonical.
+		let route = resync_route(cached(&[(1, 1, 0), (2, 12, 1), (3, 13, 12)]), 2, canonical).await;
+		assert_eq!(hashes(route.retracted), vec![13]);
+		assert_eq!(hashes(route.enacted), Vec::<u8>::new());
+	}
+
 	/// A Noop BlockInfoProvider used to test [`db::DBReceiptProvider`].
 	pub struct MockBlockInfoProvider;
 
 	#[async_trait]
 	impl BlockInfoProvider for MockBlockInfoProvider {
-		async fn cache_block(&self, _block: SubstrateBlock) -> Option<H256> {
+		async fn cache_block(&self, _block: Arc<SubstrateBlock>) -> Option<H256> {
 			None
 		}
 
+		async fn retract_block(&self, _hash: &H256) {}
+
 		async fn latest_block(&self) -> Option<Arc<SubstrateBlock>> {
 			None
 		}
 
+		async fn cached_blocks(&self) -> Vec<Arc<SubstrateBlock>> {
+			Vec::new()
+		}
+
 		async fn latest_block_number(&self) -> Option<SubstrateBlockNumber> {
 			Some(2u32)
 		}
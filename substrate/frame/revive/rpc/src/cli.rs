@@ -18,8 +18,10 @@
 use crate::{
 	client::{connect, native_to_eth_ratio, Client, SubscriptionType, SubstrateBlockNumber},
 	BlockInfoProvider, BlockInfoProviderImpl, CacheReceiptProvider, DBReceiptProvider,
-	DebugRpcServer, DebugRpcServerImpl, EthRpcServer, EthRpcServerImpl, ReceiptExtractor,
-	ReceiptProvider, SystemHealthRpcServer, SystemHealthRpcServerImpl, LOG_TARGET,
+	DebugRpcServer, DebugRpcServerImpl, EthFilterRpcServer, EthFilterRpcServerImpl,
+	EthPubSubRpcServer, EthPubSubRpcServerImpl, EthRpcServer, EthRpcServerImpl, FilterManager,
+	ReceiptExtractor, ReceiptProvider, SystemHealthRpcServer, SystemHealthRpcServerImpl,
+	DEFAULT_MAX_FEE_HISTORY_BLOCKS, LOG_TARGET,
 };
 use clap::Parser;
 use futures::{pin_mut, FutureExt};
@@ -29,7 +31,7 @@ use sc_service::{
 	config::{PrometheusConfig, RpcConfiguration},
 	start_rpc_servers, TaskManager,
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 // Default port if --prometheus-port is not specified
 const DEFAULT_PROMETHEUS_PORT: u16 = 9616;
@@ -65,6 +67,29 @@ pub struct CliCommand {
 	#[clap(long)]
 	pub index_until_block: Option<SubstrateBlockNumber>,
 
+	/// Only index finalized blocks.
+	///
+	/// Receipts are then only served once their block is finalized, and are never affected by
+	/// reorganizations of the best chain.
+	#[clap(long)]
+	pub index_finalized_blocks_only: bool,
+
+	/// The number of seconds after which a filter that has not been polled is uninstalled.
+	#[clap(long, default_value = "300", value_parser = clap::value_parser!(u64).range(1..))]
+	pub filter_timeout: u64,
+
+	/// The maximum number of filters installed at the same time, over all connections.
+	#[clap(long, default_value = "10000")]
+	pub max_filters: usize,
+
+	/// The maximum number of filters a single WebSocket connection can install.
+	#[clap(long, default_value = "64")]
+	pub max_filters_per_connection: usize,
+
+	/// The maximum number of blocks that can be requested with `eth_feeHistory`.
+	#[clap(long, default_value_t = DEFAULT_MAX_FEE_HISTORY_BLOCKS)]
+	pub max_fee_history_blocks: u32,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub shared_params: SharedParams,
@@ -158,6 +183,11 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		database_url,
 		earliest_receipt_block,
 		index_until_block,
+		index_finalized_blocks_only,
+		filter_timeout,
+		max_filters,
+		max_filters_per_connection,
+		max_fee_history_blocks,
 		shared_params,
 		..
 	} = cmd;
@@ -184,6 +214,7 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		rate_limit: rpc_params.rpc_rate_limit,
 		rate_limit_whitelisted_ips: rpc_params.rpc_rate_limit_whitelisted_ips,
 		rate_limit_trust_proxy_headers: rpc_params.rpc_rate_limit_trust_proxy_headers,
+		archive_index: false,
 	};
 
 	let prometheus_config =
@@ -203,6 +234,12 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		tokio_runtime.block_on(async { Signals::capture() })?,
 	)?;
 
+	let filters = FilterManager::new(
+		Duration::from_secs(filter_timeout),
+		max_filters,
+		max_filters_per_connection,
+	);
+
 	// Prometheus metrics.
 	if let Some(PrometheusConfig { port, registry }) = prometheus_config.clone() {
 		task_manager.spawn_handle().spawn(
@@ -216,14 +253,29 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		&rpc_config,
 		prometheus_registry,
 		tokio_handle,
-		|| rpc_module(is_dev, client.clone()),
+		|| rpc_module(is_dev, client.clone(), filters.clone(), max_fee_history_blocks),
 		None,
 	)?;
 
+	task_manager.spawn_handle().spawn("filter-manager", None, {
+		let client = client.clone();
+		async move { filters.run(&client).await }
+	});
+
+	task_manager.spawn_handle().spawn("pool-subscription", None, {
+		let client = client.clone();
+		async move { client.subscribe_pool_transactions().await }
+	});
+
 	task_manager
 		.spawn_essential_handle()
 		.spawn("block-subscription", None, async move {
-			let fut1 = client.subscribe_and_cache_new_blocks(SubscriptionType::BestBlocks);
+			let subscription_type = if index_finalized_blocks_only {
+				SubscriptionType::FinalizedBlocks
+			} else {
+				SubscriptionType::BestBlocks
+			};
+			let fut1 = client.subscribe_and_cache_new_blocks(subscription_type);
 			if let Some(index_until_block) = index_until_block {
 				let fut2 = client.cache_old_blocks(index_until_block);
 				tokio::join!(fut1, fut2);
@@ -239,16 +291,26 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 }
 
 /// Create the JSON-RPC module.
-fn rpc_module(is_dev: bool, client: Client) -> Result<RpcModule<()>, sc_service::Error> {
+fn rpc_module(
+	is_dev: bool,
+	client: Client,
+	filters: FilterManager,
+	max_fee_history_blocks: u32,
+) -> Result<RpcModule<()>, sc_service::Error> {
 	let eth_api = EthRpcServerImpl::new(client.clone())
 		.with_accounts(if is_dev { vec![crate::Account::default()] } else { vec![] })
+		.with_max_fee_history_blocks(max_fee_history_blocks)
 		.into_rpc();
 
+	let pubsub_api = EthPubSubRpcServerImpl::new(client.clone()).into_rpc();
+	let filter_api = EthFilterRpcServerImpl::new(client.clone(), filters).into_rpc();
 	let health_api = SystemHealthRpcServerImpl::new(client.clone()).into_rpc();
 	let debug_api = DebugRpcServerImpl::new(client).into_rpc();
 
 	let mut module = RpcModule::new(());
 	module.merge(eth_api).map_err(|e| sc_service::Error::Application(e.into()))?;
+	module.merge(pubsub_api).map_err(|e| sc_service::Error::Application(e.into()))?;
+	module.merge(filter_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	module.merge(health_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	module.merge(debug_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	Ok(module)
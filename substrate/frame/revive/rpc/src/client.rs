@@ -17,24 +17,28 @@
 //! The client connects to the source substrate chain
 //! and is used by the rpc server to query and send transactions to the substrate chain.
 use crate::{
+	block_info_provider::{resync_route, tree_route, TreeRoute},
 	subxt_client::{
 		revive::calls::types::EthTransact, runtime_types::pallet_revive::storage::ContractInfo,
 	},
 	BlockInfoProvider, ReceiptExtractor, ReceiptProvider, TransactionInfo, LOG_TARGET,
 };
 use codec::{Decode, Encode};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use jsonrpsee::types::{error::CALL_EXECUTION_FAILED_CODE, ErrorObjectOwned};
 use pallet_revive::{
 	evm::{
-		decode_revert_reason, Block, BlockNumberOrTag, BlockNumberOrTagOrHash, CallTrace, Filter,
-		GenericTransaction, Log, ReceiptInfo, SyncingProgress, SyncingStatus, TracerConfig,
-		TransactionSigned, TransactionTrace, H160, H256, U256,
+		decode_revert_reason, AccessList, AccessListEntry, Block, BlockNumberOrTag,
+		BlockNumberOrTagOrHash, CallTrace, FeeHistoryResult, Filter, GenericTransaction, Log,
+		PrestateTrace, PrestateTracerConfig, ReceiptInfo, StateOverrideSet, SyncingProgress,
+		SyncingStatus, Trace, TracerConfig, TransactionSigned, TransactionTrace, H160, H256, U256,
 	},
 	EthTransactError, EthTransactInfo,
 };
+use sp_core::keccak_256;
 use sp_runtime::OpaqueExtrinsic;
 use sp_weights::Weight;
-use std::{ops::ControlFlow, sync::Arc, time::Duration};
+use std::{collections::HashSet, ops::ControlFlow, sync::Arc, time::Duration};
 use subxt::{
 	backend::{
 		legacy::{rpc_methods::SystemHealth, LegacyRpcMethods},
@@ -49,7 +53,7 @@ use subxt::{
 	Config, OnlineClient,
 };
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
 use crate::subxt_client::{self, SrcChainConfig};
 
@@ -76,6 +80,35 @@ pub enum SubscriptionType {
 	FinalizedBlocks,
 }
 
+/// The capacity of the channels used to broadcast notifications to the RPC subscribers.
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 512;
+
+/// The interval at which the transaction pool of the node is polled for new Ethereum
+/// transactions, while there are pending transactions subscribers.
+const PENDING_TRANSACTIONS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of blocks whose fee data is fetched concurrently by `eth_feeHistory`.
+const FEE_HISTORY_CONCURRENCY: usize = 16;
+
+/// A notification about a change of the best chain, broadcast to the RPC subscribers.
+#[derive(Clone)]
+pub enum BlockNotification {
+	/// A new block was added to the best chain.
+	NewBlock {
+		/// The new block.
+		block: Arc<SubstrateBlock>,
+		/// The logs emitted by the Ethereum transactions of the block.
+		logs: Vec<Log>,
+	},
+	/// A previously notified block was removed from the best chain.
+	Retracted {
+		/// The hash of the retracted block.
+		block_hash: H256,
+		/// The logs emitted by the retracted block, marked as `removed`.
+		logs: Vec<Log>,
+	},
+}
+
 /// Unwrap the original `jsonrpsee::core::client::Error::Call` error.
 fn unwrap_call_err(err: &subxt::error::RpcError) -> Option<ErrorObjectOwned> {
 	use subxt::backend::rpc::reconnecting_rpc_client;
@@ -119,6 +152,9 @@ pub enum ClientError {
 	/// The block hash was not found.
 	#[error("hash not found")]
 	BlockNotFound,
+	/// The receipts of the block were not found.
+	#[error("receipts not found")]
+	ReceiptsNotFound,
 
 	#[error("No Ethereum extrinsic found")]
 	EthExtrinsicNotFound,
@@ -137,6 +173,9 @@ pub enum ClientError {
 	/// Failed to filter logs.
 	#[error("Failed to filter logs")]
 	LogFilterFailed(#[from] anyhow::Error),
+	/// The tracer is not supported by the runtime.
+	#[error("tracer not supported by the runtime")]
+	UnsupportedTracer,
 }
 
 const REVERT_CODE: i32 = 3;
@@ -181,6 +220,8 @@ pub struct Client {
 	receipt_extractor: ReceiptExtractor,
 	chain_id: u64,
 	max_block_weight: Weight,
+	block_notifier: broadcast::Sender<BlockNotification>,
+	pending_transaction_notifier: broadcast::Sender<H256>,
 }
 
 /// Fetch the chain ID from the substrate chain.
@@ -213,6 +254,38 @@ async fn extract_block_timestamp(block: &SubstrateBlock) -> Option<u64> {
 	Some(ext.value.now / 1000)
 }
 
+/// Compute the effective priority fees paid at the given percentiles of the block's gas usage.
+///
+/// The percentiles must be sorted in ascending order.
+fn block_rewards(receipts: &[ReceiptInfo], base_fee: U256, percentiles: &[f64]) -> Vec<U256> {
+	if receipts.is_empty() {
+		return vec![U256::zero(); percentiles.len()];
+	}
+
+	let mut rewards = receipts
+		.iter()
+		.map(|receipt| (receipt.effective_gas_price.saturating_sub(base_fee), receipt.gas_used))
+		.collect::<Vec<_>>();
+	rewards.sort_by_key(|(reward, _)| *reward);
+
+	let total_gas_used = rewards.iter().fold(U256::zero(), |acc, (_, gas_used)| acc + gas_used);
+	let mut index = 0;
+	let mut cumulative_gas_used = rewards[0].1;
+
+	percentiles
+		.iter()
+		.map(|percentile| {
+			let threshold =
+				U256::from((total_gas_used.low_u128() as f64 * percentile / 100.0) as u128);
+			while cumulative_gas_used < threshold && index < rewards.len() - 1 {
+				index += 1;
+				cumulative_gas_used += rewards[index].1;
+			}
+			rewards[index].0
+		})
+		.collect()
+}
+
 /// Connect to a node at the given URL, and return the underlying API, RPC client, and legacy RPC
 /// clients.
 pub async fn connect(
@@ -245,6 +318,8 @@ impl Client {
 	) -> Result<Self, ClientError> {
 		let (chain_id, max_block_weight) =
 			tokio::try_join!(chain_id(&api), max_block_weight(&api))?;
+		let (block_notifier, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+		let (pending_transaction_notifier, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
 
 		Ok(Self {
 			api,
@@ -255,6 +330,8 @@ impl Client {
 			receipt_extractor,
 			chain_id,
 			max_block_weight,
+			block_notifier,
+			pending_transaction_notifier,
 		})
 	}
 
@@ -348,16 +425,74 @@ impl Client {
 	}
 
 	/// Start the block subscription, and populate the block cache.
+	///
+	/// When the best chain is reorganized, the receipts of the retracted blocks are removed, and
+	/// the blocks enacted by the new best chain are indexed.
 	pub async fn subscribe_and_cache_new_blocks(&self, subscription_type: SubscriptionType) {
 		let res = self
 			.subscribe_new_blocks(subscription_type, |block| async {
-				let receipts = self.receipt_extractor.extract_from_block(&block).await?;
+				let notify = self.block_notifier.receiver_count() > 0;
+
+				if let Some(route) = self.reorg_route(&block).await {
+					for retracted in route.retracted {
+						let block_hash = retracted.hash();
+						log::debug!(target: LOG_TARGET, "Retracting block {block_hash:?}");
+						if notify {
+							let filter =
+								Filter { block_hash: Some(block_hash), ..Default::default() };
+							let logs = self
+								.receipt_provider
+								.logs(Some(filter))
+								.await
+								.unwrap_or_default()
+								.into_iter()
+								.map(|log| Log { removed: Some(true), ..log })
+								.collect();
+							let _ = self
+								.block_notifier
+								.send(BlockNotification::Retracted { block_hash, logs });
+						}
+
+						self.receipt_provider.retract(&block_hash).await;
+						self.block_provider.retract_block(&block_hash).await;
+					}
+
+					for enacted in route.enacted {
+						log::debug!(target: LOG_TARGET, "Indexing enacted block {:?}", enacted.hash());
+						let receipts = self.receipt_extractor.extract_from_block(&enacted).await?;
+						self.receipt_provider.insert(&enacted.hash(), &receipts).await;
+						if let Some(pruned) = self.block_provider.cache_block(enacted.clone()).await
+						{
+							self.receipt_provider.remove(&pruned).await;
+						}
+						if notify {
+							let logs = receipts
+								.into_iter()
+								.flat_map(|(_, receipt)| receipt.logs)
+								.collect();
+							let _ = self
+								.block_notifier
+								.send(BlockNotification::NewBlock { block: enacted, logs });
+						}
+					}
+				}
 
-				self.receipt_provider.insert(&block.hash(), &receipts).await;
-				if let Some(pruned) = self.block_provider.cache_block(block).await {
+				let receipts = self.receipt_extractor.extract_from_block(&block).await?;
+				let block_hash = block.hash();
+				self.receipt_provider.insert(&block_hash, &receipts).await;
+				if let Some(pruned) = self.block_provider.cache_block(Arc::new(block)).await {
 					self.receipt_provider.remove(&pruned).await;
 				}
 
+				if notify {
+					if let Some(block) = self.block_provider.block_by_hash(&block_hash).await? {
+						let logs =
+							receipts.into_iter().flat_map(|(_, receipt)| receipt.logs).collect();
+						let _ =
+							self.block_notifier.send(BlockNotification::NewBlock { block, logs });
+					}
+				}
+
 				Ok(())
 			})
 			.await;
@@ -367,6 +502,119 @@ impl Client {
 		}
 	}
 
+	/// Return the route from the latest ingested block to the parent of the given block, when
+	/// the given block does not extend the latest ingested block.
+	///
+	/// The enacted blocks of the route are the ones that were skipped by the subscription, and
+	/// still need to be indexed. When the common ancestor can't be found by walking the parents,
+	/// the route is computed by comparing the cached blocks with the canonical chain of the node,
+	/// see [`resync_route`].
+	async fn reorg_route(&self, block: &SubstrateBlock) -> Option<TreeRoute<SubstrateBlock>> {
+		let latest = self.block_provider.latest_block().await?;
+		let parent_hash = block.header().parent_hash;
+		if latest.hash() == parent_hash {
+			return None;
+		}
+
+		let parent = |hash: H256| {
+			let block_provider = self.block_provider.clone();
+			async move { block_provider.block_by_hash(&hash).await.ok().flatten() }
+		};
+
+		if let Some(new_parent) = parent(parent_hash).await {
+			if let Some(route) = tree_route(latest, new_parent, parent).await {
+				return Some(route);
+			}
+		}
+
+		log::warn!(
+			target: LOG_TARGET,
+			"Failed to find the common ancestor of block {:?}, resyncing from the canonical chain",
+			block.hash()
+		);
+
+		let canonical = |number: SubstrateBlockNumber| {
+			let (rpc, block_provider) = (self.rpc.clone(), self.block_provider.clone());
+			async move {
+				let hash = rpc.chain_get_block_hash(Some(number.into())).await.ok().flatten()?;
+				block_provider.block_by_hash(&hash).await.ok().flatten()
+			}
+		};
+
+		let cached = self.block_provider.cached_blocks().await;
+		Some(resync_route(cached, block.number().saturating_sub(1), canonical).await)
+	}
+
+	/// Subscribe to the notifications about the best chain, emitted while the block subscription
+	/// started by [`Self::subscribe_and_cache_new_blocks`] is running.
+	pub fn subscribe_block_notifications(&self) -> broadcast::Receiver<BlockNotification> {
+		self.block_notifier.subscribe()
+	}
+
+	/// Subscribe to the hashes of the Ethereum transactions entering the transaction pool of the
+	/// node, emitted while the pool subscription started by
+	/// [`Self::subscribe_pool_transactions`] is running.
+	pub fn subscribe_pending_transactions(&self) -> broadcast::Receiver<H256> {
+		self.pending_transaction_notifier.subscribe()
+	}
+
+	/// Start the pool subscription, and notify the pending transactions subscribers about the
+	/// Ethereum transactions entering the transaction pool of the node.
+	///
+	/// The pool is polled with `author_pendingExtrinsics`, so transactions that are included in
+	/// a block between two polls are not notified. The pool is not polled while there are no
+	/// subscribers.
+	pub async fn subscribe_pool_transactions(&self) {
+		let mut interval = tokio::time::interval(PENDING_TRANSACTIONS_POLL_INTERVAL);
+		let mut known = HashSet::new();
+		loop {
+			interval.tick().await;
+			if self.pending_transaction_notifier.receiver_count() == 0 {
+				known.clear();
+				continue;
+			}
+
+			let client = RpcClient::new(self.rpc_client.clone());
+			let extrinsics: Vec<sp_core::Bytes> =
+				match client.request("author_pendingExtrinsics", Default::default()).await {
+					Ok(extrinsics) => extrinsics,
+					Err(err) => {
+						log::debug!(target: LOG_TARGET, "Failed to fetch pending extrinsics: {err:?}");
+						continue;
+					},
+				};
+
+			let pending = extrinsics
+				.iter()
+				.filter_map(|extrinsic| self.eth_transact_payload(&extrinsic.0))
+				.map(|payload| H256(keccak_256(&payload)))
+				.collect::<HashSet<_>>();
+			for transaction_hash in pending.difference(&known) {
+				let _ = self.pending_transaction_notifier.send(*transaction_hash);
+			}
+			known = pending;
+		}
+	}
+
+	/// Return the payload of the given extrinsic, when it is a bare `Revive::eth_transact` call.
+	fn eth_transact_payload(&self, extrinsic: &[u8]) -> Option<Vec<u8>> {
+		let metadata = self.api.metadata();
+		let pallet = metadata.pallet_by_name("Revive")?;
+		let call_index = pallet.call_variant_by_name("eth_transact")?.index;
+
+		let input = &mut &extrinsic[..];
+		let _len = codec::Compact::<u32>::decode(input).ok()?;
+		// Signed and general extrinsics set one of the two high bits of the version byte.
+		let version = u8::decode(input).ok()?;
+		if version & 0b1100_0000 != 0 {
+			return None;
+		}
+		if (u8::decode(input).ok()?, u8::decode(input).ok()?) != (pallet.index(), call_index) {
+			return None;
+		}
+		Vec::<u8>::decode(input).ok()
+	}
+
 	/// Cache old blocks up to the given block number.
 	pub async fn cache_old_blocks(&self, oldest_block: SubstrateBlockNumber) {
 		let res = self
@@ -455,8 +703,18 @@ impl Client {
 	}
 
 	/// Get an EVM transaction receipt by hash.
+	///
+	/// Receipts from blocks that are not part of the canonical chain are never returned.
 	pub async fn receipt(&self, tx_hash: &H256) -> Option<ReceiptInfo> {
-		self.receipt_provider.receipt_by_hash(tx_hash).await
+		let receipt = self.receipt_provider.receipt_by_hash(tx_hash).await?;
+		let block_number = SubstrateBlockNumber::try_from(receipt.block_number).ok()?;
+		let block = self.block_provider.block_by_number(block_number).await.ok()??;
+		if block.hash() != receipt.block_hash {
+			log::debug!(target: LOG_TARGET, "Ignoring receipt of {tx_hash:?} from non-canonical block {:?}", receipt.block_hash);
+			return None;
+		}
+
+		Some(receipt)
 	}
 
 	/// Get the syncing status of the chain.
@@ -501,6 +759,18 @@ impl Client {
 		self.receipt_provider.receipts_count_per_block(block_hash).await
 	}
 
+	/// Get the receipts of the given block, ordered by transaction index.
+	pub async fn receipts_by_block_hash(&self, block_hash: &H256) -> Option<Vec<ReceiptInfo>> {
+		let count = self.receipt_provider.receipts_count_per_block(block_hash).await?;
+		let mut receipts = Vec::with_capacity(count);
+		for index in 0..count {
+			let receipt =
+				self.receipt_provider.receipt_by_block_hash_and_index(block_hash, index).await?;
+			receipts.push(receipt);
+		}
+		Some(receipts)
+	}
+
 	/// Get the system health.
 	pub async fn system_health(&self) -> Result<SystemHealth, ClientError> {
 		let health = self.rpc.system_health().await?;
@@ -564,22 +834,65 @@ impl Client {
 	}
 
 	/// Dry run a transaction and returns the [`EthTransactInfo`] for the transaction.
+	///
+	/// When `overrides` are given, the transaction is executed on top of the state modified by
+	/// them. The overrides are discarded once the dry run completes. Runtimes implementing a
+	/// `ReviveApi` older than version 3 don't support overrides, they are ignored in that case.
 	pub async fn dry_run(
 		&self,
 		tx: GenericTransaction,
 		block: BlockNumberOrTagOrHash,
+		overrides: Option<StateOverrideSet>,
 	) -> Result<EthTransactInfo<Balance>, ClientError> {
 		let runtime_api = self.runtime_api(&block).await?;
-		let payload = subxt_client::apis().revive_api().eth_transact(tx.into());
 
-		let result = runtime_api.call(payload).await?;
-		match result {
-			Err(err) => {
-				log::debug!(target: LOG_TARGET, "Dry run failed {err:?}");
-				Err(ClientError::TransactError(err.0))
+		let overrides = match overrides {
+			Some(overrides) if !overrides.is_empty() => {
+				let version = runtime_api.call(subxt_client::apis().core().version()).await?;
+				let api_id = sp_crypto_hashing::blake2_64(b"ReviveApi");
+				let revive_api_version = version
+					.apis
+					.iter()
+					.find_map(|(id, version)| (*id == api_id).then_some(*version))
+					.unwrap_or_default();
+
+				if revive_api_version >= 3 {
+					Some(overrides)
+				} else {
+					log::warn!(
+						target: LOG_TARGET,
+						"ReviveApi version {revive_api_version} doesn't support state overrides, \
+						 ignoring them"
+					);
+					None
+				}
 			},
-			Ok(result) => Ok(result.0),
-		}
+			_ => None,
+		};
+
+		let result = match overrides {
+			Some(overrides) => {
+				let overrides = overrides
+					.into_iter()
+					.map(|(address, state_override)| {
+						(address.0.into(), subxt::utils::Static(state_override))
+					})
+					.collect();
+				let payload = subxt_client::apis()
+					.revive_api()
+					.eth_transact_with_overrides(tx.into(), overrides);
+				runtime_api.call(payload).await?.map(|result| result.0).map_err(|err| err.0)
+			},
+			None => {
+				let payload = subxt_client::apis().revive_api().eth_transact(tx.into());
+				runtime_api.call(payload).await?.map(|result| result.0).map_err(|err| err.0)
+			},
+		};
+
+		result.map_err(|err| {
+			log::debug!(target: LOG_TARGET, "Dry run failed {err:?}");
+			ClientError::TransactError(err)
+		})
 	}
 
 	/// Get the nonce of the given address.
@@ -651,6 +964,86 @@ impl Client {
 		let gas_price = runtime_api.call(payload).await?;
 		Ok(*gas_price)
 	}
+
+	/// Get the fee history of the `block_count` blocks ending with `newest_block`.
+	pub async fn fee_history(
+		&self,
+		block_count: SubstrateBlockNumber,
+		newest_block: &BlockNumberOrTag,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> Result<FeeHistoryResult, ClientError> {
+		if block_count == 0 {
+			return Ok(Default::default());
+		}
+
+		let newest_block = self
+			.block_by_number_or_tag(newest_block)
+			.await?
+			.ok_or(ClientError::BlockNotFound)?;
+		let newest_number = newest_block.number();
+		let oldest_number = newest_number.saturating_sub(block_count - 1);
+
+		let mut result = FeeHistoryResult {
+			oldest_block: oldest_number.into(),
+			reward: reward_percentiles.as_ref().map(|_| Vec::new()),
+			..Default::default()
+		};
+
+		let blocks = stream::iter(oldest_number..=newest_number)
+			.map(|number| {
+				let newest_block = newest_block.clone();
+				async move {
+					let block = if number == newest_number {
+						newest_block
+					} else {
+						self.block_by_number(number).await?.ok_or(ClientError::BlockNotFound)?
+					};
+
+					let block_hash = block.hash();
+					let at = BlockNumberOrTagOrHash::from(block_hash);
+					let runtime_api = self.api.runtime_api().at(block_hash);
+					tokio::try_join!(
+						self.gas_price(&at),
+						Self::block_gas_limit(&runtime_api),
+						async {
+							self.receipts_by_block_hash(&block_hash)
+								.await
+								.ok_or(ClientError::ReceiptsNotFound)
+						},
+					)
+				}
+			})
+			.buffered(FEE_HISTORY_CONCURRENCY)
+			.try_collect::<Vec<_>>()
+			.await?;
+
+		for (base_fee, gas_limit, receipts) in blocks {
+			let gas_used =
+				receipts.iter().fold(U256::zero(), |acc, receipt| acc + receipt.gas_used);
+
+			result.base_fee_per_gas.push(base_fee);
+			result.gas_used_ratio.push(if gas_limit.is_zero() {
+				0.0
+			} else {
+				gas_used.low_u128() as f64 / gas_limit.low_u128() as f64
+			});
+
+			if let (Some(rewards), Some(percentiles)) =
+				(result.reward.as_mut(), reward_percentiles.as_ref())
+			{
+				rewards.push(block_rewards(&receipts, base_fee, percentiles));
+			}
+		}
+
+		// The base fee does not depend on the block congestion, so the base fee of the next block
+		// is the same as the newest one.
+		if let Some(base_fee) = result.base_fee_per_gas.last().copied() {
+			result.base_fee_per_gas.push(base_fee);
+		}
+
+		Ok(result)
+	}
+
 	/// Get the transaction traces for the given block.
 	pub async fn trace_block_by_number(
 		&self,
@@ -682,6 +1075,7 @@ impl Client {
 			.filter_map(|e| OpaqueExtrinsic::decode(&mut &e[..]).ok())
 			.collect::<Vec<_>>();
 
+		let call_traces_only = self.call_traces_only(&tracer_config, Some(parent_hash)).await?;
 		let params = ((header, exts), tracer_config).encode();
 
 		let bytes = self
@@ -692,7 +1086,14 @@ impl Client {
 				log::error!(target: LOG_TARGET, "state_call failed with: {err:?}");
 			})?;
 
-		let traces = Vec::<(u32, CallTrace)>::decode(&mut &bytes[..])?;
+		let traces = if call_traces_only {
+			Vec::<(u32, CallTrace)>::decode(&mut &bytes[..])?
+				.into_iter()
+				.map(|(index, trace)| (index, Trace::Call(trace)))
+				.collect()
+		} else {
+			Vec::<(u32, Trace)>::decode(&mut &bytes[..])?
+		};
 
 		let mut hashes = self
 			.receipt_provider
@@ -715,7 +1116,7 @@ impl Client {
 		&self,
 		transaction_hash: H256,
 		tracer_config: TracerConfig,
-	) -> Result<CallTrace, ClientError> {
+	) -> Result<Trace, ClientError> {
 		let ReceiptInfo { block_hash, transaction_index, .. } = self
 			.receipt_provider
 			.receipt_by_hash(&transaction_hash)
@@ -740,6 +1141,7 @@ impl Client {
 			.filter_map(|e| OpaqueExtrinsic::decode(&mut &e[..]).ok())
 			.collect::<Vec<_>>();
 
+		let call_traces_only = self.call_traces_only(&tracer_config, Some(parent_hash)).await?;
 		let params = ((header, exts), transaction_index.as_u32(), tracer_config).encode();
 		let bytes = self
 			.rpc
@@ -749,7 +1151,11 @@ impl Client {
 				log::error!(target: LOG_TARGET, "state_call failed with: {err:?}");
 			})?;
 
-		let trace = Option::<CallTrace>::decode(&mut &bytes[..])?;
+		let trace = if call_traces_only {
+			Option::<CallTrace>::decode(&mut &bytes[..])?.map(Trace::Call)
+		} else {
+			Option::<Trace>::decode(&mut &bytes[..])?
+		};
 		trace.ok_or(ClientError::EthExtrinsicNotFound)
 	}
 
@@ -759,7 +1165,7 @@ impl Client {
 		transaction: GenericTransaction,
 		block: BlockNumberOrTag,
 		tracer_config: TracerConfig,
-	) -> Result<CallTrace, ClientError> {
+	) -> Result<Trace, ClientError> {
 		let block_hash = match block {
 			BlockNumberOrTag::U256(n) => {
 				let block_number: SubstrateBlockNumber =
@@ -769,6 +1175,7 @@ impl Client {
 			BlockNumberOrTag::BlockTag(_) => self.latest_block().await.map(|b| b.hash()),
 		};
 
+		let call_traces_only = self.call_traces_only(&tracer_config, block_hash).await?;
 		let params = (transaction, tracer_config).encode();
 		let bytes = self
 			.rpc
@@ -778,9 +1185,92 @@ impl Client {
 				log::error!(target: LOG_TARGET, "state_call failed with: {err:?}");
 			})?;
 
-		Result::<CallTrace, EthTransactError>::decode(&mut &bytes[..])?
+		if call_traces_only {
+			return Result::<CallTrace, EthTransactError>::decode(&mut &bytes[..])?
+				.map(Trace::Call)
+				.map_err(ClientError::TransactError);
+		}
+		Result::<Trace, EthTransactError>::decode(&mut &bytes[..])?
 			.map_err(ClientError::TransactError)
 	}
+
+	/// Get the version of the `ReviveApi` runtime API at the given block, `0` if the runtime
+	/// doesn't implement it.
+	async fn revive_api_version(&self, at: Option<H256>) -> Result<u32, ClientError> {
+		let api_id = format!("0x{}", hex::encode(sp_crypto_hashing::blake2_64(b"ReviveApi")));
+		let runtime_version = self.rpc.state_get_runtime_version(at).await?;
+		let version = runtime_version
+			.other
+			.get("apis")
+			.and_then(|apis| apis.as_array())
+			.into_iter()
+			.flatten()
+			.find_map(|api| match (api.get(0)?.as_str()?, api.get(1)?.as_u64()?) {
+				(id, version) if id == api_id => Some(version),
+				_ => None,
+			})
+			.unwrap_or_default();
+		Ok(version as u32)
+	}
+
+	/// Whether the tracing runtime APIs at the given block only return call traces.
+	///
+	/// Before version 2 of the `ReviveApi`, the tracing methods return a `CallTrace` instead of
+	/// a `Trace` and only support the call tracer.
+	async fn call_traces_only(
+		&self,
+		tracer_config: &TracerConfig,
+		at: Option<H256>,
+	) -> Result<bool, ClientError> {
+		if self.revive_api_version(at).await? >= 2 {
+			return Ok(false);
+		}
+		match tracer_config {
+			TracerConfig::CallTracer { .. } => Ok(true),
+			_ => Err(ClientError::UnsupportedTracer),
+		}
+	}
+
+	/// Create the access list of the given transaction.
+	///
+	/// The access list contains the accounts and storage slots touched by the transaction, as
+	/// reported by the prestate tracer. The sender and the recipient are always warm, so they
+	/// are omitted unless some of their storage slots were touched.
+	pub async fn create_access_list(
+		&self,
+		transaction: GenericTransaction,
+		block: BlockNumberOrTag,
+	) -> Result<AccessList, ClientError> {
+		let (from, to) = (transaction.from, transaction.to);
+		let tracer_config = TracerConfig::PrestateTracer(PrestateTracerConfig {
+			disable_code: true,
+			..Default::default()
+		});
+
+		let Trace::Prestate(PrestateTrace::Prestate(accounts)) =
+			self.trace_call(transaction, block, tracer_config).await?
+		else {
+			return Ok(Default::default());
+		};
+
+		let access_list = accounts
+			.into_iter()
+			.map(|(address, info)| AccessListEntry {
+				address,
+				storage_keys: info
+					.storage
+					.into_keys()
+					.filter_map(|key| <[u8; 32]>::try_from(key.0).ok().map(H256))
+					.collect(),
+			})
+			.filter(|entry| {
+				!entry.storage_keys.is_empty() ||
+					(Some(entry.address) != from && Some(entry.address) != to)
+			})
+			.collect();
+
+		Ok(access_list)
+	}
 	/// Get the EVM block for the given hash.
 	pub async fn evm_block(
 		&self,
@@ -858,3 +1348,26 @@ impl Client {
 		Ok(logs)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn block_rewards_works() {
+		let receipt = |effective_gas_price: u64, gas_used: u64| ReceiptInfo {
+			effective_gas_price: effective_gas_price.into(),
+			gas_used: gas_used.into(),
+			..Default::default()
+		};
+
+		let base_fee = U256::from(10);
+		assert_eq!(block_rewards(&[], base_fee, &[25.0, 75.0]), vec![U256::zero(); 2]);
+
+		let receipts = [receipt(40, 500), receipt(12, 100), receipt(20, 400)];
+		assert_eq!(
+			block_rewards(&receipts, base_fee, &[0.0, 10.0, 25.0, 50.0, 100.0]),
+			vec![2u32, 2, 10, 10, 30].into_iter().map(U256::from).collect::<Vec<_>>()
+		);
+	}
+}
@@ -129,6 +129,7 @@ impl<Client: EthRpcClient + Send + Sync> TransactionBuilder<Client> {
 					..Default::default()
 				},
 				None,
+				None,
 			)
 			.await
 			.with_context(|| "eth_call failed")?;
@@ -158,6 +159,7 @@ impl<Client: EthRpcClient + Send + Sync> TransactionBuilder<Client> {
 					..Default::default()
 				},
 				None,
+				None,
 			)
 			.await
 			.with_context(|| "Failed to fetch gas estimate")?;
@@ -0,0 +1,602 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Server side filters, polled with `eth_getFilterChanges`.
+use crate::{
+	client::{BlockNotification, Client, Shared},
+	EthRpcError, LOG_TARGET,
+};
+use jsonrpsee::ConnectionId;
+use pallet_revive::evm::{BlockNumberOrTag, BlockTag, Filter, H256, U256};
+use sc_rpc_api::ConnectionClosed;
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+	time::Duration,
+};
+use tokio::{
+	sync::{broadcast::error::RecvError, RwLock},
+	time::Instant,
+};
+
+/// The maximum number of blocks covered by a single log query of an installed filter.
+pub const MAX_LOG_FILTER_BLOCK_RANGE: u64 = 1024;
+
+/// The kind of an installed filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterKind {
+	/// A log filter, installed with `eth_newFilter`.
+	Logs {
+		/// The filter criteria.
+		filter: Filter,
+		/// The first block that has not been returned by `eth_getFilterChanges` yet.
+		next_block: U256,
+	},
+	/// A block filter, installed with `eth_newBlockFilter`, holding the hashes of the new blocks
+	/// since the last poll.
+	Blocks(Vec<H256>),
+	/// A pending transaction filter, installed with `eth_newPendingTransactionFilter`, holding the
+	/// hashes of the transactions submitted since the last poll.
+	PendingTransactions(Vec<H256>),
+}
+
+/// The changes of a filter since its last poll.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterChanges {
+	/// The new block or transaction hashes.
+	Hashes(Vec<H256>),
+	/// The filter to use to query the new logs, or `None` if there are no new blocks.
+	Logs(Option<Filter>),
+}
+
+/// An installed filter.
+struct InstalledFilter {
+	/// The kind of filter.
+	kind: FilterKind,
+	/// The WebSocket connection that installed the filter, `None` if it was installed over HTTP.
+	connection_id: Option<ConnectionId>,
+	/// The last time the filter was polled.
+	last_poll: Instant,
+}
+
+impl InstalledFilter {
+	/// Whether the filter can be used by the given connection.
+	fn is_visible_to(&self, connection_id: ConnectionId) -> bool {
+		self.connection_id.map_or(true, |owner| owner == connection_id)
+	}
+}
+
+/// The filters registry.
+#[derive(Default)]
+struct FilterRegistry {
+	/// The installed filters by id.
+	filters: HashMap<U256, InstalledFilter>,
+	/// The connections whose closing uninstalls their filters.
+	watched_connections: HashSet<ConnectionId>,
+}
+
+/// Keeps track of the filters installed by the RPC clients.
+///
+/// Filters installed over WebSocket can only be used by the connection that installed them, and
+/// are uninstalled when that connection is closed. Filters installed over HTTP can be used by any
+/// request that knows their id. Both are uninstalled when they are not polled within the
+/// configured timeout.
+#[derive(Clone)]
+pub struct FilterManager {
+	/// The shared filters registry.
+	registry: Shared<FilterRegistry>,
+	/// The duration after which a filter that has not been polled is uninstalled.
+	timeout: Duration,
+	/// The maximum number of filters installed at the same time.
+	max_filters: usize,
+	/// The maximum number of filters a single WebSocket connection can install.
+	max_filters_per_connection: usize,
+}
+
+impl FilterManager {
+	/// Create a new filter manager.
+	pub fn new(timeout: Duration, max_filters: usize, max_filters_per_connection: usize) -> Self {
+		Self {
+			registry: Arc::new(RwLock::new(FilterRegistry::default())),
+			timeout,
+			max_filters,
+			max_filters_per_connection,
+		}
+	}
+
+	/// Install a new filter, and return its id.
+	///
+	/// `connection` is the WebSocket connection the filter is installed by, if any. Its filters
+	/// are uninstalled once its `ConnectionClosed` is notified.
+	pub async fn install(
+		&self,
+		connection: Option<(ConnectionId, &ConnectionClosed)>,
+		kind: FilterKind,
+	) -> Result<U256, EthRpcError> {
+		let mut registry = self.registry.write().await;
+		if registry.filters.len() >= self.max_filters {
+			return Err(EthRpcError::FilterLimitReached(self.max_filters));
+		}
+
+		if let Some((connection_id, connection_closed)) = connection {
+			let count = registry
+				.filters
+				.values()
+				.filter(|f| f.connection_id == Some(connection_id))
+				.count();
+			if count >= self.max_filters_per_connection {
+				return Err(EthRpcError::TooManyFilters(self.max_filters_per_connection));
+			}
+
+			if registry.watched_connections.insert(connection_id) {
+				let manager = self.clone();
+				let handle = tokio::runtime::Handle::current();
+				connection_closed.on_close(move || {
+					handle.spawn(async move { manager.uninstall_connection(connection_id).await });
+				});
+			}
+		}
+
+		// Filters installed over HTTP are shared, so their ids must not be guessable.
+		let id = loop {
+			let id = U256::from(rand::random::<u128>());
+			if !registry.filters.contains_key(&id) {
+				break id;
+			}
+		};
+		registry.filters.insert(
+			id,
+			InstalledFilter {
+				kind,
+				connection_id: connection.map(|(connection_id, _)| connection_id),
+				last_poll: Instant::now(),
+			},
+		);
+		Ok(id)
+	}
+
+	/// Uninstall the filter with the given id, and return whether it could be used by the given
+	/// connection.
+	pub async fn uninstall(&self, connection_id: ConnectionId, id: &U256) -> bool {
+		let mut registry = self.registry.write().await;
+		match registry.filters.get(id) {
+			Some(installed) if installed.is_visible_to(connection_id) =>
+				registry.filters.remove(id).is_some(),
+			_ => false,
+		}
+	}
+
+	/// Uninstall all the filters of the given connection.
+	async fn uninstall_connection(&self, connection_id: ConnectionId) {
+		let mut registry = self.registry.write().await;
+		registry.watched_connections.remove(&connection_id);
+		registry
+			.filters
+			.retain(|_, installed| installed.connection_id != Some(connection_id));
+		log::debug!(target: LOG_TARGET, "Uninstalled the filters of connection {connection_id:?}");
+	}
+
+	/// Return the filter with the given id if it can be used by the given connection, and record
+	/// the poll.
+	fn poll<'a>(
+		registry: &'a mut FilterRegistry,
+		connection_id: ConnectionId,
+		id: &U256,
+	) -> Result<&'a mut InstalledFilter, EthRpcError> {
+		let installed = registry
+			.filters
+			.get_mut(id)
+			.filter(|installed| installed.is_visible_to(connection_id))
+			.ok_or(EthRpcError::FilterNotFound(*id))?;
+		installed.last_poll = Instant::now();
+		Ok(installed)
+	}
+
+	/// Return the changes of the filter since its last poll.
+	///
+	/// Log filters return the criteria to use to query the logs of the blocks that were added up
+	/// to `latest_block` since the last poll, at most [`MAX_LOG_FILTER_BLOCK_RANGE`] blocks at a
+	/// time.
+	pub async fn changes(
+		&self,
+		connection_id: ConnectionId,
+		id: &U256,
+		latest_block: U256,
+	) -> Result<FilterChanges, EthRpcError> {
+		let mut registry = self.registry.write().await;
+		let installed = Self::poll(&mut registry, connection_id, id)?;
+
+		let changes = match &mut installed.kind {
+			FilterKind::Blocks(hashes) | FilterKind::PendingTransactions(hashes) =>
+				FilterChanges::Hashes(core::mem::take(hashes)),
+			FilterKind::Logs { filter, next_block } => {
+				let to_block = match filter.to_block {
+					Some(BlockNumberOrTag::U256(to_block)) => to_block.min(latest_block),
+					_ => latest_block,
+				}
+				.min(next_block.saturating_add((MAX_LOG_FILTER_BLOCK_RANGE - 1).into()));
+
+				if *next_block > to_block {
+					FilterChanges::Logs(None)
+				} else {
+					let from_block = core::mem::replace(next_block, to_block + 1);
+					FilterChanges::Logs(Some(Filter {
+						from_block: Some(from_block.into()),
+						to_block: Some(to_block.into()),
+						block_hash: None,
+						..filter.clone()
+					}))
+				}
+			},
+		};
+
+		Ok(changes)
+	}
+
+	/// Return the criteria of the log filter with the given id.
+	///
+	/// Fails if the criteria cover more than [`MAX_LOG_FILTER_BLOCK_RANGE`] blocks up to
+	/// `latest_block`.
+	pub async fn log_filter(
+		&self,
+		connection_id: ConnectionId,
+		id: &U256,
+		latest_block: U256,
+	) -> Result<Filter, EthRpcError> {
+		let mut registry = self.registry.write().await;
+		let installed = Self::poll(&mut registry, connection_id, id)?;
+		let FilterKind::Logs { filter, .. } = &installed.kind else {
+			return Err(EthRpcError::FilterNotFound(*id));
+		};
+
+		let block_number = |block: &Option<BlockNumberOrTag>| match block {
+			Some(BlockNumberOrTag::U256(number)) => (*number).min(latest_block),
+			Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)) => U256::zero(),
+			_ => latest_block,
+		};
+		let (from_block, to_block) =
+			(block_number(&filter.from_block), block_number(&filter.to_block));
+		if to_block.saturating_sub(from_block) >= MAX_LOG_FILTER_BLOCK_RANGE.into() {
+			return Err(EthRpcError::BlockRangeTooLarge(MAX_LOG_FILTER_BLOCK_RANGE));
+		}
+
+		Ok(filter.clone())
+	}
+
+	/// Record a new block for the block filters.
+	async fn on_new_block(&self, block_hash: H256) {
+		for installed in self.registry.write().await.filters.values_mut() {
+			if let FilterKind::Blocks(hashes) = &mut installed.kind {
+				hashes.push(block_hash);
+			}
+		}
+	}
+
+	/// Record a new pending transaction for the pending transaction filters.
+	async fn on_pending_transaction(&self, transaction_hash: H256) {
+		for installed in self.registry.write().await.filters.values_mut() {
+			if let FilterKind::PendingTransactions(hashes) = &mut installed.kind {
+				hashes.push(transaction_hash);
+			}
+		}
+	}
+
+	/// Uninstall the filters that have not been polled within the timeout.
+	async fn prune_expired(&self) {
+		let timeout = self.timeout;
+		self.registry.write().await.filters.retain(|id, installed| {
+			let keep = installed.last_poll.elapsed() < timeout;
+			if !keep {
+				log::debug!(target: LOG_TARGET, "Filter {id:?} expired");
+			}
+			keep
+		});
+	}
+
+	/// Feed the block and pending transaction filters from the client notifications, and
+	/// periodically uninstall the expired filters.
+	pub async fn run(&self, client: &Client) {
+		let mut blocks = client.subscribe_block_notifications();
+		let mut pending_transactions = client.subscribe_pending_transactions();
+		let mut prune_interval = tokio::time::interval(self.timeout.min(Duration::from_secs(10)));
+
+		loop {
+			tokio::select! {
+				notification = blocks.recv() => match notification {
+					Ok(BlockNotification::NewBlock { block, .. }) =>
+						self.on_new_block(block.hash()).await,
+					Ok(BlockNotification::Retracted { .. }) => {},
+					Err(RecvError::Lagged(skipped)) => {
+						log::warn!(target: LOG_TARGET, "Block filters missed {skipped} notifications");
+					},
+					Err(RecvError::Closed) => break,
+				},
+				transaction_hash = pending_transactions.recv() => match transaction_hash {
+					Ok(transaction_hash) => self.on_pending_transaction(transaction_hash).await,
+					Err(RecvError::Lagged(skipped)) => {
+						log::warn!(target: LOG_TARGET, "Pending transaction filters missed {skipped} notifications");
+					},
+					Err(RecvError::Closed) => break,
+				},
+				_ = prune_interval.tick() => self.prune_expired().await,
+			}
+		}
+
+		log::info!(target: LOG_TARGET, "Filter manager stopped");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+
+	#[tokio::test]
+	async fn filters_per_connection_are_limited() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_secs(60), 100, 2);
+		let conn = ConnectionId(1);
+
+		manager
+			.install(Some((conn, &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+		let id = manager
+			.install(Some((conn, &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+		assert!(matches!(
+			manager.install(Some((conn, &closed)), FilterKind::Blocks(vec![])).await,
+			Err(EthRpcError::TooManyFilters(2))
+		));
+
+		// Other connections are not affected.
+		manager
+			.install(Some((ConnectionId(2), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+
+		// Uninstalling a filter frees a slot.
+		assert!(manager.uninstall(conn, &id).await);
+		assert!(!manager.uninstall(conn, &id).await);
+		manager
+			.install(Some((conn, &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn block_and_pending_transaction_filters_work() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_secs(60), 100, 10);
+		let blocks = manager
+			.install(Some((ConnectionId(1), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+		let txs = manager
+			.install(Some((ConnectionId(1), &closed)), FilterKind::PendingTransactions(vec![]))
+			.await
+			.unwrap();
+
+		manager.on_new_block(H256::from([1u8; 32])).await;
+		manager.on_new_block(H256::from([2u8; 32])).await;
+		manager.on_pending_transaction(H256::from([3u8; 32])).await;
+
+		assert_eq!(
+			manager.changes(ConnectionId(1), &blocks, U256::zero()).await.unwrap(),
+			FilterChanges::Hashes(vec![H256::from([1u8; 32]), H256::from([2u8; 32])])
+		);
+		assert_eq!(
+			manager.changes(ConnectionId(1), &blocks, U256::zero()).await.unwrap(),
+			FilterChanges::Hashes(vec![])
+		);
+		assert_eq!(
+			manager.changes(ConnectionId(1), &txs, U256::zero()).await.unwrap(),
+			FilterChanges::Hashes(vec![H256::from([3u8; 32])])
+		);
+	}
+
+	#[tokio::test]
+	async fn log_filter_changes_work() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_secs(60), 100, 10);
+		let filter = Filter { to_block: Some(U256::from(5).into()), ..Default::default() };
+		let id = manager
+			.install(
+				Some((ConnectionId(1), &closed)),
+				FilterKind::Logs { filter: filter.clone(), next_block: U256::from(2) },
+			)
+			.await
+			.unwrap();
+
+		let expected = |from: u64, to: u64| {
+			FilterChanges::Logs(Some(Filter {
+				from_block: Some(U256::from(from).into()),
+				to_block: Some(U256::from(to).into()),
+				..filter.clone()
+			}))
+		};
+
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, U256::from(1)).await.unwrap(),
+			FilterChanges::Logs(None)
+		);
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, U256::from(3)).await.unwrap(),
+			expected(2, 3)
+		);
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, U256::from(3)).await.unwrap(),
+			FilterChanges::Logs(None)
+		);
+		// The range is capped by the filter's `to_block`.
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, U256::from(10)).await.unwrap(),
+			expected(4, 5)
+		);
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, U256::from(11)).await.unwrap(),
+			FilterChanges::Logs(None)
+		);
+
+		assert_eq!(manager.log_filter(ConnectionId(1), &id, U256::from(11)).await.unwrap(), filter);
+	}
+
+	#[tokio::test]
+	async fn filters_are_scoped_to_their_connection() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_secs(60), 100, 10);
+		let id = manager
+			.install(Some((ConnectionId(1), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+
+		assert!(matches!(
+			manager.changes(ConnectionId(2), &id, U256::zero()).await,
+			Err(EthRpcError::FilterNotFound(_))
+		));
+		assert!(!manager.uninstall(ConnectionId(2), &id).await);
+		assert!(manager.changes(ConnectionId(1), &id, U256::zero()).await.is_ok());
+		assert!(manager.uninstall(ConnectionId(1), &id).await);
+	}
+
+	#[tokio::test]
+	async fn http_filters_are_shared_and_globally_limited() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_secs(60), 2, 10);
+		let id = manager.install(None, FilterKind::Blocks(vec![])).await.unwrap();
+
+		// Every HTTP request has its own connection id.
+		assert!(manager.changes(ConnectionId(1), &id, U256::zero()).await.is_ok());
+		assert!(manager.changes(ConnectionId(2), &id, U256::zero()).await.is_ok());
+
+		// The limit covers both HTTP and WebSocket filters.
+		manager
+			.install(Some((ConnectionId(3), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+		assert!(matches!(
+			manager.install(None, FilterKind::Blocks(vec![])).await,
+			Err(EthRpcError::FilterLimitReached(2))
+		));
+
+		// Closing a WebSocket connection doesn't affect HTTP filters.
+		closed.close();
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		assert!(manager.changes(ConnectionId(4), &id, U256::zero()).await.is_ok());
+		assert!(manager.uninstall(ConnectionId(5), &id).await);
+		manager.install(None, FilterKind::Blocks(vec![])).await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn filters_are_uninstalled_when_the_connection_closes() {
+		let (closed, other) = (ConnectionClosed::default(), ConnectionClosed::default());
+		let manager = FilterManager::new(Duration::from_secs(60), 100, 1);
+		let id = manager
+			.install(Some((ConnectionId(1), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+		let kept = manager
+			.install(Some((ConnectionId(2), &other)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+
+		closed.close();
+		tokio::time::sleep(Duration::from_millis(50)).await;
+
+		assert!(matches!(
+			manager.changes(ConnectionId(1), &id, U256::zero()).await,
+			Err(EthRpcError::FilterNotFound(_))
+		));
+		assert!(manager.changes(ConnectionId(2), &kept, U256::zero()).await.is_ok());
+		// The slot of the closed connection is released.
+		assert!(manager
+			.registry
+			.read()
+			.await
+			.watched_connections
+			.get(&ConnectionId(1))
+			.is_none());
+	}
+
+	#[tokio::test]
+	async fn log_filter_block_range_is_limited() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_secs(60), 100, 10);
+		let filter = Filter {
+			from_block: Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)),
+			..Default::default()
+		};
+		let id = manager
+			.install(
+				Some((ConnectionId(1), &closed)),
+				FilterKind::Logs { filter: filter.clone(), next_block: U256::zero() },
+			)
+			.await
+			.unwrap();
+
+		let latest = U256::from(2 * MAX_LOG_FILTER_BLOCK_RANGE);
+		assert!(matches!(
+			manager.log_filter(ConnectionId(1), &id, latest).await,
+			Err(EthRpcError::BlockRangeTooLarge(MAX_LOG_FILTER_BLOCK_RANGE))
+		));
+
+		// Changes are returned in bounded chunks.
+		let range = |from: u64, to: u64| {
+			FilterChanges::Logs(Some(Filter {
+				from_block: Some(U256::from(from).into()),
+				to_block: Some(U256::from(to).into()),
+				..filter.clone()
+			}))
+		};
+		let max = MAX_LOG_FILTER_BLOCK_RANGE;
+		assert_eq!(manager.changes(ConnectionId(1), &id, latest).await.unwrap(), range(0, max - 1));
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, latest).await.unwrap(),
+			range(max, 2 * max - 1)
+		);
+		assert_eq!(
+			manager.changes(ConnectionId(1), &id, latest).await.unwrap(),
+			range(2 * max, 2 * max)
+		);
+	}
+
+	#[tokio::test]
+	async fn expired_filters_are_uninstalled() {
+		let closed = ConnectionClosed::default();
+		let manager = FilterManager::new(Duration::from_millis(200), 100, 10);
+		let polled = manager
+			.install(Some((ConnectionId(1), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+		let idle = manager
+			.install(Some((ConnectionId(1), &closed)), FilterKind::Blocks(vec![]))
+			.await
+			.unwrap();
+
+		tokio::time::sleep(Duration::from_millis(120)).await;
+		manager.changes(ConnectionId(1), &polled, U256::zero()).await.unwrap();
+
+		tokio::time::sleep(Duration::from_millis(120)).await;
+		manager.prune_expired().await;
+
+		assert!(manager.changes(ConnectionId(1), &polled, U256::zero()).await.is_ok());
+		assert!(matches!(
+			manager.changes(ConnectionId(1), &idle, U256::zero()).await,
+			Err(EthRpcError::FilterNotFound(_))
+		));
+	}
+}
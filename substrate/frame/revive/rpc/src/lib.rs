@@ -44,11 +44,20 @@ pub use receipt_provider::*;
 mod receipt_extractor;
 pub use receipt_extractor::*;
 
+mod filter_manager;
+pub use filter_manager::*;
+
 mod apis;
 pub use apis::*;
 
 pub const LOG_TARGET: &str = "eth-rpc";
 
+/// The default maximum number of blocks that can be requested with `eth_feeHistory`.
+pub const DEFAULT_MAX_FEE_HISTORY_BLOCKS: u32 = 128;
+
+/// The magic bytes every PolkaVM program blob starts with.
+const POLKAVM_BLOB_MAGIC: [u8; 4] = *b"PVM\0";
+
 /// An EVM RPC server implementation.
 pub struct EthRpcServerImpl {
 	/// The client used to interact with the substrate node.
@@ -56,12 +65,15 @@ pub struct EthRpcServerImpl {
 
 	/// The accounts managed by the server.
 	accounts: Vec<Account>,
+
+	/// The maximum number of blocks that can be requested with `eth_feeHistory`.
+	max_fee_history_blocks: u32,
 }
 
 impl EthRpcServerImpl {
 	/// Creates a new [`EthRpcServerImpl`].
 	pub fn new(client: client::Client) -> Self {
-		Self { client, accounts: vec![] }
+		Self { client, accounts: vec![], max_fee_history_blocks: DEFAULT_MAX_FEE_HISTORY_BLOCKS }
 	}
 
 	/// Sets the accounts managed by the server.
@@ -69,6 +81,12 @@ impl EthRpcServerImpl {
 		self.accounts = accounts;
 		self
 	}
+
+	/// Sets the maximum number of blocks that can be requested with `eth_feeHistory`.
+	pub fn with_max_fee_history_blocks(mut self, max_fee_history_blocks: u32) -> Self {
+		self.max_fee_history_blocks = max_fee_history_blocks;
+		self
+	}
 }
 
 /// The error type for the EVM RPC server.
@@ -95,6 +113,46 @@ pub enum EthRpcError {
 	/// Received an invalid transaction
 	#[error("Invalid transaction {0:?}")]
 	TransactionTypeNotSupported(Byte),
+	/// Received invalid parameters for the subscription kind.
+	#[error("Invalid parameters for subscription {0:?}")]
+	InvalidSubscriptionParams(SubscriptionKind),
+	/// The filter was not found, or it expired.
+	#[error("Filter {0:?} not found")]
+	FilterNotFound(U256),
+	/// The connection reached the maximum number of installed filters.
+	#[error("Too many filters installed, the limit is {0} per connection")]
+	TooManyFilters(usize),
+	/// The server reached the maximum number of installed filters.
+	#[error("Too many filters installed, the limit is {0}")]
+	FilterLimitReached(usize),
+	/// The log filter covers too many blocks.
+	#[error("Block range too large, the limit is {0} blocks")]
+	BlockRangeTooLarge(u64),
+	/// Block hash criteria are not supported by installed filters.
+	#[error("Block hash is not supported by eth_newFilter, use eth_getLogs instead")]
+	BlockHashFilterNotSupported,
+	/// The reward percentiles are not sorted, or out of the `[0, 100]` range.
+	#[error("Invalid reward percentiles")]
+	InvalidRewardPercentiles,
+	/// The code override of an account is not a PolkaVM program.
+	#[error("Code override for {0:?} is not a PolkaVM program, EVM bytecode is not supported")]
+	InvalidCodeOverride(H160),
+}
+
+/// Check that the given state `overrides` can be applied by the runtime.
+///
+/// Contracts are PolkaVM programs, so a `code` override must be a PolkaVM blob.
+fn check_state_overrides(overrides: Option<&StateOverrideSet>) -> Result<(), EthRpcError> {
+	let invalid_code = overrides.into_iter().flatten().find(|(_, state_override)| {
+		state_override
+			.code
+			.as_ref()
+			.is_some_and(|code| !code.0.starts_with(&POLKAVM_BLOB_MAGIC))
+	});
+	match invalid_code {
+		Some((address, _)) => Err(EthRpcError::InvalidCodeOverride(*address)),
+		None => Ok(()),
+	}
 }
 
 // TODO use https://eips.ethereum.org/EIPS/eip-1474#error-codes
@@ -140,19 +198,47 @@ impl EthRpcServer for EthRpcServerImpl {
 		&self,
 		transaction: GenericTransaction,
 		block: Option<BlockNumberOrTag>,
+		state_overrides: Option<StateOverrideSet>,
 	) -> RpcResult<U256> {
-		let dry_run = self.client.dry_run(transaction, block.unwrap_or_default().into()).await?;
+		check_state_overrides(state_overrides.as_ref())?;
+		let dry_run = self
+			.client
+			.dry_run(transaction, block.unwrap_or_default().into(), state_overrides)
+			.await?;
 		Ok(dry_run.eth_gas)
 	}
 
+	async fn create_access_list(
+		&self,
+		mut transaction: GenericTransaction,
+		block: Option<BlockNumberOrTag>,
+	) -> RpcResult<AccessListResult> {
+		let block = block.unwrap_or_default();
+		let access_list =
+			self.client.create_access_list(transaction.clone(), block.clone()).await?;
+
+		transaction.access_list = Some(access_list.clone());
+		match self.client.dry_run(transaction, block.into(), None).await {
+			Ok(dry_run) =>
+				Ok(AccessListResult { access_list, gas_used: dry_run.eth_gas, error: None }),
+			Err(err @ ClientError::TransactError(_)) => {
+				let error = ErrorObjectOwned::from(err).message().to_string();
+				Ok(AccessListResult { access_list, gas_used: U256::zero(), error: Some(error) })
+			},
+			Err(err) => Err(err.into()),
+		}
+	}
+
 	async fn call(
 		&self,
 		transaction: GenericTransaction,
 		block: Option<BlockNumberOrTagOrHash>,
+		state_overrides: Option<StateOverrideSet>,
 	) -> RpcResult<Bytes> {
+		check_state_overrides(state_overrides.as_ref())?;
 		let dry_run = self
 			.client
-			.dry_run(transaction, block.unwrap_or_else(|| BlockTag::Latest.into()))
+			.dry_run(transaction, block.unwrap_or_else(|| BlockTag::Latest.into()), state_overrides)
 			.await?;
 		Ok(dry_run.data.into())
 	}
@@ -184,7 +270,7 @@ impl EthRpcServer for EthRpcServerImpl {
 			.ok_or(EthRpcError::AccountNotFound(from))?;
 
 		if transaction.gas.is_none() {
-			transaction.gas = Some(self.estimate_gas(transaction.clone(), None).await?);
+			transaction.gas = Some(self.estimate_gas(transaction.clone(), None, None).await?);
 		}
 
 		if transaction.gas_price.is_none() {
@@ -217,6 +303,44 @@ impl EthRpcServer for EthRpcServerImpl {
 		Ok(Some(block))
 	}
 
+	async fn get_block_receipts(
+		&self,
+		block: BlockNumberOrTagOrHash,
+	) -> RpcResult<Option<Vec<ReceiptInfo>>> {
+		let block = match block {
+			BlockNumberOrTagOrHash::H256(hash) => self.client.block_by_hash(&hash).await?,
+			BlockNumberOrTagOrHash::U256(n) =>
+				self.client.block_by_number_or_tag(&BlockNumberOrTag::U256(n)).await?,
+			BlockNumberOrTagOrHash::BlockTag(tag) =>
+				self.client.block_by_number_or_tag(&BlockNumberOrTag::BlockTag(tag)).await?,
+		};
+
+		let Some(block) = block else {
+			return Ok(None);
+		};
+		Ok(self.client.receipts_by_block_hash(&block.hash()).await)
+	}
+
+	async fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumberOrTag,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> RpcResult<FeeHistoryResult> {
+		if let Some(percentiles) = &reward_percentiles {
+			let in_range = percentiles.iter().all(|p| (0.0..=100.0).contains(p));
+			let sorted = percentiles.windows(2).all(|w| w[0] <= w[1]);
+			if !in_range || !sorted {
+				return Err(EthRpcError::InvalidRewardPercentiles.into());
+			}
+		}
+
+		let block_count = block_count.min(self.max_fee_history_blocks.into()).as_u32();
+		let history =
+			self.client.fee_history(block_count, &newest_block, reward_percentiles).await?;
+		Ok(history)
+	}
+
 	async fn get_balance(&self, address: H160, block: BlockNumberOrTagOrHash) -> RpcResult<U256> {
 		let balance = self.client.balance(address, &block).await?;
 		log::debug!(target: LOG_TARGET, "balance({address}): {balance:?}");
@@ -41,6 +41,12 @@ pub trait ReceiptProvider: Send + Sync {
 	/// Deletes receipts associated with the specified block hash.
 	async fn remove(&self, block_hash: &H256);
 
+	/// Deletes receipts associated with a block that is no longer part of the canonical chain.
+	///
+	/// Unlike `remove`, this is never skipped, as the receipts of a retracted block must not be
+	/// served anymore.
+	async fn retract(&self, block_hash: &H256);
+
 	/// Return all transaction hashes for the given block hash.
 	async fn block_transaction_hashes(&self, block_hash: &H256) -> Option<HashMap<usize, H256>>;
 
@@ -75,6 +81,10 @@ impl<Cache: ReceiptProvider, Archive: ReceiptProvider> ReceiptProvider for (Cach
 		join!(self.0.remove(block_hash), self.1.remove(block_hash));
 	}
 
+	async fn retract(&self, block_hash: &H256) {
+		join!(self.0.retract(block_hash), self.1.retract(block_hash));
+	}
+
 	async fn receipt_by_block_hash_and_index(
 		&self,
 		block_hash: &H256,
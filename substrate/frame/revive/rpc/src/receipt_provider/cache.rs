@@ -51,6 +51,10 @@ impl ReceiptProvider for CacheReceiptProvider {
 		cache.remove(block_hash);
 	}
 
+	async fn retract(&self, block_hash: &H256) {
+		self.remove(block_hash).await;
+	}
+
 	async fn receipt_by_block_hash_and_index(
 		&self,
 		block_hash: &H256,
@@ -70,15 +70,9 @@ impl DBReceiptProvider {
 		let transaction_index = result.transaction_index.try_into().ok()?;
 		Some((block_hash, transaction_index))
 	}
-}
-
-#[async_trait]
-impl ReceiptProvider for DBReceiptProvider {
-	async fn remove(&self, block_hash: &H256) {
-		if !self.prune_old_blocks {
-			return;
-		}
 
+	/// Delete the transaction hashes and logs of the given block.
+	async fn delete_block(&self, block_hash: &H256) {
 		let block_hash = block_hash.as_ref();
 
 		let delete_transaction_hashes = query!(
@@ -109,6 +103,21 @@ impl ReceiptProvider for DBReceiptProvider {
 			log::error!(target: LOG_TARGET, "Error removing logs for block hash {block_hash:?}: {err:?}");
 		}
 	}
+}
+
+#[async_trait]
+impl ReceiptProvider for DBReceiptProvider {
+	async fn remove(&self, block_hash: &H256) {
+		if !self.prune_old_blocks {
+			return;
+		}
+
+		self.delete_block(block_hash).await;
+	}
+
+	async fn retract(&self, block_hash: &H256) {
+		self.delete_block(block_hash).await;
+	}
 
 	async fn archive(&self, block_hash: &H256, receipts: &[(TransactionSigned, ReceiptInfo)]) {
 		self.insert(block_hash, receipts).await;
@@ -470,6 +479,56 @@ mod tests {
 		assert_eq!(logs_count, 0);
 	}
 
+	#[sqlx::test]
+	async fn test_retract_reindex_on_fork(pool: SqlitePool) {
+		let mut provider = setup_sqlite_provider(pool).await;
+		// Retracted blocks must be removed even when old blocks are not pruned.
+		provider.prune_old_blocks = false;
+
+		let retracted_hash = H256::from([1u8; 32]);
+		let enacted_hash = H256::from([2u8; 32]);
+		let included_in_both = H256::from([3u8; 32]);
+		let only_retracted = H256::from([4u8; 32]);
+		let receipt = |block_hash, transaction_hash, index: u32| {
+			(
+				TransactionSigned::default(),
+				ReceiptInfo {
+					block_hash,
+					transaction_hash,
+					transaction_index: index.into(),
+					logs: vec![Log { block_hash, transaction_hash, ..Default::default() }],
+					..Default::default()
+				},
+			)
+		};
+
+		provider
+			.insert(
+				&retracted_hash,
+				&[
+					receipt(retracted_hash, included_in_both, 0),
+					receipt(retracted_hash, only_retracted, 1),
+				],
+			)
+			.await;
+
+		// The fork becomes the best chain: the retracted block is removed, and the enacted block
+		// is indexed.
+		provider.retract(&retracted_hash).await;
+		provider
+			.insert(&enacted_hash, &[receipt(enacted_hash, included_in_both, 0)])
+			.await;
+
+		assert_eq!(provider.fetch_row(&included_in_both).await, Some((enacted_hash, 0)));
+		assert_eq!(provider.fetch_row(&only_retracted).await, None);
+		assert_eq!(provider.receipts_count_per_block(&retracted_hash).await, Some(0));
+		let logs = provider
+			.logs(Some(Filter { block_hash: Some(retracted_hash), ..Default::default() }))
+			.await
+			.unwrap();
+		assert!(logs.is_empty());
+	}
+
 	#[sqlx::test]
 	async fn test_receipts_count_per_block(pool: SqlitePool) {
 		let provider = setup_sqlite_provider(pool).await;
@@ -30,6 +30,10 @@ use subxt::config::{signed_extensions, Config, PolkadotConfig};
 		path = "pallet_revive::evm::api::rpc_types_gen::GenericTransaction",
 		with = "::subxt::utils::Static<::pallet_revive::evm::GenericTransaction>"
 	),
+	substitute_type(
+		path = "pallet_revive::evm::api::eth_rpc_types::StateOverride",
+		with = "::subxt::utils::Static<::pallet_revive::evm::StateOverride>"
+	),
 	substitute_type(
 		path = "pallet_revive::primitives::EthTransactInfo<B>",
 		with = "::subxt::utils::Static<::pallet_revive::EthTransactInfo<B>>"
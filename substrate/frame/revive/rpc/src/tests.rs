@@ -21,16 +21,19 @@
 use crate::{
 	cli::{self, CliCommand},
 	example::TransactionBuilder,
-	EthRpcClient,
+	EthPubSubRpcClient, EthRpcClient,
 };
 use clap::Parser;
 use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 use pallet_revive::{
 	create1,
-	evm::{Account, BlockTag, U256},
+	evm::{
+		Account, BlockTag, GenericTransaction, StateOverride, SubscriptionItem, SubscriptionKind,
+		H160, H256, U256,
+	},
 };
 use static_init::dynamic;
-use std::{sync::Arc, thread};
+use std::{collections::BTreeMap, sync::Arc, thread};
 use substrate_cli_test_utils::*;
 
 /// Create a websocket client with a 120s timeout.
@@ -128,6 +131,36 @@ async fn transfer() -> anyhow::Result<()> {
 	Ok(())
 }
 
+#[tokio::test]
+async fn subscribe_works() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = Arc::new(SharedResources::client().await);
+
+	let mut pending_txs = client.subscribe(SubscriptionKind::NewPendingTransactions, None).await?;
+	let mut new_heads = client.subscribe(SubscriptionKind::NewHeads, None).await?;
+
+	let ethan = Account::from(subxt_signer::eth::dev::ethan());
+	let tx = TransactionBuilder::new(&client)
+		.value(1_000_000_000_000u128.into())
+		.to(ethan.address())
+		.send()
+		.await?;
+
+	let item = pending_txs.next().await.expect("subscription should not be closed")?;
+	assert_eq!(item, SubscriptionItem::TransactionHash(tx.hash()));
+
+	let receipt = tx.wait_for_receipt().await?;
+	loop {
+		let item = new_heads.next().await.expect("subscription should not be closed")?;
+		let SubscriptionItem::Header(block) = item else { panic!("Expected a header") };
+		if block.hash == receipt.block_hash {
+			break;
+		}
+	}
+
+	Ok(())
+}
+
 #[tokio::test]
 async fn deploy_and_call() -> anyhow::Result<()> {
 	let _lock = SHARED_RESOURCES.write();
@@ -219,3 +252,73 @@ async fn invalid_transaction() -> anyhow::Result<()> {
 
 	Ok(())
 }
+
+#[tokio::test]
+async fn call_with_state_overrides() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = Arc::new(SharedResources::client().await);
+
+	// Inject code and storage at an address that holds no contract.
+	let contract = H160::repeat_byte(0x42);
+	let key = H256::repeat_byte(1);
+	let value = H256::repeat_byte(2);
+	let (code, _) = pallet_revive_fixtures::compile_module("read_storage")?;
+	let tx = GenericTransaction {
+		from: Some(Account::default().address()),
+		to: Some(contract),
+		input: key.0.to_vec().into(),
+		..Default::default()
+	};
+	let overrides = BTreeMap::from([(
+		contract,
+		StateOverride {
+			code: Some(code.into()),
+			state_diff: Some(BTreeMap::from([(key, value)])),
+			..Default::default()
+		},
+	)]);
+
+	let result = client.call(tx.clone(), None, Some(overrides.clone())).await?;
+	assert_eq!(result.0, value.0.to_vec());
+	let gas = client.estimate_gas(tx, None, Some(overrides)).await?;
+	assert!(!gas.is_zero());
+
+	// The overrides are not persisted.
+	let code = client.get_code(contract, BlockTag::Latest.into()).await?;
+	assert!(code.0.is_empty());
+
+	// Fund an empty account to transfer from it.
+	let sender = H160::repeat_byte(0x43);
+	let tx = GenericTransaction {
+		from: Some(sender),
+		to: Some(Account::from(subxt_signer::eth::dev::ethan()).address()),
+		value: Some(1_000_000_000_000_000_000u128.into()),
+		..Default::default()
+	};
+	assert!(client.call(tx.clone(), None, None).await.is_err());
+
+	let overrides = BTreeMap::from([(
+		sender,
+		StateOverride {
+			balance: Some(1_000_000_000_000_000_000_000u128.into()),
+			..Default::default()
+		},
+	)]);
+	client.call(tx, None, Some(overrides)).await?;
+
+	// EVM bytecode can't be injected.
+	let tx = GenericTransaction {
+		from: Some(Account::default().address()),
+		to: Some(contract),
+		..Default::default()
+	};
+	let overrides = BTreeMap::from([(
+		contract,
+		StateOverride {
+			code: Some(vec![0x60, 0x00, 0x60, 0x00, 0xf3].into()),
+			..Default::default()
+		},
+	)]);
+	assert!(client.call(tx, None, Some(overrides)).await.is_err());
+	Ok(())
+}
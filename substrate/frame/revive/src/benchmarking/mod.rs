@@ -27,7 +27,7 @@ use crate::{
 	exec::{Ext, Key, MomentOf},
 	limits,
 	pure_precompiles::Precompile,
-	storage::WriteOutcome,
+	storage::{AccessListState, WriteOutcome},
 	ConversionPrecision, Pallet as Contracts, *,
 };
 use alloc::{vec, vec::Vec};
@@ -49,7 +49,7 @@ use sp_consensus_babe::{
 use sp_consensus_slots::Slot;
 use sp_runtime::{
 	generic::{Digest, DigestItem},
-	traits::{Bounded, Hash},
+	traits::{Bounded, Hash, SaturatedConversion, Saturating},
 };
 
 /// How many runs we do per API benchmark.
@@ -213,6 +213,20 @@ fn caller_funding<T: Config>() -> BalanceOf<T> {
 	BalanceOf::<T>::max_value() / 10_000u32.into()
 }
 
+/// An access list with `k` items, made of entries holding one address and one storage key each.
+fn access_list(k: u32) -> evm::AccessList {
+	(0..k.div_ceil(2))
+		.map(|i| evm::AccessListEntry {
+			address: H160::from_low_u64_be(i.into()),
+			storage_keys: if 2 * i + 1 < k {
+				vec![H256::from_low_u64_be(i.into())]
+			} else {
+				vec![]
+			},
+		})
+		.collect()
+}
+
 /// The deposit limit we use for benchmarks.
 fn default_deposit_limit<T: Config>() -> BalanceOf<T> {
 	(T::DepositPerByte::get() * 1024u32.into() * 1024u32.into()) +
@@ -447,6 +461,56 @@ mod benchmarks {
 		Ok(())
 	}
 
+	// `k`: Number of addresses and storage keys in the access list.
+	#[benchmark(pov_mode = Measured)]
+	fn call_with_access_list(k: Linear<0, 1024>) -> Result<(), BenchmarkError> {
+		let data = vec![42u8; 1024];
+		let instance =
+			Contract::<T>::with_caller(whitelisted_caller(), WasmModule::dummy(), vec![])?;
+		let value = Pallet::<T>::min_balance();
+		let origin = RawOrigin::Signed(instance.caller.clone());
+		let before = T::Currency::balance(&instance.account_id);
+		let storage_deposit = default_deposit_limit::<T>();
+		let access_list = access_list(k);
+		assert_eq!(AccessListState::item_count(&access_list), k);
+		#[extrinsic_call]
+		_(origin, instance.address, value, Weight::MAX, storage_deposit, data, access_list);
+		// contract should have received the value
+		assert_eq!(T::Currency::balance(&instance.account_id), before + value);
+
+		Ok(())
+	}
+
+	// `c`: Size of the code in bytes.
+	// `i`: Size of the input in bytes.
+	// `k`: Number of addresses and storage keys in the access list.
+	#[benchmark(pov_mode = Measured)]
+	fn instantiate_with_code_and_access_list(
+		c: Linear<0, { limits::code::STATIC_MEMORY_BYTES / limits::code::BYTES_PER_INSTRUCTION }>,
+		i: Linear<0, { limits::code::BLOB_BYTES }>,
+		k: Linear<0, 1024>,
+	) {
+		let input = vec![42u8; i as usize];
+		let value = Pallet::<T>::min_balance();
+		let caller = whitelisted_caller();
+		T::Currency::set_balance(&caller, caller_funding::<T>());
+		let WasmModule { code, .. } = WasmModule::sized(c);
+		let origin = RawOrigin::Signed(caller.clone());
+		Contracts::<T>::map_account(origin.clone().into()).unwrap();
+		let deployer = T::AddressMapper::to_address(&caller);
+		// Same as the nonce at the time of the call, see `FrameArgs::Instantiate`.
+		let nonce = System::<T>::account_nonce(&caller).saturating_sub(1u32.into());
+		let addr = crate::address::create1(&deployer, nonce.saturated_into());
+		let account_id = T::AddressMapper::to_fallback_account_id(&addr);
+		let storage_deposit = default_deposit_limit::<T>();
+		let access_list = access_list(k);
+		#[extrinsic_call]
+		_(origin, value, Weight::MAX, storage_deposit, code, input, access_list);
+
+		// contract has the full value
+		assert_eq!(T::Currency::balance(&account_id), value + Pallet::<T>::min_balance());
+	}
+
 	// This constructs a contract that is maximal expensive to instrument.
 	// It creates a maximum number of metering blocks per byte.
 	// `c`: Size of the code in bytes.
@@ -2061,6 +2125,132 @@ mod benchmarks {
 		assert_ok!(result);
 	}
 
+	#[benchmark(pov_mode = Measured)]
+	fn point_evaluation() {
+		use hex_literal::hex;
+		let input = hex!("01e798154708fe7789429634053cbf9f99b619f9f084048927333fce637f549b73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff000000001522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e98f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c");
+		let expected = hex!("000000000000000000000000000000000000000000000000000000000000100073eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::PointEvaluation::execute(ext.gas_meter_mut(), &input);
+		}
+
+		assert_eq!(result.unwrap().data, expected);
+	}
+
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_g1_add() {
+		let input = pure_precompiles::generate_random_g1_points(2, false);
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381G1Add::execute(ext.gas_meter_mut(), &input);
+		}
+		assert_ok!(result);
+	}
+
+	// `n`: number of (point, scalar) pairs
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_g1_msm(n: Linear<1, 128>) {
+		let input = pure_precompiles::generate_random_g1_points(n as usize, true);
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381G1Msm::execute(ext.gas_meter_mut(), &input);
+		}
+		assert_ok!(result);
+	}
+
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_g2_add() {
+		let input = pure_precompiles::generate_random_g2_points(2, false);
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381G2Add::execute(ext.gas_meter_mut(), &input);
+		}
+		assert_ok!(result);
+	}
+
+	// `n`: number of (point, scalar) pairs
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_g2_msm(n: Linear<1, 128>) {
+		let input = pure_precompiles::generate_random_g2_points(n as usize, true);
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381G2Msm::execute(ext.gas_meter_mut(), &input);
+		}
+		assert_ok!(result);
+	}
+
+	// `n`: pairings to perform
+	// This is a slow call: We reduce the number of runs to 20 to avoid the benchmark taking too
+	// long.
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_pairing(n: Linear<1, 20>) {
+		let input = pure_precompiles::generate_random_bls12_381_pairs(n as usize);
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381Pairing::execute(ext.gas_meter_mut(), &input);
+		}
+		assert_ok!(result);
+	}
+
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_map_fp_to_g1() {
+		use hex_literal::hex;
+		let input = hex!("00000000000000000000000000000000156c8a6a2c184569d69a76be144b5cdc5141d2d2ca4fe341f011e25e3969c55ad9e9b9ce2eb833c81a908e5fa4ac5f03");
+		let expected = hex!("00000000000000000000000000000000184bb665c37ff561a89ec2122dd343f20e0f4cbcaec84e3c3052ea81d1834e192c426074b02ed3dca4e7676ce4ce48ba0000000000000000000000000000000004407b8d35af4dacc809927071fc0405218f1401a6d15af775810e4e460064bcc9468beeba82fdc751be70476c888bf3");
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381MapFpToG1::execute(ext.gas_meter_mut(), &input);
+		}
+
+		assert_eq!(result.unwrap().data, expected);
+	}
+
+	#[benchmark(pov_mode = Measured)]
+	fn bls12_381_map_fp2_to_g2() {
+		use hex_literal::hex;
+		let input = hex!("0000000000000000000000000000000007355d25caf6e7f2f0cb2812ca0e513bd026ed09dda65b177500fa31714e09ea0ded3a078b526bed3307f804d4b93b040000000000000000000000000000000002829ce3c021339ccb5caf3e187f6370e1e2a311dec9b75363117063ab2015603ff52c3d3b98f19c2f65575e99e8b78c");
+		let expected = hex!("0000000000000000000000000000000000e7f4568a82b4b7dc1f14c6aaa055edf51502319c723c4dc2688c7fe5944c213f510328082396515734b6612c4e7bb700000000000000000000000000000000126b855e9e69b1f691f816e48ac6977664d24d99f8724868a184186469ddfd4617367e94527d4b74fc86413483afb35b000000000000000000000000000000000caead0fd7b6176c01436833c79d305c78be307da5f6af6c133c47311def6ff1e0babf57a0fb5539fce7ee12407b0a42000000000000000000000000000000001498aadcf7ae2b345243e281ae076df6de84455d766ab6fcdaad71fab60abb2e8b980a440043cd305db09d283c895e3d");
+		let mut call_setup = CallSetup::<T>::default();
+		let (mut ext, _) = call_setup.ext();
+
+		let result;
+		#[block]
+		{
+			result = pure_precompiles::Bls12381MapFp2ToG2::execute(ext.gas_meter_mut(), &input);
+		}
+
+		assert_eq!(result.unwrap().data, expected);
+	}
+
 	// Only calling the function itself for the list of
 	// generated different ECDSA keys.
 	// This is a slow call: We reduce the number of runs.
@@ -30,6 +30,12 @@ pub use type_id::*;
 mod debug_rpc_types;
 pub use debug_rpc_types::*;
 
+mod subscription_rpc_types;
+pub use subscription_rpc_types::*;
+
+mod eth_rpc_types;
+pub use eth_rpc_types::*;
+
 mod rpc_types;
 mod rpc_types_gen;
 pub use rpc_types_gen::*;
@@ -15,8 +15,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::evm::{Bytes, CallTracer};
-use alloc::{fmt, string::String, vec::Vec};
+use crate::evm::Bytes;
+use alloc::{collections::BTreeMap, fmt, string::String, vec::Vec};
 use codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{
@@ -36,15 +36,38 @@ pub enum TracerConfig {
 		#[serde(rename = "withLog")]
 		with_logs: bool,
 	},
+
+	/// A tracer that captures the state of the accounts touched by a transaction.
+	#[serde(rename = "prestateTracer")]
+	PrestateTracer(PrestateTracerConfig),
+
+	/// A tracer that captures the execution of a transaction step by step.
+	#[serde(rename = "structLogger")]
+	StructLogger(StructLoggerConfig),
 }
 
-impl TracerConfig {
-	/// Build the tracer associated to this config.
-	pub fn build<G>(self, gas_mapper: G) -> CallTracer<U256, G> {
-		match self {
-			Self::CallTracer { with_logs } => CallTracer::new(with_logs, gas_mapper),
-		}
-	}
+/// Configuration of the [`TracerConfig::PrestateTracer`].
+#[derive(TypeInfo, Debug, Clone, Default, Encode, Decode, Serialize, Deserialize, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PrestateTracerConfig {
+	/// Whether to return the state both before and after the execution of the transaction.
+	pub diff_mode: bool,
+	/// Whether to omit the storage of the touched accounts.
+	pub disable_storage: bool,
+	/// Whether to omit the code of the touched accounts.
+	pub disable_code: bool,
+}
+
+/// Configuration of the [`TracerConfig::StructLogger`].
+#[derive(TypeInfo, Debug, Clone, Default, Encode, Decode, Serialize, Deserialize, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StructLoggerConfig {
+	/// Whether to omit the storage touched at each step.
+	pub disable_storage: bool,
+	/// Whether to only log the syscalls, instead of every executed instruction.
+	pub syscalls_only: bool,
+	/// The maximum number of steps to log, `0` meaning no limit.
+	pub limit: u32,
 }
 
 /// Custom deserializer to support the following JSON format:
@@ -56,6 +79,14 @@ impl TracerConfig {
 /// ```json
 /// { "tracer": "callTracer" }
 /// ```
+///
+/// ```json
+/// { "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } }
+/// ```
+///
+/// ```json
+/// { "tracer": "structLogger", "tracerConfig": { "syscallsOnly": true } }
+/// ```
 impl<'de> Deserialize<'de> for TracerConfig {
 	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
 	where
@@ -74,8 +105,20 @@ impl<'de> Deserialize<'de> for TracerConfig {
 			where
 				M: MapAccess<'de>,
 			{
+				/// The union of the options supported by all tracers.
+				#[derive(Deserialize, Default)]
+				#[serde(default, rename_all = "camelCase")]
+				struct InnerTracerConfig {
+					with_logs: Option<bool>,
+					diff_mode: bool,
+					disable_storage: bool,
+					disable_code: bool,
+					syscalls_only: bool,
+					limit: u32,
+				}
+
 				let mut tracer_type: Option<String> = None;
-				let mut with_logs = None;
+				let mut inner = InnerTracerConfig::default();
 
 				while let Some(key) = map.next_key::<String>()? {
 					match key.as_str() {
@@ -83,13 +126,7 @@ impl<'de> Deserialize<'de> for TracerConfig {
 							tracer_type = map.next_value()?;
 						},
 						"tracerConfig" => {
-							#[derive(Deserialize)]
-							struct CallTracerConfig {
-								#[serde(rename = "withLogs")]
-								with_logs: Option<bool>,
-							}
-							let inner: CallTracerConfig = map.next_value()?;
-							with_logs = inner.with_logs;
+							inner = map.next_value()?;
 						},
 						_ => {},
 					}
@@ -97,7 +134,18 @@ impl<'de> Deserialize<'de> for TracerConfig {
 
 				match tracer_type.as_deref() {
 					Some("callTracer") =>
-						Ok(TracerConfig::CallTracer { with_logs: with_logs.unwrap_or(true) }),
+						Ok(TracerConfig::CallTracer { with_logs: inner.with_logs.unwrap_or(true) }),
+					Some("prestateTracer") =>
+						Ok(TracerConfig::PrestateTracer(PrestateTracerConfig {
+							diff_mode: inner.diff_mode,
+							disable_storage: inner.disable_storage,
+							disable_code: inner.disable_code,
+						})),
+					Some("structLogger") => Ok(TracerConfig::StructLogger(StructLoggerConfig {
+						disable_storage: inner.disable_storage,
+						syscalls_only: inner.syscalls_only,
+						limit: inner.limit,
+					})),
 					_ => Err(de::Error::custom("Unsupported or missing tracer type")),
 				}
 			}
@@ -119,6 +167,30 @@ fn test_tracer_config_serialization() {
 			r#"{"tracer": "callTracer", "tracerConfig": { "withLogs": false }}"#,
 			TracerConfig::CallTracer { with_logs: false },
 		),
+		(
+			r#"{"tracer": "prestateTracer"}"#,
+			TracerConfig::PrestateTracer(PrestateTracerConfig::default()),
+		),
+		(
+			r#"{"tracer": "prestateTracer", "tracerConfig": { "diffMode": true, "disableCode": true }}"#,
+			TracerConfig::PrestateTracer(PrestateTracerConfig {
+				diff_mode: true,
+				disable_storage: false,
+				disable_code: true,
+			}),
+		),
+		(
+			r#"{"tracer": "structLogger"}"#,
+			TracerConfig::StructLogger(StructLoggerConfig::default()),
+		),
+		(
+			r#"{"tracer": "structLogger", "tracerConfig": { "syscallsOnly": true, "limit": 10 }}"#,
+			TracerConfig::StructLogger(StructLoggerConfig {
+				disable_storage: false,
+				syscalls_only: true,
+				limit: 10,
+			}),
+		),
 	];
 
 	for (json_data, expected) in tracers {
@@ -206,6 +278,104 @@ pub struct CallLog {
 	pub position: u32,
 }
 
+/// The trace of a transaction, as returned by the configured tracer.
+#[derive(TypeInfo, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum Trace {
+	/// A call trace, returned by [`TracerConfig::CallTracer`].
+	Call(CallTrace),
+	/// A prestate trace, returned by [`TracerConfig::PrestateTracer`].
+	Prestate(PrestateTrace),
+	/// A step by step execution trace, returned by [`TracerConfig::StructLogger`].
+	StructLogger(StructLoggerTrace),
+}
+
+impl Default for Trace {
+	fn default() -> Self {
+		Trace::Call(Default::default())
+	}
+}
+
+/// The state of the accounts touched by a transaction.
+#[derive(TypeInfo, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum PrestateTrace {
+	/// The state of the touched accounts before the transaction was executed.
+	Prestate(BTreeMap<H160, PrestateTraceInfo>),
+
+	/// The state of the accounts modified by the transaction, before and after its execution.
+	///
+	/// Accounts, fields and storage slots that were not modified are omitted.
+	DiffMode {
+		/// The state of the modified accounts before the transaction was executed.
+		pre: BTreeMap<H160, PrestateTraceInfo>,
+		/// The state of the modified accounts after the transaction was executed.
+		post: BTreeMap<H160, PrestateTraceInfo>,
+	},
+}
+
+/// The state of a single account in a [`PrestateTrace`].
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+pub struct PrestateTraceInfo {
+	/// The balance of the account.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<U256>,
+	/// The nonce of the account, omitted if zero.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<u32>,
+	/// The code of the account, omitted if the account is not a contract.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// The storage slots read or written by the transaction.
+	///
+	/// A `None` value means that the slot is empty.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<Bytes, Option<Bytes>>,
+}
+
+/// The step by step execution trace of a transaction.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLoggerTrace<Gas = U256> {
+	/// Amount of gas used by the transaction.
+	pub gas: Gas,
+	/// Whether the transaction failed.
+	pub failed: bool,
+	/// The data returned by the transaction.
+	pub return_value: Bytes,
+	/// The logged execution steps.
+	pub struct_logs: Vec<StructLog<Gas>>,
+}
+
+/// A single step of a [`StructLoggerTrace`].
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog<Gas = U256> {
+	/// The PolkaVM program counter of the instruction.
+	pub pc: u32,
+	/// The gas left before executing the instruction.
+	pub gas: Gas,
+	/// The gas consumed by the instruction, including the nested calls made by a syscall.
+	pub gas_cost: Gas,
+	/// The call depth, starting at `1` for the transaction's top level call.
+	pub depth: u32,
+	/// The name of the syscall invoked by the instruction, if any.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub syscall: Option<String>,
+	/// The storage of the executing contract touched so far, if the instruction touched it.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<Bytes, Bytes>>,
+	/// The error message, if the execution failed at this step.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
 /// A transaction trace
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TransactionTrace {
@@ -214,5 +384,5 @@ pub struct TransactionTrace {
 	pub tx_hash: H256,
 	/// The trace of the transaction.
 	#[serde(rename = "result")]
-	pub trace: CallTrace,
+	pub trace: Trace,
 }
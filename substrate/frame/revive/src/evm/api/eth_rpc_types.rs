@@ -0,0 +1,96 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Hand-written Ethereum JSON-RPC types.
+//!
+//! `rpc_types_gen.rs` is overwritten whenever the types are regenerated, so the types that are
+//! not part of the generator's input yet live here instead.
+use super::{AccessList, Address, Bytes};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::{H256, U256};
+
+/// Access list result
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AccessListResult {
+	/// The access list of the transaction.
+	#[serde(rename = "accessList")]
+	pub access_list: AccessList,
+	/// The gas used by the transaction, when executed with the access list.
+	#[serde(rename = "gasUsed")]
+	pub gas_used: U256,
+	/// The error message, if the transaction failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+/// Fee history results
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeHistoryResult {
+	/// Lowest number block of returned range.
+	#[serde(rename = "oldestBlock")]
+	pub oldest_block: U256,
+	/// An array of block base fees per gas, including an extra block value. The extra value is
+	/// the next block after the newest block in the returned range.
+	#[serde(rename = "baseFeePerGas")]
+	pub base_fee_per_gas: Vec<U256>,
+	/// An array of block gas used ratios. These are calculated as the ratio of gasUsed and
+	/// gasLimit.
+	#[serde(rename = "gasUsedRatio")]
+	pub gas_used_ratio: Vec<f64>,
+	/// An array of effective priority fee per gas data points from a single block. All zeroes
+	/// are returned if the block is empty.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Account state override
+///
+/// Overrides applied to an account before a call is simulated. `state` replaces the whole
+/// storage of the account, while `stateDiff` only replaces the given slots. At most one of them
+/// can be set.
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct StateOverride {
+	/// Fake balance to set for the account before executing the call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<U256>,
+	/// Fake nonce to set for the account before executing the call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<U256>,
+	/// Fake code to inject into the account before executing the call.
+	///
+	/// Contracts are PolkaVM programs: EVM bytecode is not supported.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// Fake key-value mapping to override all slots in the account storage before executing the
+	/// call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Fake key-value mapping to override individual slots in the account storage before
+	/// executing the call.
+	#[serde(rename = "stateDiff", skip_serializing_if = "Option::is_none")]
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// State override set
+///
+/// The overrides to apply, keyed by account address.
+pub type StateOverrideSet = BTreeMap<Address, StateOverride>;
@@ -320,3 +320,105 @@ impl GenericTransaction {
 		}
 	}
 }
+
+impl Filter {
+	/// Returns `true` if the log matches the address and topics of this filter.
+	///
+	/// The block range and block hash criteria are not checked, the caller is responsible for
+	/// only matching logs from the relevant blocks.
+	pub fn matches(&self, log: &Log) -> bool {
+		let address_matches = match &self.address {
+			None => true,
+			Some(AddressOrAddresses::Address(addr)) => *addr == log.address,
+			Some(AddressOrAddresses::Addresses(addrs)) =>
+				addrs.is_empty() || addrs.contains(&log.address),
+		};
+
+		if !address_matches {
+			return false;
+		}
+
+		let Some(topics) = &self.topics else { return true };
+		topics.iter().enumerate().all(|(i, topic)| match topic {
+			FilterTopic::Single(hash) => log.topics.get(i) == Some(hash),
+			FilterTopic::Multiple(hashes) =>
+				hashes.is_empty() || log.topics.get(i).map_or(false, |t| hashes.contains(t)),
+		})
+	}
+}
+
+#[test]
+fn filter_matches_works() {
+	let log = Log {
+		address: H160::from([1u8; 20]),
+		topics: vec![H256::from([1u8; 32]), H256::from([2u8; 32])],
+		..Default::default()
+	};
+
+	let cases = [
+		("empty filter", Filter::default(), true),
+		(
+			"single address",
+			Filter { address: Some(log.address.into()), ..Default::default() },
+			true,
+		),
+		(
+			"other address",
+			Filter { address: Some(H160::from([2u8; 20]).into()), ..Default::default() },
+			false,
+		),
+		(
+			"multiple addresses",
+			Filter {
+				address: Some(vec![H160::from([2u8; 20]), log.address].into()),
+				..Default::default()
+			},
+			true,
+		),
+		(
+			"single topic",
+			Filter { topics: Some(vec![FilterTopic::Single(log.topics[0])]), ..Default::default() },
+			true,
+		),
+		(
+			"wildcard topic",
+			Filter {
+				topics: Some(vec![
+					FilterTopic::Multiple(vec![]),
+					FilterTopic::Single(log.topics[1]),
+				]),
+				..Default::default()
+			},
+			true,
+		),
+		(
+			"multiple topics",
+			Filter {
+				topics: Some(vec![FilterTopic::Multiple(vec![H256::zero(), log.topics[0]])]),
+				..Default::default()
+			},
+			true,
+		),
+		(
+			"topic mismatch",
+			Filter { topics: Some(vec![FilterTopic::Single(log.topics[1])]), ..Default::default() },
+			false,
+		),
+		(
+			"too many topics",
+			Filter {
+				topics: Some(vec![
+					FilterTopic::Single(log.topics[0]),
+					FilterTopic::Single(log.topics[1]),
+					FilterTopic::Single(H256::zero()),
+				]),
+				..Default::default()
+			},
+			false,
+		),
+	];
+
+	for (name, filter, expected) in cases {
+		assert_eq!(filter.matches(&log), expected, "{}", name);
+	}
+}
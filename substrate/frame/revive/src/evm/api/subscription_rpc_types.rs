@@ -0,0 +1,61 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Types used by the `eth_subscribe` JSON-RPC method.
+use super::{Block, Log};
+use alloc::boxed::Box;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+
+/// The kind of subscription requested with `eth_subscribe`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub enum SubscriptionKind {
+	/// Subscribe to the headers of new blocks added to the best chain.
+	#[serde(rename = "newHeads")]
+	NewHeads,
+	/// Subscribe to the logs emitted in new blocks, optionally matching a
+	/// [`Filter`](super::Filter).
+	#[serde(rename = "logs")]
+	Logs,
+	/// Subscribe to the hashes of the transactions submitted to the pool.
+	#[serde(rename = "newPendingTransactions")]
+	NewPendingTransactions,
+}
+
+/// An item sent to the subscribers of `eth_subscribe`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum SubscriptionItem {
+	/// A new block header, sent to [`SubscriptionKind::NewHeads`] subscribers.
+	Header(Box<Block>),
+	/// A log, sent to [`SubscriptionKind::Logs`] subscribers.
+	Log(Log),
+	/// A transaction hash, sent to [`SubscriptionKind::NewPendingTransactions`] subscribers.
+	TransactionHash(H256),
+}
+
+#[test]
+fn can_deserialize_subscription_kind() {
+	let cases = [
+		(r#""newHeads""#, SubscriptionKind::NewHeads),
+		(r#""logs""#, SubscriptionKind::Logs),
+		(r#""newPendingTransactions""#, SubscriptionKind::NewPendingTransactions),
+	];
+
+	for (json, expected) in cases {
+		assert_eq!(serde_json::from_str::<SubscriptionKind>(json).unwrap(), expected, "{}", json);
+	}
+}
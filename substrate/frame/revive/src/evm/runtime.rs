@@ -306,8 +306,18 @@ pub trait EthExtra {
 		})?;
 
 		let signer = <Self::Config as Config>::AddressMapper::to_fallback_account_id(&signer);
-		let GenericTransaction { nonce, chain_id, to, value, input, gas, gas_price, .. } =
-			GenericTransaction::from_signed(tx, None);
+		let GenericTransaction {
+			nonce,
+			chain_id,
+			to,
+			value,
+			input,
+			gas,
+			gas_price,
+			access_list,
+			..
+		} = GenericTransaction::from_signed(tx, None);
+		let access_list = access_list.unwrap_or_default();
 
 		let Some(gas) = gas else {
 			log::debug!(target: LOG_TARGET, "No gas provided");
@@ -337,12 +347,23 @@ pub trait EthExtra {
 			})?;
 
 		let call = if let Some(dest) = to {
-			crate::Call::call::<Self::Config> {
-				dest,
-				value,
-				gas_limit,
-				storage_deposit_limit,
-				data,
+			if access_list.is_empty() {
+				crate::Call::call::<Self::Config> {
+					dest,
+					value,
+					gas_limit,
+					storage_deposit_limit,
+					data,
+				}
+			} else {
+				crate::Call::call_with_access_list::<Self::Config> {
+					dest,
+					value,
+					gas_limit,
+					storage_deposit_limit,
+					data,
+					access_list,
+				}
 			}
 		} else {
 			let blob = match polkavm::ProgramBlob::blob_length(&data) {
@@ -356,13 +377,24 @@ pub trait EthExtra {
 				return Err(InvalidTransaction::Call);
 			};
 
-			crate::Call::instantiate_with_code::<Self::Config> {
-				value,
-				gas_limit,
-				storage_deposit_limit,
-				code: code.to_vec(),
-				data: data.to_vec(),
-				salt: None,
+			if access_list.is_empty() {
+				crate::Call::instantiate_with_code::<Self::Config> {
+					value,
+					gas_limit,
+					storage_deposit_limit,
+					code: code.to_vec(),
+					data: data.to_vec(),
+					salt: None,
+				}
+			} else {
+				crate::Call::instantiate_with_code_and_access_list::<Self::Config> {
+					value,
+					gas_limit,
+					storage_deposit_limit,
+					code: code.to_vec(),
+					data: data.to_vec(),
+					access_list,
+				}
 			}
 		};
 
@@ -588,6 +620,33 @@ mod test {
 		);
 	}
 
+	#[test]
+	fn check_eth_transact_call_with_access_list_works() {
+		let access_list = vec![AccessListEntry {
+			address: H160::from([1u8; 20]),
+			storage_keys: vec![H256::from([2u8; 32])],
+		}];
+		let mut builder = UncheckedExtrinsicBuilder::call_with(H160::from([1u8; 20]));
+		builder.tx.r#type = Some(TypeEip2930.as_byte());
+		builder.tx.access_list = Some(access_list.clone());
+		let (call, _, tx) = builder.check().unwrap();
+		let (gas_limit, storage_deposit_limit) =
+			<<Test as Config>::EthGasEncoder as GasEncoder<_>>::decode(tx.gas.unwrap()).unwrap();
+
+		assert_eq!(
+			call,
+			crate::Call::call_with_access_list::<Test> {
+				dest: tx.to.unwrap(),
+				value: tx.value.unwrap_or_default().as_u64(),
+				data: tx.input.to_vec(),
+				gas_limit,
+				storage_deposit_limit,
+				access_list,
+			}
+			.into()
+		);
+	}
+
 	#[test]
 	fn check_eth_transact_instantiate_works() {
 		let (code, _) = compile_module("dummy").unwrap();
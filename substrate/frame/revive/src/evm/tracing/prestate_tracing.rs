@@ -0,0 +1,222 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	evm::{Bytes, PrestateTrace, PrestateTraceInfo, PrestateTracerConfig},
+	exec::Key,
+	primitives::ExecReturnValue,
+	tracing::Tracer,
+	AddressMapper, BalanceOf, Config, ContractInfoOf, DispatchError, MomentOf, Pallet,
+	PristineCode, Weight,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::marker::PhantomData;
+use frame_system::Pallet as System;
+use sp_core::{H160, H256, U256};
+use sp_runtime::{
+	traits::{Bounded, Zero},
+	SaturatedConversion,
+};
+
+/// A Tracer that reports the state of the accounts touched by a transaction.
+///
+/// The state of an account is captured when it is first accessed. In diff mode, the state of
+/// the touched accounts is read again when the trace is collected, and only the modified
+/// accounts, fields and storage slots are reported.
+pub struct PrestateTracer<T> {
+	/// The tracer configuration.
+	config: PrestateTracerConfig,
+	/// Stack of the addresses of the contracts executing in the active calls.
+	calls: Vec<H160>,
+	/// The state of the touched accounts at the time they were first accessed.
+	pre: BTreeMap<H160, PrestateTraceInfo>,
+	/// The storage keys accessed for each account, used to read their values in diff mode.
+	storage_keys: BTreeMap<H160, BTreeMap<Bytes, Key>>,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config> PrestateTracer<T>
+where
+	BalanceOf<T>: Into<U256> + TryFrom<U256> + Bounded,
+	MomentOf<T>: Into<U256>,
+	T::Hash: frame_support::traits::IsType<H256>,
+{
+	/// Create a new [`PrestateTracer`] instance.
+	pub fn new(config: PrestateTracerConfig) -> Self {
+		Self {
+			config,
+			calls: Vec::new(),
+			pre: BTreeMap::new(),
+			storage_keys: BTreeMap::new(),
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Collect the trace and reset the tracer.
+	///
+	/// Returns `None` if no account was touched.
+	pub fn collect_trace(&mut self) -> Option<PrestateTrace> {
+		self.calls.clear();
+		let pre = core::mem::take(&mut self.pre);
+		let storage_keys = core::mem::take(&mut self.storage_keys);
+
+		if pre.is_empty() {
+			return None;
+		}
+
+		if !self.config.diff_mode {
+			return Some(PrestateTrace::Prestate(pre));
+		}
+
+		let mut diff_pre = BTreeMap::new();
+		let mut diff_post = BTreeMap::new();
+		for (address, mut pre_info) in pre {
+			let mut post_info = self.account_info(&address);
+			for (key, raw_key) in storage_keys.get(&address).into_iter().flatten() {
+				post_info.storage.insert(key.clone(), Self::read_storage(&address, raw_key));
+			}
+
+			if pre_info == post_info {
+				continue;
+			}
+
+			pre_info.storage.retain(|key, value| post_info.storage.get(key) != Some(value));
+			post_info
+				.storage
+				.retain(|key, value| value.is_some() && pre_info.storage.contains_key(key));
+
+			if post_info.balance == pre_info.balance {
+				post_info.balance = None;
+			}
+			if post_info.nonce == pre_info.nonce {
+				post_info.nonce = None;
+			}
+			if post_info.code == pre_info.code {
+				post_info.code = None;
+			}
+
+			if !Self::is_empty(&pre_info) {
+				diff_pre.insert(address, pre_info);
+			}
+			if post_info != Default::default() {
+				diff_post.insert(address, post_info);
+			}
+		}
+
+		Some(PrestateTrace::DiffMode { pre: diff_pre, post: diff_post })
+	}
+
+	/// Read the current state of the account at `address`, without its storage.
+	fn account_info(&self, address: &H160) -> PrestateTraceInfo {
+		let account_id = T::AddressMapper::to_account_id(address);
+		let nonce: u32 = System::<T>::account_nonce(&account_id).saturated_into();
+		let code = if self.config.disable_code {
+			None
+		} else {
+			ContractInfoOf::<T>::get(address)
+				.and_then(|info| PristineCode::<T>::get(info.code_hash))
+				.map(|code| Bytes(code.into_inner()))
+		};
+
+		PrestateTraceInfo {
+			balance: Some(Pallet::<T>::evm_balance(address)),
+			nonce: if nonce.is_zero() { None } else { Some(nonce) },
+			code,
+			storage: Default::default(),
+		}
+	}
+
+	/// Read the current value of the given storage `key` of the contract at `address`.
+	fn read_storage(address: &H160, key: &Key) -> Option<Bytes> {
+		ContractInfoOf::<T>::get(address).and_then(|info| info.read(key)).map(Bytes)
+	}
+
+	/// Whether the account did not exist.
+	fn is_empty(info: &PrestateTraceInfo) -> bool {
+		info.balance.unwrap_or_default().is_zero() &&
+			info.nonce.is_none() &&
+			info.code.is_none() &&
+			info.storage.values().all(Option::is_none)
+	}
+
+	/// Record the value of a storage slot of the current contract, if not already recorded.
+	fn record_storage(&mut self, key: &Key, value: Option<&[u8]>) {
+		if self.config.disable_storage {
+			return;
+		}
+
+		let Some(address) = self.calls.last() else { return };
+		let raw_key = Bytes(key.unhashed().to_vec());
+		self.pre
+			.entry(*address)
+			.or_default()
+			.storage
+			.entry(raw_key.clone())
+			.or_insert_with(|| value.map(|value| Bytes(value.to_vec())));
+		self.storage_keys
+			.entry(*address)
+			.or_default()
+			.entry(raw_key)
+			.or_insert_with(|| key.clone());
+	}
+}
+
+impl<T: Config> Tracer for PrestateTracer<T>
+where
+	BalanceOf<T>: Into<U256> + TryFrom<U256> + Bounded,
+	MomentOf<T>: Into<U256>,
+	T::Hash: frame_support::traits::IsType<H256>,
+{
+	fn enter_child_span(
+		&mut self,
+		from: H160,
+		to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		_value: U256,
+		_input: &[u8],
+		_gas: Weight,
+	) {
+		self.watch_address(&from);
+		self.watch_address(&to);
+		self.calls.push(to);
+	}
+
+	fn log_event(&mut self, _address: H160, _topics: &[H256], _data: &[u8]) {}
+
+	fn exit_child_span(&mut self, _output: &ExecReturnValue, _gas_used: Weight) {
+		self.calls.pop();
+	}
+
+	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_used: Weight) {
+		self.calls.pop();
+	}
+
+	fn storage_read(&mut self, key: &Key, value: Option<&[u8]>) {
+		self.record_storage(key, value);
+	}
+
+	fn storage_write(&mut self, key: &Key, old_value: Option<&[u8]>, _new_value: Option<&[u8]>) {
+		self.record_storage(key, old_value);
+	}
+
+	fn watch_address(&mut self, address: &H160) {
+		if !self.pre.contains_key(address) {
+			let info = self.account_info(address);
+			self.pre.insert(*address, info);
+		}
+	}
+}
@@ -0,0 +1,213 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	evm::{Bytes, StructLog, StructLoggerConfig, StructLoggerTrace},
+	exec::Key,
+	primitives::ExecReturnValue,
+	tracing::Tracer,
+	DispatchError, Weight,
+};
+use alloc::{collections::BTreeMap, format, string::ToString, vec::Vec};
+use sp_core::{H160, H256, U256};
+
+/// A Tracer that reports the execution of a transaction step by step.
+///
+/// Each executed instruction is logged with its program counter and the gas left before its
+/// execution. Instructions invoking a syscall are annotated with the name of the syscall and
+/// the storage of the executing contract touched so far.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct StructLogger<Gas, GasMapper> {
+	/// The tracer configuration.
+	config: StructLoggerConfig,
+	/// Map Weight to Gas equivalent.
+	gas_mapper: GasMapper,
+	/// The logged steps.
+	struct_logs: Vec<StructLog<Gas>>,
+	/// The state of each active call.
+	calls: Vec<CallState>,
+	/// The logged step and gas left of each syscall in progress.
+	///
+	/// The step is `None` when the limit of logged steps was reached.
+	syscalls: Vec<(Option<usize>, Weight)>,
+	/// The trace of the top level call, once it has completed.
+	trace: Option<StructLoggerTrace<Gas>>,
+}
+
+/// The state of an active call of a [`StructLogger`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct CallState {
+	/// The storage slots of the executing contract touched so far.
+	storage: BTreeMap<Bytes, Bytes>,
+	/// The last logged step of the call and the gas left before it, if its cost is not known yet.
+	pending_step: Option<(usize, Weight)>,
+}
+
+impl<Gas: Default, GasMapper: Fn(Weight) -> Gas> StructLogger<Gas, GasMapper> {
+	/// Create a new [`StructLogger`] instance.
+	pub fn new(config: StructLoggerConfig, gas_mapper: GasMapper) -> Self {
+		Self {
+			config,
+			gas_mapper,
+			struct_logs: Vec::new(),
+			calls: Vec::new(),
+			syscalls: Vec::new(),
+			trace: None,
+		}
+	}
+
+	/// Collect the trace and reset the tracer.
+	///
+	/// Returns `None` if no call was traced.
+	pub fn collect_trace(&mut self) -> Option<StructLoggerTrace<Gas>> {
+		self.calls.clear();
+		self.syscalls.clear();
+		let struct_logs = core::mem::take(&mut self.struct_logs);
+		self.trace.take().map(|trace| StructLoggerTrace { struct_logs, ..trace })
+	}
+
+	/// Log a new step of the current call, settling the cost of its previous step.
+	///
+	/// Returns the index of the logged step, or `None` if the limit of logged steps was reached.
+	fn log_step(&mut self, pc: u32, gas_left: Weight, syscall: Option<&str>) -> Option<usize> {
+		let depth = self.calls.len() as u32;
+		let call = self.calls.last_mut()?;
+		if let Some((index, gas_before)) = call.pending_step.take() {
+			self.struct_logs[index].gas_cost =
+				(self.gas_mapper)(gas_before.saturating_sub(gas_left));
+		}
+
+		if self.config.limit != 0 && self.struct_logs.len() >= self.config.limit as usize {
+			return None;
+		}
+
+		self.struct_logs.push(StructLog {
+			pc,
+			gas: (self.gas_mapper)(gas_left),
+			depth,
+			syscall: syscall.map(ToString::to_string),
+			..Default::default()
+		});
+		Some(self.struct_logs.len() - 1)
+	}
+
+	/// Record a storage slot touched by the current call.
+	fn record_storage(&mut self, key: &Key, value: Option<&[u8]>) {
+		if self.config.disable_storage {
+			return;
+		}
+
+		let Some(call) = self.calls.last_mut() else { return };
+		call.storage
+			.insert(Bytes(key.unhashed().to_vec()), Bytes(value.unwrap_or_default().to_vec()));
+
+		if let Some((Some(index), _)) = self.syscalls.last() {
+			self.struct_logs[*index].storage = Some(call.storage.clone());
+		}
+	}
+
+	/// Complete the current call, recording the trace if it is the top level one.
+	fn exit_call(&mut self, gas_used: Weight, output: Option<&ExecReturnValue>) {
+		self.calls.pop();
+		if !self.calls.is_empty() {
+			return;
+		}
+
+		self.trace = Some(StructLoggerTrace {
+			gas: (self.gas_mapper)(gas_used),
+			failed: output.map_or(true, |output| output.did_revert()),
+			return_value: output.map(|output| Bytes(output.data.clone())).unwrap_or_default(),
+			struct_logs: Vec::new(),
+		});
+	}
+}
+
+impl<Gas: Default, GasMapper: Fn(Weight) -> Gas> Tracer for StructLogger<Gas, GasMapper> {
+	fn enter_child_span(
+		&mut self,
+		_from: H160,
+		_to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		_value: U256,
+		_input: &[u8],
+		_gas: Weight,
+	) {
+		self.calls.push(CallState::default());
+	}
+
+	fn log_event(&mut self, _address: H160, _topics: &[H256], _data: &[u8]) {}
+
+	fn exit_child_span(&mut self, output: &ExecReturnValue, gas_used: Weight) {
+		self.exit_call(gas_used, Some(output));
+	}
+
+	fn exit_child_span_with_error(&mut self, error: DispatchError, gas_used: Weight) {
+		let depth = self.calls.len() as u32;
+		if let Some(log) = self.struct_logs.iter_mut().rev().find(|log| log.depth == depth) {
+			log.error = match error {
+				DispatchError::Module(sp_runtime::ModuleError { message, .. }) =>
+					Some(message.unwrap_or_default().to_string()),
+				_ => Some(format!("{:?}", error)),
+			};
+		}
+		self.exit_call(gas_used, None);
+	}
+
+	fn storage_read(&mut self, key: &Key, value: Option<&[u8]>) {
+		self.record_storage(key, value);
+	}
+
+	fn storage_write(&mut self, key: &Key, _old_value: Option<&[u8]>, new_value: Option<&[u8]>) {
+		self.record_storage(key, new_value);
+	}
+
+	fn trace_steps(&self) -> bool {
+		!self.config.syscalls_only
+	}
+
+	fn step(&mut self, pc: u32, gas_left: Weight) {
+		let index = self.log_step(pc, gas_left, None);
+		if let Some(call) = self.calls.last_mut() {
+			call.pending_step = index.map(|index| (index, gas_left));
+		}
+	}
+
+	fn enter_syscall(&mut self, pc: u32, name: &str, gas_left: Weight) {
+		// The instruction invoking the syscall was already logged by `step`.
+		let struct_logs = &self.struct_logs;
+		let pending_step = self.calls.last_mut().and_then(|call| match call.pending_step {
+			Some((index, _)) if struct_logs[index].pc == pc => call.pending_step.take(),
+			_ => None,
+		});
+
+		let syscall = match pending_step {
+			Some((index, gas_before)) => {
+				self.struct_logs[index].syscall = Some(name.to_string());
+				(Some(index), gas_before)
+			},
+			None => (self.log_step(pc, gas_left, Some(name)), gas_left),
+		};
+		self.syscalls.push(syscall);
+	}
+
+	fn exit_syscall(&mut self, gas_left: Weight) {
+		if let Some((Some(index), gas_before)) = self.syscalls.pop() {
+			self.struct_logs[index].gas_cost =
+				(self.gas_mapper)(gas_before.saturating_sub(gas_left));
+		}
+	}
+}
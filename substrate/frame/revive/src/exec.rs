@@ -19,10 +19,11 @@ use crate::{
 	address::{self, AddressMapper},
 	gas::GasMeter,
 	limits,
+	precompiles::{self, is_precompile},
 	primitives::{ExecReturnValue, StorageDeposit},
-	pure_precompiles::{self, is_precompile},
+	pure_precompiles,
 	runtime_decl_for_revive_api::{Decode, Encode, RuntimeDebugNoBound, TypeInfo},
-	storage::{self, meter::Diff, WriteOutcome},
+	storage::{self, meter::Diff, AccessListState, WriteOutcome},
 	tracing::if_tracing,
 	transient_storage::TransientStorage,
 	BalanceOf, CodeInfo, CodeInfoOf, Config, ContractInfo, ContractInfoOf, ConversionPrecision,
@@ -74,6 +75,7 @@ pub const EMPTY_CODE_HASH: H256 =
 	H256(sp_core::hex2array!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"));
 
 /// Combined key type for both fixed and variable sized storage keys.
+#[derive(Clone)]
 pub enum Key {
 	/// Variant for fixed sized keys.
 	Fix([u8; 32]),
@@ -83,11 +85,6 @@ pub enum Key {
 
 impl Key {
 	/// Reference to the raw unhashed key.
-	///
-	/// # Note
-	///
-	/// Only used by benchmarking in order to generate storage collisions on purpose.
-	#[cfg(feature = "runtime-benchmarks")]
 	pub fn unhashed(&self) -> &[u8] {
 		match self {
 			Key::Fix(v) => v.as_ref(),
@@ -266,6 +263,13 @@ pub trait Ext: sealing::Sealed {
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError>;
 
+	/// Returns `true` if the storage entry of the executing account for the given `key` is in
+	/// the access list of the current transaction.
+	fn is_warm_storage_key(&self, key: &Key) -> bool;
+
+	/// Returns `true` if the given `address` is in the access list of the current transaction.
+	fn is_warm_address(&self, address: &H160) -> bool;
+
 	/// Returns the transient storage entry of the executing account for the given `key`.
 	///
 	/// Returns `None` if the `key` wasn't previously set by `set_transient_storage` or
@@ -517,6 +521,9 @@ pub struct Stack<'a, T: Config, E> {
 	/// Whether or not actual transfer of funds should be performed.
 	/// This is set to `true` exclusively when we simulate a call through eth_transact.
 	skip_transfer: bool,
+	/// The addresses and storage keys of the access list of the Ethereum transaction being
+	/// executed, if any.
+	access_list: AccessListState,
 	/// No executable is held by the struct but influences its behaviour.
 	_phantom: PhantomData<E>,
 }
@@ -740,7 +747,7 @@ where
 		)? {
 			stack.run(executable, input_data).map(|_| stack.first_frame.last_frame_output)
 		} else {
-			let result = Self::transfer_from_origin(&origin, &origin, &dest, value);
+			// The span is entered before the transfer, so tracers see the balances before it.
 			if_tracing(|t| {
 				t.enter_child_span(
 					origin.account_id().map(T::AddressMapper::to_address).unwrap_or_default(),
@@ -750,11 +757,12 @@ where
 					value,
 					&input_data,
 					Weight::zero(),
-				);
-				match result {
-					Ok(ref output) => t.exit_child_span(&output, Weight::zero()),
-					Err(e) => t.exit_child_span_with_error(e.error.into(), Weight::zero()),
-				}
+				)
+			});
+			let result = Self::transfer_from_origin(&origin, &origin, &dest, value);
+			if_tracing(|t| match result {
+				Ok(ref output) => t.exit_child_span(&output, Weight::zero()),
+				Err(e) => t.exit_child_span_with_error(e.error.into(), Weight::zero()),
 			});
 
 			result
@@ -847,6 +855,11 @@ where
 			return Ok(None);
 		};
 
+		let access_list = crate::eth_access_list::with(|access_list| {
+			AccessListState::from_access_list(access_list)
+		})
+		.unwrap_or_default();
+
 		let stack = Self {
 			origin,
 			gas_meter,
@@ -857,6 +870,7 @@ where
 			frames: Default::default(),
 			transient_storage: TransientStorage::new(limits::TRANSIENT_STORAGE_BYTES),
 			skip_transfer,
+			access_list,
 			_phantom: Default::default(),
 		};
 
@@ -1404,12 +1418,32 @@ where
 				)?;
 			}
 
-			pure_precompiles::Precompiles::<T>::execute(
-				precompile_address,
+			if pure_precompiles::is_precompile(&precompile_address) {
+				return pure_precompiles::Precompiles::<T>::execute(
+					precompile_address,
+					self.gas_meter_mut(),
+					input_data,
+				)
+				.map_err(|e| ExecError { error: e.error, origin: ErrorOrigin::Callee });
+			}
+
+			if is_delegate {
+				return Err(Error::<T>::PrecompileDelegateDenied.into());
+			}
+
+			let caller = Origin::from_account_id(self.account_id().clone());
+			let mut env = precompiles::Environment::new(
 				self.gas_meter_mut(),
+				caller,
+				precompile_address,
+				is_read_only,
+			);
+			<T::Precompiles as precompiles::Precompile<T>>::call(
+				&precompile_address,
 				input_data,
+				&mut env,
 			)
-			.map_err(|e| ExecError { error: e.error, origin: ErrorOrigin::Callee })
+			.map_err(|error| ExecError { error, origin: ErrorOrigin::Callee })
 		};
 
 		let transaction_outcome =
@@ -1482,7 +1516,7 @@ where
 			// Enable read-only access if requested; cannot disable it if already set.
 			let is_read_only = read_only || self.is_read_only();
 
-			if is_precompile(dest_addr) {
+			if is_precompile::<T>(dest_addr) {
 				return self.run_precompile(*dest_addr, false, is_read_only, value, &input_data);
 			}
 
@@ -1513,6 +1547,18 @@ where
 			)? {
 				self.run(executable, input_data)
 			} else {
+				if_tracing(|t| {
+					t.enter_child_span(
+						T::AddressMapper::to_address(self.account_id()),
+						T::AddressMapper::to_address(&dest),
+						false,
+						is_read_only,
+						value,
+						&input_data,
+						Weight::zero(),
+					)
+				});
+
 				let result = if is_read_only && value.is_zero() {
 					Ok(Default::default())
 				} else if is_read_only {
@@ -1526,20 +1572,9 @@ where
 					)
 				};
 
-				if_tracing(|t| {
-					t.enter_child_span(
-						T::AddressMapper::to_address(self.account_id()),
-						T::AddressMapper::to_address(&dest),
-						false,
-						is_read_only,
-						value,
-						&input_data,
-						Weight::zero(),
-					);
-					match result {
-						Ok(ref output) => t.exit_child_span(&output, Weight::zero()),
-						Err(e) => t.exit_child_span_with_error(e.error.into(), Weight::zero()),
-					}
+				if_tracing(|t| match result {
+					Ok(ref output) => t.exit_child_span(&output, Weight::zero()),
+					Err(e) => t.exit_child_span_with_error(e.error.into(), Weight::zero()),
 				});
 				result.map(|_| ())
 			}
@@ -1561,7 +1596,7 @@ where
 		address: H160,
 		input_data: Vec<u8>,
 	) -> Result<(), ExecError> {
-		if is_precompile(&address) {
+		if is_precompile::<T>(&address) {
 			return self.run_precompile(
 				address,
 				true,
@@ -1654,11 +1689,15 @@ where
 	}
 
 	fn get_storage(&mut self, key: &Key) -> Option<Vec<u8>> {
-		self.top_frame_mut().contract_info().read(key)
+		let value = self.top_frame_mut().contract_info().read(key);
+		if_tracing(|tracer| tracer.storage_read(key, value.as_deref()));
+		value
 	}
 
 	fn get_storage_size(&mut self, key: &Key) -> Option<u32> {
-		self.top_frame_mut().contract_info().size(key.into())
+		let contract_info = self.top_frame_mut().contract_info();
+		if_tracing(|tracer| tracer.storage_read(key, contract_info.read(key).as_deref()));
+		contract_info.size(key.into())
 	}
 
 	fn set_storage(
@@ -1668,12 +1707,20 @@ where
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError> {
 		let frame = self.top_frame_mut();
-		frame.contract_info.get(&frame.account_id).write(
-			key.into(),
-			value,
-			Some(&mut frame.nested_storage),
-			take_old,
-		)
+		let contract_info = frame.contract_info.get(&frame.account_id);
+		if_tracing(|tracer| {
+			tracer.storage_write(key, contract_info.read(key).as_deref(), value.as_deref())
+		});
+		contract_info.write(key.into(), value, Some(&mut frame.nested_storage), take_old)
+	}
+
+	fn is_warm_storage_key(&self, key: &Key) -> bool {
+		let address = T::AddressMapper::to_address(self.account_id());
+		self.access_list.is_warm_storage_key(&address, key)
+	}
+
+	fn is_warm_address(&self, address: &H160) -> bool {
+		self.access_list.is_warm_address(address)
 	}
 
 	fn get_transient_storage(&self, key: &Key) -> Option<Vec<u8>> {
@@ -1724,6 +1771,7 @@ where
 	}
 
 	fn code_hash(&self, address: &H160) -> H256 {
+		if_tracing(|tracer| tracer.watch_address(address));
 		<ContractInfoOf<T>>::get(&address)
 			.map(|contract| contract.code_hash)
 			.unwrap_or_else(|| {
@@ -1735,6 +1783,7 @@ where
 	}
 
 	fn code_size(&self, address: &H160) -> u64 {
+		if_tracing(|tracer| tracer.watch_address(address));
 		<ContractInfoOf<T>>::get(&address)
 			.and_then(|contract| CodeInfoOf::<T>::get(contract.code_hash))
 			.map(|info| info.code_len())
@@ -1788,6 +1837,7 @@ where
 	}
 
 	fn balance_of(&self, address: &H160) -> U256 {
+		if_tracing(|tracer| tracer.watch_address(address));
 		self.account_balance(&<Self::T as Config>::AddressMapper::to_account_id(address))
 	}
 
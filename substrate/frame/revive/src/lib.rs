@@ -37,15 +37,19 @@ mod tests;
 
 pub mod chain_extension;
 pub mod evm;
+pub mod precompiles;
 pub mod test_utils;
 pub mod tracing;
 pub mod weights;
 
 use crate::{
-	evm::{runtime::GAS_PRICE, CallTrace, GasEncoder, GenericTransaction, TracerConfig},
-	exec::{AccountIdOf, ExecError, Executable, Key, Stack as ExecStack},
+	evm::{
+		runtime::GAS_PRICE, AccessList, CallTrace, EvmTracer, GasEncoder, GenericTransaction,
+		StateOverride, StateOverrideSet, Trace, TracerConfig,
+	},
+	exec::{AccountIdOf, ExecError, Executable, Stack as ExecStack},
 	gas::GasMeter,
-	storage::{meter::Meter as StorageMeter, ContractInfo, DeletionQueueManager},
+	storage::{meter::Meter as StorageMeter, AccessListState, ContractInfo, DeletionQueueManager},
 	wasm::{CodeInfo, RuntimeCosts, WasmBlob},
 };
 use alloc::{boxed::Box, format, vec};
@@ -58,6 +62,7 @@ use frame_support::{
 	},
 	ensure,
 	pallet_prelude::DispatchClass,
+	storage::{with_transaction_opaque_err, TransactionOutcome},
 	traits::{
 		fungible::{Inspect, Mutate, MutateHold},
 		tokens::{Fortitude::Polite, Preservation::Preserve},
@@ -80,7 +85,7 @@ use sp_runtime::{
 
 pub use crate::{
 	address::{create1, create2, is_eth_derived, AccountId32Mapper, AddressMapper},
-	exec::{MomentOf, Origin},
+	exec::{Key, MomentOf, Origin},
 	pallet::*,
 };
 pub use primitives::*;
@@ -189,6 +194,13 @@ pub mod pallet {
 		#[pallet::no_default_bounds]
 		type ChainExtension: chain_extension::ChainExtension<Self> + Default;
 
+		/// Precompiles implemented by the runtime, mapped to fixed addresses.
+		///
+		/// Use a tuple to register multiple precompiles. See [`precompiles`] for the ready-made
+		/// handlers this pallet provides.
+		#[pallet::no_default_bounds]
+		type Precompiles: precompiles::Precompile<Self>;
+
 		/// Find the author of the current block.
 		type FindAuthor: FindAuthor<Self::AccountId>;
 
@@ -349,6 +361,7 @@ pub mod pallet {
 			type RuntimeCall = ();
 			type CallFilter = ();
 			type ChainExtension = ();
+			type Precompiles = ();
 			type CodeHashLockupDepositPercent = CodeHashLockupDepositPercent;
 			type DepositPerByte = DepositPerByte;
 			type DepositPerItem = DepositPerItem;
@@ -499,6 +512,8 @@ pub mod pallet {
 		UnsupportedPrecompileAddress = 0x2F,
 		/// Precompile Error
 		PrecompileFailure = 0x30,
+		/// Runtime precompiles can't be the target of a delegate call.
+		PrecompileDelegateDenied = 0x31,
 	}
 
 	/// A reason for the pallet contracts placing a hold on funds.
@@ -984,6 +999,105 @@ pub mod pallet {
 				T::AddressMapper::to_fallback_account_id(&T::AddressMapper::to_address(&origin));
 			call.dispatch(RawOrigin::Signed(unmapped_account).into())
 		}
+
+		/// Same as [`Self::call`], but with an [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930)
+		/// access list.
+		///
+		/// The addresses and storage keys of the `access_list` are warm for the whole execution,
+		/// and accessing them is charged at a discounted rate. The worst-case weight of accessing
+		/// them is charged upfront instead. Accessing any other item is charged in full, even if
+		/// it was already accessed.
+		///
+		/// This is the call an [`Self::eth_transact`] with a non-empty access list is converted
+		/// into.
+		#[pallet::call_index(10)]
+		#[pallet::weight(
+			T::WeightInfo::call_with_access_list(AccessListState::item_count(access_list))
+				.saturating_add(AccessListState::access_list_weight::<T>(access_list))
+				.saturating_add(*gas_limit)
+		)]
+		pub fn call_with_access_list(
+			origin: OriginFor<T>,
+			dest: H160,
+			#[pallet::compact] value: BalanceOf<T>,
+			gas_limit: Weight,
+			#[pallet::compact] storage_deposit_limit: BalanceOf<T>,
+			data: Vec<u8>,
+			mut access_list: AccessList,
+		) -> DispatchResultWithPostInfo {
+			let base_weight =
+				T::WeightInfo::call_with_access_list(AccessListState::item_count(&access_list))
+					.saturating_add(AccessListState::access_list_weight::<T>(&access_list));
+			let mut output = Self::with_access_list(&mut access_list, || {
+				Self::bare_call(
+					origin,
+					dest,
+					value,
+					gas_limit,
+					DepositLimit::Balance(storage_deposit_limit),
+					data,
+				)
+			});
+
+			if let Ok(return_value) = &output.result {
+				if return_value.did_revert() {
+					output.result = Err(<Error<T>>::ContractReverted.into());
+				}
+			}
+			dispatch_result(output.result, output.gas_consumed, base_weight)
+		}
+
+		/// Same as [`Self::instantiate_with_code`], but with an
+		/// [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) access list.
+		///
+		/// See [`Self::call_with_access_list`] for how the `access_list` is used.
+		#[pallet::call_index(11)]
+		#[pallet::weight(
+			T::WeightInfo::instantiate_with_code_and_access_list(
+				code.len() as u32,
+				data.len() as u32,
+				AccessListState::item_count(access_list),
+			)
+			.saturating_add(AccessListState::access_list_weight::<T>(access_list))
+			.saturating_add(*gas_limit)
+		)]
+		pub fn instantiate_with_code_and_access_list(
+			origin: OriginFor<T>,
+			#[pallet::compact] value: BalanceOf<T>,
+			gas_limit: Weight,
+			#[pallet::compact] storage_deposit_limit: BalanceOf<T>,
+			code: Vec<u8>,
+			data: Vec<u8>,
+			mut access_list: AccessList,
+		) -> DispatchResultWithPostInfo {
+			let base_weight = T::WeightInfo::instantiate_with_code_and_access_list(
+				code.len() as u32,
+				data.len() as u32,
+				AccessListState::item_count(&access_list),
+			)
+			.saturating_add(AccessListState::access_list_weight::<T>(&access_list));
+			let mut output = Self::with_access_list(&mut access_list, || {
+				Self::bare_instantiate(
+					origin,
+					value,
+					gas_limit,
+					DepositLimit::Balance(storage_deposit_limit),
+					Code::Upload(code),
+					data,
+					None,
+				)
+			});
+			if let Ok(retval) = &output.result {
+				if retval.result.did_revert() {
+					output.result = Err(<Error<T>>::ContractReverted.into());
+				}
+			}
+			dispatch_result(
+				output.result.map(|result| result.result),
+				output.gas_consumed,
+				base_weight,
+			)
+		}
 	}
 }
 
@@ -1182,6 +1296,7 @@ where
 		};
 
 		let input = tx.input.clone().to_vec();
+		let mut access_list = tx.access_list.clone().unwrap_or_default();
 
 		let extract_error = |err| {
 			if err == Error::<T>::TransferFailed.into() ||
@@ -1205,14 +1320,16 @@ where
 			// A contract call.
 			Some(dest) => {
 				// Dry run the call.
-				let result = crate::Pallet::<T>::bare_call(
-					T::RuntimeOrigin::signed(origin),
-					dest,
-					native_value,
-					gas_limit,
-					storage_deposit_limit,
-					input.clone(),
-				);
+				let result = Self::with_access_list(&mut access_list, || {
+					crate::Pallet::<T>::bare_call(
+						T::RuntimeOrigin::signed(origin),
+						dest,
+						native_value,
+						gas_limit,
+						storage_deposit_limit,
+						input.clone(),
+					)
+				});
 
 				let data = match result.result {
 					Ok(return_value) => {
@@ -1238,14 +1355,26 @@ where
 					result.gas_required,
 					result.storage_deposit,
 				);
-				let dispatch_call: <T as Config>::RuntimeCall = crate::Call::<T>::call {
-					dest,
-					value: native_value,
-					gas_limit,
-					storage_deposit_limit,
-					data: input.clone(),
-				}
-				.into();
+				let dispatch_call: <T as Config>::RuntimeCall = if access_list.is_empty() {
+					crate::Call::<T>::call {
+						dest,
+						value: native_value,
+						gas_limit,
+						storage_deposit_limit,
+						data: input.clone(),
+					}
+					.into()
+				} else {
+					crate::Call::<T>::call_with_access_list {
+						dest,
+						value: native_value,
+						gas_limit,
+						storage_deposit_limit,
+						data: input.clone(),
+						access_list,
+					}
+					.into()
+				};
 				(result, dispatch_call.get_dispatch_info())
 			},
 			// A contract deployment
@@ -1264,15 +1393,17 @@ where
 				};
 
 				// Dry run the call.
-				let result = crate::Pallet::<T>::bare_instantiate(
-					T::RuntimeOrigin::signed(origin),
-					native_value,
-					gas_limit,
-					storage_deposit_limit,
-					Code::Upload(code.to_vec()),
-					data.to_vec(),
-					None,
-				);
+				let result = Self::with_access_list(&mut access_list, || {
+					crate::Pallet::<T>::bare_instantiate(
+						T::RuntimeOrigin::signed(origin),
+						native_value,
+						gas_limit,
+						storage_deposit_limit,
+						Code::Upload(code.to_vec()),
+						data.to_vec(),
+						None,
+					)
+				});
 
 				let returned_data = match result.result {
 					Ok(return_value) => {
@@ -1299,7 +1430,7 @@ where
 					result.gas_required,
 					result.storage_deposit,
 				);
-				let dispatch_call: <T as Config>::RuntimeCall =
+				let dispatch_call: <T as Config>::RuntimeCall = if access_list.is_empty() {
 					crate::Call::<T>::instantiate_with_code {
 						value: native_value,
 						gas_limit,
@@ -1308,7 +1439,18 @@ where
 						data: data.to_vec(),
 						salt: None,
 					}
-					.into();
+					.into()
+				} else {
+					crate::Call::<T>::instantiate_with_code_and_access_list {
+						value: native_value,
+						gas_limit,
+						storage_deposit_limit,
+						code: code.to_vec(),
+						data: data.to_vec(),
+						access_list,
+					}
+					.into()
+				};
 				(result, dispatch_call.get_dispatch_info())
 			},
 		};
@@ -1329,6 +1471,119 @@ where
 		Ok(result)
 	}
 
+	/// Run `f` on top of the state modified by the given `overrides`.
+	///
+	/// The overrides and any state change made by `f` are applied in a storage transaction that
+	/// is always rolled back. This is used to dry-run Ethereum calls with a state override set, see
+	/// [`Self::bare_eth_transact`].
+	pub fn with_state_overrides<R>(
+		overrides: StateOverrideSet,
+		f: impl FnOnce() -> Result<R, EthTransactError>,
+	) -> Result<R, EthTransactError>
+	where
+		T::Nonce: TryFrom<U256>,
+	{
+		let result = with_transaction_opaque_err(|| {
+			let result = Self::apply_state_overrides(overrides).and_then(|_| f());
+			TransactionOutcome::Rollback(result)
+		});
+
+		result.unwrap_or_else(|_| {
+			Err(EthTransactError::Message("Failed to open a storage transaction".into()))
+		})
+	}
+
+	/// Write the given state `overrides` to storage.
+	///
+	/// A `code` override must be a PolkaVM program: it is uploaded like the code of any other
+	/// contract.
+	fn apply_state_overrides(overrides: StateOverrideSet) -> Result<(), EthTransactError>
+	where
+		T::Nonce: TryFrom<U256>,
+	{
+		for (address, state_override) in overrides {
+			let StateOverride { balance, nonce, code, state, state_diff } = state_override;
+			let account_id = T::AddressMapper::to_account_id(&address);
+
+			if let Some(balance) = balance {
+				let balance = Self::convert_evm_to_native(balance, ConversionPrecision::RoundUp)
+					.map_err(|_| {
+						EthTransactError::Message(format!(
+							"Invalid balance override for {address:?}"
+						))
+					})?;
+				T::Currency::set_balance(&account_id, balance);
+			}
+
+			if let Some(nonce) = nonce {
+				let nonce = nonce.try_into().map_err(|_| {
+					EthTransactError::Message(format!("Invalid nonce override for {address:?}"))
+				})?;
+				frame_system::Account::<T>::mutate(&account_id, |account| account.nonce = nonce);
+			}
+
+			if let Some(code) = code {
+				let (module, _) = Self::try_upload_code(
+					account_id.clone(),
+					code.0,
+					BalanceOf::<T>::max_value(),
+					true,
+				)
+				.map_err(|err| {
+					EthTransactError::Message(format!(
+						"Invalid code override for {address:?}: {err:?}"
+					))
+				})?;
+				let code_hash = *module.code_hash();
+				let _ = CodeInfo::<T>::increment_refcount(code_hash);
+
+				let contract = match ContractInfoOf::<T>::get(&address) {
+					Some(mut contract) => {
+						contract.code_hash = code_hash;
+						contract
+					},
+					None => {
+						let nonce = frame_system::Pallet::<T>::account_nonce(&account_id);
+						ContractInfo::<T>::new(&address, nonce, code_hash)
+							.map_err(|err| EthTransactError::Message(format!("{err:?}")))?
+					},
+				};
+				ContractInfoOf::<T>::insert(&address, contract);
+			}
+
+			let (slots, clear) = match (state, state_diff) {
+				(Some(_), Some(_)) =>
+					return Err(EthTransactError::Message(format!(
+						"Account {address:?} has both 'state' and 'stateDiff'"
+					))),
+				(Some(state), None) => (state, true),
+				(None, Some(state_diff)) => (state_diff, false),
+				(None, None) => continue,
+			};
+
+			let Some(contract) = ContractInfoOf::<T>::get(&address) else {
+				return Err(EthTransactError::Message(format!(
+					"Storage override for {address:?}, which is not a contract"
+				)));
+			};
+			if clear {
+				let _ = frame_support::storage::child::clear_storage(
+					&contract.child_trie_info(),
+					None,
+					None,
+				);
+			}
+			for (key, value) in slots {
+				let value = (!value.is_zero()).then(|| value.0.to_vec());
+				contract
+					.write(&Key::from_fixed(key.0), value, None, false)
+					.map_err(|err| EthTransactError::Message(format!("{err:?}")))?;
+			}
+		}
+
+		Ok(())
+	}
+
 	/// Get the balance with EVM decimals of the given `address`.
 	pub fn evm_balance(address: &H160) -> U256 {
 		let account = T::AddressMapper::to_account_id(&address);
@@ -1375,6 +1630,11 @@ where
 		GAS_PRICE.into()
 	}
 
+	/// Build an [`EvmTracer`] from the given tracer `config`.
+	pub fn evm_tracer(config: TracerConfig) -> EvmTracer<T> {
+		EvmTracer::new(config, Self::evm_gas_from_weight)
+	}
+
 	/// A generalized version of [`Self::upload_code`].
 	///
 	/// It is identical to [`Self::upload_code`] and only differs in the information it returns.
@@ -1410,6 +1670,12 @@ where
 		Ok((module, deposit))
 	}
 
+	/// Run the supplied function `f` with the addresses and storage keys of the `access_list`
+	/// pre-warmed. See [`Self::call_with_access_list`].
+	fn with_access_list<R>(access_list: &mut AccessList, f: impl FnOnce() -> R) -> R {
+		eth_access_list::using_once(access_list, f)
+	}
+
 	/// Run the supplied function `f` if no other instance of this pallet is on the stack.
 	fn run_guarded<R, F: FnOnce() -> Result<R, ExecError>>(f: F) -> Result<R, ExecError> {
 		executing_contract::using_once(&mut false, || {
@@ -1470,9 +1736,12 @@ impl<T: Config> Pallet<T> {
 // Set up a global reference to the boolean flag used for the re-entrancy guard.
 environmental!(executing_contract: bool);
 
+// Set up a global reference to the access list of the Ethereum transaction being executed.
+environmental!(eth_access_list: AccessList);
+
 sp_api::decl_runtime_apis! {
 	/// The API used to dry-run contract interactions.
-	#[api_version(1)]
+	#[api_version(2)]
 	pub trait ReviveApi<AccountId, Balance, Nonce, BlockNumber> where
 		AccountId: Codec,
 		Balance: Codec,
@@ -1522,6 +1791,15 @@ sp_api::decl_runtime_apis! {
 		/// See [`crate::Pallet::bare_eth_transact`]
 		fn eth_transact(tx: GenericTransaction) -> Result<EthTransactInfo<Balance>, EthTransactError>;
 
+		/// Perform an Ethereum call on top of the state modified by the given `overrides`.
+		///
+		/// See [`crate::Pallet::with_state_overrides`] and eth-rpc `eth_call` for usage.
+		#[api_version(3)]
+		fn eth_transact_with_overrides(
+			tx: GenericTransaction,
+			overrides: StateOverrideSet,
+		) -> Result<EthTransactInfo<Balance>, EthTransactError>;
+
 		/// Upload new code without instantiating a contract from it.
 		///
 		/// See [`crate::Pallet::bare_upload_code`].
@@ -1548,6 +1826,13 @@ sp_api::decl_runtime_apis! {
 		/// parent block.
 		///
 		/// See eth-rpc `debug_traceBlockByNumber` for usage.
+		fn trace_block(
+			block: Block,
+			config: TracerConfig
+		) -> Vec<(u32, Trace)>;
+
+		/// Traces the execution of an entire block, only supports the call tracer.
+		#[changed_in(2)]
 		fn trace_block(
 			block: Block,
 			config: TracerConfig
@@ -1559,6 +1844,15 @@ sp_api::decl_runtime_apis! {
 		/// parent hash up to the transaction.
 		///
 		/// See eth-rpc `debug_traceTransaction` for usage.
+		fn trace_tx(
+			block: Block,
+			tx_index: u32,
+			config: TracerConfig
+		) -> Option<Trace>;
+
+		/// Traces the execution of a specific transaction within a block, only supports the call
+		/// tracer.
+		#[changed_in(2)]
 		fn trace_tx(
 			block: Block,
 			tx_index: u32,
@@ -1568,7 +1862,14 @@ sp_api::decl_runtime_apis! {
 		/// Dry run and return the trace of the given call.
 		///
 		/// See eth-rpc `debug_traceCall` for usage.
-		fn trace_call(tx: GenericTransaction, config: TracerConfig) -> Result<CallTrace, EthTransactError>;
+		fn trace_call(tx: GenericTransaction, config: TracerConfig) -> Result<Trace, EthTransactError>;
+
+		/// Dry run and return the trace of the given call, only supports the call tracer.
+		#[changed_in(2)]
+		fn trace_call(
+			tx: GenericTransaction,
+			config: TracerConfig
+		) -> Result<CallTrace, EthTransactError>;
 
 	}
 }
@@ -0,0 +1,207 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A registry of precompiles implemented by the runtime.
+//!
+//! Besides the Ethereum precompiles that live at the addresses `0x01..=0xff`, a runtime can
+//! expose its own functionality to contracts by mapping fixed addresses to Rust handlers. Such a
+//! handler implements the [`Precompile`] trait and is declared in this pallet's
+//! [configuration trait](Config::Precompiles). Multiple handlers are registered by putting them
+//! into a tuple, like this: `type Precompiles = (Erc20<Runtime, Prefix>, Xcm<Runtime, Address>)`.
+//! The first handler that [matches](Precompile::matches) the called address is executed.
+//!
+//! Contracts call those handlers like any other contract, using the Solidity ABI. The module
+//! provides ready-made handlers:
+//!
+//! - `Erc20`: Exposes every asset of an instance of `pallet-assets` as an ERC-20 token. Only
+//!   available with the `erc20-precompile` feature, which pulls in `pallet-assets`.
+//! - [`Xcm`]: Allows contracts to send and execute XCM programs through [`Config::Xcm`], which is
+//!   usually `pallet-xcm`.
+//!
+//! # Security
+//!
+//! The same considerations as for [chain extensions](crate::chain_extension) apply: The handler
+//! must charge the appropriate amount of weight **before** carrying out any action that consumes
+//! it, including decoding its [input](Environment::charge_input), and must reject state changes
+//! when called in a [read-only](Environment::is_read_only) context.
+//!
+//! Handlers are executed inside a storage transaction: All their state changes are rolled back
+//! when they return an error or a value with the `REVERT` flag set.
+
+#[cfg(feature = "erc20-precompile")]
+mod erc20;
+mod xcm;
+
+#[cfg(feature = "erc20-precompile")]
+pub use self::erc20::*;
+pub use self::xcm::*;
+
+use crate::{
+	exec::Origin,
+	gas::{ChargedAmount, GasMeter},
+	pure_precompiles,
+	tracing::if_tracing,
+	wasm::RuntimeCosts,
+	Config, Error, Event, ExecReturnValue, Pallet,
+};
+use alloc::{string::String, vec::Vec};
+use alloy_core::sol_types::{Revert, SolError};
+use frame_support::weights::Weight;
+use pallet_revive_uapi::ReturnFlags;
+use sp_core::{H160, H256};
+use sp_runtime::DispatchError;
+
+/// A precompile whose logic is provided by the runtime.
+///
+/// Consult the [module documentation](self) for a general explanation.
+pub trait Precompile<T: Config> {
+	/// Returns whether `address` is handled by this precompile.
+	///
+	/// Addresses in the range reserved for the Ethereum precompiles are never passed to this
+	/// function.
+	fn matches(address: &H160) -> bool;
+
+	/// Execute the precompile at `address` with the given `input`.
+	///
+	/// # Return
+	///
+	/// In case of `Err` the calling contract is trapped. A handler that wants to signal a
+	/// recoverable failure to its caller should return a value with the `REVERT` flag set
+	/// instead, for example by using [`revert`].
+	fn call(
+		address: &H160,
+		input: &[u8],
+		env: &mut Environment<T>,
+	) -> Result<ExecReturnValue, DispatchError>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(10)]
+#[tuple_types_custom_trait_bound(Precompile<T>)]
+impl<T: Config> Precompile<T> for Tuple {
+	fn matches(address: &H160) -> bool {
+		for_tuples!(
+			#(
+				if Tuple::matches(address) {
+					return true;
+				}
+			)*
+		);
+		false
+	}
+
+	fn call(
+		address: &H160,
+		input: &[u8],
+		env: &mut Environment<T>,
+	) -> Result<ExecReturnValue, DispatchError> {
+		for_tuples!(
+			#(
+				if Tuple::matches(address) {
+					return Tuple::call(address, input, env);
+				}
+			)*
+		);
+		Err(Error::<T>::UnsupportedPrecompileAddress.into())
+	}
+}
+
+/// Grants a [`Precompile`] access to its execution environment.
+pub struct Environment<'a, T: Config> {
+	gas_meter: &'a mut GasMeter<T>,
+	caller: Origin<T>,
+	address: H160,
+	is_read_only: bool,
+}
+
+impl<'a, T: Config> Environment<'a, T> {
+	pub(crate) fn new(
+		gas_meter: &'a mut GasMeter<T>,
+		caller: Origin<T>,
+		address: H160,
+		is_read_only: bool,
+	) -> Self {
+		Self { gas_meter, caller, address, is_read_only }
+	}
+
+	/// Charge the passed `weight` to the weight meter of the call.
+	///
+	/// This should be called **before** carrying out the action that consumes the weight.
+	/// Returns an error if the call ran out of gas.
+	pub fn charge(&mut self, weight: Weight) -> Result<ChargedAmount, DispatchError> {
+		self.gas_meter.charge(RuntimeCosts::Precompile(weight))
+	}
+
+	/// Charge the cost of reading the call `input` into the precompile.
+	///
+	/// This should be called before the input is decoded, so that the work done on a large input
+	/// is paid for even when it turns out to be invalid.
+	pub fn charge_input(&mut self, input: &[u8]) -> Result<ChargedAmount, DispatchError> {
+		let len = u32::try_from(input.len()).map_err(|_| Error::<T>::OutOfGas)?;
+		self.gas_meter.charge(RuntimeCosts::CopyToContract(len))
+	}
+
+	/// Adjust a previously charged amount down to the `actual` weight consumed.
+	///
+	/// This is useful when the final weight is only known after the action was carried out.
+	pub fn adjust_weight(&mut self, charged: ChargedAmount, actual: Weight) {
+		self.gas_meter.adjust_gas(charged, RuntimeCosts::Precompile(actual))
+	}
+
+	/// The weight that is left for the call.
+	pub fn weight_left(&self) -> Weight {
+		self.gas_meter.gas_left()
+	}
+
+	/// The origin calling the precompile.
+	pub fn caller(&self) -> &Origin<T> {
+		&self.caller
+	}
+
+	/// The address of the precompile being called.
+	pub fn address(&self) -> &H160 {
+		&self.address
+	}
+
+	/// Returns `true` if the precompile is called in a read-only context.
+	///
+	/// Any state changing action must be rejected in that case.
+	pub fn is_read_only(&self) -> bool {
+		self.is_read_only
+	}
+
+	/// Deposit an event with the given topics on behalf of the precompile.
+	pub fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
+		let contract = self.address;
+		if_tracing(|tracer| {
+			tracer.log_event(contract, &topics, &data);
+		});
+		Pallet::<T>::deposit_event(Event::ContractEmitted { contract, data, topics });
+	}
+}
+
+/// Returns whether `address` is either an Ethereum or a runtime precompile.
+pub(crate) fn is_precompile<T: Config>(address: &H160) -> bool {
+	pure_precompiles::is_precompile(address) || T::Precompiles::matches(address)
+}
+
+/// A reverted return value carrying a Solidity `Error(string)` with the given `reason`.
+pub fn revert(reason: impl Into<String>) -> ExecReturnValue {
+	ExecReturnValue {
+		flags: ReturnFlags::REVERT,
+		data: Revert { reason: reason.into() }.abi_encode(),
+	}
+}
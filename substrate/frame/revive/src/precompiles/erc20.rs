@@ -0,0 +1,308 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [ERC-20](https://eips.ethereum.org/EIPS/eip-20) interface to the assets of `pallet-assets`.
+
+use super::{revert, Environment, Precompile};
+use crate::{address::AddressMapper, Config, Error, ExecReturnValue};
+use alloc::{string::String, vec::Vec};
+use alloy_core::{
+	primitives::{Address, U256 as AbiU256},
+	sol,
+	sol_types::{SolCall, SolEvent, SolInterface},
+};
+use core::marker::PhantomData;
+use frame_support::traits::{
+	fungibles::{approvals, metadata, Inspect, Mutate},
+	tokens::Preservation,
+	Get,
+};
+use frame_system::RawOrigin;
+use pallet_assets::WeightInfo as _;
+use pallet_revive_uapi::ReturnFlags;
+use sp_core::{H160, H256, U256};
+use sp_runtime::{
+	traits::{StaticLookup, Zero},
+	DispatchError,
+};
+
+sol! {
+	interface IERC20 {
+		event Transfer(address indexed from, address indexed to, uint256 value);
+		event Approval(address indexed owner, address indexed spender, uint256 value);
+
+		function name() external view returns (string memory);
+		function symbol() external view returns (string memory);
+		function decimals() external view returns (uint8);
+		function totalSupply() external view returns (uint256);
+		function balanceOf(address account) external view returns (uint256);
+		function allowance(address owner, address spender) external view returns (uint256);
+		function transfer(address to, uint256 value) external returns (bool);
+		function approve(address spender, uint256 value) external returns (bool);
+		function transferFrom(address from, address to, uint256 value) external returns (bool);
+	}
+}
+
+type AssetIdOf<T, I> = <T as pallet_assets::Config<I>>::AssetId;
+type AssetBalanceOf<T, I> = <T as pallet_assets::Config<I>>::Balance;
+type Assets<T, I> = pallet_assets::Pallet<T, I>;
+
+/// Exposes every asset of the `pallet-assets` instance `I` as an ERC-20 token.
+///
+/// Each asset lives at its own address, which embeds the asset id and the `Prefix` identifying
+/// this instance: `asset_id (4 bytes, big endian) || 0x00 (12 bytes) || Prefix (2 bytes, big
+/// endian) || 0x0000`. Use [`Self::address_of`] to compute the address of an asset.
+///
+/// `Prefix` must not be zero, and must be unique among all precompiles registered with
+/// [`Config::Precompiles`].
+pub struct Erc20<T, Prefix, I = ()>(PhantomData<(T, Prefix, I)>);
+
+impl<T, Prefix, I> Erc20<T, Prefix, I>
+where
+	Prefix: Get<u16>,
+{
+	/// The address of the token contract for `asset_id`.
+	pub fn address_of(asset_id: u32) -> H160 {
+		let mut address = [0u8; 20];
+		address[..4].copy_from_slice(&asset_id.to_be_bytes());
+		address[16..18].copy_from_slice(&Prefix::get().to_be_bytes());
+		H160(address)
+	}
+
+	/// The asset id that is embedded in `address`.
+	fn asset_id_of(address: &H160) -> u32 {
+		let mut asset_id = [0u8; 4];
+		asset_id.copy_from_slice(&address.as_bytes()[..4]);
+		u32::from_be_bytes(asset_id)
+	}
+}
+
+impl<T, Prefix, I> Precompile<T> for Erc20<T, Prefix, I>
+where
+	T: Config + pallet_assets::Config<I>,
+	Prefix: Get<u16>,
+	I: 'static,
+	AssetIdOf<T, I>: From<u32>,
+	AssetBalanceOf<T, I>: Into<U256> + TryFrom<U256>,
+{
+	fn matches(address: &H160) -> bool {
+		let bytes = address.as_bytes();
+		Prefix::get() != 0 &&
+			bytes[4..16].iter().all(|b| *b == 0) &&
+			bytes[16..18] == Prefix::get().to_be_bytes() &&
+			bytes[18..] == [0u8; 2]
+	}
+
+	fn call(
+		address: &H160,
+		input: &[u8],
+		env: &mut Environment<T>,
+	) -> Result<ExecReturnValue, DispatchError> {
+		use IERC20::IERC20Calls;
+
+		env.charge_input(input)?;
+		let Ok(call) = IERC20Calls::abi_decode(input, true) else {
+			return Ok(revert("invalid input"));
+		};
+
+		let asset_id: AssetIdOf<T, I> = Self::asset_id_of(address).into();
+		let reads = T::DbWeight::get().reads(1);
+
+		let result = match call {
+			IERC20Calls::name(_) => {
+				env.charge(reads)?;
+				let name = <Assets<T, I> as metadata::Inspect<_>>::name(asset_id);
+				IERC20::nameCall::abi_encode_returns(&(to_string(name),))
+			},
+			IERC20Calls::symbol(_) => {
+				env.charge(reads)?;
+				let symbol = <Assets<T, I> as metadata::Inspect<_>>::symbol(asset_id);
+				IERC20::symbolCall::abi_encode_returns(&(to_string(symbol),))
+			},
+			IERC20Calls::decimals(_) => {
+				env.charge(reads)?;
+				let decimals = <Assets<T, I> as metadata::Inspect<_>>::decimals(asset_id);
+				IERC20::decimalsCall::abi_encode_returns(&(decimals,))
+			},
+			IERC20Calls::totalSupply(_) => {
+				env.charge(reads)?;
+				let supply = <Assets<T, I> as Inspect<_>>::total_issuance(asset_id);
+				IERC20::totalSupplyCall::abi_encode_returns(&(to_abi_u256(supply.into()),))
+			},
+			IERC20Calls::balanceOf(IERC20::balanceOfCall { account }) => {
+				env.charge(reads)?;
+				let account = T::AddressMapper::to_account_id(&to_h160(account));
+				let balance = <Assets<T, I> as Inspect<_>>::balance(asset_id, &account);
+				IERC20::balanceOfCall::abi_encode_returns(&(to_abi_u256(balance.into()),))
+			},
+			IERC20Calls::allowance(IERC20::allowanceCall { owner, spender }) => {
+				env.charge(reads)?;
+				let owner = T::AddressMapper::to_account_id(&to_h160(owner));
+				let spender = T::AddressMapper::to_account_id(&to_h160(spender));
+				let allowance =
+					<Assets<T, I> as approvals::Inspect<_>>::allowance(asset_id, &owner, &spender);
+				IERC20::allowanceCall::abi_encode_returns(&(to_abi_u256(allowance.into()),))
+			},
+			IERC20Calls::transfer(IERC20::transferCall { to, value }) => {
+				env.charge(<T as pallet_assets::Config<I>>::WeightInfo::transfer())?;
+				ensure_mutable(env)?;
+				let from = env.caller().account_id()?.clone();
+				let to = to_h160(to);
+				let Some(amount) = to_balance::<T, I>(value) else {
+					return Ok(revert("amount overflow"));
+				};
+
+				if let Err(err) = <Assets<T, I> as Mutate<_>>::transfer(
+					asset_id,
+					&from,
+					&T::AddressMapper::to_account_id(&to),
+					amount,
+					Preservation::Expendable,
+				) {
+					return Ok(revert(<&'static str>::from(err)));
+				}
+
+				let from = T::AddressMapper::to_address(&from);
+				deposit_event(
+					env,
+					IERC20::Transfer { from: from.0.into(), to: to.0.into(), value },
+				);
+				IERC20::transferCall::abi_encode_returns(&(true,))
+			},
+			IERC20Calls::approve(IERC20::approveCall { spender, value }) => {
+				env.charge(
+					<T as pallet_assets::Config<I>>::WeightInfo::cancel_approval().saturating_add(
+						<T as pallet_assets::Config<I>>::WeightInfo::approve_transfer(),
+					),
+				)?;
+				ensure_mutable(env)?;
+				let owner = env.caller().account_id()?.clone();
+				let delegate = T::AddressMapper::to_account_id(&to_h160(spender));
+				let Some(amount) = to_balance::<T, I>(value) else {
+					return Ok(revert("amount overflow"));
+				};
+
+				// `pallet-assets` adds to an existing approval, while ERC-20 replaces it.
+				let existing = <Assets<T, I> as approvals::Inspect<_>>::allowance(
+					asset_id.clone(),
+					&owner,
+					&delegate,
+				);
+				if !existing.is_zero() {
+					if let Err(err) = Assets::<T, I>::cancel_approval(
+						RawOrigin::Signed(owner.clone()).into(),
+						asset_id.clone().into(),
+						T::Lookup::unlookup(delegate.clone()),
+					) {
+						return Ok(revert(<&'static str>::from(err)));
+					}
+				}
+				if !amount.is_zero() {
+					if let Err(err) = <Assets<T, I> as approvals::Mutate<_>>::approve(
+						asset_id, &owner, &delegate, amount,
+					) {
+						return Ok(revert(<&'static str>::from(err)));
+					}
+				}
+
+				let owner = T::AddressMapper::to_address(&owner);
+				deposit_event(env, IERC20::Approval { owner: owner.0.into(), spender, value });
+				IERC20::approveCall::abi_encode_returns(&(true,))
+			},
+			IERC20Calls::transferFrom(IERC20::transferFromCall { from, to, value }) => {
+				env.charge(<T as pallet_assets::Config<I>>::WeightInfo::transfer_approved())?;
+				ensure_mutable(env)?;
+				let delegate = env.caller().account_id()?.clone();
+				let Some(amount) = to_balance::<T, I>(value) else {
+					return Ok(revert("amount overflow"));
+				};
+
+				if let Err(err) = <Assets<T, I> as approvals::Mutate<_>>::transfer_from(
+					asset_id,
+					&T::AddressMapper::to_account_id(&to_h160(from)),
+					&delegate,
+					&T::AddressMapper::to_account_id(&to_h160(to)),
+					amount,
+				) {
+					return Ok(revert(<&'static str>::from(err)));
+				}
+
+				deposit_event(env, IERC20::Transfer { from, to, value });
+				IERC20::transferFromCall::abi_encode_returns(&(true,))
+			},
+		};
+
+		Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: result })
+	}
+}
+
+/// Reject state changes in a read-only context.
+fn ensure_mutable<T: Config>(env: &Environment<T>) -> Result<(), DispatchError> {
+	if env.is_read_only() {
+		return Err(Error::<T>::StateChangeDenied.into());
+	}
+	Ok(())
+}
+
+/// Deposit `event` on behalf of the token contract.
+fn deposit_event<T: Config, E: SolEvent>(env: &mut Environment<T>, event: E) {
+	let topics = event.encode_topics().into_iter().map(|topic| H256(topic.0 .0)).collect();
+	env.deposit_event(topics, event.encode_data());
+}
+
+fn to_h160(address: Address) -> H160 {
+	H160(address.0 .0)
+}
+
+fn to_abi_u256(value: U256) -> AbiU256 {
+	AbiU256::from_be_bytes(value.to_big_endian())
+}
+
+fn to_balance<T, I>(value: AbiU256) -> Option<AssetBalanceOf<T, I>>
+where
+	T: pallet_assets::Config<I>,
+	I: 'static,
+	AssetBalanceOf<T, I>: TryFrom<U256>,
+{
+	U256::from_big_endian(&value.to_be_bytes::<32>()).try_into().ok()
+}
+
+fn to_string(bytes: Vec<u8>) -> String {
+	String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{AssetsPrefix, Test};
+
+	type Token = Erc20<Test, AssetsPrefix>;
+
+	#[test]
+	fn address_mapping_works() {
+		let address = Token::address_of(7);
+		assert_eq!(address, H160(hex_literal::hex!("0000000700000000000000000000000001200000")));
+		assert!(<Token as Precompile<Test>>::matches(&address));
+		assert_eq!(Token::asset_id_of(&address), 7);
+
+		assert!(!<Token as Precompile<Test>>::matches(&H160::from_low_u64_be(7)));
+		assert!(!<Token as Precompile<Test>>::matches(&H160::repeat_byte(1)));
+		assert!(!<Token as Precompile<Test>>::matches(&H160(hex_literal::hex!(
+			"0000000700000000000000000000000001210000"
+		))));
+	}
+}
@@ -0,0 +1,134 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A precompile to send and execute XCM programs through [`Config::Xcm`].
+
+use super::{revert, Environment, Precompile};
+use crate::{Config, Error, ExecReturnValue};
+use ::xcm::{VersionedLocation, VersionedXcm, MAX_XCM_DECODE_DEPTH};
+use alloc::{boxed::Box, vec::Vec};
+use alloy_core::{
+	primitives::FixedBytes,
+	sol,
+	sol_types::{SolCall, SolInterface},
+};
+use codec::DecodeLimit;
+use core::marker::PhantomData;
+use frame_support::{traits::Get, weights::Weight};
+use frame_system::RawOrigin;
+use pallet_revive_uapi::ReturnFlags;
+use sp_core::H160;
+use sp_runtime::DispatchError;
+use xcm_builder::{
+	ExecuteController, ExecuteControllerWeightInfo, SendController, SendControllerWeightInfo,
+};
+
+sol! {
+	interface IXcm {
+		struct Weight {
+			uint64 refTime;
+			uint64 proofSize;
+		}
+
+		function execute(bytes calldata message, Weight calldata maxWeight) external;
+		function send(bytes calldata destination, bytes calldata message) external returns (bytes32);
+	}
+}
+
+/// Allows contracts to execute XCM programs locally and to send them to other chains.
+///
+/// The messages are passed as SCALE encoded `VersionedXcm` and the destination as SCALE encoded
+/// `VersionedLocation`. The calling contract is used as the origin of the XCM program, just as
+/// with the `xcm_execute` and `xcm_send` host functions.
+///
+/// The precompile is located at the address returned by `Address`.
+pub struct Xcm<T, Address>(PhantomData<(T, Address)>);
+
+impl<T, Address> Precompile<T> for Xcm<T, Address>
+where
+	T: Config,
+	Address: Get<H160>,
+{
+	fn matches(address: &H160) -> bool {
+		*address == Address::get()
+	}
+
+	fn call(
+		_address: &H160,
+		input: &[u8],
+		env: &mut Environment<T>,
+	) -> Result<ExecReturnValue, DispatchError> {
+		use IXcm::IXcmCalls;
+
+		env.charge_input(input)?;
+		let Ok(call) = IXcmCalls::abi_decode(input, true) else {
+			return Ok(revert("invalid input"));
+		};
+
+		if env.is_read_only() {
+			return Err(Error::<T>::StateChangeDenied.into());
+		}
+		let origin = RawOrigin::Signed(env.caller().account_id()?.clone()).into();
+
+		let result = match call {
+			IXcmCalls::execute(IXcm::executeCall { message, maxWeight: max_weight }) => {
+				let execute_weight = <T::Xcm as ExecuteController<_, _>>::WeightInfo::execute();
+				let max_weight = Weight::from_parts(max_weight.refTime, max_weight.proofSize);
+				let charged = env.charge(max_weight.saturating_add(execute_weight))?;
+
+				let Ok(message) = VersionedXcm::decode_all_with_depth_limit(
+					MAX_XCM_DECODE_DEPTH,
+					&mut &message[..],
+				) else {
+					return Ok(revert("invalid message"));
+				};
+
+				match <T::Xcm>::execute(origin, Box::new(message), max_weight) {
+					Ok(weight_used) => {
+						env.adjust_weight(charged, weight_used.saturating_add(execute_weight));
+						Vec::new()
+					},
+					Err(err) => return Ok(revert(<&'static str>::from(err.error))),
+				}
+			},
+			IXcmCalls::send(IXcm::sendCall { destination, message }) => {
+				env.charge(<T::Xcm as SendController<_>>::WeightInfo::send())?;
+
+				let Ok(destination) = VersionedLocation::decode_all_with_depth_limit(
+					MAX_XCM_DECODE_DEPTH,
+					&mut &destination[..],
+				) else {
+					return Ok(revert("invalid destination"));
+				};
+				let Ok(message) = VersionedXcm::<()>::decode_all_with_depth_limit(
+					MAX_XCM_DECODE_DEPTH,
+					&mut &message[..],
+				) else {
+					return Ok(revert("invalid message"));
+				};
+
+				match <T::Xcm>::send(origin, Box::new(destination), Box::new(message)) {
+					Ok(message_id) =>
+						IXcm::sendCall::abi_encode_returns(&(FixedBytes(message_id),)),
+					Err(err) => return Ok(revert(<&'static str>::from(err))),
+				}
+			},
+		};
+
+		Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: result })
+	}
+}
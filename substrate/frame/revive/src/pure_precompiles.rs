@@ -38,6 +38,12 @@ pub use modexp::*;
 mod blake2f;
 pub use blake2f::*;
 
+mod point_evaluation;
+pub use point_evaluation::*;
+
+mod bls12_381;
+pub use bls12_381::*;
+
 /// Determine if the given address is a precompile.
 /// For now, we consider that all addresses between 0x1 and 0xff are reserved for precompiles.
 pub fn is_precompile(address: &H160) -> bool {
@@ -67,6 +73,14 @@ impl<T: Config> Precompiles<T> {
 			7u8 => Bn128Mul::execute(gas_meter, input),
 			8u8 => Bn128Pairing::execute(gas_meter, input),
 			9u8 => Blake2F::execute(gas_meter, input),
+			10u8 => PointEvaluation::execute(gas_meter, input),
+			11u8 => Bls12381G1Add::execute(gas_meter, input),
+			12u8 => Bls12381G1Msm::execute(gas_meter, input),
+			13u8 => Bls12381G2Add::execute(gas_meter, input),
+			14u8 => Bls12381G2Msm::execute(gas_meter, input),
+			15u8 => Bls12381Pairing::execute(gas_meter, input),
+			16u8 => Bls12381MapFpToG1::execute(gas_meter, input),
+			17u8 => Bls12381MapFp2ToG2::execute(gas_meter, input),
 			_ => return Err(Error::<T>::UnsupportedPrecompileAddress.into()),
 		}
 		.map_err(|reason| {
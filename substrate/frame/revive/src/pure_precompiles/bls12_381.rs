@@ -0,0 +1,468 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The BLS12-381 precompiles, as specified by [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537).
+
+use super::Precompile;
+use crate::{Config, ExecReturnValue, GasMeter, RuntimeCosts};
+use alloc::{vec, vec::Vec};
+use ark_bls12_381::{
+	g1, g2, Bls12_381, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
+};
+use ark_ec::{
+	hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurve},
+	pairing::Pairing,
+	AffineRepr, CurveGroup, VariableBaseMSM,
+};
+use ark_ff::{BigInt, BigInteger, PrimeField, Zero};
+use pallet_revive_uapi::ReturnFlags;
+
+/// Length of an encoded base field element, padded to 64 bytes.
+const FP_LENGTH: usize = 64;
+
+/// Number of zero bytes padding an encoded base field element.
+const FP_PADDING_LENGTH: usize = 16;
+
+/// Length of an encoded `Fp2` element.
+const FP2_LENGTH: usize = 2 * FP_LENGTH;
+
+/// Length of an encoded G1 point.
+const G1_LENGTH: usize = 2 * FP_LENGTH;
+
+/// Length of an encoded G2 point.
+const G2_LENGTH: usize = 2 * FP2_LENGTH;
+
+/// Length of an encoded scalar.
+const SCALAR_LENGTH: usize = 32;
+
+/// Length of a (point, scalar) pair of the G1 MSM input.
+const G1_MSM_PAIR_LENGTH: usize = G1_LENGTH + SCALAR_LENGTH;
+
+/// Length of a (point, scalar) pair of the G2 MSM input.
+const G2_MSM_PAIR_LENGTH: usize = G2_LENGTH + SCALAR_LENGTH;
+
+/// Length of a (G1, G2) pair of the pairing check input.
+const PAIRING_PAIR_LENGTH: usize = G1_LENGTH + G2_LENGTH;
+
+/// The Bls12381G1Add precompile.
+pub struct Bls12381G1Add;
+
+impl<T: Config> Precompile<T> for Bls12381G1Add {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		gas_meter.charge(RuntimeCosts::Bls12381G1Add)?;
+
+		if input.len() != 2 * G1_LENGTH {
+			return Err("invalid input length");
+		}
+
+		// No subgroup check is required for the addition.
+		let p1 = read_g1(&input[..G1_LENGTH], false)?;
+		let p2 = read_g1(&input[G1_LENGTH..], false)?;
+
+		let data = encode_g1(&(p1 + p2).into_affine());
+		Ok(ExecReturnValue { data, flags: ReturnFlags::empty() })
+	}
+}
+
+/// The Bls12381G1Msm precompile.
+pub struct Bls12381G1Msm;
+
+impl<T: Config> Precompile<T> for Bls12381G1Msm {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		if input.is_empty() || input.len() % G1_MSM_PAIR_LENGTH != 0 {
+			return Err("invalid input length");
+		}
+
+		let pairs = input.len() / G1_MSM_PAIR_LENGTH;
+		gas_meter.charge(RuntimeCosts::Bls12381G1Msm(pairs as u32))?;
+
+		let mut bases = Vec::with_capacity(pairs);
+		let mut scalars = Vec::with_capacity(pairs);
+		for pair in input.chunks_exact(G1_MSM_PAIR_LENGTH) {
+			bases.push(read_g1(&pair[..G1_LENGTH], true)?);
+			scalars.push(Fr::from_be_bytes_mod_order(&pair[G1_LENGTH..]));
+		}
+
+		let data = encode_g1(&G1Projective::msm_unchecked(&bases, &scalars).into_affine());
+		Ok(ExecReturnValue { data, flags: ReturnFlags::empty() })
+	}
+}
+
+/// The Bls12381G2Add precompile.
+pub struct Bls12381G2Add;
+
+impl<T: Config> Precompile<T> for Bls12381G2Add {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		gas_meter.charge(RuntimeCosts::Bls12381G2Add)?;
+
+		if input.len() != 2 * G2_LENGTH {
+			return Err("invalid input length");
+		}
+
+		// No subgroup check is required for the addition.
+		let p1 = read_g2(&input[..G2_LENGTH], false)?;
+		let p2 = read_g2(&input[G2_LENGTH..], false)?;
+
+		let data = encode_g2(&(p1 + p2).into_affine());
+		Ok(ExecReturnValue { data, flags: ReturnFlags::empty() })
+	}
+}
+
+/// The Bls12381G2Msm precompile.
+pub struct Bls12381G2Msm;
+
+impl<T: Config> Precompile<T> for Bls12381G2Msm {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		if input.is_empty() || input.len() % G2_MSM_PAIR_LENGTH != 0 {
+			return Err("invalid input length");
+		}
+
+		let pairs = input.len() / G2_MSM_PAIR_LENGTH;
+		gas_meter.charge(RuntimeCosts::Bls12381G2Msm(pairs as u32))?;
+
+		let mut bases = Vec::with_capacity(pairs);
+		let mut scalars = Vec::with_capacity(pairs);
+		for pair in input.chunks_exact(G2_MSM_PAIR_LENGTH) {
+			bases.push(read_g2(&pair[..G2_LENGTH], true)?);
+			scalars.push(Fr::from_be_bytes_mod_order(&pair[G2_LENGTH..]));
+		}
+
+		let data = encode_g2(&G2Projective::msm_unchecked(&bases, &scalars).into_affine());
+		Ok(ExecReturnValue { data, flags: ReturnFlags::empty() })
+	}
+}
+
+/// The Bls12381Pairing precompile.
+pub struct Bls12381Pairing;
+
+impl<T: Config> Precompile<T> for Bls12381Pairing {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		if input.is_empty() || input.len() % PAIRING_PAIR_LENGTH != 0 {
+			return Err("invalid input length");
+		}
+
+		let pairs = input.len() / PAIRING_PAIR_LENGTH;
+		gas_meter.charge(RuntimeCosts::Bls12381Pairing(pairs as u32))?;
+
+		let mut g1_points = Vec::with_capacity(pairs);
+		let mut g2_points = Vec::with_capacity(pairs);
+		for pair in input.chunks_exact(PAIRING_PAIR_LENGTH) {
+			g1_points.push(read_g1(&pair[..G1_LENGTH], true)?);
+			g2_points.push(read_g2(&pair[G1_LENGTH..], true)?);
+		}
+
+		let mut data = vec![0u8; 32];
+		if Bls12_381::multi_pairing(g1_points, g2_points).is_zero() {
+			data[31] = 1;
+		}
+
+		Ok(ExecReturnValue { data, flags: ReturnFlags::empty() })
+	}
+}
+
+/// The Bls12381MapFpToG1 precompile.
+pub struct Bls12381MapFpToG1;
+
+impl<T: Config> Precompile<T> for Bls12381MapFpToG1 {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		gas_meter.charge(RuntimeCosts::Bls12381MapFpToG1)?;
+
+		if input.len() != FP_LENGTH {
+			return Err("invalid input length");
+		}
+
+		let fp = read_fp(input)?;
+		let point = WBMap::<g1::Config>::new()
+			.and_then(|map| map.map_to_curve(fp))
+			.map_err(|_| "failed to map field element to G1")?
+			.clear_cofactor();
+
+		Ok(ExecReturnValue { data: encode_g1(&point), flags: ReturnFlags::empty() })
+	}
+}
+
+/// The Bls12381MapFp2ToG2 precompile.
+pub struct Bls12381MapFp2ToG2;
+
+impl<T: Config> Precompile<T> for Bls12381MapFp2ToG2 {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		gas_meter.charge(RuntimeCosts::Bls12381MapFp2ToG2)?;
+
+		if input.len() != FP2_LENGTH {
+			return Err("invalid input length");
+		}
+
+		let fp2 = read_fp2(input)?;
+		let point = WBMap::<g2::Config>::new()
+			.and_then(|map| map.map_to_curve(fp2))
+			.map_err(|_| "failed to map field element to G2")?
+			.clear_cofactor();
+
+		Ok(ExecReturnValue { data: encode_g2(&point), flags: ReturnFlags::empty() })
+	}
+}
+
+/// Read a big-endian unsigned integer into a [`BigInt`].
+///
+/// The input must not be longer than `8 * N` bytes.
+pub(super) fn read_bigint<const N: usize>(input: &[u8]) -> BigInt<N> {
+	let mut limbs = [0u64; N];
+	for (limb, chunk) in limbs.iter_mut().zip(input.rchunks(8)) {
+		let mut buf = [0u8; 8];
+		buf[8 - chunk.len()..].copy_from_slice(chunk);
+		*limb = u64::from_be_bytes(buf);
+	}
+	BigInt(limbs)
+}
+
+/// Read a padded base field element.
+fn read_fp(input: &[u8]) -> Result<Fq, &'static str> {
+	let (padding, value) = input.split_at(FP_PADDING_LENGTH);
+	if padding.iter().any(|byte| *byte != 0) {
+		return Err("invalid field element top bytes");
+	}
+
+	Fq::from_bigint(read_bigint(value)).ok_or("invalid fp.Element encoding")
+}
+
+/// Read an `Fp2` element, encoded as `c0 || c1`.
+fn read_fp2(input: &[u8]) -> Result<Fq2, &'static str> {
+	let c0 = read_fp(&input[..FP_LENGTH])?;
+	let c1 = read_fp(&input[FP_LENGTH..FP2_LENGTH])?;
+	Ok(Fq2::new(c0, c1))
+}
+
+/// Read a G1 point, encoded as `x || y`. The point at infinity is encoded with zeroes.
+fn read_g1(input: &[u8], subgroup_check: bool) -> Result<G1Affine, &'static str> {
+	let x = read_fp(&input[..FP_LENGTH])?;
+	let y = read_fp(&input[FP_LENGTH..G1_LENGTH])?;
+	if x.is_zero() && y.is_zero() {
+		return Ok(G1Affine::identity());
+	}
+
+	let point = G1Affine::new_unchecked(x, y);
+	if !point.is_on_curve() {
+		return Err("invalid point: not on curve");
+	}
+
+	if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+		return Err("g1 point is not on correct subgroup");
+	}
+
+	Ok(point)
+}
+
+/// Read a G2 point, encoded as `x || y`. The point at infinity is encoded with zeroes.
+fn read_g2(input: &[u8], subgroup_check: bool) -> Result<G2Affine, &'static str> {
+	let x = read_fp2(&input[..FP2_LENGTH])?;
+	let y = read_fp2(&input[FP2_LENGTH..G2_LENGTH])?;
+	if x.is_zero() && y.is_zero() {
+		return Ok(G2Affine::identity());
+	}
+
+	let point = G2Affine::new_unchecked(x, y);
+	if !point.is_on_curve() {
+		return Err("invalid point: not on curve");
+	}
+
+	if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+		return Err("g2 point is not on correct subgroup");
+	}
+
+	Ok(point)
+}
+
+/// Write a padded base field element.
+fn write_fp(fp: &Fq, output: &mut [u8]) {
+	output[FP_PADDING_LENGTH..FP_LENGTH].copy_from_slice(&fp.into_bigint().to_bytes_be());
+}
+
+/// Encode a G1 point.
+fn encode_g1(point: &G1Affine) -> Vec<u8> {
+	let mut output = vec![0u8; G1_LENGTH];
+	if let Some((x, y)) = point.xy() {
+		write_fp(x, &mut output[..FP_LENGTH]);
+		write_fp(y, &mut output[FP_LENGTH..]);
+	}
+	output
+}
+
+/// Encode a G2 point.
+fn encode_g2(point: &G2Affine) -> Vec<u8> {
+	let mut output = vec![0u8; G2_LENGTH];
+	if let Some((x, y)) = point.xy() {
+		for (i, fp) in [x.c0, x.c1, y.c0, y.c1].iter().enumerate() {
+			write_fp(fp, &mut output[i * FP_LENGTH..(i + 1) * FP_LENGTH]);
+		}
+	}
+	output
+}
+
+/// Generate `n` random G1 points, each one followed by a random scalar if `with_scalars` is set.
+#[cfg(feature = "runtime-benchmarks")]
+pub fn generate_random_g1_points(n: usize, with_scalars: bool) -> Vec<u8> {
+	use rand::{RngCore, SeedableRng};
+	use rand_pcg::Pcg64;
+	let mut rng = Pcg64::seed_from_u64(1);
+
+	let mut buffer = Vec::new();
+	for _ in 0..n {
+		let mut scalar = [0u8; SCALAR_LENGTH];
+		rng.fill_bytes(&mut scalar);
+		let point = (G1Affine::generator() * Fr::from_be_bytes_mod_order(&scalar)).into_affine();
+		buffer.extend(encode_g1(&point));
+
+		if with_scalars {
+			rng.fill_bytes(&mut scalar);
+			buffer.extend(scalar);
+		}
+	}
+
+	buffer
+}
+
+/// Generate `n` random G2 points, each one followed by a random scalar if `with_scalars` is set.
+#[cfg(feature = "runtime-benchmarks")]
+pub fn generate_random_g2_points(n: usize, with_scalars: bool) -> Vec<u8> {
+	use rand::{RngCore, SeedableRng};
+	use rand_pcg::Pcg64;
+	let mut rng = Pcg64::seed_from_u64(1);
+
+	let mut buffer = Vec::new();
+	for _ in 0..n {
+		let mut scalar = [0u8; SCALAR_LENGTH];
+		rng.fill_bytes(&mut scalar);
+		let point = (G2Affine::generator() * Fr::from_be_bytes_mod_order(&scalar)).into_affine();
+		buffer.extend(encode_g2(&point));
+
+		if with_scalars {
+			rng.fill_bytes(&mut scalar);
+			buffer.extend(scalar);
+		}
+	}
+
+	buffer
+}
+
+/// Generate the input of a pairing check of `n` random (G1, G2) pairs.
+#[cfg(feature = "runtime-benchmarks")]
+pub fn generate_random_bls12_381_pairs(n: usize) -> Vec<u8> {
+	let g1_points = generate_random_g1_points(n, false);
+	let g2_points = generate_random_g2_points(n, false);
+	g1_points
+		.chunks_exact(G1_LENGTH)
+		.zip(g2_points.chunks_exact(G2_LENGTH))
+		.flat_map(|(g1, g2)| g1.iter().chain(g2).copied())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::pure_precompiles::test::*;
+
+	#[test]
+	fn test_bls12_381_g1_add() -> Result<(), String> {
+		test_precompile_test_vectors::<Bls12381G1Add>(include_str!(
+			"./testdata/11-bls12g1add.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381G1Add>(include_str!(
+			"./testdata/11-bls12g1add-failure.json"
+		))?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_bls12_381_g1_msm() -> Result<(), String> {
+		// A multiplication is an MSM of a single pair.
+		test_precompile_test_vectors::<Bls12381G1Msm>(include_str!(
+			"./testdata/12-bls12g1mul.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381G1Msm>(include_str!(
+			"./testdata/12-bls12g1mul-failure.json"
+		))?;
+		test_precompile_test_vectors::<Bls12381G1Msm>(include_str!(
+			"./testdata/12-bls12g1multiexp.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381G1Msm>(include_str!(
+			"./testdata/12-bls12g1multiexp-failure.json"
+		))?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_bls12_381_g2_add() -> Result<(), String> {
+		test_precompile_test_vectors::<Bls12381G2Add>(include_str!(
+			"./testdata/13-bls12g2add.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381G2Add>(include_str!(
+			"./testdata/13-bls12g2add-failure.json"
+		))?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_bls12_381_g2_msm() -> Result<(), String> {
+		// A multiplication is an MSM of a single pair.
+		test_precompile_test_vectors::<Bls12381G2Msm>(include_str!(
+			"./testdata/14-bls12g2mul.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381G2Msm>(include_str!(
+			"./testdata/14-bls12g2mul-failure.json"
+		))?;
+		test_precompile_test_vectors::<Bls12381G2Msm>(include_str!(
+			"./testdata/14-bls12g2multiexp.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381G2Msm>(include_str!(
+			"./testdata/14-bls12g2multiexp-failure.json"
+		))?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_bls12_381_pairing() -> Result<(), String> {
+		test_precompile_test_vectors::<Bls12381Pairing>(include_str!(
+			"./testdata/15-bls12pairing.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381Pairing>(include_str!(
+			"./testdata/15-bls12pairing-failure.json"
+		))?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_bls12_381_map_fp_to_g1() -> Result<(), String> {
+		test_precompile_test_vectors::<Bls12381MapFpToG1>(include_str!(
+			"./testdata/16-bls12mapfptog1.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381MapFpToG1>(include_str!(
+			"./testdata/16-bls12mapfptog1-failure.json"
+		))?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_bls12_381_map_fp2_to_g2() -> Result<(), String> {
+		test_precompile_test_vectors::<Bls12381MapFp2ToG2>(include_str!(
+			"./testdata/17-bls12mapfp2tog2.json"
+		))?;
+		test_precompile_failure_test_vectors::<Bls12381MapFp2ToG2>(include_str!(
+			"./testdata/17-bls12mapfp2tog2-failure.json"
+		))?;
+		Ok(())
+	}
+}
@@ -0,0 +1,126 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The KZG point evaluation precompile, as specified by
+//! [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844#point-evaluation-precompile).
+
+use super::{bls12_381::read_bigint, Precompile};
+use crate::{Config, ExecReturnValue, GasMeter, RuntimeCosts};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::CanonicalDeserialize;
+use hex_literal::hex;
+use pallet_revive_uapi::ReturnFlags;
+
+/// The version byte of the versioned hash of a KZG commitment.
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// `FIELD_ELEMENTS_PER_BLOB` and `BLS_MODULUS`, as padded 32 bytes big endian values.
+const RETURN_VALUE: [u8; 64] = hex!(
+	"0000000000000000000000000000000000000000000000000000000000001000"
+	"73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+);
+
+/// The compressed `[τ]G2` point of the trusted setup of the Ethereum KZG ceremony.
+const TAU_G2: [u8; 96] = hex!(
+	"b5bfd7dd8cdeb128843bc287230af38926187075cbfbefa81009a2ce615ac53d"
+	"2914e5870cb452d2afaaab24f3499f72185cbfee53492714734429b7b38608e2"
+	"3926c911cceceac9a36851477ba4c60b087041de621000edc98edada20c1def2"
+);
+
+/// The PointEvaluation precompile.
+///
+/// The input is encoded as `versioned_hash || z || y || commitment || proof`, where `z` and `y`
+/// are 32 bytes big endian scalars, and `commitment` and `proof` are 48 bytes compressed G1
+/// points.
+pub struct PointEvaluation;
+
+impl<T: Config> Precompile<T> for PointEvaluation {
+	fn execute(gas_meter: &mut GasMeter<T>, input: &[u8]) -> Result<ExecReturnValue, &'static str> {
+		gas_meter.charge(RuntimeCosts::PointEvaluation)?;
+
+		if input.len() != 192 {
+			return Err("invalid input length");
+		}
+
+		let versioned_hash = &input[..32];
+		let commitment = &input[96..144];
+		if kzg_to_versioned_hash(commitment) != versioned_hash {
+			return Err("mismatched versioned hash");
+		}
+
+		let z = read_scalar(&input[32..64])?;
+		let y = read_scalar(&input[64..96])?;
+		let commitment =
+			G1Affine::deserialize_compressed(commitment).map_err(|_| "invalid commitment")?;
+		let proof =
+			G1Affine::deserialize_compressed(&input[144..192]).map_err(|_| "invalid proof")?;
+
+		if !verify_kzg_proof(commitment, z, y, proof) {
+			return Err("invalid kzg proof");
+		}
+
+		Ok(ExecReturnValue { data: RETURN_VALUE.to_vec(), flags: ReturnFlags::empty() })
+	}
+}
+
+/// Compute the versioned hash of a KZG commitment: `VERSIONED_HASH_VERSION_KZG ||
+/// sha256(commitment)[1..]`.
+fn kzg_to_versioned_hash(commitment: &[u8]) -> [u8; 32] {
+	let mut hash = sp_io::hashing::sha2_256(commitment);
+	hash[0] = VERSIONED_HASH_VERSION_KZG;
+	hash
+}
+
+/// Read a canonical scalar field element.
+fn read_scalar(input: &[u8]) -> Result<Fr, &'static str> {
+	Fr::from_bigint(read_bigint(input)).ok_or("invalid scalar field element")
+}
+
+/// Verify that `proof` proves that the polynomial committed to by `commitment` evaluates to `y`
+/// at `z`, by checking that `e(commitment - [y]G1, -G2) * e(proof, [τ]G2 - [z]G2) == 1`.
+fn verify_kzg_proof(commitment: G1Affine, z: Fr, y: Fr, proof: G1Affine) -> bool {
+	let tau_g2 =
+		G2Affine::deserialize_compressed(&TAU_G2[..]).expect("trusted setup point is valid; qed");
+
+	let x_minus_z = tau_g2.into_group() - G2Affine::generator() * z;
+	let p_minus_y = commitment.into_group() - G1Affine::generator() * y;
+
+	Bls12_381::multi_pairing(
+		[p_minus_y.into_affine(), proof],
+		[-G2Affine::generator(), x_minus_z.into_affine()],
+	)
+	.is_zero()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::pure_precompiles::test::*;
+
+	#[test]
+	fn test_point_evaluation() -> Result<(), String> {
+		test_precompile_test_vectors::<PointEvaluation>(include_str!(
+			"./testdata/10-pointevaluation.json"
+		))?;
+		test_precompile_failure_test_vectors::<PointEvaluation>(include_str!(
+			"./testdata/10-pointevaluation-failure.json"
+		))?;
+		Ok(())
+	}
+}
@@ -21,13 +21,17 @@ pub mod meter;
 
 use crate::{
 	address::AddressMapper,
+	evm::AccessListEntry,
 	exec::{AccountIdOf, Key},
 	storage::meter::Diff,
 	weights::WeightInfo,
 	BalanceOf, Config, ContractInfoOf, DeletionQueue, DeletionQueueCounter, Error, TrieId,
 	SENTINEL,
 };
-use alloc::vec::Vec;
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	vec::Vec,
+};
 use codec::{Decode, Encode, MaxEncodedLen};
 use core::marker::PhantomData;
 use frame_support::{
@@ -366,6 +370,70 @@ impl WriteOutcome {
 	}
 }
 
+/// The addresses and storage keys of the access list of the Ethereum transaction being executed.
+///
+/// The worst-case overhead of accessing these items is charged upfront, see
+/// [`Self::access_list_weight`]. Accessing them is then discounted for the whole transaction, as
+/// the trie nodes leading to them are already part of the storage proof. Items that are not in
+/// the access list are always charged in full.
+#[derive(Default, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct AccessListState {
+	/// The warm addresses.
+	addresses: BTreeSet<H160>,
+	/// The warm (unhashed) storage keys of each contract.
+	storage_keys: BTreeMap<H160, BTreeSet<Vec<u8>>>,
+}
+
+impl AccessListState {
+	/// Create a new [`AccessListState`] with all the entries of the `access_list` marked as warm.
+	pub fn from_access_list(access_list: &[AccessListEntry]) -> Self {
+		let mut state = Self::default();
+		for AccessListEntry { address, storage_keys } in access_list {
+			state.addresses.insert(*address);
+			let keys = state.storage_keys.entry(*address).or_default();
+			for key in storage_keys {
+				keys.insert(Key::from_fixed(key.0).unhashed().to_vec());
+			}
+		}
+		state
+	}
+
+	/// The number of addresses and storage keys of the `access_list`.
+	pub fn item_count(access_list: &[AccessListEntry]) -> u32 {
+		access_list
+			.iter()
+			.map(|entry| 1 + entry.storage_keys.len())
+			.sum::<usize>()
+			.try_into()
+			.unwrap_or(u32::MAX)
+	}
+
+	/// Whether the given `address` is warm.
+	pub fn is_warm_address(&self, address: &H160) -> bool {
+		self.addresses.contains(address)
+	}
+
+	/// Whether the storage `key` of the contract at `address` is warm.
+	pub fn is_warm_storage_key(&self, address: &H160, key: &Key) -> bool {
+		self.storage_keys.get(address).is_some_and(|keys| keys.contains(key.unhashed()))
+	}
+
+	/// The weight charged upfront for warming the entries of the `access_list`.
+	///
+	/// This is the worst-case overhead of reading an account, or a storage item from a full
+	/// storage trie, which is refunded when the item is accessed while warm.
+	pub fn access_list_weight<T: Config>(access_list: &[AccessListEntry]) -> Weight {
+		let storage_keys: u64 =
+			access_list.iter().map(|entry| entry.storage_keys.len() as u64).sum();
+		let storage_key_weight =
+			T::WeightInfo::set_storage_full().saturating_sub(T::WeightInfo::set_storage_empty());
+
+		T::DbWeight::get()
+			.reads(access_list.len() as u64)
+			.saturating_add(storage_key_weight.saturating_mul(storage_keys))
+	}
+}
+
 /// Manage the removal of contracts storage that are marked for deletion.
 ///
 /// When a contract is deleted by calling `seal_terminate` it becomes inaccessible
@@ -25,9 +25,12 @@ use crate::{
 		ChainExtension, Environment, Ext, RegisteredChainExtension, Result as ExtensionResult,
 		RetVal, ReturnFlags,
 	},
-	evm::{runtime::GAS_PRICE, CallTrace, CallTracer, CallType, GenericTransaction},
+	evm::{
+		runtime::GAS_PRICE, AccessListEntry, CallTrace, CallTracer, CallType, GenericTransaction,
+	},
 	exec::Key,
 	limits,
+	precompiles::Xcm as XcmPrecompile,
 	storage::DeletionQueueManager,
 	test_utils::*,
 	tests::test_utils::{get_contract, get_contract_checked},
@@ -81,7 +84,8 @@ frame_support::construct_runtime!(
 		Contracts: pallet_revive,
 		Proxy: pallet_proxy,
 		TransactionPayment: pallet_transaction_payment,
-		Dummy: pallet_dummy
+		Dummy: pallet_dummy,
+		Assets: pallet_assets,
 	}
 );
 
@@ -437,6 +441,23 @@ impl pallet_transaction_payment::Config for Test {
 
 impl pallet_dummy::Config for Test {}
 
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Test {
+	type Currency = Balances;
+	type CreateOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureSigned<Self::AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<Self::AccountId>;
+}
+
+#[cfg(feature = "erc20-precompile")]
+parameter_types! {
+	pub const AssetsPrefix: u16 = 0x0120;
+}
+
+parameter_types! {
+	pub XcmPrecompileAddress: H160 = H160::from_low_u64_be(0x0a00);
+}
+
 parameter_types! {
 	pub static DepositPerByte: BalanceOf<Test> = 1;
 	pub const DepositPerItem: BalanceOf<Test> = 2;
@@ -528,6 +549,11 @@ impl Config for Test {
 	type CallFilter = TestFilter;
 	type ChainExtension =
 		(TestExtension, DisabledExtension, RevertingExtension, TempStorageExtension);
+	#[cfg(feature = "erc20-precompile")]
+	type Precompiles =
+		(crate::precompiles::Erc20<Self, AssetsPrefix>, XcmPrecompile<Self, XcmPrecompileAddress>);
+	#[cfg(not(feature = "erc20-precompile"))]
+	type Precompiles = (XcmPrecompile<Self, XcmPrecompileAddress>,);
 	type DepositPerByte = DepositPerByte;
 	type DepositPerItem = DepositPerItem;
 	type UnsafeUnstableInterface = UnstableInterface;
@@ -946,6 +972,45 @@ fn storage_max_value_limit() {
 	});
 }
 
+#[test]
+fn access_list_warms_storage_keys() {
+	let (wasm, _code_hash) = compile_module("storage_size").unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 1_000_000);
+		let Contract { addr, .. } = builder::bare_instantiate(Code::Upload(wasm))
+			.value(30_000)
+			.build_and_unwrap_contract();
+
+		// The key written and then read back by the fixture.
+		let mut key = [0u8; 32];
+		key[0] = 1;
+		let data = 32u32.encode();
+
+		// Create the storage item, so that both calls below overwrite an existing item.
+		builder::bare_call(addr).data(data.clone()).build_and_unwrap_result();
+
+		let cold = builder::bare_call(addr).data(data.clone()).build();
+		let mut access_list =
+			vec![AccessListEntry { address: addr, storage_keys: vec![H256(key)] }];
+		let warm = Pallet::<Test>::with_access_list(&mut access_list, || {
+			builder::bare_call(addr).data(data).build()
+		});
+		assert!(cold.result.is_ok());
+		assert!(warm.result.is_ok());
+
+		// Only the accesses to the key of the access list are discounted, including the read
+		// following the write: accessing the same key again does not make it warm.
+		let discount = <Test as Config>::WeightInfo::set_storage_full()
+			.saturating_sub(<Test as Config>::WeightInfo::set_storage_empty())
+			.saturating_add(
+				<Test as Config>::WeightInfo::get_storage_full()
+					.saturating_sub(<Test as Config>::WeightInfo::get_storage_empty()),
+			);
+		assert_eq!(cold.gas_consumed.saturating_sub(warm.gas_consumed), discount);
+	});
+}
+
 #[test]
 fn transient_storage_work() {
 	let (code, _code_hash) = compile_module("transient_storage").unwrap();
@@ -4578,6 +4643,236 @@ fn tracing_works() {
 	});
 }
 
+#[test]
+fn prestate_tracing_works() {
+	use crate::evm::*;
+	use std::collections::BTreeMap;
+	let (code, _) = compile_module("multi_store").unwrap();
+
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+		let Contract { addr, .. } =
+			builder::bare_instantiate(Code::Upload(code.clone())).build_and_unwrap_contract();
+
+		let alice_balance = Pallet::<Test>::evm_balance(&ALICE_ADDR);
+		let contract_balance = Pallet::<Test>::evm_balance(&addr);
+		let storage = |size1: usize, size2: usize| {
+			BTreeMap::from([
+				(Bytes(vec![1u8; 32]), (size1 > 0).then(|| Bytes(vec![0u8; size1]))),
+				(Bytes(vec![2u8; 32]), (size2 > 0).then(|| Bytes(vec![0u8; size2]))),
+			])
+		};
+
+		// Without diff mode, the state of all touched accounts is reported.
+		let mut tracer = PrestateTracer::<Test>::new(Default::default());
+		trace(&mut tracer, || {
+			builder::bare_call(addr).data((4u32, 8u32).encode()).build_and_unwrap_result();
+		});
+		assert_eq!(
+			tracer.collect_trace(),
+			Some(PrestateTrace::Prestate(BTreeMap::from([
+				(
+					ALICE_ADDR,
+					PrestateTraceInfo {
+						balance: Some(alice_balance),
+						nonce: Some(1),
+						..Default::default()
+					}
+				),
+				(
+					addr,
+					PrestateTraceInfo {
+						balance: Some(contract_balance),
+						code: Some(code.clone().into()),
+						storage: storage(0, 0),
+						..Default::default()
+					}
+				),
+			])))
+		);
+
+		// Code and storage can be omitted.
+		let config = PrestateTracerConfig {
+			disable_code: true,
+			disable_storage: true,
+			..Default::default()
+		};
+		let mut tracer = PrestateTracer::<Test>::new(config);
+		trace(&mut tracer, || {
+			builder::bare_call(addr).data((4u32, 8u32).encode()).build_and_unwrap_result();
+		});
+		assert_matches!(
+			tracer.collect_trace(),
+			Some(PrestateTrace::Prestate(accounts)) if accounts[&addr] == PrestateTraceInfo {
+				balance: Some(contract_balance),
+				..Default::default()
+			}
+		);
+
+		// In diff mode, only the modified accounts, fields and storage slots are reported.
+		let alice_balance = Pallet::<Test>::evm_balance(&ALICE_ADDR);
+		let mut tracer = PrestateTracer::<Test>::new(PrestateTracerConfig {
+			diff_mode: true,
+			..Default::default()
+		});
+		trace(&mut tracer, || {
+			builder::bare_call(addr).data((16u32, 32u32).encode()).build_and_unwrap_result();
+		});
+		let contract_balance_after = Pallet::<Test>::evm_balance(&addr);
+		assert_eq!(
+			tracer.collect_trace(),
+			Some(PrestateTrace::DiffMode {
+				pre: BTreeMap::from([
+					(
+						ALICE_ADDR,
+						PrestateTraceInfo {
+							balance: Some(alice_balance),
+							nonce: Some(1),
+							..Default::default()
+						}
+					),
+					(
+						addr,
+						PrestateTraceInfo {
+							balance: Some(contract_balance),
+							code: Some(code.into()),
+							storage: storage(4, 8),
+							..Default::default()
+						}
+					),
+				]),
+				post: BTreeMap::from([
+					(
+						ALICE_ADDR,
+						PrestateTraceInfo {
+							balance: Some(Pallet::<Test>::evm_balance(&ALICE_ADDR)),
+							..Default::default()
+						}
+					),
+					(
+						addr,
+						PrestateTraceInfo {
+							balance: (contract_balance_after != contract_balance)
+								.then_some(contract_balance_after),
+							storage: storage(16, 32),
+							..Default::default()
+						}
+					),
+				]),
+			})
+		);
+	});
+}
+
+#[test]
+fn struct_logger_works() {
+	use crate::evm::*;
+	use std::collections::BTreeMap;
+	let (code, _) = compile_module("multi_store").unwrap();
+
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+		let Contract { addr, .. } =
+			builder::bare_instantiate(Code::Upload(code)).build_and_unwrap_contract();
+
+		let gas_mapper = Pallet::<Test>::evm_gas_from_weight as fn(Weight) -> U256;
+		let run = |config: StructLoggerConfig| {
+			let mut tracer = StructLogger::new(config, gas_mapper);
+			trace(&mut tracer, || {
+				builder::bare_call(addr).data((4u32, 8u32).encode()).build_and_unwrap_result();
+			});
+			tracer.collect_trace().unwrap()
+		};
+
+		// Only the syscalls are logged, along with the storage touched by the contract.
+		let trace = run(StructLoggerConfig { syscalls_only: true, ..Default::default() });
+		assert!(!trace.failed);
+		assert!(trace.struct_logs.iter().all(|log| log.depth == 1 && log.syscall.is_some()));
+		let storage_logs = trace
+			.struct_logs
+			.iter()
+			.filter(|log| log.syscall.as_deref() == Some("set_storage"))
+			.map(|log| log.storage.clone())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			storage_logs,
+			vec![
+				Some(BTreeMap::from([(Bytes(vec![1u8; 32]), Bytes(vec![0u8; 4]))])),
+				Some(BTreeMap::from([
+					(Bytes(vec![1u8; 32]), Bytes(vec![0u8; 4])),
+					(Bytes(vec![2u8; 32]), Bytes(vec![0u8; 8])),
+				])),
+			]
+		);
+
+		// Every instruction is logged by default, and each step consumes some gas.
+		let full_trace = run(Default::default());
+		assert!(full_trace.struct_logs.len() > trace.struct_logs.len());
+		assert!(full_trace.struct_logs.windows(2).all(|logs| logs[0].gas >= logs[1].gas));
+		assert_eq!(
+			full_trace.struct_logs.iter().filter(|log| log.syscall.is_some()).count(),
+			trace.struct_logs.len()
+		);
+		// The cost of a syscall is measured with the same gas source as the following steps.
+		assert!(full_trace.struct_logs.windows(2).all(|logs| {
+			logs[0].syscall.is_none() || logs[0].gas.saturating_sub(logs[0].gas_cost) >= logs[1].gas
+		}));
+
+		// The number of logged steps can be limited, and the storage omitted.
+		let trace =
+			run(StructLoggerConfig { disable_storage: true, limit: 3, ..Default::default() });
+		assert_eq!(trace.struct_logs.len(), 3);
+		assert!(trace.struct_logs.iter().all(|log| log.storage.is_none()));
+	});
+}
+
+#[test]
+fn prestate_tracing_works_for_transfers() {
+	use crate::evm::*;
+	use std::collections::BTreeMap;
+
+	ExtBuilder::default().build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+		let alice_balance = Pallet::<Test>::evm_balance(&ALICE_ADDR);
+
+		let mut tracer = PrestateTracer::<Test>::new(PrestateTracerConfig {
+			diff_mode: true,
+			..Default::default()
+		});
+		trace(&mut tracer, || {
+			builder::bare_call(BOB_ADDR).value(10_000_000).build_and_unwrap_result();
+		});
+
+		// BOB did not exist before the transfer, so it is only part of the post state.
+		assert_eq!(
+			tracer.collect_trace(),
+			Some(PrestateTrace::DiffMode {
+				pre: BTreeMap::from([(
+					ALICE_ADDR,
+					PrestateTraceInfo { balance: Some(alice_balance), ..Default::default() }
+				)]),
+				post: BTreeMap::from([
+					(
+						ALICE_ADDR,
+						PrestateTraceInfo {
+							balance: Some(Pallet::<Test>::evm_balance(&ALICE_ADDR)),
+							..Default::default()
+						}
+					),
+					(
+						BOB_ADDR,
+						PrestateTraceInfo {
+							balance: Some(Pallet::<Test>::evm_balance(&BOB_ADDR)),
+							..Default::default()
+						}
+					),
+				]),
+			})
+		);
+		assert!(Pallet::<Test>::evm_balance(&ALICE_ADDR) < alice_balance);
+	});
+}
+
 #[test]
 fn unknown_precompiles_revert() {
 	let (code, _code_hash) = compile_module("read_only_call").unwrap();
@@ -4675,6 +4970,18 @@ fn pure_precompile_works() {
 			hex!("0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001").to_vec(),
 			hex!("08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b").to_vec(),
 		),
+		(
+			// PointEvaluation
+			H160::from_low_u64_be(10),
+			hex!("01e798154708fe7789429634053cbf9f99b619f9f084048927333fce637f549b73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff000000001522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e98f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c").to_vec(),
+			hex!("000000000000000000000000000000000000000000000000000000000000100073eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001").to_vec(),
+		),
+		(
+			// Bls12381G1Add
+			H160::from_low_u64_be(11),
+			hex!("0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb0000000000000000000000000000000008b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e100000000000000000000000000000000112b98340eee2777cc3c14163dea3ec97977ac3dc5c70da32e6e87578f44912e902ccef9efe28d4a78b8999dfbca942600000000000000000000000000000000186b28d92356c4dfec4b5201ad099dbdede3781f8998ddf929b4cd7756192185ca7b8f4ef7088f813270ac3d48868a21").to_vec(),
+			hex!("000000000000000000000000000000000a40300ce2dec9888b60690e9a41d3004fda4886854573974fab73b046d3147ba5b7a5bde85279ffede1b45b3918d82d0000000000000000000000000000000006d3d887e9f53b9ec4eb6cedf5607226754b07c01ace7834f57f3e7315faefb739e59018e22c492006190fba4a870025").to_vec(),
+		),
 	];
 
 	for (precompile_addr, input, output) in cases {
@@ -4706,3 +5013,157 @@ fn pure_precompile_works() {
 		});
 	}
 }
+
+#[cfg(feature = "erc20-precompile")]
+#[test]
+fn erc20_precompile_works() {
+	use crate::precompiles::{Erc20, IERC20};
+	use alloy_core::{
+		primitives::U256 as AbiU256,
+		sol_types::{Revert, SolCall, SolError, SolEvent},
+	};
+	use frame_support::traits::fungibles;
+
+	const ASSET_ID: u32 = 7;
+	let token = Erc20::<Test, AssetsPrefix>::address_of(ASSET_ID);
+	let (code, _code_hash) = compile_module("call_and_return").unwrap();
+
+	ExtBuilder::default().build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000_000);
+		let Contract { addr, account_id } =
+			builder::bare_instantiate(Code::Upload(code)).build_and_unwrap_contract();
+		assert_ok!(Assets::force_create(RuntimeOrigin::root(), ASSET_ID, ALICE, true, 1));
+		assert_ok!(<Assets as fungibles::Mutate<_>>::mint_into(ASSET_ID, &account_id, 1_000));
+
+		let call_token = |input: Vec<u8>| {
+			builder::bare_call(addr)
+				.data((&token, 0u64).encode().into_iter().chain(input).collect::<Vec<_>>())
+				.build_and_unwrap_result()
+		};
+
+		let result = call_token(IERC20::balanceOfCall { account: addr.0.into() }.abi_encode());
+		assert_eq!(result.flags, ReturnFlags::empty());
+		assert_eq!(
+			result.data,
+			IERC20::balanceOfCall::abi_encode_returns(&(AbiU256::from(1_000),))
+		);
+
+		// Transfer from the calling contract to BOB.
+		let result = call_token(
+			IERC20::transferCall { to: BOB_ADDR.0.into(), value: AbiU256::from(400) }.abi_encode(),
+		);
+		assert_eq!(result.flags, ReturnFlags::empty());
+		assert_eq!(<Assets as fungibles::Inspect<_>>::balance(ASSET_ID, &BOB), 400);
+		assert_eq!(<Assets as fungibles::Inspect<_>>::balance(ASSET_ID, &account_id), 600);
+
+		let transfer = IERC20::Transfer {
+			from: addr.0.into(),
+			to: BOB_ADDR.0.into(),
+			value: AbiU256::from(400),
+		};
+		assert!(System::events().iter().any(|record| {
+			record.event ==
+				RuntimeEvent::Contracts(crate::Event::ContractEmitted {
+					contract: token,
+					data: transfer.encode_data(),
+					topics: transfer
+						.encode_topics()
+						.into_iter()
+						.map(|topic| H256(topic.0 .0))
+						.collect(),
+				})
+		}));
+
+		// Approving twice replaces the allowance instead of adding to it.
+		for value in [300, 200] {
+			call_token(
+				IERC20::approveCall { spender: BOB_ADDR.0.into(), value: AbiU256::from(value) }
+					.abi_encode(),
+			);
+		}
+		let result = call_token(
+			IERC20::allowanceCall { owner: addr.0.into(), spender: BOB_ADDR.0.into() }.abi_encode(),
+		);
+		assert_eq!(result.data, IERC20::allowanceCall::abi_encode_returns(&(AbiU256::from(200),)));
+
+		// Transferring more than the balance reverts with a reason.
+		let result = call_token(
+			IERC20::transferCall { to: BOB_ADDR.0.into(), value: AbiU256::from(601) }.abi_encode(),
+		);
+		assert_eq!(result.flags, ReturnFlags::REVERT);
+		assert!(Revert::abi_decode(&result.data, true).is_ok());
+		assert_eq!(<Assets as fungibles::Inspect<_>>::balance(ASSET_ID, &account_id), 600);
+	});
+}
+
+#[test]
+fn xcm_precompile_works() {
+	use crate::precompiles::IXcm;
+	use alloy_core::sol_types::SolCall;
+	use xcm::{
+		v5::{Location, Xcm},
+		VersionedLocation, VersionedXcm,
+	};
+
+	let (code, _code_hash) = compile_module("call_and_return").unwrap();
+
+	ExtBuilder::default().build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000_000);
+		let Contract { addr, .. } =
+			builder::bare_instantiate(Code::Upload(code)).build_and_unwrap_contract();
+
+		let input = IXcm::sendCall {
+			destination: VersionedLocation::from(Location::parent()).encode().into(),
+			message: VersionedXcm::from(Xcm::<()>::new()).encode().into(),
+		}
+		.abi_encode();
+		let result = builder::bare_call(addr)
+			.data(
+				(&XcmPrecompileAddress::get(), 0u64)
+					.encode()
+					.into_iter()
+					.chain(input)
+					.collect::<Vec<_>>(),
+			)
+			.build_and_unwrap_result();
+		assert_eq!(result.flags, ReturnFlags::empty());
+		assert_eq!(result.data, [0u8; 32]);
+
+		let result = builder::bare_call(addr)
+			.data(
+				(&XcmPrecompileAddress::get(), 0u64)
+					.encode()
+					.into_iter()
+					.chain([0u8; 4])
+					.collect::<Vec<_>>(),
+			)
+			.build_and_unwrap_result();
+		assert_eq!(result.flags, ReturnFlags::REVERT);
+	});
+}
+
+#[test]
+fn state_override_sets_exact_balance() {
+	use crate::evm::StateOverride;
+	use alloc::collections::BTreeMap;
+
+	ExtBuilder::default().build().execute_with(|| {
+		let account = <Test as Config>::AddressMapper::to_account_id(&EVE_ADDR);
+		let balance: BalanceOf<Test> = 1_000_000;
+		let overrides = BTreeMap::from([(
+			EVE_ADDR,
+			StateOverride {
+				balance: Some(Pallet::<Test>::convert_native_to_evm(balance)),
+				..Default::default()
+			},
+		)]);
+
+		let overridden = Pallet::<Test>::with_state_overrides(overrides, || {
+			Ok(<Test as Config>::Currency::total_balance(&account))
+		});
+		assert_eq!(overridden.ok(), Some(balance));
+
+		// The override is rolled back.
+		assert_eq!(<Test as Config>::Currency::total_balance(&account), 0);
+	});
+}
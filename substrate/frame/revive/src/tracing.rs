@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{primitives::ExecReturnValue, DispatchError, Weight};
+use crate::{exec::Key, primitives::ExecReturnValue, DispatchError, Weight};
 use environmental::environmental;
 use sp_core::{H160, H256, U256};
 
@@ -61,4 +61,30 @@ pub trait Tracer {
 
 	/// Called when a contract call terminates with an error
 	fn exit_child_span_with_error(&mut self, error: DispatchError, gas_left: Weight);
+
+	/// Record a read of the given storage `key` of the contract executing in the current span.
+	fn storage_read(&mut self, _key: &Key, _value: Option<&[u8]>) {}
+
+	/// Record a write to the given storage `key` of the contract executing in the current span.
+	fn storage_write(&mut self, _key: &Key, _old_value: Option<&[u8]>, _new_value: Option<&[u8]>) {}
+
+	/// Record an access to the account at `address`, e.g. when its balance or code is queried.
+	fn watch_address(&mut self, _address: &H160) {}
+
+	/// Whether the contracts should be interrupted before every instruction to call
+	/// [`Self::step`].
+	fn trace_steps(&self) -> bool {
+		false
+	}
+
+	/// Called before the instruction at `pc` of the contract executing in the current span is
+	/// executed, if [`Self::trace_steps`] is enabled.
+	fn step(&mut self, _pc: u32, _gas_left: Weight) {}
+
+	/// Called before the syscall `name` is invoked by the instruction at `pc` of the contract
+	/// executing in the current span.
+	fn enter_syscall(&mut self, _pc: u32, _name: &str, _gas_left: Weight) {}
+
+	/// Called after the syscall entered with [`Self::enter_syscall`] returned.
+	fn exit_syscall(&mut self, _gas_left: Weight) {}
 }
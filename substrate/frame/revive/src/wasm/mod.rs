@@ -33,6 +33,7 @@ use crate::{
 	gas::{GasMeter, Token},
 	limits,
 	storage::meter::Diff,
+	tracing::if_tracing,
 	weights::WeightInfo,
 	AccountIdOf, BadOrigin, BalanceOf, CodeInfoOf, CodeVec, Config, Error, ExecError, HoldReason,
 	PristineCode, Weight, LOG_TARGET,
@@ -363,6 +364,9 @@ impl<T: Config> WasmBlob<T> {
 		module_config.set_gas_metering(Some(polkavm::GasMeteringKind::Sync));
 		module_config.set_allow_sbrk(false);
 		module_config.set_aux_data_size(aux_data_size);
+		let mut trace_steps = false;
+		if_tracing(|tracer| trace_steps = tracer.trace_steps());
+		module_config.set_step_tracing(trace_steps);
 		let module = polkavm::Module::new(&engine, &module_config, self.code.into_inner().into())
 			.map_err(|err| {
 			log::debug!(target: LOG_TARGET, "failed to create polkavm module: {err:?}");
@@ -23,8 +23,9 @@ use crate::{
 	exec::{ExecError, ExecResult, Ext, Key},
 	gas::{ChargedAmount, Token},
 	limits,
+	precompiles::is_precompile,
 	primitives::ExecReturnValue,
-	pure_precompiles::is_precompile,
+	tracing::if_tracing,
 	weights::WeightInfo,
 	Config, Error, LOG_TARGET, SENTINEL,
 };
@@ -146,6 +147,7 @@ pub trait PolkaVmInstance<T: Config>: Memory<T> {
 	fn set_gas(&mut self, gas: polkavm::Gas);
 	fn read_input_regs(&self) -> (u64, u64, u64, u64, u64, u64);
 	fn write_output(&mut self, output: u64);
+	fn program_counter(&self) -> Option<u32>;
 }
 
 // Memory implementation used in benchmarking where guest memory is mapped into the host.
@@ -216,6 +218,10 @@ impl<T: Config> PolkaVmInstance<T> for polkavm::RawInstance {
 	fn write_output(&mut self, output: u64) {
 		self.set_reg(polkavm::Reg::A0, output);
 	}
+
+	fn program_counter(&self) -> Option<u32> {
+		self.program_counter().map(|pc| pc.0)
+	}
 }
 
 parameter_types! {
@@ -394,6 +400,8 @@ pub enum RuntimeCosts {
 	CallRuntime(Weight),
 	/// Weight charged for calling xcm_execute.
 	CallXcmExecute(Weight),
+	/// Weight charged by a runtime precompile.
+	Precompile(Weight),
 	/// Weight of calling `seal_set_code_hash`
 	SetCodeHash,
 	/// Weight of calling `ecdsa_to_eth_address`
@@ -414,6 +422,22 @@ pub enum RuntimeCosts {
 	Blake2F(u32),
 	/// Weight of calling `Modexp` precompile
 	Modexp(u64),
+	/// Weight of calling `PointEvaluation` precompile
+	PointEvaluation,
+	/// Weight of calling `Bls12381G1Add` precompile
+	Bls12381G1Add,
+	/// Weight of calling `Bls12381G1Msm` precompile for the given number of input pairs.
+	Bls12381G1Msm(u32),
+	/// Weight of calling `Bls12381G2Add` precompile
+	Bls12381G2Add,
+	/// Weight of calling `Bls12381G2Msm` precompile for the given number of input pairs.
+	Bls12381G2Msm(u32),
+	/// Weight of calling `Bls12381Pairing` precompile for the given number of input pairs.
+	Bls12381Pairing(u32),
+	/// Weight of calling `Bls12381MapFpToG1` precompile
+	Bls12381MapFpToG1,
+	/// Weight of calling `Bls12381MapFp2ToG2` precompile
+	Bls12381MapFp2ToG2,
 }
 
 /// For functions that modify storage, benchmarks are performed with one item in the
@@ -538,7 +562,10 @@ impl<T: Config> Token<T> for RuntimeCosts {
 			HashBlake128(len) => T::WeightInfo::seal_hash_blake2_128(len),
 			EcdsaRecovery => T::WeightInfo::ecdsa_recover(),
 			Sr25519Verify(len) => T::WeightInfo::seal_sr25519_verify(len),
-			ChainExtension(weight) | CallRuntime(weight) | CallXcmExecute(weight) => weight,
+			ChainExtension(weight) |
+			CallRuntime(weight) |
+			CallXcmExecute(weight) |
+			Precompile(weight) => weight,
 			SetCodeHash => T::WeightInfo::seal_set_code_hash(),
 			EcdsaToEthAddress => T::WeightInfo::seal_ecdsa_to_eth_address(),
 			GetImmutableData(len) => T::WeightInfo::seal_get_immutable_data(len),
@@ -562,6 +589,56 @@ impl<T: Config> Token<T> for RuntimeCosts {
 				const WEIGHT_PER_GAS: u64 = WEIGHT_REF_TIME_PER_SECOND / GAS_PER_SECOND;
 				Weight::from_parts(gas.saturating_mul(WEIGHT_PER_GAS), 0)
 			},
+			PointEvaluation => T::WeightInfo::point_evaluation(),
+			Bls12381G1Add => T::WeightInfo::bls12_381_g1_add(),
+			Bls12381G1Msm(len) => T::WeightInfo::bls12_381_g1_msm(len),
+			Bls12381G2Add => T::WeightInfo::bls12_381_g2_add(),
+			Bls12381G2Msm(len) => T::WeightInfo::bls12_381_g2_msm(len),
+			Bls12381Pairing(len) => T::WeightInfo::bls12_381_pairing(len),
+			Bls12381MapFpToG1 => T::WeightInfo::bls12_381_map_fp_to_g1(),
+			Bls12381MapFp2ToG2 => T::WeightInfo::bls12_381_map_fp2_to_g2(),
+		}
+	}
+}
+
+impl RuntimeCosts {
+	/// The weight refunded when the item accessed by this operation is in the access list.
+	///
+	/// This is the worst-case overhead of reading the item from the storage trie, which was
+	/// already paid for upfront. See [`crate::storage::AccessListState`].
+	fn warm_discount<T: Config>(&self) -> Weight {
+		use self::RuntimeCosts::*;
+		match *self {
+			SetStorage { .. } | ClearStorage(_) | TakeStorage(_) =>
+				T::WeightInfo::set_storage_full().saturating_sub(T::WeightInfo::set_storage_empty()),
+			GetStorage(_) | ContainsStorage(_) =>
+				T::WeightInfo::get_storage_full().saturating_sub(T::WeightInfo::get_storage_empty()),
+			BalanceOf | CodeHash | CodeSize => T::DbWeight::get().reads(1),
+			_ => Weight::zero(),
+		}
+	}
+}
+
+/// The [`RuntimeCosts`] of accessing a storage item or an account, which are discounted by
+/// [`RuntimeCosts::warm_discount`] if the accessed item is warm.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Copy, Clone)]
+struct AccessCosts {
+	costs: RuntimeCosts,
+	warm: bool,
+}
+
+impl<T: Config> Token<T> for AccessCosts {
+	fn influence_lowest_gas_limit(&self) -> bool {
+		Token::<T>::influence_lowest_gas_limit(&self.costs)
+	}
+
+	fn weight(&self) -> Weight {
+		let weight = Token::<T>::weight(&self.costs);
+		if self.warm {
+			weight.saturating_sub(self.costs.warm_discount::<T>())
+		} else {
+			weight
 		}
 	}
 }
@@ -634,7 +711,13 @@ impl<'a, E: Ext, M: PolkaVmInstance<E::T>> Runtime<'a, E, M> {
 			Ok(Trap) => Some(Err(Error::<E::T>::ContractTrapped.into())),
 			Ok(Segfault(_)) => Some(Err(Error::<E::T>::ExecutionFailed.into())),
 			Ok(NotEnoughGas) => Some(Err(Error::<E::T>::OutOfGas.into())),
-			Ok(Step) => None,
+			Ok(Step) => {
+				if_tracing(|tracer| {
+					let gas_left = self.ext.gas_meter().gas_left_with_engine_fuel(instance.gas());
+					tracer.step(instance.program_counter().unwrap_or_default(), gas_left);
+				});
+				None
+			},
 			Ok(Ecalli(idx)) => {
 				// This is a special hard coded syscall index which is used by benchmarks
 				// to abort contract execution. It is used to terminate the execution without
@@ -649,7 +732,20 @@ impl<'a, E: Ext, M: PolkaVmInstance<E::T>> Runtime<'a, E, M> {
 				let Some(syscall_symbol) = module.imports().get(idx) else {
 					return Some(Err(<Error<E::T>>::InvalidSyscall.into()));
 				};
-				match self.handle_ecall(instance, syscall_symbol.as_bytes()) {
+				if_tracing(|tracer| {
+					let gas_left = self.ext.gas_meter().gas_left_with_engine_fuel(instance.gas());
+					tracer.enter_syscall(
+						instance.program_counter().unwrap_or_default(),
+						core::str::from_utf8(syscall_symbol.as_bytes()).unwrap_or_default(),
+						gas_left,
+					);
+				});
+				let result = self.handle_ecall(instance, syscall_symbol.as_bytes());
+				if_tracing(|tracer| {
+					let gas_left = self.ext.gas_meter().gas_left_with_engine_fuel(instance.gas());
+					tracer.exit_syscall(gas_left);
+				});
+				match result {
 					Ok(None) => None,
 					Ok(Some(return_value)) => {
 						instance.write_output(return_value);
@@ -701,6 +797,20 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		self.ext.gas_meter_mut().adjust_gas(charged, actual_costs);
 	}
 
+	/// Same as [`Self::adjust_gas`] but for accessing a storage item or an account.
+	///
+	/// The warm discount of the `actual_costs` is refunded if the accessed item is `warm`.
+	fn adjust_access_gas(
+		&mut self,
+		charged: ChargedAmount,
+		actual_costs: RuntimeCosts,
+		warm: bool,
+	) {
+		self.ext
+			.gas_meter_mut()
+			.adjust_gas(charged, AccessCosts { costs: actual_costs, warm });
+	}
+
 	/// Charge, Run and adjust gas, for executing the given dispatchable.
 	fn call_dispatchable<ErrorReturnCode: Get<ReturnErrorCode>>(
 		&mut self,
@@ -890,12 +1000,13 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		}
 		let key = self.decode_key(memory, key_ptr, key_len)?;
 		let value = Some(memory.read(value_ptr, value_len)?);
+		let warm = !transient && self.ext.is_warm_storage_key(&key);
 		let write_outcome = if transient {
 			self.ext.set_transient_storage(&key, value, false)?
 		} else {
 			self.ext.set_storage(&key, value, false)?
 		};
-		self.adjust_gas(charged, costs(value_len, write_outcome.old_len()));
+		self.adjust_access_gas(charged, costs(value_len, write_outcome.old_len()), warm);
 		Ok(write_outcome.old_len_with_sentinel())
 	}
 
@@ -916,12 +1027,13 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		};
 		let charged = self.charge_gas(costs(self.ext.max_value_size()))?;
 		let key = self.decode_key(memory, key_ptr, key_len)?;
+		let warm = !transient && self.ext.is_warm_storage_key(&key);
 		let outcome = if transient {
 			self.ext.set_transient_storage(&key, None, false)?
 		} else {
 			self.ext.set_storage(&key, None, false)?
 		};
-		self.adjust_gas(charged, costs(outcome.old_len()));
+		self.adjust_access_gas(charged, costs(outcome.old_len()), warm);
 		Ok(outcome.old_len_with_sentinel())
 	}
 
@@ -944,13 +1056,14 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		};
 		let charged = self.charge_gas(costs(self.ext.max_value_size()))?;
 		let key = self.decode_key(memory, key_ptr, key_len)?;
+		let warm = !transient && self.ext.is_warm_storage_key(&key);
 		let outcome = if transient {
 			self.ext.get_transient_storage(&key)
 		} else {
 			self.ext.get_storage(&key)
 		};
 		if let Some(value) = outcome {
-			self.adjust_gas(charged, costs(value.len() as u32));
+			self.adjust_access_gas(charged, costs(value.len() as u32), warm);
 			self.write_sandbox_output(
 				memory,
 				out_ptr,
@@ -961,7 +1074,7 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 			)?;
 			Ok(ReturnErrorCode::Success)
 		} else {
-			self.adjust_gas(charged, costs(0));
+			self.adjust_access_gas(charged, costs(0), warm);
 			Ok(ReturnErrorCode::KeyNotFound)
 		}
 	}
@@ -983,12 +1096,13 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		};
 		let charged = self.charge_gas(costs(self.ext.max_value_size()))?;
 		let key = self.decode_key(memory, key_ptr, key_len)?;
+		let warm = !transient && self.ext.is_warm_storage_key(&key);
 		let outcome = if transient {
 			self.ext.get_transient_storage_size(&key)
 		} else {
 			self.ext.get_storage_size(&key)
 		};
-		self.adjust_gas(charged, costs(outcome.unwrap_or(0)));
+		self.adjust_access_gas(charged, costs(outcome.unwrap_or(0)), warm);
 		Ok(outcome.unwrap_or(SENTINEL))
 	}
 
@@ -1011,6 +1125,7 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		};
 		let charged = self.charge_gas(costs(self.ext.max_value_size()))?;
 		let key = self.decode_key(memory, key_ptr, key_len)?;
+		let warm = !transient && self.ext.is_warm_storage_key(&key);
 		let outcome = if transient {
 			self.ext.set_transient_storage(&key, None, true)?
 		} else {
@@ -1018,7 +1133,7 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		};
 
 		if let crate::storage::WriteOutcome::Taken(value) = outcome {
-			self.adjust_gas(charged, costs(value.len() as u32));
+			self.adjust_access_gas(charged, costs(value.len() as u32), warm);
 			self.write_sandbox_output(
 				memory,
 				out_ptr,
@@ -1029,7 +1144,7 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 			)?;
 			Ok(ReturnErrorCode::Success)
 		} else {
-			self.adjust_gas(charged, costs(0));
+			self.adjust_access_gas(charged, costs(0), warm);
 			Ok(ReturnErrorCode::KeyNotFound)
 		}
 	}
@@ -1048,7 +1163,7 @@ impl<'a, E: Ext, M: ?Sized + Memory<E::T>> Runtime<'a, E, M> {
 		output_len_ptr: u32,
 	) -> Result<ReturnErrorCode, TrapReason> {
 		let callee = match memory.read_h160(callee_ptr) {
-			Ok(callee) if is_precompile(&callee) => callee,
+			Ok(callee) if is_precompile::<E::T>(&callee) => callee,
 			Ok(callee) => {
 				self.charge_gas(call_type.cost())?;
 				callee
@@ -1467,8 +1582,10 @@ pub mod env {
 	/// See [`pallet_revive_uapi::HostFn::code_hash`].
 	#[stable]
 	fn code_hash(&mut self, memory: &mut M, addr_ptr: u32, out_ptr: u32) -> Result<(), TrapReason> {
-		self.charge_gas(RuntimeCosts::CodeHash)?;
+		let charged = self.charge_gas(RuntimeCosts::CodeHash)?;
 		let address = memory.read_h160(addr_ptr)?;
+		let warm = self.ext.is_warm_address(&address);
+		self.adjust_access_gas(charged, RuntimeCosts::CodeHash, warm);
 		Ok(self.write_fixed_sandbox_output(
 			memory,
 			out_ptr,
@@ -1482,8 +1599,10 @@ pub mod env {
 	/// See [`pallet_revive_uapi::HostFn::code_size`].
 	#[stable]
 	fn code_size(&mut self, memory: &mut M, addr_ptr: u32) -> Result<u64, TrapReason> {
-		self.charge_gas(RuntimeCosts::CodeSize)?;
+		let charged = self.charge_gas(RuntimeCosts::CodeSize)?;
 		let address = memory.read_h160(addr_ptr)?;
+		let warm = self.ext.is_warm_address(&address);
+		self.adjust_access_gas(charged, RuntimeCosts::CodeSize, warm);
 		Ok(self.ext.code_size(&address))
 	}
 
@@ -1577,8 +1696,10 @@ pub mod env {
 		addr_ptr: u32,
 		out_ptr: u32,
 	) -> Result<(), TrapReason> {
-		self.charge_gas(RuntimeCosts::BalanceOf)?;
+		let charged = self.charge_gas(RuntimeCosts::BalanceOf)?;
 		let address = memory.read_h160(addr_ptr)?;
+		let warm = self.ext.is_warm_address(&address);
+		self.adjust_access_gas(charged, RuntimeCosts::BalanceOf, warm);
 		Ok(self.write_fixed_sandbox_output(
 			memory,
 			out_ptr,
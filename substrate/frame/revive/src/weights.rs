@@ -78,6 +78,8 @@ pub trait WeightInfo {
 	fn instantiate_with_code(c: u32, i: u32, ) -> Weight;
 	fn instantiate(i: u32, ) -> Weight;
 	fn call() -> Weight;
+	fn call_with_access_list(k: u32, ) -> Weight;
+	fn instantiate_with_code_and_access_list(c: u32, i: u32, k: u32, ) -> Weight;
 	fn upload_code(c: u32, ) -> Weight;
 	fn remove_code() -> Weight;
 	fn set_code() -> Weight;
@@ -153,6 +155,14 @@ pub trait WeightInfo {
 	fn bn128_mul() -> Weight;
 	fn bn128_pairing(n: u32, ) -> Weight;
 	fn blake2f(n: u32, ) -> Weight;
+	fn point_evaluation() -> Weight;
+	fn bls12_381_g1_add() -> Weight;
+	fn bls12_381_g1_msm(n: u32, ) -> Weight;
+	fn bls12_381_g2_add() -> Weight;
+	fn bls12_381_g2_msm(n: u32, ) -> Weight;
+	fn bls12_381_pairing(n: u32, ) -> Weight;
+	fn bls12_381_map_fp_to_g1() -> Weight;
+	fn bls12_381_map_fp2_to_g2() -> Weight;
 	fn seal_ecdsa_to_eth_address() -> Weight;
 	fn seal_set_code_hash() -> Weight;
 	fn instr(r: u32, ) -> Weight;
@@ -314,6 +324,62 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(7_u64))
 			.saturating_add(T::DbWeight::get().writes(2_u64))
 	}
+	/// Storage: `Revive::OriginalAccount` (r:2 w:0)
+	/// Proof: `Revive::OriginalAccount` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `Measured`)
+	/// Storage: `Revive::ContractInfoOf` (r:1 w:1)
+	/// Proof: `Revive::ContractInfoOf` (`max_values`: None, `max_size`: Some(242), added: 2717, mode: `Measured`)
+	/// Storage: `Revive::CodeInfoOf` (r:1 w:0)
+	/// Proof: `Revive::CodeInfoOf` (`max_values`: None, `max_size`: Some(96), added: 2571, mode: `Measured`)
+	/// Storage: `Revive::PristineCode` (r:1 w:0)
+	/// Proof: `Revive::PristineCode` (`max_values`: None, `max_size`: Some(262180), added: 264655, mode: `Measured`)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Proof: `Timestamp::Now` (`max_values`: Some(1), `max_size`: Some(8), added: 503, mode: `Measured`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(128), added: 2603, mode: `Measured`)
+	/// The range of component `k` is `[0, 1024]`.
+	fn call_with_access_list(k: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `1483`
+		//  Estimated: `7423`
+		// Minimum execution time: 82_533_000 picoseconds.
+		Weight::from_parts(84_102_000, 7423)
+			// Standard Error: 1_208
+			.saturating_add(Weight::from_parts(1_873, 0).saturating_mul(k.into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Revive::CodeInfoOf` (r:1 w:1)
+	/// Proof: `Revive::CodeInfoOf` (`max_values`: None, `max_size`: Some(96), added: 2571, mode: `Measured`)
+	/// Storage: `Balances::Holds` (r:2 w:2)
+	/// Proof: `Balances::Holds` (`max_values`: None, `max_size`: Some(409), added: 2884, mode: `Measured`)
+	/// Storage: `Revive::OriginalAccount` (r:1 w:0)
+	/// Proof: `Revive::OriginalAccount` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `Measured`)
+	/// Storage: `Revive::ContractInfoOf` (r:1 w:1)
+	/// Proof: `Revive::ContractInfoOf` (`max_values`: None, `max_size`: Some(242), added: 2717, mode: `Measured`)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Proof: `Timestamp::Now` (`max_values`: Some(1), `max_size`: Some(8), added: 503, mode: `Measured`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(128), added: 2603, mode: `Measured`)
+	/// Storage: `Revive::PristineCode` (r:0 w:1)
+	/// Proof: `Revive::PristineCode` (`max_values`: None, `max_size`: Some(262180), added: 264655, mode: `Measured`)
+	/// The range of component `c` is `[0, 104857]`.
+	/// The range of component `i` is `[0, 262144]`.
+	/// The range of component `k` is `[0, 1024]`.
+	fn instantiate_with_code_and_access_list(c: u32, i: u32, k: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `474`
+		//  Estimated: `6383`
+		// Minimum execution time: 1_606_129_000 picoseconds.
+		Weight::from_parts(129_011_482, 6383)
+			// Standard Error: 33
+			.saturating_add(Weight::from_parts(20_426, 0).saturating_mul(c.into()))
+			// Standard Error: 13
+			.saturating_add(Weight::from_parts(5_634, 0).saturating_mul(i.into()))
+			// Standard Error: 1_311
+			.saturating_add(Weight::from_parts(1_902, 0).saturating_mul(k.into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
 	/// Storage: `Revive::CodeInfoOf` (r:1 w:1)
 	/// Proof: `Revive::CodeInfoOf` (`max_values`: None, `max_size`: Some(96), added: 2571, mode: `Measured`)
 	/// Storage: `Balances::Holds` (r:1 w:1)
@@ -1089,6 +1155,71 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			// Standard Error: 4
 			.saturating_add(Weight::from_parts(22_601, 0).saturating_mul(n.into()))
 	}
+	fn point_evaluation() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 31_664_210_000 picoseconds.
+		Weight::from_parts(31_903_485_000, 0)
+	}
+	fn bls12_381_g1_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 23_981_000 picoseconds.
+		Weight::from_parts(24_612_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g1_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_528_113_000 picoseconds.
+		Weight::from_parts(312_474_109, 0)
+			// Standard Error: 9_431_556
+			.saturating_add(Weight::from_parts(1_271_930_481, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_g2_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 59_962_000 picoseconds.
+		Weight::from_parts(61_078_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g2_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 4_640_213_000 picoseconds.
+		Weight::from_parts(815_362_804, 0)
+			// Standard Error: 27_805_193
+			.saturating_add(Weight::from_parts(3_912_485_216, 0).saturating_mul(n.into()))
+	}
+	/// The range of component `n` is `[1, 20]`.
+	fn bls12_381_pairing(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 17_395_228_000 picoseconds.
+		Weight::from_parts(8_940_102_316, 0)
+			// Standard Error: 15_836_217
+			.saturating_add(Weight::from_parts(8_652_417_059, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_map_fp_to_g1() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_796_145_000 picoseconds.
+		Weight::from_parts(1_812_331_000, 0)
+	}
+	fn bls12_381_map_fp2_to_g2() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 5_442_871_000 picoseconds.
+		Weight::from_parts(5_478_906_000, 0)
+	}
 	fn seal_ecdsa_to_eth_address() -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `0`
@@ -1283,6 +1414,62 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(7_u64))
 			.saturating_add(RocksDbWeight::get().writes(2_u64))
 	}
+	/// Storage: `Revive::OriginalAccount` (r:2 w:0)
+	/// Proof: `Revive::OriginalAccount` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `Measured`)
+	/// Storage: `Revive::ContractInfoOf` (r:1 w:1)
+	/// Proof: `Revive::ContractInfoOf` (`max_values`: None, `max_size`: Some(242), added: 2717, mode: `Measured`)
+	/// Storage: `Revive::CodeInfoOf` (r:1 w:0)
+	/// Proof: `Revive::CodeInfoOf` (`max_values`: None, `max_size`: Some(96), added: 2571, mode: `Measured`)
+	/// Storage: `Revive::PristineCode` (r:1 w:0)
+	/// Proof: `Revive::PristineCode` (`max_values`: None, `max_size`: Some(262180), added: 264655, mode: `Measured`)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Proof: `Timestamp::Now` (`max_values`: Some(1), `max_size`: Some(8), added: 503, mode: `Measured`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(128), added: 2603, mode: `Measured`)
+	/// The range of component `k` is `[0, 1024]`.
+	fn call_with_access_list(k: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `1483`
+		//  Estimated: `7423`
+		// Minimum execution time: 82_533_000 picoseconds.
+		Weight::from_parts(84_102_000, 7423)
+			// Standard Error: 1_208
+			.saturating_add(Weight::from_parts(1_873, 0).saturating_mul(k.into()))
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Revive::CodeInfoOf` (r:1 w:1)
+	/// Proof: `Revive::CodeInfoOf` (`max_values`: None, `max_size`: Some(96), added: 2571, mode: `Measured`)
+	/// Storage: `Balances::Holds` (r:2 w:2)
+	/// Proof: `Balances::Holds` (`max_values`: None, `max_size`: Some(409), added: 2884, mode: `Measured`)
+	/// Storage: `Revive::OriginalAccount` (r:1 w:0)
+	/// Proof: `Revive::OriginalAccount` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `Measured`)
+	/// Storage: `Revive::ContractInfoOf` (r:1 w:1)
+	/// Proof: `Revive::ContractInfoOf` (`max_values`: None, `max_size`: Some(242), added: 2717, mode: `Measured`)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Proof: `Timestamp::Now` (`max_values`: Some(1), `max_size`: Some(8), added: 503, mode: `Measured`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(128), added: 2603, mode: `Measured`)
+	/// Storage: `Revive::PristineCode` (r:0 w:1)
+	/// Proof: `Revive::PristineCode` (`max_values`: None, `max_size`: Some(262180), added: 264655, mode: `Measured`)
+	/// The range of component `c` is `[0, 104857]`.
+	/// The range of component `i` is `[0, 262144]`.
+	/// The range of component `k` is `[0, 1024]`.
+	fn instantiate_with_code_and_access_list(c: u32, i: u32, k: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `474`
+		//  Estimated: `6383`
+		// Minimum execution time: 1_606_129_000 picoseconds.
+		Weight::from_parts(129_011_482, 6383)
+			// Standard Error: 33
+			.saturating_add(Weight::from_parts(20_426, 0).saturating_mul(c.into()))
+			// Standard Error: 13
+			.saturating_add(Weight::from_parts(5_634, 0).saturating_mul(i.into()))
+			// Standard Error: 1_311
+			.saturating_add(Weight::from_parts(1_902, 0).saturating_mul(k.into()))
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
 	/// Storage: `Revive::CodeInfoOf` (r:1 w:1)
 	/// Proof: `Revive::CodeInfoOf` (`max_values`: None, `max_size`: Some(96), added: 2571, mode: `Measured`)
 	/// Storage: `Balances::Holds` (r:1 w:1)
@@ -2058,6 +2245,71 @@ impl WeightInfo for () {
 			// Standard Error: 4
 			.saturating_add(Weight::from_parts(22_601, 0).saturating_mul(n.into()))
 	}
+	fn point_evaluation() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 31_664_210_000 picoseconds.
+		Weight::from_parts(31_903_485_000, 0)
+	}
+	fn bls12_381_g1_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 23_981_000 picoseconds.
+		Weight::from_parts(24_612_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g1_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_528_113_000 picoseconds.
+		Weight::from_parts(312_474_109, 0)
+			// Standard Error: 9_431_556
+			.saturating_add(Weight::from_parts(1_271_930_481, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_g2_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 59_962_000 picoseconds.
+		Weight::from_parts(61_078_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g2_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 4_640_213_000 picoseconds.
+		Weight::from_parts(815_362_804, 0)
+			// Standard Error: 27_805_193
+			.saturating_add(Weight::from_parts(3_912_485_216, 0).saturating_mul(n.into()))
+	}
+	/// The range of component `n` is `[1, 20]`.
+	fn bls12_381_pairing(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 17_395_228_000 picoseconds.
+		Weight::from_parts(8_940_102_316, 0)
+			// Standard Error: 15_836_217
+			.saturating_add(Weight::from_parts(8_652_417_059, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_map_fp_to_g1() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_796_145_000 picoseconds.
+		Weight::from_parts(1_812_331_000, 0)
+	}
+	fn bls12_381_map_fp2_to_g2() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 5_442_871_000 picoseconds.
+		Weight::from_parts(5_478_906_000, 0)
+	}
 	fn seal_ecdsa_to_eth_address() -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `0`
@@ -51,16 +51,19 @@ pub use node_codec::NodeCodec;
 pub use storage_proof::{CompactProof, StorageProof, StorageProofError};
 /// Trie codec reexport, mainly child trie support
 /// for trie compact proof.
-pub use trie_codec::{decode_compact, encode_compact, Error as CompactProofError};
+pub use trie_codec::{
+	decode_compact, decode_compact_keyspaced, encode_compact, Error as CompactProofError,
+};
 use trie_db::proof::{generate_proof, verify_proof};
 /// Various re-exports from the `trie-db` crate.
 pub use trie_db::{
 	nibble_ops,
 	node::{NodePlan, ValuePlan},
+	trie_visit,
 	triedb::{TrieDBDoubleEndedIterator, TrieDBKeyDoubleEndedIterator},
-	CError, DBValue, Query, Recorder, Trie, TrieCache, TrieConfiguration, TrieDBIterator,
-	TrieDBKeyIterator, TrieDBNodeDoubleEndedIterator, TrieDBRawIterator, TrieLayout, TrieMut,
-	TrieRecorder,
+	CError, ChildReference, DBValue, ProcessEncodedNode, Query, Recorder, Trie, TrieCache,
+	TrieConfiguration, TrieDBIterator, TrieDBKeyIterator, TrieDBNodeDoubleEndedIterator,
+	TrieDBRawIterator, TrieLayout, TrieMut, TrieRecorder,
 };
 pub use trie_db::{proof::VerifyError, MerkleValue};
 /// The Substrate format implementation of `TrieStream`.
@@ -75,39 +75,7 @@ where
 		}
 	}
 
-	let mut child_tries = Vec::new();
-	{
-		// fetch child trie roots
-		let trie = crate::TrieDBBuilder::<L>::new(db, &top_root).build();
-
-		let mut iter = trie.iter()?;
-
-		let childtrie_roots = sp_core::storage::well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
-		if iter.seek(childtrie_roots).is_ok() {
-			loop {
-				match iter.next() {
-					Some(Ok((key, value))) if key.starts_with(childtrie_roots) => {
-						// we expect all default child trie root to be correctly encoded.
-						// see other child trie functions.
-						let mut root = TrieHash::<L>::default();
-						// still in a proof so prevent panic
-						if root.as_mut().len() != value.as_slice().len() {
-							return Err(Error::InvalidChildRoot(key, value))
-						}
-						root.as_mut().copy_from_slice(value.as_ref());
-						child_tries.push(root);
-					},
-					// allow incomplete database error: we only
-					// require access to data in the proof.
-					Some(Err(error)) => match *error {
-						trie_db::TrieError::IncompleteDatabase(..) => (),
-						e => return Err(Box::new(e).into()),
-					},
-					_ => break,
-				}
-			}
-		}
-	}
+	let child_tries = default_child_trie_roots::<L, _>(&*db, &top_root)?;
 
 	if !HashDBT::<L::Hash, _>::contains(db, &top_root, EMPTY_PREFIX) {
 		return Err(Error::IncompleteProof)
@@ -115,7 +83,7 @@ where
 
 	let mut previous_extracted_child_trie = None;
 	let mut nodes_iter = nodes_iter.peekable();
-	for child_root in child_tries.into_iter() {
+	for (_, child_root) in child_tries.into_iter() {
 		if previous_extracted_child_trie.is_none() && nodes_iter.peek().is_some() {
 			let (top_root, _) = trie_db::decode_compact_from_iter::<L, _, _>(db, &mut nodes_iter)?;
 			previous_extracted_child_trie = Some(top_root);
@@ -142,6 +110,123 @@ where
 	Ok(top_root)
 }
 
+/// Decode a compact proof like [`decode_compact`], but write the nodes of every child trie to
+/// the key space of its storage key.
+///
+/// This is the layout in which the nodes of child tries are stored in a state database, so the
+/// content of `db` can be written to it as is.
+pub fn decode_compact_keyspaced<'a, L, DB, I>(
+	db: &mut DB,
+	encoded: I,
+	expected_root: Option<&TrieHash<L>>,
+) -> Result<TrieHash<L>, Error<TrieHash<L>, CError<L>>>
+where
+	L: TrieConfiguration,
+	DB: HashDBT<L::Hash, trie_db::DBValue> + hash_db::HashDBRef<L::Hash, trie_db::DBValue>,
+	I: IntoIterator<Item = &'a [u8]>,
+{
+	let encoded = encoded.into_iter().collect::<Vec<_>>();
+	let (top_root, mut offset) =
+		trie_db::decode_compact_from_iter::<L, _, _>(db, encoded.iter().copied())?;
+
+	// Only check root if expected root is passed as argument.
+	if let Some(expected_root) = expected_root {
+		if expected_root != &top_root {
+			return Err(Error::RootMismatch(top_root, *expected_root))
+		}
+	}
+
+	let child_tries = default_child_trie_roots::<L, _>(&*db, &top_root)?;
+
+	if !HashDBT::<L::Hash, _>::contains(db, &top_root, EMPTY_PREFIX) {
+		return Err(Error::IncompleteProof)
+	}
+
+	// The root of a child trie is only known once it is decoded, so every child trie is decoded
+	// once to find its storage key, and once more into its key space.
+	let mut previous_extracted_child_trie = None;
+	for (storage_key, child_root) in child_tries.into_iter() {
+		if previous_extracted_child_trie.is_none() && offset < encoded.len() {
+			let mut scratch = crate::MemoryDB::<L::Hash>::default();
+			previous_extracted_child_trie = Some(trie_db::decode_compact_from_iter::<L, _, _>(
+				&mut scratch,
+				encoded[offset..].iter().copied(),
+			)?);
+		}
+
+		// Same as in `decode_compact`, some child roots may be in the proof without content.
+		if let Some((_, nb_used)) =
+			previous_extracted_child_trie.filter(|(root, _)| *root == child_root)
+		{
+			let mut keyspaced_db = crate::KeySpacedDBMut::new(db, &storage_key);
+			trie_db::decode_compact_from_iter::<L, _, _>(
+				&mut keyspaced_db,
+				encoded[offset..offset + nb_used].iter().copied(),
+			)?;
+			offset += nb_used;
+			previous_extracted_child_trie = None;
+		}
+	}
+
+	if let Some((child_root, _)) = previous_extracted_child_trie {
+		// A child root was read from proof but is not present
+		// in top trie.
+		return Err(Error::ExtraneousChildProof(child_root))
+	}
+
+	if offset < encoded.len() {
+		return Err(Error::ExtraneousChildNode)
+	}
+
+	Ok(top_root)
+}
+
+/// Returns the storage keys, without the default child storage key prefix, and the roots of the
+/// default child tries referenced by the top trie in `db`.
+///
+/// Only the child roots available in `db` are returned.
+fn default_child_trie_roots<L, DB>(
+	db: &DB,
+	top_root: &TrieHash<L>,
+) -> Result<Vec<(Vec<u8>, TrieHash<L>)>, Error<TrieHash<L>, CError<L>>>
+where
+	L: TrieConfiguration,
+	DB: hash_db::HashDBRef<L::Hash, trie_db::DBValue>,
+{
+	let mut child_tries = Vec::new();
+	let trie = crate::TrieDBBuilder::<L>::new(db, top_root).build();
+
+	let mut iter = trie.iter()?;
+
+	let childtrie_roots = sp_core::storage::well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+	if iter.seek(childtrie_roots).is_ok() {
+		loop {
+			match iter.next() {
+				Some(Ok((key, value))) if key.starts_with(childtrie_roots) => {
+					// we expect all default child trie root to be correctly encoded.
+					// see other child trie functions.
+					let mut root = TrieHash::<L>::default();
+					// still in a proof so prevent panic
+					if root.as_mut().len() != value.as_slice().len() {
+						return Err(Error::InvalidChildRoot(key, value))
+					}
+					root.as_mut().copy_from_slice(value.as_ref());
+					child_tries.push((key[childtrie_roots.len()..].to_vec(), root));
+				},
+				// allow incomplete database error: we only
+				// require access to data in the proof.
+				Some(Err(error)) => match *error {
+					trie_db::TrieError::IncompleteDatabase(..) => (),
+					e => return Err(Box::new(e).into()),
+				},
+				_ => break,
+			}
+		}
+	}
+
+	Ok(child_tries)
+}
+
 /// Encode a compact proof.
 ///
 /// Takes as input all full encoded node from the proof, and
@@ -317,6 +317,7 @@ construct_runtime!(
 		Babe: pallet_babe,
 		SubstrateTest: substrate_test_pallet::pallet,
 		Balances: pallet_balances,
+		ManualSealDev: pallet_manual_seal_dev,
 	}
 );
 
@@ -401,6 +402,8 @@ impl pallet_balances::Config for Runtime {
 
 impl substrate_test_pallet::Config for Runtime {}
 
+impl pallet_manual_seal_dev::Config for Runtime {}
+
 // Required for `pallet_babe::Config`.
 impl pallet_timestamp::Config for Runtime {
 	type Moment = u64;
@@ -468,6 +471,10 @@ pub const TEST_RUNTIME_BABE_EPOCH_CONFIGURATION: BabeEpochConfiguration = BabeEp
 	allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
 };
 
+/// The Sassafras epoch configuration exposed by the test runtime.
+pub const TEST_RUNTIME_SASSAFRAS_EPOCH_CONFIGURATION: sp_consensus_sassafras::EpochConfiguration =
+	sp_consensus_sassafras::EpochConfiguration { redundancy_factor: 1, attempts_number: 4 };
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -520,8 +527,8 @@ impl_runtime_apis! {
 			Executive::finalize_block()
 		}
 
-		fn inherent_extrinsics(_data: InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
-			vec![]
+		fn inherent_extrinsics(data: InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
+			data.create_extrinsics()
 		}
 
 		fn check_inherents(_block: Block, _data: InherentData) -> CheckInherentsResult {
@@ -673,6 +680,65 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl sp_consensus_sassafras::SassafrasApi<Block> for Runtime {
+		fn ring_context() -> Option<sp_consensus_sassafras::vrf::RingContext> {
+			None
+		}
+
+		fn submit_tickets_unsigned_extrinsic(
+			_tickets: Vec<sp_consensus_sassafras::TicketEnvelope>,
+		) -> bool {
+			false
+		}
+
+		fn slot_ticket_id(_slot: Slot) -> Option<sp_consensus_sassafras::TicketId> {
+			None
+		}
+
+		fn slot_ticket(
+			_slot: Slot,
+		) -> Option<(sp_consensus_sassafras::TicketId, sp_consensus_sassafras::TicketBody)> {
+			None
+		}
+
+		fn current_epoch() -> sp_consensus_sassafras::Epoch {
+			sp_consensus_sassafras::Epoch {
+				index: 0,
+				start: 0.into(),
+				length: EpochDuration::get() as u32,
+				randomness: Default::default(),
+				authorities: Vec::new(),
+				config: TEST_RUNTIME_SASSAFRAS_EPOCH_CONFIGURATION,
+			}
+		}
+
+		fn next_epoch() -> sp_consensus_sassafras::Epoch {
+			sp_consensus_sassafras::Epoch {
+				index: 1,
+				start: EpochDuration::get().into(),
+				length: EpochDuration::get() as u32,
+				randomness: Default::default(),
+				authorities: Vec::new(),
+				config: TEST_RUNTIME_SASSAFRAS_EPOCH_CONFIGURATION,
+			}
+		}
+
+		fn generate_key_ownership_proof(
+			_authority_id: sp_consensus_sassafras::AuthorityId,
+		) -> Option<sp_consensus_sassafras::OpaqueKeyOwnershipProof> {
+			None
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_sassafras::EquivocationProof<
+			<Block as BlockT>::Header,
+			>,
+			_key_owner_proof: sp_consensus_sassafras::OpaqueKeyOwnershipProof,
+		) -> bool {
+			false
+		}
+	}
+
 	impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
 		fn offchain_worker(header: &<Block as BlockT>::Header) {
 			let ext = Extrinsic::new_bare(